@@ -7,6 +7,7 @@ use std::{
 const AMENT_PREFIX_PATH: &str = "AMENT_PREFIX_PATH";
 const ROS_DISTRO: &str = "ROS_DISTRO";
 const BINDGEN_WRAPPER: &str = "src/rcl_wrapper.h";
+const QOS_PROFILES_SHIM: &str = "src/rmw_qos_profiles.c";
 
 fn get_env_var_or_abort(env_var: &'static str) -> String {
     if let Ok(value) = env::var(env_var) {
@@ -48,6 +49,7 @@ fn main() {
         .allowlist_function("rmw_.*")
         .allowlist_function("rcutils_.*")
         .allowlist_function("rosidl_.*")
+        .allowlist_function("rclrs_.*")
         .allowlist_var("rcl_.*")
         .allowlist_var("rmw_.*")
         .allowlist_var("rcutils_.*")
@@ -61,6 +63,7 @@ fn main() {
     // Invalidate the built crate whenever this script or the wrapper changes
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed={BINDGEN_WRAPPER}");
+    println!("cargo:rerun-if-changed={QOS_PROFILES_SHIM}");
 
     // #############
     // # ALGORITHM #
@@ -78,6 +81,7 @@ fn main() {
     // See REP 122 for more details: https://www.ros.org/reps/rep-0122.html#filesystem-layout
 
     let ament_prefix_paths = get_env_var_or_abort(AMENT_PREFIX_PATH);
+    let mut include_dirs = Vec::new();
     for ament_prefix_path in ament_prefix_paths.split(':').map(Path::new) {
         // Locate the ament index
         let ament_index = ament_prefix_path.join("share/ament_index/resource_index/packages");
@@ -90,6 +94,7 @@ fn main() {
 
         // Including the old-style packages
         builder = builder.clang_arg(format!("-isystem{}", include_dir.display()));
+        include_dirs.push(include_dir.clone());
 
         // Search for and include new-style-converted package paths
         for dir_entry in read_dir(&ament_index).unwrap().filter_map(|p| p.ok()) {
@@ -105,6 +110,7 @@ fn main() {
                 if package == "CycloneDDS" || new_style_include_dir.is_dir() {
                     builder =
                         builder.clang_arg(format!("-isystem{}", package_include_dir.display()));
+                    include_dirs.push(package_include_dir);
                 }
             }
         }
@@ -115,11 +121,19 @@ fn main() {
     }
 
     println!("cargo:rustc-link-lib=dylib=rcl");
+    println!("cargo:rustc-link-lib=dylib=rcl_action");
+    println!("cargo:rustc-link-lib=dylib=rcl_lifecycle");
     println!("cargo:rustc-link-lib=dylib=rcl_yaml_param_parser");
     println!("cargo:rustc-link-lib=dylib=rcutils");
     println!("cargo:rustc-link-lib=dylib=rmw");
     println!("cargo:rustc-link-lib=dylib=rmw_implementation");
 
+    // The QoS profile presets of rmw are only reachable through this shim
+    cc::Build::new()
+        .file(QOS_PROFILES_SHIM)
+        .includes(&include_dirs)
+        .compile("rclrs_rmw_qos_profiles");
+
     let bindings = builder.generate().expect("Unable to generate bindings");
 
     let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
@@ -0,0 +1,131 @@
+use std::fmt;
+
+mod client;
+mod server;
+pub use client::*;
+pub use server::*;
+
+/// The unique identifier of an action goal.
+///
+/// Goal IDs are generated by the [`ActionClient`] when sending a goal, and are used to associate
+/// feedback, results and cancel requests with the goal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GoalUuid(pub [u8; 16]);
+
+impl GoalUuid {
+    /// Creates a new, random goal ID.
+    pub fn random() -> Self {
+        Self(*uuid::Uuid::new_v4().as_bytes())
+    }
+}
+
+impl fmt::Display for GoalUuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        uuid::Uuid::from_bytes(self.0).fmt(f)
+    }
+}
+
+/// The status of an action goal.
+///
+/// This corresponds to the constants of the `action_msgs/msg/GoalStatus` message.
+#[repr(i8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GoalStatus {
+    /// The status has not been set.
+    Unknown = 0,
+    /// The goal has been accepted and is awaiting execution.
+    Accepted = 1,
+    /// The goal is currently being executed by the action server.
+    Executing = 2,
+    /// The client has requested that the goal be canceled, and the action server has accepted
+    /// the cancel request.
+    Canceling = 3,
+    /// The goal was achieved successfully by the action server.
+    Succeeded = 4,
+    /// The goal was canceled after an external request from an action client.
+    Canceled = 5,
+    /// The goal was terminated by the action server without an external request.
+    Aborted = 6,
+}
+
+impl GoalStatus {
+    /// Returns `true` if the goal has finished, i.e. it succeeded, was canceled or was aborted.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Succeeded | Self::Canceled | Self::Aborted)
+    }
+}
+
+impl From<i8> for GoalStatus {
+    /// Unrecognized values are mapped to [`GoalStatus::Unknown`].
+    fn from(status: i8) -> Self {
+        match status {
+            1 => Self::Accepted,
+            2 => Self::Executing,
+            3 => Self::Canceling,
+            4 => Self::Succeeded,
+            5 => Self::Canceled,
+            6 => Self::Aborted,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// The answer of an action server to a request to cancel a goal.
+///
+/// This corresponds to the constants of the `action_msgs/srv/CancelGoal` response.
+#[repr(i8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CancelResponseCode {
+    /// The goal is being canceled.
+    Accepted = 0,
+    /// The action server rejected the cancel request.
+    Rejected = 1,
+    /// The action server does not know the goal.
+    UnknownGoalId = 2,
+    /// The goal cannot be canceled because it has already finished.
+    GoalTerminated = 3,
+}
+
+impl From<i8> for CancelResponseCode {
+    /// Unrecognized values are mapped to [`CancelResponseCode::Rejected`].
+    fn from(code: i8) -> Self {
+        match code {
+            0 => Self::Accepted,
+            2 => Self::UnknownGoalId,
+            3 => Self::GoalTerminated,
+            _ => Self::Rejected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goal_uuids_are_unique() {
+        let a = GoalUuid::random();
+        let b = GoalUuid::random();
+        assert_ne!(a, b);
+        // The canonical hyphenated format.
+        assert_eq!(a.to_string().len(), 36);
+    }
+
+    #[test]
+    fn goal_status_conversion() {
+        for status in [
+            GoalStatus::Unknown,
+            GoalStatus::Accepted,
+            GoalStatus::Executing,
+            GoalStatus::Canceling,
+            GoalStatus::Succeeded,
+            GoalStatus::Canceled,
+            GoalStatus::Aborted,
+        ] {
+            assert_eq!(GoalStatus::from(status as i8), status);
+        }
+        assert_eq!(GoalStatus::from(42), GoalStatus::Unknown);
+        assert!(GoalStatus::Aborted.is_terminal());
+        assert!(!GoalStatus::Executing.is_terminal());
+    }
+}
@@ -0,0 +1,804 @@
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    os::raw::c_void,
+    sync::{atomic::AtomicBool, Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use futures::channel::oneshot;
+use rosidl_runtime_rs::{
+    ActionImpl, Message, RmwFeedbackMessage, RmwGoalResponse, RmwResultResponse,
+};
+
+use crate::{
+    client::{PendingResponse, RequestId},
+    error::{RclReturnCode, ToResult},
+    rcl_bindings::*,
+    validate_topic_name,
+    vendor::{action_msgs, builtin_interfaces, unique_identifier_msgs},
+    CancelResponseCode, GoalStatus, GoalUuid, MessageCow, NodeHandle, RclrsError,
+    WaitableNumEntities, ENTITY_LIFECYCLE_MUTEX,
+};
+
+// SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
+// they are running in. Therefore, this type can be safely sent to another thread.
+unsafe impl Send for rcl_action_client_t {}
+
+/// Manage the lifecycle of an `rcl_action_client_t`, including managing its dependencies
+/// on `rcl_node_t` and `rcl_context_t` by ensuring that these dependencies are
+/// [dropped after][1] the `rcl_action_client_t`.
+///
+/// [1]: <https://doc.rust-lang.org/reference/destructors.html>
+pub struct ActionClientHandle {
+    rcl_action_client: Mutex<rcl_action_client_t>,
+    node_handle: Arc<NodeHandle>,
+    pub(crate) num_entities: WaitableNumEntities,
+    pub(crate) in_use_by_wait_set: Arc<AtomicBool>,
+}
+
+impl ActionClientHandle {
+    pub(crate) fn lock(&self) -> MutexGuard<rcl_action_client_t> {
+        self.rcl_action_client.lock().unwrap()
+    }
+}
+
+impl Drop for ActionClientHandle {
+    fn drop(&mut self) {
+        let rcl_action_client = self.rcl_action_client.get_mut().unwrap();
+        let mut rcl_node = self.node_handle.rcl_node.lock().unwrap();
+        let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+        // SAFETY: The entity lifecycle mutex is locked to protect against the risk of
+        // global variables in the rmw implementation being unsafely modified during cleanup.
+        unsafe {
+            rcl_action_client_fini(rcl_action_client, &mut *rcl_node);
+        }
+    }
+}
+
+/// Trait to be implemented by concrete ActionClient structs.
+///
+/// See [`ActionClient<A>`] for an example.
+pub trait ActionClientBase: Send + Sync {
+    /// Internal function to get a reference to the `rcl` handle.
+    fn handle(&self) -> &ActionClientHandle;
+    /// Tries to take new responses, feedback and status messages and processes them.
+    fn execute(&self) -> Result<(), RclrsError>;
+}
+
+/// A handle to a goal that was accepted by an action server.
+///
+/// Goal handles are returned by [`ActionClient::send_goal()`], and are used to request the
+/// result of the goal or to cancel it.
+#[derive(Clone, Debug)]
+pub struct ClientGoalHandle {
+    goal_id: GoalUuid,
+    status: Arc<Mutex<GoalStatus>>,
+}
+
+impl ClientGoalHandle {
+    /// Returns the ID of the goal.
+    pub fn goal_id(&self) -> GoalUuid {
+        self.goal_id
+    }
+
+    /// Returns the last known status of the goal.
+    ///
+    /// The status is updated from the status messages of the action server while the node of
+    /// the action client is being spun.
+    pub fn status(&self) -> GoalStatus {
+        *self.status.lock().unwrap()
+    }
+}
+
+type FeedbackCallback<A> = Box<dyn FnMut(<A as rosidl_runtime_rs::Action>::Feedback) + Send>;
+
+// A goal request whose response has not arrived yet.
+struct PendingGoal<A: ActionImpl> {
+    goal_id: GoalUuid,
+    feedback_callback: FeedbackCallback<A>,
+    sender: oneshot::Sender<Option<ClientGoalHandle>>,
+}
+
+// A goal that was accepted by the action server and has not finished yet.
+struct ActiveGoal<A: ActionImpl> {
+    feedback_callback: FeedbackCallback<A>,
+    status: Arc<Mutex<GoalStatus>>,
+}
+
+/// Main class responsible for sending goals to a ROS action server.
+///
+/// The only available way to instantiate action clients is via
+/// [`Node::create_action_client()`][1], this is to ensure that [`Node`][2]s can track all the
+/// action clients that have been created.
+///
+/// Responses, feedback and status updates are only received while the node of this action
+/// client is being spun, e.g. in another thread.
+///
+/// [1]: crate::Node::create_action_client
+/// [2]: crate::Node
+pub struct ActionClient<A>
+where
+    A: ActionImpl,
+{
+    pub(crate) handle: Arc<ActionClientHandle>,
+    goal_requests: Mutex<HashMap<RequestId, PendingGoal<A>>>,
+    result_requests: Mutex<HashMap<RequestId, oneshot::Sender<RmwResultResponse<A>>>>,
+    cancel_requests:
+        Mutex<HashMap<RequestId, oneshot::Sender<action_msgs::srv::rmw::CancelGoal_Response>>>,
+    active_goals: Mutex<HashMap<GoalUuid, ActiveGoal<A>>>,
+}
+
+impl<A> ActionClient<A>
+where
+    A: ActionImpl,
+{
+    /// Creates a new action client.
+    pub(crate) fn new(node_handle: Arc<NodeHandle>, action_name: &str) -> Result<Self, RclrsError>
+// This uses pub(crate) visibility to avoid instantiating this struct outside
+    // [`Node::create_action_client`], see the struct's documentation for the rationale
+    {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut rcl_action_client = unsafe { rcl_action_get_zero_initialized_client() };
+        let type_support = A::get_type_support() as *const rosidl_action_type_support_t;
+        let action_name_c_string =
+            CString::new(action_name).map_err(|err| RclrsError::StringContainsNul {
+                err,
+                s: action_name.into(),
+            })?;
+        validate_topic_name(action_name)?;
+
+        // SAFETY: No preconditions for this function.
+        let action_client_options = unsafe { rcl_action_client_get_default_options() };
+
+        {
+            let mut rcl_node = node_handle.rcl_node.lock().unwrap();
+            let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+
+            // SAFETY:
+            // * The rcl_action_client was zero-initialized as expected by this function.
+            // * The rcl_node is kept alive by the NodeHandle because it is a dependency of the
+            //   action client.
+            // * The action name and the options are copied by this function, so they can be
+            //   dropped afterwards.
+            // * The entity lifecycle mutex is locked to protect against the risk of global
+            //   variables in the rmw implementation being unsafely modified during initialization.
+            unsafe {
+                rcl_action_client_init(
+                    &mut rcl_action_client,
+                    &mut *rcl_node,
+                    type_support,
+                    action_name_c_string.as_ptr(),
+                    &action_client_options,
+                )
+                .ok_for("rcl_action_client_init")?;
+            }
+        }
+
+        // The handle finalizes the action client when it is dropped, so it is created before
+        // anything else can fail.
+        let mut handle = ActionClientHandle {
+            rcl_action_client: Mutex::new(rcl_action_client),
+            node_handle,
+            num_entities: WaitableNumEntities::default(),
+            in_use_by_wait_set: Arc::new(AtomicBool::new(false)),
+        };
+        let num_entities = &mut handle.num_entities;
+        // SAFETY: The action client was successfully initialized, and the out-parameters are
+        // valid pointers.
+        unsafe {
+            rcl_action_client_wait_set_get_num_entities(
+                handle.rcl_action_client.get_mut().unwrap(),
+                &mut num_entities.subscriptions,
+                &mut num_entities.guard_conditions,
+                &mut num_entities.timers,
+                &mut num_entities.clients,
+                &mut num_entities.services,
+            )
+            .ok_for("rcl_action_client_wait_set_get_num_entities")?;
+        }
+        let handle = Arc::new(handle);
+
+        Ok(Self {
+            handle,
+            goal_requests: Mutex::new(HashMap::new()),
+            result_requests: Mutex::new(HashMap::new()),
+            cancel_requests: Mutex::new(HashMap::new()),
+            active_goals: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Sends a goal to the action server.
+    ///
+    /// A new, random [`GoalUuid`] is generated for the goal. The feedback that the action server
+    /// publishes for this goal is passed to `feedback_callback`.
+    ///
+    /// Returns a handle to the goal if the action server accepted it, and `None` if it rejected
+    /// it. The result of the goal can then be requested with [`ActionClient::get_result()`].
+    ///
+    /// Dropping the future before the response arrived discards the response, and the goal
+    /// cannot be tracked anymore.
+    pub async fn send_goal<'a, G, F>(
+        &self,
+        goal: G,
+        feedback_callback: F,
+    ) -> Result<Option<ClientGoalHandle>, RclrsError>
+    where
+        G: MessageCow<'a, A::Goal>,
+        F: FnMut(A::Feedback) + Send + 'static,
+    {
+        let goal_id = GoalUuid::random();
+        let rmw_goal = A::Goal::into_rmw_message(goal.into_cow()).into_owned();
+        let request = A::create_goal_request(&goal_id.0, rmw_goal);
+        let mut sequence_number = -1;
+        let (sender, receiver) = oneshot::channel();
+        {
+            // The lock is held while sending, so that a response cannot be executed before the
+            // pending goal is stored.
+            let mut goal_requests = self.goal_requests.lock().unwrap();
+            unsafe {
+                // SAFETY: The request type is guaranteed to match the action client type by the
+                // type system.
+                rcl_action_send_goal_request(
+                    &*self.handle.lock(),
+                    &request as *const _ as *const c_void,
+                    &mut sequence_number,
+                )
+            }
+            .ok_for("rcl_action_send_goal_request")?;
+            goal_requests.insert(
+                sequence_number,
+                PendingGoal {
+                    goal_id,
+                    feedback_callback: Box::new(feedback_callback),
+                    sender,
+                },
+            );
+        }
+        let _pending = PendingResponse {
+            futures: &self.goal_requests,
+            sequence_number,
+        };
+        // It is safe to call unwrap() here since the `Canceled` error will only happen when the
+        // `Sender` is dropped
+        // https://docs.rs/futures/latest/futures/channel/oneshot/struct.Canceled.html
+        Ok(receiver.await.unwrap())
+    }
+
+    /// Requests the result of a goal and waits for it.
+    ///
+    /// The action server responds once the goal has finished, so this also waits for the
+    /// execution of the goal to finish.
+    ///
+    /// Returns the final status of the goal together with its result.
+    pub async fn get_result(
+        &self,
+        goal_handle: &ClientGoalHandle,
+    ) -> Result<(GoalStatus, A::Result), RclrsError> {
+        let request = A::create_result_request(&goal_handle.goal_id.0);
+        let mut sequence_number = -1;
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut result_requests = self.result_requests.lock().unwrap();
+            unsafe {
+                // SAFETY: The request type is guaranteed to match the action client type by the
+                // type system.
+                rcl_action_send_result_request(
+                    &*self.handle.lock(),
+                    &request as *const _ as *const c_void,
+                    &mut sequence_number,
+                )
+            }
+            .ok_for("rcl_action_send_result_request")?;
+            result_requests.insert(sequence_number, sender);
+        }
+        let _pending = PendingResponse {
+            futures: &self.result_requests,
+            sequence_number,
+        };
+        // See send_goal() for why unwrap() is safe here.
+        let response = receiver.await.unwrap();
+        let (status, result) = A::split_result_response(response);
+        let status = GoalStatus::from(status);
+        *goal_handle.status.lock().unwrap() = status;
+        Ok((status, A::Result::from_rmw_message(result)))
+    }
+
+    /// Requests the action server to cancel a goal, and waits for the answer.
+    ///
+    /// Whether the goal is actually canceled is up to the action server.
+    pub async fn cancel_goal(
+        &self,
+        goal_handle: &ClientGoalHandle,
+    ) -> Result<CancelResponseCode, RclrsError> {
+        // A zero stamp together with a goal ID requests canceling only that goal.
+        let request = action_msgs::srv::rmw::CancelGoal_Request {
+            goal_info: action_msgs::msg::rmw::GoalInfo {
+                goal_id: unique_identifier_msgs::msg::rmw::UUID {
+                    uuid: goal_handle.goal_id.0,
+                },
+                stamp: builtin_interfaces::msg::rmw::Time { sec: 0, nanosec: 0 },
+            },
+        };
+        let mut sequence_number = -1;
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut cancel_requests = self.cancel_requests.lock().unwrap();
+            unsafe {
+                // SAFETY: The request has the type expected by the cancel service of every action.
+                rcl_action_send_cancel_request(
+                    &*self.handle.lock(),
+                    &request as *const _ as *const c_void,
+                    &mut sequence_number,
+                )
+            }
+            .ok_for("rcl_action_send_cancel_request")?;
+            cancel_requests.insert(sequence_number, sender);
+        }
+        let _pending = PendingResponse {
+            futures: &self.cancel_requests,
+            sequence_number,
+        };
+        // See send_goal() for why unwrap() is safe here.
+        let response = receiver.await.unwrap();
+        Ok(CancelResponseCode::from(response.return_code))
+    }
+
+    /// Check if an action server is available.
+    ///
+    /// Will return true if there is an action server available, false if unavailable.
+    pub fn server_is_available(&self) -> Result<bool, RclrsError> {
+        let mut is_available = false;
+        let action_client = &*self.handle.lock();
+        let node = &*self.handle.node_handle.rcl_node.lock().unwrap();
+        unsafe {
+            // SAFETY: Both the node and the action client are guaranteed to be valid here, and
+            // the action client was created with this node.
+            rcl_action_server_is_available(node, action_client, &mut is_available)
+        }
+        .ok_for("rcl_action_server_is_available")?;
+        Ok(is_available)
+    }
+
+    /// Blocks until an action server is available or the timeout has elapsed.
+    ///
+    /// Returns `true` if an action server is available, and `false` if the timeout elapsed
+    /// before a server became available.
+    pub fn wait_for_server(&self, timeout: Duration) -> Result<bool, RclrsError> {
+        // The interval at which the availability of the server is checked.
+        const POLL_INTERVAL: Duration = Duration::from_millis(10);
+        // A deadline of None means waiting forever, for timeouts too large to be represented.
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            if self.server_is_available()? {
+                return Ok(true);
+            }
+            let sleep_duration = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(false);
+                    }
+                    remaining.min(POLL_INTERVAL)
+                }
+                None => POLL_INTERVAL,
+            };
+            std::thread::sleep(sleep_duration);
+        }
+    }
+
+    fn execute_goal_response(&self) -> Result<(), RclrsError> {
+        let mut request_id = rmw_request_id_t {
+            writer_guid: [0; 16],
+            sequence_number: 0,
+        };
+        let mut response = RmwGoalResponse::<A>::default();
+        unsafe {
+            // SAFETY: The three pointers are valid/initialized, and the response type matches
+            // the action client type.
+            rcl_action_take_goal_response(
+                &*self.handle.lock(),
+                &mut request_id,
+                &mut response as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_take_goal_response")?;
+        let Some(pending_goal) = self
+            .goal_requests
+            .lock()
+            .unwrap()
+            .remove(&request_id.sequence_number)
+        else {
+            // The future of this request was dropped.
+            return Ok(());
+        };
+        if !A::get_goal_response_accepted(&response) {
+            let _ = pending_goal.sender.send(None);
+            return Ok(());
+        }
+        let status = Arc::new(Mutex::new(GoalStatus::Accepted));
+        self.active_goals.lock().unwrap().insert(
+            pending_goal.goal_id,
+            ActiveGoal {
+                feedback_callback: pending_goal.feedback_callback,
+                status: Arc::clone(&status),
+            },
+        );
+        let _ = pending_goal.sender.send(Some(ClientGoalHandle {
+            goal_id: pending_goal.goal_id,
+            status,
+        }));
+        Ok(())
+    }
+
+    fn execute_feedback(&self) -> Result<(), RclrsError> {
+        let mut feedback_message = RmwFeedbackMessage::<A>::default();
+        unsafe {
+            // SAFETY: The pointer is valid/initialized, and the message type matches the action
+            // client type.
+            rcl_action_take_feedback(
+                &*self.handle.lock(),
+                &mut feedback_message as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_take_feedback")?;
+        let (goal_id, feedback) = A::split_feedback_message(feedback_message);
+        // Feedback for goals that were not sent by this client is ignored.
+        if let Some(goal) = self
+            .active_goals
+            .lock()
+            .unwrap()
+            .get_mut(&GoalUuid(goal_id))
+        {
+            (goal.feedback_callback)(A::Feedback::from_rmw_message(feedback));
+        }
+        Ok(())
+    }
+
+    fn execute_status(&self) -> Result<(), RclrsError> {
+        let mut status_array = action_msgs::msg::rmw::GoalStatusArray::default();
+        unsafe {
+            // SAFETY: The pointer is valid/initialized, and the message type matches the status
+            // topic of every action.
+            rcl_action_take_status(
+                &*self.handle.lock(),
+                &mut status_array as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_take_status")?;
+        let mut active_goals = self.active_goals.lock().unwrap();
+        for goal_status in status_array.status_list.iter() {
+            let goal_id = GoalUuid(goal_status.goal_info.goal_id.uuid);
+            let status = GoalStatus::from(goal_status.status);
+            if let Some(goal) = active_goals.get(&goal_id) {
+                *goal.status.lock().unwrap() = status;
+            }
+            if status.is_terminal() {
+                // No more feedback is published for finished goals.
+                active_goals.remove(&goal_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_cancel_response(&self) -> Result<(), RclrsError> {
+        let mut request_id = rmw_request_id_t {
+            writer_guid: [0; 16],
+            sequence_number: 0,
+        };
+        let mut response = action_msgs::srv::rmw::CancelGoal_Response::default();
+        unsafe {
+            // SAFETY: The three pointers are valid/initialized, and the response type matches
+            // the cancel service of every action.
+            rcl_action_take_cancel_response(
+                &*self.handle.lock(),
+                &mut request_id,
+                &mut response as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_take_cancel_response")?;
+        if let Some(sender) = self
+            .cancel_requests
+            .lock()
+            .unwrap()
+            .remove(&request_id.sequence_number)
+        {
+            let _ = sender.send(response);
+        }
+        Ok(())
+    }
+
+    fn execute_result_response(&self) -> Result<(), RclrsError> {
+        let mut request_id = rmw_request_id_t {
+            writer_guid: [0; 16],
+            sequence_number: 0,
+        };
+        let mut response = RmwResultResponse::<A>::default();
+        unsafe {
+            // SAFETY: The three pointers are valid/initialized, and the response type matches
+            // the action client type.
+            rcl_action_take_result_response(
+                &*self.handle.lock(),
+                &mut request_id,
+                &mut response as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_take_result_response")?;
+        if let Some(sender) = self
+            .result_requests
+            .lock()
+            .unwrap()
+            .remove(&request_id.sequence_number)
+        {
+            let _ = sender.send(response);
+        }
+        Ok(())
+    }
+}
+
+impl<A> ActionClientBase for ActionClient<A>
+where
+    A: ActionImpl,
+{
+    fn handle(&self) -> &ActionClientHandle {
+        &self.handle
+    }
+
+    fn execute(&self) -> Result<(), RclrsError> {
+        // Goal responses are processed first, so that the feedback callback of a newly accepted
+        // goal is registered before its feedback is processed.
+        let take_results = [
+            self.execute_goal_response(),
+            self.execute_feedback(),
+            self.execute_status(),
+            self.execute_cancel_response(),
+            self.execute_result_response(),
+        ];
+        for take_result in take_results {
+            match take_result {
+                Ok(())
+                | Err(RclrsError::RclError {
+                    code: RclReturnCode::ActionClientTakeFailed,
+                    ..
+                }) => {
+                    // A take failure only means that this part of the action client was not
+                    // ready, so it shouldn't be an error.
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_helpers::*, QOS_PROFILE_DEFAULT};
+    use std::sync::atomic::Ordering;
+    use test_msgs::action;
+
+    #[test]
+    fn traits() {
+        assert_send::<ActionClient<action::Fibonacci>>();
+        assert_sync::<ActionClient<action::Fibonacci>>();
+    }
+
+    #[test]
+    fn test_wait_for_server() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_wait_for_action_server")?;
+        let client = graph
+            .node2
+            .create_action_client::<action::Fibonacci>("fibonacci")?;
+
+        assert!(!client.server_is_available()?);
+        let start = Instant::now();
+        assert!(!client.wait_for_server(Duration::from_millis(100))?);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        Ok(())
+    }
+
+    fn fibonacci(order: i32) -> Vec<i32> {
+        let mut sequence = vec![0, 1];
+        while sequence.len() < order as usize {
+            sequence.push(sequence[sequence.len() - 1] + sequence[sequence.len() - 2]);
+        }
+        sequence.truncate(order as usize);
+        sequence
+    }
+
+    // The action server is mimicked with the services and topics that make up an action, so that
+    // the test controls exactly when feedback and status updates are sent.
+    #[tokio::test]
+    async fn test_send_goal_and_get_result() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_action_client")?;
+        let results = Arc::new(Mutex::new(HashMap::new()));
+        let _send_goal_service = {
+            let results = Arc::clone(&results);
+            graph
+                .node1
+                .create_service::<action::Fibonacci_SendGoal, _>(
+                    "fibonacci/_action/send_goal",
+                    move |_, request: action::Fibonacci_SendGoal_Request| {
+                        let accepted = request.goal.order >= 0;
+                        if accepted {
+                            let sequence = fibonacci(request.goal.order);
+                            results
+                                .lock()
+                                .unwrap()
+                                .insert(request.goal_id.uuid, sequence);
+                        }
+                        action::Fibonacci_SendGoal_Response {
+                            accepted,
+                            ..Default::default()
+                        }
+                    },
+                )?
+        };
+        let _get_result_service = {
+            let results = Arc::clone(&results);
+            graph
+                .node1
+                .create_service::<action::Fibonacci_GetResult, _>(
+                    "fibonacci/_action/get_result",
+                    move |_, request: action::Fibonacci_GetResult_Request| {
+                        let sequence = results.lock().unwrap().get(&request.goal_id.uuid).cloned();
+                        match sequence {
+                            Some(sequence) => action::Fibonacci_GetResult_Response {
+                                status: action_msgs::msg::GoalStatus::STATUS_SUCCEEDED,
+                                result: action::Fibonacci_Result { sequence },
+                            },
+                            None => action::Fibonacci_GetResult_Response::default(),
+                        }
+                    },
+                )?
+        };
+        let _cancel_goal_service =
+            {
+                let results = Arc::clone(&results);
+                graph.node1.create_service::<action_msgs::srv::CancelGoal, _>(
+                "fibonacci/_action/cancel_goal",
+                move |_, request: action_msgs::srv::CancelGoal_Request| {
+                    if results
+                        .lock()
+                        .unwrap()
+                        .contains_key(&request.goal_info.goal_id.uuid)
+                    {
+                        action_msgs::srv::CancelGoal_Response {
+                            return_code: action_msgs::srv::CancelGoal_Response::ERROR_NONE,
+                            goals_canceling: vec![request.goal_info],
+                        }
+                    } else {
+                        action_msgs::srv::CancelGoal_Response {
+                            return_code:
+                                action_msgs::srv::CancelGoal_Response::ERROR_UNKNOWN_GOAL_ID,
+                            goals_canceling: Vec::new(),
+                        }
+                    }
+                },
+            )?
+            };
+        let feedback_publisher = graph
+            .node1
+            .create_publisher::<action::Fibonacci_FeedbackMessage>(
+                "fibonacci/_action/feedback",
+                QOS_PROFILE_DEFAULT,
+            )?;
+        let status_publisher = graph
+            .node1
+            .create_publisher::<action_msgs::msg::GoalStatusArray>(
+                "fibonacci/_action/status",
+                QOS_PROFILE_DEFAULT.keep_last(1).transient_local(),
+            )?;
+
+        let client = graph
+            .node2
+            .create_action_client::<action::Fibonacci>("fibonacci")?;
+        assert!(client.wait_for_server(Duration::from_secs(5))?);
+
+        let done = Arc::new(AtomicBool::new(false));
+        let spin_thread = {
+            let (node1, node2, done) = (
+                Arc::clone(&graph.node1),
+                Arc::clone(&graph.node2),
+                Arc::clone(&done),
+            );
+            std::thread::spawn(move || {
+                while !done.load(Ordering::Acquire) {
+                    let _ = crate::spin_once(Arc::clone(&node1), Some(Duration::from_millis(10)));
+                    let _ = crate::spin_once(Arc::clone(&node2), Some(Duration::from_millis(10)));
+                }
+            })
+        };
+
+        // A goal with a negative order is rejected
+        let rejected = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.send_goal(&action::Fibonacci_Goal { order: -1 }, |_| {}),
+        )
+        .await
+        .expect("No goal response received")?;
+        assert!(rejected.is_none());
+
+        let received_feedback = Arc::new(Mutex::new(Vec::new()));
+        let goal_handle = {
+            let received_feedback = Arc::clone(&received_feedback);
+            tokio::time::timeout(
+                Duration::from_secs(5),
+                client.send_goal(
+                    &action::Fibonacci_Goal { order: 5 },
+                    move |feedback: action::Fibonacci_Feedback| {
+                        received_feedback.lock().unwrap().push(feedback.sequence);
+                    },
+                ),
+            )
+            .await
+            .expect("No goal response received")?
+            .expect("The goal was rejected")
+        };
+        assert_eq!(goal_handle.status(), GoalStatus::Accepted);
+        assert!(client.goal_requests.lock().unwrap().is_empty());
+
+        // The feedback is published until it arrives, since the feedback topic is volatile
+        let mut feedback_message = action::Fibonacci_FeedbackMessage::default();
+        feedback_message.goal_id.uuid = goal_handle.goal_id().0;
+        feedback_message.feedback.sequence = vec![0, 1];
+        let start = Instant::now();
+        while received_feedback.lock().unwrap().is_empty() {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "No feedback received"
+            );
+            feedback_publisher.publish(&feedback_message)?;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(received_feedback.lock().unwrap()[0], vec![0, 1]);
+
+        let mut goal_status = action_msgs::msg::GoalStatus::default();
+        goal_status.goal_info.goal_id.uuid = goal_handle.goal_id().0;
+        goal_status.status = action_msgs::msg::GoalStatus::STATUS_SUCCEEDED;
+        status_publisher.publish(&action_msgs::msg::GoalStatusArray {
+            status_list: vec![goal_status],
+        })?;
+        let start = Instant::now();
+        while goal_handle.status() != GoalStatus::Succeeded {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "No status received"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // Finished goals don't receive feedback anymore
+        assert!(client.active_goals.lock().unwrap().is_empty());
+
+        let (status, result) =
+            tokio::time::timeout(Duration::from_secs(5), client.get_result(&goal_handle))
+                .await
+                .expect("No result received")?;
+        assert_eq!(status, GoalStatus::Succeeded);
+        assert_eq!(result.sequence, vec![0, 1, 1, 2, 3]);
+        assert!(client.result_requests.lock().unwrap().is_empty());
+
+        let code = tokio::time::timeout(Duration::from_secs(5), client.cancel_goal(&goal_handle))
+            .await
+            .expect("No cancel response received")?;
+        assert_eq!(code, CancelResponseCode::Accepted);
+
+        let unknown_goal_handle = ClientGoalHandle {
+            goal_id: GoalUuid::random(),
+            status: Arc::new(Mutex::new(GoalStatus::Unknown)),
+        };
+        let code = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.cancel_goal(&unknown_goal_handle),
+        )
+        .await
+        .expect("No cancel response received")?;
+        assert_eq!(code, CancelResponseCode::UnknownGoalId);
+        assert!(client.cancel_requests.lock().unwrap().is_empty());
+
+        done.store(true, Ordering::Release);
+        spin_thread.join().unwrap();
+        Ok(())
+    }
+}
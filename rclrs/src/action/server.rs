@@ -0,0 +1,992 @@
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    os::raw::c_void,
+    sync::{atomic::AtomicBool, Arc, Mutex, MutexGuard},
+};
+
+use rosidl_runtime_rs::{ActionImpl, Message, RmwGoalRequest, RmwResultRequest, RmwResultResponse};
+
+use crate::{
+    error::{RclReturnCode, ToResult},
+    rcl_bindings::*,
+    validate_topic_name,
+    vendor::{action_msgs, builtin_interfaces, unique_identifier_msgs},
+    Clock, GoalStatus, GoalUuid, MessageCow, NodeHandle, RclrsError, WaitableNumEntities,
+    ENTITY_LIFECYCLE_MUTEX,
+};
+
+// SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
+// they are running in. Therefore, this type can be safely sent to another thread.
+unsafe impl Send for rcl_action_server_t {}
+
+/// Manage the lifecycle of an `rcl_action_server_t`, including managing its dependencies
+/// on `rcl_node_t`, `rcl_clock_t` and `rcl_context_t` by ensuring that these dependencies are
+/// [dropped after][1] the `rcl_action_server_t`.
+///
+/// [1]: <https://doc.rust-lang.org/reference/destructors.html>
+pub struct ActionServerHandle {
+    rcl_action_server: Mutex<rcl_action_server_t>,
+    node_handle: Arc<NodeHandle>,
+    clock: Clock,
+    pub(crate) num_entities: WaitableNumEntities,
+    pub(crate) in_use_by_wait_set: Arc<AtomicBool>,
+}
+
+impl ActionServerHandle {
+    pub(crate) fn lock(&self) -> MutexGuard<rcl_action_server_t> {
+        self.rcl_action_server.lock().unwrap()
+    }
+}
+
+impl Drop for ActionServerHandle {
+    fn drop(&mut self) {
+        let rcl_action_server = self.rcl_action_server.get_mut().unwrap();
+        let mut rcl_node = self.node_handle.rcl_node.lock().unwrap();
+        let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+        // SAFETY: The entity lifecycle mutex is locked to protect against the risk of
+        // global variables in the rmw implementation being unsafely modified during cleanup.
+        unsafe {
+            rcl_action_server_fini(rcl_action_server, &mut *rcl_node);
+        }
+    }
+}
+
+/// Trait to be implemented by concrete ActionServer structs.
+///
+/// See [`ActionServer<A>`] for an example.
+pub trait ActionServerBase: Send + Sync {
+    /// Internal function to get a reference to the `rcl` handle.
+    fn handle(&self) -> &ActionServerHandle;
+    /// Tries to take new goal, cancel and result requests and processes them, and expires
+    /// finished goals whose results have been kept long enough.
+    fn execute(&self) -> Result<(), RclrsError>;
+}
+
+/// The answer of the goal callback of an [`ActionServer`] to a new goal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GoalResponse {
+    /// The goal is rejected and will not be executed.
+    Reject,
+    /// The goal is accepted and will be passed to the execute callback.
+    Accept,
+}
+
+/// The answer of the cancel callback of an [`ActionServer`] to a request to cancel a goal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CancelResponse {
+    /// The goal keeps executing.
+    Reject,
+    /// The goal transitions to [`GoalStatus::Canceling`], which the execution of the goal can
+    /// check with [`ServerGoalHandle::is_canceling()`].
+    Accept,
+}
+
+// A pointer to a goal handle that is owned by the rcl action server.
+struct RclGoalHandle(*mut rcl_action_goal_handle_t);
+
+// SAFETY: The goal handle is only accessed while the mutex of its action server is locked, and
+// the rcl functions accessing it don't care about the thread they are running in.
+unsafe impl Send for RclGoalHandle {}
+
+// The goals of an action server, which are shared with the handles of the goals.
+struct GoalTracker<A: ActionImpl> {
+    // Goals that have been accepted and have not finished yet.
+    active_goals: HashMap<GoalUuid, RclGoalHandle>,
+    // The results of finished goals, which are kept until the goals expire.
+    results: HashMap<GoalUuid, RmwResultResponse<A>>,
+    // Result requests for goals that have not finished yet.
+    result_requests: HashMap<GoalUuid, Vec<rmw_request_id_t>>,
+}
+
+impl<A: ActionImpl> Default for GoalTracker<A> {
+    fn default() -> Self {
+        Self {
+            active_goals: HashMap::new(),
+            results: HashMap::new(),
+            result_requests: HashMap::new(),
+        }
+    }
+}
+
+/// A goal that was accepted by an [`ActionServer`], used to execute it.
+///
+/// The goal handle is passed to the execute callback of the action server. Since the executor
+/// is blocked while a callback runs, long-running goals should be moved to another thread
+/// together with their handle.
+///
+/// The execution ends by calling [`succeed()`][1], [`abort()`][2] or [`canceled()`][3], which
+/// sends the result to the action clients. If the goal handle is dropped before that, the goal is
+/// aborted with a default result.
+///
+/// [1]: ServerGoalHandle::succeed
+/// [2]: ServerGoalHandle::abort
+/// [3]: ServerGoalHandle::canceled
+pub struct ServerGoalHandle<A: ActionImpl> {
+    goal_id: GoalUuid,
+    goal: A::Goal,
+    handle: Arc<ActionServerHandle>,
+    goals: Arc<Mutex<GoalTracker<A>>>,
+    finished: bool,
+}
+
+impl<A: ActionImpl> ServerGoalHandle<A> {
+    /// Returns the ID of the goal.
+    pub fn goal_id(&self) -> GoalUuid {
+        self.goal_id
+    }
+
+    /// Returns the goal that was sent by the action client.
+    pub fn goal(&self) -> &A::Goal {
+        &self.goal
+    }
+
+    /// Returns `true` if a request to cancel this goal was accepted.
+    ///
+    /// The execution of the goal should then stop as soon as possible and call
+    /// [`ServerGoalHandle::canceled()`].
+    pub fn is_canceling(&self) -> bool {
+        let goals = self.goals.lock().unwrap();
+        let Some(goal_handle) = goals.active_goals.get(&self.goal_id) else {
+            return false;
+        };
+        let _rcl_action_server = self.handle.lock();
+        let mut status = 0;
+        // SAFETY: The goal handle is valid while the goal is active, and the mutex of the action
+        // server is locked.
+        let ret = unsafe { rcl_action_goal_handle_get_status(goal_handle.0, &mut status) };
+        ret.ok_for("rcl_action_goal_handle_get_status").is_ok()
+            && GoalStatus::from(status) == GoalStatus::Canceling
+    }
+
+    /// Publishes feedback about the progress of the goal.
+    pub fn publish_feedback<'a, F>(&self, feedback: F) -> Result<(), RclrsError>
+    where
+        F: MessageCow<'a, A::Feedback>,
+    {
+        let rmw_feedback = A::Feedback::into_rmw_message(feedback.into_cow()).into_owned();
+        let mut feedback_message = A::create_feedback_message(&self.goal_id.0, rmw_feedback);
+        unsafe {
+            // SAFETY: The message type is guaranteed to match the action server type by the type
+            // system.
+            rcl_action_publish_feedback(
+                &*self.handle.lock(),
+                &mut feedback_message as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_publish_feedback")
+    }
+
+    /// Finishes the goal successfully and sends the result to the action clients.
+    pub fn succeed<'a, R>(mut self, result: R) -> Result<(), RclrsError>
+    where
+        R: MessageCow<'a, A::Result>,
+    {
+        self.finish(
+            rcl_action_goal_event_t::GOAL_EVENT_SUCCEED,
+            GoalStatus::Succeeded,
+            result,
+        )
+    }
+
+    /// Finishes the goal unsuccessfully and sends the result to the action clients.
+    pub fn abort<'a, R>(mut self, result: R) -> Result<(), RclrsError>
+    where
+        R: MessageCow<'a, A::Result>,
+    {
+        self.finish(
+            rcl_action_goal_event_t::GOAL_EVENT_ABORT,
+            GoalStatus::Aborted,
+            result,
+        )
+    }
+
+    /// Finishes a goal that is being canceled and sends the result to the action clients.
+    ///
+    /// This is only valid after a request to cancel the goal was accepted, see
+    /// [`ServerGoalHandle::is_canceling()`]. Otherwise, [`ActionGoalEventInvalid`][1] is
+    /// returned.
+    ///
+    /// [1]: crate::RclReturnCode::ActionGoalEventInvalid
+    pub fn canceled<'a, R>(mut self, result: R) -> Result<(), RclrsError>
+    where
+        R: MessageCow<'a, A::Result>,
+    {
+        self.finish(
+            rcl_action_goal_event_t::GOAL_EVENT_CANCELED,
+            GoalStatus::Canceled,
+            result,
+        )
+    }
+
+    fn finish<'a, R>(
+        &mut self,
+        event: rcl_action_goal_event_t,
+        status: GoalStatus,
+        result: R,
+    ) -> Result<(), RclrsError>
+    where
+        R: MessageCow<'a, A::Result>,
+    {
+        let mut goals = self.goals.lock().unwrap();
+        let rcl_action_server = self.handle.lock();
+        let Some(goal_handle) = goals.active_goals.get(&self.goal_id) else {
+            return Err(RclrsError::RclError {
+                code: RclReturnCode::ActionGoalHandleInvalid,
+                operation: None,
+                msg: None,
+            });
+        };
+        // SAFETY: The goal handle is valid while the goal is active, and the mutex of the action
+        // server is locked.
+        unsafe { rcl_action_update_goal_state(goal_handle.0, event) }
+            .ok_for("rcl_action_update_goal_state")?;
+        self.finished = true;
+        goals.active_goals.remove(&self.goal_id);
+
+        let rmw_result = A::Result::into_rmw_message(result.into_cow()).into_owned();
+        let mut response = A::create_result_response(status as i8, rmw_result);
+        let mut send_result = Ok(());
+        for mut request_id in goals
+            .result_requests
+            .remove(&self.goal_id)
+            .unwrap_or_default()
+        {
+            // Even if sending to one client fails, the other clients should get the result.
+            let ret = unsafe {
+                // SAFETY: The response type is guaranteed to match the action server type by the
+                // type system.
+                rcl_action_send_result_response(
+                    &*rcl_action_server,
+                    &mut request_id,
+                    &mut response as *mut _ as *mut c_void,
+                )
+            };
+            send_result = send_result.and(ret.ok_for("rcl_action_send_result_response"));
+        }
+        goals.results.insert(self.goal_id, response);
+        publish_status(&rcl_action_server)?;
+        // SAFETY: The action server is valid. This restarts the timer that expires the results.
+        unsafe { rcl_action_notify_goal_done(&*rcl_action_server) }
+            .ok_for("rcl_action_notify_goal_done")?;
+        send_result
+    }
+}
+
+impl<A: ActionImpl> Drop for ServerGoalHandle<A> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.finish(
+                rcl_action_goal_event_t::GOAL_EVENT_ABORT,
+                GoalStatus::Aborted,
+                A::Result::default(),
+            );
+        }
+    }
+}
+
+type GoalCallback<A> =
+    Box<dyn FnMut(GoalUuid, &<A as rosidl_runtime_rs::Action>::Goal) -> GoalResponse + Send>;
+type CancelCallback = Box<dyn FnMut(GoalUuid) -> CancelResponse + Send>;
+type ExecuteCallback<A> = Box<dyn FnMut(ServerGoalHandle<A>) + Send>;
+
+/// Main class responsible for executing the goals of ROS action clients.
+///
+/// The only available way to instantiate action servers is via
+/// [`Node::create_action_server()`][1], this is to ensure that [`Node`][2]s can track all the
+/// action servers that have been created.
+///
+/// A new goal is first passed to the goal callback, which decides whether the goal is accepted.
+/// Accepted goals are passed to the execute callback as a [`ServerGoalHandle`], which is used to
+/// publish feedback and to finish the goal with a result. Requests to cancel a goal are passed to
+/// the cancel callback.
+///
+/// The results of finished goals are kept for the result timeout of the action server, which is
+/// 15 minutes, so that action clients can still request them.
+///
+/// [1]: crate::Node::create_action_server
+/// [2]: crate::Node
+pub struct ActionServer<A>
+where
+    A: ActionImpl,
+{
+    pub(crate) handle: Arc<ActionServerHandle>,
+    goal_callback: Mutex<GoalCallback<A>>,
+    cancel_callback: Mutex<CancelCallback>,
+    execute_callback: Mutex<ExecuteCallback<A>>,
+    goals: Arc<Mutex<GoalTracker<A>>>,
+}
+
+impl<A> ActionServer<A>
+where
+    A: ActionImpl,
+{
+    /// Creates a new action server.
+    pub(crate) fn new(
+        node_handle: Arc<NodeHandle>,
+        clock: Clock,
+        action_name: &str,
+        goal_callback: GoalCallback<A>,
+        cancel_callback: CancelCallback,
+        execute_callback: ExecuteCallback<A>,
+    ) -> Result<Self, RclrsError>
+// This uses pub(crate) visibility to avoid instantiating this struct outside
+    // [`Node::create_action_server`], see the struct's documentation for the rationale
+    {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut rcl_action_server = unsafe { rcl_action_get_zero_initialized_server() };
+        let type_support = A::get_type_support() as *const rosidl_action_type_support_t;
+        let action_name_c_string =
+            CString::new(action_name).map_err(|err| RclrsError::StringContainsNul {
+                err,
+                s: action_name.into(),
+            })?;
+        validate_topic_name(action_name)?;
+
+        // SAFETY: No preconditions for this function.
+        let action_server_options = unsafe { rcl_action_server_get_default_options() };
+
+        {
+            let rcl_clock = clock.rcl_clock();
+            let mut rcl_clock = rcl_clock.lock().unwrap();
+            let mut rcl_node = node_handle.rcl_node.lock().unwrap();
+            let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+
+            // SAFETY:
+            // * The rcl_action_server was zero-initialized as expected by this function.
+            // * The rcl_node is kept alive by the NodeHandle because it is a dependency of the
+            //   action server.
+            // * The clock is kept alive by the handle, and is stored behind an Arc, so the
+            //   pointer that the action server keeps to it remains valid.
+            // * The action name and the options are copied by this function, so they can be
+            //   dropped afterwards.
+            // * The entity lifecycle mutex is locked to protect against the risk of global
+            //   variables in the rmw implementation being unsafely modified during initialization.
+            unsafe {
+                rcl_action_server_init(
+                    &mut rcl_action_server,
+                    &mut *rcl_node,
+                    &mut *rcl_clock,
+                    type_support,
+                    action_name_c_string.as_ptr(),
+                    &action_server_options,
+                )
+                .ok_for("rcl_action_server_init")?;
+            }
+        }
+
+        // The handle finalizes the action server when it is dropped, so it is created before
+        // anything else can fail.
+        let mut handle = ActionServerHandle {
+            rcl_action_server: Mutex::new(rcl_action_server),
+            node_handle,
+            clock,
+            num_entities: WaitableNumEntities::default(),
+            in_use_by_wait_set: Arc::new(AtomicBool::new(false)),
+        };
+        let num_entities = &mut handle.num_entities;
+        // SAFETY: The action server was successfully initialized, and the out-parameters are
+        // valid pointers.
+        unsafe {
+            rcl_action_server_wait_set_get_num_entities(
+                handle.rcl_action_server.get_mut().unwrap(),
+                &mut num_entities.subscriptions,
+                &mut num_entities.guard_conditions,
+                &mut num_entities.timers,
+                &mut num_entities.clients,
+                &mut num_entities.services,
+            )
+            .ok_for("rcl_action_server_wait_set_get_num_entities")?;
+        }
+        let handle = Arc::new(handle);
+
+        Ok(Self {
+            handle,
+            goal_callback: Mutex::new(goal_callback),
+            cancel_callback: Mutex::new(cancel_callback),
+            execute_callback: Mutex::new(execute_callback),
+            goals: Arc::new(Mutex::new(GoalTracker::default())),
+        })
+    }
+
+    fn execute_goal_request(&self) -> Result<(), RclrsError> {
+        let mut request_id = rmw_request_id_t {
+            writer_guid: [0; 16],
+            sequence_number: 0,
+        };
+        let mut request = RmwGoalRequest::<A>::default();
+        unsafe {
+            // SAFETY: The three pointers are valid/initialized, and the request type matches
+            // the action server type.
+            rcl_action_take_goal_request(
+                &*self.handle.lock(),
+                &mut request_id,
+                &mut request as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_take_goal_request")?;
+        let (uuid, rmw_goal) = A::split_goal_request(request);
+        let goal_id = GoalUuid(uuid);
+        let goal = A::Goal::from_rmw_message(rmw_goal);
+        let stamp = self.handle.clock.now();
+        let goal_info = action_msgs::msg::rmw::GoalInfo {
+            goal_id: unique_identifier_msgs::msg::rmw::UUID { uuid },
+            stamp: builtin_interfaces::msg::rmw::Time {
+                sec: stamp.nsec.div_euclid(1_000_000_000) as i32,
+                nanosec: stamp.nsec.rem_euclid(1_000_000_000) as u32,
+            },
+        };
+
+        // A goal ID that is already in use can't be accepted again.
+        let goal_exists = unsafe {
+            // SAFETY: The goal info has the same layout as rcl_action_goal_info_t.
+            rcl_action_server_goal_exists(
+                &*self.handle.lock(),
+                &goal_info as *const _ as *const rcl_action_goal_info_t,
+            )
+        };
+        let accepted = !goal_exists
+            && (*self.goal_callback.lock().unwrap())(goal_id, &goal) == GoalResponse::Accept;
+
+        {
+            let mut goals = self.goals.lock().unwrap();
+            let mut rcl_action_server = self.handle.lock();
+            if accepted {
+                // SAFETY: The goal info has the same layout as rcl_action_goal_info_t, and is
+                // copied by this function. The returned goal handle is owned by the action server.
+                let goal_handle = unsafe {
+                    rcl_action_accept_new_goal(
+                        &mut *rcl_action_server,
+                        &goal_info as *const _ as *const rcl_action_goal_info_t,
+                    )
+                };
+                if goal_handle.is_null() {
+                    return Err(RclrsError::RclError {
+                        code: RclReturnCode::Error,
+                        operation: None,
+                        msg: None,
+                    });
+                }
+                // SAFETY: The goal handle was just created and is valid.
+                unsafe {
+                    rcl_action_update_goal_state(
+                        goal_handle,
+                        rcl_action_goal_event_t::GOAL_EVENT_EXECUTE,
+                    )
+                }
+                .ok_for("rcl_action_update_goal_state")?;
+                goals
+                    .active_goals
+                    .insert(goal_id, RclGoalHandle(goal_handle));
+            }
+
+            let mut response =
+                A::create_goal_response(accepted, (goal_info.stamp.sec, goal_info.stamp.nanosec));
+            unsafe {
+                // SAFETY: The response type is guaranteed to match the action server type by the
+                // type system.
+                rcl_action_send_goal_response(
+                    &*rcl_action_server,
+                    &mut request_id,
+                    &mut response as *mut _ as *mut c_void,
+                )
+            }
+            .ok_for("rcl_action_send_goal_response")?;
+            if accepted {
+                publish_status(&rcl_action_server)?;
+            }
+        }
+
+        if accepted {
+            let goal_handle = ServerGoalHandle {
+                goal_id,
+                goal,
+                handle: Arc::clone(&self.handle),
+                goals: Arc::clone(&self.goals),
+                finished: false,
+            };
+            (*self.execute_callback.lock().unwrap())(goal_handle);
+        }
+        Ok(())
+    }
+
+    fn execute_cancel_request(&self) -> Result<(), RclrsError> {
+        let mut request_id = rmw_request_id_t {
+            writer_guid: [0; 16],
+            sequence_number: 0,
+        };
+        let mut request = action_msgs::srv::rmw::CancelGoal_Request::default();
+        unsafe {
+            // SAFETY: The three pointers are valid/initialized, and the request type matches
+            // the cancel service of every action.
+            rcl_action_take_cancel_request(
+                &*self.handle.lock(),
+                &mut request_id,
+                &mut request as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_take_cancel_request")?;
+
+        let (mut response, requested_any, candidates) = {
+            let goals = self.goals.lock().unwrap();
+            let rcl_action_server = self.handle.lock();
+            // SAFETY: Getting a zero-initialized value is always safe.
+            let mut cancel_response = unsafe { rcl_action_get_zero_initialized_cancel_response() };
+            unsafe {
+                // SAFETY: The request has the same layout as rcl_action_cancel_request_t, and the
+                // response was zero-initialized as expected by this function.
+                rcl_action_process_cancel_request(
+                    &*rcl_action_server,
+                    &request as *const _ as *const rcl_action_cancel_request_t,
+                    &mut cancel_response,
+                )
+            }
+            .ok_for("rcl_action_process_cancel_request")?;
+            // SAFETY: The message of the rcl response has the same layout as the rmw message.
+            let rcl_response = unsafe {
+                &*(&cancel_response.msg as *const _
+                    as *const action_msgs::srv::rmw::CancelGoal_Response)
+            };
+            let response = action_msgs::srv::rmw::CancelGoal_Response {
+                return_code: rcl_response.return_code,
+                ..Default::default()
+            };
+            let candidates: Vec<_> = rcl_response
+                .goals_canceling
+                .iter()
+                .filter(|goal_info| {
+                    goals
+                        .active_goals
+                        .contains_key(&GoalUuid(goal_info.goal_id.uuid))
+                })
+                .cloned()
+                .collect();
+            let requested_any = !rcl_response.goals_canceling.is_empty();
+            // SAFETY: The response was initialized by rcl_action_process_cancel_request(), and
+            // is not used afterwards.
+            unsafe { rcl_action_cancel_response_fini(&mut cancel_response) }
+                .ok_for("rcl_action_cancel_response_fini")?;
+            (response, requested_any, candidates)
+        };
+
+        // The callback is called without holding any lock, so that it can use the goal handles.
+        let accepted: Vec<_> = candidates
+            .iter()
+            .filter(|goal_info| {
+                (*self.cancel_callback.lock().unwrap())(GoalUuid(goal_info.goal_id.uuid))
+                    == CancelResponse::Accept
+            })
+            .collect();
+
+        let mut goals_canceling = Vec::new();
+        let rcl_action_server = {
+            let goals = self.goals.lock().unwrap();
+            let rcl_action_server = self.handle.lock();
+            for goal_info in accepted {
+                // The goal may have finished while the callback ran.
+                let Some(goal_handle) = goals.active_goals.get(&GoalUuid(goal_info.goal_id.uuid))
+                else {
+                    continue;
+                };
+                // SAFETY: The goal handle is valid while the goal is active, and the mutex of
+                // the action server is locked.
+                unsafe {
+                    rcl_action_update_goal_state(
+                        goal_handle.0,
+                        rcl_action_goal_event_t::GOAL_EVENT_CANCEL_GOAL,
+                    )
+                }
+                .ok_for("rcl_action_update_goal_state")?;
+                goals_canceling.push(goal_info.clone());
+            }
+            rcl_action_server
+        };
+        if requested_any && goals_canceling.is_empty() {
+            response.return_code = action_msgs::srv::rmw::CancelGoal_Response::ERROR_REJECTED;
+        }
+
+        let canceling_any = !goals_canceling.is_empty();
+        response.goals_canceling = goals_canceling.into();
+        unsafe {
+            // SAFETY: The response has the type expected by the cancel service of every action.
+            rcl_action_send_cancel_response(
+                &*rcl_action_server,
+                &mut request_id,
+                &mut response as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_send_cancel_response")?;
+        if canceling_any {
+            publish_status(&rcl_action_server)?;
+        }
+        Ok(())
+    }
+
+    fn execute_result_request(&self) -> Result<(), RclrsError> {
+        let mut request_id = rmw_request_id_t {
+            writer_guid: [0; 16],
+            sequence_number: 0,
+        };
+        let mut request = RmwResultRequest::<A>::default();
+        unsafe {
+            // SAFETY: The three pointers are valid/initialized, and the request type matches
+            // the action server type.
+            rcl_action_take_result_request(
+                &*self.handle.lock(),
+                &mut request_id,
+                &mut request as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_take_result_request")?;
+        let goal_id = GoalUuid(*A::get_result_request_uuid(&request));
+
+        let mut goals = self.goals.lock().unwrap();
+        let mut unknown_goal_response;
+        let response = if let Some(response) = goals.results.get_mut(&goal_id) {
+            response
+        } else if goals.active_goals.contains_key(&goal_id) {
+            // The response is sent once the goal has finished.
+            goals
+                .result_requests
+                .entry(goal_id)
+                .or_default()
+                .push(request_id);
+            return Ok(());
+        } else {
+            unknown_goal_response =
+                A::create_result_response(GoalStatus::Unknown as i8, Default::default());
+            &mut unknown_goal_response
+        };
+        unsafe {
+            // SAFETY: The response type is guaranteed to match the action server type by the
+            // type system.
+            rcl_action_send_result_response(
+                &*self.handle.lock(),
+                &mut request_id,
+                response as *mut _ as *mut c_void,
+            )
+        }
+        .ok_for("rcl_action_send_result_response")
+    }
+
+    fn execute_goal_expired(&self) -> Result<(), RclrsError> {
+        let mut goals = self.goals.lock().unwrap();
+        let rcl_action_server = self.handle.lock();
+        // Only finished goals can expire, so this is enough capacity for all expired goals.
+        let mut expired_goals =
+            vec![action_msgs::msg::rmw::GoalInfo::default(); goals.results.len().max(1)];
+        let mut num_expired = 0;
+        unsafe {
+            // SAFETY: The goal infos have the same layout as rcl_action_goal_info_t, and the
+            // capacity matches the length of the vector.
+            rcl_action_expire_goals(
+                &*rcl_action_server,
+                expired_goals.as_mut_ptr() as *mut rcl_action_goal_info_t,
+                expired_goals.len(),
+                &mut num_expired,
+            )
+        }
+        .ok_for("rcl_action_expire_goals")?;
+        for goal_info in &expired_goals[..num_expired] {
+            goals.results.remove(&GoalUuid(goal_info.goal_id.uuid));
+        }
+        Ok(())
+    }
+}
+
+impl<A> ActionServerBase for ActionServer<A>
+where
+    A: ActionImpl,
+{
+    fn handle(&self) -> &ActionServerHandle {
+        &self.handle
+    }
+
+    fn execute(&self) -> Result<(), RclrsError> {
+        let take_results = [
+            self.execute_goal_request(),
+            self.execute_cancel_request(),
+            self.execute_result_request(),
+        ];
+        for take_result in take_results {
+            match take_result {
+                Ok(())
+                | Err(RclrsError::RclError {
+                    code: RclReturnCode::ActionServerTakeFailed,
+                    ..
+                }) => {
+                    // A take failure only means that this part of the action server was not
+                    // ready, so it shouldn't be an error.
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        self.execute_goal_expired()
+    }
+}
+
+// Publishes the status of all goals of the action server.
+fn publish_status(rcl_action_server: &rcl_action_server_t) -> Result<(), RclrsError> {
+    // SAFETY: Getting a zero-initialized value is always safe.
+    let mut status_array = unsafe { rcl_action_get_zero_initialized_goal_status_array() };
+    // SAFETY: The status array was zero-initialized as expected by this function.
+    unsafe { rcl_action_get_goal_status_array(rcl_action_server, &mut status_array) }
+        .ok_for("rcl_action_get_goal_status_array")?;
+    let publish_result = unsafe {
+        // SAFETY: The message has the type expected by the status topic of every action.
+        rcl_action_publish_status(
+            rcl_action_server,
+            &status_array.msg as *const _ as *const c_void,
+        )
+    }
+    .ok_for("rcl_action_publish_status");
+    // SAFETY: The status array was initialized above, and is not used afterwards.
+    unsafe { rcl_action_goal_status_array_fini(&mut status_array) }
+        .ok_for("rcl_action_goal_status_array_fini")?;
+    publish_result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_helpers::*, CancelResponseCode};
+    use std::{
+        sync::atomic::Ordering,
+        time::{Duration, Instant},
+    };
+    use test_msgs::action;
+
+    #[test]
+    fn traits() {
+        assert_send::<ActionServer<action::Fibonacci>>();
+        assert_sync::<ActionServer<action::Fibonacci>>();
+        assert_send::<ServerGoalHandle<action::Fibonacci>>();
+    }
+
+    // Computes the Fibonacci sequence up to the given order, publishing the sequence as feedback
+    // after each step.
+    fn execute_fibonacci(
+        goal_handle: ServerGoalHandle<action::Fibonacci>,
+        step: Duration,
+    ) -> Result<(), RclrsError> {
+        let order = goal_handle.goal().order as usize;
+        let mut sequence = vec![0, 1];
+        while sequence.len() < order {
+            if goal_handle.is_canceling() {
+                return goal_handle.canceled(&action::Fibonacci_Result { sequence });
+            }
+            sequence.push(sequence[sequence.len() - 1] + sequence[sequence.len() - 2]);
+            goal_handle.publish_feedback(&action::Fibonacci_Feedback {
+                sequence: sequence.clone(),
+            })?;
+            std::thread::sleep(step);
+        }
+        goal_handle.succeed(&action::Fibonacci_Result { sequence })
+    }
+
+    #[tokio::test]
+    async fn test_fibonacci_action() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_fibonacci_action")?;
+        let _action_server = graph
+            .node1
+            .create_action_server::<action::Fibonacci, _, _, _>(
+                "fibonacci",
+                |_, goal: &action::Fibonacci_Goal| {
+                    if goal.order < 0 {
+                        GoalResponse::Reject
+                    } else {
+                        GoalResponse::Accept
+                    }
+                },
+                |_| CancelResponse::Accept,
+                |goal_handle: ServerGoalHandle<action::Fibonacci>| {
+                    // A goal of order 0 is dropped without a result, which aborts it
+                    if goal_handle.goal().order == 0 {
+                        return;
+                    }
+                    // Large goals are computed slowly, so that they can be canceled
+                    let step = if goal_handle.goal().order > 40 {
+                        Duration::from_millis(50)
+                    } else {
+                        Duration::from_millis(1)
+                    };
+                    std::thread::spawn(move || execute_fibonacci(goal_handle, step).unwrap());
+                },
+            )?;
+        let client = graph
+            .node2
+            .create_action_client::<action::Fibonacci>("fibonacci")?;
+        assert!(client.wait_for_server(Duration::from_secs(5))?);
+
+        let done = Arc::new(AtomicBool::new(false));
+        let spin_thread = {
+            let (node1, node2, done) = (
+                Arc::clone(&graph.node1),
+                Arc::clone(&graph.node2),
+                Arc::clone(&done),
+            );
+            std::thread::spawn(move || {
+                while !done.load(Ordering::Acquire) {
+                    let _ = crate::spin_once(Arc::clone(&node1), Some(Duration::from_millis(10)));
+                    let _ = crate::spin_once(Arc::clone(&node2), Some(Duration::from_millis(10)));
+                }
+            })
+        };
+
+        let rejected = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.send_goal(&action::Fibonacci_Goal { order: -1 }, |_| {}),
+        )
+        .await
+        .expect("No goal response received")?;
+        assert!(rejected.is_none());
+
+        let received_feedback = Arc::new(Mutex::new(Vec::new()));
+        let goal_handle = {
+            let received_feedback = Arc::clone(&received_feedback);
+            tokio::time::timeout(
+                Duration::from_secs(5),
+                client.send_goal(
+                    &action::Fibonacci_Goal { order: 10 },
+                    move |feedback: action::Fibonacci_Feedback| {
+                        received_feedback.lock().unwrap().push(feedback.sequence);
+                    },
+                ),
+            )
+            .await
+            .expect("No goal response received")?
+            .expect("The goal was rejected")
+        };
+        let (status, result) =
+            tokio::time::timeout(Duration::from_secs(5), client.get_result(&goal_handle))
+                .await
+                .expect("No result received")?;
+        assert_eq!(status, GoalStatus::Succeeded);
+        assert_eq!(result.sequence, vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+        // Feedback may be lost before the feedback topic is connected, but any feedback that
+        // arrives is the beginning of the final sequence.
+        for feedback in received_feedback.lock().unwrap().iter() {
+            assert!(result.sequence.starts_with(feedback));
+        }
+
+        // The result of a finished goal can be requested again
+        let (status, _) =
+            tokio::time::timeout(Duration::from_secs(5), client.get_result(&goal_handle))
+                .await
+                .expect("No result received")?;
+        assert_eq!(status, GoalStatus::Succeeded);
+
+        let goal_handle = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.send_goal(&action::Fibonacci_Goal { order: 0 }, |_| {}),
+        )
+        .await
+        .expect("No goal response received")?
+        .expect("The goal was rejected");
+        let (status, _) =
+            tokio::time::timeout(Duration::from_secs(5), client.get_result(&goal_handle))
+                .await
+                .expect("No result received")?;
+        assert_eq!(status, GoalStatus::Aborted);
+
+        let goal_handle = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.send_goal(&action::Fibonacci_Goal { order: 46 }, |_| {}),
+        )
+        .await
+        .expect("No goal response received")?
+        .expect("The goal was rejected");
+        let code = tokio::time::timeout(Duration::from_secs(5), client.cancel_goal(&goal_handle))
+            .await
+            .expect("No cancel response received")?;
+        assert_eq!(code, CancelResponseCode::Accepted);
+        let start = Instant::now();
+        let (status, result) =
+            tokio::time::timeout(Duration::from_secs(5), client.get_result(&goal_handle))
+                .await
+                .expect("No result received")?;
+        assert_eq!(status, GoalStatus::Canceled);
+        assert!(result.sequence.len() < 46);
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        // Canceling a finished goal is not possible
+        let code = tokio::time::timeout(Duration::from_secs(5), client.cancel_goal(&goal_handle))
+            .await
+            .expect("No cancel response received")?;
+        assert_eq!(code, CancelResponseCode::GoalTerminated);
+
+        done.store(true, Ordering::Release);
+        spin_thread.join().unwrap();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_callback_can_use_goal_handles() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_cancel_callback_can_use_goal_handles")?;
+        let server_goal_handle: Arc<Mutex<Option<ServerGoalHandle<action::Fibonacci>>>> =
+            Default::default();
+        let _action_server = {
+            let goal_handle_for_cancel = Arc::clone(&server_goal_handle);
+            let goal_handle_for_execute = Arc::clone(&server_goal_handle);
+            graph
+                .node1
+                .create_action_server::<action::Fibonacci, _, _, _>(
+                    "fibonacci",
+                    |_, _: &action::Fibonacci_Goal| GoalResponse::Accept,
+                    move |_| {
+                        // This locks the goals of the action server
+                        let goal_handle = goal_handle_for_cancel.lock().unwrap();
+                        assert!(!goal_handle.as_ref().unwrap().is_canceling());
+                        CancelResponse::Accept
+                    },
+                    move |goal_handle: ServerGoalHandle<action::Fibonacci>| {
+                        *goal_handle_for_execute.lock().unwrap() = Some(goal_handle);
+                    },
+                )?
+        };
+        let client = graph
+            .node2
+            .create_action_client::<action::Fibonacci>("fibonacci")?;
+        assert!(client.wait_for_server(Duration::from_secs(5))?);
+
+        let done = Arc::new(AtomicBool::new(false));
+        let spin_thread = {
+            let (node1, node2, done) = (
+                Arc::clone(&graph.node1),
+                Arc::clone(&graph.node2),
+                Arc::clone(&done),
+            );
+            std::thread::spawn(move || {
+                while !done.load(Ordering::Acquire) {
+                    let _ = crate::spin_once(Arc::clone(&node1), Some(Duration::from_millis(10)));
+                    let _ = crate::spin_once(Arc::clone(&node2), Some(Duration::from_millis(10)));
+                }
+            })
+        };
+
+        let goal_handle = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.send_goal(&action::Fibonacci_Goal { order: 10 }, |_| {}),
+        )
+        .await
+        .expect("No goal response received")?
+        .expect("The goal was rejected");
+        let code = tokio::time::timeout(Duration::from_secs(5), client.cancel_goal(&goal_handle))
+            .await
+            .expect("No cancel response received")?;
+        assert_eq!(code, CancelResponseCode::Accepted);
+
+        let server_goal_handle = server_goal_handle.lock().unwrap().take().unwrap();
+        assert!(server_goal_handle.is_canceling());
+        server_goal_handle.canceled(&action::Fibonacci_Result::default())?;
+        let (status, _) =
+            tokio::time::timeout(Duration::from_secs(5), client.get_result(&goal_handle))
+                .await
+                .expect("No result received")?;
+        assert_eq!(status, GoalStatus::Canceled);
+
+        done.store(true, Ordering::Release);
+        spin_thread.join().unwrap();
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    os::raw::c_void,
+    ptr::null_mut,
+    sync::Arc,
+};
+
+use crate::rcl_bindings::*;
+
+// The alignment of all allocations, which matches the alignment guaranteed by malloc() on
+// common platforms.
+const ALIGNMENT: usize = 16;
+// Every allocation is prefixed with a header storing its size, since rcl does not pass the size
+// to deallocate() and reallocate(). The header keeps the returned pointer aligned.
+const HEADER_SIZE: usize = ALIGNMENT;
+
+/// An allocator for the memory that `rcl` allocates internally.
+///
+/// By default, `rcl` allocates with `malloc()`. An `Allocator` routes these allocations through
+/// a Rust [`GlobalAlloc`] instead, e.g. a memory pool of a real-time application.
+///
+/// An allocator can be set for a context with [`InitOptions::with_allocator()`][1], which makes
+/// the context, the wait sets of its executors and its nodes use it. It can also be set for a
+/// single node with [`NodeBuilder::allocator()`][2].
+///
+/// This only covers the allocations of `rcl` for these entities. Memory that `rclrs` allocates
+/// itself, e.g. for messages, comes from the global allocator of the application, which can be
+/// replaced with the `#[global_allocator]` attribute. The [`SingleThreadedExecutor`][3] reuses
+/// its wait sets and buffers, so that spinning it allocates from neither allocator once they have
+/// grown to fit the entities of its nodes. Only the guard conditions returned by custom
+/// [`Waitable`][4]s are allocated anew by each spin.
+///
+/// The methods of the wrapped allocator are called from C code, so they must not panic.
+///
+/// # Example
+/// ```
+/// # use rclrs::{Allocator, Context, InitOptions, RclrsError};
+/// use std::alloc::System;
+///
+/// let options = InitOptions::new().with_allocator(Some(Allocator::new(System)));
+/// let context = Context::new_with_options([], options)?;
+/// # Ok::<(), RclrsError>(())
+/// ```
+///
+/// [1]: crate::InitOptions::with_allocator
+/// [2]: crate::NodeBuilder::allocator
+/// [3]: crate::SingleThreadedExecutor
+/// [4]: crate::Waitable
+#[derive(Clone)]
+pub struct Allocator {
+    // The rcl allocator points to this, so it must be kept alive as long as any rcl entity that
+    // was initialized with the allocator.
+    state: Arc<AllocatorState>,
+}
+
+struct AllocatorState {
+    alloc: Box<dyn GlobalAlloc + Send + Sync>,
+}
+
+impl Allocator {
+    /// Creates an allocator that allocates with the given Rust allocator.
+    pub fn new<A>(alloc: A) -> Self
+    where
+        A: GlobalAlloc + Send + Sync + 'static,
+    {
+        Self {
+            state: Arc::new(AllocatorState {
+                alloc: Box::new(alloc),
+            }),
+        }
+    }
+
+    /// Returns the rcl allocator that forwards to this allocator.
+    ///
+    /// The returned value must not be used after this `Allocator` and all its clones have been
+    /// dropped.
+    pub(crate) fn to_rcl(&self) -> rcl_allocator_t {
+        rcl_allocator_t {
+            allocate: Some(allocate),
+            deallocate: Some(deallocate),
+            reallocate: Some(reallocate),
+            zero_allocate: Some(zero_allocate),
+            state: Arc::as_ptr(&self.state) as *mut c_void,
+        }
+    }
+}
+
+/// Returns the rcl allocator for the given allocator, or the default allocator of rcl.
+pub(crate) fn rcl_allocator(allocator: Option<&Allocator>) -> rcl_allocator_t {
+    match allocator {
+        Some(allocator) => allocator.to_rcl(),
+        // SAFETY: No preconditions for this function.
+        None => unsafe { rcutils_get_default_allocator() },
+    }
+}
+
+fn layout_for(size: usize) -> Option<Layout> {
+    let total_size = size.checked_add(HEADER_SIZE)?;
+    Layout::from_size_align(total_size, ALIGNMENT).ok()
+}
+
+// Returns the start of the allocation and the size that was requested for the given pointer.
+//
+// SAFETY: The pointer must have been returned by allocate_impl() or reallocate().
+unsafe fn header_of(pointer: *mut c_void) -> (*mut u8, usize) {
+    let base = (pointer as *mut u8).sub(HEADER_SIZE);
+    (base, (base as *const usize).read())
+}
+
+// SAFETY: The state must point to the AllocatorState of a live Allocator.
+unsafe fn allocate_impl(size: usize, state: *mut c_void, zeroed: bool) -> *mut c_void {
+    let alloc = &(*(state as *const AllocatorState)).alloc;
+    let layout = match layout_for(size) {
+        Some(layout) => layout,
+        None => return null_mut(),
+    };
+    // SAFETY: The layout has a non-zero size, since it includes the header.
+    let base = if zeroed {
+        alloc.alloc_zeroed(layout)
+    } else {
+        alloc.alloc(layout)
+    };
+    if base.is_null() {
+        return null_mut();
+    }
+    // SAFETY: The allocation is aligned for usize and large enough for the header.
+    (base as *mut usize).write(size);
+    base.add(HEADER_SIZE) as *mut c_void
+}
+
+unsafe extern "C" fn allocate(size: usize, state: *mut c_void) -> *mut c_void {
+    allocate_impl(size, state, false)
+}
+
+unsafe extern "C" fn zero_allocate(
+    number_of_elements: usize,
+    size_of_element: usize,
+    state: *mut c_void,
+) -> *mut c_void {
+    match number_of_elements.checked_mul(size_of_element) {
+        Some(size) => allocate_impl(size, state, true),
+        None => null_mut(),
+    }
+}
+
+unsafe extern "C" fn deallocate(pointer: *mut c_void, state: *mut c_void) {
+    if pointer.is_null() {
+        return;
+    }
+    let alloc = &(*(state as *const AllocatorState)).alloc;
+    // SAFETY: rcl only deallocates pointers that were allocated with the same allocator.
+    let (base, size) = header_of(pointer);
+    alloc.dealloc(
+        base,
+        Layout::from_size_align_unchecked(size + HEADER_SIZE, ALIGNMENT),
+    );
+}
+
+unsafe extern "C" fn reallocate(
+    pointer: *mut c_void,
+    size: usize,
+    state: *mut c_void,
+) -> *mut c_void {
+    if pointer.is_null() {
+        return allocate(size, state);
+    }
+    let alloc = &(*(state as *const AllocatorState)).alloc;
+    if layout_for(size).is_none() {
+        return null_mut();
+    }
+    // SAFETY: rcl only reallocates pointers that were allocated with the same allocator.
+    let (base, old_size) = header_of(pointer);
+    let old_layout = Layout::from_size_align_unchecked(old_size + HEADER_SIZE, ALIGNMENT);
+    // SAFETY: The new size is non-zero and valid for the alignment, as checked above.
+    let new_base = alloc.realloc(base, old_layout, size + HEADER_SIZE);
+    if new_base.is_null() {
+        return null_mut();
+    }
+    (new_base as *mut usize).write(size);
+    new_base.add(HEADER_SIZE) as *mut c_void
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        create_node, test_helpers::*, Context, InitOptions, RclrsError, SingleThreadedExecutor,
+    };
+    use std::{
+        alloc::System,
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[derive(Default)]
+    struct Counts {
+        allocations: AtomicUsize,
+        deallocations: AtomicUsize,
+    }
+
+    struct CountingAllocator(Arc<Counts>);
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            self.0.allocations.fetch_add(1, Ordering::SeqCst);
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.0.deallocations.fetch_add(1, Ordering::SeqCst);
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn traits() {
+        assert_send::<Allocator>();
+        assert_sync::<Allocator>();
+    }
+
+    #[test]
+    fn test_allocations_are_stable_while_spinning() -> Result<(), RclrsError> {
+        let counts = Arc::new(Counts::default());
+        let allocator = Allocator::new(CountingAllocator(Arc::clone(&counts)));
+        let context =
+            Context::new_with_options([], InitOptions::new().with_allocator(Some(allocator)))?;
+        let node = create_node(&context, "test_allocator")?;
+        // Creating the context and the node allocates with the allocator.
+        assert!(counts.allocations.load(Ordering::SeqCst) > 0);
+
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&node)?;
+        let spin = || ignore_timeout(executor.spin_once(Some(Duration::ZERO)));
+        let outstanding = || {
+            counts.allocations.load(Ordering::SeqCst) - counts.deallocations.load(Ordering::SeqCst)
+        };
+        // The first spin creates the wait set, which is reused by the following spins.
+        spin()?;
+        let allocations = counts.allocations.load(Ordering::SeqCst);
+        let outstanding_before = outstanding();
+        for _ in 0..10 {
+            spin()?;
+            assert_eq!(counts.allocations.load(Ordering::SeqCst), allocations);
+            assert_eq!(outstanding(), outstanding_before);
+        }
+        Ok(())
+    }
+}
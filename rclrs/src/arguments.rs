@@ -63,7 +63,8 @@ pub fn extract_non_ros_args(
         // SAFETY: Getting a default value is always safe.
         let allocator = rcutils_get_default_allocator();
         // SAFETY: No preconditions for this function.
-        rcl_parse_arguments(c_args.len() as i32, argv, allocator, &mut rcl_arguments).ok()?;
+        rcl_parse_arguments(c_args.len() as i32, argv, allocator, &mut rcl_arguments)
+            .ok_for("rcl_parse_arguments")?;
     }
 
     let ret = get_rcl_arguments(
@@ -74,7 +75,7 @@ pub fn extract_non_ros_args(
     );
     unsafe {
         // SAFETY: No preconditions for this function.
-        rcl_arguments_fini(&mut rcl_arguments).ok()?;
+        rcl_arguments_fini(&mut rcl_arguments).ok_for("rcl_arguments_fini")?;
     }
     ret
 }
@@ -112,7 +113,7 @@ pub(crate) fn get_rcl_arguments(
         let allocator = rcutils_get_default_allocator();
         // SAFETY: The indices_ptr is an output parameter, so it is expected that it contains null.
         // The indices_ptr will need to be freed by the caller, which happens later in this function.
-        rcl_get_indices(rcl_arguments, allocator, &mut indices_ptr).ok()?;
+        rcl_get_indices(rcl_arguments, allocator, &mut indices_ptr).ok_for("rcl_get_indices")?;
 
         for i in 0..args_count {
             // SAFETY: rcl_get_indices finished with success and rcl_get_count is matching function
@@ -0,0 +1,110 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The concurrency policy of a [`CallbackGroup`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallbackGroupType {
+    /// At most one callback of the group is executed at any time.
+    MutuallyExclusive,
+    /// The callbacks of the group may be executed in parallel, including several executions of
+    /// the same callback.
+    Reentrant,
+}
+
+/// A group of callbacks that controls which callbacks may be executed in parallel by the
+/// [`MultiThreadedExecutor`][1].
+///
+/// Callback groups are created with [`Node::create_callback_group()`][2] and assigned to entities
+/// when creating them, e.g. with [`SubscriptionOptions::callback_group()`][3] or
+/// [`Node::create_timer_with_callback_group()`][5].
+/// Entities that are not assigned to a group belong to the default group of their node, which is
+/// mutually exclusive.
+///
+/// The [`SingleThreadedExecutor`][4] never executes callbacks in parallel, regardless of their
+/// groups.
+///
+/// [1]: crate::MultiThreadedExecutor
+/// [2]: crate::Node::create_callback_group
+/// [3]: crate::SubscriptionOptions::callback_group
+/// [4]: crate::SingleThreadedExecutor
+/// [5]: crate::Node::create_timer_with_callback_group
+#[derive(Debug)]
+pub struct CallbackGroup {
+    group_type: CallbackGroupType,
+    // Whether a callback of a mutually exclusive group is currently being executed.
+    busy: AtomicBool,
+}
+
+impl CallbackGroup {
+    pub(crate) fn new(group_type: CallbackGroupType) -> Self {
+        Self {
+            group_type,
+            busy: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the concurrency policy of this group.
+    pub fn group_type(&self) -> CallbackGroupType {
+        self.group_type
+    }
+
+    /// Returns `true` if no more callbacks of this group may be executed at the moment.
+    pub(crate) fn is_busy(&self) -> bool {
+        self.busy.load(Ordering::Acquire)
+    }
+
+    /// Marks a callback of this group as being executed, if the group's policy allows it.
+    ///
+    /// Every successful call must be followed by a call to [`Self::release()`] once the
+    /// callback has finished.
+    pub(crate) fn try_acquire(&self) -> bool {
+        match self.group_type {
+            CallbackGroupType::MutuallyExclusive => self
+                .busy
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok(),
+            CallbackGroupType::Reentrant => true,
+        }
+    }
+
+    /// Marks a callback of this group as finished.
+    pub(crate) fn release(&self) {
+        if self.group_type == CallbackGroupType::MutuallyExclusive {
+            self.busy.store(false, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn traits() {
+        assert_send::<CallbackGroup>();
+        assert_sync::<CallbackGroup>();
+    }
+
+    #[test]
+    fn mutually_exclusive_group() {
+        let group = CallbackGroup::new(CallbackGroupType::MutuallyExclusive);
+        assert!(!group.is_busy());
+        assert!(group.try_acquire());
+        assert!(group.is_busy());
+        assert!(!group.try_acquire());
+        group.release();
+        assert!(!group.is_busy());
+        assert!(group.try_acquire());
+    }
+
+    #[test]
+    fn reentrant_group() {
+        let group = CallbackGroup::new(CallbackGroupType::Reentrant);
+        assert!(group.try_acquire());
+        assert!(group.try_acquire());
+        assert!(!group.is_busy());
+        group.release();
+        group.release();
+        assert!(!group.is_busy());
+    }
+}
@@ -3,6 +3,7 @@ use std::{
     collections::HashMap,
     ffi::CString,
     sync::{atomic::AtomicBool, Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
 };
 
 use futures::channel::oneshot;
@@ -11,9 +12,12 @@ use rosidl_runtime_rs::Message;
 use crate::{
     error::{RclReturnCode, ToResult},
     rcl_bindings::*,
-    MessageCow, NodeHandle, RclrsError, ENTITY_LIFECYCLE_MUTEX,
+    validate_topic_name, CallbackGroup, MessageCow, NodeHandle, RclrsError, ENTITY_LIFECYCLE_MUTEX,
 };
 
+mod options;
+pub use options::*;
+
 // SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
 // they are running in. Therefore, this type can be safely sent to another thread.
 unsafe impl Send for rcl_client_t {}
@@ -56,11 +60,26 @@ pub trait ClientBase: Send + Sync {
     fn handle(&self) -> &ClientHandle;
     /// Tries to take a new response and run the callback or future with it.
     fn execute(&self) -> Result<(), RclrsError>;
+    /// Returns the callback group of the client, if one was assigned.
+    fn callback_group(&self) -> Option<&Arc<CallbackGroup>>;
 }
 
 type RequestValue<Response> = Box<dyn FnOnce(Response) + 'static + Send>;
 
-type RequestId = i64;
+pub(crate) type RequestId = i64;
+
+/// Removes the sender of a [`Client::call_async()`] request when the future is dropped,
+/// so that cancelled requests do not accumulate.
+pub(crate) struct PendingResponse<'a, Sender> {
+    pub(crate) futures: &'a Mutex<HashMap<RequestId, Sender>>,
+    pub(crate) sequence_number: RequestId,
+}
+
+impl<Sender> Drop for PendingResponse<'_, Sender> {
+    fn drop(&mut self) {
+        self.futures.lock().unwrap().remove(&self.sequence_number);
+    }
+}
 
 /// Main class responsible for sending requests to a ROS service.
 ///
@@ -76,6 +95,7 @@ where
     pub(crate) handle: Arc<ClientHandle>,
     requests: Mutex<HashMap<RequestId, RequestValue<T::Response>>>,
     futures: Arc<Mutex<HashMap<RequestId, oneshot::Sender<T::Response>>>>,
+    callback_group: Option<Arc<CallbackGroup>>,
 }
 
 impl<T> Client<T>
@@ -83,7 +103,11 @@ where
     T: rosidl_runtime_rs::Service,
 {
     /// Creates a new client.
-    pub(crate) fn new(node_handle: Arc<NodeHandle>, topic: &str) -> Result<Self, RclrsError>
+    pub(crate) fn new(
+        node_handle: Arc<NodeHandle>,
+        topic: &str,
+        options: ClientOptions,
+    ) -> Result<Self, RclrsError>
     // This uses pub(crate) visibility to avoid instantiating this struct outside
     // [`Node::create_client`], see the struct's documentation for the rationale
     where
@@ -97,9 +121,11 @@ where
             err,
             s: topic.into(),
         })?;
+        validate_topic_name(topic)?;
 
         // SAFETY: No preconditions for this function.
-        let client_options = unsafe { rcl_client_get_default_options() };
+        let mut client_options = unsafe { rcl_client_get_default_options() };
+        client_options.qos = options.qos.into();
 
         {
             let rcl_node = node_handle.rcl_node.lock().unwrap();
@@ -120,7 +146,7 @@ where
                     topic_c_string.as_ptr(),
                     &client_options,
                 )
-                .ok()?;
+                .ok_for("rcl_client_init")?;
             }
         }
 
@@ -136,6 +162,7 @@ where
             futures: Arc::new(Mutex::new(
                 HashMap::<RequestId, oneshot::Sender<T::Response>>::new(),
             )),
+            callback_group: options.callback_group,
         })
     }
 
@@ -169,7 +196,7 @@ where
                 &mut sequence_number,
             )
         }
-        .ok()?;
+        .ok_for("rcl_send_request")?;
         let requests = &mut *self.requests.lock().unwrap();
         requests.insert(sequence_number, Box::new(callback));
         Ok(())
@@ -187,6 +214,12 @@ where
     ///
     /// Hence, when a message will not be needed anymore after publishing, pass it by value.
     /// When a message will be needed again after publishing, pass it by reference, instead of cloning and passing by value.
+    ///
+    /// The response is matched to the request by its sequence number, and is only received
+    /// while the node of this client is being spun, e.g. in another thread.
+    ///
+    /// Dropping the future before the response arrived cancels the request, and a response
+    /// that arrives afterwards is discarded.
     pub async fn call_async<'a, R: MessageCow<'a, T::Request>>(
         &self,
         request: R,
@@ -196,17 +229,26 @@ where
     {
         let rmw_message = T::Request::into_rmw_message(request.into_cow());
         let mut sequence_number = -1;
-        unsafe {
-            // SAFETY: The request type is guaranteed to match the client type by the type system.
-            rcl_send_request(
-                &*self.handle.lock() as *const _,
-                rmw_message.as_ref() as *const <T::Request as Message>::RmwMsg as *mut _,
-                &mut sequence_number,
-            )
-        }
-        .ok()?;
         let (tx, rx) = oneshot::channel::<T::Response>();
-        self.futures.lock().unwrap().insert(sequence_number, tx);
+        {
+            // The lock is held while sending, so that a response cannot be executed before the
+            // sender is stored.
+            let mut futures = self.futures.lock().unwrap();
+            unsafe {
+                // SAFETY: The request type is guaranteed to match the client type by the type system.
+                rcl_send_request(
+                    &*self.handle.lock() as *const _,
+                    rmw_message.as_ref() as *const <T::Request as Message>::RmwMsg as *mut _,
+                    &mut sequence_number,
+                )
+            }
+            .ok_for("rcl_send_request")?;
+            futures.insert(sequence_number, tx);
+        }
+        let _pending = PendingResponse {
+            futures: &self.futures,
+            sequence_number,
+        };
         // It is safe to call unwrap() here since the `Canceled` error will only happen when the
         // `Sender` is dropped
         // https://docs.rs/futures/latest/futures/channel/oneshot/struct.Canceled.html
@@ -252,7 +294,7 @@ where
                 &mut response_out as *mut RmwMsg<T> as *mut _,
             )
         }
-        .ok()?;
+        .ok_for("rcl_take_response")?;
         Ok((T::Response::from_rmw_message(response_out), request_id_out))
     }
 
@@ -270,9 +312,36 @@ where
             // client is guaranteed to have been generated with node
             rcl_service_server_is_available(node as *const _, client as *const _, &mut is_ready)
         }
-        .ok()?;
+        .ok_for("rcl_service_server_is_available")?;
         Ok(is_ready)
     }
+
+    /// Blocks until a service server is available or the timeout has elapsed.
+    ///
+    /// Returns `true` if a service server is available, and `false` if the timeout elapsed
+    /// before a server became available.
+    pub fn wait_for_service(&self, timeout: Duration) -> Result<bool, RclrsError> {
+        // The interval at which the availability of the server is checked.
+        const POLL_INTERVAL: Duration = Duration::from_millis(10);
+        // A deadline of None means waiting forever, for timeouts too large to be represented.
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            if self.service_is_ready()? {
+                return Ok(true);
+            }
+            let sleep_duration = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(false);
+                    }
+                    remaining.min(POLL_INTERVAL)
+                }
+                None => POLL_INTERVAL,
+            };
+            std::thread::sleep(sleep_duration);
+        }
+    }
 }
 
 impl<T> ClientBase for Client<T>
@@ -305,12 +374,18 @@ where
         }
         Ok(())
     }
+
+    fn callback_group(&self) -> Option<&Arc<CallbackGroup>> {
+        self.callback_group.as_ref()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test_helpers::*;
+    use futures::FutureExt;
+    use std::sync::atomic::Ordering;
     use test_msgs::srv;
 
     #[test]
@@ -340,4 +415,133 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_wait_for_service() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_wait_for_service")?;
+        let client = graph.node2.create_client::<srv::Empty>("wait_for_me")?;
+
+        let start = Instant::now();
+        assert!(!client.wait_for_service(Duration::from_millis(100))?);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_secs(1));
+
+        let _service = graph
+            .node1
+            .create_service::<srv::Empty, _>("wait_for_me", |_, _| {
+                srv::Empty_Response::default()
+            })?;
+        assert!(client.wait_for_service(Duration::from_secs(5))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_with_options() -> Result<(), RclrsError> {
+        use crate::{
+            CallbackGroupType, ClientOptions, ServiceOptions, QOS_PROFILE_SERVICES_DEFAULT,
+        };
+
+        let graph = construct_test_graph("test_client_with_options")?;
+        let qos = QOS_PROFILE_SERVICES_DEFAULT
+            .reliable()
+            .transient_local()
+            .keep_last(5);
+        let _service = graph
+            .node1
+            .create_service_with_options::<srv::BasicTypes, _>(
+                "qos_service",
+                ServiceOptions::new(qos),
+                |_, request: srv::BasicTypes_Request| srv::BasicTypes_Response {
+                    int64_value: request.int64_value * 2,
+                    ..Default::default()
+                },
+            )?;
+        let group = graph
+            .node2
+            .create_callback_group(CallbackGroupType::Reentrant);
+        let client = graph.node2.create_client_with_options::<srv::BasicTypes>(
+            "qos_service",
+            ClientOptions::new(qos).callback_group(Arc::clone(&group)),
+        )?;
+        assert!(client
+            .callback_group()
+            .map_or(false, |client_group| Arc::ptr_eq(client_group, &group)));
+        assert!(client.wait_for_service(Duration::from_secs(5))?);
+
+        let response = Arc::new(Mutex::new(None));
+        let response_in_callback = Arc::clone(&response);
+        let request = srv::BasicTypes_Request {
+            int64_value: 21,
+            ..Default::default()
+        };
+        client.async_send_request_with_callback(
+            &request,
+            move |response: srv::BasicTypes_Response| {
+                *response_in_callback.lock().unwrap() = Some(response.int64_value);
+            },
+        )?;
+        let start = Instant::now();
+        while response.lock().unwrap().is_none() {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "No response received"
+            );
+            let _ = crate::spin_once(Arc::clone(&graph.node1), Some(Duration::from_millis(10)));
+            let _ = crate::spin_once(Arc::clone(&graph.node2), Some(Duration::from_millis(10)));
+        }
+        assert_eq!(*response.lock().unwrap(), Some(42));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_call_async() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_call_async")?;
+        let _service = graph.node1.create_service::<srv::BasicTypes, _>(
+            "add_two_ints",
+            |_, request: srv::BasicTypes_Request| srv::BasicTypes_Response {
+                int64_value: request.int64_value + i64::from(request.int32_value),
+                ..Default::default()
+            },
+        )?;
+        let client = graph
+            .node2
+            .create_client::<srv::BasicTypes>("add_two_ints")?;
+
+        assert!(client.wait_for_service(Duration::from_secs(5))?);
+
+        let done = Arc::new(AtomicBool::new(false));
+        let spin_thread = {
+            let (node1, node2, done) = (
+                Arc::clone(&graph.node1),
+                Arc::clone(&graph.node2),
+                Arc::clone(&done),
+            );
+            std::thread::spawn(move || {
+                while !done.load(Ordering::Acquire) {
+                    let _ = crate::spin_once(Arc::clone(&node1), Some(Duration::from_millis(10)));
+                    let _ = crate::spin_once(Arc::clone(&node2), Some(Duration::from_millis(10)));
+                }
+            })
+        };
+
+        let request = srv::BasicTypes_Request {
+            int32_value: 2,
+            int64_value: 40,
+            ..Default::default()
+        };
+        let response = tokio::time::timeout(Duration::from_secs(5), client.call_async(&request))
+            .await
+            .expect("No response received")?;
+        assert_eq!(response.int64_value, 42);
+        assert!(client.futures.lock().unwrap().is_empty());
+
+        // Polling the future once sends the request, dropping it cancels the request
+        assert!(client.call_async(&request).now_or_never().is_none());
+        assert!(client.futures.lock().unwrap().is_empty());
+
+        done.store(true, Ordering::Release);
+        spin_thread.join().unwrap();
+        Ok(())
+    }
 }
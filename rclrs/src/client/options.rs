@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use crate::{CallbackGroup, QoSProfile, QOS_PROFILE_SERVICES_DEFAULT};
+
+/// Options for creating a [`Client`][1].
+///
+/// Use this together with [`Node::create_client_with_options()`][2] to configure
+/// features beyond the defaults.
+///
+/// [1]: crate::Client
+/// [2]: crate::Node::create_client_with_options
+#[derive(Debug)]
+pub struct ClientOptions {
+    /// The quality of service profile of the client.
+    ///
+    /// It applies to both the requests sent by the client and the responses it receives.
+    pub qos: QoSProfile,
+    pub(crate) callback_group: Option<Arc<CallbackGroup>>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self::new(QOS_PROFILE_SERVICES_DEFAULT)
+    }
+}
+
+impl From<QoSProfile> for ClientOptions {
+    fn from(qos: QoSProfile) -> Self {
+        Self::new(qos)
+    }
+}
+
+impl ClientOptions {
+    /// Creates client options with the given quality of service profile.
+    pub fn new(qos: QoSProfile) -> Self {
+        Self {
+            qos,
+            callback_group: None,
+        }
+    }
+
+    /// Sets the quality of service profile.
+    pub fn qos(mut self, qos: QoSProfile) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Assigns the client to a [`CallbackGroup`].
+    ///
+    /// If no group is set, the client belongs to the default group of its node.
+    pub fn callback_group(mut self, callback_group: Arc<CallbackGroup>) -> Self {
+        self.callback_group = Some(callback_group);
+        self
+    }
+}
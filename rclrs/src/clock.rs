@@ -1,9 +1,19 @@
-use crate::{error::ToResult, rcl_bindings::*, time::Time, to_rclrs_result};
-use std::sync::{Arc, Mutex};
+use crate::{
+    error::ToResult, rcl_bindings::*, time::Time, Context, ContextHandle, RclReturnCode, RclrsError,
+};
+use std::{
+    fmt,
+    os::raw::c_void,
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+
+// How often a sleeping thread checks whether its context is still valid.
+const SLEEP_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Enum to describe clock type. Redefined for readability and to eliminate the uninitialized case
 /// from the `rcl_clock_type_t` enum in the binding.
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum ClockType {
     /// Time with behavior dependent on the `set_ros_time(bool)` function. If called with `true`
     /// it will be driven by a manual value override, otherwise it will be System Time
@@ -24,12 +34,139 @@ impl From<ClockType> for rcl_clock_type_t {
     }
 }
 
+/// The kind of change of a clock's time source, reported in a [`TimeJump`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockChange {
+    /// The clock uses ROS time, and its time source did not change.
+    RosTimeNoChange,
+    /// The clock switched from system time to ROS time.
+    RosTimeActivated,
+    /// The clock switched from ROS time to system time.
+    RosTimeDeactivated,
+    /// The clock uses system time, and its time source did not change.
+    SystemTimeNoChange,
+}
+
+impl From<rcl_clock_change_t> for ClockChange {
+    fn from(clock_change: rcl_clock_change_t) -> Self {
+        match clock_change {
+            rcl_clock_change_t::RCL_ROS_TIME_NO_CHANGE => ClockChange::RosTimeNoChange,
+            rcl_clock_change_t::RCL_ROS_TIME_ACTIVATED => ClockChange::RosTimeActivated,
+            rcl_clock_change_t::RCL_ROS_TIME_DEACTIVATED => ClockChange::RosTimeDeactivated,
+            rcl_clock_change_t::RCL_SYSTEM_TIME_NO_CHANGE => ClockChange::SystemTimeNoChange,
+        }
+    }
+}
+
+/// A discontinuity in the time of a clock, passed to the callbacks registered with
+/// [`Clock::add_jump_callback()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeJump {
+    /// How the time source of the clock changed.
+    pub clock_change: ClockChange,
+    /// The change of the time in nanoseconds. This is negative for backward jumps.
+    pub delta: i64,
+}
+
+/// Determines which time jumps are passed to a jump callback, see
+/// [`Clock::add_jump_callback()`].
+///
+/// The default threshold reports no jumps at all.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JumpThreshold {
+    /// Report changes of the time source, i.e. ROS time being activated or deactivated.
+    pub on_clock_change: bool,
+    /// Report forward jumps that are at least this large.
+    pub min_forward: Option<Duration>,
+    /// Report backward jumps that are at least this large.
+    pub min_backward: Option<Duration>,
+}
+
+impl JumpThreshold {
+    fn to_rcl(self) -> rcl_jump_threshold_t {
+        // A zero duration disables the respective check in rcl.
+        let to_nanoseconds = |duration: Option<Duration>| {
+            duration.map_or(0, |d| i64::try_from(d.as_nanos()).unwrap_or(i64::MAX))
+        };
+        rcl_jump_threshold_t {
+            on_clock_change: self.on_clock_change,
+            min_forward: rcl_duration_t {
+                nanoseconds: to_nanoseconds(self.min_forward),
+            },
+            min_backward: rcl_duration_t {
+                nanoseconds: -to_nanoseconds(self.min_backward),
+            },
+        }
+    }
+}
+
+type JumpCallback = Mutex<Box<dyn FnMut(&TimeJump) + Send>>;
+
+/// Keeps a jump callback registered with its clock, see [`Clock::add_jump_callback()`].
+///
+/// The callback is unregistered when this handle is dropped.
+pub struct JumpHandle {
+    rcl_clock: Arc<Mutex<rcl_clock_t>>,
+    callback: Box<JumpCallback>,
+}
+
+impl JumpHandle {
+    fn user_data(&self) -> *mut c_void {
+        &*self.callback as *const JumpCallback as *mut c_void
+    }
+}
+
+impl Drop for JumpHandle {
+    fn drop(&mut self) {
+        let mut clock = self.rcl_clock.lock().unwrap();
+        // SAFETY: The callback and user data are the ones that were registered in
+        // Clock::add_jump_callback(). After this call, rcl no longer refers to the user data.
+        unsafe {
+            rcl_clock_remove_jump_callback(
+                &mut *clock,
+                Some(jump_callback_trampoline),
+                self.user_data(),
+            );
+        }
+    }
+}
+
+// SAFETY: The user data is the pointer to the JumpCallback in a JumpHandle, which outlives the
+// registration of this function.
+unsafe extern "C" fn jump_callback_trampoline(
+    time_jump: *const rcl_time_jump_t,
+    before_jump: bool,
+    user_data: *mut c_void,
+) {
+    // rcl calls the callback before and after the jump, only the latter is passed on.
+    if before_jump {
+        return;
+    }
+    let time_jump = &*time_jump;
+    let time_jump = TimeJump {
+        clock_change: ClockChange::from(time_jump.clock_change),
+        delta: time_jump.delta.nanoseconds,
+    };
+    let callback = &*(user_data as *const JumpCallback);
+    (*callback.lock().unwrap())(&time_jump);
+}
+
 /// Struct that implements a Clock and wraps `rcl_clock_t`.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct Clock {
     kind: ClockType,
     rcl_clock: Arc<Mutex<rcl_clock_t>>,
-    // TODO(luca) Implement jump callbacks
+    // Sleeping is interrupted when this context is shut down.
+    context_handle: Option<Arc<ContextHandle>>,
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clock")
+            .field("kind", &self.kind)
+            .field("rcl_clock", &self.rcl_clock)
+            .finish_non_exhaustive()
+    }
 }
 
 /// A clock source that can be used to drive the contained clock. Created when a clock of type
@@ -74,20 +211,149 @@ impl Clock {
             // Function will return Err(_) only if there isn't enough memory to allocate a clock
             // object.
             rcl_clock_init(kind.into(), &mut rcl_clock, &mut allocator)
-                .ok()
+                .ok_for("rcl_clock_init")
                 .unwrap();
         }
         Self {
             kind,
             rcl_clock: Arc::new(Mutex::new(rcl_clock)),
+            context_handle: None,
         }
     }
 
+    /// Ties the clock to `context`, so that sleeping is interrupted when the context is shut
+    /// down, see [`Clock::sleep_until()`].
+    ///
+    /// The clock returned by [`Node::get_clock()`][1] is already tied to the context of the node.
+    ///
+    /// [1]: crate::Node::get_clock
+    pub fn with_context(self, context: &Context) -> Self {
+        self.with_context_handle(Arc::clone(&context.handle))
+    }
+
+    pub(crate) fn with_context_handle(mut self, context_handle: Arc<ContextHandle>) -> Self {
+        self.context_handle = Some(context_handle);
+        self
+    }
+
     /// Returns the clock's `ClockType`.
     pub fn clock_type(&self) -> ClockType {
         self.kind
     }
 
+    /// Registers a callback that is called when the time of the clock jumps.
+    ///
+    /// Which jumps are reported is determined by `threshold`. For example, a clock driven by
+    /// simulated time jumps backward when a rosbag is replayed in a loop.
+    ///
+    /// The callback is called while the clock is being updated, so it must not access this
+    /// clock. It stays registered until the returned [`JumpHandle`] is dropped.
+    pub fn add_jump_callback<F>(
+        &self,
+        threshold: JumpThreshold,
+        callback: F,
+    ) -> Result<JumpHandle, RclrsError>
+    where
+        F: FnMut(&TimeJump) + Send + 'static,
+    {
+        let handle = JumpHandle {
+            rcl_clock: Arc::clone(&self.rcl_clock),
+            callback: Box::new(Mutex::new(Box::new(callback))),
+        };
+        let mut clock = self.rcl_clock.lock().unwrap();
+        // SAFETY: The user data points into the heap allocation of the handle, which stays valid
+        // until the handle unregisters the callback when it is dropped.
+        unsafe {
+            rcl_clock_add_jump_callback(
+                &mut *clock,
+                threshold.to_rcl(),
+                Some(jump_callback_trampoline),
+                handle.user_data(),
+            )
+            .ok_for("rcl_clock_add_jump_callback")?;
+        }
+        drop(clock);
+        Ok(handle)
+    }
+
+    /// Blocks the current thread until the time of this clock has reached `deadline`.
+    ///
+    /// The deadline must have been obtained from this clock, e.g. with `clock.now() + duration`,
+    /// otherwise an [`InvalidArgument`][1] error is returned.
+    ///
+    /// For a clock driven by simulated time, the thread sleeps until the simulated time has
+    /// advanced past the deadline, and keeps sleeping while the simulation is paused.
+    ///
+    /// If the clock is tied to a context, e.g. because it is the clock of a node, and the context
+    /// is shut down while sleeping, this returns early with an [`AlreadyShutdown`][2] error.
+    /// See [`Clock::with_context()`].
+    ///
+    /// [1]: crate::RclReturnCode::InvalidArgument
+    /// [2]: crate::RclReturnCode::AlreadyShutdown
+    pub fn sleep_until(&self, deadline: Time) -> Result<(), RclrsError> {
+        if deadline.compare_with(&self.now(), |_, _| ()).is_none() {
+            return Err(RclrsError::RclError {
+                code: RclReturnCode::InvalidArgument,
+                operation: None,
+                msg: None,
+            });
+        }
+        // ROS time can be changed by a time source at any moment, so the sleeping thread is
+        // woken up whenever the time jumps. The counter avoids missing a jump that happens
+        // between reading the time and starting to wait.
+        let wakeup = Arc::new((Mutex::new(0usize), Condvar::new()));
+        let _jump_handle = match self.kind {
+            ClockType::RosTime => {
+                let wakeup = Arc::clone(&wakeup);
+                let threshold = JumpThreshold {
+                    on_clock_change: true,
+                    min_forward: Some(Duration::from_nanos(1)),
+                    min_backward: Some(Duration::from_nanos(1)),
+                };
+                Some(self.add_jump_callback(threshold, move |_| {
+                    let (jumps, condvar) = &*wakeup;
+                    *jumps.lock().unwrap() += 1;
+                    condvar.notify_all();
+                })?)
+            }
+            ClockType::SystemTime | ClockType::SteadyTime => None,
+        };
+        let (jumps, condvar) = &*wakeup;
+        loop {
+            if matches!(&self.context_handle, Some(handle) if !handle.is_valid()) {
+                return Err(RclrsError::RclError {
+                    code: RclReturnCode::AlreadyShutdown,
+                    operation: None,
+                    msg: None,
+                });
+            }
+            let jumps_before = *jumps.lock().unwrap();
+            let now = self.now().nsec;
+            if now >= deadline.nsec {
+                return Ok(());
+            }
+            let remaining = Duration::from_nanos((deadline.nsec - now) as u64);
+            let jumps = jumps.lock().unwrap();
+            if *jumps == jumps_before {
+                let _ = condvar
+                    .wait_timeout(jumps, remaining.min(SLEEP_POLL_INTERVAL))
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Blocks the current thread until `duration` has elapsed on this clock.
+    ///
+    /// See [`Clock::sleep_until()`] for details.
+    pub fn sleep_for(&self, duration: Duration) -> Result<(), RclrsError> {
+        self.sleep_until(self.now() + duration)
+    }
+
+    /// Returns the `rcl_clock_t` wrapped by this clock, e.g. for passing it to a timer.
+    pub(crate) fn rcl_clock(&self) -> Arc<Mutex<rcl_clock_t>> {
+        Arc::clone(&self.rcl_clock)
+    }
+
     /// Returns the current clock's timestamp.
     pub fn now(&self) -> Time {
         let mut clock = self.rcl_clock.lock().unwrap();
@@ -141,7 +407,7 @@ impl ClockSource {
             // Function will only fail if timer was uninitialized or not RosTime, which should
             // not happen
             rcl_set_ros_time_override(&mut *clock, nanoseconds)
-                .ok()
+                .ok_for("rcl_set_ros_time_override")
                 .unwrap();
         }
     }
@@ -162,7 +428,9 @@ impl ClockSource {
             unsafe {
                 // Function will only fail if timer was uninitialized or not RosTime, which should
                 // not happen
-                rcl_enable_ros_time_override(&mut *clock).ok().unwrap();
+                rcl_enable_ros_time_override(&mut *clock)
+                    .ok_for("rcl_enable_ros_time_override")
+                    .unwrap();
             }
         } else {
             // SAFETY: Safe if clock jump callbacks are not edited, which is guaranteed
@@ -170,7 +438,9 @@ impl ClockSource {
             unsafe {
                 // Function will only fail if timer was uninitialized or not RosTime, which should
                 // not happen
-                rcl_disable_ros_time_override(&mut *clock).ok().unwrap();
+                rcl_disable_ros_time_override(&mut *clock)
+                    .ok_for("rcl_disable_ros_time_override")
+                    .unwrap();
             }
         }
     }
@@ -180,7 +450,7 @@ impl Drop for rcl_clock_t {
     fn drop(&mut self) {
         // SAFETY: No preconditions for this function
         let rc = unsafe { rcl_clock_fini(&mut *self) };
-        if let Err(e) = to_rclrs_result(rc) {
+        if let Err(e) = rc.ok_for("rcl_clock_fini") {
             panic!("Unable to release Clock. {:?}", e)
         }
     }
@@ -218,4 +488,94 @@ mod tests {
         // Ros time is set, should return the value that was set
         assert_eq!(clock.now().nsec, set_time);
     }
+
+    #[test]
+    fn clock_sleep_for_wall_time() {
+        let clock = Clock::steady();
+        let start = std::time::Instant::now();
+        clock.sleep_for(Duration::from_millis(50)).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn clock_sleep_is_interrupted_by_shutdown() {
+        let context = Context::new([]).unwrap();
+        let clock = Clock::steady().with_context(&context);
+        let start = std::time::Instant::now();
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                std::thread::sleep(Duration::from_millis(50));
+                context.shutdown().unwrap();
+            });
+            assert!(matches!(
+                clock.sleep_for(Duration::from_secs(10)),
+                Err(RclrsError::RclError {
+                    code: RclReturnCode::AlreadyShutdown,
+                    ..
+                })
+            ));
+        });
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn clock_sleep_until_rejects_other_clocks() {
+        let deadline = Clock::system().now();
+        assert!(matches!(
+            Clock::steady().sleep_until(deadline),
+            Err(RclrsError::RclError {
+                code: RclReturnCode::InvalidArgument,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn clock_jump_callbacks() {
+        let (clock, source) = Clock::with_source();
+        let jumps = Arc::new(Mutex::new(Vec::new()));
+        let jumps_in_callback = Arc::clone(&jumps);
+        let threshold = JumpThreshold {
+            on_clock_change: true,
+            min_forward: Some(Duration::from_secs(1)),
+            min_backward: Some(Duration::from_secs(1)),
+        };
+        let handle = clock
+            .add_jump_callback(threshold, move |jump| {
+                jumps_in_callback.lock().unwrap().push(*jump);
+            })
+            .unwrap();
+
+        source.set_ros_time_override(10_000_000_000);
+        // Below the threshold
+        source.set_ros_time_override(10_500_000_000);
+        source.set_ros_time_override(5_000_000_000);
+        // Deactivates ROS time
+        drop(source);
+        {
+            let jumps = jumps.lock().unwrap();
+            assert_eq!(jumps.len(), 3);
+            assert_eq!(
+                jumps[0],
+                TimeJump {
+                    clock_change: ClockChange::RosTimeNoChange,
+                    delta: 10_000_000_000
+                }
+            );
+            assert_eq!(
+                jumps[1],
+                TimeJump {
+                    clock_change: ClockChange::RosTimeNoChange,
+                    delta: -5_500_000_000
+                }
+            );
+            assert_eq!(jumps[2].clock_change, ClockChange::RosTimeDeactivated);
+        }
+
+        // No callbacks after the handle was dropped
+        drop(handle);
+        let source = ClockSource::new(clock.rcl_clock());
+        source.set_ros_time_override(0);
+        assert_eq!(jumps.lock().unwrap().len(), 3);
+    }
 }
@@ -1,12 +1,17 @@
 use std::{
-    ffi::CString,
+    ffi::{CStr, CString},
     os::raw::c_char,
     string::String,
     sync::{Arc, Mutex},
     vec::Vec,
 };
 
-use crate::{rcl_bindings::*, RclrsError, ToResult};
+use crate::{
+    allocator::rcl_allocator, arguments::get_rcl_arguments, logging::configure_logging,
+    rcl_bindings::*, Allocator, IntraProcessManager, RclrsError, ToResult,
+};
+
+mod signal_handler;
 
 /// This is locked whenever initializing or dropping any middleware entity
 /// because we have found issues in RCL and some RMW implementations that
@@ -28,12 +33,18 @@ impl Drop for rcl_context_t {
             // The context may be invalid when rcl_init failed, e.g. because of invalid command
             // line arguments.
 
+            // It may also have been shut down already with Context::shutdown(), in which case
+            // it still needs to be finalized.
+
             // SAFETY: No preconditions for rcl_context_is_valid.
-            if rcl_context_is_valid(self) {
+            let is_valid = rcl_context_is_valid(self);
+            if is_valid || !self.impl_.is_null() {
                 let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
                 // SAFETY: The entity lifecycle mutex is locked to protect against the risk of
                 // global variables in the rmw implementation being unsafely modified during cleanup.
-                rcl_shutdown(self);
+                if is_valid {
+                    rcl_shutdown(self);
+                }
                 rcl_context_fini(self);
             }
         }
@@ -54,7 +65,7 @@ unsafe impl Send for rcl_context_t {}
 /// A context stores, among other things
 /// - command line arguments (used for e.g. name remapping)
 /// - middleware-specific data, e.g. the domain participant in DDS
-/// - the allocator used (left as the default by `rclrs`)
+/// - the allocator used, see [`InitOptions::with_allocator`]
 ///
 pub struct Context {
     pub(crate) handle: Arc<ContextHandle>,
@@ -68,6 +79,72 @@ pub struct Context {
 /// bindings in this library.
 pub(crate) struct ContextHandle {
     pub(crate) rcl_context: Mutex<rcl_context_t>,
+    // Triggered when the context is shut down. It is part of every wait set of this context, so
+    // that waiting threads wake up and the executors can stop spinning.
+    pub(crate) shutdown_guard_condition: Mutex<rcl_guard_condition_t>,
+    // Callbacks registered with Context::on_shutdown() that have not run yet.
+    shutdown_callbacks: Mutex<Vec<Box<dyn FnOnce() + Send>>>,
+    pub(crate) intra_process_manager: IntraProcessManager,
+    // The arguments that were not parsed as ROS arguments.
+    non_ros_arguments: Vec<String>,
+    // Declared last, so that it outlives the rcl entities above that allocate with it.
+    allocator: Option<Allocator>,
+}
+
+impl ContextHandle {
+    // The allocator for rcl entities of this context, such as wait sets.
+    pub(crate) fn rcl_allocator(&self) -> rcl_allocator_t {
+        rcl_allocator(self.allocator.as_ref())
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        let rcl_context = &mut *self.rcl_context.lock().unwrap();
+        // SAFETY: No preconditions for this function.
+        unsafe { rcl_context_is_valid(rcl_context) }
+    }
+
+    // See Context::shutdown().
+    fn shutdown(&self) -> Result<(), RclrsError> {
+        {
+            let mut rcl_context = self.rcl_context.lock().unwrap();
+            // SAFETY: No preconditions for this function.
+            if unsafe { rcl_context_is_valid(&*rcl_context) } {
+                {
+                    let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+                    // SAFETY: The context is valid, and the entity lifecycle mutex is locked to
+                    // protect against the risk of global variables in the rmw implementation
+                    // being unsafely modified during shutdown.
+                    unsafe { rcl_shutdown(&mut *rcl_context) }.ok_for("rcl_shutdown")?;
+                }
+                // SAFETY: The guard condition was initialized together with the context.
+                unsafe {
+                    rcl_trigger_guard_condition(&mut *self.shutdown_guard_condition.lock().unwrap())
+                }
+                .ok_for("rcl_trigger_guard_condition")?;
+            }
+        }
+        self.run_shutdown_callbacks();
+        Ok(())
+    }
+
+    // Runs the shutdown callbacks that have not run yet, most recently registered first.
+    fn run_shutdown_callbacks(&self) {
+        let callbacks = std::mem::take(&mut *self.shutdown_callbacks.lock().unwrap());
+        for callback in callbacks.into_iter().rev() {
+            callback();
+        }
+    }
+}
+
+impl Drop for ContextHandle {
+    fn drop(&mut self) {
+        self.run_shutdown_callbacks();
+        // SAFETY: No wait set can be using the guard condition anymore, since wait sets keep the
+        // context alive.
+        unsafe {
+            rcl_guard_condition_fini(self.shutdown_guard_condition.get_mut().unwrap());
+        }
+    }
 }
 
 impl Context {
@@ -76,6 +153,10 @@ impl Context {
     /// Usually this would be called with `std::env::args()`, analogously to `rclcpp::init()`.
     /// See also the official "Passing ROS arguments to nodes via the command-line" tutorial.
     ///
+    /// Only the given arguments are used, the command line of the process is never read. So
+    /// applications that embed ROS can pass their own arguments instead, see also
+    /// [`Context::from_args()`].
+    ///
     /// Creating a context will fail if the args contain invalid ROS arguments.
     ///
     /// # Example
@@ -89,6 +170,23 @@ impl Context {
         Self::new_with_options(args, InitOptions::new())
     }
 
+    /// Creates a new context from a slice of arguments.
+    ///
+    /// This is the same as [`Context::new()`], for arguments that are not owned.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, Node, RclrsError};
+    /// let args = ["--ros-args", "-r", "__node:=renamed"].map(String::from);
+    /// let context = Context::from_args(&args)?;
+    /// let node = Node::new(&context, "original")?;
+    /// assert_eq!(node.name(), "renamed");
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    pub fn from_args(args: &[String]) -> Result<Self, RclrsError> {
+        Self::new(args.iter().cloned())
+    }
+
     /// Same as [`Context::new`] except you can additionally provide initialization options.
     ///
     /// # Example
@@ -101,10 +199,13 @@ impl Context {
         args: impl IntoIterator<Item = String>,
         options: InitOptions,
     ) -> Result<Self, RclrsError> {
+        let shutdown_on_signal = options.shutdown_on_signal;
+        let allocator = options.allocator.clone();
         // SAFETY: Getting a zero-initialized value is always safe
         let mut rcl_context = unsafe { rcl_get_zero_initialized_context() };
+        let args: Vec<String> = args.into_iter().collect();
         let cstring_args: Vec<CString> = args
-            .into_iter()
+            .iter()
             .map(|arg| {
                 CString::new(arg.as_str()).map_err(|err| RclrsError::StringContainsNul {
                     err,
@@ -115,9 +216,9 @@ impl Context {
         // Vector of pointers into cstring_args
         let c_args: Vec<*const c_char> = cstring_args.iter().map(|arg| arg.as_ptr()).collect();
         unsafe {
-            // SAFETY: No preconditions for this function.
-            let allocator = rcutils_get_default_allocator();
-            let mut rcl_init_options = options.into_rcl(allocator)?;
+            // The allocator is kept alive by the ContextHandle, or by this function if creating
+            // the context fails.
+            let mut rcl_init_options = options.into_rcl(rcl_allocator(allocator.as_ref()))?;
             // SAFETY:
             // * This function does not store the ephemeral init_options and c_args pointers.
             // * Passing in a zero-initialized rcl_context is mandatory.
@@ -135,27 +236,78 @@ impl Context {
                     &rcl_init_options,
                     &mut rcl_context,
                 )
-                .ok()
+                .ok_for("rcl_init")
             };
             // SAFETY: It's safe to pass in an initialized object.
             // Early return will not leak memory, because this is the last fini function.
-            rcl_init_options_fini(&mut rcl_init_options).ok()?;
+            rcl_init_options_fini(&mut rcl_init_options).ok_for("rcl_init_options_fini")?;
             // Move the check after the last fini()
             ret?;
         }
-        Ok(Self {
-            handle: Arc::new(ContextHandle {
-                rcl_context: Mutex::new(rcl_context),
-            }),
-        })
+        // The global arguments were parsed from `args` by rcl_init().
+        let non_ros_arguments = get_rcl_arguments(
+            rcl_arguments_get_count_unparsed,
+            rcl_arguments_get_unparsed,
+            &rcl_context.global_arguments,
+            &args,
+        )?;
+        configure_logging(&rcl_context)?;
+        // SAFETY: Getting a zero-initialized value is always safe
+        let mut shutdown_guard_condition = unsafe { rcl_get_zero_initialized_guard_condition() };
+        unsafe {
+            // SAFETY: The context is valid, and the guard condition is zero-initialized.
+            rcl_guard_condition_init(
+                &mut shutdown_guard_condition,
+                &mut rcl_context,
+                rcl_guard_condition_get_default_options(),
+            )
+            .ok_for("rcl_guard_condition_init")?;
+        }
+        let handle = Arc::new(ContextHandle {
+            rcl_context: Mutex::new(rcl_context),
+            shutdown_guard_condition: Mutex::new(shutdown_guard_condition),
+            shutdown_callbacks: Mutex::new(Vec::new()),
+            intra_process_manager: IntraProcessManager::default(),
+            non_ros_arguments,
+            allocator,
+        });
+        if shutdown_on_signal {
+            signal_handler::shut_down_on_signal(&handle)?;
+        }
+        Ok(Self { handle })
+    }
+
+    /// Returns the arguments of the context that are not ROS arguments, in their original order.
+    ///
+    /// ROS arguments are those between `--ros-args` and `--`, or the end of the arguments. The
+    /// remaining arguments, e.g. the program name and the options of the application, can be
+    /// processed by another argument parser. See also [`extract_non_ros_args()`][1], which
+    /// does the same without creating a context.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, RclrsError};
+    /// let args = ["my_tool", "--verbose", "--ros-args", "-r", "__node:=renamed", "--", "input.txt"]
+    ///     .map(String::from);
+    /// let context = Context::new(args)?;
+    /// assert_eq!(context.non_ros_arguments(), ["my_tool", "--verbose", "input.txt"]);
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    ///
+    /// [1]: crate::extract_non_ros_args
+    pub fn non_ros_arguments(&self) -> Vec<String> {
+        self.handle.non_ros_arguments.clone()
     }
 
     /// Returns the ROS domain ID that the context is using.
     ///
     /// The domain ID controls which nodes can send messages to each other, see the [ROS 2 concept article][1].
-    /// It can be set through the `ROS_DOMAIN_ID` environment variable.
+    /// It can be set through the `ROS_DOMAIN_ID` environment variable, or programmatically with
+    /// [`InitOptions::with_domain_id()`][2]. Since the domain ID is a property of the context, a
+    /// single process can create contexts on different domains, e.g. to bridge between them.
     ///
     /// [1]: https://docs.ros.org/en/rolling/Concepts/About-Domain-ID.html
+    /// [2]: crate::InitOptions::with_domain_id
     pub fn domain_id(&self) -> usize {
         let mut domain_id: usize = 0;
         let ret = unsafe {
@@ -169,16 +321,89 @@ impl Context {
         domain_id
     }
 
+    /// Returns the identifier of the RMW implementation, e.g. `rmw_fastrtps_cpp` or
+    /// `rmw_cyclonedds_cpp`.
+    ///
+    /// The RMW implementation is selected at runtime through the `RMW_IMPLEMENTATION`
+    /// environment variable, and is the same for all contexts of the process. This is useful
+    /// for using features that only some implementations support, such as loaned messages.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, RclrsError};
+    /// let context = Context::new([])?;
+    /// if context.rmw_implementation_identifier() == "rmw_cyclonedds_cpp" {
+    ///     println!("Running on Cyclone DDS");
+    /// }
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    pub fn rmw_implementation_identifier(&self) -> &'static str {
+        rmw_implementation_identifier()
+    }
+
     /// Checks if the context is still valid.
     ///
-    /// This will return `false` when a signal has caused the context to shut down (currently
-    /// unimplemented).
+    /// This will return `false` after the context has been shut down with
+    /// [`Context::shutdown()`].
     pub fn ok(&self) -> bool {
-        // This will currently always return true, but once we have a signal handler, the signal
-        // handler could call `rcl_shutdown()`, hence making the context invalid.
-        let rcl_context = &mut *self.handle.rcl_context.lock().unwrap();
-        // SAFETY: No preconditions for this function.
-        unsafe { rcl_context_is_valid(rcl_context) }
+        self.handle.is_valid()
+    }
+
+    /// Shuts down the context.
+    ///
+    /// Afterwards, [`Context::ok()`] returns `false`, and the executors stop spinning the nodes
+    /// of this context. Wait sets of this context that are currently waiting are woken up.
+    /// The callbacks registered with [`Context::on_shutdown()`] are run.
+    ///
+    /// Shutting down a context that was already shut down does nothing.
+    pub fn shutdown(&self) -> Result<(), RclrsError> {
+        self.handle.shutdown()
+    }
+
+    /// Registers a callback that is run when the context shuts down.
+    ///
+    /// This happens when [`Context::shutdown()`] is called, or otherwise when the context is
+    /// destroyed, i.e. when the `Context` and all nodes created from it have been dropped.
+    /// Each callback runs only once, and the callbacks run in the reverse order of their
+    /// registration.
+    ///
+    /// This is useful e.g. for flushing buffers before the application exits.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, RclrsError};
+    /// let context = Context::new([])?;
+    /// context.on_shutdown(|| println!("Shutting down"));
+    /// context.shutdown()?;
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    pub fn on_shutdown<F>(&self, callback: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.handle
+            .shutdown_callbacks
+            .lock()
+            .unwrap()
+            .push(Box::new(callback));
+    }
+}
+
+pub(crate) fn rmw_implementation_identifier() -> &'static str {
+    // SAFETY: No preconditions for this function. The returned string is a static string of
+    // the RMW implementation library, which is never unloaded.
+    let identifier = unsafe { CStr::from_ptr(rmw_get_implementation_identifier()) };
+    identifier.to_str().unwrap_or_default()
+}
+
+impl Default for Context {
+    /// Creates a context without any ROS arguments.
+    ///
+    /// # Panics
+    /// Panics if the context cannot be created, e.g. because the RMW implementation fails to
+    /// initialize. Use [`Context::new()`] to handle this error instead.
+    fn default() -> Self {
+        Self::new([]).expect("Failed to create a context without arguments")
     }
 }
 
@@ -191,6 +416,10 @@ pub struct InitOptions {
     ///
     /// [1]: https://docs.ros.org/en/rolling/Concepts/Intermediate/About-Domain-ID.html#the-ros-domain-id
     domain_id: Option<usize>,
+    /// Whether the Context should be shut down when the process receives SIGINT or SIGTERM.
+    shutdown_on_signal: bool,
+    /// The allocator that rcl uses for the Context, its nodes and its wait sets.
+    allocator: Option<Allocator>,
 }
 
 impl InitOptions {
@@ -216,13 +445,75 @@ impl InitOptions {
         self.domain_id
     }
 
-    fn into_rcl(self, allocator: rcutils_allocator_s) -> Result<rcl_init_options_t, RclrsError> {
+    /// Transform an InitOptions into a new one that shuts down the Context on SIGINT or SIGTERM.
+    ///
+    /// See [`InitOptions::set_shutdown_on_signal`].
+    pub fn with_shutdown_on_signal(mut self, shutdown_on_signal: bool) -> InitOptions {
+        self.shutdown_on_signal = shutdown_on_signal;
+        self
+    }
+
+    /// Set whether the Context should be shut down when the process receives SIGINT (e.g.
+    /// Ctrl-C) or SIGTERM. This is disabled by default.
+    ///
+    /// When enabled, a signal handler is installed for the whole process the first time such a
+    /// Context is created. On a signal, all contexts created with this option are shut down
+    /// with [`Context::shutdown`], which makes the executors spinning their nodes return.
+    /// If none of these contexts is still running, the signal is handled with its default
+    /// action instead, i.e. a second Ctrl-C terminates the process.
+    ///
+    /// Leave this disabled if the application handles signals itself. It can then shut down
+    /// the context from its own handler instead.
+    ///
+    /// Signal handling is only supported on Unix platforms. On other platforms, creating a
+    /// Context with this option returns an [`Unsupported`][1] error.
+    ///
+    /// [1]: crate::RclReturnCode::Unsupported
+    pub fn set_shutdown_on_signal(&mut self, shutdown_on_signal: bool) {
+        self.shutdown_on_signal = shutdown_on_signal;
+    }
+
+    /// Get whether the Context will be shut down on SIGINT or SIGTERM.
+    pub fn shutdown_on_signal(&self) -> bool {
+        self.shutdown_on_signal
+    }
+
+    /// Transform an InitOptions into a new one with a certain allocator.
+    ///
+    /// See [`InitOptions::set_allocator`].
+    pub fn with_allocator(mut self, allocator: Option<Allocator>) -> InitOptions {
+        self.allocator = allocator;
+        self
+    }
+
+    /// Set the allocator that rcl uses for the Context, or reset it to the default allocator
+    /// of rcl by providing None.
+    ///
+    /// The allocator is used for the allocations of rcl when creating the Context, and for the
+    /// nodes and wait sets of the Context, unless a node was given its own allocator with
+    /// [`NodeBuilder::allocator`][1]. The [`SingleThreadedExecutor`][2] creates its wait set on
+    /// the first spin and reuses it afterwards, so spinning does not allocate in rcl once the
+    /// executor has been warmed up. See [`Allocator`] for details.
+    ///
+    /// [1]: crate::NodeBuilder::allocator
+    /// [2]: crate::SingleThreadedExecutor
+    pub fn set_allocator(&mut self, allocator: Option<Allocator>) {
+        self.allocator = allocator;
+    }
+
+    /// Get the allocator that will be provided by these InitOptions.
+    pub fn allocator(&self) -> Option<&Allocator> {
+        self.allocator.as_ref()
+    }
+
+    fn into_rcl(self, allocator: rcl_allocator_t) -> Result<rcl_init_options_t, RclrsError> {
         unsafe {
             // SAFETY: Getting a zero-initialized value is always safe.
             let mut rcl_init_options = rcl_get_zero_initialized_init_options();
             // SAFETY: Passing in a zero-initialized value is expected.
             // In the case where this returns not ok, there's nothing to clean up.
-            rcl_init_options_init(&mut rcl_init_options, allocator).ok()?;
+            rcl_init_options_init(&mut rcl_init_options, allocator)
+                .ok_for("rcl_init_options_init")?;
 
             // We only need to set the domain_id if the user asked for something
             // other than None. When the user asks for None, that is equivalent
@@ -254,6 +545,99 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_context_from_args() -> Result<(), RclrsError> {
+        use crate::Node;
+
+        let args = ["--ros-args", "-r", "__node:=renamed"].map(String::from);
+        let context = Context::from_args(&args)?;
+        let node = Node::new(&context, "original")?;
+        assert_eq!(node.name(), "renamed");
+
+        // The default context has no ROS arguments
+        let context = Context::default();
+        assert!(context.ok());
+        let node = Node::new(&context, "original")?;
+        assert_eq!(node.name(), "original");
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_ros_arguments() -> Result<(), RclrsError> {
+        let args = [
+            "my_tool",
+            "--verbose",
+            "--ros-args",
+            "-r",
+            "__node:=renamed",
+            "-p",
+            "rate:=5",
+            "--",
+            "input.txt",
+            "--ros-args",
+            "--log-level",
+            "warn",
+        ]
+        .map(String::from);
+        let context = Context::new(args)?;
+        assert_eq!(
+            context.non_ros_arguments(),
+            ["my_tool", "--verbose", "input.txt"]
+        );
+        assert!(Context::new([])?.non_ros_arguments().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_contexts_on_different_domains() -> Result<(), RclrsError> {
+        use crate::{Node, QOS_PROFILE_DEFAULT};
+        use std::time::Duration;
+        use test_msgs::msg::Empty;
+
+        let context_on_domain = |domain_id| {
+            Context::new_with_options([], InitOptions::new().with_domain_id(Some(domain_id)))
+        };
+        let context_a = context_on_domain(42)?;
+        let context_b = context_on_domain(43)?;
+        let context_c = context_on_domain(42)?;
+        assert_eq!(context_a.domain_id(), 42);
+        assert_eq!(context_b.domain_id(), 43);
+
+        let node_a = Node::new(&context_a, "domain_publisher")?;
+        let node_b = Node::new(&context_b, "domain_subscriber")?;
+        let node_c = Node::new(&context_c, "same_domain_subscriber")?;
+        let topic = "/test_contexts_on_different_domains";
+        let publisher = node_a.create_publisher::<Empty>(topic, QOS_PROFILE_DEFAULT)?;
+        let _subscription_b =
+            node_b.create_subscription::<Empty, _>(topic, QOS_PROFILE_DEFAULT, |_msg: Empty| {})?;
+        // A subscription on the same domain is discovered ...
+        let _subscription_c =
+            node_c.create_subscription::<Empty, _>(topic, QOS_PROFILE_DEFAULT, |_msg: Empty| {})?;
+        for _ in 0..50 {
+            if publisher.get_subscription_count()? > 0 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        // ... but the one on the other domain is not, even after waiting a bit longer.
+        std::thread::sleep(Duration::from_millis(500));
+        assert_eq!(publisher.get_subscription_count()?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rmw_implementation_identifier() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let identifier = context.rmw_implementation_identifier();
+        assert!(!identifier.is_empty());
+        if let Ok(rmw_implementation) = std::env::var("RMW_IMPLEMENTATION") {
+            assert_eq!(identifier, rmw_implementation);
+        }
+        let node = crate::create_node(&context, "test_rmw_implementation_identifier")?;
+        assert_eq!(node.rmw_implementation_identifier(), identifier);
+        Ok(())
+    }
+
     #[test]
     fn test_context_ok() -> Result<(), RclrsError> {
         // If the context fails to be created, this will cause a panic
@@ -262,4 +646,72 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_shutdown_callbacks() -> Result<(), RclrsError> {
+        let context = Context::new(vec![])?;
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        for i in 0..3 {
+            let calls = Arc::clone(&calls);
+            context.on_shutdown(move || calls.lock().unwrap().push(i));
+        }
+        assert!(calls.lock().unwrap().is_empty());
+
+        context.shutdown()?;
+        assert!(!context.ok());
+        assert_eq!(*calls.lock().unwrap(), vec![2, 1, 0]);
+
+        // Shutting down again doesn't run the callbacks again
+        context.shutdown()?;
+        drop(context);
+        assert_eq!(*calls.lock().unwrap(), vec![2, 1, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shutdown_callbacks_on_drop() -> Result<(), RclrsError> {
+        let context = Context::new(vec![])?;
+        let node = crate::create_node(&context, "test_shutdown_callbacks_on_drop")?;
+        let called = Arc::new(Mutex::new(false));
+        {
+            let called = Arc::clone(&called);
+            context.on_shutdown(move || *called.lock().unwrap() = true);
+        }
+
+        // The node keeps the context alive
+        drop(context);
+        assert!(!*called.lock().unwrap());
+        drop(node);
+        assert!(*called.lock().unwrap());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shutdown_on_signal_stops_spinning() -> Result<(), RclrsError> {
+        use std::{sync::mpsc, time::Duration};
+
+        let options = InitOptions::new().with_shutdown_on_signal(true);
+        let context = Context::new_with_options(vec![], options)?;
+        let node = crate::create_node(&context, "test_shutdown_on_signal_stops_spinning")?;
+        let (sender, receiver) = mpsc::channel();
+        let spin_thread = std::thread::spawn(move || {
+            let result = crate::spin(node);
+            sender.send(()).unwrap();
+            result
+        });
+
+        // Give the executor time to start waiting
+        std::thread::sleep(Duration::from_millis(100));
+        signal_hook::low_level::raise(signal_hook::consts::SIGINT).unwrap();
+        receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("spin() did not return after SIGINT");
+        spin_thread.join().unwrap()?;
+        assert!(!context.ok());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,84 @@
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::{ContextHandle, RclrsError};
+
+// The contexts that are shut down when the process receives SIGINT or SIGTERM, or None if the
+// signal handler has not been installed yet.
+static SIGNAL_HANDLED_CONTEXTS: Mutex<Option<Vec<Weak<ContextHandle>>>> = Mutex::new(None);
+
+/// Makes the context shut down when the process receives SIGINT or SIGTERM.
+///
+/// The signal handler is installed the first time this is called.
+pub(crate) fn shut_down_on_signal(context_handle: &Arc<ContextHandle>) -> Result<(), RclrsError> {
+    let mut contexts = SIGNAL_HANDLED_CONTEXTS.lock().unwrap();
+    if contexts.is_none() {
+        install_signal_handler()?;
+    }
+    let contexts = contexts.get_or_insert_with(Vec::new);
+    contexts.retain(|context| context.strong_count() > 0);
+    contexts.push(Arc::downgrade(context_handle));
+    Ok(())
+}
+
+// Shuts down the registered contexts. Returns false if none of them was running anymore.
+fn shut_down_contexts() -> bool {
+    // The contexts are collected first, so that the lock isn't held while running their shutdown
+    // callbacks, which might create new contexts.
+    let contexts: Vec<_> = SIGNAL_HANDLED_CONTEXTS
+        .lock()
+        .unwrap()
+        .iter()
+        .flatten()
+        .filter_map(Weak::upgrade)
+        .filter(|context| context.is_valid())
+        .collect();
+    for context in &contexts {
+        if let Err(err) = context.shutdown() {
+            crate::log_error!(
+                "rclrs",
+                "Failed to shut down the context on signal: {}",
+                err
+            );
+        }
+    }
+    !contexts.is_empty()
+}
+
+#[cfg(unix)]
+fn install_signal_handler() -> Result<(), RclrsError> {
+    use signal_hook::{
+        consts::{SIGINT, SIGTERM},
+        iterator::Signals,
+        low_level::emulate_default_handler,
+    };
+
+    let to_rclrs_error = |_| RclrsError::RclError {
+        code: crate::RclReturnCode::Error,
+        operation: None,
+        msg: None,
+    };
+    let mut signals = Signals::new([SIGINT, SIGTERM]).map_err(to_rclrs_error)?;
+    // The contexts are not shut down in the signal handler itself, since only very few
+    // functions may be called there. Instead, a thread waits for the signals.
+    std::thread::Builder::new()
+        .name("rclrs_signal_handler".to_string())
+        .spawn(move || {
+            for signal in signals.forever() {
+                if !shut_down_contexts() {
+                    // Nothing left to shut down, so don't swallow the signal.
+                    let _ = emulate_default_handler(signal);
+                }
+            }
+        })
+        .map_err(to_rclrs_error)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn install_signal_handler() -> Result<(), RclrsError> {
+    Err(RclrsError::RclError {
+        code: crate::RclReturnCode::Unsupported,
+        operation: None,
+        msg: None,
+    })
+}
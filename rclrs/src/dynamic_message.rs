@@ -16,7 +16,9 @@ use crate::rcl_bindings::{
 };
 
 mod error;
+mod message_members;
 pub use error::*;
+pub use message_members::*;
 
 /// Factory for constructing messages in a certain package dynamically.
 ///
@@ -57,7 +59,6 @@ pub struct DynamicMessageMetadata {
     // The library needs to be kept loaded in order to keep the type_support_ptr valid.
     #[allow(dead_code)]
     introspection_type_support_library: Arc<libloading::Library>,
-    #[allow(dead_code)]
     type_support_ptr: *const rosidl_message_type_support_t,
     #[allow(dead_code)]
     fini_function: unsafe extern "C" fn(*mut std::os::raw::c_void),
@@ -250,6 +251,13 @@ impl DynamicMessageMetadata {
         let pkg = DynamicMessagePackage::new(package_name)?;
         pkg.message_metadata(type_name)
     }
+
+    /// Returns the layout of the message type, see [`MessageMembers`].
+    pub fn members(&self) -> MessageMembers {
+        // SAFETY: The type support pointer is an introspection type support, and is kept valid
+        // by keeping the library loaded.
+        unsafe { MessageMembers::from_type_support(&*self.type_support_ptr) }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,530 @@
+use std::ffi::{CStr, CString};
+
+use rosidl_runtime_rs::{Message, RmwMessage};
+
+use super::{DynamicMessageError, INTROSPECTION_TYPE_SUPPORT_IDENTIFIER};
+use crate::rcl_bindings::{
+    rosidl_typesupport_introspection_c__MessageMember_s as rosidl_message_member_t,
+    rosidl_typesupport_introspection_c__MessageMembers_s as rosidl_message_members_t,
+    rosidl_typesupport_introspection_c_field_types::*, *,
+};
+
+/// The layout of a message type, i.e. the descriptions of its fields.
+///
+/// This is obtained from the introspection type support of the message type, and can be used to
+/// e.g. walk through the fields of any message without code that is specific to its type.
+///
+/// # Example
+/// ```
+/// # use rclrs::dynamic_message::{BaseType, DynamicMessageError, MessageMembers, ValueKind};
+/// let members = MessageMembers::of::<rclrs::msg::Time>()?;
+/// assert_eq!(members.type_name, "Time");
+/// let sec = members.get("sec").unwrap();
+/// assert_eq!(sec.base_type, BaseType::Int32);
+/// assert_eq!(sec.value_kind, ValueKind::Simple);
+/// # Ok::<(), DynamicMessageError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageMembers {
+    /// The namespace of the message type, e.g. `geometry_msgs__msg`.
+    pub namespace: String,
+    /// The name of the message type, e.g. `Twist`.
+    pub type_name: String,
+    /// The size of the C representation of the message, in bytes.
+    pub size: usize,
+    /// The fields of the message, in the order in which they are declared.
+    pub members: Vec<MessageMember>,
+}
+
+/// The description of a field of a message, see [`MessageMembers`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageMember {
+    /// The name of the field.
+    pub name: String,
+    /// The type of the field, or of its elements if it is an array or sequence.
+    pub base_type: BaseType,
+    /// Whether the field is a single value, an array or a sequence.
+    pub value_kind: ValueKind,
+}
+
+/// Whether a field holds a single value or several.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    /// A single value, e.g. `int32 data`.
+    Simple,
+    /// An array with a fixed number of elements, e.g. `int32[3] data`.
+    Array {
+        /// The number of elements.
+        length: usize,
+    },
+    /// A sequence without an upper bound on its length, e.g. `int32[] data`.
+    Sequence,
+    /// A sequence with an upper bound on its length, e.g. `int32[<=3] data`.
+    BoundedSequence {
+        /// The maximum number of elements.
+        upper_bound: usize,
+    },
+}
+
+/// The type of a field of a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BaseType {
+    /// `float32`
+    Float,
+    /// `float64`
+    Double,
+    /// `long double`, which is not used by ROS interface definitions.
+    LongDouble,
+    /// `char`
+    Char,
+    /// `wchar`, which is not used by ROS interface definitions.
+    WChar,
+    /// `bool`
+    Boolean,
+    /// `byte`
+    Octet,
+    /// `uint8`
+    Uint8,
+    /// `int8`
+    Int8,
+    /// `uint16`
+    Uint16,
+    /// `int16`
+    Int16,
+    /// `uint32`
+    Uint32,
+    /// `int32`
+    Int32,
+    /// `uint64`
+    Uint64,
+    /// `int64`
+    Int64,
+    /// `string`
+    String,
+    /// `string<=N`
+    BoundedString {
+        /// The maximum length of the string.
+        upper_bound: usize,
+    },
+    /// `wstring`
+    WString,
+    /// `wstring<=N`
+    BoundedWString {
+        /// The maximum length of the string.
+        upper_bound: usize,
+    },
+    /// A nested message.
+    Message(Box<MessageMembers>),
+}
+
+impl MessageMembers {
+    /// Returns the layout of the message type `T`.
+    ///
+    /// This loads the introspection type support library of the package of `T`, which fails if
+    /// that library is not installed.
+    pub fn of<T: Message>() -> Result<Self, DynamicMessageError> {
+        let introspection_type_support = introspection_type_support::<T>()?;
+        // SAFETY: This is an introspection type support.
+        Ok(unsafe { Self::from_type_support(introspection_type_support) })
+    }
+
+    /// Returns the description of the field with the given name.
+    pub fn get(&self, name: &str) -> Option<&MessageMember> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// Returns the names of all fields, in the order in which they are declared.
+    pub fn member_names(&self) -> Vec<&str> {
+        self.members
+            .iter()
+            .map(|member| member.name.as_str())
+            .collect()
+    }
+
+    // SAFETY: The type support must be an introspection type support.
+    pub(crate) unsafe fn from_type_support(type_support: &rosidl_message_type_support_t) -> Self {
+        debug_assert!(!type_support.data.is_null());
+        Self::from_rosidl_message_members(&*(type_support.data as *const rosidl_message_members_t))
+    }
+
+    // SAFETY: The message members must be valid, which they are when they come from an
+    // introspection type support.
+    unsafe fn from_rosidl_message_members(message_members: &rosidl_message_members_t) -> Self {
+        let members: &[rosidl_message_member_t] = if message_members.member_count_ == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(
+                message_members.members_,
+                message_members.member_count_ as usize,
+            )
+        };
+        Self {
+            namespace: string_from_ptr(message_members.message_namespace_),
+            type_name: string_from_ptr(message_members.message_name_),
+            size: message_members.size_of_,
+            members: members
+                .iter()
+                .map(|member| MessageMember::from_rosidl_message_member(member))
+                .collect(),
+        }
+    }
+}
+
+impl MessageMember {
+    // SAFETY: The member must be valid, which it is when it comes from an introspection type
+    // support.
+    unsafe fn from_rosidl_message_member(member: &rosidl_message_member_t) -> Self {
+        let value_kind = match (member.is_array_, member.array_size_, member.is_upper_bound_) {
+            (false, _, _) => ValueKind::Simple,
+            (true, 0, _) => ValueKind::Sequence,
+            (true, upper_bound, true) => ValueKind::BoundedSequence { upper_bound },
+            (true, length, false) => ValueKind::Array { length },
+        };
+        Self {
+            name: string_from_ptr(member.name_),
+            base_type: BaseType::from_rosidl_message_member(member),
+            value_kind,
+        }
+    }
+}
+
+impl BaseType {
+    // SAFETY: The member must be valid, which it is when it comes from an introspection type
+    // support.
+    unsafe fn from_rosidl_message_member(member: &rosidl_message_member_t) -> Self {
+        match member.type_id_ {
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_FLOAT as u8 => Self::Float,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_DOUBLE as u8 => Self::Double,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_LONG_DOUBLE as u8 => {
+                Self::LongDouble
+            }
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_CHAR as u8 => Self::Char,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_WCHAR as u8 => Self::WChar,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_BOOLEAN as u8 => Self::Boolean,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_OCTET as u8 => Self::Octet,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_UINT8 as u8 => Self::Uint8,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_INT8 as u8 => Self::Int8,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_UINT16 as u8 => Self::Uint16,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_INT16 as u8 => Self::Int16,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_UINT32 as u8 => Self::Uint32,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_INT32 as u8 => Self::Int32,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_UINT64 as u8 => Self::Uint64,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_INT64 as u8 => Self::Int64,
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_STRING as u8 => {
+                match member.string_upper_bound_ {
+                    0 => Self::String,
+                    upper_bound => Self::BoundedString { upper_bound },
+                }
+            }
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_WSTRING as u8 => {
+                match member.string_upper_bound_ {
+                    0 => Self::WString,
+                    upper_bound => Self::BoundedWString { upper_bound },
+                }
+            }
+            x if x == rosidl_typesupport_introspection_c__ROS_TYPE_MESSAGE as u8 => Self::Message(
+                Box::new(MessageMembers::from_type_support(&*member.members_)),
+            ),
+            x => panic!(
+                "Unknown type id {} of message member '{}'",
+                x,
+                string_from_ptr(member.name_)
+            ),
+        }
+    }
+}
+
+// Returns the introspection type support of the message type `T`.
+fn introspection_type_support<T: Message>(
+) -> Result<&'static rosidl_message_type_support_t, DynamicMessageError> {
+    let type_support =
+        <T as Message>::RmwMsg::get_type_support() as *const rosidl_message_type_support_t;
+    let identifier = CString::new(INTROSPECTION_TYPE_SUPPORT_IDENTIFIER).unwrap();
+    // SAFETY: The type support of a generated message is always valid, and its handle
+    // function looks up the type support for the given identifier, returning null if it
+    // cannot be found.
+    let introspection_type_support = unsafe {
+        let handle_function = (*type_support)
+            .func
+            .ok_or(DynamicMessageError::InvalidMessageType)?;
+        handle_function(type_support, identifier.as_ptr())
+    };
+    if introspection_type_support.is_null() {
+        return Err(DynamicMessageError::InvalidMessageType);
+    }
+    // SAFETY: The introspection type support was just checked to be non-null, and it stays
+    // valid since the library is not unloaded.
+    Ok(unsafe { &*introspection_type_support })
+}
+
+/// Returns the offset of the `header.stamp` field in the RMW-native message type of `T`, or
+/// `None` if the first field of `T` is not a `std_msgs/msg/Header`.
+pub(crate) fn header_stamp_offset<T: Message>() -> Result<Option<usize>, DynamicMessageError> {
+    let type_support = introspection_type_support::<T>()?;
+    // SAFETY: The data of an introspection type support are its message members.
+    let message_members = unsafe { &*(type_support.data as *const rosidl_message_members_t) };
+    if message_members.member_count_ == 0 {
+        return Ok(None);
+    }
+    // SAFETY: The message has at least one member.
+    let first_member = unsafe { &*message_members.members_ };
+    if first_member.type_id_ != rosidl_typesupport_introspection_c__ROS_TYPE_MESSAGE as u8
+        || first_member.is_array_
+    {
+        return Ok(None);
+    }
+    // SAFETY: The members of a nested message are an introspection type support.
+    let header_members =
+        unsafe { &*((*first_member.members_).data as *const rosidl_message_members_t) };
+    // SAFETY: The names of the message members are valid strings.
+    let is_header = unsafe {
+        CStr::from_ptr(header_members.message_namespace_).to_bytes() == b"std_msgs__msg"
+            && CStr::from_ptr(header_members.message_name_).to_bytes() == b"Header"
+    };
+    if !is_header {
+        return Ok(None);
+    }
+    // SAFETY: The members are valid, see above.
+    let stamp_offset = unsafe {
+        std::slice::from_raw_parts(
+            header_members.members_,
+            header_members.member_count_ as usize,
+        )
+        .iter()
+        .find(|member| CStr::from_ptr(member.name_).to_bytes() == b"stamp")
+        .map(|member| member.offset_ as usize)
+    };
+    Ok(stamp_offset.map(|stamp_offset| first_member.offset_ as usize + stamp_offset))
+}
+
+/// A bounded string or sequence in a message that is longer than its upper bound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BoundsViolation {
+    /// The path of the field, e.g. `nested.values[2]`.
+    pub(crate) field: String,
+    /// The length of the field.
+    pub(crate) len: usize,
+    /// The upper bound declared in the message type.
+    pub(crate) upper_bound: usize,
+}
+
+/// Returns the first bounded string or sequence in `message` that is longer than its upper
+/// bound, or `None` if all of them are within their bounds.
+pub(crate) fn find_bounds_violation<T: Message>(
+    message: &T::RmwMsg,
+) -> Result<Option<BoundsViolation>, DynamicMessageError> {
+    let type_support = introspection_type_support::<T>()?;
+    // SAFETY: The data of an introspection type support are its message members, and they
+    // describe the RMW-native message type of `T`.
+    Ok(unsafe {
+        find_bounds_violation_in_message(
+            &*(type_support.data as *const rosidl_message_members_t),
+            message as *const T::RmwMsg as *const u8,
+            "",
+        )
+    })
+}
+
+// SAFETY: `message` must point to a valid message that is described by `message_members`.
+unsafe fn find_bounds_violation_in_message(
+    message_members: &rosidl_message_members_t,
+    message: *const u8,
+    path: &str,
+) -> Option<BoundsViolation> {
+    if message_members.member_count_ == 0 {
+        return None;
+    }
+    let members = std::slice::from_raw_parts(
+        message_members.members_,
+        message_members.member_count_ as usize,
+    );
+    for member in members {
+        let name = string_from_ptr(member.name_);
+        let field = if path.is_empty() {
+            name
+        } else {
+            format!("{}.{}", path, name)
+        };
+        let field_ptr = message.add(member.offset_ as usize);
+        if !member.is_array_ {
+            if let Some(violation) = find_bounds_violation_in_element(member, field_ptr, &field) {
+                return Some(violation);
+            }
+            continue;
+        }
+        let len = match member.size_function {
+            Some(size_function) => size_function(field_ptr as *const std::os::raw::c_void),
+            None => member.array_size_,
+        };
+        if member.is_upper_bound_ && len > member.array_size_ {
+            return Some(BoundsViolation {
+                field,
+                len,
+                upper_bound: member.array_size_,
+            });
+        }
+        let Some(get_const_function) = member.get_const_function else {
+            continue;
+        };
+        for index in 0..len {
+            let element = get_const_function(field_ptr as *const std::os::raw::c_void, index);
+            let element_field = format!("{}[{}]", field, index);
+            if let Some(violation) =
+                find_bounds_violation_in_element(member, element as *const u8, &element_field)
+            {
+                return Some(violation);
+            }
+        }
+    }
+    None
+}
+
+// SAFETY: `element` must point to a valid value of the base type of `member`.
+unsafe fn find_bounds_violation_in_element(
+    member: &rosidl_message_member_t,
+    element: *const u8,
+    field: &str,
+) -> Option<BoundsViolation> {
+    let len = match member.type_id_ {
+        x if x == rosidl_typesupport_introspection_c__ROS_TYPE_STRING as u8 => {
+            (*(element as *const rosidl_runtime_rs::String)).len()
+        }
+        x if x == rosidl_typesupport_introspection_c__ROS_TYPE_WSTRING as u8 => {
+            (*(element as *const rosidl_runtime_rs::WString)).len()
+        }
+        x if x == rosidl_typesupport_introspection_c__ROS_TYPE_MESSAGE as u8 => {
+            return find_bounds_violation_in_message(
+                &*((*member.members_).data as *const rosidl_message_members_t),
+                element,
+                field,
+            );
+        }
+        _ => return None,
+    };
+    if member.string_upper_bound_ != 0 && len > member.string_upper_bound_ {
+        return Some(BoundsViolation {
+            field: field.to_owned(),
+            len,
+            upper_bound: member.string_upper_bound_,
+        });
+    }
+    None
+}
+
+// SAFETY: The pointer must point to a valid nul-terminated string.
+unsafe fn string_from_ptr(ptr: *const std::os::raw::c_char) -> String {
+    CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn twist_members() -> Result<(), DynamicMessageError> {
+        let twist = MessageMembers::of::<geometry_msgs::msg::Twist>()?;
+        assert_eq!(twist.namespace, "geometry_msgs__msg");
+        assert_eq!(twist.type_name, "Twist");
+        assert_eq!(twist.member_names(), ["linear", "angular"]);
+        for member in &twist.members {
+            assert_eq!(member.value_kind, ValueKind::Simple);
+            let vector3 = match &member.base_type {
+                BaseType::Message(vector3) => vector3,
+                base_type => panic!("Expected a nested message, got {:?}", base_type),
+            };
+            assert_eq!(vector3.type_name, "Vector3");
+            assert_eq!(vector3.member_names(), ["x", "y", "z"]);
+            assert!(vector3
+                .members
+                .iter()
+                .all(|member| member.base_type == BaseType::Double
+                    && member.value_kind == ValueKind::Simple));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn header_stamp_offsets() -> Result<(), DynamicMessageError> {
+        // The stamp is the first field of the header.
+        assert_eq!(
+            header_stamp_offset::<geometry_msgs::msg::PoseStamped>()?,
+            Some(0)
+        );
+        assert_eq!(header_stamp_offset::<std_msgs::msg::Header>()?, None);
+        assert_eq!(header_stamp_offset::<geometry_msgs::msg::Twist>()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn bounds_violations() -> Result<(), DynamicMessageError> {
+        use test_msgs::msg::rmw::{BoundedSequences, Strings};
+
+        let mut strings = Strings::default();
+        assert_eq!(find_bounds_violation::<Strings>(&strings)?, None);
+        // The bounded types cannot be constructed with too many elements, so the bounds are
+        // bypassed here to simulate e.g. a message that was filled in by C code.
+        strings.bounded_string_value = unsafe {
+            std::mem::transmute::<rosidl_runtime_rs::String, rosidl_runtime_rs::BoundedString<22>>(
+                rosidl_runtime_rs::String::from("a".repeat(30).as_str()),
+            )
+        };
+        assert_eq!(
+            find_bounds_violation::<Strings>(&strings)?,
+            Some(BoundsViolation {
+                field: String::from("bounded_string_value"),
+                len: 30,
+                upper_bound: 22,
+            })
+        );
+
+        let mut bounded_sequences = BoundedSequences::default();
+        assert_eq!(
+            find_bounds_violation::<BoundedSequences>(&bounded_sequences)?,
+            None
+        );
+        bounded_sequences.int32_values = unsafe {
+            std::mem::transmute::<
+                rosidl_runtime_rs::Sequence<i32>,
+                rosidl_runtime_rs::BoundedSequence<i32, 3>,
+            >(rosidl_runtime_rs::Sequence::new(5))
+        };
+        assert_eq!(
+            find_bounds_violation::<BoundedSequences>(&bounded_sequences)?,
+            Some(BoundsViolation {
+                field: String::from("int32_values"),
+                len: 5,
+                upper_bound: 3,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_and_string_members() -> Result<(), DynamicMessageError> {
+        let bounded_sequences = MessageMembers::of::<test_msgs::msg::BoundedSequences>()?;
+        let int32_values = bounded_sequences.get("int32_values").unwrap();
+        assert_eq!(int32_values.base_type, BaseType::Int32);
+        assert_eq!(
+            int32_values.value_kind,
+            ValueKind::BoundedSequence { upper_bound: 3 }
+        );
+
+        let unbounded_sequences = MessageMembers::of::<test_msgs::msg::UnboundedSequences>()?;
+        let string_values = unbounded_sequences.get("string_values").unwrap();
+        assert_eq!(string_values.base_type, BaseType::String);
+        assert_eq!(string_values.value_kind, ValueKind::Sequence);
+
+        let strings = MessageMembers::of::<test_msgs::msg::Strings>()?;
+        assert_eq!(
+            strings.get("bounded_string_value").unwrap().base_type,
+            BaseType::BoundedString { upper_bound: 22 }
+        );
+
+        let arrays = MessageMembers::of::<test_msgs::msg::Arrays>()?;
+        assert_eq!(
+            arrays.get("int32_values").unwrap().value_kind,
+            ValueKind::Array { length: 3 }
+        );
+        Ok(())
+    }
+}
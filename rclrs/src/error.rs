@@ -4,15 +4,27 @@ use std::{
     fmt::{self, Display},
 };
 
-use crate::rcl_bindings::*;
+use crate::{rcl_bindings::*, ClockType, InvalidNameError};
 
 /// The main error type.
+///
+/// Errors originating in the `rcl` layer carry the return code and the name of the failed `rcl`
+/// function and, if available, an [`RclErrorMsg`] with the error string set by `rcl`. All of them
+/// are part of the [`Display`] output, e.g.
+///
+/// ```text
+/// rcl_publisher_init failed: Topic name does not pass validation (RCL_RET_TOPIC_NAME_INVALID).
+/// <error string set by rcl>, at <file>:<line>
+/// ```
 #[derive(Debug, PartialEq, Eq)]
 pub enum RclrsError {
     /// An error originating in the `rcl` layer.
     RclError {
         /// The error code.
         code: RclReturnCode,
+        /// The name of the `rcl` function that returned the error code, e.g.
+        /// `rcl_publisher_init`, or `None` if the error was detected by `rclrs` itself.
+        operation: Option<&'static str>,
         /// The error message set in the `rcl` layer or below.
         msg: Option<RclErrorMsg>,
     },
@@ -20,6 +32,8 @@ pub enum RclrsError {
     UnknownRclError {
         /// The error code.
         code: i32,
+        /// The name of the `rcl` function that returned the error code.
+        operation: Option<&'static str>,
         /// The error message set in the `rcl` layer or below.
         msg: Option<RclErrorMsg>,
     },
@@ -32,13 +46,41 @@ pub enum RclrsError {
     },
     /// It was attempted to add a waitable to a wait set twice.
     AlreadyAddedToWaitSet,
+    /// Parameter values were rejected, because they do not match the parameter declarations or
+    /// because an on-set callback rejected them.
+    ParametersRejected {
+        /// The reason the parameters were rejected.
+        reason: String,
+    },
+    /// A topic name, service name, node name or namespace is invalid.
+    InvalidName(InvalidNameError),
+    /// Two times could not be compared, because they originate from clocks of different types.
+    IncompatibleClockTypes {
+        /// The clock type of the left-hand side, or `None` if its clock has been dropped.
+        lhs: Option<ClockType>,
+        /// The clock type of the right-hand side, or `None` if its clock has been dropped.
+        rhs: Option<ClockType>,
+    },
+    /// The future passed to [`SingleThreadedExecutor::spin_until_future_complete()`][1] was not
+    /// complete within the timeout.
+    ///
+    /// [1]: crate::SingleThreadedExecutor::spin_until_future_complete
+    SpinTimeout,
 }
 
 impl Display for RclrsError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            RclrsError::RclError { code, .. } => write!(f, "{}", code),
-            RclrsError::UnknownRclError { code, .. } => write!(f, "{}", code),
+            RclrsError::RclError {
+                code,
+                operation,
+                msg,
+            } => fmt_rcl_error(f, code, *operation, msg.as_ref()),
+            RclrsError::UnknownRclError {
+                code,
+                operation,
+                msg,
+            } => fmt_rcl_error(f, code, *operation, msg.as_ref()),
             RclrsError::StringContainsNul { s, .. } => {
                 write!(f, "Could not convert string '{}' to CString", s)
             }
@@ -48,26 +90,72 @@ impl Display for RclrsError {
                     "Could not add entity to wait set because it was already added to a wait set"
                 )
             }
+            RclrsError::ParametersRejected { reason } => {
+                write!(f, "Parameters were rejected: {}", reason)
+            }
+            RclrsError::InvalidName(err) => write!(f, "{}", err),
+            RclrsError::IncompatibleClockTypes { lhs, rhs } => {
+                write!(
+                    f,
+                    "Cannot compare times from clocks of types {:?} and {:?}",
+                    lhs, rhs
+                )
+            }
+            RclrsError::SpinTimeout => {
+                write!(f, "The future was not complete within the timeout")
+            }
         }
     }
 }
 
+// Formats an error originating in the rcl layer as "<operation> failed: <code> <message>".
+fn fmt_rcl_error(
+    f: &mut fmt::Formatter,
+    code: &dyn Display,
+    operation: Option<&str>,
+    msg: Option<&RclErrorMsg>,
+) -> fmt::Result {
+    if let Some(operation) = operation {
+        write!(f, "{} failed: ", operation)?;
+    }
+    write!(f, "{}", code)?;
+    match msg {
+        Some(msg) => write!(f, " {}", msg.message),
+        None => Ok(()),
+    }
+}
+
 /// Struct encapsulating an error message from the rcl layer or below.
 ///
-/// This struct is intended to be returned by the `source` method in the implementation of the
-/// standard [`Error`][1] trait for [`RclrsError`][2].
-/// By doing this, the error message is printed as a separate item in the error chain.
-/// This avoids an unreadable, inconsistent formatting of error codes and messages that would
-/// likely be produced by a combined display of `RclReturnCode` and message.
+/// This struct is returned by the `source` method in the implementation of the standard
+/// [`Error`][1] trait for [`RclrsError`][2], so that the error message is also available as a
+/// separate item in the error chain.
 ///
 /// [1]: std::error::Error
 /// [2]: crate::RclrsError
 #[derive(Debug, PartialEq, Eq)]
-pub struct RclErrorMsg(String);
+pub struct RclErrorMsg {
+    message: String,
+}
+
+impl RclErrorMsg {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    /// Returns the error string set by `rcl` or below, as returned by `rcl_get_error_string()`.
+    ///
+    /// This includes the source location in `rcl` where the error was set.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
 
 impl Display for RclErrorMsg {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.message)
     }
 }
 
@@ -80,6 +168,10 @@ impl Error for RclrsError {
             RclrsError::UnknownRclError { msg, .. } => msg.as_ref().map(|e| e as &dyn Error),
             RclrsError::StringContainsNul { err, .. } => Some(err).map(|e| e as &dyn Error),
             RclrsError::AlreadyAddedToWaitSet => None,
+            RclrsError::ParametersRejected { .. } => None,
+            RclrsError::InvalidName(_) => None,
+            RclrsError::IncompatibleClockTypes { .. } => None,
+            RclrsError::SpinTimeout => None,
         }
     }
 }
@@ -174,6 +266,25 @@ pub enum RclReturnCode {
     EventInvalid = 2000,
     /// Failed to take an event from the event handle
     EventTakeFailed = 2001,
+    // ====== 21XX: action client-specific errors ======
+    /// Action goal accepted
+    ActionGoalAccepted = 2100,
+    /// Action goal rejected
+    ActionGoalRejected = 2101,
+    /// Invalid `rcl_action_client_t` given
+    ActionClientInvalid = 2102,
+    /// Failed to take a response or message from the action client
+    ActionClientTakeFailed = 2103,
+    // ====== 22XX: action server-specific errors ======
+    /// Invalid `rcl_action_server_t` given
+    ActionServerInvalid = 2200,
+    /// Failed to take a request from the action server
+    ActionServerTakeFailed = 2201,
+    // ====== 23XX: action goal handle-specific errors ======
+    /// Invalid `rcl_action_goal_handle_t` given
+    ActionGoalHandleInvalid = 2300,
+    /// Invalid event for the current state of the goal
+    ActionGoalEventInvalid = 2301,
     // ====== 30XX: lifecycle-specific errors ======
     /// `rcl_lifecycle` state registered
     LifecycleStateRegistered = 3000,
@@ -222,6 +333,14 @@ impl TryFrom<i32> for RclReturnCode {
             x if x == Self::InvalidLogLevelRule as i32 => Self::InvalidLogLevelRule,
             x if x == Self::EventInvalid as i32 => Self::EventInvalid,
             x if x == Self::EventTakeFailed as i32 => Self::EventTakeFailed,
+            x if x == Self::ActionGoalAccepted as i32 => Self::ActionGoalAccepted,
+            x if x == Self::ActionGoalRejected as i32 => Self::ActionGoalRejected,
+            x if x == Self::ActionClientInvalid as i32 => Self::ActionClientInvalid,
+            x if x == Self::ActionClientTakeFailed as i32 => Self::ActionClientTakeFailed,
+            x if x == Self::ActionServerInvalid as i32 => Self::ActionServerInvalid,
+            x if x == Self::ActionServerTakeFailed as i32 => Self::ActionServerTakeFailed,
+            x if x == Self::ActionGoalHandleInvalid as i32 => Self::ActionGoalHandleInvalid,
+            x if x == Self::ActionGoalEventInvalid as i32 => Self::ActionGoalEventInvalid,
             x if x == Self::LifecycleStateRegistered as i32 => Self::LifecycleStateRegistered,
             x if x == Self::LifecycleStateNotRegistered as i32 => Self::LifecycleStateNotRegistered,
             other => {
@@ -303,6 +422,26 @@ impl Display for RclReturnCode {
             Self::EventTakeFailed => {
                 "Failed to take an event from the event handle (RCL_RET_EVENT_TAKE_FAILED)."
             }
+            Self::ActionGoalAccepted => "Action goal accepted (RCL_RET_ACTION_GOAL_ACCEPTED).",
+            Self::ActionGoalRejected => "Action goal rejected (RCL_RET_ACTION_GOAL_REJECTED).",
+            Self::ActionClientInvalid => {
+                "Invalid `rcl_action_client_t` given (RCL_RET_ACTION_CLIENT_INVALID)."
+            }
+            Self::ActionClientTakeFailed => {
+                "Failed to take a response or message from the action client (RCL_RET_ACTION_CLIENT_TAKE_FAILED)."
+            }
+            Self::ActionServerInvalid => {
+                "Invalid `rcl_action_server_t` given (RCL_RET_ACTION_SERVER_INVALID)."
+            }
+            Self::ActionServerTakeFailed => {
+                "Failed to take a request from the action server (RCL_RET_ACTION_SERVER_TAKE_FAILED)."
+            }
+            Self::ActionGoalHandleInvalid => {
+                "Invalid `rcl_action_goal_handle_t` given (RCL_RET_ACTION_GOAL_HANDLE_INVALID)."
+            }
+            Self::ActionGoalEventInvalid => {
+                "Invalid event for the current state of the goal (RCL_RET_ACTION_GOAL_EVENT_INVALID)."
+            }
             Self::LifecycleStateRegistered => {
                 "`rcl_lifecycle` state registered (RCL_RET_LIFECYCLE_STATE_REGISTERED)."
             }
@@ -316,39 +455,74 @@ impl Display for RclReturnCode {
 
 impl Error for RclReturnCode {}
 
-pub(crate) fn to_rclrs_result(ret: i32) -> Result<(), RclrsError> {
+// Converts a return code of rcl into a result, and records the name of the rcl function that
+// returned it in the error.
+fn to_rclrs_result_for(ret: i32, operation: &'static str) -> Result<(), RclrsError> {
     if ret == 0 {
         return Ok(());
     }
     let mut msg = None;
     // SAFETY: No preconditions for this function.
-    let error_state_ptr = unsafe { rcutils_get_error_state() };
-    // The returned pointer may be null if the error state has not been set.
-    if !error_state_ptr.is_null() {
-        // SAFETY: Dereferencing the pointer is safe since it was checked to be non-null.
-        let msg_ptr = unsafe { (*error_state_ptr).message.as_ptr() };
-        // SAFETY: Pointer has been created from an array, no lifetime issues due to
-        // immediate conversion to owned string.
-        let s = unsafe { CStr::from_ptr(msg_ptr) }
+    if unsafe { rcutils_error_is_set() } {
+        // SAFETY: No preconditions for this function. The returned string is formatted like
+        // "<message>, at <file>:<line>", which is what rcl_get_error_string() returns.
+        let error_string = unsafe { rcutils_get_error_string() };
+        // SAFETY: The string is nul-terminated, and it is immediately converted to an owned
+        // string.
+        let message = unsafe { CStr::from_ptr(error_string.str_.as_ptr()) }
             .to_string_lossy()
             .into_owned();
-        msg = Some(RclErrorMsg(s));
+        msg = Some(RclErrorMsg { message });
     }
     // SAFETY: No preconditions for this function.
     unsafe { rcutils_reset_error() };
+    let operation = Some(operation);
     // Finally, try to parse it into a return code.
     Err(match RclReturnCode::try_from(ret) {
-        Ok(code) => RclrsError::RclError { code, msg },
-        Err(code) => RclrsError::UnknownRclError { code, msg },
+        Ok(code) => RclrsError::RclError {
+            code,
+            operation,
+            msg,
+        },
+        Err(code) => RclrsError::UnknownRclError {
+            code,
+            operation,
+            msg,
+        },
     })
 }
 
 pub(crate) trait ToResult {
-    fn ok(&self) -> Result<(), RclrsError>;
+    /// Converts the return code into a `Result`, recording `operation`, the name of the `rcl`
+    /// function that returned the code, in the error.
+    fn ok_for(&self, operation: &'static str) -> Result<(), RclrsError>;
 }
 
 impl ToResult for rcl_ret_t {
-    fn ok(&self) -> Result<(), RclrsError> {
-        to_rclrs_result(*self)
+    fn ok_for(&self, operation: &'static str) -> Result<(), RclrsError> {
+        to_rclrs_result_for(*self, operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_is_recorded_without_error_string() {
+        // SAFETY: No preconditions for this function.
+        unsafe { rcutils_reset_error() };
+        let error = (RclReturnCode::Error as rcl_ret_t)
+            .ok_for("rcl_test_function")
+            .unwrap_err();
+        assert_eq!(
+            error,
+            RclrsError::RclError {
+                code: RclReturnCode::Error,
+                operation: Some("rcl_test_function"),
+                msg: None,
+            }
+        );
+        assert!(error.to_string().starts_with("rcl_test_function failed: "));
     }
 }
@@ -0,0 +1,371 @@
+use std::{
+    os::raw::c_void,
+    sync::{atomic::AtomicBool, Arc, Mutex, MutexGuard},
+};
+
+use crate::{
+    error::{RclReturnCode, ToResult},
+    rcl_bindings::*,
+    PublisherHandle, RclrsError, SubscriptionHandle, ENTITY_LIFECYCLE_MUTEX,
+};
+
+// SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
+// they are running in. Therefore, this type can be safely sent to another thread.
+unsafe impl Send for rcl_event_t {}
+
+/// The entity that an event belongs to.
+///
+/// The event holds a reference to it, since an `rcl_event_t` must not outlive its entity.
+pub(crate) enum EventParent {
+    Publisher(Arc<PublisherHandle>),
+    Subscription(Arc<SubscriptionHandle>),
+}
+
+/// Manage the lifecycle of an `rcl_event_t`, including managing its dependency
+/// on the publisher or subscription it belongs to by ensuring that this dependency
+/// is [dropped after][1] the `rcl_event_t`.
+///
+/// [1]: <https://doc.rust-lang.org/reference/destructors.html>
+pub struct EventHandle {
+    rcl_event: Mutex<rcl_event_t>,
+    #[allow(dead_code)]
+    parent: EventParent,
+    pub(crate) in_use_by_wait_set: Arc<AtomicBool>,
+}
+
+impl EventHandle {
+    pub(crate) fn lock(&self) -> MutexGuard<rcl_event_t> {
+        self.rcl_event.lock().unwrap()
+    }
+}
+
+impl Drop for EventHandle {
+    fn drop(&mut self) {
+        let rcl_event = self.rcl_event.get_mut().unwrap();
+        let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+        // SAFETY: The entity lifecycle mutex is locked to protect against the risk of
+        // global variables in the rmw implementation being unsafely modified during cleanup.
+        unsafe {
+            rcl_event_fini(rcl_event);
+        }
+    }
+}
+
+/// Trait to be implemented by concrete [`Event`]s.
+pub trait EventBase: Send + Sync {
+    /// Internal function to get a reference to the `rcl` handle.
+    fn handle(&self) -> &EventHandle;
+    /// Takes the event status and executes the callback.
+    fn execute(&self) -> Result<(), RclrsError>;
+}
+
+/// A status that is reported by an [`Event`].
+///
+/// This trait is implemented by the status types in this crate, and does not need to be
+/// implemented by user code.
+pub trait EventStatus: Send + 'static {
+    /// The corresponding `rmw` status struct.
+    type RmwStatus;
+
+    /// Converts the `rmw` status struct.
+    fn from_rmw_status(status: &Self::RmwStatus) -> Self;
+}
+
+/// The status of the message-lost event of a subscription.
+///
+/// See [`SubscriptionOptions::message_lost_callback()`][1].
+///
+/// [1]: crate::SubscriptionOptions::message_lost_callback
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageLostStatus {
+    /// The total number of messages that have been lost.
+    pub total_count: usize,
+    /// The number of messages that have been lost since the last time the callback was called.
+    pub total_count_change: usize,
+}
+
+impl EventStatus for MessageLostStatus {
+    type RmwStatus = rmw_message_lost_status_t;
+
+    fn from_rmw_status(status: &Self::RmwStatus) -> Self {
+        Self {
+            total_count: status.total_count,
+            total_count_change: status.total_count_change,
+        }
+    }
+}
+
+/// The status of the matched event of a publisher or subscription.
+///
+/// Matched events are only available since ROS 2 Iron.
+///
+/// See [`PublisherOptions::matched_callback()`][1] and
+/// [`SubscriptionOptions::matched_callback()`][2].
+///
+/// [1]: crate::PublisherOptions::matched_callback
+/// [2]: crate::SubscriptionOptions::matched_callback
+#[cfg(not(ros_distro = "humble"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchedStatus {
+    /// The total number of times an entity was matched.
+    pub total_count: usize,
+    /// The change of `total_count` since the last time the callback was called.
+    pub total_count_change: usize,
+    /// The number of entities that are currently matched.
+    pub current_count: usize,
+    /// The change of `current_count` since the last time the callback was called.
+    ///
+    /// This is negative when entities were unmatched.
+    pub current_count_change: i32,
+}
+
+#[cfg(not(ros_distro = "humble"))]
+impl EventStatus for MatchedStatus {
+    type RmwStatus = rmw_matched_status_t;
+
+    fn from_rmw_status(status: &Self::RmwStatus) -> Self {
+        Self {
+            total_count: status.total_count,
+            total_count_change: status.total_count_change,
+            current_count: status.current_count,
+            current_count_change: status.current_count_change,
+        }
+    }
+}
+
+/// The status of the offered-deadline-missed event of a publisher.
+///
+/// See [`PublisherOptions::offered_deadline_missed_callback()`][1].
+///
+/// [1]: crate::PublisherOptions::offered_deadline_missed_callback
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OfferedDeadlineMissedStatus {
+    /// The total number of deadlines the publisher has missed.
+    pub total_count: i32,
+    /// The number of deadlines missed since the last time the callback was called.
+    pub total_count_change: i32,
+}
+
+impl EventStatus for OfferedDeadlineMissedStatus {
+    type RmwStatus = rmw_offered_deadline_missed_status_t;
+
+    fn from_rmw_status(status: &Self::RmwStatus) -> Self {
+        Self {
+            total_count: status.total_count,
+            total_count_change: status.total_count_change,
+        }
+    }
+}
+
+/// The status of the requested-deadline-missed event of a subscription.
+///
+/// See [`SubscriptionOptions::requested_deadline_missed_callback()`][1].
+///
+/// [1]: crate::SubscriptionOptions::requested_deadline_missed_callback
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestedDeadlineMissedStatus {
+    /// The total number of deadlines the subscription has missed.
+    pub total_count: i32,
+    /// The number of deadlines missed since the last time the callback was called.
+    pub total_count_change: i32,
+}
+
+impl EventStatus for RequestedDeadlineMissedStatus {
+    type RmwStatus = rmw_requested_deadline_missed_status_t;
+
+    fn from_rmw_status(status: &Self::RmwStatus) -> Self {
+        Self {
+            total_count: status.total_count,
+            total_count_change: status.total_count_change,
+        }
+    }
+}
+
+/// The status of the liveliness-lost event of a publisher.
+///
+/// See [`PublisherOptions::liveliness_lost_callback()`][1].
+///
+/// [1]: crate::PublisherOptions::liveliness_lost_callback
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LivelinessLostStatus {
+    /// The total number of times the publisher failed to assert its liveliness within the
+    /// lease duration.
+    pub total_count: i32,
+    /// The change of `total_count` since the last time the callback was called.
+    pub total_count_change: i32,
+}
+
+impl EventStatus for LivelinessLostStatus {
+    type RmwStatus = rmw_liveliness_lost_status_t;
+
+    fn from_rmw_status(status: &Self::RmwStatus) -> Self {
+        Self {
+            total_count: status.total_count,
+            total_count_change: status.total_count_change,
+        }
+    }
+}
+
+/// The status of the liveliness-changed event of a subscription.
+///
+/// See [`SubscriptionOptions::liveliness_changed_callback()`][1].
+///
+/// [1]: crate::SubscriptionOptions::liveliness_changed_callback
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LivelinessChangedStatus {
+    /// The number of matched publishers that are currently alive.
+    pub alive_count: i32,
+    /// The number of matched publishers that stopped asserting their liveliness, but have not
+    /// been unmatched.
+    pub not_alive_count: i32,
+    /// The change of `alive_count` since the last time the callback was called.
+    pub alive_count_change: i32,
+    /// The change of `not_alive_count` since the last time the callback was called.
+    pub not_alive_count_change: i32,
+}
+
+impl EventStatus for LivelinessChangedStatus {
+    type RmwStatus = rmw_liveliness_changed_status_t;
+
+    fn from_rmw_status(status: &Self::RmwStatus) -> Self {
+        Self {
+            alive_count: status.alive_count,
+            not_alive_count: status.not_alive_count,
+            alive_count_change: status.alive_count_change,
+            not_alive_count_change: status.not_alive_count_change,
+        }
+    }
+}
+
+/// A callback for events of a publisher or subscription, such as lost messages.
+///
+/// Events are created together with their publisher or subscription, and their callbacks
+/// are executed when spinning the node.
+pub struct Event<S: EventStatus> {
+    pub(crate) handle: Arc<EventHandle>,
+    callback: Mutex<Box<dyn FnMut(S) + Send>>,
+}
+
+impl<S: EventStatus> Event<S> {
+    /// Creates an event for a publisher.
+    pub(crate) fn new_for_publisher(
+        publisher_handle: &Arc<PublisherHandle>,
+        event_type: rcl_publisher_event_type_t,
+        callback: Box<dyn FnMut(S) + Send>,
+    ) -> Result<Self, RclrsError> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut rcl_event = unsafe { rcl_get_zero_initialized_event() };
+        {
+            let rcl_publisher = publisher_handle.lock();
+            let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+            // SAFETY: The event is zero-initialized as expected by this function, and the
+            // publisher is kept alive by the event handle.
+            // The entity lifecycle mutex is locked to protect against the risk of global
+            // variables in the rmw implementation being unsafely modified during initialization.
+            unsafe {
+                rcl_publisher_event_init(&mut rcl_event, &*rcl_publisher, event_type)
+                    .ok_for("rcl_publisher_event_init")?;
+            }
+        }
+        Ok(Self::new(
+            rcl_event,
+            EventParent::Publisher(Arc::clone(publisher_handle)),
+            callback,
+        ))
+    }
+
+    /// Creates an event for a subscription.
+    pub(crate) fn new_for_subscription(
+        subscription_handle: &Arc<SubscriptionHandle>,
+        event_type: rcl_subscription_event_type_t,
+        callback: Box<dyn FnMut(S) + Send>,
+    ) -> Result<Self, RclrsError> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut rcl_event = unsafe { rcl_get_zero_initialized_event() };
+        {
+            let rcl_subscription = subscription_handle.lock();
+            let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+            // SAFETY: The event is zero-initialized as expected by this function, and the
+            // subscription is kept alive by the event handle.
+            // The entity lifecycle mutex is locked to protect against the risk of global
+            // variables in the rmw implementation being unsafely modified during initialization.
+            unsafe {
+                rcl_subscription_event_init(&mut rcl_event, &*rcl_subscription, event_type)
+                    .ok_for("rcl_subscription_event_init")?;
+            }
+        }
+        Ok(Self::new(
+            rcl_event,
+            EventParent::Subscription(Arc::clone(subscription_handle)),
+            callback,
+        ))
+    }
+
+    fn new(
+        rcl_event: rcl_event_t,
+        parent: EventParent,
+        callback: Box<dyn FnMut(S) + Send>,
+    ) -> Self {
+        Self {
+            handle: Arc::new(EventHandle {
+                rcl_event: Mutex::new(rcl_event),
+                parent,
+                in_use_by_wait_set: Arc::new(AtomicBool::new(false)),
+            }),
+            callback: Mutex::new(callback),
+        }
+    }
+
+    /// Takes the current status of the event.
+    ///
+    /// When the event has not occurred since the last time it was taken, this will return an
+    /// [`EventTakeFailed`][1].
+    ///
+    /// [1]: crate::RclReturnCode::EventTakeFailed
+    pub fn take(&self) -> Result<S, RclrsError> {
+        // SAFETY: The rmw status structs consist of plain integers, for which all-zero is valid.
+        let mut rmw_status: S::RmwStatus = unsafe { std::mem::zeroed() };
+        // SAFETY: The status struct matches the type of the event.
+        unsafe {
+            rcl_take_event(
+                &*self.handle.lock(),
+                &mut rmw_status as *mut S::RmwStatus as *mut c_void,
+            )
+            .ok_for("rcl_take_event")?;
+        }
+        Ok(S::from_rmw_status(&rmw_status))
+    }
+}
+
+impl<S: EventStatus> EventBase for Event<S> {
+    fn handle(&self) -> &EventHandle {
+        &self.handle
+    }
+
+    fn execute(&self) -> Result<(), RclrsError> {
+        match self.take() {
+            Ok(status) => {
+                (*self.callback.lock().unwrap())(status);
+                Ok(())
+            }
+            // Spurious wakeup
+            Err(RclrsError::RclError {
+                code: RclReturnCode::EventTakeFailed,
+                ..
+            }) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn traits() {
+        assert_send::<Event<MessageLostStatus>>();
+        assert_sync::<Event<MessageLostStatus>>();
+    }
+}
@@ -1,12 +1,300 @@
-use crate::{rcl_bindings::rcl_context_is_valid, Node, RclReturnCode, RclrsError, WaitSet};
+use crate::{
+    rcl_bindings::rcl_context_is_valid, CallbackGroup, CallbackGroupType, Context, ContextHandle,
+    GuardCondition, Node, RclReturnCode, RclrsError, ReadyEntities, WaitSet, Waitable,
+    WaitableNumEntities,
+};
+use futures::task::{waker, ArcWake};
 use std::{
-    sync::{Arc, Mutex, Weak},
-    time::Duration,
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc, Mutex, Weak,
+    },
+    task::{self, Poll},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
+// Whether the context of the node has not been shut down.
+fn is_live(node: &Node) -> bool {
+    unsafe { rcl_context_is_valid(&*node.handle.context_handle.rcl_context.lock().unwrap()) }
+}
+
+// Returns the nodes that are still alive and whose context has not been shut down.
+fn live_nodes(nodes_mtx: &Mutex<Vec<Weak<Node>>>) -> Vec<Arc<Node>> {
+    let mut nodes = Vec::new();
+    extend_live_nodes(nodes_mtx, &mut nodes);
+    nodes
+}
+
+// Like live_nodes(), but appends the nodes to `live`, which doesn't allocate if it has room.
+fn extend_live_nodes(nodes_mtx: &Mutex<Vec<Weak<Node>>>, live: &mut Vec<Arc<Node>>) {
+    live.extend(
+        { nodes_mtx.lock().unwrap() }
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|node| is_live(node)),
+    );
+}
+
+fn has_live_nodes(nodes_mtx: &Mutex<Vec<Weak<Node>>>) -> bool {
+    { nodes_mtx.lock().unwrap() }
+        .iter()
+        .filter_map(Weak::upgrade)
+        .any(|node| is_live(&node))
+}
+
+// Groups the nodes by their context, since a wait set can only contain entities of one context.
+fn nodes_by_context(nodes: Vec<Arc<Node>>) -> Vec<Vec<Arc<Node>>> {
+    let mut groups: Vec<Vec<Arc<Node>>> = Vec::new();
+    for node in nodes {
+        let context_handle = &node.handle.context_handle;
+        match groups
+            .iter_mut()
+            .find(|group| Arc::ptr_eq(&group[0].handle.context_handle, context_handle))
+        {
+            Some(group) => group.push(node),
+            None => groups.push(vec![node]),
+        }
+    }
+    groups
+}
+
+// Wakes up the wait set of the single-threaded executor when the future that it spins on is
+// woken up. It never has any work to do.
+struct WakeWaitable {
+    guard_condition: Arc<GuardCondition>,
+}
+
+impl Waitable for WakeWaitable {
+    fn guard_conditions(&self) -> Vec<Arc<GuardCondition>> {
+        vec![Arc::clone(&self.guard_condition)]
+    }
+
+    fn is_ready(&self) -> bool {
+        false
+    }
+
+    fn execute(&self) -> Result<(), RclrsError> {
+        Ok(())
+    }
+}
+
+// Wakes up the thread that spins an executor until a future is complete, and the wait set it is
+// waiting on, if there is one.
+struct FutureWaker {
+    thread: thread::Thread,
+    guard_condition: Option<Arc<GuardCondition>>,
+}
+
+impl ArcWake for FutureWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.thread.unpark();
+        if let Some(guard_condition) = &arc_self.guard_condition {
+            let _ = guard_condition.trigger();
+        }
+    }
+}
+
+// Requests a cancellation, and wakes up the wait set that is currently being waited on, if any.
+fn request_cancel(cancelled: &AtomicBool, wake_mtx: &Mutex<Option<Arc<GuardCondition>>>) {
+    // Setting the flag while holding the lock guarantees that a concurrent wait either sees the
+    // flag or gets woken up.
+    let wake_guard_condition = wake_mtx.lock().unwrap();
+    cancelled.store(true, Ordering::SeqCst);
+    if let Some(wake_guard_condition) = &*wake_guard_condition {
+        let _ = wake_guard_condition.trigger();
+    }
+}
+
+// The timeout of a wait, which must not block if the executor was cancelled.
+fn cancellable_timeout(cancelled: &AtomicBool, timeout: Option<Duration>) -> Option<Duration> {
+    if cancelled.load(Ordering::SeqCst) {
+        Some(Duration::ZERO)
+    } else {
+        timeout
+    }
+}
+
+// The number of ready entities and the duration of the most recent wait of an executor.
+type LastWait = Mutex<Option<(usize, Duration)>>;
+
+/// Runtime statistics of an executor.
+///
+/// The entity counts are taken over all live nodes of the executor at the time the statistics
+/// are requested. See [`SingleThreadedExecutor::statistics`] and
+/// [`MultiThreadedExecutor::statistics`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecutorStats {
+    /// The number of nodes that the executor spins.
+    pub nodes: usize,
+    /// The number of subscriptions of the nodes.
+    pub subscriptions: usize,
+    /// The number of timers of the nodes.
+    pub timers: usize,
+    /// The number of services of the nodes.
+    pub services: usize,
+    /// The number of clients of the nodes.
+    pub clients: usize,
+    /// The number of custom [`Waitable`]s added to the executor.
+    pub waitables: usize,
+    /// The number of entities that were ready after the most recent wait.
+    pub ready_on_last_wait: usize,
+    /// How long the most recent wait took, or `None` if the executor has not waited yet.
+    pub last_wait_duration: Option<Duration>,
+}
+
+fn executor_statistics(
+    nodes_mtx: &Mutex<Vec<Weak<Node>>>,
+    waitables_mtx: &Mutex<Vec<Arc<dyn Waitable>>>,
+    last_wait_mtx: &LastWait,
+) -> ExecutorStats {
+    let nodes = live_nodes(nodes_mtx);
+    let (ready_on_last_wait, last_wait_duration) = match *last_wait_mtx.lock().unwrap() {
+        Some((ready, duration)) => (ready, Some(duration)),
+        None => (0, None),
+    };
+    ExecutorStats {
+        nodes: nodes.len(),
+        subscriptions: nodes.iter().map(|n| n.live_subscriptions().len()).sum(),
+        timers: nodes.iter().map(|n| n.live_timers().len()).sum(),
+        services: nodes.iter().map(|n| n.live_services().len()).sum(),
+        clients: nodes.iter().map(|n| n.live_clients().len()).sum(),
+        waitables: waitables_mtx.lock().unwrap().len(),
+        ready_on_last_wait,
+        last_wait_duration,
+    }
+}
+
+// Stores the number of ready entities and the duration of a wait that started at `wait_start`.
+// A wait that timed out or failed counts as having no ready entities.
+fn record_wait(
+    last_wait_mtx: &LastWait,
+    wait_start: Instant,
+    wait_result: Result<&ReadyEntities, &RclrsError>,
+) {
+    let ready = wait_result.map_or(0, ready_work);
+    *last_wait_mtx.lock().unwrap() = Some((ready, wait_start.elapsed()));
+}
+
+// The number of ready entities that have a callback to execute. Guard conditions don't.
+fn ready_work(ready_entities: &ReadyEntities) -> usize {
+    ready_entities.subscriptions.len()
+        + ready_entities.clients.len()
+        + ready_entities.services.len()
+        + ready_entities.events.len()
+        + ready_entities.timers.len()
+        + ready_entities.action_clients.len()
+        + ready_entities.action_servers.len()
+        + ready_entities.waitables.len()
+}
+
+// Executes the ready work at `index`, which counts through the ready entities in the order of
+// ready_work(), i.e. subscriptions first and custom waitables last.
+fn execute_ready_work(ready_entities: &ReadyEntities, index: usize) -> Result<(), RclrsError> {
+    let mut index = index;
+    if let Some(subscription) = ready_entities.subscriptions.get(index) {
+        return subscription.execute();
+    }
+    index -= ready_entities.subscriptions.len();
+    if let Some(client) = ready_entities.clients.get(index) {
+        return client.execute();
+    }
+    index -= ready_entities.clients.len();
+    if let Some(service) = ready_entities.services.get(index) {
+        return service.execute();
+    }
+    index -= ready_entities.services.len();
+    if let Some(event) = ready_entities.events.get(index) {
+        return event.execute();
+    }
+    index -= ready_entities.events.len();
+    if let Some(timer) = ready_entities.timers.get(index) {
+        return timer.execute();
+    }
+    index -= ready_entities.timers.len();
+    if let Some(action_client) = ready_entities.action_clients.get(index) {
+        return action_client.execute();
+    }
+    index -= ready_entities.action_clients.len();
+    if let Some(action_server) = ready_entities.action_servers.get(index) {
+        return action_server.execute();
+    }
+    index -= ready_entities.action_servers.len();
+    ready_entities.waitables[index].execute()
+}
+
 /// Single-threaded executor implementation.
+///
+/// Several nodes can be added to the executor, which spins all of them on the calling thread.
+/// The entities of nodes in the same context are waited on together with a single wait set, so
+/// that work of any node is executed as soon as it is ready.
 pub struct SingleThreadedExecutor {
     nodes_mtx: Mutex<Vec<Weak<Node>>>,
+    waitables_mtx: Mutex<Vec<Arc<dyn Waitable>>>,
+    last_wait_mtx: LastWait,
+    // Whether cancel() was called and spin() has not returned because of it yet.
+    cancelled: AtomicBool,
+    // A guard condition that is part of the wait set currently being waited on, if any.
+    wake_mtx: Mutex<Option<Arc<GuardCondition>>>,
+    // The wait sets of the contexts of the nodes, which are reused by each spin so that spinning
+    // does not allocate in rcl.
+    wait_sets_mtx: Mutex<Vec<ContextWaitSet>>,
+    // Buffers that are reused by each spin, so that spinning does not allocate once they have
+    // grown to fit the entities of the nodes.
+    spin_buffers_mtx: Mutex<SpinBuffers>,
+    // Where to start executing the ready work. It advances with each execution, so that an
+    // entity that is always ready can't starve the others when only part of the work is executed.
+    work_offset: AtomicUsize,
+}
+
+// A wait set for the nodes of one context, together with a guard condition to wake it up.
+struct ContextWaitSet {
+    wait_set: WaitSet,
+    wake_guard_condition: Arc<GuardCondition>,
+}
+
+impl ContextWaitSet {
+    // The wait set is empty, it grows when it is filled with the entities of the nodes.
+    fn new(context_handle: &Arc<ContextHandle>) -> Result<Self, RclrsError> {
+        let context = Context {
+            handle: Arc::clone(context_handle),
+        };
+        Ok(Self {
+            wait_set: WaitSet::new(0, 0, 0, 0, 0, 0, &context)?,
+            wake_guard_condition: Arc::new(GuardCondition::new_with_context_handle(
+                Arc::clone(context_handle),
+                None,
+            )),
+        })
+    }
+
+    fn is_for(&self, context_handle: &Arc<ContextHandle>) -> bool {
+        Arc::ptr_eq(
+            &self.wake_guard_condition.handle.context_handle,
+            context_handle,
+        )
+    }
+}
+
+// The buffers of a spin. They are taken out of the executor while spinning, so that callbacks
+// can use the executor, and they are put back empty.
+#[derive(Default)]
+struct SpinBuffers {
+    nodes: Vec<Arc<Node>>,
+    waitables: Vec<Arc<dyn Waitable>>,
+    ready_entities: ReadyEntities,
+}
+
+impl SpinBuffers {
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.waitables.clear();
+        self.ready_entities.clear();
+    }
 }
 
 impl Default for SingleThreadedExecutor {
@@ -20,10 +308,20 @@ impl SingleThreadedExecutor {
     pub fn new() -> Self {
         SingleThreadedExecutor {
             nodes_mtx: Mutex::new(Vec::new()),
+            waitables_mtx: Mutex::new(Vec::new()),
+            last_wait_mtx: Mutex::new(None),
+            cancelled: AtomicBool::new(false),
+            wake_mtx: Mutex::new(None),
+            wait_sets_mtx: Mutex::new(Vec::new()),
+            spin_buffers_mtx: Mutex::new(SpinBuffers::default()),
+            work_offset: AtomicUsize::new(0),
         }
     }
 
     /// Add a node to the executor.
+    ///
+    /// The executor only keeps a weak reference to the node, so a node that is dropped is
+    /// removed from the executor together with its entities.
     pub fn add_node(&self, node: &Arc<Node>) -> Result<(), RclrsError> {
         { self.nodes_mtx.lock().unwrap() }.push(Arc::downgrade(node));
         Ok(())
@@ -36,49 +334,1447 @@ impl SingleThreadedExecutor {
         Ok(())
     }
 
-    /// Polls the nodes for new messages and executes the corresponding callbacks.
+    /// Adds a custom [`Waitable`] to the executor.
+    ///
+    /// The waitable is waited on together with the nodes of the executor, so it is only
+    /// executed while the executor has at least one node.
+    pub fn add_waitable(&self, waitable: Arc<dyn Waitable>) -> Result<(), RclrsError> {
+        { self.waitables_mtx.lock().unwrap() }.push(waitable);
+        Ok(())
+    }
+
+    /// Removes a custom [`Waitable`] from the executor.
+    pub fn remove_waitable(&self, waitable: &Arc<dyn Waitable>) -> Result<(), RclrsError> {
+        { self.waitables_mtx.lock().unwrap() }.retain(|w| !Arc::ptr_eq(w, waitable));
+        Ok(())
+    }
+
+    /// Makes [`SingleThreadedExecutor::spin`] return, e.g. from another thread.
+    ///
+    /// If the executor is currently waiting for work, it is woken up. A callback that is being
+    /// executed is finished first, and messages that have not been taken yet stay in the
+    /// middleware, so they are processed when spinning again. The executor can be spun again
+    /// after `spin()` returned.
+    ///
+    /// If the executor is not spinning, the next call to `spin()` returns after executing the
+    /// work that is ready. Until then, [`SingleThreadedExecutor::spin_once`] does not wait.
+    pub fn cancel(&self) {
+        request_cancel(&self.cancelled, &self.wake_mtx);
+    }
+
+    /// Returns the number of entities spun by this executor and the outcome of its most
+    /// recent wait.
+    ///
+    /// This is meant for inspecting a running executor, e.g. when tuning a busy node, and does
+    /// not influence its behavior.
+    pub fn statistics(&self) -> ExecutorStats {
+        executor_statistics(&self.nodes_mtx, &self.waitables_mtx, &self.last_wait_mtx)
+    }
+
+    /// Polls the nodes for new messages and executes at most one of the corresponding callbacks.
+    ///
+    /// If no callback is ready, this waits for at most `timeout`, see [`WaitSet::wait`]. If
+    /// several callbacks are ready, successive calls take turns between them, so that e.g. a busy
+    /// subscription does not prevent timers from firing.
     ///
     /// This function additionally checks that the context is still valid.
     pub fn spin_once(&self, timeout: Option<Duration>) -> Result<(), RclrsError> {
-        for node in { self.nodes_mtx.lock().unwrap() }
-            .iter()
-            .filter_map(Weak::upgrade)
-            .filter(|node| unsafe {
-                rcl_context_is_valid(&*node.handle.context_handle.rcl_context.lock().unwrap())
+        self.wait_and_execute(timeout, 1).map(|_| ())
+    }
+
+    /// Executes the callbacks that are ready, without waiting for new work.
+    ///
+    /// Work that becomes ready while executing callbacks, e.g. further messages arriving on a
+    /// subscription, is also executed. This function returns once there is no more ready work,
+    /// or once `max_duration` has elapsed.
+    ///
+    /// This function additionally checks that the context is still valid.
+    pub fn spin_some(&self, max_duration: Duration) -> Result<(), RclrsError> {
+        let start = Instant::now();
+        while start.elapsed() < max_duration {
+            match self.wait_and_execute(Some(Duration::ZERO), usize::MAX) {
+                Ok(0)
+                | Err(RclrsError::RclError {
+                    code: RclReturnCode::Timeout,
+                    ..
+                }) => break,
+                Ok(_) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Executes callbacks as work arrives, until `duration` has elapsed.
+    ///
+    /// Unlike [`SingleThreadedExecutor::spin_some`], this does not return when there is no ready
+    /// work, but keeps waiting for new work until the deadline, which is useful e.g. for
+    /// short-lived programs and tests. A callback that is running at the deadline is finished
+    /// first, so this may return slightly later.
+    ///
+    /// This also returns early once the executor was cancelled with
+    /// [`SingleThreadedExecutor::cancel`]. Without any nodes, this just sleeps for `duration`.
+    ///
+    /// This function additionally checks that the context is still valid.
+    pub fn spin_for(&self, duration: Duration) -> Result<(), RclrsError> {
+        let deadline = Instant::now() + duration;
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            if !has_live_nodes(&self.nodes_mtx) {
+                thread::sleep(deadline - now);
+                break;
+            }
+            match self.spin_once(Some(deadline - now)) {
+                Ok(())
+                | Err(RclrsError::RclError {
+                    code: RclReturnCode::Timeout,
+                    ..
+                }) => {}
+                Err(err) => return Err(err),
+            }
+            if self.cancelled.swap(false, Ordering::SeqCst) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Waits for each node and executes up to `max_executions` ready callbacks in total.
+    // Returns the number of executed callbacks.
+    fn wait_and_execute(
+        &self,
+        timeout: Option<Duration>,
+        max_executions: usize,
+    ) -> Result<usize, RclrsError> {
+        // A callback that spins this executor again gets new buffers, which is fine since it
+        // should not do that anyway.
+        let mut buffers = std::mem::take(&mut *self.spin_buffers_mtx.lock().unwrap());
+        let result = self.wait_and_execute_with(&mut buffers, timeout, max_executions);
+        buffers.clear();
+        *self.spin_buffers_mtx.lock().unwrap() = buffers;
+        result
+    }
+
+    fn wait_and_execute_with(
+        &self,
+        buffers: &mut SpinBuffers,
+        timeout: Option<Duration>,
+        max_executions: usize,
+    ) -> Result<usize, RclrsError> {
+        let mut executions = 0;
+        extend_live_nodes(&self.nodes_mtx, &mut buffers.nodes);
+        let nodes = &buffers.nodes;
+        // Forget the wait sets of contexts without nodes, since they keep the context alive.
+        self.wait_sets_mtx.lock().unwrap().retain(|cached| {
+            nodes
+                .iter()
+                .any(|node| cached.is_for(&node.handle.context_handle))
+        });
+        for (i, node) in nodes.iter().enumerate() {
+            if executions >= max_executions {
+                break;
+            }
+            let context_handle = &node.handle.context_handle;
+            // Wait for each context once, together with all nodes of the context.
+            if nodes[..i]
+                .iter()
+                .any(|other| Arc::ptr_eq(&other.handle.context_handle, context_handle))
+            {
+                continue;
+            }
+            let context_nodes = nodes[i..]
+                .iter()
+                .filter(|other| Arc::ptr_eq(&other.handle.context_handle, context_handle))
+                .map(|other| &**other);
+            let ready_entities = &mut buffers.ready_entities;
+            {
+                let mut wait_sets = self.wait_sets_mtx.lock().unwrap();
+                let index = match wait_sets
+                    .iter()
+                    .position(|cached| cached.is_for(context_handle))
+                {
+                    Some(index) => index,
+                    None => {
+                        wait_sets.push(ContextWaitSet::new(context_handle)?);
+                        wait_sets.len() - 1
+                    }
+                };
+                let ContextWaitSet {
+                    wait_set,
+                    wake_guard_condition,
+                } = &mut wait_sets[index];
+                buffers.waitables.clear();
+                buffers.waitables.extend(
+                    { self.waitables_mtx.lock().unwrap() }
+                        .iter()
+                        .map(Arc::clone),
+                );
+                wait_set.refill_for_nodes_and_waitables(
+                    context_nodes,
+                    std::slice::from_ref(wake_guard_condition),
+                    &buffers.waitables,
+                )?;
+                // The guard condition must be published before checking for a cancellation, so
+                // that a cancellation in between always wakes up the wait set.
+                *self.wake_mtx.lock().unwrap() = Some(Arc::clone(wake_guard_condition));
+                let wait_start = Instant::now();
+                let wait_result = wait_set.wait_into(
+                    cancellable_timeout(&self.cancelled, timeout),
+                    ready_entities,
+                );
+                *self.wake_mtx.lock().unwrap() = None;
+                // Make the entities available to other wait sets until the next spin.
+                wait_set.clear();
+                record_wait(
+                    &self.last_wait_mtx,
+                    wait_start,
+                    wait_result.as_ref().map(|_| &*ready_entities),
+                );
+                wait_result?;
+            }
+
+            let ready = ready_work(ready_entities);
+            let offset = match ready {
+                0 => 0,
+                _ => self.work_offset.load(Ordering::Relaxed) % ready,
+            };
+            for n in 0..ready.min(max_executions - executions) {
+                self.work_offset.fetch_add(1, Ordering::Relaxed);
+                execute_ready_work(ready_entities, (offset + n) % ready)?;
+                executions += 1;
+            }
+        }
+
+        Ok(executions)
+    }
+
+    /// Convenience function for calling [`SingleThreadedExecutor::spin_once`] in a loop.
+    ///
+    /// Returns once all nodes have been dropped or removed, or their contexts have been shut
+    /// down, e.g. by [`Context::shutdown()`], or once the executor was cancelled with
+    /// [`SingleThreadedExecutor::cancel`].
+    pub fn spin(&self) -> Result<(), RclrsError> {
+        while has_live_nodes(&self.nodes_mtx) {
+            match self.spin_once(None) {
+                Ok(_)
+                | Err(RclrsError::RclError {
+                    code: RclReturnCode::Timeout,
+                    ..
+                }) => std::thread::yield_now(),
+                // The context may have been shut down while preparing the wait set
+                Err(_) if !has_live_nodes(&self.nodes_mtx) => break,
+                error => return error,
+            }
+            if self.cancelled.swap(false, Ordering::SeqCst) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Spins the executor until `future` is complete, and returns its output.
+    ///
+    /// This is useful e.g. for waiting for the response of [`Client::call_async()`][1] in a
+    /// `main` function. The future is polled whenever it has been woken up, which also wakes up
+    /// the executor if it is waiting for work, and callbacks are executed in between.
+    ///
+    /// If the future is not complete within `timeout`, a [`SpinTimeout`][2] error is returned
+    /// and the future is dropped. Passing `None` waits indefinitely.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::*;
+    /// # use std::{sync::Arc, time::Duration};
+    /// # let context = Context::new([])?;
+    /// # let node = create_node(&context, "future_node")?;
+    /// let executor = SingleThreadedExecutor::new();
+    /// executor.add_node(&node)?;
+    /// let output = executor.spin_until_future_complete(async { 42 }, Some(Duration::from_secs(1)))?;
+    /// assert_eq!(output, 42);
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    ///
+    /// [1]: crate::Client::call_async
+    /// [2]: crate::RclrsError::SpinTimeout
+    pub fn spin_until_future_complete<F: Future>(
+        &self,
+        future: F,
+        timeout: Option<Duration>,
+    ) -> Result<F::Output, RclrsError> {
+        let guard_condition = live_nodes(&self.nodes_mtx).first().map(|node| {
+            Arc::new(GuardCondition::new_with_context_handle(
+                Arc::clone(&node.handle.context_handle),
+                None,
+            ))
+        });
+        let wake_waitable = guard_condition.as_ref().map(|guard_condition| {
+            Arc::new(WakeWaitable {
+                guard_condition: Arc::clone(guard_condition),
+            }) as Arc<dyn Waitable>
+        });
+        if let Some(wake_waitable) = &wake_waitable {
+            self.add_waitable(Arc::clone(wake_waitable))?;
+        }
+        let waker = waker(Arc::new(FutureWaker {
+            thread: thread::current(),
+            guard_condition,
+        }));
+        let result = self.poll_until_complete(future, &waker, timeout);
+        if let Some(wake_waitable) = &wake_waitable {
+            self.remove_waitable(wake_waitable)?;
+        }
+        result
+    }
+
+    // Polls the future whenever it is woken up, and spins the executor in between.
+    fn poll_until_complete<F: Future>(
+        &self,
+        future: F,
+        waker: &task::Waker,
+        timeout: Option<Duration>,
+    ) -> Result<F::Output, RclrsError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut future = Box::pin(future);
+        let mut task_context = task::Context::from_waker(waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut task_context) {
+                return Ok(output);
+            }
+            let remaining = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(RclrsError::SpinTimeout);
+                    }
+                    Some(deadline - now)
+                }
+                None => None,
+            };
+            // Without nodes, only another thread can complete the future.
+            if !has_live_nodes(&self.nodes_mtx) {
+                match remaining {
+                    Some(remaining) => thread::park_timeout(remaining),
+                    None => thread::park(),
+                }
+                continue;
+            }
+            match self.spin_once(remaining) {
+                Ok(())
+                | Err(RclrsError::RclError {
+                    code: RclReturnCode::Timeout,
+                    ..
+                }) => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// Releases a callback group when a callback of the group has finished, including by panicking,
+// and wakes up the wait set that is currently being waited on, if any.
+struct ReleaseGroupOnDrop {
+    group: Arc<CallbackGroup>,
+    wake_mtx: Arc<Mutex<Option<Arc<GuardCondition>>>>,
+}
+
+impl Drop for ReleaseGroupOnDrop {
+    fn drop(&mut self) {
+        // Releasing the group while holding the lock guarantees that a concurrent spin_once()
+        // either sees the group as available or gets woken up.
+        let wake_guard_condition = self.wake_mtx.lock().unwrap();
+        self.group.release();
+        if let Some(wake_guard_condition) = &*wake_guard_condition {
+            let _ = wake_guard_condition.trigger();
+        }
+    }
+}
+
+// The callback groups of the entities in a wait set of the multi-threaded executor, by the
+// address of the entity. The wait set contains the entities of several nodes, whose default
+// groups differ.
+#[derive(Default)]
+struct EntityGroups(HashMap<*const (), Arc<CallbackGroup>>);
+
+impl EntityGroups {
+    // Returns the entities whose callback group is currently available, and records their
+    // groups. Entities without a group of their own belong to `default_group`.
+    fn available<T: ?Sized>(
+        &mut self,
+        entities: Vec<Arc<T>>,
+        group_of: impl Fn(&T) -> Option<&Arc<CallbackGroup>>,
+        default_group: &Arc<CallbackGroup>,
+    ) -> Vec<Arc<T>> {
+        entities
+            .into_iter()
+            .filter(|entity| {
+                let group = group_of(&**entity).unwrap_or(default_group);
+                if group.is_busy() {
+                    return false;
+                }
+                self.0
+                    .insert(Arc::as_ptr(entity) as *const (), Arc::clone(group));
+                true
             })
-        {
-            let wait_set = WaitSet::new_for_node(&node)?;
-            let ready_entities = wait_set.wait(timeout)?;
+            .collect()
+    }
+
+    // Returns the group of an entity that was returned by `available()`.
+    fn get<T: ?Sized>(&self, entity: &Arc<T>) -> Arc<CallbackGroup> {
+        Arc::clone(&self.0[&(Arc::as_ptr(entity) as *const ())])
+    }
+}
+
+/// Multi-threaded executor implementation.
+///
+/// Several nodes can be added to the executor. The entities of nodes in the same context are
+/// waited on together with a single wait set, so that work of any node is picked up as soon as it
+/// is ready.
+///
+/// Callbacks are executed by a pool of worker threads. Which callbacks may run in parallel is
+/// controlled by their [`CallbackGroup`]: callbacks of a mutually exclusive group are never
+/// executed at the same time, while callbacks of a reentrant group may be.
+///
+/// Entities that were not assigned to a group, as well as events, action clients and action
+/// servers, belong to the default group of their node, which is mutually exclusive.
+pub struct MultiThreadedExecutor {
+    nodes_mtx: Mutex<Vec<Weak<Node>>>,
+    waitables_mtx: Mutex<Vec<Arc<dyn Waitable>>>,
+    // The group of the custom waitables, which are never executed in parallel.
+    waitables_callback_group: Arc<CallbackGroup>,
+    job_sender: Option<Mutex<mpsc::Sender<Job>>>,
+    workers: Vec<JoinHandle<()>>,
+    // Errors returned by callbacks executed on the worker threads.
+    errors: Arc<Mutex<Vec<RclrsError>>>,
+    // The payloads of panics in callbacks executed on the worker threads, which are resumed on
+    // the spinning thread.
+    panics: Arc<Mutex<Vec<Box<dyn Any + Send>>>>,
+    // A guard condition that is part of the wait set currently being waited on, if any.
+    // It is triggered whenever a callback group becomes available again.
+    wake_mtx: Arc<Mutex<Option<Arc<GuardCondition>>>>,
+    last_wait_mtx: LastWait,
+    // Whether cancel() was called and spin() has not returned because of it yet.
+    cancelled: AtomicBool,
+}
+
+impl Default for MultiThreadedExecutor {
+    fn default() -> Self {
+        let number_of_threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2);
+        Self::new(number_of_threads)
+    }
+}
+
+impl Drop for MultiThreadedExecutor {
+    fn drop(&mut self) {
+        // Dropping the sender makes the workers exit once all pending jobs are done.
+        self.job_sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl MultiThreadedExecutor {
+    /// Creates a new executor with the given number of worker threads.
+    ///
+    /// At least one worker thread is always created.
+    pub fn new(number_of_threads: usize) -> Self {
+        let (job_sender, job_receiver) = mpsc::channel::<Job>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let panics = Arc::new(Mutex::new(Vec::new()));
+        let workers = (0..number_of_threads.max(1))
+            .map(|_| {
+                let job_receiver = Arc::clone(&job_receiver);
+                let panics = Arc::clone(&panics);
+                thread::spawn(move || loop {
+                    // The lock is released before the job is run.
+                    let job = match job_receiver.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => break,
+                    };
+                    // A panicking callback must not take down the worker thread.
+                    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(job)) {
+                        panics.lock().unwrap().push(payload);
+                    }
+                })
+            })
+            .collect();
+        MultiThreadedExecutor {
+            nodes_mtx: Mutex::new(Vec::new()),
+            waitables_mtx: Mutex::new(Vec::new()),
+            waitables_callback_group: Arc::new(CallbackGroup::new(
+                CallbackGroupType::MutuallyExclusive,
+            )),
+            job_sender: Some(Mutex::new(job_sender)),
+            workers,
+            errors: Arc::new(Mutex::new(Vec::new())),
+            panics,
+            wake_mtx: Arc::new(Mutex::new(None)),
+            last_wait_mtx: Mutex::new(None),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    /// Add a node to the executor.
+    ///
+    /// The executor only keeps a weak reference to the node, so a node that is dropped is
+    /// removed from the executor together with its entities.
+    pub fn add_node(&self, node: &Arc<Node>) -> Result<(), RclrsError> {
+        { self.nodes_mtx.lock().unwrap() }.push(Arc::downgrade(node));
+        Ok(())
+    }
+
+    /// Remove a node from the executor.
+    pub fn remove_node(&self, node: Arc<Node>) -> Result<(), RclrsError> {
+        { self.nodes_mtx.lock().unwrap() }
+            .retain(|n| !n.upgrade().map(|n| Arc::ptr_eq(&n, &node)).unwrap_or(false));
+        Ok(())
+    }
+
+    /// Adds a custom [`Waitable`] to the executor.
+    ///
+    /// The waitable is waited on together with the nodes of the executor, so it is only
+    /// executed while the executor has at least one node.
+    pub fn add_waitable(&self, waitable: Arc<dyn Waitable>) -> Result<(), RclrsError> {
+        { self.waitables_mtx.lock().unwrap() }.push(waitable);
+        Ok(())
+    }
+
+    /// Removes a custom [`Waitable`] from the executor.
+    pub fn remove_waitable(&self, waitable: &Arc<dyn Waitable>) -> Result<(), RclrsError> {
+        { self.waitables_mtx.lock().unwrap() }.retain(|w| !Arc::ptr_eq(w, waitable));
+        Ok(())
+    }
+
+    /// Makes [`MultiThreadedExecutor::spin`] return, e.g. from another thread.
+    ///
+    /// If the executor is currently waiting for work, it is woken up. Callbacks that were
+    /// already handed to the worker threads are still executed, and messages that have not been
+    /// taken yet stay in the middleware, so they are processed when spinning again. The executor
+    /// can be spun again after `spin()` returned.
+    ///
+    /// If the executor is not spinning, the next call to `spin()` returns after handing the work
+    /// that is ready to the worker threads. Until then, [`MultiThreadedExecutor::spin_once`]
+    /// does not wait.
+    pub fn cancel(&self) {
+        request_cancel(&self.cancelled, &self.wake_mtx);
+    }
+
+    /// Returns the number of entities spun by this executor and the outcome of its most
+    /// recent wait.
+    ///
+    /// This is meant for inspecting a running executor, e.g. when tuning a busy node, and does
+    /// not influence its behavior.
+    pub fn statistics(&self) -> ExecutorStats {
+        executor_statistics(&self.nodes_mtx, &self.waitables_mtx, &self.last_wait_mtx)
+    }
+
+    /// Polls the nodes for new messages and hands the corresponding callbacks to the worker
+    /// threads.
+    ///
+    /// This function does not wait for the callbacks to finish. Entities whose callback group
+    /// is busy are not polled; they are picked up by a later call once the group is available.
+    ///
+    /// If a callback executed since the last call returned an error, that error is returned. If
+    /// a callback panicked, the panic is resumed on the calling thread.
+    ///
+    /// This function additionally checks that the context is still valid.
+    pub fn spin_once(&self, timeout: Option<Duration>) -> Result<(), RclrsError> {
+        if let Some(payload) = { self.panics.lock().unwrap() }.pop() {
+            panic::resume_unwind(payload);
+        }
+        if let Some(error) = { self.errors.lock().unwrap() }.pop() {
+            return Err(error);
+        }
+
+        for nodes in nodes_by_context(live_nodes(&self.nodes_mtx)) {
+            let wake_guard_condition = Arc::new(GuardCondition::new_with_context_handle(
+                Arc::clone(&nodes[0].handle.context_handle),
+                None,
+            ));
+            // The guard condition must be published before checking which groups are busy, so
+            // that a group becoming available in between always wakes up the wait set.
+            *self.wake_mtx.lock().unwrap() = Some(Arc::clone(&wake_guard_condition));
+            let mut groups = EntityGroups::default();
+            let wait_start = Instant::now();
+            let wait_result = self.wait_for_nodes(
+                &nodes,
+                wake_guard_condition,
+                cancellable_timeout(&self.cancelled, timeout),
+                &mut groups,
+            );
+            *self.wake_mtx.lock().unwrap() = None;
+            record_wait(&self.last_wait_mtx, wait_start, wait_result.as_ref());
+            let ready_entities = wait_result?;
 
             for ready_subscription in ready_entities.subscriptions {
-                ready_subscription.execute()?;
+                let group = groups.get(&ready_subscription);
+                self.dispatch(&group, move || ready_subscription.execute())?;
             }
 
             for ready_client in ready_entities.clients {
-                ready_client.execute()?;
+                let group = groups.get(&ready_client);
+                self.dispatch(&group, move || ready_client.execute())?;
             }
 
             for ready_service in ready_entities.services {
-                ready_service.execute()?;
+                let group = groups.get(&ready_service);
+                self.dispatch(&group, move || ready_service.execute())?;
+            }
+
+            for ready_event in ready_entities.events {
+                let group = groups.get(&ready_event);
+                self.dispatch(&group, move || ready_event.execute())?;
+            }
+
+            for ready_timer in ready_entities.timers {
+                let group = groups.get(&ready_timer);
+                self.dispatch(&group, move || ready_timer.execute())?;
+            }
+
+            for ready_action_client in ready_entities.action_clients {
+                let group = groups.get(&ready_action_client);
+                self.dispatch(&group, move || ready_action_client.execute())?;
+            }
+
+            for ready_action_server in ready_entities.action_servers {
+                let group = groups.get(&ready_action_server);
+                self.dispatch(&group, move || ready_action_server.execute())?;
+            }
+
+            for ready_waitable in ready_entities.waitables {
+                self.dispatch(&self.waitables_callback_group, move || {
+                    ready_waitable.execute()
+                })?;
             }
         }
 
         Ok(())
     }
 
-    /// Convenience function for calling [`SingleThreadedExecutor::spin_once`] in a loop.
+    /// Convenience function for calling [`MultiThreadedExecutor::spin_once`] in a loop.
+    ///
+    /// Returns once all nodes have been dropped or removed, or their contexts have been shut
+    /// down, e.g. by [`Context::shutdown()`], or once the executor was cancelled with
+    /// [`MultiThreadedExecutor::cancel`].
     pub fn spin(&self) -> Result<(), RclrsError> {
-        while !{ self.nodes_mtx.lock().unwrap() }.is_empty() {
+        while has_live_nodes(&self.nodes_mtx) {
             match self.spin_once(None) {
                 Ok(_)
                 | Err(RclrsError::RclError {
                     code: RclReturnCode::Timeout,
                     ..
                 }) => std::thread::yield_now(),
+                // The context may have been shut down while preparing the wait set
+                Err(_) if !has_live_nodes(&self.nodes_mtx) => break,
                 error => return error,
             }
+            if self.cancelled.swap(false, Ordering::SeqCst) {
+                break;
+            }
         }
 
         Ok(())
     }
+
+    // Waits on all entities of the nodes whose callback group is currently available, and
+    // records the group of each entity in `groups`. All nodes must belong to the same context.
+    fn wait_for_nodes(
+        &self,
+        nodes: &[Arc<Node>],
+        wake_guard_condition: Arc<GuardCondition>,
+        timeout: Option<Duration>,
+        groups: &mut EntityGroups,
+    ) -> Result<ReadyEntities, RclrsError> {
+        let mut live_subscriptions = Vec::new();
+        let mut live_services = Vec::new();
+        let mut live_clients = Vec::new();
+        let mut live_events = Vec::new();
+        let mut live_timers = Vec::new();
+        let mut live_action_clients = Vec::new();
+        let mut live_action_servers = Vec::new();
+        let mut live_guard_conditions = Vec::new();
+        for node in nodes {
+            let default_group = &node.default_callback_group;
+            live_subscriptions.extend(groups.available(
+                node.live_subscriptions(),
+                |subscription| subscription.callback_group(),
+                default_group,
+            ));
+            live_services.extend(groups.available(
+                node.live_services(),
+                |service| service.callback_group(),
+                default_group,
+            ));
+            live_clients.extend(groups.available(
+                node.live_clients(),
+                |client| client.callback_group(),
+                default_group,
+            ));
+            live_events.extend(groups.available(node.live_events(), |_| None, default_group));
+            live_timers.extend(groups.available(
+                node.live_timers(),
+                |timer| timer.callback_group(),
+                default_group,
+            ));
+            live_action_clients.extend(groups.available(
+                node.live_action_clients(),
+                |_| None,
+                default_group,
+            ));
+            live_action_servers.extend(groups.available(
+                node.live_action_servers(),
+                |_| None,
+                default_group,
+            ));
+            live_guard_conditions.extend(node.live_guard_conditions());
+        }
+        let action_entities = live_action_clients
+            .iter()
+            .map(|action_client| action_client.handle().num_entities)
+            .chain(
+                live_action_servers
+                    .iter()
+                    .map(|action_server| action_server.handle().num_entities),
+            )
+            .fold(WaitableNumEntities::default(), |sum, n| sum + n);
+        let waitables = if self.waitables_callback_group.is_busy() {
+            Vec::new()
+        } else {
+            { self.waitables_mtx.lock().unwrap() }.clone()
+        };
+        let intra_process_guard_conditions = live_subscriptions
+            .iter()
+            .filter(|subscription| subscription.intra_process_guard_condition().is_some())
+            .count();
+        let waitable_guard_conditions: usize = waitables
+            .iter()
+            .map(|waitable| waitable.guard_conditions().len())
+            .sum();
+        let ctx = Context {
+            handle: Arc::clone(&nodes[0].handle.context_handle),
+        };
+        let mut wait_set = WaitSet::new(
+            live_subscriptions.len() + action_entities.subscriptions,
+            live_guard_conditions.len()
+                + 1
+                + intra_process_guard_conditions
+                + action_entities.guard_conditions
+                + waitable_guard_conditions,
+            live_timers.len() + action_entities.timers,
+            live_clients.len() + action_entities.clients,
+            live_services.len() + action_entities.services,
+            live_events.len(),
+            &ctx,
+        )?;
+
+        for live_subscription in live_subscriptions {
+            wait_set.add_subscription(live_subscription)?;
+        }
+
+        for live_client in live_clients {
+            wait_set.add_client(live_client)?;
+        }
+
+        for live_guard_condition in live_guard_conditions {
+            wait_set.add_guard_condition(live_guard_condition)?;
+        }
+        wait_set.add_guard_condition(wake_guard_condition)?;
+
+        for live_service in live_services {
+            wait_set.add_service(live_service)?;
+        }
+
+        for live_event in live_events {
+            wait_set.add_event(live_event)?;
+        }
+
+        for live_timer in live_timers {
+            wait_set.add_timer(live_timer)?;
+        }
+
+        for live_action_client in live_action_clients {
+            wait_set.add_action_client(live_action_client)?;
+        }
+
+        for live_action_server in live_action_servers {
+            wait_set.add_action_server(live_action_server)?;
+        }
+
+        for waitable in waitables {
+            wait_set.add_waitable(waitable)?;
+        }
+        wait_set.wait(timeout)
+    }
+
+    // Hands the callback to a worker thread, unless its group is busy. In that case, the
+    // entity is still ready and will be picked up again after the group becomes available.
+    fn dispatch<F>(&self, group: &Arc<CallbackGroup>, execute: F) -> Result<(), RclrsError>
+    where
+        F: FnOnce() -> Result<(), RclrsError> + Send + 'static,
+    {
+        if !group.try_acquire() {
+            return Ok(());
+        }
+        let release = ReleaseGroupOnDrop {
+            group: Arc::clone(group),
+            wake_mtx: Arc::clone(&self.wake_mtx),
+        };
+        let errors = Arc::clone(&self.errors);
+        let job: Job = Box::new(move || {
+            // The group is released after the error was stored, even if the callback panics.
+            let _release = release;
+            if let Err(error) = execute() {
+                errors.lock().unwrap().push(error);
+            }
+        });
+        // The sender is only taken when dropping the executor, and the workers only exit
+        // after that, so sending cannot fail.
+        let job_sender = self.job_sender.as_ref().unwrap().lock().unwrap();
+        job_sender
+            .send(job)
+            .expect("The worker threads of the MultiThreadedExecutor have stopped");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{test_helpers::*, Subscription, SubscriptionOptions, QOS_PROFILE_DEFAULT};
+    use test_msgs::msg;
+
+    #[test]
+    fn spin_some_executes_all_ready_messages() -> Result<(), RclrsError> {
+        const N: usize = 5;
+        let topic = "test_spin_some";
+        let graph = construct_test_graph(topic)?;
+        let received = Arc::new(AtomicUsize::new(0));
+        let received_in_callback = Arc::clone(&received);
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            topic,
+            QOS_PROFILE_DEFAULT,
+            move |_: msg::Empty| {
+                received_in_callback.fetch_add(1, Ordering::SeqCst);
+            },
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>(topic, QOS_PROFILE_DEFAULT)?;
+        let start = Instant::now();
+        while publisher.get_subscription_count()? == 0 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        for _ in 0..N {
+            publisher.publish(msg::Empty::default())?;
+        }
+
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&graph.node2)?;
+        // Wait until the first message has arrived, which processes exactly one of them.
+        while received.load(Ordering::SeqCst) == 0 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            ignore_timeout(executor.spin_once(Some(Duration::from_millis(10))))?;
+        }
+        assert_eq!(received.load(Ordering::SeqCst), 1);
+
+        // Give the middleware time to deliver the remaining messages.
+        thread::sleep(Duration::from_millis(100));
+        executor.spin_some(Duration::from_secs(1))?;
+        assert_eq!(received.load(Ordering::SeqCst), N);
+
+        // Nothing is ready anymore, so this returns immediately.
+        executor.spin_some(Duration::from_secs(1))?;
+        assert_eq!(received.load(Ordering::SeqCst), N);
+        Ok(())
+    }
+
+    #[test]
+    fn spin_for_waits_until_the_deadline() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_spin_for")?;
+        let _subscription = graph.node1.create_subscription::<msg::Empty, _>(
+            "test_spin_for_topic",
+            QOS_PROFILE_DEFAULT,
+            |_: msg::Empty| {},
+        )?;
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&graph.node1)?;
+
+        // Nothing arrives, but this still waits for the whole duration.
+        let start = Instant::now();
+        executor.spin_for(Duration::from_millis(200))?;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(200), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(400), "{elapsed:?}");
+
+        // Work that arrives repeatedly is executed, without returning early.
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let ticks_in_callback = Arc::clone(&ticks);
+        let _timer = graph
+            .node1
+            .create_timer(Duration::from_millis(20), move || {
+                ticks_in_callback.fetch_add(1, Ordering::SeqCst);
+            })?;
+        let start = Instant::now();
+        executor.spin_for(Duration::from_millis(200))?;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(200), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(400), "{elapsed:?}");
+        let ticks = ticks.load(Ordering::SeqCst);
+        assert!((5..=10).contains(&ticks), "timer fired {ticks} times");
+        Ok(())
+    }
+
+    #[test]
+    fn spin_some_services_all_nodes() -> Result<(), RclrsError> {
+        let topic = "test_spin_some_nodes";
+        let graph = construct_test_graph(topic)?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let subscribe = |node: &Arc<Node>, id: usize| {
+            let received = Arc::clone(&received);
+            node.create_subscription::<msg::Empty, _>(
+                topic,
+                QOS_PROFILE_DEFAULT,
+                move |_: msg::Empty| {
+                    received.lock().unwrap().push(id);
+                },
+            )
+        };
+        let _subscription_1 = subscribe(&graph.node1, 1)?;
+        let _subscription_2 = subscribe(&graph.node2, 2)?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>(topic, QOS_PROFILE_DEFAULT)?;
+        let start = Instant::now();
+        while publisher.get_subscription_count()? < 2 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&graph.node1)?;
+        executor.add_node(&graph.node2)?;
+        publisher.publish(msg::Empty::default())?;
+        // Give the middleware time to deliver the message to both subscriptions.
+        thread::sleep(Duration::from_millis(100));
+        executor.spin_some(Duration::from_secs(1))?;
+        let mut received_by = received.lock().unwrap().clone();
+        received_by.sort();
+        assert_eq!(received_by, [1, 2]);
+
+        // A dropped node is removed from the executor.
+        drop(graph.node1);
+        assert_eq!(executor.statistics().nodes, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn spin_once_alternates_between_ready_entities() -> Result<(), RclrsError> {
+        let topic = "test_spin_once_alternates";
+        let graph = construct_test_graph(topic)?;
+        let received = Arc::new(AtomicUsize::new(0));
+        let received_in_callback = Arc::clone(&received);
+        let _subscription = graph.node1.create_subscription::<msg::Empty, _>(
+            topic,
+            QOS_PROFILE_DEFAULT,
+            move |_: msg::Empty| {
+                received_in_callback.fetch_add(1, Ordering::SeqCst);
+            },
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>(topic, QOS_PROFILE_DEFAULT)?;
+        for _ in 0..5 {
+            publisher.publish(msg::Empty::default())?;
+        }
+        // Give the middleware time to deliver the messages, so that the subscription stays ready.
+        thread::sleep(Duration::from_millis(100));
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let ticks_in_callback = Arc::clone(&ticks);
+        let _timer = graph.node1.create_timer(Duration::ZERO, move || {
+            ticks_in_callback.fetch_add(1, Ordering::SeqCst);
+        })?;
+
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&graph.node1)?;
+        for _ in 0..4 {
+            executor.spin_once(Some(Duration::ZERO))?;
+        }
+        assert_eq!(received.load(Ordering::SeqCst), 2);
+        assert_eq!(ticks.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn reused_wait_set_picks_up_new_entities() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = crate::create_node(&context, "test_reused_wait_set")?;
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&node)?;
+        let spin = || ignore_timeout(executor.spin_once(Some(Duration::ZERO)));
+        spin()?;
+
+        // The wait set grows to fit a timer that was created after the first spin.
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let ticks_in_callback = Arc::clone(&ticks);
+        let _timer = node.create_timer(Duration::ZERO, move || {
+            ticks_in_callback.fetch_add(1, Ordering::SeqCst);
+        })?;
+        spin()?;
+        assert_eq!(ticks.load(Ordering::SeqCst), 1);
+
+        // Between spins, the entities can be added to other wait sets.
+        let mut wait_set = WaitSet::new_for_node(&node)?;
+        assert_eq!(wait_set.wait(Some(Duration::ZERO))?.timers.len(), 1);
+        drop(wait_set);
+        spin()?;
+        assert_eq!(ticks.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn spin_until_future_complete_returns_response() -> Result<(), RclrsError> {
+        use test_msgs::srv;
+
+        let graph = construct_test_graph("test_spin_until_future_complete")?;
+        let _service = graph.node1.create_service::<srv::BasicTypes, _>(
+            "future_service",
+            |_, request: srv::BasicTypes_Request| srv::BasicTypes_Response {
+                int64_value: request.int64_value + 1,
+                ..Default::default()
+            },
+        )?;
+        let client = graph
+            .node2
+            .create_client::<srv::BasicTypes>("future_service")?;
+        assert!(client.wait_for_service(Duration::from_secs(5))?);
+
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&graph.node1)?;
+        executor.add_node(&graph.node2)?;
+        let request = srv::BasicTypes_Request {
+            int64_value: 41,
+            ..Default::default()
+        };
+        let response = executor.spin_until_future_complete(
+            client.call_async(&request),
+            Some(Duration::from_secs(5)),
+        )??;
+        assert_eq!(response.int64_value, 42);
+        // The waitable for waking up the executor was removed again.
+        assert_eq!(executor.statistics().waitables, 0);
+
+        // A future that is never complete times out.
+        let start = Instant::now();
+        assert!(matches!(
+            executor.spin_until_future_complete(
+                futures::future::pending::<()>(),
+                Some(Duration::from_millis(100))
+            ),
+            Err(RclrsError::SpinTimeout)
+        ));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        Ok(())
+    }
+
+    struct CountingWaitable {
+        guard_condition: Arc<GuardCondition>,
+        executed: AtomicUsize,
+    }
+
+    impl Waitable for CountingWaitable {
+        fn guard_conditions(&self) -> Vec<Arc<GuardCondition>> {
+            vec![Arc::clone(&self.guard_condition)]
+        }
+
+        fn is_ready(&self) -> bool {
+            true
+        }
+
+        fn execute(&self) -> Result<(), RclrsError> {
+            self.executed.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn waitable_is_executed_when_triggered() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = crate::create_node(&context, "test_waitable")?;
+        let waitable = Arc::new(CountingWaitable {
+            guard_condition: Arc::new(GuardCondition::new(&context)),
+            executed: AtomicUsize::new(0),
+        });
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&node)?;
+        executor.add_waitable(Arc::clone(&waitable) as Arc<dyn Waitable>)?;
+
+        waitable.guard_condition.trigger()?;
+        executor.spin_once(Some(Duration::from_secs(1)))?;
+        assert_eq!(waitable.executed.load(Ordering::SeqCst), 1);
+
+        // The guard condition was not triggered again, so the waitable is not executed.
+        ignore_timeout(executor.spin_once(Some(Duration::from_millis(50))))?;
+        assert_eq!(waitable.executed.load(Ordering::SeqCst), 1);
+
+        waitable.guard_condition.trigger()?;
+        executor.spin_once(Some(Duration::from_secs(1)))?;
+        assert_eq!(waitable.executed.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn statistics_count_entities_and_waits() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = crate::create_node(&context, "test_executor_statistics")?;
+        let _subscriptions = (0..2)
+            .map(|_| {
+                node.create_subscription::<msg::Empty, _>(
+                    "test_executor_statistics",
+                    QOS_PROFILE_DEFAULT,
+                    |_: msg::Empty| {},
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let executor = SingleThreadedExecutor::new();
+        assert_eq!(executor.statistics(), ExecutorStats::default());
+
+        executor.add_node(&node)?;
+        let stats = executor.statistics();
+        assert_eq!(stats.nodes, 1);
+        assert_eq!(stats.subscriptions, 2);
+        assert_eq!(stats.last_wait_duration, None);
+
+        // Nothing was published, so the wait times out without any ready entities.
+        ignore_timeout(executor.spin_once(Some(Duration::from_millis(10))))?;
+        let stats = executor.statistics();
+        assert_eq!(stats.ready_on_last_wait, 0);
+        assert!(stats.last_wait_duration.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn cancel_stops_spin_and_allows_spinning_again() -> Result<(), RclrsError> {
+        let topic = "test_executor_cancel";
+        let graph = construct_test_graph(topic)?;
+        let received = Arc::new(AtomicUsize::new(0));
+        let received_in_callback = Arc::clone(&received);
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            topic,
+            QOS_PROFILE_DEFAULT,
+            move |_: msg::Empty| {
+                received_in_callback.fetch_add(1, Ordering::SeqCst);
+            },
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>(topic, QOS_PROFILE_DEFAULT)?;
+        let executor = Arc::new(SingleThreadedExecutor::new());
+        executor.add_node(&graph.node2)?;
+        let spin_in_thread = || {
+            let executor = Arc::clone(&executor);
+            thread::spawn(move || executor.spin())
+        };
+
+        // Nothing was published, so the executor is blocked until it is cancelled.
+        let spin_thread = spin_in_thread();
+        thread::sleep(Duration::from_millis(100));
+        executor.cancel();
+        spin_thread.join().unwrap()?;
+        assert_eq!(received.load(Ordering::SeqCst), 0);
+
+        // The executor can be spun again, and still processes new messages.
+        let start = Instant::now();
+        while publisher.get_subscription_count()? == 0 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        let spin_thread = spin_in_thread();
+        publisher.publish(msg::Empty::default())?;
+        while received.load(Ordering::SeqCst) == 0 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        executor.cancel();
+        spin_thread.join().unwrap()?;
+        assert_eq!(received.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn multi_threaded_executor_can_be_cancelled() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = crate::create_node(&context, "test_multi_threaded_cancel")?;
+        let executor = Arc::new(MultiThreadedExecutor::new(2));
+        executor.add_node(&node)?;
+        for _ in 0..2 {
+            let spin_thread = {
+                let executor = Arc::clone(&executor);
+                thread::spawn(move || executor.spin())
+            };
+            thread::sleep(Duration::from_millis(100));
+            executor.cancel();
+            spin_thread.join().unwrap()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn traits() {
+        assert_send::<MultiThreadedExecutor>();
+        assert_sync::<MultiThreadedExecutor>();
+    }
+
+    // Two subscriptions in one callback group, whose callbacks block until they are released.
+    struct BlockingCallbacks {
+        _subscriptions: Vec<Arc<Subscription<msg::Empty>>>,
+        // Receives a message whenever a callback is entered.
+        entered: mpsc::Receiver<()>,
+        // Dropping the sender releases all callbacks.
+        release: mpsc::Sender<()>,
+    }
+
+    impl BlockingCallbacks {
+        fn new(
+            node: &Node,
+            group_type: CallbackGroupType,
+            topic: &str,
+        ) -> Result<Self, RclrsError> {
+            let group = node.create_callback_group(group_type);
+            let (entered_sender, entered) = mpsc::channel();
+            let (release, release_receiver) = mpsc::channel::<()>();
+            let release_receiver = Arc::new(Mutex::new(release_receiver));
+            let mut subscriptions = Vec::new();
+            for _ in 0..2 {
+                let entered_sender = entered_sender.clone();
+                let release_receiver = Arc::clone(&release_receiver);
+                subscriptions.push(
+                    node.create_subscription_with_options(
+                        topic,
+                        SubscriptionOptions::new(QOS_PROFILE_DEFAULT)
+                            .callback_group(Arc::clone(&group)),
+                        move |_: msg::Empty| {
+                            let _ = entered_sender.send(());
+                            // This returns an error once the sender is dropped.
+                            let _ = release_receiver.lock().unwrap().recv();
+                        },
+                    )?,
+                );
+            }
+            Ok(Self {
+                _subscriptions: subscriptions,
+                entered,
+                release,
+            })
+        }
+    }
+
+    #[test]
+    fn reentrant_callbacks_run_in_parallel() -> Result<(), RclrsError> {
+        let topic = "test_multi_threaded_executor_reentrant";
+        let graph = construct_test_graph(topic)?;
+        // The executor is declared first, so that the callbacks are released before it is
+        // dropped, which waits for them to finish.
+        let executor = MultiThreadedExecutor::new(4);
+        executor.add_node(&graph.node2)?;
+        let callbacks = BlockingCallbacks::new(&graph.node2, CallbackGroupType::Reentrant, topic)?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>(topic, QOS_PROFILE_DEFAULT)?;
+
+        // Both callbacks are entered while the first one is still blocked.
+        let mut entered = 0;
+        let start = Instant::now();
+        while entered < 2 && start.elapsed() < Duration::from_secs(5) {
+            if entered == 0 {
+                publisher.publish(msg::Empty::default())?;
+            }
+            ignore_timeout(executor.spin_once(Some(Duration::from_millis(10))))?;
+            entered += callbacks.entered.try_iter().count();
+        }
+        drop(callbacks.release);
+        assert!(entered >= 2, "{entered}");
+        Ok(())
+    }
+
+    #[test]
+    fn mutually_exclusive_callbacks_do_not_overlap() -> Result<(), RclrsError> {
+        let topic = "test_multi_threaded_executor_mutually_exclusive";
+        let graph = construct_test_graph(topic)?;
+        // The executor is declared first, so that the callbacks are released before it is
+        // dropped, which waits for them to finish.
+        let executor = MultiThreadedExecutor::new(4);
+        executor.add_node(&graph.node2)?;
+        let callbacks =
+            BlockingCallbacks::new(&graph.node2, CallbackGroupType::MutuallyExclusive, topic)?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>(topic, QOS_PROFILE_DEFAULT)?;
+        let spin = || ignore_timeout(executor.spin_once(Some(Duration::from_millis(10))));
+
+        let mut entered = 0;
+        let start = Instant::now();
+        while entered == 0 && start.elapsed() < Duration::from_secs(5) {
+            publisher.publish(msg::Empty::default())?;
+            spin()?;
+            entered += callbacks.entered.try_iter().count();
+        }
+        assert_eq!(entered, 1);
+
+        // The other subscription has a message as well, which must not be executed while the
+        // first callback is blocked.
+        for _ in 0..10 {
+            spin()?;
+        }
+        assert_eq!(callbacks.entered.try_iter().count(), 0);
+
+        // Once the first callback has finished, the group is available again.
+        drop(callbacks.release);
+        let start = Instant::now();
+        while entered < 2 && start.elapsed() < Duration::from_secs(5) {
+            spin()?;
+            entered += callbacks.entered.try_iter().count();
+        }
+        assert!(entered >= 2, "{entered}");
+        Ok(())
+    }
+
+    #[test]
+    fn multi_threaded_executor_spins_all_nodes() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_multi_threaded_executor_spins_all_nodes")?;
+        let received = Arc::new(AtomicUsize::new(0));
+        let received_in_callback = Arc::clone(&received);
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            "topic",
+            QOS_PROFILE_DEFAULT,
+            move |_: msg::Empty| {
+                received_in_callback.fetch_add(1, Ordering::SeqCst);
+            },
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("topic", QOS_PROFILE_DEFAULT)?;
+
+        // The first node never has any work, which must not keep the executor from waiting on
+        // the second one.
+        let executor = MultiThreadedExecutor::new(2);
+        executor.add_node(&graph.node1)?;
+        executor.add_node(&graph.node2)?;
+        let start = Instant::now();
+        while received.load(Ordering::SeqCst) == 0 && start.elapsed() < Duration::from_secs(5) {
+            publisher.publish(msg::Empty::default())?;
+            ignore_timeout(executor.spin_once(Some(Duration::from_millis(10))))?;
+        }
+        drop(executor);
+        assert!(received.load(Ordering::SeqCst) > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn panicking_callback_releases_its_group() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_panicking_callback_releases_its_group")?;
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_in_callback = Arc::clone(&calls);
+        // The subscription belongs to the default group of the node, which is mutually exclusive.
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            "topic",
+            QOS_PROFILE_DEFAULT,
+            move |_: msg::Empty| {
+                if calls_in_callback.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("The first callback panics");
+                }
+            },
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("topic", QOS_PROFILE_DEFAULT)?;
+
+        let executor = MultiThreadedExecutor::new(1);
+        executor.add_node(&graph.node2)?;
+        let mut panicked = false;
+        let start = Instant::now();
+        while (calls.load(Ordering::SeqCst) < 2 || !panicked)
+            && start.elapsed() < Duration::from_secs(5)
+        {
+            publisher.publish(msg::Empty::default())?;
+            match panic::catch_unwind(AssertUnwindSafe(|| {
+                executor.spin_once(Some(Duration::from_millis(10)))
+            })) {
+                Ok(result) => ignore_timeout(result)?,
+                Err(_) => panicked = true,
+            }
+        }
+        // The panic was resumed when spinning, and the callback was executed again afterwards.
+        assert!(panicked);
+        assert!(calls.load(Ordering::SeqCst) >= 2);
+        Ok(())
+    }
+
+    #[test]
+    fn timer_callback_group_runs_in_parallel() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_timer_callback_group_runs_in_parallel")?;
+        // The executor is declared first, so that the subscription callback is released before
+        // the executor is dropped, which waits for it to finish.
+        let executor = MultiThreadedExecutor::new(2);
+        executor.add_node(&graph.node2)?;
+        let (entered_sender, entered_receiver) = mpsc::channel();
+        let (release_sender, release_receiver) = mpsc::channel::<()>();
+        // The subscription belongs to the default group of the node, which it keeps busy until
+        // it is released.
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            "topic",
+            QOS_PROFILE_DEFAULT,
+            move |_: msg::Empty| {
+                let _ = entered_sender.send(());
+                // This returns an error once the sender is dropped.
+                let _ = release_receiver.recv();
+            },
+        )?;
+        let (tick_sender, tick_receiver) = mpsc::channel();
+        let group = graph
+            .node2
+            .create_callback_group(CallbackGroupType::MutuallyExclusive);
+        let timer = graph.node2.create_timer_with_callback_group(
+            Duration::from_millis(10),
+            Arc::clone(&group),
+            move || {
+                let _ = tick_sender.send(());
+            },
+        )?;
+        assert!(Arc::ptr_eq(timer.callback_group().unwrap(), &group));
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("topic", QOS_PROFILE_DEFAULT)?;
+        let spin = || ignore_timeout(executor.spin_once(Some(Duration::from_millis(10))));
+
+        let start = Instant::now();
+        while entered_receiver.try_recv().is_err() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            publisher.publish(msg::Empty::default())?;
+            spin()?;
+        }
+        // Ticks from before the subscription callback was entered don't count.
+        while tick_receiver.try_recv().is_ok() {}
+
+        // The timer keeps firing while the default group is busy.
+        let start = Instant::now();
+        while tick_receiver.try_recv().is_err() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            spin()?;
+        }
+        drop(release_sender);
+        Ok(())
+    }
 }
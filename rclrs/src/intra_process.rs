@@ -0,0 +1,484 @@
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::SystemTime,
+};
+
+use rosidl_runtime_rs::Message;
+
+use crate::{
+    rcl_bindings::*, ContextHandle, GuardCondition, MessageInfo, PublisherGid, QoSHistoryPolicy,
+    SubscriptionHandle, ToResult,
+};
+
+/// Keeps track of the publishers and subscriptions of a context that use intra-process
+/// communication, see [`NodeBuilder::use_intra_process_comms()`][1].
+///
+/// [1]: crate::NodeBuilder::use_intra_process_comms
+#[derive(Default)]
+pub(crate) struct IntraProcessManager {
+    // The endpoints, by fully qualified topic name.
+    topics: Mutex<HashMap<String, IntraProcessTopic>>,
+}
+
+#[derive(Default)]
+struct IntraProcessTopic {
+    publisher_gids: Vec<PublisherGid>,
+    // The buffers of the subscriptions, which are downcast to the message type when publishing.
+    // Subscriptions with a different message type on the same topic are skipped.
+    subscriptions: Vec<Weak<dyn Any + Send + Sync>>,
+}
+
+impl IntraProcessManager {
+    fn add_publisher(&self, topic: &str, gid: PublisherGid) {
+        let mut topics = self.topics.lock().unwrap();
+        topics
+            .entry(topic.to_string())
+            .or_default()
+            .publisher_gids
+            .push(gid);
+    }
+
+    fn remove_publisher(&self, topic: &str, gid: &PublisherGid) {
+        let mut topics = self.topics.lock().unwrap();
+        if let Some(entry) = topics.get_mut(topic) {
+            if let Some(position) = entry.publisher_gids.iter().position(|g| g == gid) {
+                entry.publisher_gids.remove(position);
+            }
+            if entry.publisher_gids.is_empty() && entry.subscriptions.is_empty() {
+                topics.remove(topic);
+            }
+        }
+    }
+
+    fn add_subscription(&self, topic: &str, buffer: Weak<dyn Any + Send + Sync>) {
+        let mut topics = self.topics.lock().unwrap();
+        let entry = topics.entry(topic.to_string()).or_default();
+        entry.subscriptions.retain(|s| s.strong_count() > 0);
+        entry.subscriptions.push(buffer);
+    }
+
+    // Returns the live subscription buffers of the topic with the message type T.
+    fn subscriptions<T: Message>(&self, topic: &str) -> Vec<Arc<IntraProcessBuffer<T>>> {
+        let mut topics = self.topics.lock().unwrap();
+        let entry = match topics.get_mut(topic) {
+            Some(entry) => entry,
+            None => return Vec::new(),
+        };
+        entry.subscriptions.retain(|s| s.strong_count() > 0);
+        entry
+            .subscriptions
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter_map(|buffer| buffer.downcast::<IntraProcessBuffer<T>>().ok())
+            .collect()
+    }
+
+    fn is_intra_process_publisher(&self, topic: &str, gid: &PublisherGid) -> bool {
+        let topics = self.topics.lock().unwrap();
+        topics
+            .get(topic)
+            .map_or(false, |entry| entry.publisher_gids.contains(gid))
+    }
+}
+
+/// The intra-process part of a publisher.
+pub(crate) struct IntraProcessPublisher {
+    context_handle: Arc<ContextHandle>,
+    topic: String,
+    gid: PublisherGid,
+    sequence_number: AtomicU64,
+}
+
+impl IntraProcessPublisher {
+    pub(crate) fn new(
+        context_handle: Arc<ContextHandle>,
+        topic: String,
+        gid: PublisherGid,
+    ) -> Self {
+        context_handle
+            .intra_process_manager
+            .add_publisher(&topic, gid.clone());
+        Self {
+            context_handle,
+            topic,
+            gid,
+            sequence_number: AtomicU64::new(0),
+        }
+    }
+
+    /// Delivers the message to all intra-process subscriptions of the topic, and returns how many
+    /// of them are matched with a publisher in the middleware.
+    ///
+    /// Subscriptions that are not matched yet are not included in the subscription count of
+    /// the publisher either, so the count of matched subscriptions can be compared with it to
+    /// decide whether other subscriptions need the message from the middleware.
+    pub(crate) fn publish<T: Message>(&self, message: Arc<T>) -> usize {
+        let subscriptions = self
+            .context_handle
+            .intra_process_manager
+            .subscriptions::<T>(&self.topic);
+        let publication_sequence_number = self.sequence_number.fetch_add(1, Ordering::Relaxed) + 1;
+        let timestamp = SystemTime::now();
+        for subscription in &subscriptions {
+            let message_info = MessageInfo {
+                source_timestamp: Some(timestamp),
+                received_timestamp: Some(timestamp),
+                publication_sequence_number,
+                reception_sequence_number: subscription
+                    .reception_sequence_number
+                    .fetch_add(1, Ordering::Relaxed)
+                    + 1,
+                publisher_gid: self.gid.clone(),
+                from_intra_process: true,
+            };
+            subscription.push(Arc::clone(&message), message_info);
+        }
+        subscriptions
+            .iter()
+            .filter(|subscription| subscription.is_matched())
+            .count()
+    }
+}
+
+impl Drop for IntraProcessPublisher {
+    fn drop(&mut self) {
+        self.context_handle
+            .intra_process_manager
+            .remove_publisher(&self.topic, &self.gid);
+    }
+}
+
+/// The queue of messages that were delivered to a subscription through intra-process
+/// communication.
+pub(crate) struct IntraProcessBuffer<T> {
+    context_handle: Arc<ContextHandle>,
+    subscription_handle: Arc<SubscriptionHandle>,
+    topic: String,
+    // When the buffer was registered. Messages published before were not delivered to it.
+    registered_at: Mutex<SystemTime>,
+    queue: Mutex<VecDeque<(Arc<T>, MessageInfo)>>,
+    // The maximum number of queued messages, or None to keep all messages.
+    depth: Option<usize>,
+    // Triggered when a message is pushed, so that the subscription is executed.
+    pub(crate) guard_condition: Arc<GuardCondition>,
+    reception_sequence_number: AtomicU64,
+}
+
+impl<T> IntraProcessBuffer<T>
+where
+    T: Message,
+{
+    pub(crate) fn new(
+        context_handle: Arc<ContextHandle>,
+        subscription_handle: Arc<SubscriptionHandle>,
+        topic: String,
+        history: QoSHistoryPolicy,
+    ) -> Arc<Self> {
+        let depth = match history {
+            QoSHistoryPolicy::KeepLast { depth } | QoSHistoryPolicy::SystemDefault { depth } => {
+                Some((depth as usize).max(1))
+            }
+            QoSHistoryPolicy::KeepAll => None,
+        };
+        let buffer = Arc::new(Self {
+            guard_condition: Arc::new(GuardCondition::new_with_context_handle(
+                Arc::clone(&context_handle),
+                None,
+            )),
+            context_handle,
+            subscription_handle,
+            topic,
+            registered_at: Mutex::new(SystemTime::now()),
+            queue: Mutex::new(VecDeque::new()),
+            depth,
+            reception_sequence_number: AtomicU64::new(0),
+        });
+        let weak_buffer = Arc::downgrade(&buffer) as Weak<dyn Any + Send + Sync>;
+        buffer
+            .context_handle
+            .intra_process_manager
+            .add_subscription(&buffer.topic, weak_buffer);
+        // Taking the time after registering errs on the side of delivering a message twice
+        // rather than not at all, if it is published concurrently.
+        *buffer.registered_at.lock().unwrap() = SystemTime::now();
+        buffer
+    }
+
+    fn push(&self, message: Arc<T>, message_info: MessageInfo) {
+        {
+            let mut queue = self.queue.lock().unwrap();
+            if self.depth.map_or(false, |depth| queue.len() >= depth) {
+                queue.pop_front();
+            }
+            queue.push_back((message, message_info));
+        }
+        // Triggering can only fail if the guard condition is invalid.
+        let _ = self.guard_condition.trigger();
+    }
+
+    /// Takes the oldest message from the queue.
+    pub(crate) fn pop(&self) -> Option<(Arc<T>, MessageInfo)> {
+        let mut queue = self.queue.lock().unwrap();
+        let message = queue.pop_front();
+        if !queue.is_empty() {
+            // The guard condition only wakes up the wait set once, so it needs to be triggered
+            // again for the remaining messages.
+            let _ = self.guard_condition.trigger();
+        }
+        message
+    }
+
+    /// Checks whether a message that was received through the RMW implementation was sent by a
+    /// publisher that already delivered it through intra-process communication.
+    ///
+    /// Messages that were published before the subscription existed, e.g. by a transient local
+    /// publisher, were not delivered through intra-process communication.
+    pub(crate) fn is_duplicate(&self, message_info: &MessageInfo) -> bool {
+        let published_before_registration =
+            message_info.source_timestamp.map_or(false, |timestamp| {
+                timestamp < *self.registered_at.lock().unwrap()
+            });
+        !published_before_registration
+            && self
+                .context_handle
+                .intra_process_manager
+                .is_intra_process_publisher(&self.topic, &message_info.publisher_gid)
+    }
+
+    // Whether the subscription of the buffer is matched with any publisher in the middleware.
+    fn is_matched(&self) -> bool {
+        let mut publisher_count = 0;
+        // SAFETY: The subscription is valid, and the out-parameter is a valid pointer.
+        let ret = unsafe {
+            rcl_subscription_get_publisher_count(
+                &*self.subscription_handle.lock(),
+                &mut publisher_count,
+            )
+        };
+        ret.ok_for("rcl_subscription_get_publisher_count").is_ok() && publisher_count > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_helpers::ignore_timeout, Context, Node, NodeBuilder, PublisherOptions, QoSProfile,
+        RclrsError, SingleThreadedExecutor, SubscriptionOptions, QOS_PROFILE_DEFAULT,
+    };
+    use std::time::Duration;
+    use test_msgs::msg;
+
+    fn create_node(
+        context: &Context,
+        name: &str,
+        use_intra_process_comms: bool,
+    ) -> Result<Arc<Node>, RclrsError> {
+        NodeBuilder::new(context, name)
+            .namespace("/test_intra_process")
+            .use_intra_process_comms(use_intra_process_comms)
+            .build()
+    }
+
+    #[test]
+    fn test_intra_process_pub_sub() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let publisher_node = create_node(&context, "publisher_node", true)?;
+        let intra_process_node = create_node(&context, "intra_process_node", true)?;
+        let inter_process_node = create_node(&context, "inter_process_node", false)?;
+
+        let intra_process_received = Arc::new(Mutex::new(Vec::new()));
+        let intra_process_received_in_callback = Arc::clone(&intra_process_received);
+        let _intra_process_subscription = intra_process_node
+            .create_subscription::<msg::BasicTypes, _>(
+                "topic",
+                QOS_PROFILE_DEFAULT,
+                move |msg: Arc<msg::BasicTypes>, info: MessageInfo| {
+                    intra_process_received_in_callback
+                        .lock()
+                        .unwrap()
+                        .push((msg.int32_value, info.from_intra_process));
+                },
+            )?;
+        let inter_process_received = Arc::new(Mutex::new(Vec::new()));
+        let inter_process_received_in_callback = Arc::clone(&inter_process_received);
+        let _inter_process_subscription = inter_process_node
+            .create_subscription::<msg::BasicTypes, _>(
+                "topic",
+                QOS_PROFILE_DEFAULT,
+                move |msg: msg::BasicTypes, info: MessageInfo| {
+                    inter_process_received_in_callback
+                        .lock()
+                        .unwrap()
+                        .push((msg.int32_value, info.from_intra_process));
+                },
+            )?;
+        let publisher =
+            publisher_node.create_publisher::<msg::BasicTypes>("topic", QOS_PROFILE_DEFAULT)?;
+
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&intra_process_node)?;
+        executor.add_node(&inter_process_node)?;
+        let spin = || ignore_timeout(executor.spin_once(Some(Duration::from_millis(10))));
+
+        // Publish until the subscription in the other node has been matched and receives the
+        // messages through the middleware as well.
+        let mut published = 0;
+        for i in 0..100 {
+            publisher.publish(msg::BasicTypes {
+                int32_value: i,
+                ..Default::default()
+            })?;
+            published += 1;
+            for _ in 0..5 {
+                spin()?;
+            }
+            if !inter_process_received.lock().unwrap().is_empty() {
+                break;
+            }
+        }
+        for _ in 0..10 {
+            spin()?;
+        }
+
+        // Each message was delivered exactly once to the intra-process subscription, i.e. the
+        // copies sent through the middleware were ignored.
+        let intra_process_received = intra_process_received.lock().unwrap();
+        assert_eq!(intra_process_received.len(), published);
+        assert!(intra_process_received
+            .iter()
+            .enumerate()
+            .all(|(i, &(value, from_intra_process))| value == i as i32 && from_intra_process));
+        let inter_process_received = inter_process_received.lock().unwrap();
+        assert!(!inter_process_received.is_empty());
+        assert!(inter_process_received
+            .iter()
+            .all(|&(_, from_intra_process)| !from_intra_process));
+        Ok(())
+    }
+
+    #[test]
+    fn test_intra_process_disabled_per_endpoint() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = create_node(&context, "per_endpoint_node", true)?;
+
+        // Each subscription records the publisher index and whether the message was
+        // delivered intra-process.
+        let subscribe = |options: SubscriptionOptions| {
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let received_in_callback = Arc::clone(&received);
+            let subscription = node.create_subscription_with_options::<msg::BasicTypes, _>(
+                "per_endpoint_topic",
+                options,
+                move |msg: msg::BasicTypes, info: MessageInfo| {
+                    received_in_callback
+                        .lock()
+                        .unwrap()
+                        .push((msg.int32_value, info.from_intra_process));
+                },
+            )?;
+            Ok::<_, RclrsError>((subscription, received))
+        };
+        let (_subscription, received) = subscribe(SubscriptionOptions::default())?;
+        let (_inter_process_subscription, inter_process_received) =
+            subscribe(SubscriptionOptions::default().use_intra_process(false))?;
+        let intra_process_publisher =
+            node.create_publisher::<msg::BasicTypes>("per_endpoint_topic", QOS_PROFILE_DEFAULT)?;
+        let inter_process_publisher = node.create_publisher_with_options::<msg::BasicTypes>(
+            "per_endpoint_topic",
+            PublisherOptions::default().use_intra_process(false),
+        )?;
+
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&node)?;
+        let spin = || ignore_timeout(executor.spin_once(Some(Duration::from_millis(10))));
+
+        // Publish until both publishers have been matched with both subscriptions in the
+        // middleware.
+        let received_from = |received: &Mutex<Vec<(i32, bool)>>, publisher: i32| {
+            received
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|&(value, _)| value == publisher)
+        };
+        for _ in 0..100 {
+            for (index, publisher) in [&intra_process_publisher, &inter_process_publisher]
+                .into_iter()
+                .enumerate()
+            {
+                publisher.publish(msg::BasicTypes {
+                    int32_value: index as i32,
+                    ..Default::default()
+                })?;
+            }
+            for _ in 0..5 {
+                spin()?;
+            }
+            if received_from(&received, 1)
+                && received_from(&inter_process_received, 0)
+                && received_from(&inter_process_received, 1)
+            {
+                break;
+            }
+        }
+
+        // Only messages between two endpoints that both use intra-process communication are
+        // delivered intra-process.
+        let received = received.lock().unwrap();
+        assert!(received
+            .iter()
+            .all(|&(publisher, from_intra_process)| from_intra_process == (publisher == 0)));
+        assert!(received.iter().any(|&(publisher, _)| publisher == 1));
+        let inter_process_received = inter_process_received.lock().unwrap();
+        assert!(inter_process_received.len() >= 2);
+        assert!(inter_process_received
+            .iter()
+            .all(|&(_, from_intra_process)| !from_intra_process));
+        Ok(())
+    }
+
+    #[test]
+    fn test_intra_process_late_subscription_receives_latched_message() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = create_node(&context, "latched_node", true)?;
+        let publisher = node.create_publisher::<msg::BasicTypes>(
+            "latched_topic",
+            QoSProfile::transient_local_latch(),
+        )?;
+        // Without any subscription, the message can only be kept by the middleware.
+        publisher.publish(msg::BasicTypes {
+            int32_value: 42,
+            ..Default::default()
+        })?;
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_in_callback = Arc::clone(&received);
+        let _subscription = node.create_subscription::<msg::BasicTypes, _>(
+            "latched_topic",
+            QoSProfile::transient_local_latch(),
+            move |msg: msg::BasicTypes, info: MessageInfo| {
+                received_in_callback
+                    .lock()
+                    .unwrap()
+                    .push((msg.int32_value, info.from_intra_process));
+            },
+        )?;
+        let executor = SingleThreadedExecutor::new();
+        executor.add_node(&node)?;
+        for _ in 0..100 {
+            ignore_timeout(executor.spin_once(Some(Duration::from_millis(10))))?;
+            if !received.lock().unwrap().is_empty() {
+                break;
+            }
+        }
+
+        assert_eq!(*received.lock().unwrap(), vec![(42, false)]);
+        Ok(())
+    }
+}
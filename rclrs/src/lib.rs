@@ -5,20 +5,33 @@
 //!
 //! [1]: https://github.com/ros2-rust/ros2_rust/blob/main/README.md
 
+mod action;
+mod allocator;
 mod arguments;
+mod callback_group;
 mod client;
 mod clock;
 mod context;
 mod error;
+mod event;
 mod executor;
+mod intra_process;
+mod lifecycle;
+mod logging;
+mod names;
+mod network_flow_endpoint;
 mod node;
 mod parameter;
 mod publisher;
 mod qos;
+mod rate;
+mod serialized_message;
 mod service;
 mod subscription;
 mod time;
 mod time_source;
+mod timer;
+mod type_adapter;
 mod vendor;
 mod wait;
 
@@ -29,27 +42,41 @@ mod rcl_bindings;
 
 #[cfg(feature = "dyn_msg")]
 pub mod dynamic_message;
+pub mod msg;
 
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
 
+pub use action::*;
+pub use allocator::*;
 pub use arguments::*;
+pub use callback_group::*;
 pub use client::*;
 pub use clock::*;
 pub use context::*;
 pub use error::*;
+pub use event::*;
 pub use executor::*;
+use intra_process::*;
+pub use lifecycle::*;
+pub use logging::*;
+pub use names::*;
+pub use network_flow_endpoint::*;
 pub use node::*;
 pub use parameter::*;
 pub use publisher::*;
 pub use qos::*;
+pub use rate::*;
 pub use rcl_bindings::rmw_request_id_t;
+pub use serialized_message::*;
 pub use service::*;
 pub use subscription::*;
 pub use time::*;
 use time_source::*;
+pub use timer::*;
+pub use type_adapter::*;
 pub use wait::*;
 
-/// Polls the node for new messages and executes the corresponding callbacks.
+/// Polls the node for new messages and executes at most one of the corresponding callbacks.
 ///
 /// See [`WaitSet::wait`] for the meaning of the `timeout` parameter.
 ///
@@ -59,12 +86,23 @@ pub use wait::*;
 /// This can usually be ignored.
 ///
 /// [1]: crate::RclReturnCode
-pub fn spin_once(node: Arc<Node>, timeout: Option<Duration>) -> Result<(), RclrsError> {
+pub fn spin_once(node: Arc<Node>, timeout: Option<std::time::Duration>) -> Result<(), RclrsError> {
     let executor = SingleThreadedExecutor::new();
     executor.add_node(&node)?;
     executor.spin_once(timeout)
 }
 
+/// Executes the callbacks of the node that are ready, without waiting for new work.
+///
+/// Unlike [`spin_once`], this executes all the ready callbacks, including work that becomes
+/// ready in the meantime, for at most `max_duration`.
+/// See [`SingleThreadedExecutor::spin_some`] for details.
+pub fn spin_some(node: Arc<Node>, max_duration: std::time::Duration) -> Result<(), RclrsError> {
+    let executor = SingleThreadedExecutor::new();
+    executor.add_node(&node)?;
+    executor.spin_some(max_duration)
+}
+
 /// Convenience function for calling [`spin_once`] in a loop.
 pub fn spin(node: Arc<Node>) -> Result<(), RclrsError> {
     let executor = SingleThreadedExecutor::new();
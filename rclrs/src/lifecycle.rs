@@ -0,0 +1,970 @@
+use std::{
+    ffi::{CStr, CString},
+    fmt,
+    sync::{Arc, Mutex, MutexGuard, Weak},
+    thread::{self, ThreadId},
+};
+
+use rosidl_runtime_rs::{Message, RmwMessage};
+
+mod publisher;
+pub use publisher::*;
+
+use crate::{
+    error::{RclReturnCode, ToResult},
+    rcl_bindings::*,
+    vendor::lifecycle_msgs::{msg, srv},
+    Context, Node, NodeHandle, QoSProfile, RclrsError, Service, ENTITY_LIFECYCLE_MUTEX,
+};
+
+// SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
+// they are running in. Therefore, this type can be safely sent to another thread.
+unsafe impl Send for rcl_lifecycle_state_machine_t {}
+
+/// A state of a [`LifecycleNode`].
+///
+/// This corresponds to the constants of the `lifecycle_msgs/msg/State` message. The primary
+/// states are the stable states that a node rests in, while the transition states are only
+/// occupied while the callback of a transition runs.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LifecycleState {
+    /// The state has not been set.
+    Unknown = 0,
+    /// The primary state of a node immediately after it has been created.
+    Unconfigured = 1,
+    /// The primary state of a configured node that is not currently performing any processing.
+    Inactive = 2,
+    /// The primary state of a node that performs its processing.
+    Active = 3,
+    /// The primary state of a node that has been shut down.
+    Finalized = 4,
+    /// The transition state while the configure callback runs.
+    Configuring = 10,
+    /// The transition state while the cleanup callback runs.
+    CleaningUp = 11,
+    /// The transition state while the shutdown callback runs.
+    ShuttingDown = 12,
+    /// The transition state while the activate callback runs.
+    Activating = 13,
+    /// The transition state while the deactivate callback runs.
+    Deactivating = 14,
+    /// The transition state while the error callback runs.
+    ErrorProcessing = 15,
+}
+
+impl From<u8> for LifecycleState {
+    /// Unrecognized values are mapped to [`LifecycleState::Unknown`].
+    fn from(id: u8) -> Self {
+        match id {
+            1 => Self::Unconfigured,
+            2 => Self::Inactive,
+            3 => Self::Active,
+            4 => Self::Finalized,
+            10 => Self::Configuring,
+            11 => Self::CleaningUp,
+            12 => Self::ShuttingDown,
+            13 => Self::Activating,
+            14 => Self::Deactivating,
+            15 => Self::ErrorProcessing,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// The outcome of a transition callback of a [`LifecycleNode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransitionCallbackReturn {
+    /// The transition completes, and the node moves to the goal state of the transition.
+    Success,
+    /// The transition is aborted, and the node returns to the state it started from.
+    Failure,
+    /// The transition is aborted, and the node moves to [`LifecycleState::ErrorProcessing`].
+    ///
+    /// There, the error callback decides whether the node recovers to
+    /// [`LifecycleState::Unconfigured`] or is [`LifecycleState::Finalized`].
+    Error,
+}
+
+impl TransitionCallbackReturn {
+    fn label(&self) -> *const std::os::raw::c_char {
+        // SAFETY: These are constant strings defined by rcl_lifecycle, which are never modified.
+        unsafe {
+            match self {
+                Self::Success => rcl_lifecycle_transition_success_label,
+                Self::Failure => rcl_lifecycle_transition_failure_label,
+                Self::Error => rcl_lifecycle_transition_error_label,
+            }
+        }
+    }
+}
+
+type TransitionCallback =
+    Box<dyn FnMut(LifecycleState) -> TransitionCallbackReturn + 'static + Send>;
+
+#[derive(Default)]
+struct TransitionCallbacks {
+    on_configure: Option<TransitionCallback>,
+    on_cleanup: Option<TransitionCallback>,
+    on_shutdown: Option<TransitionCallback>,
+    on_activate: Option<TransitionCallback>,
+    on_deactivate: Option<TransitionCallback>,
+    on_error: Option<TransitionCallback>,
+}
+
+impl TransitionCallbacks {
+    // Returns the callback slot of the given transition state.
+    fn slot(
+        &mut self,
+        transition_state: LifecycleState,
+    ) -> Option<&mut Option<TransitionCallback>> {
+        match transition_state {
+            LifecycleState::Configuring => Some(&mut self.on_configure),
+            LifecycleState::CleaningUp => Some(&mut self.on_cleanup),
+            LifecycleState::ShuttingDown => Some(&mut self.on_shutdown),
+            LifecycleState::Activating => Some(&mut self.on_activate),
+            LifecycleState::Deactivating => Some(&mut self.on_deactivate),
+            LifecycleState::ErrorProcessing => Some(&mut self.on_error),
+            _ => None,
+        }
+    }
+}
+
+/// Manage the lifecycle of an `rcl_lifecycle_state_machine_t`, including managing its dependency
+/// on `rcl_node_t` by ensuring that this dependency is [dropped after][1] the
+/// `rcl_lifecycle_state_machine_t`.
+///
+/// [1]: <https://doc.rust-lang.org/reference/destructors.html>
+struct LifecycleStateMachine {
+    rcl_state_machine: Mutex<rcl_lifecycle_state_machine_t>,
+    node_handle: Arc<NodeHandle>,
+    callbacks: Mutex<TransitionCallbacks>,
+    managed_entities_mtx: Mutex<Vec<Weak<dyn ManagedEntity>>>,
+    // Serializes transitions, since a transition consists of multiple steps of the state machine.
+    transition_mtx: Mutex<()>,
+    // The thread that is executing a transition, so that a transition triggered by one of its
+    // callbacks is rejected instead of waiting for itself.
+    transitioning_thread: Mutex<Option<ThreadId>>,
+}
+
+impl Drop for LifecycleStateMachine {
+    fn drop(&mut self) {
+        let rcl_state_machine = self.rcl_state_machine.get_mut().unwrap();
+        let mut rcl_node = self.node_handle.rcl_node.lock().unwrap();
+        let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+        // SAFETY: The entity lifecycle mutex is locked to protect against the risk of
+        // global variables in the rmw implementation being unsafely modified during cleanup.
+        unsafe {
+            rcl_lifecycle_state_machine_fini(rcl_state_machine, &mut *rcl_node);
+        }
+    }
+}
+
+impl LifecycleStateMachine {
+    fn new(node_handle: Arc<NodeHandle>) -> Result<Self, RclrsError> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut rcl_state_machine = unsafe { rcl_lifecycle_get_zero_state_machine() };
+        // SAFETY: No preconditions for this function.
+        let mut options = unsafe { rcl_lifecycle_get_default_state_machine_options() };
+        // The services are created by rclrs instead, so that they are handled by the executor.
+        options.enable_com_interface = false;
+
+        {
+            let mut rcl_node = node_handle.rcl_node.lock().unwrap();
+            let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+            // SAFETY:
+            // * The state machine was zero-initialized as expected by this function.
+            // * The rcl_node is kept alive by the NodeHandle because it is a dependency of the
+            //   state machine.
+            // * The type supports are static, and the options are copied by this function.
+            // * The entity lifecycle mutex is locked to protect against the risk of global
+            //   variables in the rmw implementation being unsafely modified during initialization.
+            unsafe {
+                rcl_lifecycle_state_machine_init(
+                    &mut rcl_state_machine,
+                    &mut *rcl_node,
+                    msg::rmw::TransitionEvent::get_type_support()
+                        as *const rosidl_message_type_support_t,
+                    <srv::ChangeState as rosidl_runtime_rs::Service>::get_type_support()
+                        as *const rosidl_service_type_support_t,
+                    <srv::GetState as rosidl_runtime_rs::Service>::get_type_support()
+                        as *const rosidl_service_type_support_t,
+                    <srv::GetAvailableStates as rosidl_runtime_rs::Service>::get_type_support()
+                        as *const rosidl_service_type_support_t,
+                    <srv::GetAvailableTransitions as rosidl_runtime_rs::Service>::get_type_support()
+                        as *const rosidl_service_type_support_t,
+                    <srv::GetAvailableTransitions as rosidl_runtime_rs::Service>::get_type_support()
+                        as *const rosidl_service_type_support_t,
+                    &options,
+                )
+                .ok_for("rcl_lifecycle_state_machine_init")?;
+            }
+        }
+
+        Ok(Self {
+            rcl_state_machine: Mutex::new(rcl_state_machine),
+            node_handle,
+            callbacks: Mutex::new(TransitionCallbacks::default()),
+            managed_entities_mtx: Mutex::new(Vec::new()),
+            transition_mtx: Mutex::new(()),
+            transitioning_thread: Mutex::new(None),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<rcl_lifecycle_state_machine_t> {
+        self.rcl_state_machine.lock().unwrap()
+    }
+
+    fn current_state(&self) -> LifecycleState {
+        let rcl_state_machine = self.lock();
+        // SAFETY: The current state of an initialized state machine points to one of the states
+        // of its transition map, which live as long as the state machine.
+        LifecycleState::from(unsafe { (*rcl_state_machine.current_state).id })
+    }
+
+    fn current_state_msg(&self) -> msg::State {
+        let rcl_state_machine = self.lock();
+        // SAFETY: See current_state().
+        unsafe { state_to_msg(&*rcl_state_machine.current_state) }
+    }
+
+    fn available_states(&self) -> Vec<msg::State> {
+        let rcl_state_machine = self.lock();
+        let transition_map = &rcl_state_machine.transition_map;
+        // SAFETY: The transition map owns an array of states of the given size.
+        let states =
+            unsafe { slice_or_empty(transition_map.states, transition_map.states_size as usize) };
+        // SAFETY: The states of the transition map are valid.
+        states
+            .iter()
+            .map(|state| unsafe { state_to_msg(state) })
+            .collect()
+    }
+
+    fn available_transitions(&self) -> Vec<msg::TransitionDescription> {
+        let rcl_state_machine = self.lock();
+        // SAFETY: See current_state(). The current state owns an array of its valid transitions
+        // of the given size.
+        let transitions = unsafe {
+            let current_state = &*rcl_state_machine.current_state;
+            slice_or_empty(
+                current_state.valid_transitions,
+                current_state.valid_transition_size as usize,
+            )
+        };
+        // SAFETY: The transitions of the transition map are valid.
+        transitions
+            .iter()
+            .map(|transition| unsafe { transition_to_msg(transition) })
+            .collect()
+    }
+
+    fn transition_graph(&self) -> Vec<msg::TransitionDescription> {
+        let rcl_state_machine = self.lock();
+        let transition_map = &rcl_state_machine.transition_map;
+        // SAFETY: The transition map owns an array of transitions of the given size.
+        let transitions = unsafe {
+            slice_or_empty(
+                transition_map.transitions,
+                transition_map.transitions_size as usize,
+            )
+        };
+        // SAFETY: The transitions of the transition map are valid.
+        transitions
+            .iter()
+            .map(|transition| unsafe { transition_to_msg(transition) })
+            .collect()
+    }
+
+    // Returns the ID of the transition with the given label that starts in the current state.
+    fn transition_id_by_label(&self, label: &str) -> Option<u8> {
+        let label = CString::new(label).ok()?;
+        let rcl_state_machine = self.lock();
+        // SAFETY: See current_state(). The label is copied by this function.
+        let transition = unsafe {
+            rcl_lifecycle_get_transition_by_label(rcl_state_machine.current_state, label.as_ptr())
+        };
+        // SAFETY: The transition is either null or points into the transition map.
+        unsafe { transition.as_ref() }.map(|transition| transition.id as u8)
+    }
+
+    // Moves the state machine along the transition with the given ID, runs the callback of the
+    // transition, and completes the transition according to the outcome of the callback.
+    //
+    // Transitions triggered from a transition callback are rejected.
+    fn change_state(&self, transition_id: u8) -> Result<TransitionCallbackReturn, RclrsError> {
+        if *self.transitioning_thread.lock().unwrap() == Some(thread::current().id()) {
+            return Err(RclrsError::RclError {
+                code: RclReturnCode::Error,
+                operation: None,
+                msg: None,
+            });
+        }
+        let _transition_lock = self.transition_mtx.lock().unwrap();
+        *self.transitioning_thread.lock().unwrap() = Some(thread::current().id());
+        let result = self.run_transition(transition_id);
+        *self.transitioning_thread.lock().unwrap() = None;
+        result
+    }
+
+    fn run_transition(&self, transition_id: u8) -> Result<TransitionCallbackReturn, RclrsError> {
+        let start_state = self.current_state();
+        // SAFETY: The state machine is initialized, and is locked for the duration of the call.
+        unsafe { rcl_lifecycle_trigger_transition_by_id(&mut *self.lock(), transition_id, true) }
+            .ok_for("rcl_lifecycle_trigger_transition_by_id")?;
+
+        let callback_return = self.execute_callback(self.current_state(), start_state);
+        self.trigger_transition_by_label(callback_return)?;
+
+        if callback_return == TransitionCallbackReturn::Error {
+            let error_callback_return =
+                self.execute_callback(LifecycleState::ErrorProcessing, start_state);
+            self.trigger_transition_by_label(error_callback_return)?;
+        }
+
+        // The managed entities are only notified when the node enters or leaves the active state
+        let was_active = start_state == LifecycleState::Active;
+        let is_active = self.current_state() == LifecycleState::Active;
+        if was_active != is_active {
+            for entity in { self.managed_entities_mtx.lock().unwrap() }
+                .iter()
+                .filter_map(Weak::upgrade)
+            {
+                if is_active {
+                    entity.on_activate();
+                } else {
+                    entity.on_deactivate();
+                }
+            }
+        }
+        Ok(callback_return)
+    }
+
+    fn trigger_transition_by_label(
+        &self,
+        callback_return: TransitionCallbackReturn,
+    ) -> Result<(), RclrsError> {
+        // SAFETY: The state machine is initialized, and is locked for the duration of the call.
+        // The label is a static string.
+        unsafe {
+            rcl_lifecycle_trigger_transition_by_label(
+                &mut *self.lock(),
+                callback_return.label(),
+                true,
+            )
+        }
+        .ok_for("rcl_lifecycle_trigger_transition_by_label")
+    }
+
+    fn execute_callback(
+        &self,
+        transition_state: LifecycleState,
+        previous_state: LifecycleState,
+    ) -> TransitionCallbackReturn {
+        // The callback is taken out of its slot while it runs, so that it is called without
+        // holding the lock on the callbacks.
+        let Some(callback) = self
+            .callbacks
+            .lock()
+            .unwrap()
+            .slot(transition_state)
+            .map(Option::take)
+        else {
+            return TransitionCallbackReturn::Error;
+        };
+        match callback {
+            Some(mut callback) => {
+                let callback_return = callback(previous_state);
+                // Unless the callback was replaced in the meantime, it is put back.
+                if let Some(slot) = self.callbacks.lock().unwrap().slot(transition_state) {
+                    if slot.is_none() {
+                        *slot = Some(callback);
+                    }
+                }
+                callback_return
+            }
+            // Errors cannot be recovered from without a user-defined error callback.
+            None if transition_state == LifecycleState::ErrorProcessing => {
+                TransitionCallbackReturn::Failure
+            }
+            None => TransitionCallbackReturn::Success,
+        }
+    }
+}
+
+// Returns an empty slice for null pointers, which rcl uses for empty arrays.
+unsafe fn slice_or_empty<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    if data.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
+}
+
+unsafe fn state_to_msg(state: &rcl_lifecycle_state_t) -> msg::State {
+    msg::State {
+        id: state.id,
+        label: CStr::from_ptr(state.label).to_string_lossy().into_owned(),
+    }
+}
+
+unsafe fn transition_to_msg(transition: &rcl_lifecycle_transition_t) -> msg::TransitionDescription {
+    msg::TransitionDescription {
+        transition: msg::Transition {
+            id: transition.id as u8,
+            label: CStr::from_ptr(transition.label)
+                .to_string_lossy()
+                .into_owned(),
+        },
+        start_state: state_to_msg(&*transition.start),
+        goal_state: state_to_msg(&*transition.goal),
+    }
+}
+
+/// A node with a managed lifecycle.
+///
+/// A lifecycle node wraps a [`Node`] together with a state machine that moves between the
+/// primary states [`Unconfigured`][1], [`Inactive`][2], [`Active`][3] and [`Finalized`][4], as
+/// explained in the [design article on managed nodes][5]. The transitions are triggered by the
+/// methods [`configure()`][6], [`activate()`][7], [`deactivate()`][8], [`cleanup()`][9] and
+/// [`shutdown()`][10], or by other nodes through the `~/change_state` service.
+///
+/// During each transition, the corresponding callback that was registered with e.g.
+/// [`set_on_configure()`][11] is called. Transitions for which no callback was registered succeed.
+/// Each transition is published on the `~/transition_event` topic.
+///
+/// The node also provides the `~/get_state`, `~/get_available_states`,
+/// `~/get_available_transitions` and `~/get_transition_graph` services. Like other services,
+/// these are only handled while the node is spinning, e.g. with
+/// `rclrs::spin(lifecycle_node.node().clone())`.
+///
+/// [1]: LifecycleState::Unconfigured
+/// [2]: LifecycleState::Inactive
+/// [3]: LifecycleState::Active
+/// [4]: LifecycleState::Finalized
+/// [5]: https://design.ros2.org/articles/node_lifecycle.html
+/// [6]: LifecycleNode::configure
+/// [7]: LifecycleNode::activate
+/// [8]: LifecycleNode::deactivate
+/// [9]: LifecycleNode::cleanup
+/// [10]: LifecycleNode::shutdown
+/// [11]: LifecycleNode::set_on_configure
+pub struct LifecycleNode {
+    state_machine: Arc<LifecycleStateMachine>,
+    _change_state_service: Arc<Service<srv::ChangeState>>,
+    _get_state_service: Arc<Service<srv::GetState>>,
+    _get_available_states_service: Arc<Service<srv::GetAvailableStates>>,
+    _get_available_transitions_service: Arc<Service<srv::GetAvailableTransitions>>,
+    _get_transition_graph_service: Arc<Service<srv::GetAvailableTransitions>>,
+    node: Arc<Node>,
+}
+
+impl fmt::Debug for LifecycleNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("LifecycleNode")
+            .field("node", &self.node)
+            .field("current_state", &self.current_state())
+            .finish()
+    }
+}
+
+impl LifecycleNode {
+    /// Creates a new lifecycle node in the empty namespace.
+    ///
+    /// See [`NodeBuilder::new()`][1] for documentation.
+    ///
+    /// [1]: crate::NodeBuilder::new
+    pub fn new(context: &Context, node_name: &str) -> Result<Self, RclrsError> {
+        Self::from_node(Node::new(context, node_name)?)
+    }
+
+    /// Turns a node into a lifecycle node, which starts out in [`LifecycleState::Unconfigured`].
+    ///
+    /// This can be used together with [`NodeBuilder`][1] to configure the node. Each node should
+    /// be turned into a lifecycle node at most once.
+    ///
+    /// [1]: crate::NodeBuilder
+    pub fn from_node(node: Arc<Node>) -> Result<Self, RclrsError> {
+        let state_machine = Arc::new(LifecycleStateMachine::new(Arc::clone(&node.handle))?);
+
+        let change_state_service = {
+            let state_machine = Arc::clone(&state_machine);
+            node.create_service::<srv::ChangeState, _>(
+                "~/change_state",
+                move |_, request: srv::ChangeState_Request| {
+                    let transition_id =
+                        if request.transition.id == 0 && !request.transition.label.is_empty() {
+                            state_machine.transition_id_by_label(&request.transition.label)
+                        } else {
+                            Some(request.transition.id)
+                        };
+                    let success = transition_id.map_or(false, |id| {
+                        state_machine.change_state(id) == Ok(TransitionCallbackReturn::Success)
+                    });
+                    srv::ChangeState_Response { success }
+                },
+            )?
+        };
+        let get_state_service = {
+            let state_machine = Arc::clone(&state_machine);
+            node.create_service::<srv::GetState, _>("~/get_state", move |_, _| {
+                srv::GetState_Response {
+                    current_state: state_machine.current_state_msg(),
+                }
+            })?
+        };
+        let get_available_states_service = {
+            let state_machine = Arc::clone(&state_machine);
+            node.create_service::<srv::GetAvailableStates, _>(
+                "~/get_available_states",
+                move |_, _| srv::GetAvailableStates_Response {
+                    available_states: state_machine.available_states(),
+                },
+            )?
+        };
+        let get_available_transitions_service = {
+            let state_machine = Arc::clone(&state_machine);
+            node.create_service::<srv::GetAvailableTransitions, _>(
+                "~/get_available_transitions",
+                move |_, _| srv::GetAvailableTransitions_Response {
+                    available_transitions: state_machine.available_transitions(),
+                },
+            )?
+        };
+        let get_transition_graph_service = {
+            let state_machine = Arc::clone(&state_machine);
+            node.create_service::<srv::GetAvailableTransitions, _>(
+                "~/get_transition_graph",
+                move |_, _| srv::GetAvailableTransitions_Response {
+                    available_transitions: state_machine.transition_graph(),
+                },
+            )?
+        };
+
+        Ok(Self {
+            state_machine,
+            _change_state_service: change_state_service,
+            _get_state_service: get_state_service,
+            _get_available_states_service: get_available_states_service,
+            _get_available_transitions_service: get_available_transitions_service,
+            _get_transition_graph_service: get_transition_graph_service,
+            node,
+        })
+    }
+
+    /// Returns the underlying node, e.g. to create subscriptions or to spin it.
+    pub fn node(&self) -> &Arc<Node> {
+        &self.node
+    }
+
+    /// Returns the current state of the node.
+    pub fn current_state(&self) -> LifecycleState {
+        self.state_machine.current_state()
+    }
+
+    /// Creates a [`LifecyclePublisher`], which only publishes while the node is active.
+    pub fn create_publisher<T>(
+        &self,
+        topic: &str,
+        qos: QoSProfile,
+    ) -> Result<Arc<LifecyclePublisher<T>>, RclrsError>
+    where
+        T: Message,
+    {
+        let publisher = self.node.create_publisher::<T>(topic, qos)?;
+        let mut managed_entities = self.state_machine.managed_entities_mtx.lock().unwrap();
+        let lifecycle_publisher = Arc::new(LifecyclePublisher::new(
+            publisher,
+            self.node.logger_name(),
+            self.current_state() == LifecycleState::Active,
+        ));
+        managed_entities.push(Arc::downgrade(&lifecycle_publisher) as Weak<dyn ManagedEntity>);
+        Ok(lifecycle_publisher)
+    }
+
+    /// Triggers the transition with the given ID, which is one of the `TRANSITION_*` constants of
+    /// [`Transition`][1].
+    ///
+    /// Returns the state of the node after the transition. If the callback of the transition
+    /// does not succeed, this is not the goal state of the transition.
+    ///
+    /// Returns an error if the transition is not available in the current state, or if this is
+    /// called from a transition callback of this node. Transitions triggered concurrently from
+    /// other threads are executed one after the other.
+    ///
+    /// [1]: crate::msg::Transition
+    pub fn trigger_transition(&self, transition_id: u8) -> Result<LifecycleState, RclrsError> {
+        self.state_machine.change_state(transition_id)?;
+        Ok(self.current_state())
+    }
+
+    /// Triggers the `configure` transition from [`LifecycleState::Unconfigured`] to
+    /// [`LifecycleState::Inactive`].
+    ///
+    /// See [`trigger_transition()`][1].
+    ///
+    /// [1]: LifecycleNode::trigger_transition
+    pub fn configure(&self) -> Result<LifecycleState, RclrsError> {
+        self.trigger_transition(msg::Transition::TRANSITION_CONFIGURE)
+    }
+
+    /// Triggers the `cleanup` transition from [`LifecycleState::Inactive`] to
+    /// [`LifecycleState::Unconfigured`].
+    ///
+    /// See [`trigger_transition()`][1].
+    ///
+    /// [1]: LifecycleNode::trigger_transition
+    pub fn cleanup(&self) -> Result<LifecycleState, RclrsError> {
+        self.trigger_transition(msg::Transition::TRANSITION_CLEANUP)
+    }
+
+    /// Triggers the `activate` transition from [`LifecycleState::Inactive`] to
+    /// [`LifecycleState::Active`].
+    ///
+    /// See [`trigger_transition()`][1].
+    ///
+    /// [1]: LifecycleNode::trigger_transition
+    pub fn activate(&self) -> Result<LifecycleState, RclrsError> {
+        self.trigger_transition(msg::Transition::TRANSITION_ACTIVATE)
+    }
+
+    /// Triggers the `deactivate` transition from [`LifecycleState::Active`] to
+    /// [`LifecycleState::Inactive`].
+    ///
+    /// See [`trigger_transition()`][1].
+    ///
+    /// [1]: LifecycleNode::trigger_transition
+    pub fn deactivate(&self) -> Result<LifecycleState, RclrsError> {
+        self.trigger_transition(msg::Transition::TRANSITION_DEACTIVATE)
+    }
+
+    /// Triggers the `shutdown` transition from the current primary state to
+    /// [`LifecycleState::Finalized`].
+    ///
+    /// See [`trigger_transition()`][1].
+    ///
+    /// [1]: LifecycleNode::trigger_transition
+    pub fn shutdown(&self) -> Result<LifecycleState, RclrsError> {
+        let transition_id = match self.current_state() {
+            LifecycleState::Unconfigured => msg::Transition::TRANSITION_UNCONFIGURED_SHUTDOWN,
+            LifecycleState::Inactive => msg::Transition::TRANSITION_INACTIVE_SHUTDOWN,
+            LifecycleState::Active => msg::Transition::TRANSITION_ACTIVE_SHUTDOWN,
+            _ => {
+                return Err(RclrsError::RclError {
+                    code: RclReturnCode::Error,
+                    operation: None,
+                    msg: None,
+                })
+            }
+        };
+        self.trigger_transition(transition_id)
+    }
+
+    /// Sets the callback of the `configure` transition.
+    ///
+    /// The callback receives the state that the transition started from.
+    pub fn set_on_configure<F>(&self, callback: F)
+    where
+        F: FnMut(LifecycleState) -> TransitionCallbackReturn + 'static + Send,
+    {
+        self.state_machine.callbacks.lock().unwrap().on_configure = Some(Box::new(callback));
+    }
+
+    /// Sets the callback of the `cleanup` transition.
+    ///
+    /// The callback receives the state that the transition started from.
+    pub fn set_on_cleanup<F>(&self, callback: F)
+    where
+        F: FnMut(LifecycleState) -> TransitionCallbackReturn + 'static + Send,
+    {
+        self.state_machine.callbacks.lock().unwrap().on_cleanup = Some(Box::new(callback));
+    }
+
+    /// Sets the callback of the `shutdown` transitions.
+    ///
+    /// The callback receives the state that the transition started from.
+    pub fn set_on_shutdown<F>(&self, callback: F)
+    where
+        F: FnMut(LifecycleState) -> TransitionCallbackReturn + 'static + Send,
+    {
+        self.state_machine.callbacks.lock().unwrap().on_shutdown = Some(Box::new(callback));
+    }
+
+    /// Sets the callback of the `activate` transition.
+    ///
+    /// The callback receives the state that the transition started from.
+    pub fn set_on_activate<F>(&self, callback: F)
+    where
+        F: FnMut(LifecycleState) -> TransitionCallbackReturn + 'static + Send,
+    {
+        self.state_machine.callbacks.lock().unwrap().on_activate = Some(Box::new(callback));
+    }
+
+    /// Sets the callback of the `deactivate` transition.
+    ///
+    /// The callback receives the state that the transition started from.
+    pub fn set_on_deactivate<F>(&self, callback: F)
+    where
+        F: FnMut(LifecycleState) -> TransitionCallbackReturn + 'static + Send,
+    {
+        self.state_machine.callbacks.lock().unwrap().on_deactivate = Some(Box::new(callback));
+    }
+
+    /// Sets the callback that handles an error in another transition callback.
+    ///
+    /// The callback receives the state that the failed transition started from. If it succeeds,
+    /// the node moves to [`LifecycleState::Unconfigured`], otherwise to
+    /// [`LifecycleState::Finalized`]. Without an error callback, the node is finalized.
+    pub fn set_on_error<F>(&self, callback: F)
+    where
+        F: FnMut(LifecycleState) -> TransitionCallbackReturn + 'static + Send,
+    {
+        self.state_machine.callbacks.lock().unwrap().on_error = Some(Box::new(callback));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_helpers::*, QOS_PROFILE_DEFAULT};
+    use std::{
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn traits() {
+        assert_send::<LifecycleNode>();
+        assert_sync::<LifecycleNode>();
+    }
+
+    #[test]
+    fn test_transitions() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = LifecycleNode::new(&context, "test_lifecycle_transitions")?;
+        let publisher = node.create_publisher::<msg::State>("test_topic", QOS_PROFILE_DEFAULT)?;
+        let previous_states = Arc::new(Mutex::new(Vec::new()));
+        {
+            let previous_states = Arc::clone(&previous_states);
+            node.set_on_configure(move |state| {
+                previous_states.lock().unwrap().push(state);
+                TransitionCallbackReturn::Success
+            });
+        }
+        assert_eq!(node.current_state(), LifecycleState::Unconfigured);
+        assert!(!publisher.is_activated());
+
+        assert_eq!(node.configure()?, LifecycleState::Inactive);
+        assert_eq!(node.current_state(), LifecycleState::Inactive);
+        assert!(!publisher.is_activated());
+
+        assert_eq!(node.activate()?, LifecycleState::Active);
+        assert_eq!(node.current_state(), LifecycleState::Active);
+        assert!(publisher.is_activated());
+
+        assert_eq!(node.deactivate()?, LifecycleState::Inactive);
+        assert_eq!(node.current_state(), LifecycleState::Inactive);
+        assert!(!publisher.is_activated());
+
+        // Transitions that don't start in the current state are rejected
+        assert!(node.deactivate().is_err());
+        assert_eq!(node.current_state(), LifecycleState::Inactive);
+
+        assert_eq!(
+            *previous_states.lock().unwrap(),
+            vec![LifecycleState::Unconfigured]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_failing_callbacks() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = LifecycleNode::new(&context, "test_lifecycle_failing_callbacks")?;
+
+        node.set_on_configure(|_| TransitionCallbackReturn::Failure);
+        assert_eq!(node.configure()?, LifecycleState::Unconfigured);
+
+        // An error is handled by the error callback, which can recover the node
+        node.set_on_configure(|_| TransitionCallbackReturn::Error);
+        node.set_on_error(|_| TransitionCallbackReturn::Success);
+        assert_eq!(node.configure()?, LifecycleState::Unconfigured);
+
+        node.set_on_error(|_| TransitionCallbackReturn::Failure);
+        assert_eq!(node.configure()?, LifecycleState::Finalized);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_transitions_are_rejected() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = Arc::new(LifecycleNode::new(
+            &context,
+            "test_lifecycle_nested_transitions",
+        )?);
+        let nested_result = Arc::new(Mutex::new(None));
+        {
+            let weak_node = Arc::downgrade(&node);
+            let nested_result = Arc::clone(&nested_result);
+            node.set_on_configure(move |_| {
+                let node = weak_node.upgrade().unwrap();
+                // The callbacks can access the node, but not trigger another transition
+                assert_eq!(node.current_state(), LifecycleState::Configuring);
+                *nested_result.lock().unwrap() = Some(node.activate());
+                TransitionCallbackReturn::Success
+            });
+        }
+        assert_eq!(node.configure()?, LifecycleState::Inactive);
+        assert!(matches!(*nested_result.lock().unwrap(), Some(Err(_))));
+
+        // The callback is still registered afterwards
+        assert_eq!(node.cleanup()?, LifecycleState::Unconfigured);
+        *nested_result.lock().unwrap() = None;
+        assert_eq!(node.configure()?, LifecycleState::Inactive);
+        assert!(nested_result.lock().unwrap().is_some());
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct CountingEntity {
+        activations: AtomicUsize,
+        deactivations: AtomicUsize,
+    }
+
+    impl ManagedEntity for CountingEntity {
+        fn on_activate(&self) {
+            self.activations.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_deactivate(&self) {
+            self.deactivations.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_entities_are_notified_when_the_active_state_changes() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = LifecycleNode::new(&context, "test_lifecycle_entity_notifications")?;
+        let entity = Arc::new(CountingEntity::default());
+        node.state_machine
+            .managed_entities_mtx
+            .lock()
+            .unwrap()
+            .push(Arc::downgrade(&entity) as Weak<dyn ManagedEntity>);
+        let counts = || {
+            (
+                entity.activations.load(Ordering::SeqCst),
+                entity.deactivations.load(Ordering::SeqCst),
+            )
+        };
+
+        node.configure()?;
+        assert_eq!(counts(), (0, 0));
+        node.set_on_activate(|_| TransitionCallbackReturn::Failure);
+        assert_eq!(node.activate()?, LifecycleState::Inactive);
+        assert_eq!(counts(), (0, 0));
+        node.set_on_activate(|_| TransitionCallbackReturn::Success);
+        node.activate()?;
+        assert_eq!(counts(), (1, 0));
+        node.deactivate()?;
+        assert_eq!(counts(), (1, 1));
+        node.shutdown()?;
+        assert_eq!(counts(), (1, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_services() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_lifecycle_services")?;
+        let lifecycle_node = LifecycleNode::from_node(Arc::clone(&graph.node1))?;
+        let fully_qualified_name = graph.node1.fully_qualified_name();
+        let change_state_client = graph
+            .node2
+            .create_client::<srv::ChangeState>(&format!("{fully_qualified_name}/change_state"))?;
+        let get_state_client = graph
+            .node2
+            .create_client::<srv::GetState>(&format!("{fully_qualified_name}/get_state"))?;
+        assert!(change_state_client.wait_for_service(Duration::from_secs(5))?);
+        assert!(get_state_client.wait_for_service(Duration::from_secs(5))?);
+
+        let done = Arc::new(AtomicBool::new(false));
+        let spin_thread = {
+            let (node1, node2, done) = (
+                Arc::clone(&graph.node1),
+                Arc::clone(&graph.node2),
+                Arc::clone(&done),
+            );
+            std::thread::spawn(move || {
+                while !done.load(Ordering::Acquire) {
+                    let _ = crate::spin_once(Arc::clone(&node1), Some(Duration::from_millis(10)));
+                    let _ = crate::spin_once(Arc::clone(&node2), Some(Duration::from_millis(10)));
+                }
+            })
+        };
+
+        let get_state = || -> Result<msg::State, RclrsError> {
+            let response: Arc<Mutex<Option<srv::GetState_Response>>> = Arc::default();
+            {
+                let response = Arc::clone(&response);
+                get_state_client.async_send_request_with_callback(
+                    &srv::GetState_Request::default(),
+                    move |r: srv::GetState_Response| *response.lock().unwrap() = Some(r),
+                )?;
+            }
+            let start = Instant::now();
+            loop {
+                if let Some(r) = response.lock().unwrap().take() {
+                    return Ok(r.current_state);
+                }
+                assert!(start.elapsed() < Duration::from_secs(5), "No response");
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        };
+        let change_state = |transition: msg::Transition| -> Result<bool, RclrsError> {
+            let response: Arc<Mutex<Option<srv::ChangeState_Response>>> = Arc::default();
+            {
+                let response = Arc::clone(&response);
+                change_state_client.async_send_request_with_callback(
+                    &srv::ChangeState_Request { transition },
+                    move |r: srv::ChangeState_Response| *response.lock().unwrap() = Some(r),
+                )?;
+            }
+            let start = Instant::now();
+            loop {
+                if let Some(r) = response.lock().unwrap().take() {
+                    return Ok(r.success);
+                }
+                assert!(start.elapsed() < Duration::from_secs(5), "No response");
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        };
+
+        let state = get_state()?;
+        assert_eq!(state.id, msg::State::PRIMARY_STATE_UNCONFIGURED);
+        assert_eq!(state.label, "unconfigured");
+
+        assert!(change_state(msg::Transition {
+            id: msg::Transition::TRANSITION_CONFIGURE,
+            label: String::new(),
+        })?);
+        assert_eq!(get_state()?.id, msg::State::PRIMARY_STATE_INACTIVE);
+
+        // Transitions can also be selected by their label
+        assert!(change_state(msg::Transition {
+            id: 0,
+            label: "activate".into(),
+        })?);
+        assert_eq!(get_state()?.id, msg::State::PRIMARY_STATE_ACTIVE);
+        assert_eq!(lifecycle_node.current_state(), LifecycleState::Active);
+
+        assert!(!change_state(msg::Transition {
+            id: msg::Transition::TRANSITION_CONFIGURE,
+            label: String::new(),
+        })?);
+        assert_eq!(get_state()?.id, msg::State::PRIMARY_STATE_ACTIVE);
+
+        done.store(true, Ordering::Release);
+        spin_thread.join().unwrap();
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use rosidl_runtime_rs::Message;
+
+use crate::{MessageCow, Publisher, RclrsError};
+
+/// An entity of a [`LifecycleNode`][1] that is enabled and disabled together with the node.
+///
+/// [1]: crate::LifecycleNode
+pub(crate) trait ManagedEntity: Send + Sync {
+    /// Enables the entity, which happens when the node becomes active.
+    fn on_activate(&self);
+    /// Disables the entity, which happens when the node stops being active.
+    fn on_deactivate(&self);
+}
+
+/// A publisher that only publishes messages while its [`LifecycleNode`][1] is active.
+///
+/// Lifecycle publishers are created with [`LifecycleNode::create_publisher()`][2]. They are
+/// activated when the node transitions to [`LifecycleState::Active`][3] and deactivated when it
+/// leaves that state. Messages published while the publisher is deactivated are dropped, and a
+/// warning is logged.
+///
+/// [1]: crate::LifecycleNode
+/// [2]: crate::LifecycleNode::create_publisher
+/// [3]: crate::LifecycleState::Active
+pub struct LifecyclePublisher<T>
+where
+    T: Message,
+{
+    publisher: Arc<Publisher<T>>,
+    logger_name: String,
+    activated: AtomicBool,
+    // Whether the warning about publishing while deactivated should be logged, which is done only
+    // once per deactivation.
+    should_warn: AtomicBool,
+}
+
+impl<T> LifecyclePublisher<T>
+where
+    T: Message,
+{
+    pub(crate) fn new(publisher: Arc<Publisher<T>>, logger_name: String, activated: bool) -> Self {
+        Self {
+            publisher,
+            logger_name,
+            activated: AtomicBool::new(activated),
+            should_warn: AtomicBool::new(true),
+        }
+    }
+
+    /// Returns the topic name of the publisher.
+    ///
+    /// See [`Publisher::topic_name()`].
+    pub fn topic_name(&self) -> String {
+        self.publisher.topic_name()
+    }
+
+    /// Returns `true` if the publisher currently publishes messages.
+    pub fn is_activated(&self) -> bool {
+        self.activated.load(Ordering::Acquire)
+    }
+
+    /// Publishes a message if the publisher is activated.
+    ///
+    /// If the publisher is deactivated, the message is dropped and `Ok(())` is returned.
+    /// See [`Publisher::publish()`] for the other errors that can occur.
+    pub fn publish<'a, M: MessageCow<'a, T>>(&self, message: M) -> Result<(), RclrsError> {
+        if !self.is_activated() {
+            if self.should_warn.swap(false, Ordering::AcqRel) {
+                crate::log_warn!(
+                    self.logger_name.as_str(),
+                    "Trying to publish a message on the topic '{}', but the publisher is not activated",
+                    self.topic_name()
+                );
+            }
+            return Ok(());
+        }
+        self.publisher.publish(message)
+    }
+}
+
+impl<T> ManagedEntity for LifecyclePublisher<T>
+where
+    T: Message,
+{
+    fn on_activate(&self) {
+        self.activated.store(true, Ordering::Release);
+    }
+
+    fn on_deactivate(&self) {
+        self.should_warn.store(true, Ordering::Release);
+        self.activated.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn traits() {
+        assert_send::<LifecyclePublisher<test_msgs::msg::BoundedSequences>>();
+        assert_sync::<LifecyclePublisher<test_msgs::msg::BoundedSequences>>();
+    }
+}
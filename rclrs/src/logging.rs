@@ -0,0 +1,959 @@
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int},
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use crate::{rcl_bindings::*, Node, RclrsError, Time, ToResult, ENTITY_LIFECYCLE_MUTEX};
+
+// The logging functions of rcutils are not thread-safe, so all calls into them are serialized.
+// Since log messages are published on /rosout with the rosout publisher of the node, this is also
+// locked when nodes are created and destroyed.
+pub(crate) static LOGGING_MUTEX: Mutex<()> = Mutex::new(());
+
+// Whether rcl logging has been configured. This is done once per process, by the first context.
+static LOGGING_CONFIGURED: Mutex<bool> = Mutex::new(false);
+
+// The handler set with set_logging_output_handler(), if any.
+type OutputHandler = Box<dyn Fn(&LogRecord) + Send + Sync>;
+static OUTPUT_HANDLER: Mutex<Option<OutputHandler>> = Mutex::new(None);
+
+/// The severity of a log message.
+///
+/// The values correspond to the `RCUTILS_LOG_SEVERITY` enum, and severities are ordered from
+/// least to most severe.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogSeverity {
+    /// Detailed information that is usually only relevant when debugging.
+    Debug = 10,
+    /// Information about the normal operation of the program.
+    Info = 20,
+    /// Something unexpected happened, but the program can continue normally.
+    Warn = 30,
+    /// Something went wrong, and the program may not be able to continue normally.
+    Error = 40,
+    /// Something went wrong, and the program cannot continue.
+    Fatal = 50,
+}
+
+impl LogSeverity {
+    // Converts an `RCUTILS_LOG_SEVERITY` value, returning `None` for unset and unknown levels.
+    fn from_rcutils(severity: i32) -> Option<Self> {
+        match severity {
+            10 => Some(Self::Debug),
+            20 => Some(Self::Info),
+            30 => Some(Self::Warn),
+            40 => Some(Self::Error),
+            50 => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+}
+
+/// Types that a logger name can be derived from, for use with the logging macros.
+///
+/// This is implemented for string types, which are used as the logger name directly, and for
+/// [`Node`], whose logger name is derived from its name and namespace.
+pub trait ToLoggerName {
+    /// Returns the name of the logger.
+    fn to_logger_name(&self) -> CString;
+}
+
+impl ToLoggerName for str {
+    /// Names containing nul bytes are cut off at the first one.
+    fn to_logger_name(&self) -> CString {
+        to_c_string_lossy(self)
+    }
+}
+
+impl ToLoggerName for Node {
+    fn to_logger_name(&self) -> CString {
+        to_c_string_lossy(&self.logger_name())
+    }
+}
+
+// Converts a string to a C string, cutting it off at the first nul byte.
+fn to_c_string_lossy(s: &str) -> CString {
+    let until_nul = s.split('\0').next().unwrap_or_default();
+    // The string cannot contain nul bytes anymore.
+    CString::new(until_nul).unwrap()
+}
+
+/// The time at which a throttled log message was last emitted, see [`log_throttle!`].
+///
+/// This is used by the throttled logging macros, which keep one instance per call site.
+#[doc(hidden)]
+pub struct LogThrottle {
+    // The time of the last emitted message in nanoseconds, if any.
+    last_logged: Mutex<Option<i64>>,
+}
+
+impl LogThrottle {
+    /// Creates a throttle that lets the next message pass.
+    pub const fn new() -> Self {
+        Self {
+            last_logged: Mutex::new(None),
+        }
+    }
+
+    /// Returns `true` if a message should be emitted at time `now`, and if so, records `now` as
+    /// the time of the last emitted message.
+    ///
+    /// A message is emitted if at least `period` has passed since the last one. If the clock
+    /// jumped back before the last emitted message, the throttle is reset and the message is
+    /// emitted.
+    pub fn should_log(&self, now: Time, period: Duration) -> bool {
+        let period_ns = i64::try_from(period.as_nanos()).unwrap_or(i64::MAX);
+        let mut last_logged = self.last_logged.lock().unwrap();
+        let should_log = match *last_logged {
+            Some(last_logged) => {
+                now.nsec < last_logged || now.nsec.saturating_sub(last_logged) >= period_ns
+            }
+            None => true,
+        };
+        if should_log {
+            *last_logged = Some(now.nsec);
+        }
+        should_log
+    }
+}
+
+impl Default for LogThrottle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configures rcl logging from the command line arguments of the context, e.g. `--log-level`,
+/// and enables logging to `/rosout`.
+///
+/// Only the first call has an effect, since logging is configured for the entire process.
+pub(crate) fn configure_logging(rcl_context: &rcl_context_t) -> Result<(), RclrsError> {
+    let mut configured = LOGGING_CONFIGURED.lock().unwrap();
+    if *configured {
+        return Ok(());
+    }
+    let _logging_lock = LOGGING_MUTEX.lock().unwrap();
+    let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+    // SAFETY: The global arguments are initialized by rcl_init(), and the allocator is copied.
+    unsafe {
+        let allocator = rcutils_get_default_allocator();
+        rcl_logging_configure(&rcl_context.global_arguments, &allocator)
+            .ok_for("rcl_logging_configure")?;
+        // Configuring logging installs the rcl output handler, which must not replace a
+        // handler that was set before.
+        if OUTPUT_HANDLER.lock().unwrap().is_some() {
+            rcutils_logging_set_output_handler(Some(output_handler_trampoline));
+        }
+    }
+    *configured = true;
+    Ok(())
+}
+
+/// A log message, as passed to a handler set with [`set_logging_output_handler()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogRecord {
+    /// The severity of the message.
+    pub severity: LogSeverity,
+    /// The name of the logger that emitted the message.
+    pub logger_name: String,
+    /// The formatted message.
+    pub message: String,
+    /// The function in which the message was logged.
+    pub function: String,
+    /// The source file in which the message was logged.
+    pub file: String,
+    /// The line in the source file at which the message was logged.
+    pub line: usize,
+    /// The time at which the message was logged.
+    pub timestamp: SystemTime,
+}
+
+/// Sets a function that receives all log messages of the process, instead of printing them.
+///
+/// This applies to messages logged with the logging macros of `rclrs`, as well as messages
+/// logged by `rcl` and the RMW implementation, and can be used e.g. to forward them to the
+/// `log` or `tracing` crates. Only messages that pass the [logger level][1] are passed to the
+/// handler.
+///
+/// The handler replaces the output handler of `rcl`, so the messages are neither printed to
+/// the console, nor published on `/rosout`, nor written to log files anymore. Setting another
+/// handler replaces the previous one.
+///
+/// The handler must not log messages itself, since logging is not reentrant, and it may be
+/// called from any thread.
+///
+/// # Example
+/// ```
+/// # use rclrs::{log_info, set_logging_output_handler};
+/// set_logging_output_handler(|record| {
+///     eprintln!("[{:?}] [{}]: {}", record.severity, record.logger_name, record.message);
+/// });
+/// log_info!("my_logger", "Hello");
+/// ```
+///
+/// [1]: set_logger_level
+pub fn set_logging_output_handler(handler: impl Fn(&LogRecord) + Send + Sync + 'static) {
+    let _logging_lock = LOGGING_MUTEX.lock().unwrap();
+    *OUTPUT_HANDLER.lock().unwrap() = Some(Box::new(handler));
+    // SAFETY: The trampoline is a valid function for the entire program, and the logging mutex
+    // is locked, since the output handler is a global variable.
+    unsafe { rcutils_logging_set_output_handler(Some(output_handler_trampoline)) };
+}
+
+// The output handler registered with rcutils, which passes the messages to the handler that was
+// set with set_logging_output_handler().
+unsafe extern "C" fn output_handler_trampoline(
+    location: *const rcutils_log_location_t,
+    severity: c_int,
+    name: *const c_char,
+    timestamp: rcutils_time_point_value_t,
+    format: *const c_char,
+    args: *mut va_list,
+) {
+    // rcutils only logs messages with one of the known severities.
+    let Some(severity) = LogSeverity::from_rcutils(severity) else {
+        return;
+    };
+    let to_string = |s: *const c_char| {
+        if s.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(s).to_string_lossy().into_owned()
+        }
+    };
+    let (function, file, line) = match location.as_ref() {
+        Some(location) => (
+            to_string(location.function_name),
+            to_string(location.file_name),
+            location.line_number,
+        ),
+        None => (String::new(), String::new(), 0),
+    };
+    let record = LogRecord {
+        severity,
+        logger_name: to_string(name),
+        message: format_message(format, args).unwrap_or_else(|| to_string(format)),
+        function,
+        file,
+        line,
+        timestamp: SystemTime::UNIX_EPOCH + Duration::from_nanos(timestamp.max(0) as u64),
+    };
+    if let Some(handler) = &*OUTPUT_HANDLER.lock().unwrap() {
+        // Unwinding into C code is undefined behavior.
+        let _ = catch_unwind(AssertUnwindSafe(|| handler(&record)));
+    }
+}
+
+// Formats a log message with its printf-style arguments.
+//
+// SAFETY: The format string and the arguments must be those passed to an output handler.
+unsafe fn format_message(format: *const c_char, args: *mut va_list) -> Option<String> {
+    let mut char_array = rcutils_get_zero_initialized_char_array();
+    let allocator = rcutils_get_default_allocator();
+    if rcutils_char_array_init(&mut char_array, 0, &allocator)
+        .ok_for("rcutils_char_array_init")
+        .is_err()
+    {
+        return None;
+    }
+    // A va_list is an array on x86_64, which is passed as a pointer to its first element, and
+    // a struct or a pointer on other platforms.
+    #[cfg(target_arch = "x86_64")]
+    let args = (*args).as_mut_ptr();
+    #[cfg(not(target_arch = "x86_64"))]
+    let args = *args;
+    let message = match rcutils_char_array_vsprintf(&mut char_array, format, args)
+        .ok_for("rcutils_char_array_vsprintf")
+    {
+        Ok(()) if !char_array.buffer.is_null() => Some(
+            CStr::from_ptr(char_array.buffer)
+                .to_string_lossy()
+                .into_owned(),
+        ),
+        _ => None,
+    };
+    rcutils_char_array_fini(&mut char_array);
+    message
+}
+
+/// Checks whether a message of the given severity would be logged by the logger.
+///
+/// This is used by the logging macros, to avoid formatting messages that are not logged.
+pub fn logger_is_enabled_for(logger_name: &CStr, severity: LogSeverity) -> bool {
+    let _logging_lock = LOGGING_MUTEX.lock().unwrap();
+    // SAFETY: Initializing logging has no preconditions, and does nothing if it was initialized
+    // before. The logger name is a valid C string.
+    unsafe {
+        if rcutils_logging_initialize()
+            .ok_for("rcutils_logging_initialize")
+            .is_err()
+        {
+            return false;
+        }
+        rcutils_logging_logger_is_enabled_for(logger_name.as_ptr(), severity as i32)
+    }
+}
+
+/// Sets the level of the logger with the given name, so that it only emits messages of `level`
+/// or higher severity.
+///
+/// This is the programmatic equivalent of `--log-level <logger_name>:=<level>` on the command
+/// line, and can be used e.g. to temporarily enable debug messages of a single node, whose
+/// logger name is returned by [`Node::logger_name()`]. Loggers whose name starts with
+/// `<logger_name>.` inherit the level, unless they have their own level. An empty logger name
+/// sets the default level for all loggers.
+///
+/// # Example
+/// ```
+/// # use rclrs::{log_debug, set_logger_level, Context, LogSeverity, RclrsError};
+/// let context = Context::new([])?;
+/// let node = rclrs::create_node(&context, "verbose_node")?;
+/// set_logger_level(&node.logger_name(), LogSeverity::Debug)?;
+/// log_debug!(&node, "This message is emitted");
+/// # Ok::<(), RclrsError>(())
+/// ```
+pub fn set_logger_level(logger_name: &str, level: LogSeverity) -> Result<(), RclrsError> {
+    let logger_name_c_string =
+        CString::new(logger_name).map_err(|err| RclrsError::StringContainsNul {
+            err,
+            s: logger_name.into(),
+        })?;
+    let _logging_lock = LOGGING_MUTEX.lock().unwrap();
+    // SAFETY: Initializing logging has no preconditions, and does nothing if it was initialized
+    // before. The logger name is a valid C string, which is copied by rcutils.
+    unsafe {
+        rcutils_logging_initialize().ok_for("rcutils_logging_initialize")?;
+        rcutils_logging_set_logger_level(logger_name_c_string.as_ptr(), level as i32)
+            .ok_for("rcutils_logging_set_logger_level")
+    }
+}
+
+/// Returns the level that was set for the logger with the given name, with
+/// [`set_logger_level()`] or on the command line.
+///
+/// Returns `None` if no level was set for this logger specifically, in which case it inherits
+/// the level of its closest ancestor or the default level. Use [`logger_is_enabled_for()`] to
+/// check whether a message of some severity would be emitted.
+pub fn get_logger_level(logger_name: &str) -> Option<LogSeverity> {
+    let logger_name = to_c_string_lossy(logger_name);
+    let _logging_lock = LOGGING_MUTEX.lock().unwrap();
+    // SAFETY: Initializing logging has no preconditions, and does nothing if it was initialized
+    // before. The logger name is a valid C string.
+    let level = unsafe {
+        if rcutils_logging_initialize()
+            .ok_for("rcutils_logging_initialize")
+            .is_err()
+        {
+            return None;
+        }
+        rcutils_logging_get_logger_level(logger_name.as_ptr())
+    };
+    LogSeverity::from_rcutils(level)
+}
+
+/// Logs a message, without checking whether the logger is enabled for the severity.
+///
+/// This is used by the logging macros, see [`log!`](crate::log).
+#[doc(hidden)]
+pub fn log_unconditionally(
+    logger_name: &CStr,
+    severity: LogSeverity,
+    message: &str,
+    function: &str,
+    file: &str,
+    line: u32,
+) {
+    // The message is used as the format string, so that output handlers receive it without
+    // having to process any arguments. Percent signs are escaped for this.
+    let message = to_c_string_lossy(&message.replace('%', "%%"));
+    let function_name = to_c_string_lossy(function);
+    let file_name = to_c_string_lossy(file);
+    let location = rcutils_log_location_t {
+        function_name: function_name.as_ptr(),
+        file_name: file_name.as_ptr(),
+        line_number: line as usize,
+    };
+    let _logging_lock = LOGGING_MUTEX.lock().unwrap();
+    // SAFETY: All pointers are valid for the duration of the call, and the format string
+    // doesn't contain any conversion specifiers, since percent signs are escaped.
+    unsafe {
+        rcutils_log(
+            &location,
+            severity as i32,
+            logger_name.as_ptr(),
+            message.as_ptr(),
+        );
+    }
+}
+
+/// Logs a message with the given [`LogSeverity`].
+///
+/// The first argument after the severity is the logger, which can be a string or a
+/// [`Node`](crate::Node), see [`ToLoggerName`](crate::ToLoggerName). The remaining arguments
+/// are the same as for [`format!`].
+///
+/// Messages are routed through `rcutils` logging, so they are printed to the console and
+/// published on `/rosout`, and they honor the log levels set e.g. with `--log-level`. The
+/// message is only formatted if the logger is enabled for the severity.
+///
+/// Usually, one of the macros for a specific severity is used instead, e.g. [`log_info!`].
+///
+/// # Example
+/// ```
+/// # use rclrs::{log, Context, LogSeverity, RclrsError};
+/// let context = Context::new([])?;
+/// let node = rclrs::create_node(&context, "my_node")?;
+/// log!(LogSeverity::Info, &node, "The answer is {}", 42);
+/// log!(LogSeverity::Warn, "my_logger", "Something unexpected happened");
+/// # Ok::<(), RclrsError>(())
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($severity:expr, $logger:expr, $($arg:tt)+) => {{
+        #[allow(unused_imports)]
+        use $crate::ToLoggerName as _;
+        let logger_name = ($logger).to_logger_name();
+        let severity: $crate::LogSeverity = $severity;
+        if $crate::logger_is_enabled_for(&logger_name, severity) {
+            $crate::log_unconditionally(
+                &logger_name,
+                severity,
+                &::std::format!($($arg)+),
+                $crate::__function_name!(),
+                ::std::file!(),
+                ::std::line!(),
+            );
+        }
+    }};
+}
+
+/// Logs a message with [`LogSeverity::Debug`](crate::LogSeverity::Debug), see [`log!`].
+#[macro_export]
+macro_rules! log_debug {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($crate::LogSeverity::Debug, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Info`](crate::LogSeverity::Info), see [`log!`].
+#[macro_export]
+macro_rules! log_info {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($crate::LogSeverity::Info, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Warn`](crate::LogSeverity::Warn), see [`log!`].
+#[macro_export]
+macro_rules! log_warn {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($crate::LogSeverity::Warn, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Error`](crate::LogSeverity::Error), see [`log!`].
+#[macro_export]
+macro_rules! log_error {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($crate::LogSeverity::Error, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Fatal`](crate::LogSeverity::Fatal), see [`log!`].
+#[macro_export]
+macro_rules! log_fatal {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log!($crate::LogSeverity::Fatal, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with the given [`LogSeverity`], at most once per `period` of the given
+/// [`Clock`](crate::Clock).
+///
+/// The arguments after the period are the same as for [`log!`]. Each use of the macro is
+/// throttled independently, and messages that are dropped by the throttle are not formatted.
+///
+/// If the clock jumps back, e.g. when a simulation is restarted, the next message is emitted
+/// and the period starts again from that time.
+///
+/// Usually, one of the macros for a specific severity is used instead, e.g.
+/// [`log_info_throttle!`].
+///
+/// # Example
+/// ```
+/// # use rclrs::{log_info_throttle, Context, RclrsError};
+/// # use std::time::Duration;
+/// let context = Context::new([])?;
+/// let node = rclrs::create_node(&context, "my_node")?;
+/// let clock = node.get_clock();
+/// for i in 0..1000 {
+///     // Only the first iteration is logged, unless the loop takes longer than a second
+///     log_info_throttle!(clock, Duration::from_secs(1), &node, "Iteration {}", i);
+/// }
+/// # Ok::<(), RclrsError>(())
+/// ```
+#[macro_export]
+macro_rules! log_throttle {
+    ($severity:expr, $clock:expr, $period:expr, $logger:expr, $($arg:tt)+) => {{
+        static THROTTLE: $crate::LogThrottle = $crate::LogThrottle::new();
+        #[allow(unused_imports)]
+        use $crate::ToLoggerName as _;
+        let logger_name = ($logger).to_logger_name();
+        let severity: $crate::LogSeverity = $severity;
+        if $crate::logger_is_enabled_for(&logger_name, severity)
+            && THROTTLE.should_log(($clock).now(), $period)
+        {
+            $crate::log_unconditionally(
+                &logger_name,
+                severity,
+                &::std::format!($($arg)+),
+                $crate::__function_name!(),
+                ::std::file!(),
+                ::std::line!(),
+            );
+        }
+    }};
+}
+
+/// Logs a message with [`LogSeverity::Debug`](crate::LogSeverity::Debug), at most once per
+/// period, see [`log_throttle!`].
+#[macro_export]
+macro_rules! log_debug_throttle {
+    ($clock:expr, $period:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log_throttle!($crate::LogSeverity::Debug, $clock, $period, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Info`](crate::LogSeverity::Info), at most once per
+/// period, see [`log_throttle!`].
+#[macro_export]
+macro_rules! log_info_throttle {
+    ($clock:expr, $period:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log_throttle!($crate::LogSeverity::Info, $clock, $period, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Warn`](crate::LogSeverity::Warn), at most once per
+/// period, see [`log_throttle!`].
+#[macro_export]
+macro_rules! log_warn_throttle {
+    ($clock:expr, $period:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log_throttle!($crate::LogSeverity::Warn, $clock, $period, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Error`](crate::LogSeverity::Error), at most once per
+/// period, see [`log_throttle!`].
+#[macro_export]
+macro_rules! log_error_throttle {
+    ($clock:expr, $period:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log_throttle!($crate::LogSeverity::Error, $clock, $period, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Fatal`](crate::LogSeverity::Fatal), at most once per
+/// period, see [`log_throttle!`].
+#[macro_export]
+macro_rules! log_fatal_throttle {
+    ($clock:expr, $period:expr, $logger:expr, $($arg:tt)+) => {
+        $crate::log_throttle!($crate::LogSeverity::Fatal, $clock, $period, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with the given [`LogSeverity`], only the first time the call site is reached.
+///
+/// The arguments are the same as for [`log!`]. Each use of the macro is tracked independently,
+/// for the lifetime of the process, which is useful e.g. for startup banners and warnings that
+/// would otherwise be repeated in a loop.
+///
+/// A call site only counts as reached if the logger is enabled for the severity, so a message
+/// that was suppressed by the [logger level][1] is still emitted once the level is lowered.
+///
+/// Usually, one of the macros for a specific severity is used instead, e.g. [`log_info_once!`].
+///
+/// # Example
+/// ```
+/// # use rclrs::{log_warn_once, Context, RclrsError};
+/// let context = Context::new([])?;
+/// let node = rclrs::create_node(&context, "my_node")?;
+/// for i in 0..1000 {
+///     // Only logged in the first iteration
+///     log_warn_once!(&node, "The loop started at iteration {}", i);
+/// }
+/// # Ok::<(), RclrsError>(())
+/// ```
+///
+/// [1]: crate::set_logger_level
+#[macro_export]
+macro_rules! log_once {
+    ($severity:expr, $logger:expr, $($arg:tt)+) => {{
+        static LOGGED: ::std::sync::atomic::AtomicBool = ::std::sync::atomic::AtomicBool::new(false);
+        #[allow(unused_imports)]
+        use $crate::ToLoggerName as _;
+        let logger_name = ($logger).to_logger_name();
+        let severity: $crate::LogSeverity = $severity;
+        if $crate::logger_is_enabled_for(&logger_name, severity)
+            && !LOGGED.swap(true, ::std::sync::atomic::Ordering::Relaxed)
+        {
+            $crate::log_unconditionally(
+                &logger_name,
+                severity,
+                &::std::format!($($arg)+),
+                $crate::__function_name!(),
+                ::std::file!(),
+                ::std::line!(),
+            );
+        }
+    }};
+}
+
+/// Logs a message with [`LogSeverity::Debug`](crate::LogSeverity::Debug), only the first time
+/// the call site is reached, see [`log_once!`].
+#[macro_export]
+macro_rules! log_debug_once {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log_once!($crate::LogSeverity::Debug, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Info`](crate::LogSeverity::Info), only the first time
+/// the call site is reached, see [`log_once!`].
+#[macro_export]
+macro_rules! log_info_once {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log_once!($crate::LogSeverity::Info, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Warn`](crate::LogSeverity::Warn), only the first time
+/// the call site is reached, see [`log_once!`].
+#[macro_export]
+macro_rules! log_warn_once {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log_once!($crate::LogSeverity::Warn, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Error`](crate::LogSeverity::Error), only the first time
+/// the call site is reached, see [`log_once!`].
+#[macro_export]
+macro_rules! log_error_once {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log_once!($crate::LogSeverity::Error, $logger, $($arg)+)
+    };
+}
+
+/// Logs a message with [`LogSeverity::Fatal`](crate::LogSeverity::Fatal), only the first time
+/// the call site is reached, see [`log_once!`].
+#[macro_export]
+macro_rules! log_fatal_once {
+    ($logger:expr, $($arg:tt)+) => {
+        $crate::log_once!($crate::LogSeverity::Fatal, $logger, $($arg)+)
+    };
+}
+
+/// Expands to the path of the function it is used in, for the location of log messages.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __function_name {
+    () => {{
+        fn f() {}
+        fn type_name_of<T>(_: T) -> &'static str {
+            ::std::any::type_name::<T>()
+        }
+        let name = type_name_of(f);
+        // Remove the "::f" of the helper function.
+        &name[..name.len() - 3]
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_node, Clock, Context};
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Once,
+    };
+
+    // The log messages received by the output handler.
+    static CAPTURED: Mutex<Vec<LogRecord>> = Mutex::new(Vec::new());
+
+    // Installs an output handler that captures all messages, and configures logging by creating
+    // a context.
+    fn capture_log_output() -> Result<Context, RclrsError> {
+        static INSTALL_HANDLER: Once = Once::new();
+        INSTALL_HANDLER.call_once(|| {
+            set_logging_output_handler(|record| CAPTURED.lock().unwrap().push(record.clone()));
+        });
+        Context::new([])
+    }
+
+    fn captured_records(logger_name: &str) -> Vec<LogRecord> {
+        CAPTURED
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|record| record.logger_name == logger_name)
+            .cloned()
+            .collect()
+    }
+
+    fn captured_messages(logger_name: &str) -> Vec<(c_int, String)> {
+        captured_records(logger_name)
+            .into_iter()
+            .map(|record| (record.severity as c_int, record.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_log_level_gating() -> Result<(), RclrsError> {
+        let _context = capture_log_output()?;
+        let logger_name = "test_log_level_gating";
+        set_logger_level(logger_name, LogSeverity::Warn)?;
+
+        log_debug!(logger_name, "debug {}", 1);
+        log_info!(logger_name, "info {}", 2);
+        log_warn!(logger_name, "warn {}", 3);
+        log_error!(logger_name, "error {}", 4);
+        log_fatal!(logger_name, "fatal {}", 5);
+
+        assert_eq!(
+            captured_messages(logger_name),
+            vec![
+                (LogSeverity::Warn as c_int, "warn 3".to_string()),
+                (LogSeverity::Error as c_int, "error 4".to_string()),
+                (LogSeverity::Fatal as c_int, "fatal 5".to_string()),
+            ]
+        );
+
+        set_logger_level(logger_name, LogSeverity::Debug)?;
+        log_debug!(logger_name, "debug {}", 6);
+        assert_eq!(
+            captured_messages(logger_name).last(),
+            Some(&(LogSeverity::Debug as c_int, "debug 6".to_string()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_disabled_messages_are_not_formatted() -> Result<(), RclrsError> {
+        let _context = capture_log_output()?;
+        let logger_name = "test_disabled_messages_are_not_formatted";
+        set_logger_level(logger_name, LogSeverity::Error)?;
+
+        let formatted = AtomicBool::new(false);
+        let format_arg = || {
+            formatted.store(true, Ordering::Release);
+            "argument"
+        };
+        log_info!(logger_name, "{}", format_arg());
+        assert!(!formatted.load(Ordering::Acquire));
+        log_error!(logger_name, "{}", format_arg());
+        assert!(formatted.load(Ordering::Acquire));
+        Ok(())
+    }
+
+    #[test]
+    fn test_node_logger() -> Result<(), RclrsError> {
+        let context = capture_log_output()?;
+        let node = create_node(&context, "test_node_logger")?;
+        assert_eq!(node.logger_name(), "test_node_logger");
+
+        log_info!(&node, "Hello from {}", node.name());
+        log!(
+            LogSeverity::Warn,
+            node,
+            "Percent signs are not format specifiers: %s"
+        );
+
+        assert_eq!(
+            captured_messages("test_node_logger"),
+            vec![
+                (
+                    LogSeverity::Info as c_int,
+                    "Hello from test_node_logger".to_string()
+                ),
+                (
+                    LogSeverity::Warn as c_int,
+                    "Percent signs are not format specifiers: %s".to_string()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_name() {
+        assert_eq!(
+            crate::__function_name!(),
+            "rclrs::logging::tests::test_function_name"
+        );
+    }
+
+    #[test]
+    fn test_throttled_logging() -> Result<(), RclrsError> {
+        let _context = capture_log_output()?;
+        let logger_name = "test_throttled_logging";
+        set_logger_level(logger_name, LogSeverity::Info)?;
+        let (clock, source) = Clock::with_source();
+        let period = Duration::from_millis(100);
+
+        // Advances the clock by 10 ms per iteration, for a total of one second
+        for i in 0..100 {
+            source.set_ros_time_override(1_000_000_000 + i * 10_000_000);
+            log_info_throttle!(clock, period, logger_name, "iteration {}", i);
+        }
+        let messages = captured_messages(logger_name);
+        assert_eq!(messages.len(), 10);
+        for (j, (severity, message)) in messages.iter().enumerate() {
+            assert_eq!(*severity, LogSeverity::Info as c_int);
+            assert_eq!(*message, format!("iteration {}", j * 10));
+        }
+
+        // A different call site is throttled independently
+        log_warn_throttle!(clock, period, logger_name, "other call site");
+        assert_eq!(captured_messages(logger_name).len(), 11);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_throttle_resets_on_backward_jump() {
+        let throttle = LogThrottle::new();
+        let (clock, source) = Clock::with_source();
+        let period = Duration::from_secs(1);
+
+        source.set_ros_time_override(10_000_000_000);
+        assert!(throttle.should_log(clock.now(), period));
+        source.set_ros_time_override(10_500_000_000);
+        assert!(!throttle.should_log(clock.now(), period));
+
+        // After jumping back, the next message is emitted and the period starts again
+        source.set_ros_time_override(2_000_000_000);
+        assert!(throttle.should_log(clock.now(), period));
+        source.set_ros_time_override(2_500_000_000);
+        assert!(!throttle.should_log(clock.now(), period));
+        source.set_ros_time_override(3_000_000_000);
+        assert!(throttle.should_log(clock.now(), period));
+    }
+
+    #[test]
+    fn test_set_and_get_logger_level() -> Result<(), RclrsError> {
+        let _context = capture_log_output()?;
+        let logger_name = "test_set_and_get_logger_level";
+        let logger_c_name = logger_name.to_logger_name();
+        set_logger_level(logger_name, LogSeverity::Info)?;
+        assert_eq!(get_logger_level(logger_name), Some(LogSeverity::Info));
+        assert!(!logger_is_enabled_for(&logger_c_name, LogSeverity::Debug));
+
+        set_logger_level(logger_name, LogSeverity::Debug)?;
+        assert_eq!(get_logger_level(logger_name), Some(LogSeverity::Debug));
+        assert!(logger_is_enabled_for(&logger_c_name, LogSeverity::Debug));
+
+        // Child loggers inherit the level, other loggers are unaffected.
+        let child_name = format!("{logger_name}.child").to_logger_name();
+        assert!(logger_is_enabled_for(&child_name, LogSeverity::Debug));
+        assert_eq!(get_logger_level(&format!("{logger_name}.child")), None);
+        set_logger_level("test_set_and_get_logger_level_other", LogSeverity::Info)?;
+        let other_name = "test_set_and_get_logger_level_other".to_logger_name();
+        assert!(!logger_is_enabled_for(&other_name, LogSeverity::Debug));
+
+        assert!(matches!(
+            set_logger_level("nul\0logger", LogSeverity::Debug),
+            Err(RclrsError::StringContainsNul { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_logging_output_handler() -> Result<(), RclrsError> {
+        let _context = capture_log_output()?;
+        let logger_name = "test_logging_output_handler";
+        set_logger_level(logger_name, LogSeverity::Info)?;
+        let before = SystemTime::now();
+        let line = line!() + 1;
+        log_warn!(logger_name, "{} bottles of %s", 99);
+        log_debug!(logger_name, "Below the logger level");
+
+        let records = captured_records(logger_name);
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.severity, LogSeverity::Warn);
+        assert_eq!(record.message, "99 bottles of %s");
+        assert_eq!(record.file, file!());
+        assert_eq!(record.line, line as usize);
+        assert!(record.function.ends_with("test_logging_output_handler"));
+        // The timestamp is only precise to what the system clock offers.
+        assert!(record.timestamp + Duration::from_secs(1) > before);
+        Ok(())
+    }
+
+    #[test]
+    fn test_messages_with_format_arguments() -> Result<(), RclrsError> {
+        let _context = capture_log_output()?;
+        let logger_name = "test_messages_with_format_arguments";
+        set_logger_level(logger_name, LogSeverity::Info)?;
+        let name = logger_name.to_logger_name();
+        let format = CString::new("%s: %d").unwrap();
+        let argument = CString::new("answer").unwrap();
+        let location = rcutils_log_location_t {
+            function_name: std::ptr::null(),
+            file_name: std::ptr::null(),
+            line_number: 0,
+        };
+        {
+            let _logging_lock = LOGGING_MUTEX.lock().unwrap();
+            // SAFETY: The format string matches the arguments, and all pointers are valid.
+            unsafe {
+                rcutils_log(
+                    &location,
+                    LogSeverity::Info as c_int,
+                    name.as_ptr(),
+                    format.as_ptr(),
+                    argument.as_ptr(),
+                    42 as c_int,
+                );
+            }
+        }
+        assert_eq!(
+            captured_messages(logger_name),
+            vec![(LogSeverity::Info as c_int, "answer: 42".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_once_logging() -> Result<(), RclrsError> {
+        let _context = capture_log_output()?;
+        let logger_name = "test_once_logging";
+        set_logger_level(logger_name, LogSeverity::Warn)?;
+
+        for i in 0..10 {
+            // Suppressed by the logger level, so this doesn't count as reached.
+            log_info_once!(logger_name, "info {}", i);
+            log_warn_once!(logger_name, "warn {}", i);
+            log_error_once!(logger_name, "error {}", i);
+            if i == 4 {
+                set_logger_level(logger_name, LogSeverity::Info)?;
+            }
+        }
+        // Another call site with the same message is tracked independently.
+        log_warn_once!(logger_name, "warn {}", 10);
+
+        assert_eq!(
+            captured_messages(logger_name),
+            vec![
+                (LogSeverity::Warn as c_int, "warn 0".to_string()),
+                (LogSeverity::Error as c_int, "error 0".to_string()),
+                (LogSeverity::Info as c_int, "info 5".to_string()),
+                (LogSeverity::Warn as c_int, "warn 10".to_string()),
+            ]
+        );
+        Ok(())
+    }
+}
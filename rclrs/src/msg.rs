@@ -0,0 +1,11 @@
+//! Message types of the ROS 2 core interfaces that are part of the API of `rclrs`.
+//!
+//! `rclrs` contains its own copy of these interfaces, so these are separate types from the ones
+//! generated for the corresponding packages, e.g. `rcl_interfaces::msg::ParameterEvent`. They
+//! have the same type names and fields, so they can be used to communicate with other nodes.
+
+pub use crate::vendor::builtin_interfaces::msg::{Duration, Time};
+pub use crate::vendor::lifecycle_msgs::msg::Transition;
+pub use crate::vendor::rcl_interfaces::msg::{
+    ListParametersResult, ParameterEvent, SetParametersResult,
+};
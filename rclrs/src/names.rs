@@ -0,0 +1,504 @@
+use std::{
+    error::Error,
+    ffi::CString,
+    fmt::{self, Display},
+    os::raw::c_int,
+};
+
+use crate::{rcl_bindings::*, RclrsError, ToResult};
+
+// The validation results of rmw_validate_node_name(), see rmw/validate_node_name.h.
+const RMW_NODE_NAME_VALID: c_int = 0;
+const RMW_NODE_NAME_INVALID_IS_EMPTY_STRING: c_int = 1;
+const RMW_NODE_NAME_INVALID_CONTAINS_UNALLOWED_CHARACTERS: c_int = 2;
+const RMW_NODE_NAME_INVALID_STARTS_WITH_NUMBER: c_int = 3;
+const RMW_NODE_NAME_INVALID_TOO_LONG: c_int = 4;
+
+// The validation results of rmw_validate_namespace(), see rmw/validate_namespace.h.
+const RMW_NAMESPACE_VALID: c_int = 0;
+const RMW_NAMESPACE_INVALID_IS_EMPTY_STRING: c_int = 1;
+const RMW_NAMESPACE_INVALID_NOT_ABSOLUTE: c_int = 2;
+const RMW_NAMESPACE_INVALID_ENDS_WITH_FORWARD_SLASH: c_int = 3;
+const RMW_NAMESPACE_INVALID_CONTAINS_UNALLOWED_CHARACTERS: c_int = 4;
+const RMW_NAMESPACE_INVALID_CONTAINS_REPEATED_FORWARD_SLASH: c_int = 5;
+const RMW_NAMESPACE_INVALID_NAME_TOKEN_STARTS_WITH_NUMBER: c_int = 6;
+const RMW_NAMESPACE_INVALID_TOO_LONG: c_int = 7;
+
+// The validation results of rmw_validate_full_topic_name(), see
+// rmw/validate_full_topic_name.h.
+const RMW_TOPIC_VALID: c_int = 0;
+const RMW_TOPIC_INVALID_IS_EMPTY_STRING: c_int = 1;
+const RMW_TOPIC_INVALID_NOT_ABSOLUTE: c_int = 2;
+const RMW_TOPIC_INVALID_ENDS_WITH_FORWARD_SLASH: c_int = 3;
+const RMW_TOPIC_INVALID_CONTAINS_UNALLOWED_CHARACTERS: c_int = 4;
+const RMW_TOPIC_INVALID_CONTAINS_REPEATED_FORWARD_SLASH: c_int = 5;
+const RMW_TOPIC_INVALID_NAME_TOKEN_STARTS_WITH_NUMBER: c_int = 6;
+const RMW_TOPIC_INVALID_TOO_LONG: c_int = 7;
+
+// The validation results of rcl_validate_topic_name(), see rcl/validate_topic_name.h.
+const RCL_TOPIC_NAME_VALID: c_int = 0;
+const RCL_TOPIC_NAME_INVALID_IS_EMPTY_STRING: c_int = 1;
+const RCL_TOPIC_NAME_INVALID_ENDS_WITH_FORWARD_SLASH: c_int = 2;
+const RCL_TOPIC_NAME_INVALID_CONTAINS_UNALLOWED_CHARACTERS: c_int = 3;
+const RCL_TOPIC_NAME_INVALID_NAME_TOKEN_STARTS_WITH_NUMBER: c_int = 4;
+const RCL_TOPIC_NAME_INVALID_UNMATCHED_CURLY_BRACE: c_int = 5;
+const RCL_TOPIC_NAME_INVALID_MISPLACED_TILDE: c_int = 6;
+const RCL_TOPIC_NAME_INVALID_TILDE_NOT_FOLLOWED_BY_FORWARD_SLASH: c_int = 7;
+const RCL_TOPIC_NAME_INVALID_SUBSTITUTION_CONTAINS_UNALLOWED_CHARACTERS: c_int = 8;
+const RCL_TOPIC_NAME_INVALID_SUBSTITUTION_STARTS_WITH_NUMBER: c_int = 9;
+
+/// The kind of name that was validated, see [`InvalidNameError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NameKind {
+    /// A topic or service name.
+    Topic,
+    /// A node name.
+    Node,
+    /// A node namespace.
+    Namespace,
+}
+
+/// The rule that an invalid name violates, see [`InvalidNameError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NameViolation {
+    /// The name is empty.
+    IsEmptyString,
+    /// The namespace does not start with a `/`.
+    NotAbsolute,
+    /// The name ends with a `/`.
+    EndsWithForwardSlash,
+    /// The name contains a character other than `a-z`, `A-Z`, `0-9`, `_` and, depending on the
+    /// kind of name, `/`, `~`, `{` and `}`.
+    ContainsUnallowedCharacters,
+    /// The name contains two or more `/` in a row.
+    ContainsRepeatedForwardSlash,
+    /// The node name starts with a number.
+    StartsWithNumber,
+    /// A token of the name, i.e. the start of the name or a part after a `/`, starts with a
+    /// number.
+    NameTokenStartsWithNumber,
+    /// The topic name contains a `{` without a matching `}` or vice versa.
+    UnmatchedCurlyBrace,
+    /// The topic name contains a `~` that is not its first character.
+    MisplacedTilde,
+    /// The `~` at the start of the topic name is not followed by a `/`.
+    TildeNotFollowedByForwardSlash,
+    /// A substitution, e.g. `{node}`, contains a character that is not allowed.
+    SubstitutionContainsUnallowedCharacters,
+    /// A substitution, e.g. `{node}`, starts with a number.
+    SubstitutionStartsWithNumber,
+    /// The name is longer than the maximum length allowed by the middleware.
+    TooLong,
+}
+
+impl Display for NameViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::IsEmptyString => "must not be empty",
+            Self::NotAbsolute => "must start with '/'",
+            Self::EndsWithForwardSlash => "must not end with '/'",
+            Self::ContainsUnallowedCharacters => "contains a character that is not allowed",
+            Self::ContainsRepeatedForwardSlash => "must not contain repeated '/'",
+            Self::StartsWithNumber => "must not start with a number",
+            Self::NameTokenStartsWithNumber => {
+                "must not have a number at the beginning or after a '/'"
+            }
+            Self::UnmatchedCurlyBrace => "contains an unmatched curly brace",
+            Self::MisplacedTilde => "must only contain '~' as the first character",
+            Self::TildeNotFollowedByForwardSlash => "must have a '/' after the leading '~'",
+            Self::SubstitutionContainsUnallowedCharacters => {
+                "contains a substitution with a character that is not allowed"
+            }
+            Self::SubstitutionStartsWithNumber => {
+                "contains a substitution that starts with a number"
+            }
+            Self::TooLong => "is too long",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A topic name, node name or namespace that is not valid.
+///
+/// This is returned inside [`RclrsError::InvalidName`] by [`validate_topic_name()`],
+/// [`validate_node_name()`] and [`validate_namespace()`], and by the functions that create
+/// nodes and endpoints.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidNameError {
+    /// The invalid name.
+    pub name: String,
+    /// The kind of the name.
+    pub kind: NameKind,
+    /// The rule that the name violates.
+    pub violation: NameViolation,
+    /// The index of the byte in `name` at which the violation was detected.
+    pub invalid_index: usize,
+}
+
+impl Display for InvalidNameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            NameKind::Topic => "topic name",
+            NameKind::Node => "node name",
+            NameKind::Namespace => "namespace",
+        };
+        write!(
+            f,
+            "The {} '{}' is invalid: it {} (at index {})",
+            kind, self.name, self.violation, self.invalid_index
+        )
+    }
+}
+
+impl Error for InvalidNameError {}
+
+/// Checks that `name` is a valid topic or service name.
+///
+/// Besides fully qualified names like `/foo/bar`, this accepts relative names like `foo`,
+/// private names like `~/foo` and names with substitutions like `{node}/foo`, i.e. any name
+/// that can be passed to e.g. [`Node::create_publisher()`][1]. See the
+/// [rules for topic and service names][2].
+///
+/// # Example
+/// ```
+/// # use rclrs::{validate_topic_name, InvalidNameError, NameViolation, RclrsError};
+/// assert!(validate_topic_name("~/chatter").is_ok());
+/// assert!(matches!(
+///     validate_topic_name("chatter/").unwrap_err(),
+///     RclrsError::InvalidName(InvalidNameError {
+///         violation: NameViolation::EndsWithForwardSlash,
+///         invalid_index: 7,
+///         ..
+///     })
+/// ));
+/// ```
+///
+/// [1]: crate::Node::create_publisher
+/// [2]: http://design.ros2.org/articles/topic_and_service_names.html
+pub fn validate_topic_name(name: &str) -> Result<(), RclrsError> {
+    let c_name = to_cstring(name, NameKind::Topic)?;
+    let mut validation_result = 0;
+    let mut invalid_index = 0;
+    // SAFETY: The name is a valid nul-terminated string, and the out-parameters are valid.
+    unsafe { rcl_validate_topic_name(c_name.as_ptr(), &mut validation_result, &mut invalid_index) }
+        .ok_for("rcl_validate_topic_name")?;
+    let violation = match validation_result {
+        RCL_TOPIC_NAME_VALID => None,
+        RCL_TOPIC_NAME_INVALID_IS_EMPTY_STRING => Some(NameViolation::IsEmptyString),
+        RCL_TOPIC_NAME_INVALID_ENDS_WITH_FORWARD_SLASH => Some(NameViolation::EndsWithForwardSlash),
+        RCL_TOPIC_NAME_INVALID_CONTAINS_UNALLOWED_CHARACTERS => {
+            Some(NameViolation::ContainsUnallowedCharacters)
+        }
+        RCL_TOPIC_NAME_INVALID_NAME_TOKEN_STARTS_WITH_NUMBER => {
+            Some(NameViolation::NameTokenStartsWithNumber)
+        }
+        RCL_TOPIC_NAME_INVALID_UNMATCHED_CURLY_BRACE => Some(NameViolation::UnmatchedCurlyBrace),
+        RCL_TOPIC_NAME_INVALID_MISPLACED_TILDE => Some(NameViolation::MisplacedTilde),
+        RCL_TOPIC_NAME_INVALID_TILDE_NOT_FOLLOWED_BY_FORWARD_SLASH => {
+            Some(NameViolation::TildeNotFollowedByForwardSlash)
+        }
+        RCL_TOPIC_NAME_INVALID_SUBSTITUTION_CONTAINS_UNALLOWED_CHARACTERS => {
+            Some(NameViolation::SubstitutionContainsUnallowedCharacters)
+        }
+        RCL_TOPIC_NAME_INVALID_SUBSTITUTION_STARTS_WITH_NUMBER => {
+            Some(NameViolation::SubstitutionStartsWithNumber)
+        }
+        _ => Some(NameViolation::ContainsUnallowedCharacters),
+    };
+    if let Some(violation) = violation {
+        return Err(invalid_name(
+            name,
+            NameKind::Topic,
+            violation,
+            invalid_index,
+        ));
+    }
+    // rcl_validate_topic_name() does not check for repeated forward slashes and the maximum
+    // length, which are only checked for the fully qualified name. Names with a tilde or
+    // substitutions can only be fully qualified by the node, so only the other names are
+    // checked here.
+    if name.starts_with('~') || name.contains('{') {
+        return Ok(());
+    }
+    let (full_name, offset) = if name.starts_with('/') {
+        (c_name, 0)
+    } else {
+        // The name was checked for nul bytes above.
+        (CString::new(format!("/{}", name)).unwrap(), 1)
+    };
+    // SAFETY: The name is a valid nul-terminated string, and the out-parameters are valid.
+    unsafe {
+        rmw_validate_full_topic_name(
+            full_name.as_ptr(),
+            &mut validation_result,
+            &mut invalid_index,
+        )
+    }
+    .ok_for("rmw_validate_full_topic_name")?;
+    let violation = match validation_result {
+        RMW_TOPIC_VALID => return Ok(()),
+        RMW_TOPIC_INVALID_IS_EMPTY_STRING => NameViolation::IsEmptyString,
+        RMW_TOPIC_INVALID_NOT_ABSOLUTE => NameViolation::NotAbsolute,
+        RMW_TOPIC_INVALID_ENDS_WITH_FORWARD_SLASH => NameViolation::EndsWithForwardSlash,
+        RMW_TOPIC_INVALID_CONTAINS_UNALLOWED_CHARACTERS => {
+            NameViolation::ContainsUnallowedCharacters
+        }
+        RMW_TOPIC_INVALID_CONTAINS_REPEATED_FORWARD_SLASH => {
+            NameViolation::ContainsRepeatedForwardSlash
+        }
+        RMW_TOPIC_INVALID_NAME_TOKEN_STARTS_WITH_NUMBER => NameViolation::NameTokenStartsWithNumber,
+        RMW_TOPIC_INVALID_TOO_LONG => NameViolation::TooLong,
+        _ => NameViolation::ContainsUnallowedCharacters,
+    };
+    Err(invalid_name(
+        name,
+        NameKind::Topic,
+        violation,
+        invalid_index.saturating_sub(offset),
+    ))
+}
+
+/// Checks that `name` is a valid node name.
+///
+/// See [`NodeBuilder::new()`][1] for the rules for valid node names.
+///
+/// # Example
+/// ```
+/// # use rclrs::{validate_node_name, InvalidNameError, NameViolation, RclrsError};
+/// assert!(validate_node_name("my_node").is_ok());
+/// assert!(matches!(
+///     validate_node_name("3d_mapper").unwrap_err(),
+///     RclrsError::InvalidName(InvalidNameError {
+///         violation: NameViolation::StartsWithNumber,
+///         invalid_index: 0,
+///         ..
+///     })
+/// ));
+/// ```
+///
+/// [1]: crate::NodeBuilder::new
+pub fn validate_node_name(name: &str) -> Result<(), RclrsError> {
+    let c_name = to_cstring(name, NameKind::Node)?;
+    let mut validation_result = 0;
+    let mut invalid_index = 0;
+    // SAFETY: The name is a valid nul-terminated string, and the out-parameters are valid.
+    unsafe { rmw_validate_node_name(c_name.as_ptr(), &mut validation_result, &mut invalid_index) }
+        .ok_for("rmw_validate_node_name")?;
+    let violation = match validation_result {
+        RMW_NODE_NAME_VALID => return Ok(()),
+        RMW_NODE_NAME_INVALID_IS_EMPTY_STRING => NameViolation::IsEmptyString,
+        RMW_NODE_NAME_INVALID_CONTAINS_UNALLOWED_CHARACTERS => {
+            NameViolation::ContainsUnallowedCharacters
+        }
+        RMW_NODE_NAME_INVALID_STARTS_WITH_NUMBER => NameViolation::StartsWithNumber,
+        RMW_NODE_NAME_INVALID_TOO_LONG => NameViolation::TooLong,
+        _ => NameViolation::ContainsUnallowedCharacters,
+    };
+    Err(invalid_name(name, NameKind::Node, violation, invalid_index))
+}
+
+/// Checks that `namespace` is a valid node namespace.
+///
+/// Unlike [`NodeBuilder::namespace()`][1], this does not add a missing leading `/`, so e.g.
+/// `foo` is rejected with [`NameViolation::NotAbsolute`]. See [`NodeBuilder::namespace()`][1]
+/// for the rules for valid namespaces.
+///
+/// # Example
+/// ```
+/// # use rclrs::{validate_namespace, InvalidNameError, NameViolation, RclrsError};
+/// assert!(validate_namespace("/robot1/sensors").is_ok());
+/// assert!(matches!(
+///     validate_namespace("/robot1//sensors").unwrap_err(),
+///     RclrsError::InvalidName(InvalidNameError {
+///         violation: NameViolation::ContainsRepeatedForwardSlash,
+///         invalid_index: 8,
+///         ..
+///     })
+/// ));
+/// ```
+///
+/// [1]: crate::NodeBuilder::namespace
+pub fn validate_namespace(namespace: &str) -> Result<(), RclrsError> {
+    let c_namespace = to_cstring(namespace, NameKind::Namespace)?;
+    let mut validation_result = 0;
+    let mut invalid_index = 0;
+    // SAFETY: The namespace is a valid nul-terminated string, and the out-parameters are valid.
+    unsafe {
+        rmw_validate_namespace(
+            c_namespace.as_ptr(),
+            &mut validation_result,
+            &mut invalid_index,
+        )
+    }
+    .ok_for("rmw_validate_namespace")?;
+    let violation = match validation_result {
+        RMW_NAMESPACE_VALID => return Ok(()),
+        RMW_NAMESPACE_INVALID_IS_EMPTY_STRING => NameViolation::IsEmptyString,
+        RMW_NAMESPACE_INVALID_NOT_ABSOLUTE => NameViolation::NotAbsolute,
+        RMW_NAMESPACE_INVALID_ENDS_WITH_FORWARD_SLASH => NameViolation::EndsWithForwardSlash,
+        RMW_NAMESPACE_INVALID_CONTAINS_UNALLOWED_CHARACTERS => {
+            NameViolation::ContainsUnallowedCharacters
+        }
+        RMW_NAMESPACE_INVALID_CONTAINS_REPEATED_FORWARD_SLASH => {
+            NameViolation::ContainsRepeatedForwardSlash
+        }
+        RMW_NAMESPACE_INVALID_NAME_TOKEN_STARTS_WITH_NUMBER => {
+            NameViolation::NameTokenStartsWithNumber
+        }
+        RMW_NAMESPACE_INVALID_TOO_LONG => NameViolation::TooLong,
+        _ => NameViolation::ContainsUnallowedCharacters,
+    };
+    Err(invalid_name(
+        namespace,
+        NameKind::Namespace,
+        violation,
+        invalid_index,
+    ))
+}
+
+// Converts the name to a C string. A nul byte is reported as a character that is not allowed,
+// so that the caller only needs to validate names without nul bytes.
+fn to_cstring(name: &str, kind: NameKind) -> Result<CString, RclrsError> {
+    CString::new(name).map_err(|err| {
+        invalid_name(
+            name,
+            kind,
+            NameViolation::ContainsUnallowedCharacters,
+            err.nul_position(),
+        )
+    })
+}
+
+fn invalid_name(
+    name: &str,
+    kind: NameKind,
+    violation: NameViolation,
+    invalid_index: usize,
+) -> RclrsError {
+    RclrsError::InvalidName(InvalidNameError {
+        name: name.to_owned(),
+        kind,
+        violation,
+        invalid_index,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violation(result: Result<(), RclrsError>) -> Option<(NameViolation, usize)> {
+        match result {
+            Ok(()) => None,
+            Err(RclrsError::InvalidName(err)) => Some((err.violation, err.invalid_index)),
+            Err(err) => panic!("Expected an InvalidName error, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_validate_topic_name() {
+        for name in [
+            "chatter",
+            "/chatter",
+            "~/chatter",
+            "{node}/chatter",
+            "a/b_c/d1",
+        ] {
+            assert_eq!(violation(validate_topic_name(name)), None, "{}", name);
+        }
+        assert_eq!(
+            violation(validate_topic_name("")),
+            Some((NameViolation::IsEmptyString, 0))
+        );
+        assert_eq!(
+            violation(validate_topic_name("chat ter")),
+            Some((NameViolation::ContainsUnallowedCharacters, 4))
+        );
+        assert_eq!(
+            violation(validate_topic_name("chat\0ter")),
+            Some((NameViolation::ContainsUnallowedCharacters, 4))
+        );
+        assert_eq!(
+            violation(validate_topic_name("/foo/1bar")),
+            Some((NameViolation::NameTokenStartsWithNumber, 5))
+        );
+        assert_eq!(
+            violation(validate_topic_name("/foo//bar")),
+            Some((NameViolation::ContainsRepeatedForwardSlash, 5))
+        );
+        assert_eq!(
+            violation(validate_topic_name("foo//bar")),
+            Some((NameViolation::ContainsRepeatedForwardSlash, 4))
+        );
+        assert_eq!(
+            violation(validate_topic_name("foo/")),
+            Some((NameViolation::EndsWithForwardSlash, 3))
+        );
+        assert_eq!(
+            violation(validate_topic_name("foo/~bar")),
+            Some((NameViolation::MisplacedTilde, 4))
+        );
+    }
+
+    #[test]
+    fn test_validate_node_name() {
+        assert_eq!(violation(validate_node_name("my_node")), None);
+        assert_eq!(
+            violation(validate_node_name("")),
+            Some((NameViolation::IsEmptyString, 0))
+        );
+        assert_eq!(
+            violation(validate_node_name("my-node")),
+            Some((NameViolation::ContainsUnallowedCharacters, 2))
+        );
+        assert_eq!(
+            violation(validate_node_name("1node")),
+            Some((NameViolation::StartsWithNumber, 0))
+        );
+        assert_eq!(
+            violation(validate_node_name("my/node")),
+            Some((NameViolation::ContainsUnallowedCharacters, 2))
+        );
+    }
+
+    #[test]
+    fn test_validate_namespace() {
+        for namespace in ["/", "/foo", "/foo/bar_1"] {
+            assert_eq!(
+                violation(validate_namespace(namespace)),
+                None,
+                "{}",
+                namespace
+            );
+        }
+        assert_eq!(
+            violation(validate_namespace("foo")),
+            Some((NameViolation::NotAbsolute, 0))
+        );
+        assert_eq!(
+            violation(validate_namespace("/foo//bar")),
+            Some((NameViolation::ContainsRepeatedForwardSlash, 5))
+        );
+        assert_eq!(
+            violation(validate_namespace("/foo/2bar")),
+            Some((NameViolation::NameTokenStartsWithNumber, 5))
+        );
+        assert_eq!(
+            violation(validate_namespace("/foo$")),
+            Some((NameViolation::ContainsUnallowedCharacters, 4))
+        );
+        assert_eq!(
+            violation(validate_namespace("/foo/")),
+            Some((NameViolation::EndsWithForwardSlash, 4))
+        );
+    }
+
+    #[test]
+    fn test_invalid_name_display() {
+        let err = validate_node_name("1node").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The node name '1node' is invalid: it must not start with a number (at index 0)"
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::{ffi::CStr, slice};
+
+use crate::rcl_bindings::*;
+
+impl Drop for rmw_network_flow_endpoint_array_t {
+    fn drop(&mut self) {
+        // The array is only initialized if the rmw implementation returned endpoints.
+        if self.network_flow_endpoint.is_null() {
+            return;
+        }
+        // SAFETY: The array was initialized with its allocator by rcl.
+        unsafe {
+            rmw_network_flow_endpoint_array_fini(self);
+        }
+    }
+}
+
+/// The transport protocol of a [`NetworkFlowEndpoint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransportProtocol {
+    /// The transport protocol is not known.
+    Unknown,
+    /// User Datagram Protocol.
+    Udp,
+    /// Transmission Control Protocol.
+    Tcp,
+}
+
+/// The internet protocol of a [`NetworkFlowEndpoint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InternetProtocol {
+    /// The internet protocol is not known.
+    Unknown,
+    /// Internet Protocol version 4.
+    Ipv4,
+    /// Internet Protocol version 6.
+    Ipv6,
+}
+
+/// Whether a publisher or subscription requires network flow endpoints that are unique, i.e.
+/// not shared with other publishers or subscriptions.
+///
+/// Unique network flows allow e.g. traffic shaping per publisher. See
+/// [`PublisherOptions::unique_network_flow_endpoints()`][1] and
+/// [`SubscriptionOptions::unique_network_flow_endpoints()`][2].
+///
+/// [1]: crate::PublisherOptions::unique_network_flow_endpoints
+/// [2]: crate::SubscriptionOptions::unique_network_flow_endpoints
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UniqueNetworkFlowEndpoints {
+    /// Unique network flow endpoints are not required.
+    NotRequired,
+    /// Unique network flow endpoints are required. If the RMW implementation cannot provide
+    /// them, creating the publisher or subscription fails.
+    StrictlyRequired,
+    /// Unique network flow endpoints are used if the RMW implementation supports them.
+    Optional,
+    /// The RMW implementation decides whether the endpoints are unique.
+    #[default]
+    SystemDefault,
+}
+
+impl From<UniqueNetworkFlowEndpoints> for rmw_unique_network_flow_endpoints_requirement_t {
+    fn from(requirement: UniqueNetworkFlowEndpoints) -> Self {
+        match requirement {
+            UniqueNetworkFlowEndpoints::NotRequired => {
+                Self::RMW_UNIQUE_NETWORK_FLOW_ENDPOINTS_NOT_REQUIRED
+            }
+            UniqueNetworkFlowEndpoints::StrictlyRequired => {
+                Self::RMW_UNIQUE_NETWORK_FLOW_ENDPOINTS_STRICTLY_REQUIRED
+            }
+            UniqueNetworkFlowEndpoints::Optional => {
+                Self::RMW_UNIQUE_NETWORK_FLOW_ENDPOINTS_OPTIONALLY_REQUIRED
+            }
+            UniqueNetworkFlowEndpoints::SystemDefault => {
+                Self::RMW_UNIQUE_NETWORK_FLOW_ENDPOINTS_SYSTEM_DEFAULT
+            }
+        }
+    }
+}
+
+/// A network flow endpoint that a publisher or subscription uses in the middleware.
+///
+/// See [`Publisher::get_network_flow_endpoints()`][1] and
+/// [`Subscription::get_network_flow_endpoints()`][2].
+///
+/// [1]: crate::Publisher::get_network_flow_endpoints
+/// [2]: crate::Subscription::get_network_flow_endpoints
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NetworkFlowEndpoint {
+    /// The transport protocol, e.g. UDP.
+    pub transport_protocol: TransportProtocol,
+    /// The internet protocol, e.g. IPv4.
+    pub internet_protocol: InternetProtocol,
+    /// The port of the transport protocol.
+    pub transport_port: u16,
+    /// The IPv6 flow label.
+    pub flow_label: u32,
+    /// The DSCP (Differentiated Services Code Point) value.
+    pub dscp: u8,
+    /// The internet address, e.g. `192.168.1.2`.
+    pub internet_address: String,
+}
+
+impl From<&rmw_network_flow_endpoint_t> for NetworkFlowEndpoint {
+    fn from(endpoint: &rmw_network_flow_endpoint_t) -> Self {
+        let transport_protocol = match endpoint.transport_protocol {
+            rmw_transport_protocol_t::RMW_TRANSPORT_PROTOCOL_UDP => TransportProtocol::Udp,
+            rmw_transport_protocol_t::RMW_TRANSPORT_PROTOCOL_TCP => TransportProtocol::Tcp,
+            _ => TransportProtocol::Unknown,
+        };
+        let internet_protocol = match endpoint.internet_protocol {
+            rmw_internet_protocol_t::RMW_INTERNET_PROTOCOL_IPV4 => InternetProtocol::Ipv4,
+            rmw_internet_protocol_t::RMW_INTERNET_PROTOCOL_IPV6 => InternetProtocol::Ipv6,
+            _ => InternetProtocol::Unknown,
+        };
+        // SAFETY: The address is a null-terminated string stored in the endpoint.
+        let internet_address = unsafe { CStr::from_ptr(endpoint.internet_address.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+        Self {
+            transport_protocol,
+            internet_protocol,
+            transport_port: endpoint.transport_port,
+            flow_label: endpoint.flow_label,
+            dscp: endpoint.dscp,
+            internet_address,
+        }
+    }
+}
+
+// Converts the endpoints returned by rcl.
+pub(crate) fn network_flow_endpoints_from_rcl(
+    array: &rmw_network_flow_endpoint_array_t,
+) -> Vec<NetworkFlowEndpoint> {
+    if array.network_flow_endpoint.is_null() {
+        return Vec::new();
+    }
+    // SAFETY: The array contains `size` initialized endpoints.
+    unsafe { slice::from_raw_parts(array.network_flow_endpoint, array.size) }
+        .iter()
+        .map(NetworkFlowEndpoint::from)
+        .collect()
+}
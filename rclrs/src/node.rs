@@ -5,7 +5,9 @@ use std::{
     ffi::CStr,
     fmt,
     os::raw::c_char,
+    path::Path,
     sync::{Arc, Mutex, Weak},
+    time::Duration,
     vec::Vec,
 };
 
@@ -13,10 +15,18 @@ use rosidl_runtime_rs::Message;
 
 pub use self::{builder::*, graph::*};
 use crate::{
-    rcl_bindings::*, Client, ClientBase, Clock, Context, ContextHandle, GuardCondition,
-    ParameterBuilder, ParameterInterface, ParameterVariant, Parameters, Publisher, QoSProfile,
-    RclrsError, Service, ServiceBase, Subscription, SubscriptionBase, SubscriptionCallback,
-    TimeSource, ENTITY_LIFECYCLE_MUTEX,
+    context::rmw_implementation_identifier,
+    logging::LOGGING_MUTEX,
+    rcl_bindings::*,
+    vendor::rcl_interfaces::msg::{ListParametersResult, ParameterEvent, SetParametersResult},
+    ActionClient, ActionClientBase, ActionServer, ActionServerBase, AdaptedPublisher, Allocator,
+    CallbackGroup, CallbackGroupType, CancelResponse, Client, ClientBase, ClientOptions, Clock,
+    Context, ContextHandle, EventBase, GoalResponse, GoalUuid, GuardCondition, MessageBuffer,
+    MessageInfo, Parameter, ParameterBuilder, ParameterInterface, ParameterValue, ParameterVariant,
+    Parameters, Publisher, PublisherOptions, QoSProfile, Rate, RclrsError, ServerGoalHandle,
+    Service, ServiceBase, ServiceCallback, ServiceInfo, ServiceOptions, Subscription,
+    SubscriptionBase, SubscriptionCallback, SubscriptionOptions, TimeSource, Timer, TimerCallback,
+    TimerInfo, TopicStatistics, TypeAdapter, ENTITY_LIFECYCLE_MUTEX, QOS_PROFILE_PARAMETER_EVENTS,
 };
 
 // SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
@@ -59,10 +69,15 @@ unsafe impl Send for rcl_node_t {}
 /// [3]: crate::NodeBuilder::new
 /// [4]: crate::NodeBuilder::namespace
 pub struct Node {
+    pub(crate) action_clients_mtx: Mutex<Vec<Weak<dyn ActionClientBase>>>,
+    pub(crate) action_servers_mtx: Mutex<Vec<Weak<dyn ActionServerBase>>>,
     pub(crate) clients_mtx: Mutex<Vec<Weak<dyn ClientBase>>>,
+    pub(crate) events_mtx: Mutex<Vec<Weak<dyn EventBase>>>,
     pub(crate) guard_conditions_mtx: Mutex<Vec<Weak<GuardCondition>>>,
     pub(crate) services_mtx: Mutex<Vec<Weak<dyn ServiceBase>>>,
     pub(crate) subscriptions_mtx: Mutex<Vec<Weak<dyn SubscriptionBase>>>,
+    pub(crate) timers_mtx: Mutex<Vec<Weak<Timer>>>,
+    pub(crate) default_callback_group: Arc<CallbackGroup>,
     time_source: TimeSource,
     parameter: ParameterInterface,
     pub(crate) handle: Arc<NodeHandle>,
@@ -76,15 +91,21 @@ pub struct Node {
 pub(crate) struct NodeHandle {
     pub(crate) rcl_node: Mutex<rcl_node_t>,
     pub(crate) context_handle: Arc<ContextHandle>,
+    // Whether the publishers and subscriptions of the node use intra-process communication.
+    pub(crate) use_intra_process_comms: bool,
+    // The allocator given to the NodeBuilder, which must outlive the rcl_node.
+    pub(crate) allocator: Option<Allocator>,
 }
 
 impl Drop for NodeHandle {
     fn drop(&mut self) {
         let _context_lock = self.context_handle.rcl_context.lock().unwrap();
         let mut rcl_node = self.rcl_node.lock().unwrap();
+        let _logging_lock = LOGGING_MUTEX.lock().unwrap();
         let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
         // SAFETY: The entity lifecycle mutex is locked to protect against the risk of
         // global variables in the rmw implementation being unsafely modified during cleanup.
+        // The logging mutex is locked, since this destroys the rosout publisher of the node.
         unsafe { rcl_node_fini(&mut *rcl_node) };
     }
 }
@@ -116,7 +137,9 @@ impl Node {
 
     /// Returns the clock associated with this node.
     pub fn get_clock(&self) -> Clock {
-        self.time_source.get_clock()
+        self.time_source
+            .get_clock()
+            .with_context_handle(Arc::clone(&self.handle.context_handle))
     }
 
     /// Returns the name of the node.
@@ -188,7 +211,18 @@ impl Node {
         self.call_string_getter(rcl_node_get_fully_qualified_name)
     }
 
-    // Helper for name(), namespace(), fully_qualified_name()
+    /// Returns the name of the logger of the node.
+    ///
+    /// The logger name is derived from the fully qualified name of the node, with the namespace
+    /// separators replaced by dots. It can be used with the logging macros, e.g. [`log_info!`][1],
+    /// although the node itself can be passed to them as well.
+    ///
+    /// [1]: crate::log_info
+    pub fn logger_name(&self) -> String {
+        self.call_string_getter(rcl_node_get_logger_name)
+    }
+
+    // Helper for name(), namespace(), fully_qualified_name(), logger_name()
     fn call_string_getter(
         &self,
         getter: unsafe extern "C" fn(*const rcl_node_t) -> *const c_char,
@@ -205,11 +239,87 @@ impl Node {
     where
         T: rosidl_runtime_rs::Service,
     {
-        let client = Arc::new(Client::<T>::new(Arc::clone(&self.handle), topic)?);
+        self.create_client_with_options(topic, ClientOptions::default())
+    }
+
+    /// Creates a [`Client`][1] with the given [`ClientOptions`].
+    ///
+    /// [1]: crate::Client
+    pub fn create_client_with_options<T>(
+        &self,
+        topic: &str,
+        options: ClientOptions,
+    ) -> Result<Arc<Client<T>>, RclrsError>
+    where
+        T: rosidl_runtime_rs::Service,
+    {
+        let client = Arc::new(Client::<T>::new(Arc::clone(&self.handle), topic, options)?);
         { self.clients_mtx.lock().unwrap() }.push(Arc::downgrade(&client) as Weak<dyn ClientBase>);
         Ok(client)
     }
 
+    /// Creates an [`ActionClient`][1].
+    ///
+    /// [1]: crate::ActionClient
+    // TODO: make action client's lifetime depend on node's lifetime
+    pub fn create_action_client<A>(
+        &self,
+        action_name: &str,
+    ) -> Result<Arc<ActionClient<A>>, RclrsError>
+    where
+        A: rosidl_runtime_rs::ActionImpl,
+    {
+        let action_client = Arc::new(ActionClient::<A>::new(
+            Arc::clone(&self.handle),
+            action_name,
+        )?);
+        { self.action_clients_mtx.lock().unwrap() }
+            .push(Arc::downgrade(&action_client) as Weak<dyn ActionClientBase>);
+        Ok(action_client)
+    }
+
+    /// Creates an [`ActionServer`][1].
+    ///
+    /// New goals are passed to `goal_callback`, which decides whether they are accepted.
+    /// Accepted goals are passed to `execute_callback`, and requests to cancel a goal are passed
+    /// to `cancel_callback`. See the documentation of [`ActionServer`][1] for details.
+    ///
+    /// [1]: crate::ActionServer
+    // TODO: make action server's lifetime depend on node's lifetime
+    pub fn create_action_server<A, G, C, E>(
+        &self,
+        action_name: &str,
+        goal_callback: G,
+        cancel_callback: C,
+        execute_callback: E,
+    ) -> Result<Arc<ActionServer<A>>, RclrsError>
+    where
+        A: rosidl_runtime_rs::ActionImpl,
+        G: FnMut(GoalUuid, &A::Goal) -> GoalResponse + Send + 'static,
+        C: FnMut(GoalUuid) -> CancelResponse + Send + 'static,
+        E: FnMut(ServerGoalHandle<A>) + Send + 'static,
+    {
+        let action_server = Arc::new(ActionServer::<A>::new(
+            Arc::clone(&self.handle),
+            self.get_clock(),
+            action_name,
+            Box::new(goal_callback),
+            Box::new(cancel_callback),
+            Box::new(execute_callback),
+        )?);
+        { self.action_servers_mtx.lock().unwrap() }
+            .push(Arc::downgrade(&action_server) as Weak<dyn ActionServerBase>);
+        Ok(action_server)
+    }
+
+    /// Creates a [`CallbackGroup`] with the given concurrency policy.
+    ///
+    /// The group can be assigned to entities of this node when creating them, e.g. with
+    /// [`SubscriptionOptions::callback_group()`].
+    pub fn create_callback_group(&self, group_type: CallbackGroupType) -> Arc<CallbackGroup> {
+        Arc::new(CallbackGroup::new(group_type))
+    }
+
     /// Creates a [`GuardCondition`][1] with no callback.
     ///
     /// A weak pointer to the `GuardCondition` is stored within this node.
@@ -238,7 +348,7 @@ impl Node {
     ///
     /// [1]: crate::GuardCondition
     /// [2]: crate::spin_once
-    pub fn create_guard_condition_with_callback<F>(&mut self, callback: F) -> Arc<GuardCondition>
+    pub fn create_guard_condition_with_callback<F>(&self, callback: F) -> Arc<GuardCondition>
     where
         F: Fn() + Send + Sync + 'static,
     {
@@ -263,10 +373,49 @@ impl Node {
     where
         T: Message,
     {
-        let publisher = Arc::new(Publisher::<T>::new(Arc::clone(&self.handle), topic, qos)?);
+        self.create_publisher_with_options(topic, PublisherOptions::new(qos))
+    }
+
+    /// Creates a [`Publisher`][1] with additional [`PublisherOptions`][2].
+    ///
+    /// [1]: crate::Publisher
+    /// [2]: crate::PublisherOptions
+    pub fn create_publisher_with_options<T>(
+        &self,
+        topic: &str,
+        options: PublisherOptions,
+    ) -> Result<Arc<Publisher<T>>, RclrsError>
+    where
+        T: Message,
+    {
+        let publisher = Arc::new(Publisher::<T>::new(
+            Arc::clone(&self.handle),
+            topic,
+            options,
+        )?);
+        { self.events_mtx.lock() }
+            .unwrap()
+            .extend(publisher.events.iter().map(Arc::downgrade));
         Ok(publisher)
     }
 
+    /// Creates an [`AdaptedPublisher`][1], which publishes values of the custom type of the
+    /// [`TypeAdapter`][2] `A`.
+    ///
+    /// [1]: crate::AdaptedPublisher
+    /// [2]: crate::TypeAdapter
+    pub fn create_adapted_publisher<A>(
+        &self,
+        topic: &str,
+        qos: QoSProfile,
+    ) -> Result<Arc<AdaptedPublisher<A>>, RclrsError>
+    where
+        A: TypeAdapter,
+    {
+        let publisher = self.create_publisher::<A::Ros>(topic, qos)?;
+        Ok(Arc::new(AdaptedPublisher::new(publisher)))
+    }
+
     /// Creates a [`Service`][1].
     ///
     /// [1]: crate::Service
@@ -279,10 +428,63 @@ impl Node {
     where
         T: rosidl_runtime_rs::Service,
         F: Fn(&rmw_request_id_t, T::Request) -> T::Response + 'static + Send,
+    {
+        self.create_service_with_options(topic, ServiceOptions::default(), callback)
+    }
+
+    /// Creates a [`Service`][1] with the given [`ServiceOptions`].
+    ///
+    /// [1]: crate::Service
+    pub fn create_service_with_options<T, F>(
+        &self,
+        topic: &str,
+        options: ServiceOptions,
+        callback: F,
+    ) -> Result<Arc<Service<T>>, RclrsError>
+    where
+        T: rosidl_runtime_rs::Service,
+        F: Fn(&rmw_request_id_t, T::Request) -> T::Response + 'static + Send,
+    {
+        self.create_service_with_callback(
+            topic,
+            options,
+            ServiceCallback::Basic(Box::new(callback)),
+        )
+    }
+
+    /// Creates a [`Service`][1] whose callback also receives the [`ServiceInfo`] of each
+    /// request, such as the GID of the client and the time the request was sent.
+    ///
+    /// [1]: crate::Service
+    pub fn create_service_with_info<T, F>(
+        &self,
+        topic: &str,
+        callback: F,
+    ) -> Result<Arc<Service<T>>, RclrsError>
+    where
+        T: rosidl_runtime_rs::Service,
+        F: Fn(T::Request, ServiceInfo) -> T::Response + 'static + Send,
+    {
+        self.create_service_with_callback(
+            topic,
+            ServiceOptions::default(),
+            ServiceCallback::WithInfo(Box::new(callback)),
+        )
+    }
+
+    fn create_service_with_callback<T>(
+        &self,
+        topic: &str,
+        options: ServiceOptions,
+        callback: ServiceCallback<T::Request, T::Response>,
+    ) -> Result<Arc<Service<T>>, RclrsError>
+    where
+        T: rosidl_runtime_rs::Service,
     {
         let service = Arc::new(Service::<T>::new(
             Arc::clone(&self.handle),
             topic,
+            options,
             callback,
         )?);
         { self.services_mtx.lock().unwrap() }
@@ -303,18 +505,197 @@ impl Node {
     where
         T: Message,
     {
-        let subscription = Arc::new(Subscription::<T>::new(
-            Arc::clone(&self.handle),
-            topic,
-            qos,
-            callback,
-        )?);
+        self.create_subscription_with_options(topic, SubscriptionOptions::new(qos), callback)
+    }
+
+    /// Creates a [`Subscription`][1] with additional [`SubscriptionOptions`][2].
+    ///
+    /// [1]: crate::Subscription
+    /// [2]: crate::SubscriptionOptions
+    pub fn create_subscription_with_options<T, Args>(
+        &self,
+        topic: &str,
+        mut options: SubscriptionOptions,
+        callback: impl SubscriptionCallback<T, Args>,
+    ) -> Result<Arc<Subscription<T>>, RclrsError>
+    where
+        T: Message,
+    {
+        let topic_statistics = options.topic_statistics.take();
+        let mut subscription =
+            Subscription::<T>::new(Arc::clone(&self.handle), topic, options, callback)?;
+        if let Some(topic_statistics) = topic_statistics {
+            subscription.statistics = Some(TopicStatistics::new(self, topic_statistics)?);
+        }
+        let subscription = Arc::new(subscription);
         { self.subscriptions_mtx.lock() }
             .unwrap()
             .push(Arc::downgrade(&subscription) as Weak<dyn SubscriptionBase>);
+        { self.events_mtx.lock() }
+            .unwrap()
+            .extend(subscription.events.iter().map(Arc::downgrade));
         Ok(subscription)
     }
 
+    /// Creates a [`Subscription`][1] whose callback receives values of the custom type of the
+    /// [`TypeAdapter`][2] `A`.
+    ///
+    /// Each received ROS message is converted with [`TypeAdapter::convert_to_custom()`][3] before
+    /// the callback is called.
+    ///
+    /// [1]: crate::Subscription
+    /// [2]: crate::TypeAdapter
+    /// [3]: crate::TypeAdapter::convert_to_custom
+    pub fn create_adapted_subscription<A, F>(
+        &self,
+        topic: &str,
+        qos: QoSProfile,
+        mut callback: F,
+    ) -> Result<Arc<Subscription<A::Ros>>, RclrsError>
+    where
+        A: TypeAdapter,
+        F: FnMut(A::Custom) + Send + 'static,
+    {
+        self.create_subscription::<A::Ros, _>(topic, qos, move |msg: A::Ros| {
+            callback(A::convert_to_custom(msg))
+        })
+    }
+
+    /// Creates a [`Subscription`][1] that pushes the received messages into a
+    /// [`MessageBuffer`][2] instead of calling a callback.
+    ///
+    /// The messages can then be processed at a different rate than they arrive, e.g. in another
+    /// thread. What happens when the buffer is full is determined by its
+    /// [`OverflowPolicy`][3].
+    ///
+    /// [1]: crate::Subscription
+    /// [2]: crate::MessageBuffer
+    /// [3]: crate::OverflowPolicy
+    pub fn create_buffered_subscription<T>(
+        &self,
+        topic: &str,
+        qos: QoSProfile,
+        buffer: Arc<MessageBuffer<T>>,
+    ) -> Result<Arc<Subscription<T>>, RclrsError>
+    where
+        T: Message,
+    {
+        self.create_subscription(topic, qos, move |msg: T, info: MessageInfo| {
+            buffer.push(msg, info)
+        })
+    }
+
+    /// Creates a [`Subscription`][1] to the changes of parameters on the `/parameter_events` topic.
+    ///
+    /// If `node_filter` is `Some`, only events of the node with that fully qualified name are
+    /// passed to the callback, e.g. `Some("/my_namespace/my_node")`.
+    ///
+    /// The callback receives [`ParameterEvent`][2] messages.
+    ///
+    /// [1]: crate::Subscription
+    /// [2]: crate::msg::ParameterEvent
+    pub fn create_parameter_event_subscription<F>(
+        &self,
+        node_filter: Option<&str>,
+        mut callback: F,
+    ) -> Result<Arc<Subscription<ParameterEvent>>, RclrsError>
+    where
+        F: FnMut(ParameterEvent) + Send + 'static,
+    {
+        let node_filter = node_filter.map(String::from);
+        self.create_subscription(
+            "/parameter_events",
+            QOS_PROFILE_PARAMETER_EVENTS,
+            move |event: ParameterEvent| {
+                if node_filter
+                    .as_ref()
+                    .map_or(true, |node| *node == event.node)
+                {
+                    callback(event);
+                }
+            },
+        )
+    }
+
+    /// Creates a [`Rate`][1] that ticks every `period`, measured with the clock of this node.
+    ///
+    /// [1]: crate::Rate
+    pub fn create_rate(&self, period: Duration) -> Rate {
+        Rate::new(self.get_clock(), period)
+    }
+
+    /// Creates a [`Timer`][1] that calls `callback` every `period`.
+    ///
+    /// The period is measured with the clock of this node, see [`Node::get_clock()`].
+    /// The callback is executed when spinning the node. It can either take no arguments, or a
+    /// [`TimerInfo`][2] with the time that actually elapsed since its previous execution.
+    ///
+    /// [1]: crate::Timer
+    /// [2]: crate::TimerInfo
+    pub fn create_timer<Args>(
+        &self,
+        period: Duration,
+        callback: impl TimerCallback<Args>,
+    ) -> Result<Arc<Timer>, RclrsError> {
+        self.create_timer_with_callback(period, false, callback.into_timer_callback(), None)
+    }
+
+    /// Creates a [`Timer`][1] that calls `callback` every `period`, in the given
+    /// [`CallbackGroup`].
+    ///
+    /// This is the same as [`Node::create_timer()`], except that the callback group controls
+    /// which callbacks may be executed in parallel with the timer by the
+    /// [`MultiThreadedExecutor`][2].
+    ///
+    /// [1]: crate::Timer
+    /// [2]: crate::MultiThreadedExecutor
+    pub fn create_timer_with_callback_group<Args>(
+        &self,
+        period: Duration,
+        callback_group: Arc<CallbackGroup>,
+        callback: impl TimerCallback<Args>,
+    ) -> Result<Arc<Timer>, RclrsError> {
+        self.create_timer_with_callback(
+            period,
+            false,
+            callback.into_timer_callback(),
+            Some(callback_group),
+        )
+    }
+
+    /// Creates a [`Timer`][1] that calls `callback` once, after `delay` has elapsed.
+    ///
+    /// The timer is canceled after firing, which can be undone with [`Timer::reset()`][2].
+    ///
+    /// [1]: crate::Timer
+    /// [2]: crate::Timer::reset
+    pub fn create_one_shot_timer<Args>(
+        &self,
+        delay: Duration,
+        callback: impl TimerCallback<Args>,
+    ) -> Result<Arc<Timer>, RclrsError> {
+        self.create_timer_with_callback(delay, true, callback.into_timer_callback(), None)
+    }
+
+    fn create_timer_with_callback(
+        &self,
+        period: Duration,
+        one_shot: bool,
+        callback: Box<dyn FnMut(TimerInfo) + Send>,
+        callback_group: Option<Arc<CallbackGroup>>,
+    ) -> Result<Arc<Timer>, RclrsError> {
+        let timer = Arc::new(Timer::new(
+            self.get_clock(),
+            Arc::clone(&self.handle.context_handle),
+            period,
+            one_shot,
+            callback,
+            callback_group,
+        )?);
+        { self.timers_mtx.lock().unwrap() }.push(Arc::downgrade(&timer));
+        Ok(timer)
+    }
+
     /// Returns the subscriptions that have not been dropped yet.
     pub(crate) fn live_subscriptions(&self) -> Vec<Arc<dyn SubscriptionBase>> {
         { self.subscriptions_mtx.lock().unwrap() }
@@ -330,6 +711,13 @@ impl Node {
             .collect()
     }
 
+    pub(crate) fn live_events(&self) -> Vec<Arc<dyn EventBase>> {
+        { self.events_mtx.lock().unwrap() }
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
     pub(crate) fn live_guard_conditions(&self) -> Vec<Arc<GuardCondition>> {
         { self.guard_conditions_mtx.lock().unwrap() }
             .iter()
@@ -344,6 +732,31 @@ impl Node {
             .collect()
     }
 
+    /// Returns the timers that have not been dropped yet.
+    ///
+    /// Canceled timers are included, since rcl never reports them as ready, and resetting a
+    /// timer wakes up the wait set that it is in.
+    pub(crate) fn live_timers(&self) -> Vec<Arc<Timer>> {
+        { self.timers_mtx.lock().unwrap() }
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
+    pub(crate) fn live_action_clients(&self) -> Vec<Arc<dyn ActionClientBase>> {
+        { self.action_clients_mtx.lock().unwrap() }
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
+    pub(crate) fn live_action_servers(&self) -> Vec<Arc<dyn ActionServerBase>> {
+        { self.action_servers_mtx.lock().unwrap() }
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
     /// Returns the ROS domain ID that the node is using.
     ///
     /// The domain ID controls which nodes can send messages to each other, see the [ROS 2 concept article][1].
@@ -377,11 +790,22 @@ impl Node {
         domain_id
     }
 
+    /// Returns the identifier of the RMW implementation, e.g. `rmw_fastrtps_cpp`.
+    ///
+    /// This is the same as [`Context::rmw_implementation_identifier()`].
+    pub fn rmw_implementation_identifier(&self) -> &'static str {
+        rmw_implementation_identifier()
+    }
+
     /// Creates a [`ParameterBuilder`] that can be used to set parameter declaration options and
     /// declare a parameter as [`OptionalParameter`](crate::parameter::OptionalParameter),
     /// [`MandatoryParameter`](crate::parameter::MandatoryParameter), or
     /// [`ReadOnly`](crate::parameter::ReadOnlyParameter).
     ///
+    /// The parameter is statically typed by `T`, so setting it to a value of another type is
+    /// rejected. To declare a dynamically typed parameter, whose type can change at runtime, use
+    /// [`ParameterValue`] as `T`.
+    ///
     /// # Example
     /// ```
     /// # use rclrs::{Context, ParameterRange, RclrsError};
@@ -422,6 +846,130 @@ impl Node {
         }
     }
 
+    /// Returns the current values of several parameters at once.
+    ///
+    /// The values are returned in the order of the given names. The value is `None` for
+    /// parameters that do not exist, and for optional parameters that are unset.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, ParameterValue, RclrsError};
+    /// let context = Context::new([])?;
+    /// let node = rclrs::create_node(&context, "get_parameters_node")?;
+    /// let _rate = node.declare_parameter("rate").default(10).mandatory().unwrap();
+    /// let values = node.get_parameters(&["rate", "unknown"]);
+    /// assert_eq!(values[0], (String::from("rate"), Some(ParameterValue::Integer(10))));
+    /// assert_eq!(values[1], (String::from("unknown"), None));
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    pub fn get_parameters(&self, names: &[&str]) -> Vec<(String, Option<ParameterValue>)> {
+        self.parameter.get_parameters(names)
+    }
+
+    /// Lists the names of the parameters of this node, like the `list_parameters` service.
+    ///
+    /// If `prefixes` is empty, all parameters are listed. Otherwise, only parameters whose name
+    /// equals one of the prefixes, or continues it after a `.`, are listed. The `depth` limits
+    /// how many levels of the dot-separated parameter tree below the prefix are included, with
+    /// `0` meaning unlimited.
+    ///
+    /// The result also contains the prefixes of the listed names, i.e. the names without their
+    /// last dot-separated component.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, RclrsError};
+    /// let context = Context::new([])?;
+    /// let node = rclrs::create_node(&context, "list_parameters_node")?;
+    /// let _gain = node.declare_parameter("pid.gain").default(1.0).mandatory().unwrap();
+    /// let _rate = node.declare_parameter("rate").default(10).mandatory().unwrap();
+    /// let result = node.list_parameters(&[String::from("pid")], 0);
+    /// assert_eq!(result.names, ["pid.gain"]);
+    /// assert_eq!(result.prefixes, ["pid"]);
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    pub fn list_parameters(&self, prefixes: &[String], depth: usize) -> ListParametersResult {
+        self.parameter.list_parameters(prefixes, depth as u64)
+    }
+
+    /// Undeclares a dynamically typed parameter.
+    ///
+    /// Afterwards, the parameter behaves as if it had never been declared, and it can be declared
+    /// again. The parameter object returned by the declaration keeps its last value, but is no
+    /// longer connected to the node.
+    ///
+    /// Like in `rclcpp`, only parameters that were declared with [`ParameterValue`] as their type
+    /// can be undeclared this way. Other parameters are undeclared by dropping their parameter
+    /// object.
+    ///
+    /// Returns [`RclrsError::ParametersRejected`] if the parameter was not declared, is read only,
+    /// or is statically typed.
+    pub fn undeclare_parameter(&self, name: &str) -> Result<(), RclrsError> {
+        self.parameter.undeclare_parameter(name)
+    }
+
+    /// Registers a callback that validates parameter changes before they are applied.
+    ///
+    /// The callback receives the parameters that are about to be set and can reject them by
+    /// returning a [`SetParametersResult`] with `successful: false` and a `reason`. It is called
+    /// for every change made through a parameter object, through [`Node::use_undeclared_parameters()`],
+    /// or through the parameter services of the node.
+    ///
+    /// Multiple callbacks can be registered, and a change is only applied if all of them accept
+    /// it. A rejected change leaves the stored values untouched.
+    ///
+    /// The callback is called without holding the lock on the parameters, so it can read the
+    /// parameters of this node. The change is validated again after the callbacks ran, in case
+    /// the parameters changed in the meantime.
+    pub fn add_on_set_parameters_callback<F>(&self, callback: F)
+    where
+        F: Fn(&[Parameter]) -> SetParametersResult + Send + Sync + 'static,
+    {
+        self.parameter.add_on_set_callback(Box::new(callback));
+    }
+
+    /// Sets several parameters at once, with all-or-nothing semantics.
+    ///
+    /// Every value is checked against the type, range and read-only setting of its declaration,
+    /// and the whole batch is passed to the callbacks registered with
+    /// [`Node::add_on_set_parameters_callback()`]. Only if all checks pass are the values
+    /// applied, otherwise no parameter is changed and the returned [`SetParametersResult`]
+    /// contains the reason.
+    ///
+    /// Setting parameters that have not been declared requires
+    /// [`Node::use_undeclared_parameters()`] to have been called.
+    pub fn set_parameters_atomically(
+        &self,
+        params: Vec<Parameter>,
+    ) -> Result<SetParametersResult, RclrsError> {
+        let result = match self.parameter.set_parameters_atomically(params) {
+            Ok(()) => SetParametersResult {
+                successful: true,
+                reason: String::new(),
+            },
+            Err(reason) => SetParametersResult {
+                successful: false,
+                reason,
+            },
+        };
+        Ok(result)
+    }
+
+    /// Loads parameters from a parameter file in the standard ROS 2 YAML format.
+    ///
+    /// Only the sections for this node's fully qualified name and for the `/**` wildcard are
+    /// used, with the node-specific values taking precedence. Nested maps result in parameter
+    /// names separated by `.`.
+    ///
+    /// Parameters that have already been declared are set to the values from the file. All other
+    /// parameters are stored as undeclared parameters, whose values are used as prior values
+    /// when they are declared. If any value does not match its declaration or is rejected by an
+    /// on-set callback, no parameter is changed and [`RclrsError::ParametersRejected`] is returned.
+    pub fn load_parameters_from_file(&self, path: &Path) -> Result<(), RclrsError> {
+        self.parameter
+            .load_parameters_from_file(&self.fully_qualified_name(), path)
+    }
+
     /// Creates a [`NodeBuilder`][1] with the given name.
     ///
     /// Convenience function equivalent to [`NodeBuilder::new()`][2].
@@ -440,8 +988,39 @@ impl Node {
     pub fn builder(context: &Context, node_name: &str) -> NodeBuilder {
         NodeBuilder::new(context, node_name)
     }
+
+    /// Creates a weak reference to the node.
+    ///
+    /// The callbacks of a node's subscriptions, services and timers are owned by the node, so
+    /// capturing an `Arc<Node>` in them creates a reference cycle, and the node is never
+    /// dropped. Capture a [`WeakNode`] instead, and upgrade it when the callback runs.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, Node, RclrsError, QOS_PROFILE_DEFAULT};
+    /// # use rclrs::msg::Time;
+    /// let context = Context::new([])?;
+    /// let node = Node::new(&context, "my_node")?;
+    /// let weak_node = node.downgrade();
+    /// let _subscription = node.create_subscription::<Time, _>(
+    ///     "topic",
+    ///     QOS_PROFILE_DEFAULT,
+    ///     move |_msg: Time| {
+    ///         if let Some(node) = weak_node.upgrade() {
+    ///             println!("{} received a message", node.name());
+    ///         }
+    ///     },
+    /// )?;
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    pub fn downgrade(self: &Arc<Self>) -> WeakNode {
+        Arc::downgrade(self)
+    }
 }
 
+/// A weak reference to a [`Node`], see [`Node::downgrade()`].
+pub type WeakNode = Weak<Node>;
+
 // Helper used to implement call_string_getter(), but also used to get the FQN in the Node::new()
 // function, which is why it's not merged into Node::call_string_getter().
 // This function is unsafe since it's possible to pass in an rcl_node_t with dangling
@@ -470,6 +1049,140 @@ mod tests {
         assert_sync::<Node>();
     }
 
+    #[test]
+    fn test_weak_node() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use test_msgs::msg;
+
+        let context = Context::new([])?;
+        let node = NodeBuilder::new(&context, "weak_node").build()?;
+        let weak_node = node.downgrade();
+        let subscription = node.create_subscription::<msg::Empty, _>(
+            "weak_node_topic",
+            QOS_PROFILE_DEFAULT,
+            move |_msg: msg::Empty| {
+                let _node = weak_node.upgrade();
+            },
+        )?;
+        let weak_node = node.downgrade();
+        assert_eq!(weak_node.upgrade().unwrap().name(), "weak_node");
+
+        // The callback does not keep the node alive
+        drop(node);
+        assert!(weak_node.upgrade().is_none());
+        drop(subscription);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fully_qualified_name() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = NodeBuilder::new(&context, "camera")
+            .namespace("/robot1")
+            .build()?;
+        assert_eq!(node.name(), "camera");
+        assert_eq!(node.namespace(), "/robot1");
+        assert_eq!(node.fully_qualified_name(), "/robot1/camera");
+
+        // The name and namespace reflect the remapping
+        let remapping =
+            ["--ros-args", "-r", "__ns:=/robot2", "-r", "__node:=lidar"].map(String::from);
+        let context_r = Context::new(remapping)?;
+        let node_r = NodeBuilder::new(&context_r, "camera")
+            .namespace("/robot1")
+            .build()?;
+        assert_eq!(node_r.name(), "lidar");
+        assert_eq!(node_r.namespace(), "/robot2");
+        assert_eq!(node_r.fully_qualified_name(), "/robot2/lidar");
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_names() -> Result<(), RclrsError> {
+        use crate::{InvalidNameError, NameKind, NameViolation, QOS_PROFILE_DEFAULT};
+
+        let context = Context::new([])?;
+        let invalid_name = |result: Result<Arc<Node>, RclrsError>| match result {
+            Err(RclrsError::InvalidName(err)) => err,
+            Err(err) => panic!("Expected an InvalidName error, got {:?}", err),
+            Ok(_) => panic!("Expected an InvalidName error"),
+        };
+        assert_eq!(
+            invalid_name(NodeBuilder::new(&context, "my node").build()),
+            InvalidNameError {
+                name: String::from("my node"),
+                kind: NameKind::Node,
+                violation: NameViolation::ContainsUnallowedCharacters,
+                invalid_index: 2,
+            }
+        );
+        // The missing leading forward slash does not shift the reported index.
+        assert_eq!(
+            invalid_name(
+                NodeBuilder::new(&context, "my_node")
+                    .namespace("robot1//arm")
+                    .build()
+            ),
+            InvalidNameError {
+                name: String::from("robot1//arm"),
+                kind: NameKind::Namespace,
+                violation: NameViolation::ContainsRepeatedForwardSlash,
+                invalid_index: 7,
+            }
+        );
+
+        let node = NodeBuilder::new(&context, "my_node").build()?;
+        match node.create_publisher::<test_msgs::msg::Empty>("2chatter", QOS_PROFILE_DEFAULT) {
+            Err(RclrsError::InvalidName(err)) => {
+                assert_eq!(err.kind, NameKind::Topic);
+                assert_eq!(err.violation, NameViolation::NameTokenStartsWithNumber);
+                assert_eq!(err.invalid_index, 0);
+            }
+            Err(err) => panic!("Expected an InvalidName error, got {:?}", err),
+            Ok(_) => panic!("Expected an InvalidName error"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_remap_rules_and_parameter_overrides() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use test_msgs::msg;
+
+        let context = Context::new(["--ros-args", "-p", "rate:=5"].map(String::from))?;
+        let node = NodeBuilder::new(&context, "camera")
+            .namespace("/robot1")
+            .remap_rule("image", "image_raw")
+            .remap_rule("/absolute", "/remapped/absolute")
+            .remap_rule("__node", "lidar")
+            .parameter_override("rate", 10)
+            .parameter_override("frame", Arc::<str>::from("base_link"))
+            .build()?;
+        assert_eq!(node.fully_qualified_name(), "/robot1/lidar");
+
+        let publisher = node.create_publisher::<msg::Empty>("image", QOS_PROFILE_DEFAULT)?;
+        assert_eq!(publisher.topic_name(), "/robot1/image_raw");
+        let publisher = node.create_publisher::<msg::Empty>("/absolute", QOS_PROFILE_DEFAULT)?;
+        assert_eq!(publisher.topic_name(), "/remapped/absolute");
+        let publisher = node.create_publisher::<msg::Empty>("other", QOS_PROFILE_DEFAULT)?;
+        assert_eq!(publisher.topic_name(), "/robot1/other");
+
+        // The override from the builder takes precedence over the one from the command line.
+        let rate = node
+            .declare_parameter("rate")
+            .default(1)
+            .mandatory()
+            .unwrap();
+        assert_eq!(rate.get(), 10);
+        let frame = node
+            .declare_parameter("frame")
+            .default(Arc::<str>::from("map"))
+            .mandatory()
+            .unwrap();
+        assert_eq!(&*frame.get(), "base_link");
+        Ok(())
+    }
+
     #[test]
     fn test_topic_names_and_types() -> Result<(), RclrsError> {
         use crate::QOS_PROFILE_SYSTEM_DEFAULT;
@@ -514,4 +1227,50 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_parameter_event_subscription() -> Result<(), RclrsError> {
+        use crate::vendor::rcl_interfaces::msg::ParameterEvent;
+        use std::time::Duration;
+
+        let graph = construct_test_graph("test_parameter_event_subscription")?;
+        let received_nodes = Arc::new(Mutex::new(Vec::new()));
+        let received_nodes_in_callback = Arc::clone(&received_nodes);
+        let _subscription = graph.node2.create_parameter_event_subscription(
+            Some("/test_parameter_event_subscription/watched_node"),
+            move |event: ParameterEvent| {
+                received_nodes_in_callback.lock().unwrap().push(event.node);
+            },
+        )?;
+        let publisher = graph.node1.create_publisher::<ParameterEvent>(
+            "/parameter_events",
+            QOS_PROFILE_PARAMETER_EVENTS,
+        )?;
+
+        for _ in 0..50 {
+            for node in [
+                "/test_parameter_event_subscription/watched_node",
+                "/test_parameter_event_subscription/other_node",
+            ] {
+                publisher.publish(ParameterEvent {
+                    node: node.to_string(),
+                    ..Default::default()
+                })?;
+            }
+            ignore_timeout(crate::spin_once(
+                Arc::clone(&graph.node2),
+                Some(Duration::from_millis(100)),
+            ))?;
+            if !received_nodes.lock().unwrap().is_empty() {
+                break;
+            }
+        }
+
+        let received_nodes = received_nodes.lock().unwrap();
+        assert!(!received_nodes.is_empty());
+        assert!(received_nodes
+            .iter()
+            .all(|node| node == "/test_parameter_event_subscription/watched_node"));
+        Ok(())
+    }
 }
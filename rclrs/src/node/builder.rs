@@ -4,8 +4,10 @@ use std::{
 };
 
 use crate::{
-    rcl_bindings::*, ClockType, Context, ContextHandle, Node, NodeHandle, ParameterInterface,
-    QoSProfile, RclrsError, TimeSource, ToResult, ENTITY_LIFECYCLE_MUTEX, QOS_PROFILE_CLOCK,
+    logging::LOGGING_MUTEX, rcl_bindings::*, validate_namespace, validate_node_name, Allocator,
+    CallbackGroup, CallbackGroupType, ClockType, Context, ContextHandle, Node, NodeHandle,
+    ParameterInterface, ParameterOverrideMap, ParameterValue, QoSProfile, RclrsError, TimeSource,
+    ToResult, ENTITY_LIFECYCLE_MUTEX, QOS_PROFILE_CLOCK,
 };
 
 /// A builder for creating a [`Node`][1].
@@ -17,10 +19,14 @@ use crate::{
 /// - `namespace: "/"`
 /// - `use_global_arguments: true`
 /// - `arguments: []`
+/// - `remap_rules: []`
+/// - `parameter_overrides: {}`
 /// - `enable_rosout: true`
 /// - `start_parameter_services: true`
+/// - `start_parameter_event_publisher: true`
 /// - `clock_type: ClockType::RosTime`
 /// - `clock_qos: QOS_PROFILE_CLOCK`
+/// - `use_intra_process_comms: false`
 ///
 /// # Example
 /// ```
@@ -41,18 +47,27 @@ use crate::{
 /// # Ok::<(), RclrsError>(())
 /// ```
 ///
+/// The enclave, which determines the security credentials, is not an option of a node in `rcl`,
+/// but of its context. It is set with the `--enclave` argument of [`Context::new()`][3].
+///
 /// [1]: crate::Node
 /// [2]: crate::Node::builder
+/// [3]: crate::Context::new
 pub struct NodeBuilder {
     context: Arc<ContextHandle>,
     name: String,
     namespace: String,
     use_global_arguments: bool,
     arguments: Vec<String>,
+    remap_rules: Vec<(String, String)>,
+    parameter_overrides: ParameterOverrideMap,
     enable_rosout: bool,
     start_parameter_services: bool,
+    start_parameter_event_publisher: bool,
     clock_type: ClockType,
     clock_qos: QoSProfile,
+    use_intra_process_comms: bool,
+    allocator: Option<Allocator>,
 }
 
 impl NodeBuilder {
@@ -63,7 +78,7 @@ impl NodeBuilder {
     /// # Rules for valid node names
     ///
     /// The rules for a valid node name are checked by the [`rmw_validate_node_name()`][2]
-    /// function. They are:
+    /// function, which is also available as [`validate_node_name()`][4]. They are:
     /// - Must contain only the `a-z`, `A-Z`, `0-9`, and `_` characters
     /// - Must not be empty and not be longer than `RMW_NODE_NAME_MAX_NAME_LENGTH`
     /// - Must not start with a number
@@ -72,7 +87,7 @@ impl NodeBuilder {
     ///
     /// # Example
     /// ```
-    /// # use rclrs::{Context, NodeBuilder, RclrsError, RclReturnCode};
+    /// # use rclrs::{Context, InvalidNameError, NameViolation, NodeBuilder, RclrsError};
     /// let context = Context::new([])?;
     /// // This is a valid node name
     /// assert!(NodeBuilder::new(&context, "my_node").build().is_ok());
@@ -83,7 +98,11 @@ impl NodeBuilder {
     ///     NodeBuilder::new(&context, "röböt")
     ///         .build()
     ///         .unwrap_err(),
-    ///     RclrsError::RclError { code: RclReturnCode::NodeInvalidName, .. }
+    ///     RclrsError::InvalidName(InvalidNameError {
+    ///         violation: NameViolation::ContainsUnallowedCharacters,
+    ///         invalid_index: 1,
+    ///         ..
+    ///     })
     /// ));
     /// # Ok::<(), RclrsError>(())
     /// ```
@@ -91,6 +110,7 @@ impl NodeBuilder {
     /// [1]: crate::Node#naming
     /// [2]: https://docs.ros2.org/latest/api/rmw/validate__node__name_8h.html#a5690a285aed9735f89ef11950b6e39e3
     /// [3]: NodeBuilder::build
+    /// [4]: crate::validate_node_name
     pub fn new(context: &Context, name: &str) -> NodeBuilder {
         NodeBuilder {
             context: Arc::clone(&context.handle),
@@ -98,10 +118,15 @@ impl NodeBuilder {
             namespace: "/".to_string(),
             use_global_arguments: true,
             arguments: vec![],
+            remap_rules: vec![],
+            parameter_overrides: ParameterOverrideMap::new(),
             enable_rosout: true,
             start_parameter_services: true,
+            start_parameter_event_publisher: true,
             clock_type: ClockType::RosTime,
             clock_qos: QOS_PROFILE_CLOCK,
+            use_intra_process_comms: false,
+            allocator: None,
         }
     }
 
@@ -126,7 +151,7 @@ impl NodeBuilder {
     ///
     /// # Example
     /// ```
-    /// # use rclrs::{Context, Node, RclrsError, RclReturnCode};
+    /// # use rclrs::{Context, InvalidNameError, NameViolation, Node, RclrsError};
     /// let context = Context::new([])?;
     /// // This is a valid namespace
     /// let builder_ok_ns = Node::builder(&context, "my_node").namespace("/some/nested/namespace");
@@ -137,7 +162,11 @@ impl NodeBuilder {
     ///         .namespace("/10_percent_luck/20_percent_skill")
     ///         .build()
     ///         .unwrap_err(),
-    ///     RclrsError::RclError { code: RclReturnCode::NodeInvalidNamespace, .. }
+    ///     RclrsError::InvalidName(InvalidNameError {
+    ///         violation: NameViolation::NameTokenStartsWithNumber,
+    ///         invalid_index: 1,
+    ///         ..
+    ///     })
     /// ));
     /// // A missing forward slash at the beginning is automatically added
     /// assert_eq!(
@@ -223,12 +252,61 @@ impl NodeBuilder {
         self
     }
 
+    /// Adds a rule that remaps the name `from` to `to` for this node.
+    ///
+    /// This is equivalent to passing `--ros-args --remap from:=to` to [`arguments()`][1], and
+    /// applies to the names of topics, services and actions as well as to the special names
+    /// `__node` and `__ns`. The rules are applied in the order in which they were added, after
+    /// the rules given in the arguments.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, Node, RclrsError, QOS_PROFILE_DEFAULT};
+    /// # use rclrs::msg::Time;
+    /// let context = Context::new([])?;
+    /// let node = Node::builder(&context, "my_node")
+    ///     .namespace("/robot")
+    ///     .remap_rule("chatter", "talk")
+    ///     .build()?;
+    /// let publisher = node.create_publisher::<Time>("chatter", QOS_PROFILE_DEFAULT)?;
+    /// assert_eq!(publisher.topic_name(), "/robot/talk");
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    ///
+    /// [1]: NodeBuilder::arguments
+    pub fn remap_rule(mut self, from: &str, to: &str) -> Self {
+        self.remap_rules.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Sets the value of a parameter that is used when the parameter is declared.
+    ///
+    /// This takes precedence over parameter overrides from the command line, i.e. the arguments
+    /// of the context and of the node.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, Node, RclrsError};
+    /// let context = Context::new([])?;
+    /// let node = Node::builder(&context, "my_node")
+    ///     .parameter_override("rate", 10)
+    ///     .build()?;
+    /// let rate = node.declare_parameter("rate").default(1).mandatory()?;
+    /// assert_eq!(rate.get(), 10);
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    pub fn parameter_override(mut self, name: &str, value: impl Into<ParameterValue>) -> Self {
+        self.parameter_overrides
+            .insert(name.to_string(), value.into());
+        self
+    }
+
     /// Enables or disables logging to rosout.
     ///
     /// When enabled, log messages are published to the `/rosout` topic in addition to
-    /// standard output.
-    ///
-    /// This option is currently unused in `rclrs`.
+    /// standard output. When disabled, the node does not create a `/rosout` publisher, which
+    /// is useful e.g. for short-lived utility nodes. Log messages of the node are still written
+    /// to standard output.
     pub fn enable_rosout(mut self, enable: bool) -> Self {
         self.enable_rosout = enable;
         self
@@ -238,11 +316,29 @@ impl NodeBuilder {
     ///
     /// Parameter services can be used to allow external nodes to list, get and set
     /// parameters for this node.
+    ///
+    /// When enabled, which is the default, the node offers the `describe_parameters`,
+    /// `get_parameter_types`, `get_parameters`, `list_parameters`, `set_parameters` and
+    /// `set_parameters_atomically` services of `rcl_interfaces` below its fully qualified name.
+    /// These are used by tools like `ros2 param`.
     pub fn start_parameter_services(mut self, start: bool) -> Self {
         self.start_parameter_services = start;
         self
     }
 
+    /// Enables or disables the parameter event publisher.
+    ///
+    /// When enabled, the node publishes a `rcl_interfaces/msg/ParameterEvent` on the
+    /// `/parameter_events` topic whenever one of its parameters is declared, changed or
+    /// undeclared. This allows other nodes to track parameter changes, see also
+    /// [`Node::create_parameter_event_subscription()`][1].
+    ///
+    /// [1]: crate::Node::create_parameter_event_subscription
+    pub fn start_parameter_event_publisher(mut self, start: bool) -> Self {
+        self.start_parameter_event_publisher = start;
+        self
+    }
+
     /// Sets the node's clock type.
     pub fn clock_type(mut self, clock_type: ClockType) -> Self {
         self.clock_type = clock_type;
@@ -255,9 +351,55 @@ impl NodeBuilder {
         self
     }
 
+    /// Enables or disables intra-process communication for the publishers and subscriptions of
+    /// the node.
+    ///
+    /// When enabled, messages are delivered directly between the publishers and subscriptions
+    /// of nodes in the same context that have intra-process communication enabled, without
+    /// going through the middleware. The published message is moved into an [`Arc`], which is
+    /// shared by all receiving subscriptions, so a subscription callback taking an `Arc<T>` gets
+    /// the message without any copy. Such messages are marked with
+    /// [`MessageInfo::from_intra_process`][1].
+    ///
+    /// Publishers still publish through the middleware when there are subscriptions that don't
+    /// receive their messages intra-process, e.g. in other processes.
+    ///
+    /// This setting can be overridden for individual publishers and subscriptions with
+    /// [`PublisherOptions::use_intra_process()`][3] and
+    /// [`SubscriptionOptions::use_intra_process()`][4].
+    ///
+    /// Some limitations apply to intra-process communication:
+    /// - Only the history depth of the subscription's QoS profile is taken into account; e.g.
+    ///   transient local durability has no effect.
+    /// - Subscriptions with loaned or serialized message callbacks, and loaned messages of
+    ///   publishers, always use the middleware.
+    /// - Messages received intra-process are only passed to the subscription callback, not
+    ///   returned by [`Subscription::take()`][2].
+    ///
+    /// [1]: crate::MessageInfo::from_intra_process
+    /// [2]: crate::Subscription::take
+    /// [3]: crate::PublisherOptions::use_intra_process
+    /// [4]: crate::SubscriptionOptions::use_intra_process
+    pub fn use_intra_process_comms(mut self, enable: bool) -> Self {
+        self.use_intra_process_comms = enable;
+        self
+    }
+
+    /// Sets the allocator that rcl uses for the node.
+    ///
+    /// By default, the node uses the allocator of its context, see
+    /// [`InitOptions::with_allocator`][1].
+    ///
+    /// [1]: crate::InitOptions::with_allocator
+    pub fn allocator(mut self, allocator: Allocator) -> Self {
+        self.allocator = Some(allocator);
+        self
+    }
+
     /// Builds the node instance.
     ///
-    /// Node name and namespace validation is performed in this method.
+    /// Node name and namespace validation is performed in this method. An invalid name or
+    /// namespace is reported as [`RclrsError::InvalidName`].
     ///
     /// For example usage, see the [`NodeBuilder`][1] docs.
     ///
@@ -273,6 +415,8 @@ impl NodeBuilder {
                 err,
                 s: self.namespace.clone(),
             })?;
+        validate_node_name(&self.name)?;
+        self.check_namespace()?;
         let rcl_node_options = self.create_rcl_node_options()?;
         let rcl_context = &mut *self.context.rcl_context.lock().unwrap();
 
@@ -285,6 +429,8 @@ impl NodeBuilder {
             // * The rcl_context is kept alive by the ContextHandle because it is a dependency of the node.
             // * The entity lifecycle mutex is locked to protect against the risk of
             //   global variables in the rmw implementation being unsafely modified during cleanup.
+            // * The logging mutex is locked, since this creates the rosout publisher of the node.
+            let _logging_lock = LOGGING_MUTEX.lock().unwrap();
             let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
             rcl_node_init(
                 &mut rcl_node,
@@ -293,12 +439,14 @@ impl NodeBuilder {
                 rcl_context,
                 &rcl_node_options,
             )
-            .ok()?;
+            .ok_for("rcl_node_init")?;
         };
 
         let handle = Arc::new(NodeHandle {
             rcl_node: Mutex::new(rcl_node),
             context_handle: Arc::clone(&self.context),
+            use_intra_process_comms: self.use_intra_process_comms,
+            allocator: self.allocator.clone(),
         });
         let parameter = {
             let rcl_node = handle.rcl_node.lock().unwrap();
@@ -306,19 +454,32 @@ impl NodeBuilder {
                 &rcl_node,
                 &rcl_node_options.arguments,
                 &rcl_context.global_arguments,
+                self.parameter_overrides.clone(),
             )?
         };
         let node = Arc::new(Node {
             handle,
+            default_callback_group: Arc::new(CallbackGroup::new(
+                CallbackGroupType::MutuallyExclusive,
+            )),
+            action_clients_mtx: Mutex::new(vec![]),
+            action_servers_mtx: Mutex::new(vec![]),
             clients_mtx: Mutex::new(vec![]),
+            events_mtx: Mutex::new(vec![]),
             guard_conditions_mtx: Mutex::new(vec![]),
             services_mtx: Mutex::new(vec![]),
             subscriptions_mtx: Mutex::new(vec![]),
+            timers_mtx: Mutex::new(vec![]),
             time_source: TimeSource::builder(self.clock_type)
                 .clock_qos(self.clock_qos)
                 .build(),
             parameter,
         });
+        // Created before the time source declares its parameter, so that this declaration is
+        // published as well.
+        if self.start_parameter_event_publisher {
+            node.parameter.create_event_publisher(&node)?;
+        }
         node.time_source.attach_node(&node);
         if self.start_parameter_services {
             node.parameter.create_services(&node)?;
@@ -332,16 +493,51 @@ impl NodeBuilder {
     /// For detail about default values, see [`NodeBuilder`][1] docs.
     ///
     /// [1]: crate::NodeBuilder
+    // Validates the namespace like rcl_node_init() does, i.e. after adding a missing leading
+    // forward slash. An empty namespace is the same as "/".
+    fn check_namespace(&self) -> Result<(), RclrsError> {
+        if self.namespace.is_empty() {
+            return Ok(());
+        }
+        if self.namespace.starts_with('/') {
+            return validate_namespace(&self.namespace);
+        }
+        validate_namespace(&format!("/{}", self.namespace)).map_err(|err| match err {
+            // Report the namespace as it was passed to the builder.
+            RclrsError::InvalidName(mut err) => {
+                err.name = self.namespace.clone();
+                err.invalid_index = err.invalid_index.saturating_sub(1);
+                RclrsError::InvalidName(err)
+            }
+            err => err,
+        })
+    }
+
     fn create_rcl_node_options(&self) -> Result<rcl_node_options_t, RclrsError> {
         // SAFETY: No preconditions for this function.
         let mut rcl_node_options = unsafe { rcl_node_get_default_options() };
+        // The allocator is kept alive by the NodeHandle, or by the ContextHandle if the node
+        // uses the allocator of its context.
+        let allocator = match &self.allocator {
+            Some(allocator) => allocator.to_rcl(),
+            None => self.context.rcl_allocator(),
+        };
 
+        let remap_args = self.remap_rules.iter().flat_map(|(from, to)| {
+            [
+                "--ros-args".to_string(),
+                "--remap".to_string(),
+                format!("{from}:={to}"),
+            ]
+        });
         let cstring_args = self
             .arguments
             .iter()
+            .cloned()
+            .chain(remap_args)
             .map(|s| match CString::new(s.as_str()) {
                 Ok(cstr) => Ok(cstr),
-                Err(err) => Err(RclrsError::StringContainsNul { s: s.clone(), err }),
+                Err(err) => Err(RclrsError::StringContainsNul { s, err }),
             })
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -352,16 +548,15 @@ impl NodeBuilder {
             rcl_parse_arguments(
                 cstring_arg_ptrs.len() as i32,
                 cstring_arg_ptrs.as_ptr(),
-                rcutils_get_default_allocator(),
+                allocator,
                 &mut rcl_node_options.arguments,
             )
         }
-        .ok()?;
+        .ok_for("rcl_parse_arguments")?;
 
         rcl_node_options.use_global_arguments = self.use_global_arguments;
         rcl_node_options.enable_rosout = self.enable_rosout;
-        // SAFETY: No preconditions for this function.
-        rcl_node_options.allocator = unsafe { rcutils_get_default_allocator() };
+        rcl_node_options.allocator = allocator;
 
         Ok(rcl_node_options)
     }
@@ -372,7 +567,9 @@ impl Drop for rcl_node_options_t {
         // SAFETY: Do not finish this struct except here.
         unsafe {
             // This also finalizes the `rcl_arguments_t` contained in `rcl_node_options_t`.
-            rcl_node_options_fini(self).ok().unwrap();
+            rcl_node_options_fini(self)
+                .ok_for("rcl_node_options_fini")
+                .unwrap();
         }
     }
 }
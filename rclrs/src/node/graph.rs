@@ -1,16 +1,20 @@
 use std::{
     collections::HashMap,
     ffi::{CStr, CString},
+    os::raw::{c_char, c_void},
     slice,
+    time::Duration,
 };
 
-use crate::{rcl_bindings::*, Node, RclrsError, ToResult};
+use crate::{rcl_bindings::*, Node, QoSProfile, RclReturnCode, RclrsError, ToResult};
 
 impl Drop for rmw_names_and_types_t {
     fn drop(&mut self) {
         // SAFETY: No preconditions for this function.
         unsafe {
-            rcl_names_and_types_fini(self).ok().unwrap();
+            rcl_names_and_types_fini(self)
+                .ok_for("rcl_names_and_types_fini")
+                .unwrap();
         }
     }
 }
@@ -20,7 +24,7 @@ impl Drop for rmw_topic_endpoint_info_array_t {
         // SAFETY: No preconditions for this function.
         unsafe {
             rmw_topic_endpoint_info_array_fini(self, &mut rcutils_get_default_allocator())
-                .ok()
+                .ok_for("rmw_topic_endpoint_info_array_fini")
                 .unwrap();
         }
     }
@@ -48,7 +52,7 @@ pub struct NodeNameInfo {
 }
 
 /// Contains topic endpoint information
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TopicEndpointInfo {
     /// The name of the endpoint node
     pub node_name: String,
@@ -56,6 +60,13 @@ pub struct TopicEndpointInfo {
     pub node_namespace: String,
     /// The type of the topic
     pub topic_type: String,
+    /// The GID of the endpoint, which identifies it in the RMW implementation
+    pub endpoint_gid: [u8; RMW_GID_STORAGE_SIZE],
+    /// The QoS profile of the endpoint
+    ///
+    /// This is the profile that is actually used by the endpoint, so it can be compared with
+    /// a local profile with [`QoSProfile::check_compatible()`].
+    pub qos: QoSProfile,
 }
 
 impl Node {
@@ -145,7 +156,7 @@ impl Node {
                 false,
                 &mut rcl_names_and_types,
             )
-            .ok()?
+            .ok_for("rcl_get_topic_names_and_types")?
         };
 
         Ok(convert_names_and_types(rcl_names_and_types))
@@ -175,7 +186,7 @@ impl Node {
                 &mut rcl_names,
                 &mut rcl_namespaces,
             )
-            .ok()?;
+            .ok_for("rcl_get_node_names")?;
         };
 
         // SAFETY: Because the previous function call successfully returned, the names and
@@ -224,7 +235,7 @@ impl Node {
                 &mut rcl_namespaces,
                 &mut rcl_enclaves,
             )
-            .ok()?;
+            .ok_for("rcl_get_node_names_with_enclaves")?;
         };
 
         // SAFETY: The previous function successfully returned, so the arrays are valid
@@ -257,43 +268,201 @@ impl Node {
     }
 
     /// Counts the number of publishers for a given topic.
+    ///
+    /// The namespace of the node and remapping rules are applied to the topic name, like when
+    /// creating a publisher.
     pub fn count_publishers(&self, topic: &str) -> Result<usize, RclrsError> {
-        let topic_name = CString::new(topic).map_err(|err| RclrsError::StringContainsNul {
-            s: topic.to_string(),
-            err,
-        })?;
+        let topic_name = self.resolve_name(topic, false)?;
         let mut count: usize = 0;
 
         // SAFETY: The topic_name string was correctly allocated previously
         unsafe {
             let rcl_node = self.handle.rcl_node.lock().unwrap();
-            rcl_count_publishers(&*rcl_node, topic_name.as_ptr(), &mut count).ok()?
+            rcl_count_publishers(&*rcl_node, topic_name.as_ptr(), &mut count)
+                .ok_for("rcl_count_publishers")?
         };
         Ok(count)
     }
 
     /// Counts the number of subscriptions for a given topic.
+    ///
+    /// The namespace of the node and remapping rules are applied to the topic name, like when
+    /// creating a subscription.
     pub fn count_subscriptions(&self, topic: &str) -> Result<usize, RclrsError> {
-        let topic_name = CString::new(topic).map_err(|err| RclrsError::StringContainsNul {
-            s: topic.to_string(),
-            err,
-        })?;
+        let topic_name = self.resolve_name(topic, false)?;
         let mut count: usize = 0;
 
         // SAFETY: The topic_name string was correctly allocated previously
         unsafe {
             let rcl_node = self.handle.rcl_node.lock().unwrap();
-            rcl_count_subscribers(&*rcl_node, topic_name.as_ptr(), &mut count).ok()?
+            rcl_count_subscribers(&*rcl_node, topic_name.as_ptr(), &mut count)
+                .ok_for("rcl_count_subscribers")?
         };
         Ok(count)
     }
 
+    /// Blocks until the ROS graph changes, or the timeout elapses.
+    ///
+    /// The graph changes e.g. when a node, publisher, subscription, service or client is created
+    /// or destroyed anywhere in the ROS network. This can be used to react to discovery, instead
+    /// of polling functions like [`Node::count_publishers()`] in a loop.
+    ///
+    /// Returns `true` if the graph changed, and `false` if the timeout elapsed or the context was
+    /// shut down first. Passing `None` as the timeout waits indefinitely.
+    ///
+    /// Since the graph may change at any time, the condition of interest should be checked again
+    /// after this returns.
+    pub fn wait_for_graph_change(&self, timeout: Option<Duration>) -> Result<bool, RclrsError> {
+        let timeout_ns = match timeout.map(|d| d.as_nanos()) {
+            None => -1,
+            Some(ns) if ns <= i64::MAX as u128 => ns as i64,
+            _ => {
+                return Err(RclrsError::RclError {
+                    code: RclReturnCode::InvalidArgument,
+                    operation: None,
+                    msg: None,
+                })
+            }
+        };
+        let context_handle = &self.handle.context_handle;
+        // The wait set is finalized when it is dropped.
+        let mut rcl_wait_set = unsafe {
+            // SAFETY: Getting a zero-initialized value is always safe
+            let mut rcl_wait_set = rcl_get_zero_initialized_wait_set();
+            let mut rcl_context = context_handle.rcl_context.lock().unwrap();
+            // SAFETY: We're passing in a zero-initialized wait set and a valid context.
+            // There are no other preconditions.
+            rcl_wait_set_init(
+                &mut rcl_wait_set,
+                0,
+                2,
+                0,
+                0,
+                0,
+                0,
+                &mut *rcl_context,
+                context_handle.rcl_allocator(),
+            )
+            .ok_for("rcl_wait_set_init")?;
+            rcl_wait_set
+        };
+        let mut graph_guard_condition_index = 0;
+        {
+            let rcl_node = self.handle.rcl_node.lock().unwrap();
+            let shutdown_guard_condition = context_handle.shutdown_guard_condition.lock().unwrap();
+            // SAFETY: The graph guard condition is valid as long as the node is, and both guard
+            // conditions outlive the wait set, which is finalized at the end of this function.
+            unsafe {
+                let graph_guard_condition = rcl_node_get_graph_guard_condition(&*rcl_node);
+                rcl_wait_set_add_guard_condition(
+                    &mut rcl_wait_set,
+                    graph_guard_condition,
+                    &mut graph_guard_condition_index,
+                )
+                .ok_for("rcl_wait_set_add_guard_condition")?;
+                rcl_wait_set_add_guard_condition(
+                    &mut rcl_wait_set,
+                    &*shutdown_guard_condition,
+                    std::ptr::null_mut(),
+                )
+                .ok_for("rcl_wait_set_add_guard_condition")?;
+            }
+        }
+        // SAFETY: The wait set is valid and not shared with other threads.
+        match unsafe { rcl_wait(&mut rcl_wait_set, timeout_ns) }.ok_for("rcl_wait") {
+            Ok(()) => {}
+            Err(RclrsError::RclError {
+                code: RclReturnCode::Timeout,
+                ..
+            }) => return Ok(false),
+            Err(e) => return Err(e),
+        }
+        // SAFETY: The `guard_conditions` entry is an array of pointers, and the index was
+        // returned when adding the guard condition. Entries that were not triggered are NULL.
+        let graph_guard_condition = unsafe {
+            *rcl_wait_set
+                .guard_conditions
+                .add(graph_guard_condition_index)
+        };
+        Ok(!graph_guard_condition.is_null())
+    }
+
+    /// Returns the fully qualified name that a topic name resolves to for this node.
+    ///
+    /// Relative names are expanded with the namespace of the node, private names starting with
+    /// `~/` are expanded with the fully qualified name of the node, and absolute names are kept.
+    /// Remapping rules are applied to the expanded name. This is the name that a publisher or
+    /// subscription created with the same topic name would use.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{Context, RclrsError};
+    /// let context = Context::new([])?;
+    /// let node = rclrs::create_node_builder(&context, "camera")
+    ///     .namespace("/robot1")
+    ///     .build()?;
+    /// assert_eq!(node.resolve_topic_name("image")?, "/robot1/image");
+    /// assert_eq!(node.resolve_topic_name("~/image")?, "/robot1/camera/image");
+    /// assert_eq!(node.resolve_topic_name("/image")?, "/image");
+    /// # Ok::<(), RclrsError>(())
+    /// ```
+    pub fn resolve_topic_name(&self, topic: &str) -> Result<String, RclrsError> {
+        let resolved_name = self.resolve_name(topic, false)?;
+        Ok(resolved_name.to_string_lossy().into_owned())
+    }
+
+    /// Returns the fully qualified name that a service name resolves to for this node.
+    ///
+    /// This is the same as [`Node::resolve_topic_name()`], except that the remapping rules for
+    /// services are applied.
+    pub fn resolve_service_name(&self, service: &str) -> Result<String, RclrsError> {
+        let resolved_name = self.resolve_name(service, true)?;
+        Ok(resolved_name.to_string_lossy().into_owned())
+    }
+
+    // Expands the topic or service name with the namespace of the node and applies remapping
+    // rules.
+    fn resolve_name(&self, name: &str, is_service: bool) -> Result<CString, RclrsError> {
+        let c_name = CString::new(name).map_err(|err| RclrsError::StringContainsNul {
+            s: name.to_string(),
+            err,
+        })?;
+        let mut resolved_name: *mut c_char = std::ptr::null_mut();
+        unsafe {
+            // SAFETY: No preconditions for this function.
+            let allocator = rcutils_get_default_allocator();
+            {
+                let rcl_node = self.handle.rcl_node.lock().unwrap();
+                // SAFETY: The name is a valid string, and the output is a null pointer as
+                // expected by this function.
+                rcl_node_resolve_name(
+                    &*rcl_node,
+                    c_name.as_ptr(),
+                    allocator,
+                    is_service,
+                    false,
+                    &mut resolved_name,
+                )
+                .ok_for("rcl_node_resolve_name")?;
+            }
+            // SAFETY: The resolved name is a valid string that was allocated with the allocator,
+            // and it is not used after being deallocated.
+            let resolved = CStr::from_ptr(resolved_name).to_owned();
+            allocator.deallocate.unwrap()(resolved_name as *mut c_void, allocator.state);
+            Ok(resolved)
+        }
+    }
+
     /// Returns topic publisher info.
     pub fn get_publishers_info_by_topic(
         &self,
         topic: &str,
     ) -> Result<Vec<TopicEndpointInfo>, RclrsError> {
-        self.get_publisher_subscriber_info_by_topic(topic, rcl_get_publishers_info_by_topic)
+        self.get_publisher_subscriber_info_by_topic(
+            topic,
+            rcl_get_publishers_info_by_topic,
+            "rcl_get_publishers_info_by_topic",
+        )
     }
 
     /// Returns topic subscriptions info.
@@ -301,7 +470,11 @@ impl Node {
         &self,
         topic: &str,
     ) -> Result<Vec<TopicEndpointInfo>, RclrsError> {
-        self.get_publisher_subscriber_info_by_topic(topic, rcl_get_subscriptions_info_by_topic)
+        self.get_publisher_subscriber_info_by_topic(
+            topic,
+            rcl_get_subscriptions_info_by_topic,
+            "rcl_get_subscriptions_info_by_topic",
+        )
     }
 
     /// Returns an rcl names_and_types function, without a "no_demangle" argument.
@@ -356,11 +529,9 @@ impl Node {
             bool,
             *mut rcl_topic_endpoint_info_array_t,
         ) -> rcl_ret_t,
+        getter_name: &'static str,
     ) -> Result<Vec<TopicEndpointInfo>, RclrsError> {
-        let topic = CString::new(topic).map_err(|err| RclrsError::StringContainsNul {
-            s: topic.to_string(),
-            err,
-        })?;
+        let topic = self.resolve_name(topic, false)?;
 
         // SAFETY: Getting a zero-initialized value is always safe.
         let mut rcl_publishers_info =
@@ -376,7 +547,7 @@ impl Node {
                 false,
                 &mut rcl_publishers_info,
             )
-            .ok()?;
+            .ok_for(getter_name)?;
         }
 
         // SAFETY: The previous call returned successfully, so the slice is valid
@@ -406,6 +577,8 @@ impl Node {
                     node_name,
                     node_namespace,
                     topic_type,
+                    endpoint_gid: info.endpoint_gid,
+                    qos: QoSProfile::from(info.qos_profile),
                 }
             })
             .collect();
@@ -488,7 +661,12 @@ mod tests {
             Context::new_with_options([], InitOptions::new().with_domain_id(Some(domain_id)))
                 .unwrap();
         let node_name = "test_publisher_names_and_types";
-        let node = Node::new(&context, node_name).unwrap();
+        // The rosout and parameter event publishers would show up in the graph.
+        let node = Node::builder(&context, node_name)
+            .enable_rosout(false)
+            .start_parameter_event_publisher(false)
+            .build()
+            .unwrap();
         // Test that the graph has no publishers
         let names_and_topics = node
             .get_publisher_names_and_types_by_node(node_name, "")
@@ -543,6 +721,166 @@ mod tests {
         assert_eq!(names_and_topics.len(), 0);
     }
 
+    #[test]
+    fn test_enable_rosout() -> Result<(), RclrsError> {
+        let namespace = "/test_enable_rosout";
+        let context = Context::new([])?;
+        let node_with_rosout = Node::builder(&context, "with_rosout")
+            .namespace(namespace)
+            .build()?;
+        let node_without_rosout = Node::builder(&context, "without_rosout")
+            .namespace(namespace)
+            .enable_rosout(false)
+            .build()?;
+
+        let publishes_rosout = |node_name: &str| -> Result<bool, RclrsError> {
+            Ok(node_with_rosout
+                .get_publisher_names_and_types_by_node(node_name, namespace)?
+                .contains_key("/rosout"))
+        };
+        // The graph is updated asynchronously
+        for _ in 0..50 {
+            if publishes_rosout("with_rosout")? {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        assert!(publishes_rosout("with_rosout")?);
+        assert!(!publishes_rosout("without_rosout")?);
+        assert!(node_without_rosout.count_publishers("/rosout")? >= 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_topic_names_and_types_with_publisher() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use test_msgs::msg;
+
+        let context = Context::new([])?;
+        let node = Node::new(&context, "test_topic_names_and_types_with_publisher")?;
+        let _publisher = node.create_publisher::<msg::Strings>("/foo", QOS_PROFILE_DEFAULT)?;
+
+        // The graph is updated asynchronously
+        let mut topic_names_and_types = node.get_topic_names_and_types()?;
+        for _ in 0..50 {
+            if topic_names_and_types.contains_key("/foo") {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            topic_names_and_types = node.get_topic_names_and_types()?;
+        }
+
+        let types = topic_names_and_types.get("/foo").unwrap();
+        assert_eq!(types, &vec!["test_msgs/msg/Strings".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_publishers_and_subscriptions() -> Result<(), RclrsError> {
+        use crate::{test_helpers::construct_test_graph, QOS_PROFILE_DEFAULT};
+        use test_msgs::msg;
+
+        let namespace = "/test_count_publishers_and_subscriptions";
+        let graph = construct_test_graph(namespace)?;
+        let _publisher_1 = graph
+            .node1
+            .create_publisher::<msg::Empty>("count_topic", QOS_PROFILE_DEFAULT)?;
+        let _publisher_2 = graph
+            .node2
+            .create_publisher::<msg::Empty>("count_topic", QOS_PROFILE_DEFAULT)?;
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            "count_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::Empty| {},
+        )?;
+
+        // The graph is updated asynchronously
+        for _ in 0..50 {
+            if graph.node1.count_publishers("count_topic")? == 2
+                && graph.node1.count_subscriptions("count_topic")? == 1
+            {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+
+        // The relative topic name is expanded with the namespace of the node
+        assert_eq!(graph.node1.count_publishers("count_topic")?, 2);
+        assert_eq!(graph.node1.count_subscriptions("count_topic")?, 1);
+        let fully_qualified_topic = format!("{}/count_topic", namespace);
+        assert_eq!(graph.node1.count_publishers(&fully_qualified_topic)?, 2);
+        assert_eq!(graph.node1.count_subscriptions(&fully_qualified_topic)?, 1);
+        assert_eq!(graph.node1.count_publishers("/count_topic")?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_for_graph_change() -> Result<(), RclrsError> {
+        use crate::{test_helpers::construct_test_graph, QOS_PROFILE_DEFAULT};
+        use std::{sync::Arc, time::Instant};
+        use test_msgs::msg;
+
+        let namespace = "/test_wait_for_graph_change";
+        let graph = construct_test_graph(namespace)?;
+        let node1 = Arc::clone(&graph.node1);
+        let publisher_thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            node1.create_publisher::<msg::Empty>("graph_change_topic", QOS_PROFILE_DEFAULT)
+        });
+
+        // Other tests change the graph as well, so wait until the publisher was discovered.
+        let timeout = Duration::from_secs(10);
+        let start = Instant::now();
+        while graph.node2.count_publishers("graph_change_topic")? == 0 {
+            let remaining = timeout
+                .checked_sub(start.elapsed())
+                .expect("The publisher was not discovered before the timeout");
+            assert!(graph.node2.wait_for_graph_change(Some(remaining))?);
+        }
+        let _publisher = publisher_thread.join().unwrap()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_names() -> Result<(), RclrsError> {
+        use crate::NodeBuilder;
+
+        let remapping = [
+            "--ros-args",
+            "-r",
+            "image:=image_raw",
+            "-r",
+            "/robot1/set_mode:=/robot1/set_camera_mode",
+        ]
+        .map(String::from);
+        let context = Context::new(remapping)?;
+        let node = NodeBuilder::new(&context, "camera")
+            .namespace("/robot1")
+            .build()?;
+
+        // Relative names
+        assert_eq!(node.resolve_topic_name("info")?, "/robot1/info");
+        assert_eq!(node.resolve_service_name("reset")?, "/robot1/reset");
+        // Private names
+        assert_eq!(node.resolve_topic_name("~/info")?, "/robot1/camera/info");
+        assert_eq!(
+            node.resolve_service_name("~/reset")?,
+            "/robot1/camera/reset"
+        );
+        // Absolute names
+        assert_eq!(node.resolve_topic_name("/info")?, "/info");
+        assert_eq!(node.resolve_service_name("/reset")?, "/reset");
+        // Remapped names
+        assert_eq!(node.resolve_topic_name("image")?, "/robot1/image_raw");
+        assert_eq!(
+            node.resolve_service_name("set_mode")?,
+            "/robot1/set_camera_mode"
+        );
+        // Invalid names
+        assert!(node.resolve_topic_name("invalid name").is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_node_names() {
         let context = Context::new([]).unwrap();
@@ -559,6 +897,23 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn test_node_names_of_two_nodes() -> Result<(), RclrsError> {
+        use crate::test_helpers::construct_test_graph;
+
+        let namespace = "/test_node_names_of_two_nodes";
+        let graph = construct_test_graph(namespace)?;
+
+        let names_and_namespaces = graph.node1.get_node_names()?;
+        for node in [&graph.node1, &graph.node2] {
+            assert!(names_and_namespaces.contains(&NodeNameInfo {
+                name: node.name(),
+                namespace: namespace.to_string()
+            }));
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_node_names_with_enclaves() {
         let context = Context::new([]).unwrap();
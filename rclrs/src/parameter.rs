@@ -8,13 +8,28 @@ pub use range::*;
 use service::*;
 pub use value::*;
 
-use crate::vendor::rcl_interfaces::msg::rmw::{ParameterType, ParameterValue as RmwParameterValue};
+use rosidl_runtime_rs::Message;
+
+use crate::vendor::{
+    builtin_interfaces::msg::rmw::Time as RmwTime,
+    rcl_interfaces::msg::{
+        rmw::{
+            Parameter as RmwParameter, ParameterEvent as RmwParameterEvent, ParameterType,
+            ParameterValue as RmwParameterValue,
+        },
+        ListParametersResult, ParameterEvent, SetParametersResult,
+    },
+};
 
-use crate::{call_string_getter_with_rcl_node, rcl_bindings::*, Node, RclrsError};
+use crate::{
+    call_string_getter_with_rcl_node, rcl_bindings::*, Clock, Node, Publisher, RclrsError,
+    QOS_PROFILE_PARAMETER_EVENTS,
+};
 use std::{
-    collections::{btree_map::Entry, BTreeMap},
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
     fmt::Debug,
     marker::PhantomData,
+    path::Path,
     sync::{Arc, Mutex, RwLock, Weak},
 };
 
@@ -74,6 +89,24 @@ impl<T: ParameterVariant> Default for ParameterOptions<T> {
     }
 }
 
+/// A parameter name together with a value, as passed to the callbacks registered with
+/// [`Node::add_on_set_parameters_callback()`][1].
+///
+/// [1]: crate::Node::add_on_set_parameters_callback
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    /// The name of the parameter.
+    pub name: Arc<str>,
+    /// The value the parameter is about to be set to.
+    pub value: ParameterValue,
+}
+
+/// A callback that validates parameter changes before they are applied, see
+/// [`Node::add_on_set_parameters_callback()`][1].
+///
+/// [1]: crate::Node::add_on_set_parameters_callback
+pub type OnSetParametersCallback = dyn Fn(&[Parameter]) -> SetParametersResult + Send + Sync;
+
 #[derive(Clone, Debug)]
 enum DeclaredValue {
     Mandatory(Arc<RwLock<ParameterValue>>),
@@ -174,6 +207,11 @@ impl<'a, T: ParameterVariant> ParameterBuilder<'a, T> {
 
     /// Declares the parameter as a ReadOnly parameter, that cannot be edited.
     ///
+    /// The value is determined once at declaration, from the parameter overrides or the default
+    /// value. Any later attempt to set the parameter, including atomic sets and requests through
+    /// the parameter services, is rejected with a "Parameter is read only" reason, before the
+    /// on-set callbacks are run.
+    ///
     /// # See also
     /// * [`Self::optional()`]
     /// * [`Self::mandatory()`]
@@ -310,10 +348,19 @@ impl<T: ParameterVariant + Debug> Debug for MandatoryParameter<T> {
 
 impl<T: ParameterVariant> Drop for MandatoryParameter<T> {
     fn drop(&mut self) {
-        // Clear the entry from the parameter map
+        // Clear the entry from the parameter map, unless the parameter was undeclared and
+        // possibly declared again in the meantime
         if let Some(map) = self.map.upgrade() {
-            let storage = &mut map.lock().unwrap().storage;
-            storage.remove(&self.name);
+            let mut map = map.lock().unwrap();
+            if matches!(
+                map.storage.get(&self.name),
+                Some(ParameterStorage::Declared(DeclaredStorage {
+                    value: DeclaredValue::Mandatory(value),
+                    ..
+                })) if Arc::ptr_eq(value, &self.value)
+            ) {
+                map.remove_parameter(&self.name);
+            }
         }
     }
 }
@@ -374,10 +421,19 @@ impl<T: ParameterVariant + Debug> Debug for OptionalParameter<T> {
 
 impl<T: ParameterVariant> Drop for OptionalParameter<T> {
     fn drop(&mut self) {
-        // Clear the entry from the parameter map
+        // Clear the entry from the parameter map, unless the parameter was undeclared and
+        // possibly declared again in the meantime
         if let Some(map) = self.map.upgrade() {
-            let storage = &mut map.lock().unwrap().storage;
-            storage.remove(&self.name);
+            let mut map = map.lock().unwrap();
+            if matches!(
+                map.storage.get(&self.name),
+                Some(ParameterStorage::Declared(DeclaredStorage {
+                    value: DeclaredValue::Optional(value),
+                    ..
+                })) if Arc::ptr_eq(value, &self.value)
+            ) {
+                map.remove_parameter(&self.name);
+            }
         }
     }
 }
@@ -405,8 +461,7 @@ impl<T: ParameterVariant> Drop for ReadOnlyParameter<T> {
     fn drop(&mut self) {
         // Clear the entry from the parameter map
         if let Some(map) = self.map.upgrade() {
-            let storage = &mut map.lock().unwrap().storage;
-            storage.remove(&self.name);
+            map.lock().unwrap().remove_parameter(&self.name);
         }
     }
 }
@@ -457,6 +512,18 @@ enum ParameterStorage {
 }
 
 impl ParameterStorage {
+    /// Returns the current value, or `None` for an optional parameter that is unset.
+    pub(crate) fn value(&self) -> Option<ParameterValue> {
+        match self {
+            ParameterStorage::Declared(storage) => match &storage.value {
+                DeclaredValue::Mandatory(v) => Some(v.read().unwrap().clone()),
+                DeclaredValue::Optional(v) => v.read().unwrap().clone(),
+                DeclaredValue::ReadOnly(v) => Some(v.clone()),
+            },
+            ParameterStorage::Undeclared(value) => Some(value.clone()),
+        }
+    }
+
     pub(crate) fn to_parameter_type(&self) -> u8 {
         match self {
             ParameterStorage::Declared(s) => match s.kind {
@@ -488,36 +555,113 @@ impl ParameterStorage {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub(crate) struct ParameterMap {
     storage: BTreeMap<Arc<str>, ParameterStorage>,
     allow_undeclared: bool,
+    on_set_callbacks: Vec<Arc<OnSetParametersCallback>>,
+    event_publisher: Option<ParameterEventPublisher>,
+}
+
+/// Publishes the parameter changes of a node on the `/parameter_events` topic.
+struct ParameterEventPublisher {
+    publisher: Arc<Publisher<ParameterEvent>>,
+    node_name: String,
+    clock: Clock,
+}
+
+fn to_rmw_parameter(name: &str, value: Option<ParameterValue>) -> RmwParameter {
+    RmwParameter {
+        name: name.into(),
+        // A missing value is represented by the PARAMETER_NOT_SET type
+        value: value.map(RmwParameterValue::from).unwrap_or_default(),
+    }
 }
 
 impl ParameterMap {
-    /// Validates the requested parameter setting and returns an error if the requested value is
-    /// not valid.
-    fn validate_parameter_setting(
-        &self,
-        name: &str,
-        value: RmwParameterValue,
-    ) -> Result<ParameterValue, &str> {
-        let Ok(value): Result<ParameterValue, _> = value.try_into() else {
-            return Err("Invalid parameter type");
+    /// Validates the parameters, runs the on-set callbacks on them and commits them if they are
+    /// accepted. The reason given by the first callback that rejects the parameters is turned
+    /// into an error with `rejected`.
+    ///
+    /// The callbacks are called without holding the lock on the map, so that they can access the
+    /// parameters of the node. Since the map can change while they run, `validate` is run again
+    /// together with `commit` under a single lock once the callbacks accepted the change.
+    fn set_with_callbacks<E>(
+        map: &Mutex<ParameterMap>,
+        parameters: Vec<Parameter>,
+        validate: impl Fn(&ParameterMap, &[Parameter]) -> Result<(), E>,
+        rejected: impl FnOnce(String) -> E,
+        commit: impl FnOnce(&mut ParameterMap, Vec<Parameter>),
+    ) -> Result<(), E> {
+        let on_set_callbacks = {
+            let map = map.lock().unwrap();
+            validate(&map, &parameters)?;
+            map.on_set_callbacks.clone()
+        };
+        for callback in &on_set_callbacks {
+            let result = callback(&parameters);
+            if !result.successful {
+                return Err(rejected(result.reason));
+            }
+        }
+        let mut map = map.lock().unwrap();
+        validate(&map, &parameters)?;
+        commit(&mut map, parameters);
+        Ok(())
+    }
+
+    /// Sets the value of a parameter object, after running the on-set callbacks on it.
+    ///
+    /// The range of the parameter is checked by the caller, and `commit` writes the value.
+    fn set_through_object(
+        map: &Weak<Mutex<ParameterMap>>,
+        name: &Arc<str>,
+        value: ParameterValue,
+        commit: impl FnOnce(ParameterValue),
+    ) -> Result<(), ParameterValueError> {
+        // Without a map, the node is gone and there are no callbacks or events
+        let Some(map) = map.upgrade() else {
+            commit(value);
+            return Ok(());
+        };
+        let parameter = Parameter {
+            name: name.clone(),
+            value,
         };
+        Self::set_with_callbacks(
+            &map,
+            vec![parameter],
+            |_, _| Ok(()),
+            ParameterValueError::Rejected,
+            |map, mut parameters| {
+                let Parameter { name, value } = parameters.remove(0);
+                commit(value.clone());
+                map.publish_event(
+                    Vec::new(),
+                    vec![to_rmw_parameter(&name, Some(value))],
+                    Vec::new(),
+                );
+            },
+        )
+    }
+
+    /// Validates the requested parameter value against the declaration of the parameter.
+    fn validate_parameter_value(&self, name: &str, value: &ParameterValue) -> Result<(), &str> {
         match self.storage.get(name) {
             Some(entry) => {
                 if let ParameterStorage::Declared(storage) = entry {
+                    // Checked first, so that read-only parameters are always rejected with the
+                    // same reason regardless of the value.
+                    if matches!(&storage.value, DeclaredValue::ReadOnly(_)) {
+                        return Err("Parameter is read only");
+                    }
                     if std::mem::discriminant(&storage.kind)
                         == std::mem::discriminant(&value.kind())
                         || matches!(storage.kind, ParameterKind::Dynamic)
                     {
-                        if !storage.options.ranges.in_range(&value) {
+                        if !storage.options.ranges.in_range(value) {
                             return Err("Parameter value is out of range");
                         }
-                        if matches!(&storage.value, DeclaredValue::ReadOnly(_)) {
-                            return Err("Parameter is read only");
-                        }
                     } else {
                         return Err(
                             "Parameter set to different type and dynamic typing is disabled",
@@ -533,11 +677,174 @@ impl ParameterMap {
                 }
             }
         }
-        Ok(value)
+        Ok(())
+    }
+
+    /// Validates the given parameters against their declarations.
+    fn validate_parameters(&self, parameters: &[Parameter]) -> Result<(), String> {
+        for parameter in parameters {
+            self.validate_parameter_value(&parameter.name, &parameter.value)?;
+        }
+        Ok(())
+    }
+
+    /// Sets all the given parameters if every one of them is valid and accepted by the on-set
+    /// callbacks, otherwise none of them is set.
+    fn set_parameters_atomically(
+        map: &Mutex<ParameterMap>,
+        parameters: Vec<Parameter>,
+    ) -> Result<(), String> {
+        Self::set_with_callbacks(
+            map,
+            parameters,
+            Self::validate_parameters,
+            |reason| reason,
+            Self::store_parameters,
+        )
+    }
+
+    /// Sets the given parameters, or stores them as undeclared parameters if they have not been
+    /// declared, so that their values are used when they are declared.
+    ///
+    /// Like [`Self::set_parameters_atomically()`], none of the parameters are set if any of them
+    /// is rejected.
+    fn load_parameters(
+        map: &Mutex<ParameterMap>,
+        parameters: Vec<Parameter>,
+    ) -> Result<(), String> {
+        Self::set_with_callbacks(
+            map,
+            parameters,
+            |map, parameters| {
+                for parameter in parameters {
+                    if let Some(ParameterStorage::Declared(_)) = map.storage.get(&parameter.name) {
+                        map.validate_parameter_value(&parameter.name, &parameter.value)
+                            .map_err(|reason| format!("{} ({})", reason, parameter.name))?;
+                    }
+                }
+                Ok(())
+            },
+            |reason| reason,
+            Self::store_parameters,
+        )
+    }
+
+    /// Lists the names of the parameters that match one of the prefixes, with the semantics of
+    /// the `rcl_interfaces/srv/ListParameters` service.
+    fn list_parameters(&self, prefixes: &[String], depth: u64) -> ListParametersResult {
+        // A depth of 0 includes all levels of the parameter tree.
+        let is_within_depth =
+            |relative_name: &str| depth == 0 || (relative_name.matches('.').count() as u64) < depth;
+        let names: Vec<String> = self
+            .storage
+            .keys()
+            .filter(|name| {
+                if prefixes.is_empty() {
+                    return is_within_depth(name);
+                }
+                prefixes.iter().any(|prefix| {
+                    if **name == **prefix {
+                        return true;
+                    }
+                    name.strip_prefix(prefix.as_str())
+                        .and_then(|rest| rest.strip_prefix('.'))
+                        .map_or(false, |rest| !rest.is_empty() && is_within_depth(rest))
+                })
+            })
+            .map(|name| name.to_string())
+            .collect();
+        let prefixes: BTreeSet<String> = names
+            .iter()
+            .filter_map(|name| name.rfind('.').map(|pos| name[..pos].to_string()))
+            .collect();
+        ListParametersResult {
+            names,
+            prefixes: prefixes.into_iter().collect(),
+        }
+    }
+
+    /// Removes a declared parameter, if it is dynamically typed and not read only.
+    fn undeclare_parameter(&mut self, name: &str) -> Result<(), String> {
+        match self.storage.get(name) {
+            Some(ParameterStorage::Declared(storage)) => {
+                if matches!(storage.value, DeclaredValue::ReadOnly(_)) {
+                    return Err(String::from("Parameter is read only"));
+                }
+                if !matches!(storage.kind, ParameterKind::Dynamic) {
+                    return Err(String::from(
+                        "Parameter is statically typed and cannot be undeclared",
+                    ));
+                }
+            }
+            _ => return Err(String::from("Parameter was not declared")),
+        }
+        self.remove_parameter(name);
+        Ok(())
+    }
+
+    /// Removes the parameter from the map and publishes its deletion.
+    fn remove_parameter(&mut self, name: &str) {
+        if self.storage.remove(name).is_some() {
+            self.publish_event(Vec::new(), Vec::new(), vec![to_rmw_parameter(name, None)]);
+        }
+    }
+
+    /// Stores the requested parameters in the map, and publishes a single event for all of them.
+    fn store_parameters(&mut self, parameters: Vec<Parameter>) {
+        let mut new_parameters = Vec::new();
+        let mut changed_parameters = Vec::new();
+        for parameter in parameters {
+            let rmw_parameter = to_rmw_parameter(&parameter.name, Some(parameter.value.clone()));
+            if self.store_parameter(parameter.name, parameter.value) {
+                new_parameters.push(rmw_parameter);
+            } else {
+                changed_parameters.push(rmw_parameter);
+            }
+        }
+        self.publish_event(new_parameters, changed_parameters, Vec::new());
+    }
+
+    /// Publishes a parameter event with the given changes, if the node has a parameter event
+    /// publisher.
+    fn publish_event(
+        &self,
+        new_parameters: Vec<RmwParameter>,
+        changed_parameters: Vec<RmwParameter>,
+        deleted_parameters: Vec<RmwParameter>,
+    ) {
+        if let Some(event_publisher) = &self.event_publisher {
+            let stamp = event_publisher.clock.now().to_ros_msg().unwrap_or_default();
+            let event = RmwParameterEvent {
+                stamp: RmwTime {
+                    sec: stamp.sec,
+                    nanosec: stamp.nanosec,
+                },
+                node: event_publisher.node_name.as_str().into(),
+                new_parameters: new_parameters.into(),
+                changed_parameters: changed_parameters.into(),
+                deleted_parameters: deleted_parameters.into(),
+            };
+            // The parameters were changed regardless of whether the event could be published, so
+            // a failure is not reported to the caller.
+            let _ = event_publisher
+                .publisher
+                .publish(ParameterEvent::from_rmw_message(event));
+        }
+    }
+
+    /// Publishes a parameter event for a change made through a parameter object.
+    fn publish_changed(map: &Weak<Mutex<ParameterMap>>, name: &str, value: Option<ParameterValue>) {
+        if let Some(map) = map.upgrade() {
+            map.lock().unwrap().publish_event(
+                Vec::new(),
+                vec![to_rmw_parameter(name, value)],
+                Vec::new(),
+            );
+        }
     }
 
-    /// Stores the requested parameter in the map.
-    fn store_parameter(&mut self, name: Arc<str>, value: ParameterValue) {
+    /// Stores the requested parameter in the map, and returns whether it did not exist before.
+    fn store_parameter(&mut self, name: Arc<str>, value: ParameterValue) -> bool {
         match self.storage.entry(name) {
             Entry::Occupied(mut entry) => match entry.get_mut() {
                 ParameterStorage::Declared(storage) => match &storage.value {
@@ -551,8 +858,10 @@ impl ParameterMap {
             },
             Entry::Vacant(entry) => {
                 entry.insert(ParameterStorage::Undeclared(value));
+                return true;
             }
         }
+        false
     }
 }
 
@@ -563,14 +872,16 @@ impl<T: ParameterVariant> MandatoryParameter<T> {
     }
 
     /// Sets the parameter value.
-    /// Returns [`ParameterValueError::OutOfRange`] if the value is out of the parameter's range.
+    /// Returns [`ParameterValueError::OutOfRange`] if the value is out of the parameter's range,
+    /// or [`ParameterValueError::Rejected`] if an on-set callback rejected the value.
     pub fn set<U: Into<T>>(&self, value: U) -> Result<(), ParameterValueError> {
         let value = value.into().into();
         if !self.ranges.in_range(&value) {
             return Err(ParameterValueError::OutOfRange);
         }
-        *self.value.write().unwrap() = value;
-        Ok(())
+        ParameterMap::set_through_object(&self.map, &self.name, value, |value| {
+            *self.value.write().unwrap() = value;
+        })
     }
 }
 
@@ -592,19 +903,22 @@ impl<T: ParameterVariant> OptionalParameter<T> {
     }
 
     /// Assigns a value to the optional parameter, setting it to `Some(value)`.
-    /// Returns [`ParameterValueError::OutOfRange`] if the value is out of the parameter's range.
+    /// Returns [`ParameterValueError::OutOfRange`] if the value is out of the parameter's range,
+    /// or [`ParameterValueError::Rejected`] if an on-set callback rejected the value.
     pub fn set<U: Into<T>>(&self, value: U) -> Result<(), ParameterValueError> {
         let value = value.into().into();
         if !self.ranges.in_range(&value) {
             return Err(ParameterValueError::OutOfRange);
         }
-        *self.value.write().unwrap() = Some(value);
-        Ok(())
+        ParameterMap::set_through_object(&self.map, &self.name, value, |value| {
+            *self.value.write().unwrap() = Some(value);
+        })
     }
 
     /// Unsets the optional parameter value to `None`.
     pub fn unset(&self) {
         *self.value.write().unwrap() = None;
+        ParameterMap::publish_changed(&self.map, &self.name, None);
     }
 }
 
@@ -622,6 +936,8 @@ pub enum ParameterValueError {
     TypeMismatch,
     /// A write on a read-only parameter was attempted.
     ReadOnly,
+    /// An on-set callback rejected the value, with the given reason.
+    Rejected(String),
 }
 
 /// Error that can be generated when doing operations on parameters.
@@ -644,6 +960,47 @@ pub enum DeclarationError {
     InvalidRange,
 }
 
+impl std::fmt::Display for ParameterValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfRange => write!(f, "Parameter value is out of range"),
+            Self::TypeMismatch => write!(
+                f,
+                "Parameter set to different type and dynamic typing is disabled"
+            ),
+            Self::ReadOnly => write!(f, "Parameter is read only"),
+            Self::Rejected(reason) => write!(f, "Parameter value was rejected: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ParameterValueError {}
+
+impl std::fmt::Display for DeclarationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AlreadyDeclared => write!(f, "Parameter was already declared"),
+            Self::NoValueAvailable => write!(f, "No value is available for the parameter"),
+            Self::OverrideValueTypeMismatch => {
+                write!(f, "The override value of the parameter has the wrong type")
+            }
+            Self::PriorValueTypeMismatch => {
+                write!(f, "The prior value of the parameter has the wrong type")
+            }
+            Self::InitialValueOutOfRange => {
+                write!(f, "The initial value of the parameter is out of range")
+            }
+            Self::InvalidRange => write!(
+                f,
+                "Invalid parameter range, the lower bound must not exceed the upper bound and \
+                 the step must be positive"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeclarationError {}
+
 impl<'a> Parameters<'a> {
     /// Tries to read a parameter of the requested type.
     ///
@@ -672,45 +1029,39 @@ impl<'a> Parameters<'a> {
     /// * [`Err(DeclarationError::OutOfRange)`] if the requested value is out of the parameter's
     /// range.
     /// * [`Err(DeclarationError::ReadOnly)`] if the parameter is read only.
+    /// * [`Err(ParameterValueError::Rejected)`] if an on-set callback rejected the value.
     pub fn set<T: ParameterVariant>(
         &self,
         name: impl Into<Arc<str>>,
         value: T,
     ) -> Result<(), ParameterValueError> {
-        let mut map = self.interface.parameter_map.lock().unwrap();
-        let name: Arc<str> = name.into();
-        match map.storage.entry(name) {
-            Entry::Occupied(mut entry) => {
-                // If it's declared, we can only set if it's the same variant.
-                // Undeclared parameters are dynamic by default
-                match entry.get_mut() {
-                    ParameterStorage::Declared(param) => {
-                        if T::kind() == param.kind {
-                            let value = value.into();
-                            if !param.options.ranges.in_range(&value) {
-                                return Err(ParameterValueError::OutOfRange);
-                            }
-                            match &param.value {
-                                DeclaredValue::Mandatory(p) => *p.write().unwrap() = value,
-                                DeclaredValue::Optional(p) => *p.write().unwrap() = Some(value),
-                                DeclaredValue::ReadOnly(_) => {
-                                    return Err(ParameterValueError::ReadOnly);
-                                }
-                            }
-                        } else {
-                            return Err(ParameterValueError::TypeMismatch);
-                        }
+        let parameter = Parameter {
+            name: name.into(),
+            value: value.into(),
+        };
+        ParameterMap::set_with_callbacks(
+            &self.interface.parameter_map,
+            vec![parameter],
+            |map, parameters| {
+                let Parameter { name, value } = &parameters[0];
+                // If it's declared, we can only set if it's the same variant, unless it is
+                // dynamically typed. Undeclared parameters are dynamic by default
+                if let Some(ParameterStorage::Declared(param)) = map.storage.get(name) {
+                    if matches!(param.value, DeclaredValue::ReadOnly(_)) {
+                        return Err(ParameterValueError::ReadOnly);
+                    }
+                    if !matches!(param.kind, ParameterKind::Dynamic) && value.kind() != param.kind {
+                        return Err(ParameterValueError::TypeMismatch);
                     }
-                    ParameterStorage::Undeclared(param) => {
-                        *param = value.into();
+                    if !param.options.ranges.in_range(value) {
+                        return Err(ParameterValueError::OutOfRange);
                     }
                 }
-            }
-            Entry::Vacant(entry) => {
-                entry.insert(ParameterStorage::Undeclared(value.into()));
-            }
-        }
-        Ok(())
+                Ok(())
+            },
+            ParameterValueError::Rejected,
+            ParameterMap::store_parameters,
+        )
     }
 }
 
@@ -725,11 +1076,14 @@ impl ParameterInterface {
         rcl_node: &rcl_node_t,
         node_arguments: &rcl_arguments_t,
         global_arguments: &rcl_arguments_t,
+        parameter_overrides: ParameterOverrideMap,
     ) -> Result<Self, RclrsError> {
-        let override_map = unsafe {
+        let mut override_map = unsafe {
             let fqn = call_string_getter_with_rcl_node(rcl_node, rcl_node_get_fully_qualified_name);
             resolve_parameter_overrides(&fqn, node_arguments, global_arguments)?
         };
+        // Overrides given in code take precedence over those from the command line.
+        override_map.extend(parameter_overrides);
 
         Ok(ParameterInterface {
             parameter_map: Default::default(),
@@ -759,6 +1113,16 @@ impl ParameterInterface {
         Ok(())
     }
 
+    pub(crate) fn create_event_publisher(&self, node: &Node) -> Result<(), RclrsError> {
+        let publisher = node.create_publisher("/parameter_events", QOS_PROFILE_PARAMETER_EVENTS)?;
+        self.parameter_map.lock().unwrap().event_publisher = Some(ParameterEventPublisher {
+            publisher,
+            node_name: node.fully_qualified_name(),
+            clock: node.get_clock(),
+        });
+        Ok(())
+    }
+
     fn get_declaration_initial_value<'a, T: ParameterVariant + 'a>(
         &self,
         name: &str,
@@ -820,19 +1184,77 @@ impl ParameterInterface {
         value: DeclaredValue,
         options: ParameterOptionsStorage,
     ) {
-        self.parameter_map.lock().unwrap().storage.insert(
-            name,
-            ParameterStorage::Declared(DeclaredStorage {
-                options,
-                value,
-                kind,
-            }),
-        );
+        let mut map = self.parameter_map.lock().unwrap();
+        let storage = ParameterStorage::Declared(DeclaredStorage {
+            options,
+            value,
+            kind,
+        });
+        let rmw_parameter = to_rmw_parameter(&name, storage.value());
+        map.storage.insert(name, storage);
+        map.publish_event(vec![rmw_parameter], Vec::new(), Vec::new());
     }
 
     pub(crate) fn allow_undeclared(&self) {
         self.parameter_map.lock().unwrap().allow_undeclared = true;
     }
+
+    pub(crate) fn set_parameters_atomically(
+        &self,
+        parameters: Vec<Parameter>,
+    ) -> Result<(), String> {
+        ParameterMap::set_parameters_atomically(&self.parameter_map, parameters)
+    }
+
+    pub(crate) fn load_parameters_from_file(
+        &self,
+        node_fqn: &str,
+        path: &Path,
+    ) -> Result<(), RclrsError> {
+        let parameters = parameters_from_file(node_fqn, path)?
+            .into_iter()
+            .map(|(name, value)| Parameter {
+                name: name.into(),
+                value,
+            })
+            .collect();
+        ParameterMap::load_parameters(&self.parameter_map, parameters)
+            .map_err(|reason| RclrsError::ParametersRejected { reason })
+    }
+
+    pub(crate) fn get_parameters(&self, names: &[&str]) -> Vec<(String, Option<ParameterValue>)> {
+        let map = self.parameter_map.lock().unwrap();
+        names
+            .iter()
+            .map(|&name| {
+                let value = map.storage.get(name).and_then(ParameterStorage::value);
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+
+    pub(crate) fn list_parameters(&self, prefixes: &[String], depth: u64) -> ListParametersResult {
+        self.parameter_map
+            .lock()
+            .unwrap()
+            .list_parameters(prefixes, depth)
+    }
+
+    pub(crate) fn undeclare_parameter(&self, name: &str) -> Result<(), RclrsError> {
+        self.parameter_map
+            .lock()
+            .unwrap()
+            .undeclare_parameter(name)
+            .map_err(|reason| RclrsError::ParametersRejected { reason })
+    }
+
+    pub(crate) fn add_on_set_callback(&self, callback: Box<OnSetParametersCallback>) {
+        self.parameter_map
+            .lock()
+            .unwrap()
+            .on_set_callbacks
+            .push(callback.into());
+    }
 }
 
 #[cfg(test)]
@@ -1250,6 +1672,324 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    fn test_unconstrained_range() {
+        let ctx = Context::new([]).unwrap();
+        let node = create_node(&ctx, "param_test_node").unwrap();
+        let param = node
+            .declare_parameter("unconstrained")
+            .default(0.0)
+            .range(ParameterRange::default())
+            .mandatory()
+            .unwrap();
+        assert!(param.set(f64::MAX).is_ok());
+        assert!(param.set(-1e300).is_ok());
+        let param = node
+            .declare_parameter("lower_only")
+            .default(5)
+            .range(ParameterRange {
+                lower: Some(0),
+                ..Default::default()
+            })
+            .mandatory()
+            .unwrap();
+        assert!(param.set(i64::MAX).is_ok());
+        let err = param.set(-1).unwrap_err();
+        assert!(matches!(err, ParameterValueError::OutOfRange));
+        assert_eq!(err.to_string(), "Parameter value is out of range");
+        assert_eq!(param.get(), i64::MAX);
+    }
+
+    #[test]
+    fn test_on_set_parameters_callbacks() {
+        let ctx = Context::new([]).unwrap();
+        let node = create_node(&ctx, "param_test_node").unwrap();
+        let param = node
+            .declare_parameter("int_param")
+            .default(5)
+            .mandatory()
+            .unwrap();
+        node.add_on_set_parameters_callback(|parameters| {
+            let negative = parameters
+                .iter()
+                .any(|p| matches!(p.value, ParameterValue::Integer(v) if v < 0));
+            SetParametersResult {
+                successful: !negative,
+                reason: if negative {
+                    String::from("negative values are not allowed")
+                } else {
+                    String::new()
+                },
+            }
+        });
+        assert!(param.set(3).is_ok());
+        assert_eq!(param.get(), 3);
+        assert!(matches!(
+            param.set(-3),
+            Err(ParameterValueError::Rejected(reason)) if reason == "negative values are not allowed"
+        ));
+        assert_eq!(param.get(), 3);
+        assert!(matches!(
+            node.use_undeclared_parameters().set("int_param", -4),
+            Err(ParameterValueError::Rejected(_))
+        ));
+        assert_eq!(param.get(), 3);
+        // Rejected undeclared parameters are not created either
+        assert!(matches!(
+            node.use_undeclared_parameters().set("undeclared_int", -1),
+            Err(ParameterValueError::Rejected(_))
+        ));
+        assert!(node
+            .use_undeclared_parameters()
+            .get::<i64>("undeclared_int")
+            .is_none());
+
+        // All callbacks must accept the change
+        node.add_on_set_parameters_callback(|parameters| SetParametersResult {
+            successful: parameters
+                .iter()
+                .all(|p| p.value != ParameterValue::Integer(7)),
+            reason: String::from("seven is not allowed"),
+        });
+        assert!(param.set(6).is_ok());
+        assert!(matches!(
+            param.set(7),
+            Err(ParameterValueError::Rejected(reason)) if reason == "seven is not allowed"
+        ));
+        assert!(matches!(
+            param.set(-7),
+            Err(ParameterValueError::Rejected(reason)) if reason == "negative values are not allowed"
+        ));
+        assert_eq!(param.get(), 6);
+    }
+
+    #[test]
+    fn test_on_set_parameters_callback_can_read_parameters() {
+        let ctx = Context::new([]).unwrap();
+        let node = create_node(&ctx, "param_test_node").unwrap();
+        let limit = node
+            .declare_parameter("limit")
+            .default(10)
+            .mandatory()
+            .unwrap();
+        let param = node
+            .declare_parameter("int_param")
+            .default(5)
+            .mandatory()
+            .unwrap();
+        let weak_node = Arc::downgrade(&node);
+        node.add_on_set_parameters_callback(move |parameters| {
+            let node = weak_node.upgrade().unwrap();
+            let limit: i64 = node.use_undeclared_parameters().get("limit").unwrap();
+            SetParametersResult {
+                successful: parameters
+                    .iter()
+                    .all(|p| !matches!(p.value, ParameterValue::Integer(v) if v > limit)),
+                reason: String::from("over the limit"),
+            }
+        });
+        assert!(param.set(10).is_ok());
+        assert!(matches!(
+            param.set(11),
+            Err(ParameterValueError::Rejected(reason)) if reason == "over the limit"
+        ));
+        assert!(limit.set(20).is_ok());
+        assert!(node
+            .use_undeclared_parameters()
+            .set("int_param", 15)
+            .is_ok());
+        assert_eq!(param.get(), 15);
+    }
+
+    #[test]
+    fn test_set_parameters_atomically() {
+        let ctx = Context::new([]).unwrap();
+        let node = create_node(&ctx, "param_test_node").unwrap();
+        let int_param = node
+            .declare_parameter("int_param")
+            .default(5)
+            .range(ParameterRange {
+                lower: Some(0),
+                upper: Some(10),
+                step: None,
+            })
+            .mandatory()
+            .unwrap();
+        let string_param = node
+            .declare_parameter("string_param")
+            .default(Arc::from("foo"))
+            .mandatory()
+            .unwrap();
+        let read_only_param = node
+            .declare_parameter("read_only_param")
+            .default(1.0)
+            .read_only()
+            .unwrap();
+
+        let parameters = |int_value: ParameterValue| {
+            vec![
+                Parameter {
+                    name: "int_param".into(),
+                    value: int_value,
+                },
+                Parameter {
+                    name: "string_param".into(),
+                    value: ParameterValue::String("bar".into()),
+                },
+            ]
+        };
+        // A fully valid batch is applied
+        let result = node
+            .set_parameters_atomically(parameters(ParameterValue::Integer(7)))
+            .unwrap();
+        assert!(result.successful);
+        assert_eq!(int_param.get(), 7);
+        assert_eq!(&*string_param.get(), "bar");
+        string_param.set("foo").unwrap();
+
+        // A batch with one bad entry leaves all values unchanged
+        let bad_values = [
+            ParameterValue::Integer(11),
+            ParameterValue::Double(3.0),
+            ParameterValue::Bool(true),
+        ];
+        for bad_value in bad_values {
+            let result = node
+                .set_parameters_atomically(parameters(bad_value))
+                .unwrap();
+            assert!(!result.successful);
+            assert!(!result.reason.is_empty());
+            assert_eq!(int_param.get(), 7);
+            assert_eq!(&*string_param.get(), "foo");
+        }
+        let mut batch = parameters(ParameterValue::Integer(8));
+        batch.push(Parameter {
+            name: "read_only_param".into(),
+            value: ParameterValue::Double(2.0),
+        });
+        assert!(!node.set_parameters_atomically(batch).unwrap().successful);
+        assert_eq!(int_param.get(), 7);
+        assert_eq!(read_only_param.get(), 1.0);
+        // Undeclared parameters are rejected unless explicitly allowed
+        let mut batch = parameters(ParameterValue::Integer(8));
+        batch.push(Parameter {
+            name: "undeclared_param".into(),
+            value: ParameterValue::Integer(1),
+        });
+        assert!(
+            !node
+                .set_parameters_atomically(batch.clone())
+                .unwrap()
+                .successful
+        );
+        assert_eq!(int_param.get(), 7);
+        node.use_undeclared_parameters();
+        assert!(node.set_parameters_atomically(batch).unwrap().successful);
+        assert_eq!(int_param.get(), 8);
+
+        // On-set callbacks see the whole batch and can reject it
+        node.add_on_set_parameters_callback(|parameters| SetParametersResult {
+            successful: parameters.len() < 2,
+            reason: String::from("only one parameter at a time"),
+        });
+        let result = node
+            .set_parameters_atomically(parameters(ParameterValue::Integer(9)))
+            .unwrap();
+        assert!(!result.successful);
+        assert_eq!(result.reason, "only one parameter at a time");
+        assert_eq!(int_param.get(), 8);
+        assert_eq!(&*string_param.get(), "bar");
+    }
+
+    #[test]
+    fn test_load_parameters_from_file() {
+        use std::io::Write;
+
+        const PARAMS_FILE: &str = r#"
+/**:
+    ros__parameters:
+        wildcard_param: 1
+        shared_param: "from wildcard"
+/my_ns/my_node:
+    ros__parameters:
+        shared_param: "from node"
+        declared_int: 42
+        nested:
+            double_param: 1.5
+            deeper:
+                bool_param: true
+        int_array: [1, 2, 3]
+        string_array: ["a", "b"]
+/other_node:
+    ros__parameters:
+        other_param: 5
+"#;
+        let mut params_file = tempfile::NamedTempFile::new().unwrap();
+        write!(params_file, "{}", PARAMS_FILE).unwrap();
+
+        let ctx = Context::new([]).unwrap();
+        let node = Node::builder(&ctx, "my_node")
+            .namespace("/my_ns")
+            .build()
+            .unwrap();
+        let declared_int = node
+            .declare_parameter("declared_int")
+            .default(0)
+            .mandatory()
+            .unwrap();
+        node.load_parameters_from_file(params_file.path()).unwrap();
+
+        assert_eq!(declared_int.get(), 42);
+        let params = node.use_undeclared_parameters();
+        assert_eq!(params.get::<i64>("wildcard_param"), Some(1));
+        assert_eq!(
+            params.get::<Arc<str>>("shared_param").as_deref(),
+            Some("from node")
+        );
+        assert_eq!(params.get::<f64>("nested.double_param"), Some(1.5));
+        assert_eq!(params.get::<bool>("nested.deeper.bool_param"), Some(true));
+        assert_eq!(
+            params.get::<Arc<[i64]>>("int_array").as_deref(),
+            Some([1, 2, 3].as_slice())
+        );
+        assert_eq!(
+            params.get::<Arc<[Arc<str>]>>("string_array").as_deref(),
+            Some([Arc::from("a"), Arc::from("b")].as_slice())
+        );
+        assert!(params.get::<i64>("other_param").is_none());
+
+        // Loaded values are used when declaring the parameters
+        let double_param = node
+            .declare_parameter("nested.double_param")
+            .default(0.0)
+            .mandatory()
+            .unwrap();
+        assert_eq!(double_param.get(), 1.5);
+
+        // A value that does not match its declaration rejects the whole file
+        declared_int.set(1).unwrap();
+        let string_param = node
+            .declare_parameter::<Arc<str>>("shared_param")
+            .mandatory()
+            .unwrap();
+        string_param.set("changed").unwrap();
+        let _read_only = node
+            .declare_parameter::<Arc<[i64]>>("int_array")
+            .read_only()
+            .unwrap();
+        assert!(matches!(
+            node.load_parameters_from_file(params_file.path()),
+            Err(RclrsError::ParametersRejected { .. })
+        ));
+        assert_eq!(declared_int.get(), 1);
+        assert_eq!(&*string_param.get(), "changed");
+
+        // Files that cannot be parsed result in an error
+        assert!(node
+            .load_parameters_from_file(Path::new("/does/not/exist.yaml"))
+            .is_err());
+    }
+
     #[test]
     fn test_readonly_parameters() {
         let ctx = Context::new([]).unwrap();
@@ -1275,6 +2015,358 @@ mod tests {
             node.use_undeclared_parameters().set("int_param", 10),
             Err(ParameterValueError::ReadOnly)
         ));
+        // Also with a value of a different type
+        assert!(matches!(
+            node.use_undeclared_parameters().set("int_param", 1.0),
+            Err(ParameterValueError::ReadOnly)
+        ));
+        assert_eq!(param.get(), 100);
+    }
+
+    #[test]
+    fn test_readonly_parameter_with_override() {
+        let ctx = Context::new([
+            String::from("--ros-args"),
+            String::from("-p"),
+            String::from("read_only_int:=42"),
+        ])
+        .unwrap();
+        let node = create_node(&ctx, "param_test_node").unwrap();
+        let param = node
+            .declare_parameter("read_only_int")
+            .default(1)
+            .range(ParameterRange {
+                lower: Some(0),
+                upper: Some(100),
+                step: None,
+            })
+            .read_only()
+            .unwrap();
+        // The override takes effect at declaration
+        assert_eq!(param.get(), 42);
+
+        let callback_calls = Arc::new(Mutex::new(0));
+        let callback_calls_in_callback = Arc::clone(&callback_calls);
+        node.add_on_set_parameters_callback(move |_parameters| {
+            *callback_calls_in_callback.lock().unwrap() += 1;
+            SetParametersResult {
+                successful: true,
+                reason: String::new(),
+            }
+        });
+        // Values that are valid otherwise, and values that are out of range, are both rejected
+        // because the parameter is read only, without running the on-set callbacks
+        for value in [50, 1000] {
+            let result = node
+                .set_parameters_atomically(vec![Parameter {
+                    name: "read_only_int".into(),
+                    value: ParameterValue::Integer(value),
+                }])
+                .unwrap();
+            assert!(!result.successful);
+            assert_eq!(result.reason, "Parameter is read only");
+        }
+        assert_eq!(*callback_calls.lock().unwrap(), 0);
+        assert_eq!(param.get(), 42);
+    }
+
+    #[test]
+    fn test_dynamic_typing() {
+        let ctx = Context::new([]).unwrap();
+        let node = create_node(&ctx, "param_test_node").unwrap();
+        let dynamic_param = node
+            .declare_parameter::<ParameterValue>("dynamic_param")
+            .default(ParameterValue::Integer(1))
+            .mandatory()
+            .unwrap();
+        let static_param = node
+            .declare_parameter("static_param")
+            .default(1)
+            .mandatory()
+            .unwrap();
+
+        // A dynamically typed parameter accepts values of any type
+        dynamic_param.set(ParameterValue::Integer(2)).unwrap();
+        assert_eq!(dynamic_param.get(), ParameterValue::Integer(2));
+        dynamic_param
+            .set(ParameterValue::String("two".into()))
+            .unwrap();
+        assert_eq!(dynamic_param.get(), ParameterValue::String("two".into()));
+        node.use_undeclared_parameters()
+            .set("dynamic_param", 3)
+            .unwrap();
+        assert_eq!(dynamic_param.get(), ParameterValue::Integer(3));
+        let result = node
+            .set_parameters_atomically(vec![Parameter {
+                name: "dynamic_param".into(),
+                value: ParameterValue::String("three".into()),
+            }])
+            .unwrap();
+        assert!(result.successful);
+        assert_eq!(dynamic_param.get(), ParameterValue::String("three".into()));
+
+        // A statically typed parameter rejects values of another type
+        assert!(matches!(
+            node.use_undeclared_parameters()
+                .set("static_param", Arc::<str>::from("two")),
+            Err(ParameterValueError::TypeMismatch)
+        ));
+        let result = node
+            .set_parameters_atomically(vec![Parameter {
+                name: "static_param".into(),
+                value: ParameterValue::String("three".into()),
+            }])
+            .unwrap();
+        assert!(!result.successful);
+        assert_eq!(
+            result.reason,
+            "Parameter set to different type and dynamic typing is disabled"
+        );
+        // Values of the declared type are still accepted, also when passed as a ParameterValue
+        node.use_undeclared_parameters()
+            .set("static_param", ParameterValue::Integer(2))
+            .unwrap();
+        assert_eq!(static_param.get(), 2);
+    }
+
+    #[test]
+    fn test_get_parameters() {
+        let ctx = Context::new([]).unwrap();
+        let node = create_node(&ctx, "param_test_node").unwrap();
+        let _int_param = node
+            .declare_parameter("int_param")
+            .default(5)
+            .mandatory()
+            .unwrap();
+        let _string_param = node
+            .declare_parameter("string_param")
+            .default(Arc::from("foo"))
+            .read_only()
+            .unwrap();
+        let _optional_param = node
+            .declare_parameter::<f64>("optional_param")
+            .optional()
+            .unwrap();
+
+        let values = node.get_parameters(&[
+            "string_param",
+            "int_param",
+            "optional_param",
+            "undeclared_param",
+        ]);
+        assert_eq!(
+            values,
+            [
+                (
+                    String::from("string_param"),
+                    Some(ParameterValue::String("foo".into()))
+                ),
+                (String::from("int_param"), Some(ParameterValue::Integer(5))),
+                (String::from("optional_param"), None),
+                (String::from("undeclared_param"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_parameters() {
+        let ctx = Context::new([]).unwrap();
+        let node = create_node(&ctx, "param_test_node").unwrap();
+        let _params = [
+            "rate",
+            "arm.speed",
+            "arm.joint1.gain",
+            "arm.joint1.limits.max",
+            "armrest",
+        ]
+        .map(|name| node.declare_parameter(name).default(1).mandatory().unwrap());
+
+        let all = node.list_parameters(&[], 0);
+        assert_eq!(
+            all.names,
+            [
+                "arm.joint1.gain",
+                "arm.joint1.limits.max",
+                "arm.speed",
+                "armrest",
+                "rate"
+            ]
+        );
+        assert_eq!(all.prefixes, ["arm", "arm.joint1", "arm.joint1.limits"]);
+        // Without prefixes, the depth counts from the root of the parameter tree
+        let top_level = node.list_parameters(&[], 1);
+        assert_eq!(top_level.names, ["armrest", "rate"]);
+        assert!(top_level.prefixes.is_empty());
+
+        // The prefix only matches whole name components
+        let arm = node.list_parameters(&[String::from("arm")], 0);
+        assert_eq!(
+            arm.names,
+            ["arm.joint1.gain", "arm.joint1.limits.max", "arm.speed"]
+        );
+        assert_eq!(arm.prefixes, ["arm", "arm.joint1", "arm.joint1.limits"]);
+        let arm = node.list_parameters(&[String::from("arm")], 1);
+        assert_eq!(arm.names, ["arm.speed"]);
+        assert_eq!(arm.prefixes, ["arm"]);
+        let arm = node.list_parameters(&[String::from("arm")], 2);
+        assert_eq!(arm.names, ["arm.joint1.gain", "arm.speed"]);
+        assert_eq!(arm.prefixes, ["arm", "arm.joint1"]);
+
+        // Several prefixes, and a prefix that is a full parameter name
+        let result = node.list_parameters(&[String::from("arm.joint1"), String::from("rate")], 0);
+        assert_eq!(
+            result.names,
+            ["arm.joint1.gain", "arm.joint1.limits.max", "rate"]
+        );
+        assert!(node
+            .list_parameters(&[String::from("unknown")], 0)
+            .names
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parameter_events() -> Result<(), RclrsError> {
+        use crate::{spin_once, test_helpers::ignore_timeout, NodeBuilder};
+        use std::time::Duration;
+
+        let ctx = Context::new([]).unwrap();
+        let node = NodeBuilder::new(&ctx, "node")
+            .namespace("/test_parameter_events")
+            .build()?;
+        let observer = NodeBuilder::new(&ctx, "observer")
+            .namespace("/test_parameter_events")
+            .build()?;
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_in_callback = Arc::clone(&events);
+        let _subscription = observer.create_parameter_event_subscription(
+            Some("/test_parameter_events/node"),
+            move |event: ParameterEvent| events_in_callback.lock().unwrap().push(event),
+        )?;
+        let int_param = node
+            .declare_parameter("int_param")
+            .default(0)
+            .mandatory()
+            .unwrap();
+        let _string_param = node
+            .declare_parameter("string_param")
+            .default(Arc::from("foo"))
+            .mandatory()
+            .unwrap();
+        let spin = || {
+            ignore_timeout(spin_once(
+                Arc::clone(&observer),
+                Some(Duration::from_millis(100)),
+            ))
+        };
+        let find_event = |predicate: &dyn Fn(&ParameterEvent) -> bool| {
+            events
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|e| predicate(e))
+                .cloned()
+        };
+
+        // Set the parameter until the subscription has been matched and received a change
+        let is_int_change = |event: &ParameterEvent| !event.changed_parameters.is_empty();
+        for value in 1..50 {
+            int_param.set(value).unwrap();
+            spin()?;
+            if find_event(&is_int_change).is_some() {
+                break;
+            }
+        }
+        let event = find_event(&is_int_change).expect("No parameter event was received");
+        assert!(event.new_parameters.is_empty());
+        assert!(event.deleted_parameters.is_empty());
+        assert_eq!(event.changed_parameters.len(), 1);
+        assert_eq!(event.changed_parameters[0].name, "int_param");
+        assert_eq!(
+            event.changed_parameters[0].value.type_,
+            ParameterType::PARAMETER_INTEGER
+        );
+
+        // Setting several parameters atomically results in a single event
+        node.set_parameters_atomically(vec![
+            Parameter {
+                name: "int_param".into(),
+                value: ParameterValue::Integer(100),
+            },
+            Parameter {
+                name: "string_param".into(),
+                value: ParameterValue::String("bar".into()),
+            },
+        ])?;
+        let is_batch = |event: &ParameterEvent| event.changed_parameters.len() == 2;
+        for _ in 0..100 {
+            if find_event(&is_batch).is_some() {
+                break;
+            }
+            spin()?;
+        }
+        let event = find_event(&is_batch).expect("No event for the atomic set was received");
+        assert_eq!(event.changed_parameters[0].name, "int_param");
+        assert_eq!(event.changed_parameters[0].value.integer_value, 100);
+        assert_eq!(event.changed_parameters[1].name, "string_param");
+        assert_eq!(event.changed_parameters[1].value.string_value, "bar");
+        Ok(())
+    }
+
+    #[test]
+    fn test_undeclare_parameter() {
+        let ctx = Context::new([]).unwrap();
+        let node = create_node(&ctx, "param_test_node").unwrap();
+        let dynamic_param = node
+            .declare_parameter::<ParameterValue>("dynamic_param")
+            .default(ParameterValue::Integer(1))
+            .mandatory()
+            .unwrap();
+        let _static_param = node
+            .declare_parameter("static_param")
+            .default(1)
+            .mandatory()
+            .unwrap();
+        let _read_only_param = node
+            .declare_parameter::<ParameterValue>("read_only_param")
+            .default(ParameterValue::Integer(1))
+            .read_only()
+            .unwrap();
+
+        node.undeclare_parameter("dynamic_param").unwrap();
+        assert!(node
+            .use_undeclared_parameters()
+            .get::<ParameterValue>("dynamic_param")
+            .is_none());
+        assert!(matches!(
+            node.undeclare_parameter("dynamic_param"),
+            Err(RclrsError::ParametersRejected { .. })
+        ));
+        // The parameter can be declared again, and dropping the old parameter object does not
+        // undeclare the new one
+        let redeclared_param = node
+            .declare_parameter::<ParameterValue>("dynamic_param")
+            .default(ParameterValue::Integer(2))
+            .mandatory()
+            .unwrap();
+        drop(dynamic_param);
+        assert_eq!(
+            node.use_undeclared_parameters()
+                .get::<ParameterValue>("dynamic_param"),
+            Some(ParameterValue::Integer(2))
+        );
+        assert_eq!(redeclared_param.get(), ParameterValue::Integer(2));
+
+        // Statically typed and read only parameters cannot be undeclared
+        for name in ["static_param", "read_only_param", "never_declared"] {
+            assert!(matches!(
+                node.undeclare_parameter(name),
+                Err(RclrsError::ParametersRejected { .. })
+            ));
+        }
+        assert_eq!(
+            node.use_undeclared_parameters().get::<i64>("static_param"),
+            Some(1)
+        );
     }
 
     #[test]
@@ -1,6 +1,11 @@
-use std::{collections::BTreeMap, ffi::CStr, os::raw::c_char};
+use std::{
+    collections::BTreeMap,
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    path::Path,
+};
 
-use crate::{rcl_bindings::*, ParameterValue, RclrsError, ToResult};
+use crate::{rcl_bindings::*, ParameterValue, RclReturnCode, RclrsError, ToResult};
 
 // Internal helper struct, iterator for rcl_params_t
 struct RclParamsIter<'a> {
@@ -115,22 +120,81 @@ pub(crate) unsafe fn resolve_parameter_overrides(
     let mut map = BTreeMap::new();
     for rcl_arguments in [rcl_global_arguments, rcl_node_arguments] {
         let mut rcl_params = std::ptr::null_mut();
-        rcl_arguments_get_param_overrides(rcl_arguments, &mut rcl_params).ok()?;
-        // Check for the /** node first, and later overwrite with the more specific node
-        // parameters, if they exist
-        for name_to_match in ["/**", node_fqn] {
-            for (node_name, node_params) in RclParamsIter::new(rcl_params) {
-                if node_name == name_to_match {
-                    for (param_name, variant) in RclNodeParamsIter::new(node_params) {
-                        let value = ParameterValue::from_rcl_variant(variant);
-                        map.insert(param_name, value);
-                    }
+        rcl_arguments_get_param_overrides(rcl_arguments, &mut rcl_params)
+            .ok_for("rcl_arguments_get_param_overrides")?;
+        insert_node_parameters(&mut map, node_fqn, rcl_params);
+        rcl_yaml_node_struct_fini(rcl_params);
+    }
+    Ok(map)
+}
+
+/// Reads the parameters for the node with the given fully qualified name from a parameter file
+/// in the standard ROS 2 YAML format.
+///
+/// Parameters for the `/**` wildcard are included, and overwritten by the parameters for this
+/// specific node. Sections for other nodes are ignored.
+pub(crate) fn parameters_from_file(
+    node_fqn: &str,
+    path: &Path,
+) -> Result<ParameterOverrideMap, RclrsError> {
+    let Some(path) = path.to_str() else {
+        return Err(RclrsError::RclError {
+            code: RclReturnCode::InvalidArgument,
+            operation: None,
+            msg: None,
+        });
+    };
+    let path = CString::new(path).map_err(|err| RclrsError::StringContainsNul {
+        err,
+        s: path.to_owned(),
+    })?;
+    // SAFETY: No preconditions for this function.
+    let rcl_params = unsafe { rcl_yaml_node_struct_init(rcutils_get_default_allocator()) };
+    if rcl_params.is_null() {
+        return Err(RclrsError::RclError {
+            code: RclReturnCode::BadAlloc,
+            operation: None,
+            msg: None,
+        });
+    }
+    let mut map = BTreeMap::new();
+    // SAFETY: The path is a valid C string and rcl_params has been initialized above.
+    // The rcl_params are only accessed before being finalized.
+    let result = unsafe {
+        if rcl_parse_yaml_file(path.as_ptr(), rcl_params) {
+            insert_node_parameters(&mut map, node_fqn, rcl_params);
+            Ok(())
+        } else {
+            (RclReturnCode::Error as i32).ok_for("rcl_parse_yaml_file")
+        }
+    };
+    // SAFETY: rcl_params is not used after this.
+    unsafe { rcl_yaml_node_struct_fini(rcl_params) };
+    result.map(|()| map)
+}
+
+/// Inserts the parameters for the `/**` wildcard and then the more specific parameters for the
+/// node with the given fully qualified name into the map.
+///
+/// This function is unsafe since the rcl_params argument might contain incorrect array sizes or
+/// dangling pointers.
+unsafe fn insert_node_parameters(
+    map: &mut ParameterOverrideMap,
+    node_fqn: &str,
+    rcl_params: *const rcl_params_t,
+) {
+    // Check for the /** node first, and later overwrite with the more specific node
+    // parameters, if they exist
+    for name_to_match in ["/**", node_fqn] {
+        for (node_name, node_params) in RclParamsIter::new(rcl_params) {
+            if node_name == name_to_match {
+                for (param_name, variant) in RclNodeParamsIter::new(node_params) {
+                    let value = ParameterValue::from_rcl_variant(variant);
+                    map.insert(param_name, value);
                 }
             }
         }
-        rcl_yaml_node_struct_fini(rcl_params);
     }
-    Ok(map)
 }
 
 #[cfg(test)]
@@ -203,3 +203,86 @@ impl ParameterRange<f64> {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_range_is_unconstrained() {
+        let ranges = ParameterRanges::from(ParameterRange::<i64>::default());
+        assert!(ranges.validate().is_ok());
+        assert!(ranges.in_range(&ParameterValue::Integer(i64::MIN)));
+        assert!(ranges.in_range(&ParameterValue::Integer(i64::MAX)));
+        let (int_range, float_range) = ranges.to_descriptor_ranges();
+        assert!(int_range.is_empty());
+        assert!(float_range.is_empty());
+
+        let ranges = ParameterRanges::from(ParameterRange::<f64>::default());
+        assert!(ranges.in_range(&ParameterValue::Double(f64::MAX)));
+        let (_, float_range) = ranges.to_descriptor_ranges();
+        assert!(float_range.is_empty());
+    }
+
+    #[test]
+    fn ranges_are_reported_in_descriptor() {
+        let ranges = ParameterRanges::from(ParameterRange {
+            lower: Some(-2),
+            upper: None,
+            step: Some(2),
+        });
+        let (int_range, float_range) = ranges.to_descriptor_ranges();
+        assert_eq!(int_range.len(), 1);
+        assert_eq!(int_range[0].from_value, -2);
+        assert_eq!(int_range[0].to_value, i64::MAX);
+        assert_eq!(int_range[0].step, 2);
+        assert!(float_range.is_empty());
+
+        let ranges = ParameterRanges::from(ParameterRange {
+            lower: None,
+            upper: Some(1.5),
+            step: None,
+        });
+        let (int_range, float_range) = ranges.to_descriptor_ranges();
+        assert!(int_range.is_empty());
+        assert_eq!(float_range.len(), 1);
+        assert_eq!(float_range[0].from_value, f64::NEG_INFINITY);
+        assert_eq!(float_range[0].to_value, 1.5);
+        assert_eq!(float_range[0].step, 0.0);
+    }
+
+    #[test]
+    fn step_aligned_values() {
+        let range = ParameterRange {
+            lower: Some(0),
+            upper: Some(10),
+            step: Some(3),
+        };
+        let accepted: Vec<i64> = (-5..15).filter(|v| range.in_range(*v)).collect();
+        assert_eq!(accepted, [0, 3, 6, 9, 10]);
+
+        let range = ParameterRange {
+            lower: Some(0.0),
+            upper: Some(1.0),
+            step: Some(0.1),
+        };
+        // 0.3 is not exactly representable, but is within rounding tolerance of 3 * 0.1
+        assert!(range.in_range(0.1 + 0.2));
+        assert!(range.in_range(0.7));
+        assert!(!range.in_range(0.75));
+        assert!(!range.in_range(1.1));
+        assert!(!range.in_range(-0.1));
+    }
+
+    #[test]
+    fn ranges_do_not_apply_to_other_types() {
+        let ranges = ParameterRanges::from(ParameterRange {
+            lower: Some(0),
+            upper: Some(1),
+            step: None,
+        });
+        assert!(ranges.in_range(&ParameterValue::Double(100.0)));
+        assert!(ranges.in_range(&ParameterValue::Bool(true)));
+        assert!(!ranges.in_range(&ParameterValue::Integer(2)));
+    }
+}
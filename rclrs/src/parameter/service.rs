@@ -1,15 +1,13 @@
-use std::{
-    collections::BTreeSet,
-    sync::{Arc, Mutex},
-};
+use std::sync::{Arc, Mutex};
 
 use crate::vendor::rcl_interfaces::{msg::rmw::*, srv::rmw::*};
-use rosidl_runtime_rs::Sequence;
 
 use super::ParameterMap;
 use crate::{
-    parameter::{DeclaredValue, ParameterKind, ParameterStorage},
-    rmw_request_id_t, Node, RclrsError, Service,
+    parameter::{DeclaredValue, Parameter, ParameterKind, ParameterStorage},
+    rmw_request_id_t,
+    vendor::rcl_interfaces::msg::rmw::Parameter as RmwParameter,
+    Node, RclrsError, Service,
 };
 
 // The variables only exist to keep a strong reference to the services and are technically unused.
@@ -99,23 +97,13 @@ fn get_parameters(req: GetParameters_Request, map: &ParameterMap) -> GetParamete
         .into_iter()
         .map(|name| {
             let name = name.to_cstr().to_str().ok()?;
-            let Some(storage) = map.storage.get(name) else {
-                return Some(ParameterValue::default());
-            };
-            match storage {
-                ParameterStorage::Declared(storage) => match &storage.value {
-                    DeclaredValue::Mandatory(v) => Some(v.read().unwrap().clone().into()),
-                    DeclaredValue::Optional(v) => Some(
-                        v.read()
-                            .unwrap()
-                            .clone()
-                            .map(|v| v.into())
-                            .unwrap_or_default(),
-                    ),
-                    DeclaredValue::ReadOnly(v) => Some(v.clone().into()),
-                },
-                ParameterStorage::Undeclared(value) => Some(value.clone().into()),
-            }
+            Some(
+                map.storage
+                    .get(name)
+                    .and_then(ParameterStorage::value)
+                    .map(|v| v.into())
+                    .unwrap_or_default(),
+            )
         })
         .collect::<Option<_>>()
         .unwrap_or_default();
@@ -123,79 +111,59 @@ fn get_parameters(req: GetParameters_Request, map: &ParameterMap) -> GetParamete
 }
 
 fn list_parameters(req: ListParameters_Request, map: &ParameterMap) -> ListParameters_Response {
-    let check_parameter_name_depth = |substring: &[std::os::raw::c_char]| {
-        if req.depth == ListParameters_Request::DEPTH_RECURSIVE {
-            return true;
-        }
-        u64::try_from(substring.iter().filter(|c| **c == ('.' as _)).count()).unwrap() < req.depth
-    };
-    let names: Sequence<_> = map
-        .storage
-        .keys()
-        .filter_map(|name| {
-            let name: rosidl_runtime_rs::String = name.clone().into();
-            if req.prefixes.len() == 0 && check_parameter_name_depth(&name[..]) {
-                return Some(name);
-            }
-            req.prefixes
-                .iter()
-                .any(|prefix| {
-                    if name == *prefix {
-                        return true;
-                    }
-                    let mut prefix = prefix.clone();
-                    prefix.extend(".".chars());
-                    if name.len() > prefix.len()
-                        && name.starts_with(&prefix)
-                        && check_parameter_name_depth(&name[prefix.len()..])
-                    {
-                        return true;
-                    }
-                    false
-                })
-                .then_some(name)
-        })
-        .collect();
-    let prefixes: BTreeSet<rosidl_runtime_rs::String> = names
+    let prefixes: Vec<String> = req
+        .prefixes
         .iter()
-        .filter_map(|name| {
-            let name = name.to_string();
-            if let Some(pos) = name.rfind('.') {
-                return Some(name[0..pos].into());
-            }
-            None
-        })
+        .map(|prefix| prefix.to_string())
         .collect();
+    let result = map.list_parameters(&prefixes, req.depth);
     ListParameters_Response {
         result: ListParametersResult {
-            names,
-            prefixes: prefixes.into_iter().collect(),
+            names: result
+                .names
+                .iter()
+                .map(|name| name.as_str().into())
+                .collect(),
+            prefixes: result
+                .prefixes
+                .iter()
+                .map(|prefix| prefix.as_str().into())
+                .collect(),
         },
     }
 }
 
-fn set_parameters(req: SetParameters_Request, map: &mut ParameterMap) -> SetParameters_Response {
+fn to_parameter(param: RmwParameter) -> Result<Parameter, String> {
+    let Ok(name) = param.name.to_cstr().to_str() else {
+        return Err(String::from("Failed parsing into UTF-8"));
+    };
+    let Ok(value): Result<crate::ParameterValue, _> = param.value.try_into() else {
+        return Err(String::from("Invalid parameter type"));
+    };
+    Ok(Parameter {
+        name: name.into(),
+        value,
+    })
+}
+
+fn set_parameters(req: SetParameters_Request, map: &Mutex<ParameterMap>) -> SetParameters_Response {
     let results = req
         .parameters
         .into_iter()
         .map(|param| {
-            let Ok(name) = param.name.to_cstr().to_str() else {
-                return SetParametersResult {
-                    successful: false,
-                    reason: "Failed parsing into UTF-8".into(),
-                };
-            };
-            match map.validate_parameter_setting(name, param.value) {
-                Ok(value) => {
-                    map.store_parameter(name.into(), value);
-                    SetParametersResult {
-                        successful: true,
-                        reason: Default::default(),
-                    }
-                }
+            let result = to_parameter(param).and_then(|parameter| {
+                // Each parameter is set on its own, so that a rejected one doesn't prevent the
+                // others from being set
+                ParameterMap::set_parameters_atomically(map, vec![parameter])
+            });
+            match result {
+                Ok(()) => SetParametersResult {
+                    successful: true,
+                    reason: Default::default(),
+                },
                 Err(e) => SetParametersResult {
                     successful: false,
-                    reason: e.into(),
+                    reason: e.as_str().into(),
                 },
             }
         })
@@ -205,33 +173,23 @@ fn set_parameters(req: SetParameters_Request, map: &mut ParameterMap) -> SetPara
 
 fn set_parameters_atomically(
     req: SetParametersAtomically_Request,
-    map: &mut ParameterMap,
+    map: &Mutex<ParameterMap>,
 ) -> SetParametersAtomically_Response {
-    let results = req
+    let result = req
         .parameters
         .into_iter()
-        .map(|param| {
-            let Ok(name) = param.name.to_cstr().to_str() else {
-                return Err("Failed parsing into UTF-8".into());
-            };
-            let value = map.validate_parameter_setting(name, param.value)?;
-            Ok((name.into(), value))
-        })
-        .collect::<Result<Vec<_>, _>>();
-    // Check if there was any error and update parameters accordingly
-    let result = match results {
-        Ok(results) => {
-            for (name, value) in results.into_iter() {
-                map.store_parameter(name, value);
-            }
-            SetParametersResult {
-                successful: true,
-                reason: Default::default(),
-            }
-        }
+        .map(to_parameter)
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|parameters| ParameterMap::set_parameters_atomically(map, parameters));
+    // Check if there was any error and report it
+    let result = match result {
+        Ok(()) => SetParametersResult {
+            successful: true,
+            reason: Default::default(),
+        },
         Err(reason) => SetParametersResult {
             successful: false,
-            reason,
+            reason: reason.as_str().into(),
         },
     };
     SetParametersAtomically_Response { result }
@@ -280,16 +238,12 @@ impl ParameterService {
         let map = parameter_map.clone();
         let set_parameters_service = node.create_service(
             &(fqn.clone() + "/set_parameters"),
-            move |_req_id: &rmw_request_id_t, req: SetParameters_Request| {
-                let mut map = map.lock().unwrap();
-                set_parameters(req, &mut map)
-            },
+            move |_req_id: &rmw_request_id_t, req: SetParameters_Request| set_parameters(req, &map),
         )?;
         let set_parameters_atomically_service = node.create_service(
             &(fqn.clone() + "/set_parameters_atomically"),
             move |_req_id: &rmw_request_id_t, req: SetParametersAtomically_Request| {
-                let mut map = parameter_map.lock().unwrap();
-                set_parameters_atomically(req, &mut map)
+                set_parameters_atomically(req, &parameter_map)
             },
         )?;
         Ok(Self {
@@ -427,6 +381,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parameter_services_disabled() -> Result<(), RclrsError> {
+        let context = Context::new([]).unwrap();
+        let node = NodeBuilder::new(&context, "node")
+            .namespace("/services_disabled")
+            .start_parameter_services(false)
+            .build()?;
+        let _param = node
+            .declare_parameter("param")
+            .default(1)
+            .mandatory()
+            .unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let names_and_types =
+            node.get_service_names_and_types_by_node("node", "/services_disabled")?;
+        assert!(names_and_types.is_empty());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_list_parameters_service() -> Result<(), RclrsError> {
         let context = Context::new([]).unwrap();
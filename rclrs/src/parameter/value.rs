@@ -565,7 +565,7 @@ mod tests {
                     &ctx.handle.rcl_context.lock().unwrap().global_arguments,
                     &mut rcl_params,
                 )
-                .ok()?;
+                .ok_for("rcl_arguments_get_param_overrides")?;
             }
             assert!(!rcl_params.is_null());
             assert_eq!(unsafe { (*rcl_params).num_nodes }, 1);
@@ -2,20 +2,32 @@ use std::{
     borrow::Cow,
     ffi::{CStr, CString},
     marker::PhantomData,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
 };
 
 use rosidl_runtime_rs::{Message, RmwMessage};
 
+#[cfg(feature = "dyn_msg")]
 use crate::{
-    error::{RclrsError, ToResult},
-    qos::QoSProfile,
+    dynamic_message::{find_bounds_violation, header_stamp_offset},
+    vendor::builtin_interfaces,
+    Clock,
+};
+use crate::{
+    error::{RclErrorMsg, RclReturnCode, RclrsError, ToResult},
+    network_flow_endpoint::network_flow_endpoints_from_rcl,
     rcl_bindings::*,
-    NodeHandle, ENTITY_LIFECYCLE_MUTEX,
+    validate_topic_name, Event, EventBase, IntraProcessPublisher, NetworkFlowEndpoint, NodeHandle,
+    PublisherGid, QoSDurabilityPolicy, QoSLivelinessPolicy, ENTITY_LIFECYCLE_MUTEX,
 };
 
 mod loaned_message;
+mod message_pool;
+mod options;
 pub use loaned_message::*;
+pub use message_pool::*;
+pub use options::*;
 
 // SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
 // they are running in. Therefore, this type can be safely sent to another thread.
@@ -26,11 +38,17 @@ unsafe impl Send for rcl_publisher_t {}
 /// [dropped after][1] the `rcl_publisher_t`.
 ///
 /// [1]: <https://doc.rust-lang.org/reference/destructors.html>
-struct PublisherHandle {
+pub(crate) struct PublisherHandle {
     rcl_publisher: Mutex<rcl_publisher_t>,
     node_handle: Arc<NodeHandle>,
 }
 
+impl PublisherHandle {
+    pub(crate) fn lock(&self) -> MutexGuard<rcl_publisher_t> {
+        self.rcl_publisher.lock().unwrap()
+    }
+}
+
 impl Drop for PublisherHandle {
     fn drop(&mut self) {
         let mut rcl_node = self.node_handle.rcl_node.lock().unwrap();
@@ -61,7 +79,11 @@ where
     // it is global data in the type support library.
     type_support_ptr: *const rosidl_message_type_support_t,
     message: PhantomData<T>,
-    handle: PublisherHandle,
+    handle: Arc<PublisherHandle>,
+    pub(crate) events: Vec<Arc<dyn EventBase>>,
+    intra_process: Option<IntraProcessPublisher>,
+    // Whether the middleware keeps the published messages for late-joining subscriptions.
+    transient_local: bool,
 }
 
 // SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
@@ -81,7 +103,7 @@ where
     pub(crate) fn new(
         node_handle: Arc<NodeHandle>,
         topic: &str,
-        qos: QoSProfile,
+        options: PublisherOptions,
     ) -> Result<Self, RclrsError>
     where
         T: Message,
@@ -94,10 +116,14 @@ where
             err,
             s: topic.into(),
         })?;
+        validate_topic_name(topic)?;
 
         // SAFETY: No preconditions for this function.
         let mut publisher_options = unsafe { rcl_publisher_get_default_options() };
-        publisher_options.qos = qos.into();
+        publisher_options.qos = options.qos.into();
+        publisher_options
+            .rmw_publisher_options
+            .require_unique_network_flow_endpoints = options.unique_network_flow_endpoints.into();
 
         {
             let rcl_node = node_handle.rcl_node.lock().unwrap();
@@ -116,24 +142,68 @@ where
                     topic_c_string.as_ptr(),
                     &publisher_options,
                 )
-                .ok()?;
+                .ok_for("rcl_publisher_init")?;
             }
         }
 
-        Ok(Self {
+        let handle = Arc::new(PublisherHandle {
+            rcl_publisher: Mutex::new(rcl_publisher),
+            node_handle,
+        });
+
+        let mut events: Vec<Arc<dyn EventBase>> = Vec::new();
+        if let Some(callback) = options.event_callbacks.offered_deadline_missed {
+            events.push(Arc::new(Event::new_for_publisher(
+                &handle,
+                rcl_publisher_event_type_t::RCL_PUBLISHER_OFFERED_DEADLINE_MISSED,
+                callback,
+            )?));
+        }
+        #[cfg(not(ros_distro = "humble"))]
+        if let Some(callback) = options.event_callbacks.matched {
+            events.push(Arc::new(Event::new_for_publisher(
+                &handle,
+                rcl_publisher_event_type_t::RCL_PUBLISHER_MATCHED,
+                callback,
+            )?));
+        }
+        if let Some(callback) = options.event_callbacks.liveliness_lost {
+            events.push(Arc::new(Event::new_for_publisher(
+                &handle,
+                rcl_publisher_event_type_t::RCL_PUBLISHER_LIVELINESS_LOST,
+                callback,
+            )?));
+        }
+
+        // SAFETY: The publisher is valid, so the returned pointer is valid as well.
+        let durability = QoSDurabilityPolicy::from(unsafe {
+            (*rcl_publisher_get_actual_qos(&*handle.lock())).durability
+        });
+        let mut publisher = Self {
             type_support_ptr,
             message: PhantomData,
-            handle: PublisherHandle {
-                rcl_publisher: Mutex::new(rcl_publisher),
-                node_handle,
-            },
-        })
+            handle,
+            events,
+            intra_process: None,
+            transient_local: durability == QoSDurabilityPolicy::TransientLocal,
+        };
+        let use_intra_process_comms = options
+            .use_intra_process
+            .unwrap_or(publisher.handle.node_handle.use_intra_process_comms);
+        if use_intra_process_comms {
+            publisher.intra_process = Some(IntraProcessPublisher::new(
+                Arc::clone(&publisher.handle.node_handle.context_handle),
+                publisher.topic_name(),
+                publisher.gid()?,
+            ));
+        }
+        Ok(publisher)
     }
 
     /// Returns the topic name of the publisher.
     ///
-    /// This returns the topic name after remapping, so it is not necessarily the
-    /// topic name which was used when creating the publisher.
+    /// This returns the fully-qualified topic name after namespace expansion and remapping, so
+    /// it is not necessarily the topic name which was used when creating the publisher.
     pub fn topic_name(&self) -> String {
         // SAFETY: No preconditions for the functions called.
         // The unsafe variables created get converted to safe types before being returned
@@ -146,6 +216,158 @@ where
         }
     }
 
+    /// Returns the GID that identifies the publisher in the RMW implementation.
+    ///
+    /// This is the same GID that subscriptions receive in [`MessageInfo::publisher_gid`][1] for
+    /// messages from this publisher, so it can be used to find out which of the local publishers
+    /// a message came from.
+    ///
+    /// [1]: crate::MessageInfo::publisher_gid
+    pub fn gid(&self) -> Result<PublisherGid, RclrsError> {
+        let mut rmw_gid = rmw_gid_t {
+            implementation_identifier: std::ptr::null(),
+            data: [0; RMW_GID_STORAGE_SIZE],
+        };
+        // SAFETY: The rmw publisher is valid as long as the rcl publisher is, and the GID is
+        // copied into the out-parameter.
+        unsafe {
+            let rmw_publisher =
+                rcl_publisher_get_rmw_handle(&*self.handle.rcl_publisher.lock().unwrap());
+            rmw_get_gid_for_publisher(rmw_publisher, &mut rmw_gid)
+                .ok_for("rmw_get_gid_for_publisher")?;
+        }
+        Ok(PublisherGid {
+            data: rmw_gid.data,
+            implementation_identifier: rmw_gid.implementation_identifier,
+        })
+    }
+
+    /// Returns the number of subscriptions that are matched with this publisher.
+    ///
+    /// This reflects the current state of the graph, so it changes as subscriptions come and go.
+    pub fn get_subscription_count(&self) -> Result<usize, RclrsError> {
+        let mut subscription_count = 0;
+        // SAFETY: No preconditions for the function called.
+        unsafe {
+            rcl_publisher_get_subscription_count(
+                &*self.handle.rcl_publisher.lock().unwrap(),
+                &mut subscription_count,
+            )
+            .ok_for("rcl_publisher_get_subscription_count")?
+        };
+        Ok(subscription_count)
+    }
+
+    /// Waits until all published messages have been acknowledged by the matched subscriptions.
+    ///
+    /// Returns `true` if all messages were acknowledged, and `false` if the timeout expired first.
+    /// Passing `None` as the timeout waits indefinitely. This is useful to make sure messages were
+    /// delivered before shutting down.
+    ///
+    /// This is only meaningful for publishers with the [`Reliable`][1] reliability policy. With
+    /// [`BestEffort`][2], messages are never acknowledged, so this returns `true` immediately.
+    ///
+    /// # Errors
+    /// - The timeout must not be so large so as to overflow an `i64` with its nanosecond
+    ///   representation, or an [`InvalidArgument`][3] error will occur.
+    /// - If the RMW implementation does not support this, an [`Unsupported`][4] error is returned.
+    ///
+    /// [1]: crate::QoSReliabilityPolicy::Reliable
+    /// [2]: crate::QoSReliabilityPolicy::BestEffort
+    /// [3]: crate::RclReturnCode::InvalidArgument
+    /// [4]: crate::RclReturnCode::Unsupported
+    pub fn wait_for_all_acked(&self, timeout: Option<Duration>) -> Result<bool, RclrsError> {
+        let timeout_ns = match timeout.map(|d| d.as_nanos()) {
+            None => -1,
+            Some(ns) if ns <= i64::MAX as u128 => ns as i64,
+            _ => {
+                return Err(RclrsError::RclError {
+                    code: RclReturnCode::InvalidArgument,
+                    operation: None,
+                    msg: None,
+                })
+            }
+        };
+        // SAFETY: No preconditions for this function (besides passing in a valid publisher).
+        match unsafe {
+            rcl_publisher_wait_for_all_acked(
+                &*self.handle.rcl_publisher.lock().unwrap(),
+                timeout_ns,
+            )
+        }
+        .ok_for("rcl_publisher_wait_for_all_acked")
+        {
+            Ok(()) => Ok(true),
+            Err(RclrsError::RclError {
+                code: RclReturnCode::Timeout,
+                ..
+            }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the message type of the publisher, e.g. `std_msgs/msg/String`.
+    pub fn type_name(&self) -> &'static str {
+        <T as Message>::RmwMsg::TYPE_NAME
+    }
+
+    /// Returns the network flow endpoints that the publisher uses in the middleware.
+    ///
+    /// Each endpoint consists of the transport and internet protocol, the address and the port,
+    /// which is useful e.g. for debugging on machines with several network interfaces.
+    ///
+    /// # Errors
+    /// If the RMW implementation does not support this, an [`Unsupported`][1] error is returned.
+    ///
+    /// [1]: crate::RclReturnCode::Unsupported
+    pub fn get_network_flow_endpoints(&self) -> Result<Vec<NetworkFlowEndpoint>, RclrsError> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut array = unsafe { rmw_get_zero_initialized_network_flow_endpoint_array() };
+        // SAFETY: The array is zero-initialized as expected, and is finalized when dropped.
+        unsafe {
+            rcl_publisher_get_network_flow_endpoints(
+                &*self.handle.rcl_publisher.lock().unwrap(),
+                &mut rcutils_get_default_allocator(),
+                &mut array,
+            )
+            .ok_for("rcl_publisher_get_network_flow_endpoints")?;
+        }
+        Ok(network_flow_endpoints_from_rcl(&array))
+    }
+
+    /// Manually asserts that the publisher is alive.
+    ///
+    /// Publishers with the [`ManualByTopic`][1] liveliness policy must call this, or publish a
+    /// message, at least once per liveliness lease duration. Otherwise, subscriptions consider
+    /// the publisher as not alive anymore.
+    ///
+    /// Returns an [`InvalidArgument`][2] error if the liveliness policy of the publisher is not
+    /// [`ManualByTopic`][1].
+    ///
+    /// [1]: crate::QoSLivelinessPolicy::ManualByTopic
+    /// [2]: crate::RclReturnCode::InvalidArgument
+    pub fn assert_liveliness(&self) -> Result<(), RclrsError> {
+        let rcl_publisher = &*self.handle.rcl_publisher.lock().unwrap();
+        // SAFETY: The publisher is valid, so the returned pointer is valid as well.
+        let liveliness = QoSLivelinessPolicy::from(unsafe {
+            (*rcl_publisher_get_actual_qos(rcl_publisher)).liveliness
+        });
+        if liveliness != QoSLivelinessPolicy::ManualByTopic {
+            return Err(RclrsError::RclError {
+                code: RclReturnCode::InvalidArgument,
+                operation: None,
+                msg: Some(RclErrorMsg::new(format!(
+                    "Liveliness can only be asserted manually with the ManualByTopic \
+                     liveliness policy, but the publisher uses {:?}",
+                    liveliness
+                ))),
+            });
+        }
+        // SAFETY: No preconditions for this function (besides passing in a valid publisher).
+        unsafe { rcl_publisher_assert_liveliness(rcl_publisher) }
+            .ok_for("rcl_publisher_assert_liveliness")
+    }
+
     /// Publishes a message.
     ///
     /// The [`MessageCow`] trait is implemented by any
@@ -161,9 +383,122 @@ where
     ///
     /// Calling `publish()` is a potentially blocking call, see [this issue][1] for details.
     ///
+    /// If the publisher uses [intra-process communication][2], the message is
+    /// delivered directly to the intra-process subscriptions, and only published through the
+    /// middleware when there are other subscriptions as well, or when the publisher has
+    /// [transient local][4] durability.
+    ///
+    /// In debug builds with the `dyn_msg` feature, the bounded strings and sequences of the
+    /// message are checked against their upper bounds before publishing, using the
+    /// introspection type support of the message. If a field is too long, an
+    /// [`InvalidArgument`][3] error naming the field is returned. The bounded types of
+    /// `rosidl_runtime_rs` already prevent this, so the check only catches messages whose bounds
+    /// were bypassed, e.g. by unsafe code. If the introspection type support cannot be loaded,
+    /// an [`Error`][5] is returned, since the message can't be checked.
+    ///
     /// [1]: https://github.com/ros2/ros2/issues/255
+    /// [2]: crate::NodeBuilder::use_intra_process_comms
+    /// [3]: crate::RclReturnCode::InvalidArgument
+    /// [4]: crate::QoSDurabilityPolicy::TransientLocal
+    /// [5]: crate::RclReturnCode::Error
     pub fn publish<'a, M: MessageCow<'a, T>>(&self, message: M) -> Result<(), RclrsError> {
-        let rmw_message = T::into_rmw_message(message.into_cow());
+        let message = message.into_cow();
+        #[cfg(feature = "dyn_msg")]
+        if cfg!(debug_assertions) {
+            self.check_bounds(&message)?;
+        }
+        if let Some(intra_process) = &self.intra_process {
+            let message = Arc::new(message.into_owned());
+            let matched_intra_process_subscriptions = intra_process.publish(Arc::clone(&message));
+            // A transient local publisher always publishes to the middleware, which keeps the
+            // message for late-joining subscriptions.
+            if !self.transient_local
+                && self.get_subscription_count()? <= matched_intra_process_subscriptions
+            {
+                return Ok(());
+            }
+            return self.publish_to_middleware(Cow::Borrowed(&*message));
+        }
+        self.publish_to_middleware(message)
+    }
+
+    /// Sets the stamp of the message header to the current time of `clock`, and publishes the
+    /// message.
+    ///
+    /// This works for any message type whose first field is a `std_msgs/msg/Header`, which is
+    /// detected through the introspection type support of the message. The `frame_id` of the
+    /// header is left unchanged. Setting the stamp requires converting the message to its
+    /// RMW-native type and back, so for idiomatic message types this is more expensive than
+    /// setting the stamp manually.
+    ///
+    /// # Errors
+    /// If the first field of the message is not a header, or the introspection type support of
+    /// the message cannot be loaded, an [`InvalidArgument`][1] error is returned.
+    ///
+    /// [1]: crate::RclReturnCode::InvalidArgument
+    #[cfg(feature = "dyn_msg")]
+    pub fn publish_stamped(&self, message: T, clock: &Clock) -> Result<(), RclrsError> {
+        let invalid_argument = || RclrsError::RclError {
+            code: RclReturnCode::InvalidArgument,
+            operation: None,
+            msg: None,
+        };
+        let stamp_offset = header_stamp_offset::<T>()
+            .ok()
+            .flatten()
+            .ok_or_else(invalid_argument)?;
+        let now = clock.now().to_ros_msg().map_err(|_| invalid_argument())?;
+        let stamp = builtin_interfaces::msg::rmw::Time {
+            sec: now.sec,
+            nanosec: now.nanosec,
+        };
+        let mut rmw_message = T::into_rmw_message(Cow::Owned(message)).into_owned();
+        // SAFETY: The offset is the offset of the header stamp within the RMW-native message,
+        // and the stamp has the layout of a builtin_interfaces/msg/Time.
+        unsafe {
+            let stamp_ptr = (&mut rmw_message as *mut <T as Message>::RmwMsg as *mut u8)
+                .add(stamp_offset)
+                as *mut builtin_interfaces::msg::rmw::Time;
+            stamp_ptr.write(stamp);
+        }
+        self.publish(T::from_rmw_message(rmw_message))
+    }
+
+    // Checks that no bounded string or sequence of the message exceeds its upper bound.
+    #[cfg(feature = "dyn_msg")]
+    fn check_bounds(&self, message: &T) -> Result<(), RclrsError> {
+        let rmw_message = T::into_rmw_message(Cow::Borrowed(message));
+        let violation = match find_bounds_violation::<T>(&rmw_message) {
+            Ok(Some(violation)) => violation,
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                return Err(RclrsError::RclError {
+                    code: RclReturnCode::Error,
+                    operation: None,
+                    msg: Some(RclErrorMsg::new(format!(
+                        "Could not check the bounds of a {} message: {}",
+                        self.type_name(),
+                        err
+                    ))),
+                })
+            }
+        };
+        Err(RclrsError::RclError {
+            code: RclReturnCode::InvalidArgument,
+            operation: None,
+            msg: Some(RclErrorMsg::new(format!(
+                "The field '{}' of a {} message has a length of {}, which exceeds its upper \
+                 bound of {}",
+                violation.field,
+                self.type_name(),
+                violation.len,
+                violation.upper_bound
+            ))),
+        })
+    }
+
+    fn publish_to_middleware(&self, message: Cow<'_, T>) -> Result<(), RclrsError> {
+        let rmw_message = T::into_rmw_message(message);
         let rcl_publisher = &mut *self.handle.rcl_publisher.lock().unwrap();
         unsafe {
             // SAFETY: The message type is guaranteed to match the publisher type by the type system.
@@ -174,7 +509,7 @@ where
                 rmw_message.as_ref() as *const <T as Message>::RmwMsg as *mut _,
                 std::ptr::null_mut(),
             )
-            .ok()
+            .ok_for("rcl_publish")
         }
     }
 }
@@ -202,6 +537,9 @@ where
     /// 1. `Publisher::borrow_loaned_message()` is used and the subscription uses a callback taking a
     ///    [`ReadOnlyLoanedMessage`][1]
     ///
+    /// If the middleware cannot loan messages for this publisher, the message is allocated on the
+    /// heap instead, and publishing it falls back to a regular publish.
+    ///
     /// This function is only implemented for [`RmwMessage`]s since the "idiomatic" message type
     /// does not have a typesupport library.
     ///
@@ -214,6 +552,16 @@ where
     // - What happens when only *some* subscribers are local?
     // - What QOS settings are required exactly? https://cyclonedds.io/docs/cyclonedds/latest/shared_memory.html
     pub fn borrow_loaned_message(&self) -> Result<LoanedMessage<'_, T>, RclrsError> {
+        // SAFETY: No preconditions for this function.
+        let can_loan_messages =
+            unsafe { rcl_publisher_can_loan_messages(&*self.handle.rcl_publisher.lock().unwrap()) };
+        if !can_loan_messages {
+            return Ok(LoanedMessage {
+                publisher: self,
+                msg_ptr: Box::into_raw(Box::<T>::default()),
+                is_loaned: false,
+            });
+        }
         let mut msg_ptr = std::ptr::null_mut();
         unsafe {
             // SAFETY: msg_ptr contains a null ptr as expected by this function.
@@ -222,13 +570,30 @@ where
                 self.type_support_ptr,
                 &mut msg_ptr,
             )
-            .ok()?;
+            .ok_for("rcl_borrow_loaned_message")?;
         }
         Ok(LoanedMessage {
             publisher: self,
             msg_ptr: msg_ptr as *mut T,
+            is_loaned: true,
         })
     }
+
+    /// Publishes a message from a [`MessagePool`], and returns it to the pool.
+    ///
+    /// The message is passed to the middleware as it is, so neither publishing it nor acquiring
+    /// the next message from the pool allocates. This is why this function is only implemented
+    /// for [`RmwMessage`]s: an idiomatic message would have to be converted to a new RMW-native
+    /// message for each publication.
+    ///
+    /// With [intra-process communication][1], the message is cloned for the intra-process
+    /// subscriptions, like with [`publish()`][2].
+    ///
+    /// [1]: crate::NodeBuilder::use_intra_process_comms
+    /// [2]: Self::publish
+    pub fn publish_pooled(&self, message: PooledMessage<T>) -> Result<(), RclrsError> {
+        self.publish(&*message)
+    }
 }
 
 /// Convenience trait for [`Publisher::publish`].
@@ -260,9 +625,95 @@ mod tests {
         assert_sync::<Publisher<test_msgs::msg::BoundedSequences>>();
     }
 
+    #[test]
+    fn test_gid_matches_message_info() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use std::time::Duration;
+        use test_msgs::msg::Empty;
+
+        let namespace = "/test_gid_matches_message_info";
+        let graph = construct_test_graph(namespace)?;
+        let subscription = graph.node2.create_subscription::<Empty, _>(
+            "gid_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: Empty| {},
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<Empty>("gid_topic", QOS_PROFILE_DEFAULT)?;
+        let other_publisher = graph
+            .node1
+            .create_publisher::<Empty>("gid_topic", QOS_PROFILE_DEFAULT)?;
+        let gid = publisher.gid()?;
+        assert_eq!(publisher.gid()?, gid);
+        assert_ne!(other_publisher.gid()?, gid);
+        for _ in 0..50 {
+            if publisher.get_subscription_count()? > 0 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        publisher.publish(Empty::default())?;
+        let mut message_info = None;
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(100));
+            if let Some((_, info)) = subscription.take()? {
+                message_info = Some(info);
+                break;
+            }
+        }
+        let message_info = message_info.expect("The message was not received");
+        assert_eq!(message_info.publisher_gid, gid);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rcl_error_details() -> Result<(), RclrsError> {
+        use crate::{RclReturnCode, QOS_PROFILE_DEFAULT};
+        use std::error::Error;
+
+        let namespace = "/test_rcl_error_details";
+        let graph = construct_test_graph(namespace)?;
+        let Err(error) = graph.node1.create_publisher::<test_msgs::msg::Empty>(
+            "{unknown_substitution}/chatter",
+            QOS_PROFILE_DEFAULT,
+        ) else {
+            panic!("Creating a publisher with an unknown substitution succeeded");
+        };
+        let RclrsError::RclError {
+            code: RclReturnCode::TopicNameInvalid,
+            operation,
+            msg: Some(msg),
+        } = &error
+        else {
+            panic!("Expected a TopicNameInvalid error, got {:?}", error);
+        };
+        assert_eq!(*operation, Some("rcl_publisher_init"));
+        assert!(!msg.message().is_empty());
+
+        let display = error.to_string();
+        assert!(
+            display.starts_with("rcl_publisher_init failed: "),
+            "{}",
+            display
+        );
+        assert!(
+            display.contains("RCL_RET_TOPIC_NAME_INVALID"),
+            "{}",
+            display
+        );
+        assert!(display.contains(msg.message()), "{}", display);
+        assert_eq!(
+            error.source().map(|source| source.to_string()),
+            Some(msg.message().to_owned())
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_publishers() -> Result<(), RclrsError> {
-        use crate::{TopicEndpointInfo, QOS_PROFILE_SYSTEM_DEFAULT};
+        use crate::QOS_PROFILE_SYSTEM_DEFAULT;
         use test_msgs::msg;
 
         let namespace = "/test_publishers_graph";
@@ -308,20 +759,411 @@ mod tests {
         assert!(types.contains(&"test_msgs/msg/Defaults".to_string()));
 
         // Test get_publishers_info_by_topic()
-        let expected_publishers_info = vec![TopicEndpointInfo {
-            node_name: String::from("graph_test_node_1"),
-            node_namespace: String::from(namespace),
-            topic_type: String::from("test_msgs/msg/Empty"),
-        }];
-        assert_eq!(
-            graph.node1.get_publishers_info_by_topic(&topic1)?,
-            expected_publishers_info
-        );
+        let publishers_info = graph.node1.get_publishers_info_by_topic(&topic1)?;
+        assert_eq!(publishers_info.len(), 1);
+        assert_eq!(publishers_info[0].node_name, "graph_test_node_1");
+        assert_eq!(publishers_info[0].node_namespace, namespace);
+        assert_eq!(publishers_info[0].topic_type, "test_msgs/msg/Empty");
         assert_eq!(
             graph.node2.get_publishers_info_by_topic(&topic1)?,
-            expected_publishers_info
+            publishers_info
         );
 
         Ok(())
     }
+
+    #[test]
+    fn test_publishers_info_qos() -> Result<(), RclrsError> {
+        use crate::{QoSReliabilityPolicy, QOS_PROFILE_DEFAULT};
+        use std::time::Duration;
+        use test_msgs::msg;
+
+        let namespace = "/test_publishers_info_qos";
+        let graph = construct_test_graph(namespace)?;
+        let qos = QOS_PROFILE_DEFAULT.reliable();
+        let _publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("info_qos_topic", qos)?;
+
+        // The graph is updated asynchronously
+        let mut publishers_info = graph.node2.get_publishers_info_by_topic("info_qos_topic")?;
+        for _ in 0..50 {
+            if !publishers_info.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+            publishers_info = graph.node2.get_publishers_info_by_topic("info_qos_topic")?;
+        }
+
+        assert_eq!(publishers_info.len(), 1);
+        let info = &publishers_info[0];
+        assert_eq!(info.node_name, graph.node1.name());
+        assert_eq!(info.qos.reliability, QoSReliabilityPolicy::Reliable);
+        assert!(info.endpoint_gid.iter().any(|&byte| byte != 0));
+        assert!(graph
+            .node2
+            .get_subscriptions_info_by_topic("info_qos_topic")?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_assert_liveliness() -> Result<(), RclrsError> {
+        use crate::{QoSLivelinessPolicy, QOS_PROFILE_DEFAULT};
+        use test_msgs::msg;
+
+        let namespace = "/test_assert_liveliness";
+        let graph = construct_test_graph(namespace)?;
+
+        let manual_publisher = graph.node1.create_publisher::<msg::Empty>(
+            "manual_liveliness_topic",
+            QOS_PROFILE_DEFAULT.liveliness(QoSLivelinessPolicy::ManualByTopic),
+        )?;
+        manual_publisher.assert_liveliness()?;
+
+        let automatic_publisher = graph.node1.create_publisher::<msg::Empty>(
+            "automatic_liveliness_topic",
+            QOS_PROFILE_DEFAULT.liveliness(QoSLivelinessPolicy::Automatic),
+        )?;
+        assert!(matches!(
+            automatic_publisher.assert_liveliness(),
+            Err(RclrsError::RclError {
+                code: RclReturnCode::InvalidArgument,
+                ..
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_subscription_count() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use std::time::Duration;
+        use test_msgs::msg;
+
+        let namespace = "/test_get_subscription_count";
+        let graph = construct_test_graph(namespace)?;
+
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("subscription_count_topic", QOS_PROFILE_DEFAULT)?;
+        assert_eq!(publisher.get_subscription_count()?, 0);
+
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            "subscription_count_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::Empty| {},
+        )?;
+        for _ in 0..50 {
+            if publisher.get_subscription_count()? == 1 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(publisher.get_subscription_count()?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_for_all_acked() -> Result<(), RclrsError> {
+        use crate::{spin_once, QOS_PROFILE_DEFAULT};
+        use test_msgs::msg;
+
+        let namespace = "/test_wait_for_all_acked";
+        let graph = construct_test_graph(namespace)?;
+
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("acked_topic", QOS_PROFILE_DEFAULT)?;
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            "acked_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::Empty| {},
+        )?;
+        for _ in 0..50 {
+            if publisher.get_subscription_count()? == 1 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        publisher.publish(msg::Empty::default())?;
+        ignore_timeout(spin_once(
+            Arc::clone(&graph.node2),
+            Some(Duration::from_millis(100)),
+        ))?;
+        assert!(publisher.wait_for_all_acked(Some(Duration::from_secs(5)))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_flow_endpoints() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use test_msgs::msg;
+
+        let namespace = "/test_network_flow_endpoints";
+        let graph = construct_test_graph(namespace)?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("flow_topic", QOS_PROFILE_DEFAULT)?;
+        let subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            "flow_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::Empty| {},
+        )?;
+
+        // Not every RMW implementation supports this, but it must not fail otherwise
+        for result in [
+            publisher.get_network_flow_endpoints(),
+            subscription.get_network_flow_endpoints(),
+        ] {
+            match result {
+                Ok(endpoints) => {
+                    for endpoint in endpoints {
+                        assert!(!endpoint.internet_address.is_empty());
+                    }
+                }
+                Err(RclrsError::RclError {
+                    code: RclReturnCode::Unsupported,
+                    ..
+                }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_network_flow_endpoints() -> Result<(), RclrsError> {
+        use crate::{UniqueNetworkFlowEndpoints, QOS_PROFILE_DEFAULT};
+        use test_msgs::msg;
+
+        let graph = construct_test_graph("/test_unique_network_flow_endpoints")?;
+        let options = |requirement| {
+            PublisherOptions::new(QOS_PROFILE_DEFAULT).unique_network_flow_endpoints(requirement)
+        };
+
+        // These requirements can always be fulfilled
+        for requirement in [
+            UniqueNetworkFlowEndpoints::NotRequired,
+            UniqueNetworkFlowEndpoints::Optional,
+            UniqueNetworkFlowEndpoints::SystemDefault,
+        ] {
+            graph.node1.create_publisher_with_options::<msg::Empty>(
+                "unique_flow_topic",
+                options(requirement),
+            )?;
+        }
+
+        // This fails if the RMW implementation does not support unique network flow endpoints
+        match graph.node1.create_publisher_with_options::<msg::Empty>(
+            "unique_flow_topic",
+            options(UniqueNetworkFlowEndpoints::StrictlyRequired),
+        ) {
+            Ok(publisher) => match publisher.get_network_flow_endpoints() {
+                Ok(endpoints) => assert!(!endpoints.is_empty()),
+                Err(RclrsError::RclError {
+                    code: RclReturnCode::Unsupported,
+                    ..
+                }) => {}
+                Err(e) => return Err(e),
+            },
+            Err(RclrsError::RclError {
+                code: RclReturnCode::Error | RclReturnCode::Unsupported,
+                ..
+            }) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_offered_deadline_missed_event() -> Result<(), RclrsError> {
+        use crate::{spin_once, OfferedDeadlineMissedStatus, QOS_PROFILE_DEFAULT};
+        use std::time::Duration;
+        use test_msgs::msg;
+
+        let namespace = "/test_offered_deadline_missed_event";
+        let graph = construct_test_graph(namespace)?;
+        let qos = QOS_PROFILE_DEFAULT.deadline(Duration::from_millis(50));
+
+        let deadline_missed = Arc::new(Mutex::new(None));
+        let deadline_missed_in_callback = Arc::clone(&deadline_missed);
+        let options = PublisherOptions::new(qos).offered_deadline_missed_callback(
+            move |status: OfferedDeadlineMissedStatus| {
+                *deadline_missed_in_callback.lock().unwrap() = Some(status);
+            },
+        );
+        let publisher = graph
+            .node1
+            .create_publisher_with_options::<msg::Empty>("offered_deadline_topic", options)?;
+        assert_eq!(publisher.events.len(), 1);
+
+        publisher.publish(msg::Empty::default())?;
+        for _ in 0..20 {
+            ignore_timeout(spin_once(
+                Arc::clone(&graph.node1),
+                Some(Duration::from_millis(100)),
+            ))?;
+            if deadline_missed.lock().unwrap().is_some() {
+                break;
+            }
+        }
+
+        let status = deadline_missed
+            .lock()
+            .unwrap()
+            .take()
+            .expect("The deadline-missed callback was not called");
+        assert!(status.total_count > 0);
+        Ok(())
+    }
+
+    #[cfg(not(ros_distro = "humble"))]
+    #[test]
+    fn test_publisher_matched_event() -> Result<(), RclrsError> {
+        use crate::{spin_once, MatchedStatus, QOS_PROFILE_DEFAULT};
+        use std::time::Duration;
+        use test_msgs::msg;
+
+        let namespace = "/test_publisher_matched_event";
+        let graph = construct_test_graph(namespace)?;
+
+        let matched = Arc::new(Mutex::new(None));
+        let matched_in_callback = Arc::clone(&matched);
+        let options = PublisherOptions::new(QOS_PROFILE_DEFAULT).matched_callback(
+            move |status: MatchedStatus| {
+                *matched_in_callback.lock().unwrap() = Some(status);
+            },
+        );
+        let _publisher = graph
+            .node1
+            .create_publisher_with_options::<msg::Empty>("matched_topic", options)?;
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            "matched_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::Empty| {},
+        )?;
+
+        for _ in 0..50 {
+            ignore_timeout(spin_once(
+                Arc::clone(&graph.node1),
+                Some(Duration::from_millis(100)),
+            ))?;
+            if matched.lock().unwrap().is_some() {
+                break;
+            }
+        }
+
+        let status = matched
+            .lock()
+            .unwrap()
+            .take()
+            .expect("The matched callback was not called");
+        assert_eq!(status.current_count, 1);
+        assert_eq!(status.current_count_change, 1);
+        Ok(())
+    }
+
+    #[cfg(all(feature = "dyn_msg", debug_assertions))]
+    #[test]
+    fn test_publish_checks_bounds() -> Result<(), RclrsError> {
+        use crate::{RclReturnCode, QOS_PROFILE_DEFAULT};
+        use test_msgs::msg::Strings;
+
+        let namespace = "/test_publish_checks_bounds";
+        let graph = construct_test_graph(namespace)?;
+        let publisher = graph
+            .node1
+            .create_publisher::<Strings>("bounded_topic", QOS_PROFILE_DEFAULT)?;
+
+        let mut message = Strings::default();
+        message.bounded_string_value = "short enough".try_into().unwrap();
+        publisher.publish(&message)?;
+
+        // BoundedString cannot be constructed from a string that is too long, so the bound is
+        // bypassed to simulate e.g. a message that was filled in by unsafe code.
+        message.bounded_string_value = unsafe {
+            std::mem::transmute::<rosidl_runtime_rs::String, rosidl_runtime_rs::BoundedString<22>>(
+                rosidl_runtime_rs::String::from("this string is much too long"),
+            )
+        };
+        match publisher.publish(&message) {
+            Err(RclrsError::RclError {
+                code: RclReturnCode::InvalidArgument,
+                msg: Some(msg),
+                ..
+            }) => {
+                let msg = msg.to_string();
+                assert!(msg.contains("'bounded_string_value'"), "{}", msg);
+                assert!(msg.contains("test_msgs/msg/Strings"), "{}", msg);
+                assert!(msg.contains("length of 28"), "{}", msg);
+                assert!(msg.contains("upper bound of 22"), "{}", msg);
+            }
+            result => panic!("Expected an InvalidArgument error, got {:?}", result),
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "dyn_msg")]
+    #[test]
+    fn test_publish_stamped() -> Result<(), RclrsError> {
+        use crate::{Clock, RclReturnCode, QOS_PROFILE_DEFAULT};
+        use geometry_msgs::msg::PoseStamped;
+        use std::time::Duration;
+
+        let namespace = "/test_publish_stamped";
+        let graph = construct_test_graph(namespace)?;
+        let subscription = graph.node2.create_subscription::<PoseStamped, _>(
+            "stamped_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: PoseStamped| {},
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<PoseStamped>("stamped_topic", QOS_PROFILE_DEFAULT)?;
+        for _ in 0..50 {
+            if publisher.get_subscription_count()? > 0 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        let clock = Clock::system();
+        let mut message = PoseStamped::default();
+        message.header.frame_id = String::from("map");
+        publisher.publish_stamped(message, &clock)?;
+        let mut received = None;
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(100));
+            if let Some((message, _)) = subscription.take()? {
+                received = Some(message);
+                break;
+            }
+        }
+        let received = received.expect("The stamped message was not received");
+        assert_eq!(received.header.frame_id, "map");
+        assert_ne!(received.header.stamp, Default::default());
+        // The generated messages use their own builtin_interfaces types, so the stamp is
+        // compared in nanoseconds.
+        let stamp_nsec = i64::from(received.header.stamp.sec) * 1_000_000_000
+            + i64::from(received.header.stamp.nanosec);
+        let now_nsec = clock.now().nsec;
+        assert!(stamp_nsec <= now_nsec);
+        assert!(now_nsec - stamp_nsec < 10_000_000_000);
+
+        // Messages without a header are rejected.
+        let publisher = graph.node1.create_publisher::<geometry_msgs::msg::Twist>(
+            "unstamped_topic",
+            QOS_PROFILE_DEFAULT,
+        )?;
+        assert!(matches!(
+            publisher.publish_stamped(Default::default(), &clock),
+            Err(RclrsError::RclError {
+                code: RclReturnCode::InvalidArgument,
+                ..
+            })
+        ));
+        Ok(())
+    }
 }
@@ -13,6 +13,9 @@ use crate::{rcl_bindings::*, Publisher, RclrsError, ToResult};
 ///
 /// The loan is returned by dropping the message or [publishing it][1].
 ///
+/// If the middleware does not support loaning messages for this publisher, the message is
+/// allocated on the heap instead and published regularly. See [`LoanedMessage::is_loaned()`].
+///
 /// [1]: LoanedMessage::publish
 pub struct LoanedMessage<'a, T>
 where
@@ -20,6 +23,8 @@ where
 {
     pub(super) msg_ptr: *mut T,
     pub(super) publisher: &'a Publisher<T>,
+    // Whether msg_ptr was obtained through rcl_borrow_loaned_message, as opposed to Box::into_raw.
+    pub(super) is_loaned: bool,
 }
 
 impl<'a, T> Deref for LoanedMessage<'a, T>
@@ -28,7 +33,7 @@ where
 {
     type Target = T;
     fn deref(&self) -> &Self::Target {
-        // SAFETY: msg_ptr is a valid pointer, obtained through rcl_borrow_loaned_message.
+        // SAFETY: msg_ptr is a valid pointer, obtained through rcl_borrow_loaned_message or Box::into_raw.
         unsafe { &*self.msg_ptr }
     }
 }
@@ -38,7 +43,7 @@ where
     T: RmwMessage,
 {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        // SAFETY: msg_ptr is a valid pointer, obtained through rcl_borrow_loaned_message.
+        // SAFETY: msg_ptr is a valid pointer, obtained through rcl_borrow_loaned_message or Box::into_raw.
         unsafe { &mut *self.msg_ptr }
     }
 }
@@ -50,16 +55,22 @@ where
     fn drop(&mut self) {
         // Check whether the loan was already returned with
         // rcl_publish_loaned_message()
-        if !self.msg_ptr.is_null() {
-            unsafe {
-                // SAFETY: These two pointers are valid, and the msg_ptr is not used afterwards.
-                rcl_return_loaned_message_from_publisher(
-                    &*self.publisher.handle.rcl_publisher.lock().unwrap(),
-                    self.msg_ptr as *mut _,
-                )
-                .ok()
-                .unwrap()
-            }
+        if self.msg_ptr.is_null() {
+            return;
+        }
+        if !self.is_loaned {
+            // SAFETY: The msg_ptr was obtained through Box::into_raw and is not used afterwards.
+            drop(unsafe { Box::from_raw(self.msg_ptr) });
+            return;
+        }
+        unsafe {
+            // SAFETY: These two pointers are valid, and the msg_ptr is not used afterwards.
+            rcl_return_loaned_message_from_publisher(
+                &*self.publisher.handle.rcl_publisher.lock().unwrap(),
+                self.msg_ptr as *mut _,
+            )
+            .ok_for("rcl_return_loaned_message_from_publisher")
+            .unwrap()
         }
     }
 }
@@ -74,8 +85,27 @@ impl<'a, T> LoanedMessage<'a, T>
 where
     T: RmwMessage,
 {
+    /// Returns `true` if the message is owned by the middleware, and `false` if the middleware
+    /// does not support loaning messages and the message was allocated on the heap instead.
+    pub fn is_loaned(&self) -> bool {
+        self.is_loaned
+    }
+
     /// Publishes the loaned message, falling back to regular publishing if needed.
     pub fn publish(mut self) -> Result<(), RclrsError> {
+        if !self.is_loaned {
+            unsafe {
+                // SAFETY: The message type is guaranteed to match the publisher type by the type
+                // system. The third argument is explictly allowed to be NULL.
+                // The heap-allocated message is freed by the drop impl.
+                return rcl_publish(
+                    &*self.publisher.handle.rcl_publisher.lock().unwrap(),
+                    self.msg_ptr as *mut _,
+                    std::ptr::null_mut(),
+                )
+                .ok_for("rcl_publish");
+            }
+        }
         unsafe {
             // SAFETY: These two pointers are valid, and the msg_ptr is not used afterwards.
             rcl_publish_loaned_message(
@@ -83,7 +113,7 @@ where
                 self.msg_ptr as *mut _,
                 std::ptr::null_mut(),
             )
-            .ok()?;
+            .ok_for("rcl_publish_loaned_message")?;
         }
         // Set the msg_ptr to null, as a signal to the drop impl that this
         // loan was already returned.
@@ -103,4 +133,28 @@ mod tests {
         assert_send::<LoanedMessage<test_msgs::msg::rmw::BoundedSequences>>();
         assert_sync::<LoanedMessage<test_msgs::msg::rmw::BoundedSequences>>();
     }
+
+    #[test]
+    fn test_heap_fallback() -> Result<(), RclrsError> {
+        use crate::{Context, QOS_PROFILE_DEFAULT};
+        use test_msgs::msg;
+
+        let context = Context::new([])?;
+        let node = crate::create_node(&context, "test_loaned_message_heap_fallback")?;
+        // Messages with unbounded sequences can never be loaned.
+        let publisher = node.create_publisher::<msg::rmw::UnboundedSequences>(
+            "loaned_topic",
+            QOS_PROFILE_DEFAULT,
+        )?;
+        let mut message = publisher.borrow_loaned_message()?;
+        assert!(!message.is_loaned());
+        message.int32_values = rosidl_runtime_rs::seq![1, 2, 3];
+        assert_eq!(message.int32_values.len(), 3);
+        message.publish()?;
+
+        // Dropping an unpublished message frees it.
+        let message = publisher.borrow_loaned_message()?;
+        drop(message);
+        Ok(())
+    }
 }
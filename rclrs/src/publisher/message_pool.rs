@@ -0,0 +1,212 @@
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+use rosidl_runtime_rs::Message;
+
+/// A pool of reusable message allocations, to avoid allocating a message for every publication.
+///
+/// Messages are taken from the pool with [`acquire()`][1], and return to the pool when the
+/// [`PooledMessage`] is dropped, e.g. after publishing it with [`Publisher::publish_pooled()`][2].
+/// Once the pool has been warmed up, acquiring a message does not allocate.
+///
+/// This is useful for publishers with a high rate, when the middleware does not support
+/// [loaned messages][3]. Publishing from a pool requires a publisher of the RMW-native message
+/// type, since an idiomatic message would be converted to a new RMW-native message anyway. Note
+/// that only the allocation of the message itself is reused: returned messages are reset to their
+/// default value, which frees the contents of their strings and sequences.
+///
+/// The pool can be cloned cheaply, and all clones share the same messages.
+///
+/// # Example
+/// ```
+/// # use rclrs::{Context, MessagePool, RclrsError, QOS_PROFILE_DEFAULT};
+/// use test_msgs::msg::rmw::BasicTypes;
+///
+/// let context = Context::new([])?;
+/// let node = rclrs::create_node(&context, "my_node")?;
+/// let publisher = node.create_publisher::<BasicTypes>("topic", QOS_PROFILE_DEFAULT)?;
+/// let pool = MessagePool::<BasicTypes>::new(1);
+/// for i in 0..10 {
+///     let mut message = pool.acquire();
+///     message.int32_value = i;
+///     publisher.publish_pooled(message)?;
+/// }
+/// # Ok::<(), RclrsError>(())
+/// ```
+///
+/// [1]: MessagePool::acquire
+/// [2]: crate::Publisher::publish_pooled
+/// [3]: crate::Publisher::borrow_loaned_message
+pub struct MessagePool<T>
+where
+    T: Message,
+{
+    shared: Arc<MessagePoolShared<T>>,
+}
+
+struct MessagePoolShared<T> {
+    // The messages that are ready to be acquired again.
+    recycled: Mutex<Vec<Box<T>>>,
+    // The maximum number of recycled messages. Further messages are freed when dropped.
+    capacity: usize,
+}
+
+impl<T> Clone for MessagePool<T>
+where
+    T: Message,
+{
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T> fmt::Debug for MessagePool<T>
+where
+    T: Message,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessagePool")
+            .field("available", &self.available())
+            .field("capacity", &self.shared.capacity)
+            .finish()
+    }
+}
+
+impl<T> MessagePool<T>
+where
+    T: Message,
+{
+    /// Creates an empty pool that keeps at most `capacity` messages for reuse.
+    ///
+    /// The capacity should be the number of messages that are in use at the same time, which is
+    /// usually 1 for a publisher.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            shared: Arc::new(MessagePoolShared {
+                recycled: Mutex::new(Vec::with_capacity(capacity)),
+                capacity,
+            }),
+        }
+    }
+
+    /// Takes a message with the default value from the pool.
+    ///
+    /// If the pool is empty, a new message is allocated.
+    pub fn acquire(&self) -> PooledMessage<T> {
+        let message = self
+            .shared
+            .recycled
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or_default();
+        PooledMessage {
+            message: Some(message),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// Returns the number of messages that can be acquired without allocating.
+    pub fn available(&self) -> usize {
+        self.shared.recycled.lock().unwrap().len()
+    }
+}
+
+/// A message from a [`MessagePool`].
+///
+/// It dereferences to a `&mut T`, and returns to the pool when dropped.
+pub struct PooledMessage<T>
+where
+    T: Message,
+{
+    // Only None while dropping.
+    message: Option<Box<T>>,
+    shared: Arc<MessagePoolShared<T>>,
+}
+
+impl<T> Deref for PooledMessage<T>
+where
+    T: Message,
+{
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        self.message.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for PooledMessage<T>
+where
+    T: Message,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.message.as_mut().unwrap()
+    }
+}
+
+impl<T> fmt::Debug for PooledMessage<T>
+where
+    T: Message,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PooledMessage").field(&**self).finish()
+    }
+}
+
+impl<T> Drop for PooledMessage<T>
+where
+    T: Message,
+{
+    fn drop(&mut self) {
+        let mut message = match self.message.take() {
+            Some(message) => message,
+            None => return,
+        };
+        let mut recycled = self.shared.recycled.lock().unwrap();
+        if recycled.len() < self.shared.capacity {
+            *message = T::default();
+            recycled.push(message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_helpers::*, RclrsError, QOS_PROFILE_DEFAULT};
+    use test_msgs::msg;
+
+    #[test]
+    fn recycled_messages_are_cleared() {
+        let pool = MessagePool::<msg::BasicTypes>::new(1);
+        {
+            let mut first = pool.acquire();
+            let mut second = pool.acquire();
+            first.int32_value = 1;
+            second.int32_value = 2;
+        }
+        // Only one message is kept, according to the capacity.
+        assert_eq!(pool.available(), 1);
+        assert_eq!(pool.acquire().int32_value, 0);
+    }
+
+    #[test]
+    fn test_publish_pooled() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_publish_pooled")?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::rmw::BasicTypes>("pooled_topic", QOS_PROFILE_DEFAULT)?;
+        let pool = MessagePool::new(1);
+        for i in 0..3 {
+            let mut message = pool.acquire();
+            message.int32_value = i;
+            publisher.publish_pooled(message)?;
+            assert_eq!(pool.available(), 1);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+
+#[cfg(not(ros_distro = "humble"))]
+use crate::MatchedStatus;
+use crate::{
+    LivelinessLostStatus, OfferedDeadlineMissedStatus, QoSProfile, UniqueNetworkFlowEndpoints,
+    QOS_PROFILE_DEFAULT,
+};
+
+/// Options for creating a [`Publisher`][1].
+///
+/// Use this together with [`Node::create_publisher_with_options()`][2] to configure
+/// features beyond the quality of service profile.
+///
+/// [1]: crate::Publisher
+/// [2]: crate::Node::create_publisher_with_options
+pub struct PublisherOptions {
+    /// The quality of service profile of the publisher.
+    pub qos: QoSProfile,
+    pub(crate) unique_network_flow_endpoints: UniqueNetworkFlowEndpoints,
+    pub(crate) event_callbacks: PublisherEventCallbacks,
+    // Overrides the intra-process setting of the node, if set.
+    pub(crate) use_intra_process: Option<bool>,
+}
+
+/// The callbacks for the events of a publisher.
+#[derive(Default)]
+pub(crate) struct PublisherEventCallbacks {
+    pub(crate) offered_deadline_missed: Option<Box<dyn FnMut(OfferedDeadlineMissedStatus) + Send>>,
+    #[cfg(not(ros_distro = "humble"))]
+    pub(crate) matched: Option<Box<dyn FnMut(MatchedStatus) + Send>>,
+    pub(crate) liveliness_lost: Option<Box<dyn FnMut(LivelinessLostStatus) + Send>>,
+}
+
+impl fmt::Debug for PublisherOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("PublisherOptions");
+        debug
+            .field("qos", &self.qos)
+            .field(
+                "unique_network_flow_endpoints",
+                &self.unique_network_flow_endpoints,
+            )
+            .field(
+                "offered_deadline_missed_callback",
+                &self.event_callbacks.offered_deadline_missed.is_some(),
+            );
+        #[cfg(not(ros_distro = "humble"))]
+        debug.field("matched_callback", &self.event_callbacks.matched.is_some());
+        debug
+            .field(
+                "liveliness_lost_callback",
+                &self.event_callbacks.liveliness_lost.is_some(),
+            )
+            .field("use_intra_process", &self.use_intra_process)
+            .finish()
+    }
+}
+
+impl Default for PublisherOptions {
+    fn default() -> Self {
+        Self::new(QOS_PROFILE_DEFAULT)
+    }
+}
+
+impl From<QoSProfile> for PublisherOptions {
+    fn from(qos: QoSProfile) -> Self {
+        Self::new(qos)
+    }
+}
+
+impl PublisherOptions {
+    /// Creates publisher options with the given quality of service profile.
+    pub fn new(qos: QoSProfile) -> Self {
+        Self {
+            qos,
+            unique_network_flow_endpoints: UniqueNetworkFlowEndpoints::default(),
+            event_callbacks: PublisherEventCallbacks::default(),
+            use_intra_process: None,
+        }
+    }
+
+    /// Sets the quality of service profile.
+    pub fn qos(mut self, qos: QoSProfile) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Sets whether the publisher requires unique network flow endpoints.
+    ///
+    /// The default is [`UniqueNetworkFlowEndpoints::SystemDefault`]. With
+    /// [`UniqueNetworkFlowEndpoints::StrictlyRequired`], creating the publisher returns an error if
+    /// the RMW implementation cannot provide unique network flow endpoints. The endpoints can be
+    /// inspected with [`Publisher::get_network_flow_endpoints()`][1].
+    ///
+    /// [1]: crate::Publisher::get_network_flow_endpoints
+    pub fn unique_network_flow_endpoints(
+        mut self,
+        requirement: UniqueNetworkFlowEndpoints,
+    ) -> Self {
+        self.unique_network_flow_endpoints = requirement;
+        self
+    }
+
+    /// Sets whether the publisher uses intra-process communication, overriding the setting of
+    /// its node, see [`NodeBuilder::use_intra_process_comms()`][1].
+    ///
+    /// Messages only take the intra-process path when both the publisher and the subscription
+    /// use intra-process communication. E.g. a publisher with intra-process communication
+    /// disabled publishes all of its messages through the middleware, where they can be observed
+    /// by tools in other processes.
+    ///
+    /// [1]: crate::NodeBuilder::use_intra_process_comms
+    pub fn use_intra_process(mut self, enable: bool) -> Self {
+        self.use_intra_process = Some(enable);
+        self
+    }
+
+    /// Sets a callback that is called when the publisher did not publish a message within the
+    /// [`deadline`][1] of its QoS profile.
+    ///
+    /// If the RMW implementation does not support this event, creating the publisher returns an
+    /// [`Unsupported`][2] error.
+    ///
+    /// The callback is executed when spinning the node.
+    ///
+    /// [1]: crate::QoSProfile::deadline
+    /// [2]: crate::RclReturnCode::Unsupported
+    pub fn offered_deadline_missed_callback(
+        mut self,
+        callback: impl FnMut(OfferedDeadlineMissedStatus) + Send + 'static,
+    ) -> Self {
+        self.event_callbacks.offered_deadline_missed = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when a subscription is matched with or unmatched from
+    /// the publisher.
+    ///
+    /// If the RMW implementation does not support this event, creating the publisher returns an
+    /// [`Unsupported`][1] error.
+    ///
+    /// The callback is executed when spinning the node.
+    ///
+    /// This is only available since ROS 2 Iron.
+    ///
+    /// [1]: crate::RclReturnCode::Unsupported
+    #[cfg(not(ros_distro = "humble"))]
+    pub fn matched_callback(
+        mut self,
+        callback: impl FnMut(MatchedStatus) + Send + 'static,
+    ) -> Self {
+        self.event_callbacks.matched = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when the publisher did not assert its liveliness within
+    /// the [`liveliness_lease_duration`][1] of its QoS profile.
+    ///
+    /// With the [`ManualByTopic`][2] liveliness policy, liveliness is asserted by publishing or
+    /// by calling [`Publisher::assert_liveliness()`][3].
+    ///
+    /// If the RMW implementation does not support this event, creating the publisher returns an
+    /// [`Unsupported`][4] error.
+    ///
+    /// The callback is executed when spinning the node.
+    ///
+    /// [1]: crate::QoSProfile::liveliness_lease_duration
+    /// [2]: crate::QoSLivelinessPolicy::ManualByTopic
+    /// [3]: crate::Publisher::assert_liveliness
+    /// [4]: crate::RclReturnCode::Unsupported
+    pub fn liveliness_lost_callback(
+        mut self,
+        callback: impl FnMut(LivelinessLostStatus) + Send + 'static,
+    ) -> Self {
+        self.event_callbacks.liveliness_lost = Some(Box::new(callback));
+        self
+    }
+}
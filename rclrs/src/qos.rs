@@ -1,6 +1,6 @@
-use std::time::Duration;
+use std::{ffi::CStr, os::raw::c_char, time::Duration};
 
-use crate::rcl_bindings::*;
+use crate::{error::ToResult, rcl_bindings::*, RclrsError};
 
 /// The `HISTORY` DDS QoS policy.
 ///
@@ -127,6 +127,17 @@ pub enum QoSDuration {
     Custom(Duration),
 }
 
+impl QoSDuration {
+    /// Returns the duration if it is a specific duration, or `None` for the system default and
+    /// infinite durations.
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            Self::Custom(duration) => Some(*duration),
+            Self::SystemDefault | Self::Infinite => None,
+        }
+    }
+}
+
 /// A Quality of Service profile.
 ///
 /// See [docs.ros.org][1] on Quality of Service settings in general.
@@ -179,6 +190,20 @@ pub struct QoSProfile {
     pub avoid_ros_namespace_conventions: bool,
 }
 
+/// The result of checking whether a publisher and a subscription QoS profile are compatible.
+///
+/// See [`qos_check_compatible()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QoSCompatibility {
+    /// The profiles are compatible.
+    Ok,
+    /// The profiles might be incompatible, e.g. because one of them uses a system default
+    /// policy whose value is not known.
+    Warning(String),
+    /// The profiles are incompatible, so the publisher and subscription will not communicate.
+    Error(String),
+}
+
 /// Sets the `QoSProfile` to the RCL default.
 impl Default for QoSProfile {
     fn default() -> Self {
@@ -206,7 +231,113 @@ impl From<QoSProfile> for rmw_qos_profile_t {
     }
 }
 
+impl From<rmw_qos_profile_t> for QoSProfile {
+    fn from(qos: rmw_qos_profile_t) -> Self {
+        let depth = qos.depth as u32;
+        Self {
+            history: match qos.history {
+                rmw_qos_history_policy_t::RMW_QOS_POLICY_HISTORY_KEEP_LAST => {
+                    QoSHistoryPolicy::KeepLast { depth }
+                }
+                rmw_qos_history_policy_t::RMW_QOS_POLICY_HISTORY_KEEP_ALL => {
+                    QoSHistoryPolicy::KeepAll
+                }
+                _ => QoSHistoryPolicy::SystemDefault { depth },
+            },
+            reliability: qos.reliability.into(),
+            durability: qos.durability.into(),
+            deadline: qos.deadline.into(),
+            lifespan: qos.lifespan.into(),
+            liveliness: qos.liveliness.into(),
+            liveliness_lease_duration: qos.liveliness_lease_duration.into(),
+            avoid_ros_namespace_conventions: qos.avoid_ros_namespace_conventions,
+        }
+    }
+}
+
 impl QoSProfile {
+    /// Returns the QoS profile for sensor data, see [`QOS_PROFILE_SENSOR_DATA`].
+    pub fn sensor_data() -> Self {
+        QOS_PROFILE_SENSOR_DATA
+    }
+
+    /// Returns the QoS profile for parameters, see [`QOS_PROFILE_PARAMETERS`].
+    pub fn parameters() -> Self {
+        QOS_PROFILE_PARAMETERS
+    }
+
+    /// Returns the QoS profile for services, see [`QOS_PROFILE_SERVICES_DEFAULT`].
+    pub fn services_default() -> Self {
+        QOS_PROFILE_SERVICES_DEFAULT
+    }
+
+    /// Returns the QoS profile for parameter events, see [`QOS_PROFILE_PARAMETER_EVENTS`].
+    pub fn parameter_events() -> Self {
+        QOS_PROFILE_PARAMETER_EVENTS
+    }
+
+    /// Returns a QoS profile for "latched" topics, on which subscriptions that are created late
+    /// still receive the last published message.
+    ///
+    /// This is the [default profile][1] with [transient local][2] durability and a history depth
+    /// of 1. The publisher keeps its last message around for subscriptions that join later.
+    /// For this to work, both sides need a transient local durability: a volatile subscription
+    /// only receives new messages, and a volatile publisher is [incompatible][3] with a transient
+    /// local subscription, so they never connect. Use this profile for both the publisher and
+    /// the subscription.
+    ///
+    /// [1]: QOS_PROFILE_DEFAULT
+    /// [2]: QoSDurabilityPolicy::TransientLocal
+    /// [3]: Self::check_compatible
+    pub fn transient_local_latch() -> Self {
+        QOS_PROFILE_DEFAULT.keep_last(1).transient_local()
+    }
+
+    /// Returns the QoS profile that uses the RMW implementation's defaults, see
+    /// [`QOS_PROFILE_SYSTEM_DEFAULT`].
+    pub fn system_default() -> Self {
+        QOS_PROFILE_SYSTEM_DEFAULT
+    }
+
+    /// Checks whether a publisher with the `publisher` profile and a subscription with the
+    /// `subscription` profile can communicate with each other.
+    ///
+    /// Incompatible profiles do not cause an error when creating the publisher or subscription,
+    /// they just never connect. This function can be used to detect that ahead of time.
+    /// If the result is not [`QoSCompatibility::Ok`], it contains a human-readable reason.
+    pub fn check_compatible(
+        publisher: &QoSProfile,
+        subscription: &QoSProfile,
+    ) -> Result<QoSCompatibility, RclrsError> {
+        let mut compatibility = rmw_qos_compatibility_type_t::RMW_QOS_COMPATIBILITY_OK;
+        let mut reason = [0 as c_char; 2048];
+        // SAFETY: The reason buffer is valid for the given size, and the function
+        // nul-terminates the reason within that size.
+        unsafe {
+            rmw_qos_profile_check_compatible(
+                (*publisher).into(),
+                (*subscription).into(),
+                &mut compatibility,
+                reason.as_mut_ptr(),
+                reason.len(),
+            )
+            .ok_for("rmw_qos_profile_check_compatible")?;
+        }
+        // SAFETY: The buffer is nul-terminated, see above.
+        let reason = unsafe { CStr::from_ptr(reason.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+        Ok(match compatibility {
+            rmw_qos_compatibility_type_t::RMW_QOS_COMPATIBILITY_OK => QoSCompatibility::Ok,
+            rmw_qos_compatibility_type_t::RMW_QOS_COMPATIBILITY_WARNING => {
+                QoSCompatibility::Warning(reason)
+            }
+            rmw_qos_compatibility_type_t::RMW_QOS_COMPATIBILITY_ERROR => {
+                QoSCompatibility::Error(reason)
+            }
+        })
+    }
+
     /// Sets the QoS profile history to [QoSHistoryPolicy::KeepLast] with the specified depth.
     pub fn keep_last(mut self, depth: u32) -> Self {
         self.history = QoSHistoryPolicy::KeepLast { depth };
@@ -260,6 +391,60 @@ impl QoSProfile {
         self.lifespan = QoSDuration::Custom(lifespan);
         self
     }
+
+    /// Returns the deadline as a `Duration`, or `None` if it is the system default or infinite.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::QoSProfile;
+    /// # use std::time::Duration;
+    /// let qos = QoSProfile::default().deadline(Duration::from_millis(100));
+    /// assert_eq!(qos.get_deadline(), Some(Duration::from_millis(100)));
+    /// assert_eq!(QoSProfile::default().get_deadline(), None);
+    /// ```
+    pub fn get_deadline(&self) -> Option<Duration> {
+        self.deadline.as_duration()
+    }
+
+    /// Returns the liveliness lease duration as a `Duration`, or `None` if it is the system
+    /// default or infinite.
+    pub fn get_liveliness_lease_duration(&self) -> Option<Duration> {
+        self.liveliness_lease_duration.as_duration()
+    }
+
+    /// Returns the lifespan as a `Duration`, or `None` if it is the system default or infinite.
+    pub fn get_lifespan(&self) -> Option<Duration> {
+        self.lifespan.as_duration()
+    }
+
+    /// Sets the QoS profile liveliness policy.
+    pub fn liveliness(mut self, liveliness: QoSLivelinessPolicy) -> Self {
+        self.liveliness = liveliness;
+        self
+    }
+
+    /// Sets whether the QoS profile circumvents ROS specific namespacing conventions.
+    ///
+    /// See [`QoSProfile::avoid_ros_namespace_conventions`].
+    pub fn avoid_ros_namespace_conventions(
+        mut self,
+        avoid_ros_namespace_conventions: bool,
+    ) -> Self {
+        self.avoid_ros_namespace_conventions = avoid_ros_namespace_conventions;
+        self
+    }
+}
+
+/// Checks whether a publisher with the `publisher` profile and a subscription with the
+/// `subscription` profile can communicate with each other.
+///
+/// This is the same as [`QoSProfile::check_compatible()`], and can be used e.g. by tooling to
+/// check the profiles of a whole graph before creating any endpoints.
+pub fn qos_check_compatible(
+    publisher: &QoSProfile,
+    subscription: &QoSProfile,
+) -> Result<QoSCompatibility, RclrsError> {
+    QoSProfile::check_compatible(publisher, subscription)
 }
 
 impl From<QoSHistoryPolicy> for rmw_qos_history_policy_t {
@@ -324,6 +509,54 @@ impl From<QoSLivelinessPolicy> for rmw_qos_liveliness_policy_t {
     }
 }
 
+impl From<rmw_qos_reliability_policy_t> for QoSReliabilityPolicy {
+    fn from(policy: rmw_qos_reliability_policy_t) -> Self {
+        match policy {
+            rmw_qos_reliability_policy_t::RMW_QOS_POLICY_RELIABILITY_RELIABLE => Self::Reliable,
+            rmw_qos_reliability_policy_t::RMW_QOS_POLICY_RELIABILITY_BEST_EFFORT => {
+                Self::BestEffort
+            }
+            _ => Self::SystemDefault,
+        }
+    }
+}
+
+impl From<rmw_qos_durability_policy_t> for QoSDurabilityPolicy {
+    fn from(policy: rmw_qos_durability_policy_t) -> Self {
+        match policy {
+            rmw_qos_durability_policy_t::RMW_QOS_POLICY_DURABILITY_TRANSIENT_LOCAL => {
+                Self::TransientLocal
+            }
+            rmw_qos_durability_policy_t::RMW_QOS_POLICY_DURABILITY_VOLATILE => Self::Volatile,
+            _ => Self::SystemDefault,
+        }
+    }
+}
+
+impl From<rmw_qos_liveliness_policy_t> for QoSLivelinessPolicy {
+    fn from(policy: rmw_qos_liveliness_policy_t) -> Self {
+        match policy {
+            rmw_qos_liveliness_policy_t::RMW_QOS_POLICY_LIVELINESS_AUTOMATIC => Self::Automatic,
+            rmw_qos_liveliness_policy_t::RMW_QOS_POLICY_LIVELINESS_MANUAL_BY_TOPIC => {
+                Self::ManualByTopic
+            }
+            _ => Self::SystemDefault,
+        }
+    }
+}
+
+impl From<rmw_time_t> for QoSDuration {
+    fn from(time: rmw_time_t) -> Self {
+        match (time.sec, time.nsec) {
+            // See RMW_DURATION_DEFAULT
+            (0, 0) => Self::SystemDefault,
+            // See RMW_DURATION_INFINITE
+            (9223372036, 854775807) => Self::Infinite,
+            (sec, nsec) => Self::Custom(Duration::new(sec, nsec as u32)),
+        }
+    }
+}
+
 impl From<QoSDuration> for rmw_time_t {
     fn from(duration: QoSDuration) -> Self {
         match duration {
@@ -420,7 +653,7 @@ pub const QOS_PROFILE_SERVICES_DEFAULT: QoSProfile = QoSProfile {
 ///
 /// [1]: https://github.com/ros2/rmw/blob/master/rmw/include/rmw/qos_profiles.h
 pub const QOS_PROFILE_PARAMETER_EVENTS: QoSProfile = QoSProfile {
-    history: QoSHistoryPolicy::KeepAll,
+    history: QoSHistoryPolicy::KeepLast { depth: 1000 },
     reliability: QoSReliabilityPolicy::Reliable,
     durability: QoSDurabilityPolicy::Volatile,
     deadline: QoSDuration::SystemDefault,
@@ -443,3 +676,267 @@ pub const QOS_PROFILE_SYSTEM_DEFAULT: QoSProfile = QoSProfile {
     liveliness_lease_duration: QoSDuration::SystemDefault,
     avoid_ros_namespace_conventions: false,
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn setters_only_change_their_field() {
+        let base = QoSProfile::default();
+        let dt = Duration::from_millis(100);
+
+        assert_eq!(
+            base.keep_last(42),
+            QoSProfile {
+                history: QoSHistoryPolicy::KeepLast { depth: 42 },
+                ..base
+            }
+        );
+        assert_eq!(
+            base.keep_all(),
+            QoSProfile {
+                history: QoSHistoryPolicy::KeepAll,
+                ..base
+            }
+        );
+        assert_eq!(
+            base.best_effort(),
+            QoSProfile {
+                reliability: QoSReliabilityPolicy::BestEffort,
+                ..base
+            }
+        );
+        assert_eq!(
+            base.best_effort().reliable(),
+            QoSProfile {
+                reliability: QoSReliabilityPolicy::Reliable,
+                ..base
+            }
+        );
+        assert_eq!(
+            base.transient_local(),
+            QoSProfile {
+                durability: QoSDurabilityPolicy::TransientLocal,
+                ..base
+            }
+        );
+        assert_eq!(
+            base.transient_local().volatile(),
+            QoSProfile {
+                durability: QoSDurabilityPolicy::Volatile,
+                ..base
+            }
+        );
+        assert_eq!(
+            base.deadline(dt),
+            QoSProfile {
+                deadline: QoSDuration::Custom(dt),
+                ..base
+            }
+        );
+        assert_eq!(
+            base.lifespan(dt),
+            QoSProfile {
+                lifespan: QoSDuration::Custom(dt),
+                ..base
+            }
+        );
+        assert_eq!(
+            base.liveliness_lease_duration(dt),
+            QoSProfile {
+                liveliness_lease_duration: QoSDuration::Custom(dt),
+                ..base
+            }
+        );
+        assert_eq!(
+            base.liveliness(QoSLivelinessPolicy::ManualByTopic),
+            QoSProfile {
+                liveliness: QoSLivelinessPolicy::ManualByTopic,
+                ..base
+            }
+        );
+        assert_eq!(
+            base.avoid_ros_namespace_conventions(true),
+            QoSProfile {
+                avoid_ros_namespace_conventions: true,
+                ..base
+            }
+        );
+    }
+
+    #[test]
+    fn transient_local_latch() -> Result<(), RclrsError> {
+        let latch = QoSProfile::transient_local_latch();
+        assert_eq!(latch.history, QoSHistoryPolicy::KeepLast { depth: 1 });
+        assert_eq!(latch.durability, QoSDurabilityPolicy::TransientLocal);
+        assert_eq!(latch.reliability, QoSReliabilityPolicy::Reliable);
+        assert_eq!(
+            QoSProfile::check_compatible(&latch, &latch)?,
+            QoSCompatibility::Ok
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_compatible() -> Result<(), RclrsError> {
+        let qos = QoSProfile::default();
+        assert_eq!(
+            QoSProfile::check_compatible(&qos, &qos)?,
+            QoSCompatibility::Ok
+        );
+
+        // A best-effort publisher can't satisfy a reliable subscription
+        let result = QoSProfile::check_compatible(&qos.best_effort(), &qos.reliable())?;
+        assert!(matches!(result, QoSCompatibility::Error(reason) if !reason.is_empty()));
+        // But the opposite works
+        assert_eq!(
+            QoSProfile::check_compatible(&qos.reliable(), &qos.best_effort())?,
+            QoSCompatibility::Ok
+        );
+
+        // A volatile publisher can't satisfy a transient local subscription
+        let result = QoSProfile::check_compatible(&qos.volatile(), &qos.transient_local())?;
+        assert!(matches!(result, QoSCompatibility::Error(reason) if !reason.is_empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn qos_check_compatible_matches_associated_function() -> Result<(), RclrsError> {
+        let qos = QoSProfile::default();
+        for (publisher, subscription) in [
+            (qos, qos),
+            (qos.best_effort(), qos.reliable()),
+            (qos.volatile(), qos.transient_local()),
+        ] {
+            assert_eq!(
+                qos_check_compatible(&publisher, &subscription)?,
+                QoSProfile::check_compatible(&publisher, &subscription)?
+            );
+        }
+        Ok(())
+    }
+
+    fn assert_rmw_profiles_eq(actual: &rmw_qos_profile_t, expected: &rmw_qos_profile_t) {
+        let duration = |time: &rmw_time_t| (time.sec, time.nsec);
+        assert_eq!(actual.history, expected.history);
+        assert_eq!(actual.depth, expected.depth);
+        assert_eq!(actual.reliability, expected.reliability);
+        assert_eq!(actual.durability, expected.durability);
+        assert_eq!(duration(&actual.deadline), duration(&expected.deadline));
+        assert_eq!(duration(&actual.lifespan), duration(&expected.lifespan));
+        assert_eq!(actual.liveliness, expected.liveliness);
+        assert_eq!(
+            duration(&actual.liveliness_lease_duration),
+            duration(&expected.liveliness_lease_duration)
+        );
+        assert_eq!(
+            actual.avoid_ros_namespace_conventions,
+            expected.avoid_ros_namespace_conventions
+        );
+    }
+
+    #[test]
+    fn presets_match_rmw() {
+        // SAFETY: No preconditions for these functions.
+        let presets = unsafe {
+            [
+                (QOS_PROFILE_SENSOR_DATA, rclrs_rmw_qos_profile_sensor_data()),
+                (QOS_PROFILE_PARAMETERS, rclrs_rmw_qos_profile_parameters()),
+                (QOS_PROFILE_DEFAULT, rclrs_rmw_qos_profile_default()),
+                (
+                    QOS_PROFILE_SERVICES_DEFAULT,
+                    rclrs_rmw_qos_profile_services_default(),
+                ),
+                (
+                    QOS_PROFILE_PARAMETER_EVENTS,
+                    rclrs_rmw_qos_profile_parameter_events(),
+                ),
+                (
+                    QOS_PROFILE_SYSTEM_DEFAULT,
+                    rclrs_rmw_qos_profile_system_default(),
+                ),
+            ]
+        };
+        for (preset, rmw_preset) in presets {
+            assert_rmw_profiles_eq(&preset.into(), &rmw_preset);
+            assert_eq!(QoSProfile::from(rmw_preset), preset);
+        }
+
+        // The associated constructors return the same presets
+        assert_eq!(QoSProfile::sensor_data(), QOS_PROFILE_SENSOR_DATA);
+        assert_eq!(QoSProfile::parameters(), QOS_PROFILE_PARAMETERS);
+        assert_eq!(QoSProfile::default(), QOS_PROFILE_DEFAULT);
+        assert_eq!(QoSProfile::services_default(), QOS_PROFILE_SERVICES_DEFAULT);
+        assert_eq!(QoSProfile::parameter_events(), QOS_PROFILE_PARAMETER_EVENTS);
+        assert_eq!(QoSProfile::system_default(), QOS_PROFILE_SYSTEM_DEFAULT);
+    }
+
+    #[test]
+    fn setters_map_to_rmw_fields() {
+        let qos = QoSProfile::default()
+            .reliable()
+            .keep_last(10)
+            .transient_local()
+            .deadline(Duration::from_millis(100));
+        let rmw_qos: rmw_qos_profile_t = qos.into();
+        assert_eq!(
+            rmw_qos.history,
+            rmw_qos_history_policy_t::RMW_QOS_POLICY_HISTORY_KEEP_LAST
+        );
+        assert_eq!(rmw_qos.depth, 10);
+        assert_eq!(
+            rmw_qos.reliability,
+            rmw_qos_reliability_policy_t::RMW_QOS_POLICY_RELIABILITY_RELIABLE
+        );
+        assert_eq!(
+            rmw_qos.durability,
+            rmw_qos_durability_policy_t::RMW_QOS_POLICY_DURABILITY_TRANSIENT_LOCAL
+        );
+        assert_eq!(rmw_qos.deadline.sec, 0);
+        assert_eq!(rmw_qos.deadline.nsec, 100_000_000);
+    }
+
+    #[test]
+    fn duration_getters_round_trip() {
+        let dt = Duration::from_millis(100);
+        let qos = QoSProfile::default()
+            .deadline(dt)
+            .lifespan(Duration::from_secs(2))
+            .liveliness_lease_duration(Duration::new(1, 500));
+        assert_eq!(qos.get_deadline(), Some(dt));
+        assert_eq!(qos.get_lifespan(), Some(Duration::from_secs(2)));
+        assert_eq!(
+            qos.get_liveliness_lease_duration(),
+            Some(Duration::new(1, 500))
+        );
+
+        // Through the raw sec/nsec representation and back
+        let rmw_qos: rmw_qos_profile_t = qos.into();
+        assert_eq!(
+            (rmw_qos.deadline.sec, rmw_qos.deadline.nsec),
+            (0, 100_000_000)
+        );
+        assert_eq!((rmw_qos.lifespan.sec, rmw_qos.lifespan.nsec), (2, 0));
+        assert_eq!(
+            (
+                rmw_qos.liveliness_lease_duration.sec,
+                rmw_qos.liveliness_lease_duration.nsec
+            ),
+            (1, 500)
+        );
+        let qos_from_rmw = QoSProfile::from(rmw_qos);
+        assert_eq!(qos_from_rmw, qos);
+        assert_eq!(qos_from_rmw.get_deadline(), Some(dt));
+
+        // The sentinel values have no duration
+        let qos = QoSProfile {
+            deadline: QoSDuration::Infinite,
+            ..QoSProfile::default()
+        };
+        assert_eq!(qos.get_deadline(), None);
+        assert_eq!(qos.get_lifespan(), None);
+        let rmw_qos: rmw_qos_profile_t = qos.into();
+        assert_eq!(QoSProfile::from(rmw_qos).deadline, QoSDuration::Infinite);
+    }
+}
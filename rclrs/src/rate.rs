@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use crate::{Clock, RclReturnCode, RclrsError, Time};
+
+/// A helper for running a loop at a fixed frequency.
+///
+/// Each call to [`Rate::sleep()`] sleeps until the next tick of the rate, so that the time spent
+/// in the loop body is accounted for. The ticks are measured with the given [`Clock`], so a rate
+/// that uses a clock driven by simulated time runs at the simulated frequency.
+///
+/// # Example
+/// ```
+/// # use rclrs::{Clock, Rate, RclrsError};
+/// # use std::time::Duration;
+/// let mut rate = Rate::new(Clock::steady(), Duration::from_millis(10));
+/// for _ in 0..3 {
+///     // Do some work
+///     if !rate.sleep()? {
+///         println!("The loop took longer than the period");
+///     }
+/// }
+/// # Ok::<(), RclrsError>(())
+/// ```
+pub struct Rate {
+    clock: Clock,
+    period: Duration,
+    // The time of the last tick in nanoseconds, on the time scale of the clock.
+    last_tick: i64,
+}
+
+impl Rate {
+    /// Creates a rate that ticks every `period`, starting now.
+    ///
+    /// Sleeping is interrupted when the context of the clock is shut down, see
+    /// [`Clock::sleep_until()`].
+    pub fn new(clock: Clock, period: Duration) -> Self {
+        let last_tick = clock.now().nsec;
+        Self {
+            clock,
+            period,
+            last_tick,
+        }
+    }
+
+    /// Creates a rate that ticks `frequency` times per second, starting now.
+    ///
+    /// Returns an [`InvalidArgument`][1] error if the frequency is not a positive, finite number.
+    ///
+    /// [1]: crate::RclReturnCode::InvalidArgument
+    pub fn from_frequency(clock: Clock, frequency: f64) -> Result<Self, RclrsError> {
+        if !(frequency.is_finite() && frequency > 0.0) {
+            return Err(RclrsError::RclError {
+                code: RclReturnCode::InvalidArgument,
+                operation: None,
+                msg: None,
+            });
+        }
+        Ok(Self::new(clock, Duration::from_secs_f64(1.0 / frequency)))
+    }
+
+    /// Returns the period between two ticks.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Restarts the rate, so that the next tick is one period from now.
+    pub fn reset(&mut self) {
+        self.last_tick = self.clock.now().nsec;
+    }
+
+    /// Sleeps until the next tick.
+    ///
+    /// Returns `true` if the tick was reached in time. If the time since the last tick was longer
+    /// than the period, i.e. the loop overran, this returns `false` immediately. When the loop
+    /// falls behind by more than a whole period, the missed ticks are skipped instead of being
+    /// caught up on.
+    ///
+    /// If the time of the clock jumps backward, e.g. because a rosbag is replayed in a loop, the
+    /// rate is restarted.
+    pub fn sleep(&mut self) -> Result<bool, RclrsError> {
+        let now = self.clock.now();
+        let period_ns = i64::try_from(self.period.as_nanos()).unwrap_or(i64::MAX);
+        if now.nsec < self.last_tick {
+            self.last_tick = now.nsec;
+        }
+        let next_tick = self.last_tick.saturating_add(period_ns);
+        if now.nsec > next_tick {
+            if now.nsec - next_tick > period_ns {
+                self.last_tick = now.nsec;
+            } else {
+                self.last_tick = next_tick;
+            }
+            return Ok(false);
+        }
+        self.last_tick = next_tick;
+        let deadline = Time {
+            nsec: next_tick,
+            clock: now.clock,
+        };
+        self.clock.sleep_until(deadline)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn traits() {
+        use crate::test_helpers::*;
+
+        assert_send::<Rate>();
+        assert_sync::<Rate>();
+    }
+
+    #[test]
+    fn test_rate_keeps_period() -> Result<(), RclrsError> {
+        let mut rate = Rate::new(Clock::steady(), Duration::from_millis(50));
+        let start = Instant::now();
+        for _ in 0..4 {
+            // The time spent in the loop body is subtracted from the sleep
+            std::thread::sleep(Duration::from_millis(20));
+            assert!(rate.sleep()?);
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(200));
+        assert!(elapsed < Duration::from_millis(500));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rate_overrun() -> Result<(), RclrsError> {
+        let (clock, source) = Clock::with_source();
+        source.set_ros_time_override(1_000_000_000);
+        let mut rate = Rate::new(clock, Duration::from_secs(1));
+
+        // The loop body took longer than a period, so sleep() returns immediately
+        source.set_ros_time_override(2_500_000_000);
+        let start = Instant::now();
+        assert!(!rate.sleep()?);
+        assert!(start.elapsed() < Duration::from_millis(500));
+
+        // The rate keeps its schedule, and the next tick is at 3 s
+        let advance_time = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            source.set_ros_time_override(3_000_000_000);
+            source
+        });
+        assert!(rate.sleep()?);
+        let source = advance_time.join().unwrap();
+
+        // Missed ticks are skipped when falling behind by more than a period
+        source.set_ros_time_override(10_000_000_000);
+        assert!(!rate.sleep()?);
+        assert_eq!(rate.last_tick, 10_000_000_000);
+        Ok(())
+    }
+}
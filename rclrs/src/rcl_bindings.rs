@@ -13,6 +13,14 @@
 
 cfg_if::cfg_if! {
     if #[cfg(feature="generate_docs")] {
+        #[repr(C)]
+        #[derive(Debug)]
+        pub struct rcl_action_client_t;
+
+        #[repr(C)]
+        #[derive(Debug)]
+        pub struct rcl_action_server_t;
+
         #[repr(C)]
         #[derive(Debug)]
         pub struct rcl_allocator_t;
@@ -37,10 +45,18 @@ cfg_if::cfg_if! {
         #[derive(Debug)]
         pub struct rcl_context_t;
 
+        #[repr(C)]
+        #[derive(Debug)]
+        pub struct rcl_event_t;
+
         #[repr(C)]
         #[derive(Debug)]
         pub struct rcl_guard_condition_t;
 
+        #[repr(C)]
+        #[derive(Debug)]
+        pub struct rcl_lifecycle_state_machine_t;
+
         #[repr(C)]
         #[derive(Debug)]
         pub struct rcl_names_and_types_t;
@@ -69,6 +85,10 @@ cfg_if::cfg_if! {
         #[derive(Debug)]
         pub struct rcl_ret_t;
 
+        #[repr(C)]
+        #[derive(Debug)]
+        pub struct rcl_serialized_message_t;
+
         #[repr(C)]
         #[derive(Debug)]
         pub struct rcl_service_t;
@@ -77,6 +97,14 @@ cfg_if::cfg_if! {
         #[derive(Debug)]
         pub struct rcl_subscription_t;
 
+        #[repr(C)]
+        #[derive(Debug)]
+        pub struct rcl_time_jump_t;
+
+        #[repr(C)]
+        #[derive(Debug)]
+        pub struct rcl_timer_t;
+
         #[repr(C)]
         #[derive(Debug)]
         pub struct rcl_topic_endpoint_info_array_t;
@@ -125,6 +153,10 @@ cfg_if::cfg_if! {
         #[derive(Debug)]
         pub struct rmw_request_id_t;
 
+        #[repr(C)]
+        #[derive(Debug)]
+        pub struct rmw_service_info_t;
+
         #[repr(C)]
         #[derive(Debug)]
         pub struct rmw_time_t;
@@ -1,9 +1,27 @@
 #include <rcl/graph.h>
 #include <rcl/rcl.h>
+#include <rcl/validate_topic_name.h>
+#include <rcl_action/rcl_action.h>
+#include <rcl_lifecycle/rcl_lifecycle.h>
 #include <rcl_yaml_param_parser/parser.h>
 #include <rcutils/error_handling.h>
+#include <rcutils/logging.h>
+#include <rcutils/types/char_array.h>
+#include <rmw/qos_profiles.h>
+#include <rmw/rmw.h>
 #include <rmw/types.h>
+#include <rmw/validate_full_topic_name.h>
+#include <rmw/validate_namespace.h>
+#include <rmw/validate_node_name.h>
 #include <rosidl_typesupport_introspection_c/field_types.h>
 #include <rosidl_typesupport_introspection_c/message_introspection.h>
 
 const size_t rmw_gid_storage_size_constant = RMW_GID_STORAGE_SIZE;
+
+// Defined in rmw_qos_profiles.c
+rmw_qos_profile_t rclrs_rmw_qos_profile_sensor_data(void);
+rmw_qos_profile_t rclrs_rmw_qos_profile_parameters(void);
+rmw_qos_profile_t rclrs_rmw_qos_profile_default(void);
+rmw_qos_profile_t rclrs_rmw_qos_profile_services_default(void);
+rmw_qos_profile_t rclrs_rmw_qos_profile_parameter_events(void);
+rmw_qos_profile_t rclrs_rmw_qos_profile_system_default(void);
@@ -0,0 +1,34 @@
+#include <rmw/qos_profiles.h>
+
+// The rmw_qos_profile_* presets are static variables in the rmw headers, so they have no
+// symbol that the Rust side could link against. These functions return copies of them.
+
+rmw_qos_profile_t rclrs_rmw_qos_profile_sensor_data(void)
+{
+  return rmw_qos_profile_sensor_data;
+}
+
+rmw_qos_profile_t rclrs_rmw_qos_profile_parameters(void)
+{
+  return rmw_qos_profile_parameters;
+}
+
+rmw_qos_profile_t rclrs_rmw_qos_profile_default(void)
+{
+  return rmw_qos_profile_default;
+}
+
+rmw_qos_profile_t rclrs_rmw_qos_profile_services_default(void)
+{
+  return rmw_qos_profile_services_default;
+}
+
+rmw_qos_profile_t rclrs_rmw_qos_profile_parameter_events(void)
+{
+  return rmw_qos_profile_parameter_events;
+}
+
+rmw_qos_profile_t rclrs_rmw_qos_profile_system_default(void)
+{
+  return rmw_qos_profile_system_default;
+}
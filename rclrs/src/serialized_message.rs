@@ -0,0 +1,174 @@
+use std::{borrow::Cow, os::raw::c_void};
+
+use rosidl_runtime_rs::{Message, RmwMessage};
+
+use crate::{error::ToResult, rcl_bindings::*, RclrsError};
+
+/// A message in its serialized (CDR) form.
+///
+/// The bytes are owned by this struct and freed when it is dropped.
+///
+/// Serialized messages can be taken from a subscription without deserializing them, see
+/// [`Subscription::take_serialized()`][1].
+///
+/// [1]: crate::Subscription::take_serialized
+pub struct SerializedMessage {
+    pub(crate) rcl_serialized_message: rcl_serialized_message_t,
+}
+
+// SAFETY: The serialized message only contains a buffer and an allocator, neither of which
+// is tied to a particular thread.
+unsafe impl Send for SerializedMessage {}
+
+// SAFETY: The buffer is only mutated through `&mut self`.
+unsafe impl Sync for SerializedMessage {}
+
+impl Drop for SerializedMessage {
+    fn drop(&mut self) {
+        // SAFETY: The serialized message was initialized in the constructor and is not
+        // used after this.
+        unsafe {
+            rmw_serialized_message_fini(&mut self.rcl_serialized_message);
+        }
+    }
+}
+
+impl SerializedMessage {
+    /// Creates an empty serialized message.
+    pub fn new() -> Result<Self, RclrsError> {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty serialized message with a buffer of `capacity` bytes.
+    ///
+    /// The buffer grows as needed when a message is taken into it.
+    pub fn with_capacity(capacity: usize) -> Result<Self, RclrsError> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut rcl_serialized_message = unsafe { rmw_get_zero_initialized_serialized_message() };
+        // SAFETY: The serialized message is zero-initialized as expected by this function.
+        unsafe {
+            let allocator = rcutils_get_default_allocator();
+            rmw_serialized_message_init(&mut rcl_serialized_message, capacity, &allocator)
+                .ok_for("rmw_serialized_message_init")?;
+        }
+        Ok(Self {
+            rcl_serialized_message,
+        })
+    }
+
+    /// Returns the serialized bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        if self.rcl_serialized_message.buffer.is_null() {
+            return &[];
+        }
+        // SAFETY: The buffer is non-null and holds `buffer_length` initialized bytes.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.rcl_serialized_message.buffer,
+                self.rcl_serialized_message.buffer_length,
+            )
+        }
+    }
+
+    /// Returns the number of serialized bytes.
+    pub fn len(&self) -> usize {
+        self.rcl_serialized_message.buffer_length
+    }
+
+    /// Returns `true` if the message contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bytes the buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.rcl_serialized_message.buffer_capacity
+    }
+}
+
+/// Serializes a message into its CDR form.
+///
+/// This is the same serialization that is used when publishing the message, so the result can
+/// e.g. be stored and later be turned back into a message with [`deserialize_message()`].
+pub fn serialize_message<T: Message>(message: &T) -> Result<SerializedMessage, RclrsError> {
+    let rmw_message = T::into_rmw_message(Cow::Borrowed(message));
+    let type_support =
+        <T as Message>::RmwMsg::get_type_support() as *const rosidl_message_type_support_t;
+    let mut serialized_message = SerializedMessage::new()?;
+    // SAFETY: The type support matches the message type, and the serialized message is
+    // initialized. Its buffer is grown as needed by this function.
+    unsafe {
+        rmw_serialize(
+            rmw_message.as_ref() as *const <T as Message>::RmwMsg as *const c_void,
+            type_support,
+            &mut serialized_message.rcl_serialized_message,
+        )
+        .ok_for("rmw_serialize")?;
+    }
+    Ok(serialized_message)
+}
+
+/// Deserializes a message from its CDR form.
+///
+/// This is the inverse of [`serialize_message()`]. An error is returned if the bytes are not a
+/// valid serialization of a `T`.
+pub fn deserialize_message<T: Message>(
+    serialized_message: &SerializedMessage,
+) -> Result<T, RclrsError> {
+    let type_support =
+        <T as Message>::RmwMsg::get_type_support() as *const rosidl_message_type_support_t;
+    let mut rmw_message = <T as Message>::RmwMsg::default();
+    // SAFETY: The type support matches the message type, and the message is initialized.
+    unsafe {
+        rmw_deserialize(
+            &serialized_message.rcl_serialized_message,
+            type_support,
+            &mut rmw_message as *mut <T as Message>::RmwMsg as *mut c_void,
+        )
+        .ok_for("rmw_deserialize")?;
+    }
+    Ok(T::from_rmw_message(rmw_message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn traits() {
+        assert_send::<SerializedMessage>();
+        assert_sync::<SerializedMessage>();
+    }
+
+    #[test]
+    fn empty_serialized_message() -> Result<(), RclrsError> {
+        let message = SerializedMessage::with_capacity(16)?;
+        assert!(message.is_empty());
+        assert_eq!(message.as_bytes(), &[] as &[u8]);
+        assert_eq!(message.capacity(), 16);
+        Ok(())
+    }
+
+    #[test]
+    fn serialization_round_trip() -> Result<(), RclrsError> {
+        use test_msgs::msg;
+
+        let mut message = msg::Arrays::default();
+        message.int32_values = [1, -2, 3];
+        message.string_values = [
+            String::from("first"),
+            String::new(),
+            String::from("third string"),
+        ];
+        message.basic_types_values[1].int32_value = 42;
+        message.basic_types_values[2].float64_value = 2.5;
+        message.basic_types_values[2].bool_value = true;
+
+        let serialized_message = serialize_message(&message)?;
+        assert!(!serialized_message.is_empty());
+        let deserialized_message: msg::Arrays = deserialize_message(&serialized_message)?;
+        assert_eq!(deserialized_message, message);
+        Ok(())
+    }
+}
@@ -9,9 +9,14 @@ use rosidl_runtime_rs::Message;
 use crate::{
     error::{RclReturnCode, ToResult},
     rcl_bindings::*,
-    MessageCow, NodeHandle, RclrsError, ENTITY_LIFECYCLE_MUTEX,
+    validate_topic_name, CallbackGroup, MessageCow, NodeHandle, RclrsError, ENTITY_LIFECYCLE_MUTEX,
 };
 
+mod options;
+mod service_info;
+pub use options::*;
+pub use service_info::*;
+
 // SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
 // they are running in. Therefore, this type can be safely sent to another thread.
 unsafe impl Send for rcl_service_t {}
@@ -54,10 +59,17 @@ pub trait ServiceBase: Send + Sync {
     fn handle(&self) -> &ServiceHandle;
     /// Tries to take a new request and run the callback with it.
     fn execute(&self) -> Result<(), RclrsError>;
+    /// Returns the callback group of the service, if one was assigned.
+    fn callback_group(&self) -> Option<&Arc<CallbackGroup>>;
 }
 
-type ServiceCallback<Request, Response> =
-    Box<dyn Fn(&rmw_request_id_t, Request) -> Response + 'static + Send>;
+/// The callback of a [`Service`].
+pub enum ServiceCallback<Request, Response> {
+    /// A callback that receives the request ID and the request.
+    Basic(Box<dyn Fn(&rmw_request_id_t, Request) -> Response + 'static + Send>),
+    /// A callback that receives the request and additional information about it.
+    WithInfo(Box<dyn Fn(Request, ServiceInfo) -> Response + 'static + Send>),
+}
 
 /// Main class responsible for responding to requests sent by ROS clients.
 ///
@@ -73,6 +85,7 @@ where
     pub(crate) handle: Arc<ServiceHandle>,
     /// The callback function that runs when a request was received.
     pub callback: Mutex<ServiceCallback<T::Request, T::Response>>,
+    callback_group: Option<Arc<CallbackGroup>>,
 }
 
 impl<T> Service<T>
@@ -80,16 +93,16 @@ where
     T: rosidl_runtime_rs::Service,
 {
     /// Creates a new service.
-    pub(crate) fn new<F>(
+    pub(crate) fn new(
         node_handle: Arc<NodeHandle>,
         topic: &str,
-        callback: F,
+        options: ServiceOptions,
+        callback: ServiceCallback<T::Request, T::Response>,
     ) -> Result<Self, RclrsError>
     // This uses pub(crate) visibility to avoid instantiating this struct outside
     // [`Node::create_service`], see the struct's documentation for the rationale
     where
         T: rosidl_runtime_rs::Service,
-        F: Fn(&rmw_request_id_t, T::Request) -> T::Response + 'static + Send,
     {
         // SAFETY: Getting a zero-initialized value is always safe.
         let mut rcl_service = unsafe { rcl_get_zero_initialized_service() };
@@ -99,9 +112,11 @@ where
             err,
             s: topic.into(),
         })?;
+        validate_topic_name(topic)?;
 
         // SAFETY: No preconditions for this function.
-        let service_options = unsafe { rcl_service_get_default_options() };
+        let mut service_options = unsafe { rcl_service_get_default_options() };
+        service_options.qos = options.qos.into();
 
         {
             let rcl_node = node_handle.rcl_node.lock().unwrap();
@@ -121,7 +136,7 @@ where
                     topic_c_string.as_ptr(),
                     &service_options as *const _,
                 )
-                .ok()?;
+                .ok_for("rcl_service_init")?;
             }
         }
 
@@ -133,7 +148,8 @@ where
 
         Ok(Self {
             handle,
-            callback: Mutex::new(Box::new(callback)),
+            callback: Mutex::new(callback),
+            callback_group: options.callback_group,
         })
     }
 
@@ -176,9 +192,48 @@ where
                 &mut request_out as *mut RmwMsg<T> as *mut _,
             )
         }
-        .ok()?;
+        .ok_for("rcl_take_request")?;
         Ok((T::Request::from_rmw_message(request_out), request_id_out))
     }
+
+    /// Fetches a new request, together with additional information about it.
+    ///
+    /// When there is no new message, this will return a
+    /// [`ServiceTakeFailed`][1].
+    ///
+    /// [1]: crate::RclrsError
+    pub fn take_request_with_info(&self) -> Result<(T::Request, ServiceInfo), RclrsError> {
+        let (request, rmw_service_info) = self.take_request_with_rmw_info()?;
+        Ok((
+            request,
+            ServiceInfo::from_rmw_service_info(&rmw_service_info),
+        ))
+    }
+
+    fn take_request_with_rmw_info(&self) -> Result<(T::Request, rmw_service_info_t), RclrsError> {
+        let mut service_info_out = rmw_service_info_t {
+            source_timestamp: 0,
+            received_timestamp: 0,
+            request_id: rmw_request_id_t {
+                writer_guid: [0; 16],
+                sequence_number: 0,
+            },
+        };
+        type RmwMsg<T> =
+            <<T as rosidl_runtime_rs::Service>::Request as rosidl_runtime_rs::Message>::RmwMsg;
+        let mut request_out = RmwMsg::<T>::default();
+        let handle = &*self.handle.lock();
+        unsafe {
+            // SAFETY: The three pointers are valid/initialized
+            rcl_take_request_with_info(
+                handle,
+                &mut service_info_out,
+                &mut request_out as *mut RmwMsg<T> as *mut _,
+            )
+        }
+        .ok_for("rcl_take_request_with_info")?;
+        Ok((T::Request::from_rmw_message(request_out), service_info_out))
+    }
 }
 
 impl<T> ServiceBase for Service<T>
@@ -189,9 +244,13 @@ where
         &self.handle
     }
 
+    fn callback_group(&self) -> Option<&Arc<CallbackGroup>> {
+        self.callback_group.as_ref()
+    }
+
     fn execute(&self) -> Result<(), RclrsError> {
-        let (req, mut req_id) = match self.take_request() {
-            Ok((req, req_id)) => (req, req_id),
+        let (req, rmw_service_info) = match self.take_request_with_rmw_info() {
+            Ok((req, rmw_service_info)) => (req, rmw_service_info),
             Err(RclrsError::RclError {
                 code: RclReturnCode::ServiceTakeFailed,
                 ..
@@ -202,7 +261,13 @@ where
             }
             Err(e) => return Err(e),
         };
-        let res = (*self.callback.lock().unwrap())(&req_id, req);
+        let res = match &*self.callback.lock().unwrap() {
+            ServiceCallback::Basic(callback) => callback(&rmw_service_info.request_id, req),
+            ServiceCallback::WithInfo(callback) => {
+                callback(req, ServiceInfo::from_rmw_service_info(&rmw_service_info))
+            }
+        };
+        let mut req_id = rmw_service_info.request_id;
         let rmw_message = <T::Response as Message>::into_rmw_message(res.into_cow());
         let handle = &*self.handle.lock();
         unsafe {
@@ -213,7 +278,7 @@ where
                 rmw_message.as_ref() as *const <T::Response as Message>::RmwMsg as *mut _,
             )
         }
-        .ok()
+        .ok_for("rcl_send_response")
     }
 }
 
@@ -266,4 +331,40 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_service_with_info() -> Result<(), RclrsError> {
+        use std::time::{Duration, Instant};
+        use test_msgs::srv;
+
+        let graph = construct_test_graph("test_service_with_info")?;
+        let received_info = Arc::new(Mutex::new(None));
+        let _service = {
+            let received_info = Arc::clone(&received_info);
+            graph.node1.create_service_with_info::<srv::Empty, _>(
+                "service_with_info",
+                move |_, info: ServiceInfo| {
+                    *received_info.lock().unwrap() = Some(info);
+                    srv::Empty_Response::default()
+                },
+            )?
+        };
+        let client = graph
+            .node2
+            .create_client::<srv::Empty>("service_with_info")?;
+        assert!(client.wait_for_service(Duration::from_secs(5))?);
+        client.async_send_request_with_callback(&srv::Empty_Request::default(), |_| {})?;
+
+        let start = Instant::now();
+        while received_info.lock().unwrap().is_none() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            ignore_timeout(crate::spin_once(
+                Arc::clone(&graph.node1),
+                Some(Duration::from_millis(100)),
+            ))?;
+        }
+        let info = received_info.lock().unwrap().take().unwrap();
+        assert!(info.received_timestamp.is_some());
+        Ok(())
+    }
 }
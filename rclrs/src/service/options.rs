@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use crate::{CallbackGroup, QoSProfile, QOS_PROFILE_SERVICES_DEFAULT};
+
+/// Options for creating a [`Service`][1].
+///
+/// Use this together with [`Node::create_service_with_options()`][2] to configure
+/// features beyond the defaults.
+///
+/// [1]: crate::Service
+/// [2]: crate::Node::create_service_with_options
+#[derive(Debug)]
+pub struct ServiceOptions {
+    /// The quality of service profile of the service.
+    ///
+    /// It applies to both the requests received by the service and the responses it sends.
+    pub qos: QoSProfile,
+    pub(crate) callback_group: Option<Arc<CallbackGroup>>,
+}
+
+impl Default for ServiceOptions {
+    fn default() -> Self {
+        Self::new(QOS_PROFILE_SERVICES_DEFAULT)
+    }
+}
+
+impl From<QoSProfile> for ServiceOptions {
+    fn from(qos: QoSProfile) -> Self {
+        Self::new(qos)
+    }
+}
+
+impl ServiceOptions {
+    /// Creates service options with the given quality of service profile.
+    pub fn new(qos: QoSProfile) -> Self {
+        Self {
+            qos,
+            callback_group: None,
+        }
+    }
+
+    /// Sets the quality of service profile.
+    pub fn qos(mut self, qos: QoSProfile) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Assigns the service to a [`CallbackGroup`].
+    ///
+    /// If no group is set, the service belongs to the default group of its node.
+    pub fn callback_group(mut self, callback_group: Arc<CallbackGroup>) -> Self {
+        self.callback_group = Some(callback_group);
+        self
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::SystemTime;
+
+use crate::{rcl_bindings::*, timestamp_to_system_time};
+
+/// Additional information about a request received by a [`Service`][1].
+///
+/// See [`Node::create_service_with_info()`][2].
+///
+/// [1]: crate::Service
+/// [2]: crate::Node::create_service_with_info
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceInfo {
+    /// Time when the request was sent by the client.
+    ///
+    /// As for [`MessageInfo::source_timestamp`][1], the exact point at which the timestamp is
+    /// taken depends on the RMW implementation.
+    ///
+    /// [1]: crate::MessageInfo::source_timestamp
+    pub source_timestamp: Option<SystemTime>,
+    /// Time when the request was received by the service.
+    ///
+    /// As for [`MessageInfo::received_timestamp`][1], the exact point at which the timestamp is
+    /// taken depends on the RMW implementation.
+    ///
+    /// [1]: crate::MessageInfo::received_timestamp
+    pub received_timestamp: Option<SystemTime>,
+    /// Sequence number of the request, set by the client.
+    ///
+    /// Together with the client GID, this uniquely identifies the request.
+    pub request_sequence_number: i64,
+    /// An identifier for the client that sent the request.
+    pub client_gid: [u8; 16],
+}
+
+impl ServiceInfo {
+    pub(crate) fn from_rmw_service_info(rmw_service_info: &rmw_service_info_t) -> Self {
+        Self {
+            source_timestamp: timestamp_to_system_time(rmw_service_info.source_timestamp),
+            received_timestamp: timestamp_to_system_time(rmw_service_info.received_timestamp),
+            request_sequence_number: rmw_service_info.request_id.sequence_number,
+            // The GUID is declared as either signed or unsigned bytes depending on the ROS
+            // distribution.
+            #[allow(clippy::unnecessary_cast)]
+            client_gid: rmw_service_info.request_id.writer_guid.map(|b| b as u8),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn from_rmw_service_info() {
+        let rmw_service_info = rmw_service_info_t {
+            source_timestamp: 1_000_000_000,
+            received_timestamp: 0,
+            request_id: rmw_request_id_t {
+                writer_guid: [1; 16],
+                sequence_number: 7,
+            },
+        };
+        let info = ServiceInfo::from_rmw_service_info(&rmw_service_info);
+        assert_eq!(
+            info.source_timestamp,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+        );
+        assert_eq!(info.received_timestamp, None);
+        assert_eq!(info.request_sequence_number, 7);
+        assert_eq!(info.client_gid, [1; 16]);
+    }
+}
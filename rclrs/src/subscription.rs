@@ -1,4 +1,5 @@
 use std::{
+    any::TypeId,
     ffi::{CStr, CString},
     marker::PhantomData,
     sync::{atomic::AtomicBool, Arc, Mutex, MutexGuard},
@@ -8,17 +9,24 @@ use rosidl_runtime_rs::{Message, RmwMessage};
 
 use crate::{
     error::{RclReturnCode, ToResult},
-    qos::QoSProfile,
+    network_flow_endpoint::network_flow_endpoints_from_rcl,
     rcl_bindings::*,
-    NodeHandle, RclrsError, ENTITY_LIFECYCLE_MUTEX,
+    validate_topic_name, CallbackGroup, Event, EventBase, GuardCondition, IntraProcessBuffer,
+    NetworkFlowEndpoint, NodeHandle, RclrsError, SerializedMessage, ENTITY_LIFECYCLE_MUTEX,
 };
 
 mod callback;
+mod message_buffer;
 mod message_info;
+mod options;
 mod readonly_loaned_message;
+mod statistics;
 pub use callback::*;
+pub use message_buffer::*;
 pub use message_info::*;
+pub use options::*;
 pub use readonly_loaned_message::*;
+pub(crate) use statistics::*;
 
 // SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
 // they are running in. Therefore, this type can be safely sent to another thread.
@@ -60,6 +68,11 @@ pub trait SubscriptionBase: Send + Sync {
     fn handle(&self) -> &SubscriptionHandle;
     /// Tries to take a new message and run the callback with it.
     fn execute(&self) -> Result<(), RclrsError>;
+    /// Returns the callback group of the subscription, if one was assigned.
+    fn callback_group(&self) -> Option<&Arc<CallbackGroup>>;
+    /// Returns the guard condition that is triggered when a message was delivered to the
+    /// subscription through intra-process communication, if it uses intra-process communication.
+    fn intra_process_guard_condition(&self) -> Option<&Arc<GuardCondition>>;
 }
 
 /// Struct for receiving messages of type `T`.
@@ -85,6 +98,11 @@ where
     pub(crate) handle: Arc<SubscriptionHandle>,
     /// The callback function that runs when a message was received.
     pub callback: Mutex<AnySubscriptionCallback<T>>,
+    pub(crate) events: Vec<Arc<dyn EventBase>>,
+    callback_group: Option<Arc<CallbackGroup>>,
+    intra_process: Option<Arc<IntraProcessBuffer<T>>>,
+    filter: Option<MessageFilter<T>>,
+    pub(crate) statistics: Option<TopicStatistics>,
     message: PhantomData<T>,
 }
 
@@ -96,7 +114,7 @@ where
     pub(crate) fn new<Args>(
         node_handle: Arc<NodeHandle>,
         topic: &str,
-        qos: QoSProfile,
+        mut options: SubscriptionOptions,
         callback: impl SubscriptionCallback<T, Args>,
     ) -> Result<Self, RclrsError>
     // This uses pub(crate) visibility to avoid instantiating this struct outside
@@ -112,10 +130,41 @@ where
             err,
             s: topic.into(),
         })?;
+        validate_topic_name(topic)?;
+        let filter = match options.message_filter.take() {
+            Some(filter) => Some(*filter.downcast::<MessageFilter<T>>().map_err(|_| {
+                // The filter was created for a different message type.
+                RclrsError::RclError {
+                    code: RclReturnCode::InvalidArgument,
+                    operation: None,
+                    msg: None,
+                }
+            })?),
+            None => None,
+        };
 
         // SAFETY: No preconditions for this function.
         let mut subscription_options = unsafe { rcl_subscription_get_default_options() };
-        subscription_options.qos = qos.into();
+        subscription_options.qos = options.qos.into();
+        subscription_options
+            .rmw_subscription_options
+            .require_unique_network_flow_endpoints = options.unique_network_flow_endpoints.into();
+
+        if let Some(content_filter) = options.get_content_filter() {
+            let (expression, parameters) = content_filter.to_c_strings()?;
+            let mut parameter_ptrs = c_string_pointers(&parameters);
+            // SAFETY: The strings are copied into the subscription options, which are
+            // finalized when they are dropped.
+            unsafe {
+                rcl_subscription_options_set_content_filter_options(
+                    expression.as_ptr(),
+                    parameter_ptrs.len(),
+                    parameter_ptrs.as_mut_ptr(),
+                    &mut subscription_options,
+                )
+                .ok_for("rcl_subscription_options_set_content_filter_options")?;
+            }
+        }
 
         {
             let rcl_node = node_handle.rcl_node.lock().unwrap();
@@ -134,27 +183,96 @@ where
                     topic_c_string.as_ptr(),
                     &subscription_options,
                 )
-                .ok()?;
+                .ok_for("rcl_subscription_init")?;
             }
         }
 
+        let callback = callback.into_callback();
+        // Loaned and serialized messages can only be received through the middleware.
+        let use_intra_process_comms = options
+            .use_intra_process
+            .unwrap_or(node_handle.use_intra_process_comms)
+            && !matches!(
+                callback,
+                AnySubscriptionCallback::Loaned(_)
+                    | AnySubscriptionCallback::LoanedWithMessageInfo(_)
+                    | AnySubscriptionCallback::Serialized(_)
+                    | AnySubscriptionCallback::SerializedWithMessageInfo(_)
+            );
         let handle = Arc::new(SubscriptionHandle {
             rcl_subscription: Mutex::new(rcl_subscription),
             node_handle,
             in_use_by_wait_set: Arc::new(AtomicBool::new(false)),
         });
 
-        Ok(Self {
+        // The RMW implementation silently ignores content filters it doesn't support.
+        // SAFETY: The subscription was initialized above.
+        if options.get_content_filter().is_some()
+            && !unsafe { rcl_subscription_is_cft_enabled(&*handle.lock()) }
+        {
+            return Err(RclrsError::RclError {
+                code: RclReturnCode::Unsupported,
+                operation: None,
+                msg: None,
+            });
+        }
+
+        let mut events: Vec<Arc<dyn EventBase>> = Vec::new();
+        if let Some(callback) = options.event_callbacks.message_lost {
+            events.push(Arc::new(Event::new_for_subscription(
+                &handle,
+                rcl_subscription_event_type_t::RCL_SUBSCRIPTION_MESSAGE_LOST,
+                callback,
+            )?));
+        }
+        if let Some(callback) = options.event_callbacks.requested_deadline_missed {
+            events.push(Arc::new(Event::new_for_subscription(
+                &handle,
+                rcl_subscription_event_type_t::RCL_SUBSCRIPTION_REQUESTED_DEADLINE_MISSED,
+                callback,
+            )?));
+        }
+        #[cfg(not(ros_distro = "humble"))]
+        if let Some(callback) = options.event_callbacks.matched {
+            events.push(Arc::new(Event::new_for_subscription(
+                &handle,
+                rcl_subscription_event_type_t::RCL_SUBSCRIPTION_MATCHED,
+                callback,
+            )?));
+        }
+        if let Some(callback) = options.event_callbacks.liveliness_changed {
+            events.push(Arc::new(Event::new_for_subscription(
+                &handle,
+                rcl_subscription_event_type_t::RCL_SUBSCRIPTION_LIVELINESS_CHANGED,
+                callback,
+            )?));
+        }
+
+        let mut subscription = Self {
             handle,
-            callback: Mutex::new(callback.into_callback()),
+            callback: Mutex::new(callback),
+            events,
+            callback_group: options.callback_group,
+            intra_process: None,
+            filter,
+            statistics: None,
             message: PhantomData,
-        })
+        };
+        if use_intra_process_comms {
+            subscription.intra_process = Some(IntraProcessBuffer::new(
+                Arc::clone(&subscription.handle.node_handle.context_handle),
+                Arc::clone(&subscription.handle),
+                subscription.topic_name(),
+                options.qos.history,
+            ));
+        }
+        Ok(subscription)
     }
 
     /// Returns the topic name of the subscription.
     ///
-    /// This returns the topic name after remapping, so it is not necessarily the
-    /// topic name which was used when creating the subscription.
+    /// This returns the fully-qualified topic name after namespace expansion and remapping, so
+    /// it is not necessarily the topic name which was used when creating the subscription.
     pub fn topic_name(&self) -> String {
         // SAFETY: No preconditions for the function used
         // The unsafe variables get converted to safe types before being returned
@@ -166,12 +284,94 @@ where
         }
     }
 
-    /// Fetches a new message.
+    /// Returns the message type of the subscription, e.g. `std_msgs/msg/String`.
+    pub fn type_name(&self) -> &'static str {
+        <T as Message>::RmwMsg::TYPE_NAME
+    }
+
+    /// Returns the network flow endpoints that the subscription uses in the middleware.
+    ///
+    /// Each endpoint consists of the transport and internet protocol, the address and the port,
+    /// which is useful e.g. for debugging on machines with several network interfaces.
+    ///
+    /// # Errors
+    /// If the RMW implementation does not support this, an [`Unsupported`][1] error is returned.
+    ///
+    /// [1]: crate::RclReturnCode::Unsupported
+    pub fn get_network_flow_endpoints(&self) -> Result<Vec<NetworkFlowEndpoint>, RclrsError> {
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut array = unsafe { rmw_get_zero_initialized_network_flow_endpoint_array() };
+        // SAFETY: The array is zero-initialized as expected, and is finalized when dropped.
+        unsafe {
+            rcl_subscription_get_network_flow_endpoints(
+                &*self.handle.lock(),
+                &mut rcutils_get_default_allocator(),
+                &mut array,
+            )
+            .ok_for("rcl_subscription_get_network_flow_endpoints")?;
+        }
+        Ok(network_flow_endpoints_from_rcl(&array))
+    }
+
+    /// Returns `true` if the middleware filters messages for this subscription.
     ///
-    /// When there is no new message, this will return a
-    /// [`SubscriptionTakeFailed`][1].
+    /// See [`SubscriptionOptions::content_filter()`].
+    pub fn is_content_filter_enabled(&self) -> bool {
+        // SAFETY: No preconditions for this function.
+        unsafe { rcl_subscription_is_cft_enabled(&*self.handle.lock()) }
+    }
+
+    /// Replaces the content filter of the subscription at runtime.
     ///
-    /// [1]: crate::RclrsError
+    /// The subscription must have been created with a content filter, see
+    /// [`SubscriptionOptions::content_filter()`] for the syntax.
+    pub fn set_content_filter(
+        &self,
+        expression: &str,
+        parameters: &[String],
+    ) -> Result<(), RclrsError> {
+        let content_filter = ContentFilter {
+            expression: expression.to_string(),
+            parameters: parameters.to_vec(),
+        };
+        let (expression, parameters) = content_filter.to_c_strings()?;
+        let mut parameter_ptrs = c_string_pointers(&parameters);
+        let rcl_subscription = &*self.handle.lock();
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut rcl_content_filter_options =
+            unsafe { rcl_get_zero_initialized_subscription_content_filter_options() };
+        unsafe {
+            // SAFETY: The content filter options are zero-initialized as expected, and the
+            // strings are copied by this function.
+            rcl_subscription_content_filter_options_init(
+                rcl_subscription,
+                expression.as_ptr(),
+                parameter_ptrs.len(),
+                parameter_ptrs.as_mut_ptr(),
+                &mut rcl_content_filter_options,
+            )
+            .ok_for("rcl_subscription_content_filter_options_init")?;
+        }
+        // SAFETY: The subscription and the content filter options are initialized. The
+        // options are finalized right afterwards, since the subscription copies them.
+        let ret = unsafe {
+            rcl_subscription_set_content_filter(rcl_subscription, &rcl_content_filter_options)
+        };
+        unsafe {
+            rcl_subscription_content_filter_options_fini(
+                rcl_subscription,
+                &mut rcl_content_filter_options,
+            );
+        }
+        ret.ok_for("rcl_subscription_set_content_filter")
+    }
+
+    /// Fetches a new message, if one is available.
+    ///
+    /// When there is no new message, this returns `Ok(None)` instead of blocking. Together with a
+    /// [`WaitSet`][1], this allows receiving messages in a custom event loop without an executor.
+    ///
+    /// [1]: crate::WaitSet
     //
     // ```text
     // +-------------+
@@ -188,16 +388,78 @@ where
     // |  rmw_take   |
     // +-------------+
     // ```
-    pub fn take(&self) -> Result<(T, MessageInfo), RclrsError> {
+    pub fn take(&self) -> Result<Option<(T, MessageInfo)>, RclrsError> {
+        match self.take_message() {
+            Ok(message) => Ok(Some(message)),
+            Err(RclrsError::RclError {
+                code: RclReturnCode::SubscriptionTakeFailed,
+                ..
+            }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetches a new message into an existing message, if one is available.
+    ///
+    /// This allows reusing a single message across takes, e.g. in a real-time loop. When `T` is
+    /// an RMW-native message type, such as `std_msgs::msg::rmw::String`, the middleware
+    /// deserializes directly into `message`; depending on the RMW implementation, the memory of
+    /// its sequences and strings may still be reallocated. For idiomatic message types, the
+    /// message is converted and assigned to `message`, which is not more efficient than
+    /// [`take()`][1].
+    ///
+    /// When there is no new message, this returns `Ok(None)` and leaves `message` unchanged.
+    ///
+    /// [1]: Subscription::take
+    pub fn take_into(&self, message: &mut T) -> Result<Option<MessageInfo>, RclrsError> {
+        let result = if TypeId::of::<T>() == TypeId::of::<<T as Message>::RmwMsg>() {
+            // SAFETY: T and its RMW-native type are the same type, so the cast is a no-op.
+            let rmw_message = unsafe { &mut *(message as *mut T as *mut <T as Message>::RmwMsg) };
+            self.take_inner(rmw_message)
+        } else {
+            let mut rmw_message = <T as Message>::RmwMsg::default();
+            self.take_inner(&mut rmw_message).map(|message_info| {
+                *message = T::from_rmw_message(rmw_message);
+                message_info
+            })
+        };
+        match result {
+            Ok(message_info) => Ok(Some(message_info)),
+            Err(RclrsError::RclError {
+                code: RclReturnCode::SubscriptionTakeFailed,
+                ..
+            }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Like take(), but returns a SubscriptionTakeFailed error when there is no new message.
+    fn take_message(&self) -> Result<(T, MessageInfo), RclrsError> {
         let mut rmw_message = <T as Message>::RmwMsg::default();
         let message_info = self.take_inner(&mut rmw_message)?;
         Ok((T::from_rmw_message(rmw_message), message_info))
     }
 
-    /// This is a version of take() that returns a boxed message.
+    /// This is a version of [`take()`][1] that returns a boxed message.
     ///
     /// This can be more efficient for messages containing large arrays.
-    pub fn take_boxed(&self) -> Result<(Box<T>, MessageInfo), RclrsError> {
+    ///
+    /// When there is no new message, this returns `Ok(None)`.
+    ///
+    /// [1]: Subscription::take
+    pub fn take_boxed(&self) -> Result<Option<(Box<T>, MessageInfo)>, RclrsError> {
+        match self.take_boxed_message() {
+            Ok(message) => Ok(Some(message)),
+            Err(RclrsError::RclError {
+                code: RclReturnCode::SubscriptionTakeFailed,
+                ..
+            }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Like take_boxed(), but returns a SubscriptionTakeFailed error when there is no new message.
+    fn take_boxed_message(&self) -> Result<(Box<T>, MessageInfo), RclrsError> {
         let mut rmw_message = Box::<<T as Message>::RmwMsg>::default();
         let message_info = self.take_inner(&mut *rmw_message)?;
         // TODO: This will still use the stack in general. Change signature of
@@ -223,9 +485,62 @@ where
                 &mut message_info,
                 std::ptr::null_mut(),
             )
-            .ok()?
+            .ok_for("rcl_take")?
         };
-        Ok(MessageInfo::from_rmw_message_info(&message_info))
+        let message_info = MessageInfo::from_rmw_message_info(&message_info);
+        if let Some(intra_process) = &self.intra_process {
+            // The message was already delivered through intra-process communication.
+            if intra_process.is_duplicate(&message_info) {
+                return Err(RclrsError::RclError {
+                    code: RclReturnCode::SubscriptionTakeFailed,
+                    operation: None,
+                    msg: None,
+                });
+            }
+        }
+        Ok(message_info)
+    }
+
+    // Returns whether the message passes the filter of the subscription, if it has one.
+    fn passes_filter(&self, message: &T) -> bool {
+        self.filter.as_ref().map_or(true, |filter| filter(message))
+    }
+
+    // Adds a received message to the topic statistics, if they are enabled.
+    fn record_statistics(&self, message_info: &MessageInfo) {
+        if let Some(statistics) = &self.statistics {
+            statistics.collector.lock().unwrap().record(message_info);
+        }
+    }
+
+    // Runs the callback with a message that was delivered through intra-process communication.
+    fn execute_intra_process(&self, message: Arc<T>, message_info: MessageInfo) {
+        self.record_statistics(&message_info);
+        if !self.passes_filter(&message) {
+            return;
+        }
+        // The message is only copied if the callback needs to own it and it is shared with
+        // other subscriptions.
+        let into_owned =
+            |message: Arc<T>| Arc::try_unwrap(message).unwrap_or_else(|m| (*m).clone());
+        match &mut *self.callback.lock().unwrap() {
+            AnySubscriptionCallback::Regular(cb) => cb(into_owned(message)),
+            AnySubscriptionCallback::RegularWithMessageInfo(cb) => {
+                cb(into_owned(message), message_info)
+            }
+            AnySubscriptionCallback::Boxed(cb) => cb(Box::new(into_owned(message))),
+            AnySubscriptionCallback::BoxedWithMessageInfo(cb) => {
+                cb(Box::new(into_owned(message)), message_info)
+            }
+            AnySubscriptionCallback::Shared(cb) => cb(message),
+            AnySubscriptionCallback::SharedWithMessageInfo(cb) => cb(message, message_info),
+            // Subscriptions with these callbacks don't use intra-process communication, unless
+            // the callback was replaced afterwards, in which case the message is dropped.
+            AnySubscriptionCallback::Loaned(_)
+            | AnySubscriptionCallback::LoanedWithMessageInfo(_)
+            | AnySubscriptionCallback::Serialized(_)
+            | AnySubscriptionCallback::SerializedWithMessageInfo(_) => {}
+        }
     }
 
     /// Obtains a read-only handle to a message owned by the middleware.
@@ -250,7 +565,7 @@ where
                 &mut message_info,
                 std::ptr::null_mut(),
             )
-            .ok()?;
+            .ok_for("rcl_take_loaned_message")?;
         }
         let read_only_loaned_msg = ReadOnlyLoanedMessage {
             msg_ptr: msg_ptr as *const T::RmwMsg,
@@ -261,6 +576,44 @@ where
             MessageInfo::from_rmw_message_info(&message_info),
         ))
     }
+
+    /// Fetches a new message in its serialized form, without deserializing it.
+    ///
+    /// When there is no new message, this will return a
+    /// [`SubscriptionTakeFailed`][1].
+    ///
+    /// [1]: crate::RclrsError
+    pub fn take_serialized(&self) -> Result<(SerializedMessage, MessageInfo), RclrsError> {
+        let mut serialized_message = SerializedMessage::new()?;
+        let mut message_info = unsafe { rmw_get_zero_initialized_message_info() };
+        unsafe {
+            // SAFETY: The serialized message is initialized, and its buffer is resized by
+            // this function as needed. The allocation argument may be null.
+            rcl_take_serialized_message(
+                &*self.handle.lock(),
+                &mut serialized_message.rcl_serialized_message,
+                &mut message_info,
+                std::ptr::null_mut(),
+            )
+            .ok_for("rcl_take_serialized_message")?;
+        }
+        Ok((
+            serialized_message,
+            MessageInfo::from_rmw_message_info(&message_info),
+        ))
+    }
+}
+
+impl Drop for rcl_subscription_options_t {
+    fn drop(&mut self) {
+        // SAFETY: Do not finish this struct except here. The subscription keeps its own copy of
+        // the content filter options.
+        unsafe {
+            rcl_subscription_options_fini(self)
+                .ok_for("rcl_subscription_options_fini")
+                .unwrap();
+        }
+    }
 }
 
 impl<T> SubscriptionBase for Subscription<T>
@@ -271,33 +624,93 @@ where
         &self.handle
     }
 
+    fn callback_group(&self) -> Option<&Arc<CallbackGroup>> {
+        self.callback_group.as_ref()
+    }
+
+    fn intra_process_guard_condition(&self) -> Option<&Arc<GuardCondition>> {
+        self.intra_process
+            .as_ref()
+            .map(|intra_process| &intra_process.guard_condition)
+    }
+
     fn execute(&self) -> Result<(), RclrsError> {
+        if let Some(intra_process) = &self.intra_process {
+            if let Some((message, message_info)) = intra_process.pop() {
+                self.execute_intra_process(message, message_info);
+                return Ok(());
+            }
+        }
         // Immediately evaluated closure, to handle SubscriptionTakeFailed
         // outside this match
         match (|| {
             match &mut *self.callback.lock().unwrap() {
                 AnySubscriptionCallback::Regular(cb) => {
-                    let (msg, _) = self.take()?;
+                    let (msg, msg_info) = self.take_message()?;
+                    self.record_statistics(&msg_info);
+                    if !self.passes_filter(&msg) {
+                        return Ok(());
+                    }
                     cb(msg)
                 }
                 AnySubscriptionCallback::RegularWithMessageInfo(cb) => {
-                    let (msg, msg_info) = self.take()?;
+                    let (msg, msg_info) = self.take_message()?;
+                    self.record_statistics(&msg_info);
+                    if !self.passes_filter(&msg) {
+                        return Ok(());
+                    }
                     cb(msg, msg_info)
                 }
                 AnySubscriptionCallback::Boxed(cb) => {
-                    let (msg, _) = self.take_boxed()?;
+                    let (msg, msg_info) = self.take_boxed_message()?;
+                    self.record_statistics(&msg_info);
+                    if !self.passes_filter(&msg) {
+                        return Ok(());
+                    }
                     cb(msg)
                 }
                 AnySubscriptionCallback::BoxedWithMessageInfo(cb) => {
-                    let (msg, msg_info) = self.take_boxed()?;
+                    let (msg, msg_info) = self.take_boxed_message()?;
+                    self.record_statistics(&msg_info);
+                    if !self.passes_filter(&msg) {
+                        return Ok(());
+                    }
                     cb(msg, msg_info)
                 }
+                AnySubscriptionCallback::Shared(cb) => {
+                    let (msg, msg_info) = self.take_message()?;
+                    self.record_statistics(&msg_info);
+                    if !self.passes_filter(&msg) {
+                        return Ok(());
+                    }
+                    cb(Arc::new(msg))
+                }
+                AnySubscriptionCallback::SharedWithMessageInfo(cb) => {
+                    let (msg, msg_info) = self.take_message()?;
+                    self.record_statistics(&msg_info);
+                    if !self.passes_filter(&msg) {
+                        return Ok(());
+                    }
+                    cb(Arc::new(msg), msg_info)
+                }
                 AnySubscriptionCallback::Loaned(cb) => {
-                    let (msg, _) = self.take_loaned()?;
+                    let (msg, msg_info) = self.take_loaned()?;
+                    self.record_statistics(&msg_info);
                     cb(msg)
                 }
                 AnySubscriptionCallback::LoanedWithMessageInfo(cb) => {
                     let (msg, msg_info) = self.take_loaned()?;
+                    self.record_statistics(&msg_info);
+                    cb(msg, msg_info)
+                }
+                AnySubscriptionCallback::Serialized(cb) => {
+                    let (msg, msg_info) = self.take_serialized()?;
+                    self.record_statistics(&msg_info);
+                    cb(msg)
+                }
+                AnySubscriptionCallback::SerializedWithMessageInfo(cb) => {
+                    let (msg, msg_info) = self.take_serialized()?;
+                    self.record_statistics(&msg_info);
                     cb(msg, msg_info)
                 }
             }
@@ -319,7 +732,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_helpers::*;
+    use crate::{test_helpers::*, MessageLostStatus};
     use test_msgs::msg;
 
     #[test]
@@ -328,9 +741,39 @@ mod tests {
         assert_sync::<Subscription<msg::BoundedSequences>>();
     }
 
+    #[test]
+    fn test_topic_and_type_names() -> Result<(), RclrsError> {
+        use crate::{Context, Node, QOS_PROFILE_DEFAULT};
+
+        let context = Context::new([])?;
+        let node = Node::builder(&context, "topic_and_type_names")
+            .namespace("/ns")
+            .build()?;
+        let subscription = node.create_subscription::<msg::BasicTypes, _>(
+            "foo",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::BasicTypes| {},
+        )?;
+        assert_eq!(subscription.topic_name(), "/ns/foo");
+        assert_eq!(subscription.type_name(), "test_msgs/msg/BasicTypes");
+
+        let subscription = node.create_subscription::<msg::Empty, _>(
+            "/ns/foo",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::Empty| {},
+        )?;
+        assert_eq!(subscription.topic_name(), "/ns/foo");
+        assert_eq!(subscription.type_name(), "test_msgs/msg/Empty");
+
+        let publisher = node.create_publisher::<msg::Empty>("~/bar", QOS_PROFILE_DEFAULT)?;
+        assert_eq!(publisher.topic_name(), "/ns/topic_and_type_names/bar");
+        assert_eq!(publisher.type_name(), "test_msgs/msg/Empty");
+        Ok(())
+    }
+
     #[test]
     fn test_subscriptions() -> Result<(), RclrsError> {
-        use crate::{TopicEndpointInfo, QOS_PROFILE_SYSTEM_DEFAULT};
+        use crate::QOS_PROFILE_SYSTEM_DEFAULT;
 
         let namespace = "/test_subscriptions_graph";
         let graph = construct_test_graph(namespace)?;
@@ -380,19 +823,490 @@ mod tests {
         assert!(types.contains(&"test_msgs/msg/BasicTypes".to_string()));
 
         // Test get_subscriptions_info_by_topic()
-        let expected_subscriptions_info = vec![TopicEndpointInfo {
-            node_name: String::from("graph_test_node_2"),
-            node_namespace: String::from(namespace),
-            topic_type: String::from("test_msgs/msg/Empty"),
-        }];
-        assert_eq!(
-            graph.node1.get_subscriptions_info_by_topic(&topic1)?,
-            expected_subscriptions_info
-        );
+        let subscriptions_info = graph.node1.get_subscriptions_info_by_topic(&topic1)?;
+        assert_eq!(subscriptions_info.len(), 1);
+        assert_eq!(subscriptions_info[0].node_name, "graph_test_node_2");
+        assert_eq!(subscriptions_info[0].node_namespace, namespace);
+        assert_eq!(subscriptions_info[0].topic_type, "test_msgs/msg/Empty");
         assert_eq!(
             graph.node2.get_subscriptions_info_by_topic(&topic1)?,
-            expected_subscriptions_info
+            subscriptions_info
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_subscription_callback_with_message_info() -> Result<(), RclrsError> {
+        use crate::{spin_once, QOS_PROFILE_DEFAULT};
+        use std::time::Duration;
+
+        let namespace = "/test_subscription_message_info";
+        let graph = construct_test_graph(namespace)?;
+
+        let received_info = Arc::new(Mutex::new(None));
+        let received_info_in_callback = Arc::clone(&received_info);
+        let _subscription = graph.node2.create_subscription::<msg::Empty, _>(
+            "message_info_topic",
+            QOS_PROFILE_DEFAULT,
+            move |_msg: msg::Empty, info: MessageInfo| {
+                *received_info_in_callback.lock().unwrap() = Some(info);
+            },
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("message_info_topic", QOS_PROFILE_DEFAULT)?;
+
+        for _ in 0..50 {
+            publisher.publish(msg::Empty::default())?;
+            ignore_timeout(spin_once(
+                Arc::clone(&graph.node2),
+                Some(Duration::from_millis(100)),
+            ))?;
+            if received_info.lock().unwrap().is_some() {
+                break;
+            }
+        }
+
+        let info = received_info
+            .lock()
+            .unwrap()
+            .take()
+            .expect("The subscription did not receive a message");
+        assert!(info.received_timestamp.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_late_subscription_receives_latched_message() -> Result<(), RclrsError> {
+        use crate::{spin_once, QoSProfile};
+        use std::time::Duration;
+
+        let namespace = "/test_late_subscription_receives_latched_message";
+        let graph = construct_test_graph(namespace)?;
+
+        let publisher = graph.node1.create_publisher::<msg::BasicTypes>(
+            "latched_topic",
+            QoSProfile::transient_local_latch(),
+        )?;
+        for int32_value in [1, 2] {
+            publisher.publish(msg::BasicTypes {
+                int32_value,
+                ..Default::default()
+            })?;
+        }
+
+        // The subscription only comes into existence after the messages were published.
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_in_callback = Arc::clone(&received);
+        let _subscription = graph.node2.create_subscription::<msg::BasicTypes, _>(
+            "latched_topic",
+            QoSProfile::transient_local_latch(),
+            move |msg: msg::BasicTypes| {
+                received_in_callback.lock().unwrap().push(msg.int32_value);
+            },
+        )?;
+
+        for _ in 0..50 {
+            ignore_timeout(spin_once(
+                Arc::clone(&graph.node2),
+                Some(Duration::from_millis(100)),
+            ))?;
+            if !received.lock().unwrap().is_empty() {
+                break;
+            }
+        }
+
+        // Only the last message is kept for late subscriptions.
+        assert_eq!(*received.lock().unwrap(), vec![2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_take_serialized() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use std::time::Duration;
+
+        let namespace = "/test_take_serialized";
+        let graph = construct_test_graph(namespace)?;
+
+        let subscription = graph.node2.create_subscription::<msg::BasicTypes, _>(
+            "serialized_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::BasicTypes| {},
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::BasicTypes>("serialized_topic", QOS_PROFILE_DEFAULT)?;
+
+        for _ in 0..50 {
+            publisher.publish(msg::BasicTypes::default())?;
+            std::thread::sleep(Duration::from_millis(100));
+            match subscription.take_serialized() {
+                Ok((serialized_message, _)) => {
+                    assert!(!serialized_message.is_empty());
+                    assert_eq!(
+                        serialized_message.as_bytes().len(),
+                        serialized_message.len()
+                    );
+                    return Ok(());
+                }
+                Err(RclrsError::RclError {
+                    code: RclReturnCode::SubscriptionTakeFailed,
+                    ..
+                }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        panic!("The subscription did not receive a serialized message");
+    }
+
+    #[test]
+    fn test_take_until_empty() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use std::time::Duration;
+
+        let namespace = "/test_take_until_empty";
+        let graph = construct_test_graph(namespace)?;
+
+        let subscription = graph.node2.create_subscription::<msg::BasicTypes, _>(
+            "take_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::BasicTypes| {},
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::BasicTypes>("take_topic", QOS_PROFILE_DEFAULT)?;
+        assert!(subscription.take()?.is_none());
+        assert!(subscription.take_boxed()?.is_none());
+
+        for _ in 0..50 {
+            if publisher.get_subscription_count()? > 0 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        for int32_value in 0..3 {
+            publisher.publish(msg::BasicTypes {
+                int32_value,
+                ..Default::default()
+            })?;
+        }
+
+        let mut received = Vec::new();
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(100));
+            while let Some((msg, _)) = subscription.take()? {
+                received.push(msg.int32_value);
+            }
+            if received.len() == 3 {
+                break;
+            }
+        }
+        assert_eq!(received, [0, 1, 2]);
+        assert!(subscription.take()?.is_none());
+        assert!(subscription.take_boxed()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_take_into() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use std::time::Duration;
+
+        let namespace = "/test_take_into";
+        let graph = construct_test_graph(namespace)?;
+
+        // The RMW-native message type is deserialized in place.
+        let subscription = graph.node2.create_subscription::<msg::rmw::BasicTypes, _>(
+            "take_into_topic",
+            QOS_PROFILE_DEFAULT,
+            |_msg: msg::rmw::BasicTypes| {},
+        )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::BasicTypes>("take_into_topic", QOS_PROFILE_DEFAULT)?;
+        for _ in 0..50 {
+            if publisher.get_subscription_count()? > 0 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        let mut message = msg::rmw::BasicTypes::default();
+        assert!(subscription.take_into(&mut message)?.is_none());
+        for int32_value in [1, 2] {
+            publisher.publish(msg::BasicTypes {
+                int32_value,
+                ..Default::default()
+            })?;
+            let mut received = false;
+            for _ in 0..50 {
+                std::thread::sleep(Duration::from_millis(100));
+                if subscription.take_into(&mut message)?.is_some() {
+                    received = true;
+                    break;
+                }
+            }
+            assert!(received);
+            assert_eq!(message.int32_value, int32_value);
+        }
+        assert!(subscription.take_into(&mut message)?.is_none());
+        assert_eq!(message.int32_value, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_filtered_subscription() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+        use std::time::Duration;
+
+        let namespace = "/test_content_filtered_subscription";
+        let graph = construct_test_graph(namespace)?;
+
+        let options = SubscriptionOptions::new(QOS_PROFILE_DEFAULT)
+            .content_filter("int32_value > %0", &[String::from("10")]);
+        let subscription = match graph
+            .node2
+            .create_subscription_with_options::<msg::BasicTypes, _>(
+                "content_filtered_topic",
+                options,
+                |_msg: msg::BasicTypes| {},
+            ) {
+            Ok(subscription) => subscription,
+            // Content filtering is optional for RMW implementations.
+            Err(RclrsError::RclError {
+                code: RclReturnCode::Unsupported,
+                ..
+            }) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        assert!(subscription.is_content_filter_enabled());
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::BasicTypes>("content_filtered_topic", QOS_PROFILE_DEFAULT)?;
+
+        let mut received = Vec::new();
+        for _ in 0..20 {
+            for int32_value in [5, 20] {
+                publisher.publish(msg::BasicTypes {
+                    int32_value,
+                    ..Default::default()
+                })?;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+            while let Some((msg, _)) = subscription.take()? {
+                received.push(msg.int32_value);
+            }
+            if !received.is_empty() {
+                break;
+            }
+        }
+        assert!(!received.is_empty());
+        assert!(received.iter().all(|&value| value > 10));
+        Ok(())
+    }
+
+    #[test]
+    fn test_message_lost_event() -> Result<(), RclrsError> {
+        use crate::QOS_PROFILE_DEFAULT;
+
+        let namespace = "/test_message_lost_event";
+        let graph = construct_test_graph(namespace)?;
+
+        let options = SubscriptionOptions::new(QOS_PROFILE_DEFAULT)
+            .message_lost_callback(|_status: MessageLostStatus| {});
+        let subscription = match graph
+            .node2
+            .create_subscription_with_options::<msg::Empty, _>(
+                "message_lost_topic",
+                options,
+                |_msg: msg::Empty| {},
+            ) {
+            Ok(subscription) => subscription,
+            // The message-lost event is optional for RMW implementations.
+            Err(RclrsError::RclError {
+                code: RclReturnCode::Unsupported,
+                ..
+            }) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        assert_eq!(subscription.events.len(), 1);
+        assert_eq!(graph.node2.live_events().len(), 1);
+
+        drop(subscription);
+        assert!(graph.node2.live_events().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_requested_deadline_missed_event() -> Result<(), RclrsError> {
+        use crate::{spin_once, RequestedDeadlineMissedStatus, QOS_PROFILE_DEFAULT};
+        use std::time::Duration;
+
+        let namespace = "/test_requested_deadline_missed_event";
+        let graph = construct_test_graph(namespace)?;
+        let qos = QOS_PROFILE_DEFAULT.deadline(Duration::from_millis(50));
+
+        let deadline_missed = Arc::new(Mutex::new(None));
+        let deadline_missed_in_callback = Arc::clone(&deadline_missed);
+        let options = SubscriptionOptions::new(qos).requested_deadline_missed_callback(
+            move |status: RequestedDeadlineMissedStatus| {
+                *deadline_missed_in_callback.lock().unwrap() = Some(status);
+            },
+        );
+        let _subscription = graph
+            .node2
+            .create_subscription_with_options::<msg::Empty, _>(
+                "deadline_topic",
+                options,
+                |_msg: msg::Empty| {},
+            )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("deadline_topic", qos)?;
+
+        // Publish once, then stop publishing so that the deadline is missed.
+        std::thread::sleep(Duration::from_millis(100));
+        publisher.publish(msg::Empty::default())?;
+        for _ in 0..20 {
+            ignore_timeout(spin_once(
+                Arc::clone(&graph.node2),
+                Some(Duration::from_millis(100)),
+            ))?;
+            if deadline_missed.lock().unwrap().is_some() {
+                break;
+            }
+        }
+
+        let status = deadline_missed
+            .lock()
+            .unwrap()
+            .take()
+            .expect("The deadline-missed callback was not called");
+        assert!(status.total_count > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_liveliness_changed_event() -> Result<(), RclrsError> {
+        use crate::{spin_once, LivelinessChangedStatus, QoSLivelinessPolicy, QOS_PROFILE_DEFAULT};
+        use std::time::Duration;
+
+        let namespace = "/test_liveliness_changed_event";
+        let graph = construct_test_graph(namespace)?;
+        let qos = QOS_PROFILE_DEFAULT
+            .liveliness(QoSLivelinessPolicy::Automatic)
+            .liveliness_lease_duration(Duration::from_secs(1));
+
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let statuses_in_callback = Arc::clone(&statuses);
+        let options = SubscriptionOptions::new(qos).liveliness_changed_callback(
+            move |status: LivelinessChangedStatus| {
+                statuses_in_callback.lock().unwrap().push(status);
+            },
         );
+        let _subscription = match graph
+            .node2
+            .create_subscription_with_options::<msg::Empty, _>(
+                "liveliness_topic",
+                options,
+                |_msg: msg::Empty| {},
+            ) {
+            Ok(subscription) => subscription,
+            // The liveliness-changed event is optional for RMW implementations.
+            Err(RclrsError::RclError {
+                code: RclReturnCode::Unsupported,
+                ..
+            }) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        // Spins until a reported status fulfills the condition
+        let spin_until = |condition: &dyn Fn(&LivelinessChangedStatus) -> bool| {
+            for _ in 0..50 {
+                ignore_timeout(spin_once(
+                    Arc::clone(&graph.node2),
+                    Some(Duration::from_millis(100)),
+                ))?;
+                if statuses.lock().unwrap().iter().any(condition) {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        };
+
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("liveliness_topic", qos)?;
+        assert!(spin_until(&|status| status.alive_count == 1)?);
+
+        drop(publisher);
+        assert!(spin_until(
+            &|status| status.alive_count == 0 && status.alive_count_change < 0
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter() -> Result<(), RclrsError> {
+        use crate::{spin_once, QOS_PROFILE_DEFAULT};
+        use std::time::{Duration, Instant};
+
+        let namespace = "/test_subscription_filter";
+        let graph = construct_test_graph(namespace)?;
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_in_callback = Arc::clone(&received);
+        let options = SubscriptionOptions::new(QOS_PROFILE_DEFAULT)
+            .filter(|msg: &msg::BasicTypes| msg.int32_value % 2 == 0);
+        let _subscription = graph
+            .node2
+            .create_subscription_with_options::<msg::BasicTypes, _>(
+                "filter_topic",
+                options,
+                move |msg: msg::BasicTypes| {
+                    received_in_callback.lock().unwrap().push(msg.int32_value);
+                },
+            )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::BasicTypes>("filter_topic", QOS_PROFILE_DEFAULT)?;
+        let start = Instant::now();
+        while publisher.get_subscription_count()? == 0 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        for value in 1..=6 {
+            publisher.publish(msg::BasicTypes {
+                int32_value: value,
+                ..Default::default()
+            })?;
+        }
+        while received.lock().unwrap().last() != Some(&6) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            ignore_timeout(spin_once(
+                Arc::clone(&graph.node2),
+                Some(Duration::from_millis(100)),
+            ))?;
+        }
+        assert_eq!(*received.lock().unwrap(), vec![2, 4, 6]);
+
+        // A filter for a different message type is rejected.
+        let options = SubscriptionOptions::new(QOS_PROFILE_DEFAULT).filter(|_: &msg::Empty| true);
+        assert!(matches!(
+            graph
+                .node2
+                .create_subscription_with_options::<msg::BasicTypes, _>(
+                    "filter_topic",
+                    options,
+                    |_msg: msg::BasicTypes| {},
+                ),
+            Err(RclrsError::RclError {
+                code: RclReturnCode::InvalidArgument,
+                ..
+            })
+        ));
         Ok(())
     }
 }
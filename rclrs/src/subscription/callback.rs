@@ -1,7 +1,9 @@
+use std::sync::Arc;
+
 use rosidl_runtime_rs::Message;
 
 use super::MessageInfo;
-use crate::ReadOnlyLoanedMessage;
+use crate::{ReadOnlyLoanedMessage, SerializedMessage};
 
 /// A trait for allowed callbacks for subscriptions.
 ///
@@ -31,12 +33,25 @@ where
     Boxed(Box<dyn FnMut(Box<T>) + Send>),
     /// A callback with the boxed message and the message info as arguments.
     BoxedWithMessageInfo(Box<dyn FnMut(Box<T>, MessageInfo) + Send>),
+    /// A callback with only the shared message as an argument.
+    ///
+    /// With [intra-process communication][1], the message is shared with the publisher and the
+    /// other subscriptions instead of being copied.
+    ///
+    /// [1]: crate::NodeBuilder::use_intra_process_comms
+    Shared(Box<dyn FnMut(Arc<T>) + Send>),
+    /// A callback with the shared message and the message info as arguments.
+    SharedWithMessageInfo(Box<dyn FnMut(Arc<T>, MessageInfo) + Send>),
     /// A callback with only the loaned message as an argument.
     #[allow(clippy::type_complexity)]
     Loaned(Box<dyn for<'a> FnMut(ReadOnlyLoanedMessage<'a, T>) + Send>),
     /// A callback with the loaned message and the message info as arguments.
     #[allow(clippy::type_complexity)]
     LoanedWithMessageInfo(Box<dyn for<'a> FnMut(ReadOnlyLoanedMessage<'a, T>, MessageInfo) + Send>),
+    /// A callback with only the serialized message as an argument.
+    Serialized(Box<dyn FnMut(SerializedMessage) + Send>),
+    /// A callback with the serialized message and the message info as arguments.
+    SerializedWithMessageInfo(Box<dyn FnMut(SerializedMessage, MessageInfo) + Send>),
 }
 
 // We need one implementation per arity. This was inspired by Bevy's systems.
@@ -113,6 +128,26 @@ where
     }
 }
 
+impl<T, Func> ArgTuple<T, Func> for (Arc<T>,)
+where
+    T: Message,
+    Func: FnMut(Arc<T>) + Send + 'static,
+{
+    fn into_callback_with_args(func: Func) -> AnySubscriptionCallback<T> {
+        AnySubscriptionCallback::Shared(Box::new(func))
+    }
+}
+
+impl<T, Func> ArgTuple<T, Func> for (Arc<T>, MessageInfo)
+where
+    T: Message,
+    Func: FnMut(Arc<T>, MessageInfo) + Send + 'static,
+{
+    fn into_callback_with_args(func: Func) -> AnySubscriptionCallback<T> {
+        AnySubscriptionCallback::SharedWithMessageInfo(Box::new(func))
+    }
+}
+
 impl<T, Func> ArgTuple<T, Func> for (ReadOnlyLoanedMessage<'_, T>,)
 where
     T: Message,
@@ -133,6 +168,26 @@ where
     }
 }
 
+impl<T, Func> ArgTuple<T, Func> for (SerializedMessage,)
+where
+    T: Message,
+    Func: FnMut(SerializedMessage) + Send + 'static,
+{
+    fn into_callback_with_args(func: Func) -> AnySubscriptionCallback<T> {
+        AnySubscriptionCallback::Serialized(Box::new(func))
+    }
+}
+
+impl<T, Func> ArgTuple<T, Func> for (SerializedMessage, MessageInfo)
+where
+    T: Message,
+    Func: FnMut(SerializedMessage, MessageInfo) + Send + 'static,
+{
+    fn into_callback_with_args(func: Func) -> AnySubscriptionCallback<T> {
+        AnySubscriptionCallback::SerializedWithMessageInfo(Box::new(func))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,6 +215,16 @@ mod tests {
             cb.into_callback(),
             AnySubscriptionCallback::<Message>::BoxedWithMessageInfo(_)
         ));
+        let cb = |_msg: Arc<Message>| {};
+        assert!(matches!(
+            cb.into_callback(),
+            AnySubscriptionCallback::<Message>::Shared(_)
+        ));
+        let cb = |_msg: Arc<Message>, _info: MessageInfo| {};
+        assert!(matches!(
+            cb.into_callback(),
+            AnySubscriptionCallback::<Message>::SharedWithMessageInfo(_)
+        ));
         let cb = |_msg: ReadOnlyLoanedMessage<'_, Message>| {};
         assert!(matches!(
             cb.into_callback(),
@@ -170,5 +235,15 @@ mod tests {
             cb.into_callback(),
             AnySubscriptionCallback::<Message>::LoanedWithMessageInfo(_)
         ));
+        let cb = |_msg: SerializedMessage| {};
+        assert!(matches!(
+            cb.into_callback(),
+            AnySubscriptionCallback::<Message>::Serialized(_)
+        ));
+        let cb = |_msg: SerializedMessage, _info: MessageInfo| {};
+        assert!(matches!(
+            cb.into_callback(),
+            AnySubscriptionCallback::<Message>::SerializedWithMessageInfo(_)
+        ));
     }
 }
@@ -0,0 +1,281 @@
+use std::{
+    collections::VecDeque,
+    sync::{Condvar, Mutex},
+    time::Duration,
+};
+
+use crate::MessageInfo;
+
+/// What a [`MessageBuffer`] does with a message that arrives while it is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The oldest message in the buffer is dropped to make room for the new message.
+    DropOldest,
+    /// The new message is dropped.
+    DropNewest,
+    /// The subscription waits until a message has been popped from the buffer.
+    ///
+    /// This blocks the executor that received the message, so the buffer must be emptied from
+    /// another thread.
+    Block,
+}
+
+/// A bounded buffer that a subscription created with
+/// [`Node::create_buffered_subscription()`][1] pushes its messages into.
+///
+/// This decouples receiving messages from processing them: the executor only moves each
+/// message into the buffer, and the application pops them at its own rate, e.g. from a
+/// processing thread. When the buffer is full, the [`OverflowPolicy`] decides which message
+/// is kept.
+///
+/// # Example
+/// ```
+/// # use rclrs::*;
+/// # use rclrs::msg::Time;
+/// # use std::sync::Arc;
+/// # let context = Context::new([])?;
+/// # let node = create_node(&context, "buffer_node")?;
+/// let buffer = Arc::new(MessageBuffer::new(100, OverflowPolicy::DropOldest));
+/// let _subscription = node.create_buffered_subscription::<Time>(
+///     "topic",
+///     QOS_PROFILE_DEFAULT,
+///     Arc::clone(&buffer),
+/// )?;
+/// while let Some((_message, _info)) = buffer.pop() {
+///     // Process the message
+/// }
+/// # Ok::<(), RclrsError>(())
+/// ```
+///
+/// [1]: crate::Node::create_buffered_subscription
+pub struct MessageBuffer<T> {
+    capacity: usize,
+    policy: OverflowPolicy,
+    state: Mutex<BufferState<T>>,
+    // Notified whenever a message is popped, for pushes that are waiting for room.
+    popped: Condvar,
+    // Notified whenever a message is pushed, for pops that are waiting for a message.
+    pushed: Condvar,
+}
+
+struct BufferState<T> {
+    messages: VecDeque<(T, MessageInfo)>,
+    dropped: u64,
+}
+
+impl<T> MessageBuffer<T> {
+    /// Creates an empty buffer that holds at most `capacity` messages.
+    ///
+    /// The capacity is at least 1.
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            policy,
+            state: Mutex::new(BufferState {
+                messages: VecDeque::with_capacity(capacity),
+                dropped: 0,
+            }),
+            popped: Condvar::new(),
+            pushed: Condvar::new(),
+        }
+    }
+
+    /// Returns the maximum number of messages in the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the overflow policy of the buffer.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Returns the number of messages in the buffer.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().messages.len()
+    }
+
+    /// Returns whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of messages that were dropped because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
+    /// Removes the oldest message from the buffer, or returns `None` if it is empty.
+    pub fn pop(&self) -> Option<(T, MessageInfo)> {
+        let message = self.state.lock().unwrap().messages.pop_front();
+        if message.is_some() {
+            self.popped.notify_one();
+        }
+        message
+    }
+
+    /// Removes the oldest message from the buffer, waiting for at most `timeout` for a message
+    /// to arrive if it is empty.
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<(T, MessageInfo)> {
+        let state = self.state.lock().unwrap();
+        let (mut state, _) = self
+            .pushed
+            .wait_timeout_while(state, timeout, |state| state.messages.is_empty())
+            .unwrap();
+        let message = state.messages.pop_front();
+        drop(state);
+        if message.is_some() {
+            self.popped.notify_one();
+        }
+        message
+    }
+
+    /// Removes all messages from the buffer, oldest first.
+    pub fn drain(&self) -> Vec<(T, MessageInfo)> {
+        let messages: Vec<_> = self.state.lock().unwrap().messages.drain(..).collect();
+        self.popped.notify_all();
+        messages
+    }
+
+    // Adds a message according to the overflow policy.
+    pub(crate) fn push(&self, message: T, info: MessageInfo) {
+        let mut state = self.state.lock().unwrap();
+        if state.messages.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::DropOldest => {
+                    state.messages.pop_front();
+                    state.dropped += 1;
+                }
+                OverflowPolicy::DropNewest => {
+                    state.dropped += 1;
+                    return;
+                }
+                OverflowPolicy::Block => {
+                    state = self
+                        .popped
+                        .wait_while(state, |state| state.messages.len() >= self.capacity)
+                        .unwrap();
+                }
+            }
+        }
+        state.messages.push_back((message, info));
+        drop(state);
+        self.pushed.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rcl_bindings::RMW_GID_STORAGE_SIZE, spin_once, test_helpers::*, PublisherGid, RclrsError,
+        QOS_PROFILE_DEFAULT,
+    };
+    use std::{
+        sync::Arc,
+        thread,
+        time::{Duration, Instant},
+    };
+    use test_msgs::msg;
+
+    fn message_info() -> MessageInfo {
+        MessageInfo {
+            source_timestamp: None,
+            received_timestamp: None,
+            publication_sequence_number: 0,
+            reception_sequence_number: 0,
+            publisher_gid: PublisherGid {
+                data: [0; RMW_GID_STORAGE_SIZE],
+                implementation_identifier: std::ptr::null(),
+            },
+            from_intra_process: false,
+        }
+    }
+
+    #[test]
+    fn traits() {
+        assert_send::<MessageBuffer<msg::BasicTypes>>();
+        assert_sync::<MessageBuffer<msg::BasicTypes>>();
+    }
+
+    // Publishes the values 1..=5 to a buffered subscription with a capacity of 3, and returns the
+    // values in the buffer once all messages have been received.
+    fn receive_into_buffer(policy: OverflowPolicy, topic: &str) -> Result<Vec<i32>, RclrsError> {
+        let graph = construct_test_graph(topic)?;
+        let buffer = Arc::new(MessageBuffer::new(3, policy));
+        let _subscription = graph
+            .node2
+            .create_buffered_subscription::<msg::BasicTypes>(
+                topic,
+                QOS_PROFILE_DEFAULT,
+                Arc::clone(&buffer),
+            )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::BasicTypes>(topic, QOS_PROFILE_DEFAULT)?;
+        let start = Instant::now();
+        while publisher.get_subscription_count()? == 0 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        for value in 1..=5 {
+            publisher.publish(msg::BasicTypes {
+                int32_value: value,
+                ..Default::default()
+            })?;
+        }
+        while buffer.len() as u64 + buffer.dropped() < 5 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            ignore_timeout(spin_once(
+                Arc::clone(&graph.node2),
+                Some(Duration::from_millis(10)),
+            ))?;
+        }
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.dropped(), 2);
+        Ok(buffer
+            .drain()
+            .into_iter()
+            .map(|(msg, _)| msg.int32_value)
+            .collect())
+    }
+
+    #[test]
+    fn test_drop_oldest() -> Result<(), RclrsError> {
+        let values = receive_into_buffer(OverflowPolicy::DropOldest, "test_buffer_drop_oldest")?;
+        assert_eq!(values, vec![3, 4, 5]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_drop_newest() -> Result<(), RclrsError> {
+        let values = receive_into_buffer(OverflowPolicy::DropNewest, "test_buffer_drop_newest")?;
+        assert_eq!(values, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_block() {
+        let buffer = Arc::new(MessageBuffer::new(2, OverflowPolicy::Block));
+        let pusher = {
+            let buffer = Arc::clone(&buffer);
+            thread::spawn(move || {
+                for value in 1..=4 {
+                    buffer.push(value, message_info());
+                }
+            })
+        };
+        let mut values = Vec::new();
+        while values.len() < 4 {
+            let (value, _) = buffer
+                .pop_timeout(Duration::from_secs(5))
+                .expect("The pushing thread is stuck");
+            assert!(buffer.len() <= 2);
+            values.push(value);
+        }
+        pusher.join().unwrap();
+        assert_eq!(values, vec![1, 2, 3, 4]);
+        assert_eq!(buffer.dropped(), 0);
+    }
+}
@@ -1,6 +1,10 @@
-use std::time::{Duration, SystemTime};
+use std::{
+    ffi::CStr,
+    hash::{Hash, Hasher},
+    time::{Duration, SystemTime},
+};
 
-use crate::rcl_bindings::*;
+use crate::{rcl_bindings::*, Clock, Time};
 
 /// An identifier for a publisher in the local context.
 ///
@@ -24,7 +28,7 @@ use crate::rcl_bindings::*;
 /// behavior is not defined here.
 /// However, this should be avoided, if at all possible, by the RMW implementation,
 /// and should be unlikely to happen in practice.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub struct PublisherGid {
     /// Bytes identifying a publisher in the RMW implementation.
     pub data: [u8; RMW_GID_STORAGE_SIZE],
@@ -40,11 +44,56 @@ pub struct PublisherGid {
     pub implementation_identifier: *const std::os::raw::c_char,
 }
 
+impl PublisherGid {
+    /// Returns the RMW implementation's name as a string slice.
+    ///
+    /// Returns `None` if the implementation identifier is a null pointer or is not valid UTF-8.
+    pub fn implementation_identifier_str(&self) -> Option<&str> {
+        self.implementation_identifier_cstr()?.to_str().ok()
+    }
+
+    fn implementation_identifier_cstr(&self) -> Option<&CStr> {
+        if self.implementation_identifier.is_null() {
+            return None;
+        }
+        // SAFETY: The pointer is not null, and the RMW implementation identifier is a
+        // nul-terminated static string that outlives any GID referring to it.
+        Some(unsafe { CStr::from_ptr(self.implementation_identifier) })
+    }
+}
+
+// The implementation identifier is compared and hashed by its contents, not by its address,
+// since the same identifier may be reachable through different pointers.
+impl PartialEq for PublisherGid {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+            && self.implementation_identifier_cstr() == other.implementation_identifier_cstr()
+    }
+}
+
+impl Eq for PublisherGid {}
+
+impl Hash for PublisherGid {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+        self.implementation_identifier_cstr().hash(state);
+    }
+}
+
 // SAFETY: The implementation identifier doesn't care about which thread it's read from.
 unsafe impl Send for PublisherGid {}
 // SAFETY: A char does not have interior mutability.
 unsafe impl Sync for PublisherGid {}
 
+/// Converts an `rmw` timestamp in nanoseconds since the epoch, where 0 means "not set".
+pub(crate) fn timestamp_to_system_time(timestamp: rmw_time_point_value_t) -> Option<SystemTime> {
+    match timestamp {
+        0 => None,
+        ts if ts < 0 => Some(SystemTime::UNIX_EPOCH - Duration::from_nanos(ts.unsigned_abs())),
+        ts => Some(SystemTime::UNIX_EPOCH + Duration::from_nanos(ts.unsigned_abs())),
+    }
+}
+
 /// Additional information about a received message.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MessageInfo {
@@ -100,20 +149,17 @@ pub struct MessageInfo {
     pub reception_sequence_number: u64,
     /// An identifier for the publisher that sent the message.
     pub publisher_gid: PublisherGid,
+    /// Whether this message was delivered through the intra-process path.
+    ///
+    /// If `false`, the message was received over the RMW implementation's
+    /// inter-process transport.
+    pub from_intra_process: bool,
 }
 
 impl MessageInfo {
     pub(crate) fn from_rmw_message_info(rmw_message_info: &rmw_message_info_t) -> Self {
-        let source_timestamp = match rmw_message_info.source_timestamp {
-            0 => None,
-            ts if ts < 0 => Some(SystemTime::UNIX_EPOCH - Duration::from_nanos(ts.unsigned_abs())),
-            ts => Some(SystemTime::UNIX_EPOCH + Duration::from_nanos(ts.unsigned_abs())),
-        };
-        let received_timestamp = match rmw_message_info.received_timestamp {
-            0 => None,
-            ts if ts < 0 => Some(SystemTime::UNIX_EPOCH - Duration::from_nanos(ts.unsigned_abs())),
-            ts => Some(SystemTime::UNIX_EPOCH + Duration::from_nanos(ts.unsigned_abs())),
-        };
+        let source_timestamp = timestamp_to_system_time(rmw_message_info.source_timestamp);
+        let received_timestamp = timestamp_to_system_time(rmw_message_info.received_timestamp);
         let publisher_gid = PublisherGid {
             data: rmw_message_info.publisher_gid.data,
             implementation_identifier: rmw_message_info.publisher_gid.implementation_identifier,
@@ -124,7 +170,42 @@ impl MessageInfo {
             publication_sequence_number: rmw_message_info.publication_sequence_number,
             reception_sequence_number: rmw_message_info.reception_sequence_number,
             publisher_gid,
+            from_intra_process: rmw_message_info.from_intra_process,
+        }
+    }
+
+    /// Returns the [`source_timestamp`][1] as a [`Time`] of the given clock.
+    ///
+    /// The source timestamp is taken from the wall time of the publisher, so this should usually
+    /// be a [`Clock::system()`] or a ROS clock that is not driven by simulated time.
+    ///
+    /// [1]: MessageInfo::source_timestamp
+    pub fn source_time(&self, clock: &Clock) -> Option<Time> {
+        self.source_timestamp
+            .map(|source_timestamp| Time::from_system_time(source_timestamp, clock))
+    }
+
+    /// Returns the number of messages the publisher sent between `previous` and this message.
+    ///
+    /// This applies the arithmetic described in the documentation of
+    /// [`publication_sequence_number`][1], i.e. `psn2 - psn1 - 1`, with wrap-around of the
+    /// sequence number taken into account.
+    ///
+    /// Returns `None` if the two messages were sent by different publishers, if either
+    /// sequence number is [`u64::MAX`] (meaning the RMW implementation doesn't support
+    /// sequence numbers), or if both messages carry the same sequence number.
+    ///
+    /// [1]: MessageInfo::publication_sequence_number
+    pub fn messages_missed_since(&self, previous: &MessageInfo) -> Option<u64> {
+        if self.publisher_gid != previous.publisher_gid {
+            return None;
         }
+        let psn1 = previous.publication_sequence_number;
+        let psn2 = self.publication_sequence_number;
+        if psn1 == u64::MAX || psn2 == u64::MAX || psn1 == psn2 {
+            return None;
+        }
+        Some(psn2.wrapping_sub(psn1).wrapping_sub(1))
     }
 }
 
@@ -150,6 +231,102 @@ mod tests {
             message_info.source_timestamp.unwrap() + Duration::from_nanos(2_000_000_000),
             message_info.received_timestamp.unwrap()
         );
+        // A source timestamp before the epoch saturates at the epoch
+        let source_time = message_info.source_time(&Clock::system()).unwrap();
+        assert_eq!(source_time.nsec, 0);
+        assert!(!message_info.from_intra_process);
+    }
+
+    #[test]
+    fn implementation_identifier_str() {
+        let mut gid = PublisherGid {
+            data: [0; RMW_GID_STORAGE_SIZE],
+            implementation_identifier: std::ptr::null(),
+        };
+        assert_eq!(gid.implementation_identifier_str(), None);
+
+        let identifier = b"rmw_test_implementation\0";
+        gid.implementation_identifier = identifier.as_ptr().cast();
+        assert_eq!(
+            gid.implementation_identifier_str(),
+            Some("rmw_test_implementation")
+        );
+
+        let invalid_utf8 = b"\xff\xfe\0";
+        gid.implementation_identifier = invalid_utf8.as_ptr().cast();
+        assert_eq!(gid.implementation_identifier_str(), None);
+    }
+
+    #[test]
+    fn gid_hash_uses_identifier_contents() {
+        use std::collections::HashSet;
+
+        // Two distinct buffers with the same contents, so the pointers differ.
+        let identifier_a = b"rmw_test_implementation\0".to_vec();
+        let identifier_b = b"rmw_test_implementation\0".to_vec();
+        assert_ne!(identifier_a.as_ptr(), identifier_b.as_ptr());
+        let gid_a = PublisherGid {
+            data: [7; RMW_GID_STORAGE_SIZE],
+            implementation_identifier: identifier_a.as_ptr().cast(),
+        };
+        let gid_b = PublisherGid {
+            data: [7; RMW_GID_STORAGE_SIZE],
+            implementation_identifier: identifier_b.as_ptr().cast(),
+        };
+        assert_eq!(gid_a, gid_b);
+
+        let mut set = HashSet::new();
+        set.insert(gid_a.clone());
+        set.insert(gid_b);
+        assert_eq!(set.len(), 1);
+
+        let gid_c = PublisherGid {
+            data: [8; RMW_GID_STORAGE_SIZE],
+            ..gid_a
+        };
+        set.insert(gid_c);
+        assert_eq!(set.len(), 2);
+    }
+
+    fn message_info_with_sequence_number(
+        publication_sequence_number: u64,
+        gid_byte: u8,
+    ) -> MessageInfo {
+        MessageInfo {
+            source_timestamp: None,
+            received_timestamp: None,
+            publication_sequence_number,
+            reception_sequence_number: 0,
+            publisher_gid: PublisherGid {
+                data: [gid_byte; RMW_GID_STORAGE_SIZE],
+                implementation_identifier: std::ptr::null(),
+            },
+            from_intra_process: false,
+        }
+    }
+
+    #[test]
+    fn messages_missed_since() {
+        let first = message_info_with_sequence_number(10, 1);
+        let consecutive = message_info_with_sequence_number(11, 1);
+        let later = message_info_with_sequence_number(15, 1);
+        assert_eq!(consecutive.messages_missed_since(&first), Some(0));
+        assert_eq!(later.messages_missed_since(&first), Some(4));
+        assert_eq!(first.messages_missed_since(&first), None);
+
+        // Messages from different publishers can't be compared
+        let other_publisher = message_info_with_sequence_number(15, 2);
+        assert_eq!(other_publisher.messages_missed_since(&first), None);
+
+        // Sequence numbers are unsupported
+        let unsupported = message_info_with_sequence_number(u64::MAX, 1);
+        assert_eq!(unsupported.messages_missed_since(&first), None);
+        assert_eq!(first.messages_missed_since(&unsupported), None);
+
+        // Wrap-around
+        let before_wrap = message_info_with_sequence_number(u64::MAX - 2, 1);
+        let after_wrap = message_info_with_sequence_number(1, 1);
+        assert_eq!(after_wrap.messages_missed_since(&before_wrap), Some(3));
     }
 
     #[test]
@@ -0,0 +1,369 @@
+use std::{any::Any, ffi::CString, fmt, os::raw::c_char, sync::Arc, time::Duration};
+
+use rosidl_runtime_rs::Message;
+
+use super::statistics::TopicStatisticsOptions;
+#[cfg(not(ros_distro = "humble"))]
+use crate::MatchedStatus;
+use crate::{
+    CallbackGroup, LivelinessChangedStatus, MessageLostStatus, QoSProfile, RclrsError,
+    RequestedDeadlineMissedStatus, UniqueNetworkFlowEndpoints, QOS_PROFILE_DEFAULT,
+};
+
+/// Options for creating a [`Subscription`][1].
+///
+/// Use this together with [`Node::create_subscription_with_options()`][2] to configure
+/// features beyond the quality of service profile.
+///
+/// # Example
+/// ```
+/// # use rclrs::{SubscriptionOptions, QOS_PROFILE_SENSOR_DATA};
+/// let options = SubscriptionOptions::new(QOS_PROFILE_SENSOR_DATA)
+///     .content_filter("data > %0", &[String::from("10")]);
+/// assert!(options.get_content_filter().is_some());
+/// ```
+///
+/// [1]: crate::Subscription
+/// [2]: crate::Node::create_subscription_with_options
+pub struct SubscriptionOptions {
+    /// The quality of service profile of the subscription.
+    pub qos: QoSProfile,
+    content_filter: Option<ContentFilter>,
+    pub(crate) unique_network_flow_endpoints: UniqueNetworkFlowEndpoints,
+    pub(crate) callback_group: Option<Arc<CallbackGroup>>,
+    pub(crate) event_callbacks: SubscriptionEventCallbacks,
+    // A `MessageFilter<T>`, which is type-erased since the options are not generic over the
+    // message type.
+    pub(crate) message_filter: Option<Box<dyn Any + Send + Sync>>,
+    pub(crate) topic_statistics: Option<TopicStatisticsOptions>,
+    // Overrides the intra-process setting of the node, if set.
+    pub(crate) use_intra_process: Option<bool>,
+}
+
+/// A predicate that decides whether a message is passed to the callback of a subscription.
+pub(crate) type MessageFilter<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// The callbacks for the events of a subscription.
+#[derive(Default)]
+pub(crate) struct SubscriptionEventCallbacks {
+    pub(crate) message_lost: Option<Box<dyn FnMut(MessageLostStatus) + Send>>,
+    pub(crate) requested_deadline_missed:
+        Option<Box<dyn FnMut(RequestedDeadlineMissedStatus) + Send>>,
+    #[cfg(not(ros_distro = "humble"))]
+    pub(crate) matched: Option<Box<dyn FnMut(MatchedStatus) + Send>>,
+    pub(crate) liveliness_changed: Option<Box<dyn FnMut(LivelinessChangedStatus) + Send>>,
+}
+
+impl fmt::Debug for SubscriptionOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("SubscriptionOptions");
+        debug
+            .field("qos", &self.qos)
+            .field("content_filter", &self.content_filter)
+            .field(
+                "unique_network_flow_endpoints",
+                &self.unique_network_flow_endpoints,
+            )
+            .field("callback_group", &self.callback_group)
+            .field(
+                "message_lost_callback",
+                &self.event_callbacks.message_lost.is_some(),
+            )
+            .field(
+                "requested_deadline_missed_callback",
+                &self.event_callbacks.requested_deadline_missed.is_some(),
+            );
+        #[cfg(not(ros_distro = "humble"))]
+        debug.field("matched_callback", &self.event_callbacks.matched.is_some());
+        debug
+            .field(
+                "liveliness_changed_callback",
+                &self.event_callbacks.liveliness_changed.is_some(),
+            )
+            .field("filter", &self.message_filter.is_some())
+            .field("topic_statistics", &self.topic_statistics)
+            .field("use_intra_process", &self.use_intra_process)
+            .finish()
+    }
+}
+
+impl Default for SubscriptionOptions {
+    fn default() -> Self {
+        Self::new(QOS_PROFILE_DEFAULT)
+    }
+}
+
+impl From<QoSProfile> for SubscriptionOptions {
+    fn from(qos: QoSProfile) -> Self {
+        Self::new(qos)
+    }
+}
+
+impl SubscriptionOptions {
+    /// Creates subscription options with the given quality of service profile.
+    pub fn new(qos: QoSProfile) -> Self {
+        Self {
+            qos,
+            content_filter: None,
+            unique_network_flow_endpoints: UniqueNetworkFlowEndpoints::default(),
+            callback_group: None,
+            event_callbacks: SubscriptionEventCallbacks::default(),
+            message_filter: None,
+            topic_statistics: None,
+            use_intra_process: None,
+        }
+    }
+
+    /// Sets the quality of service profile.
+    pub fn qos(mut self, qos: QoSProfile) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Filters messages in the middleware, so that only messages matching `expression` are
+    /// delivered to the subscription.
+    ///
+    /// The expression uses the SQL-like syntax of DDS content-filtered topics, e.g.
+    /// `"data > %0 AND data < %1"`. The placeholders `%0`, `%1`, … are replaced by the
+    /// corresponding element of `parameters`.
+    ///
+    /// Not all RMW implementations support content filtering. If it is not supported,
+    /// creating the subscription returns an [`Unsupported`][1] error.
+    ///
+    /// [1]: crate::RclReturnCode::Unsupported
+    pub fn content_filter(mut self, expression: &str, parameters: &[String]) -> Self {
+        self.content_filter = Some(ContentFilter {
+            expression: expression.to_string(),
+            parameters: parameters.to_vec(),
+        });
+        self
+    }
+
+    /// Discards messages for which `predicate` returns `false`, so that the callback of the
+    /// subscription never sees them.
+    ///
+    /// Unlike [`content_filter()`][1], this runs in the subscription after the message has been
+    /// taken from the middleware, so it works with every RMW implementation and can use
+    /// arbitrary Rust code, at the cost of transferring and deserializing the discarded
+    /// messages. Messages delivered through intra-process communication are filtered as well.
+    ///
+    /// The filter does not apply to callbacks taking loaned or serialized messages, nor to
+    /// messages fetched directly with [`Subscription::take()`][2].
+    ///
+    /// The message type `T` must be the message type of the subscription, otherwise creating
+    /// the subscription returns an [`InvalidArgument`][3] error.
+    ///
+    /// # Example
+    /// ```
+    /// # use rclrs::{SubscriptionOptions, QOS_PROFILE_DEFAULT};
+    /// # use rclrs::msg::Time;
+    /// let options = SubscriptionOptions::new(QOS_PROFILE_DEFAULT).filter(|msg: &Time| msg.sec > 0);
+    /// ```
+    ///
+    /// [1]: Self::content_filter
+    /// [2]: crate::Subscription::take
+    /// [3]: crate::RclReturnCode::InvalidArgument
+    pub fn filter<T: Message>(
+        mut self,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let predicate: MessageFilter<T> = Box::new(predicate);
+        self.message_filter = Some(Box::new(predicate));
+        self
+    }
+
+    /// Publishes statistics about the received messages on `topic` every `period`.
+    ///
+    /// Like topic statistics in `rclcpp`, two `statistics_msgs/MetricsMessage`s are published
+    /// per period, with the average, minimum, maximum, standard deviation and number of samples
+    /// of
+    /// - the message age, i.e. the time from the [source timestamp][1] to the
+    ///   [received timestamp][2] of each message, and
+    /// - the message period, i.e. the time between receiving two consecutive messages,
+    ///
+    /// both in milliseconds. The statistics are reset after each publication. The conventional
+    /// topic is `/statistics`.
+    ///
+    /// Messages are counted when they are taken from the middleware, including messages that are
+    /// discarded by a [`filter()`][3]. Messages fetched directly with
+    /// [`Subscription::take()`][4] are not counted.
+    ///
+    /// [1]: crate::MessageInfo::source_timestamp
+    /// [2]: crate::MessageInfo::received_timestamp
+    /// [3]: Self::filter
+    /// [4]: crate::Subscription::take
+    pub fn enable_topic_statistics(mut self, topic: &str, period: Duration) -> Self {
+        self.topic_statistics = Some(TopicStatisticsOptions {
+            topic: topic.to_string(),
+            period,
+        });
+        self
+    }
+
+    /// Sets whether the subscription requires unique network flow endpoints.
+    ///
+    /// The default is [`UniqueNetworkFlowEndpoints::SystemDefault`]. With
+    /// [`UniqueNetworkFlowEndpoints::StrictlyRequired`], creating the subscription returns an error if
+    /// the RMW implementation cannot provide unique network flow endpoints. The endpoints can be
+    /// inspected with [`Subscription::get_network_flow_endpoints()`][1].
+    ///
+    /// [1]: crate::Subscription::get_network_flow_endpoints
+    pub fn unique_network_flow_endpoints(
+        mut self,
+        requirement: UniqueNetworkFlowEndpoints,
+    ) -> Self {
+        self.unique_network_flow_endpoints = requirement;
+        self
+    }
+
+    /// Sets whether the subscription uses intra-process communication, overriding the setting of
+    /// its node, see [`NodeBuilder::use_intra_process_comms()`][1].
+    ///
+    /// Messages only take the intra-process path when both the publisher and the subscription
+    /// use intra-process communication. E.g. a subscription with intra-process communication
+    /// disabled receives all of its messages through the middleware, like a subscription in
+    /// another process.
+    ///
+    /// [1]: crate::NodeBuilder::use_intra_process_comms
+    pub fn use_intra_process(mut self, enable: bool) -> Self {
+        self.use_intra_process = Some(enable);
+        self
+    }
+
+    /// Assigns the subscription to a [`CallbackGroup`].
+    ///
+    /// If no group is set, the subscription belongs to the default group of its node.
+    pub fn callback_group(mut self, callback_group: Arc<CallbackGroup>) -> Self {
+        self.callback_group = Some(callback_group);
+        self
+    }
+
+    /// Sets a callback that is called when the middleware reports that messages were lost.
+    ///
+    /// Whether lost messages can be detected depends on the RMW implementation and on the
+    /// reliability setting of the QoS profile. If the RMW implementation does not support this
+    /// event, creating the subscription returns an [`Unsupported`][1] error.
+    ///
+    /// The callback is executed when spinning the node.
+    ///
+    /// [1]: crate::RclReturnCode::Unsupported
+    pub fn message_lost_callback(
+        mut self,
+        callback: impl FnMut(MessageLostStatus) + Send + 'static,
+    ) -> Self {
+        self.event_callbacks.message_lost = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when the subscription did not receive a message within
+    /// the [`deadline`][1] of its QoS profile.
+    ///
+    /// If the RMW implementation does not support this event, creating the subscription returns
+    /// an [`Unsupported`][2] error.
+    ///
+    /// The callback is executed when spinning the node.
+    ///
+    /// [1]: crate::QoSProfile::deadline
+    /// [2]: crate::RclReturnCode::Unsupported
+    pub fn requested_deadline_missed_callback(
+        mut self,
+        callback: impl FnMut(RequestedDeadlineMissedStatus) + Send + 'static,
+    ) -> Self {
+        self.event_callbacks.requested_deadline_missed = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when a publisher is matched with or unmatched from
+    /// the subscription.
+    ///
+    /// If the RMW implementation does not support this event, creating the subscription returns
+    /// an [`Unsupported`][1] error.
+    ///
+    /// The callback is executed when spinning the node.
+    ///
+    /// This is only available since ROS 2 Iron.
+    ///
+    /// [1]: crate::RclReturnCode::Unsupported
+    #[cfg(not(ros_distro = "humble"))]
+    pub fn matched_callback(
+        mut self,
+        callback: impl FnMut(MatchedStatus) + Send + 'static,
+    ) -> Self {
+        self.event_callbacks.matched = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called when a matched publisher becomes alive or stops being
+    /// alive, i.e. does not assert its liveliness within the lease duration anymore.
+    ///
+    /// This allows detecting publishers that stopped working without being destroyed. See
+    /// [`QoSLivelinessPolicy`][1] for how publishers assert their liveliness.
+    ///
+    /// If the RMW implementation does not support this event, creating the subscription returns
+    /// an [`Unsupported`][2] error.
+    ///
+    /// The callback is executed when spinning the node.
+    ///
+    /// [1]: crate::QoSLivelinessPolicy
+    /// [2]: crate::RclReturnCode::Unsupported
+    pub fn liveliness_changed_callback(
+        mut self,
+        callback: impl FnMut(LivelinessChangedStatus) + Send + 'static,
+    ) -> Self {
+        self.event_callbacks.liveliness_changed = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the content filter, if one was set.
+    pub fn get_content_filter(&self) -> Option<&ContentFilter> {
+        self.content_filter.as_ref()
+    }
+}
+
+/// A filter expression and its parameters for a content-filtered subscription.
+///
+/// See [`SubscriptionOptions::content_filter()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentFilter {
+    /// The SQL-like filter expression.
+    pub expression: String,
+    /// The values that replace the `%N` placeholders in the expression.
+    pub parameters: Vec<String>,
+}
+
+impl ContentFilter {
+    pub(crate) fn to_c_strings(&self) -> Result<(CString, Vec<CString>), RclrsError> {
+        let to_c_string = |s: &str| {
+            CString::new(s).map_err(|err| RclrsError::StringContainsNul { err, s: s.into() })
+        };
+        let expression = to_c_string(&self.expression)?;
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|p| to_c_string(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((expression, parameters))
+    }
+}
+
+// Returns pointers into the given strings, suitable for an `argv`-style rcl argument.
+pub(crate) fn c_string_pointers(strings: &[CString]) -> Vec<*const c_char> {
+    strings.iter().map(|s| s.as_ptr()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_filter_with_nul_is_rejected() {
+        let filter = ContentFilter {
+            expression: String::from("data > %0"),
+            parameters: vec![String::from("1\0")],
+        };
+        assert!(matches!(
+            filter.to_c_strings(),
+            Err(RclrsError::StringContainsNul { .. })
+        ));
+    }
+}
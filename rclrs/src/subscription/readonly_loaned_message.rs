@@ -42,7 +42,7 @@ where
                 &*self.subscription.handle.lock(),
                 self.msg_ptr as *mut _,
             )
-            .ok()
+            .ok_for("rcl_return_loaned_message_from_subscription")
             .unwrap();
         }
     }
@@ -0,0 +1,306 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    vendor::{
+        builtin_interfaces,
+        statistics_msgs::msg::{MetricsMessage, StatisticDataPoint, StatisticDataType},
+    },
+    MessageInfo, Node, RclrsError, Timer, QOS_PROFILE_DEFAULT,
+};
+
+/// The topic and publishing period for the statistics of a subscription.
+///
+/// See [`SubscriptionOptions::enable_topic_statistics()`][1].
+///
+/// [1]: crate::SubscriptionOptions::enable_topic_statistics
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TopicStatisticsOptions {
+    pub(crate) topic: String,
+    pub(crate) period: Duration,
+}
+
+/// Publishes the statistics of a subscription periodically.
+///
+/// This is owned by the subscription, and stops publishing when the subscription is dropped.
+pub(crate) struct TopicStatistics {
+    pub(crate) collector: Arc<Mutex<TopicStatisticsCollector>>,
+    _timer: Arc<Timer>,
+}
+
+impl TopicStatistics {
+    pub(crate) fn new(node: &Node, options: TopicStatisticsOptions) -> Result<Self, RclrsError> {
+        let clock = node.get_clock();
+        let window_start = clock.now().to_ros_msg().unwrap_or_default();
+        let collector = Arc::new(Mutex::new(TopicStatisticsCollector::new(
+            node.name(),
+            window_start,
+        )));
+        let publisher =
+            node.create_publisher::<MetricsMessage>(&options.topic, QOS_PROFILE_DEFAULT)?;
+        let logger_name = node.logger_name();
+        let collector_in_timer = Arc::clone(&collector);
+        let timer = node.create_timer(options.period, move || {
+            let window_stop = clock.now().to_ros_msg().unwrap_or_default();
+            let metrics = collector_in_timer.lock().unwrap().take_metrics(window_stop);
+            for message in metrics {
+                if let Err(err) = publisher.publish(message) {
+                    crate::log_warn!(
+                        logger_name.as_str(),
+                        "Failed to publish topic statistics: {}",
+                        err
+                    );
+                }
+            }
+        })?;
+        Ok(Self {
+            collector,
+            _timer: timer,
+        })
+    }
+}
+
+/// Accumulates the age and the period of the messages received by a subscription, over a
+/// window that ends whenever the metrics are taken.
+///
+/// The age of a message is the time between its [`source_timestamp`][1] and its
+/// [`received_timestamp`][2]. The period is the time between the receipt of two consecutive
+/// messages. Both are reported in milliseconds, like in `rclcpp`.
+///
+/// [1]: MessageInfo::source_timestamp
+/// [2]: MessageInfo::received_timestamp
+pub(crate) struct TopicStatisticsCollector {
+    node_name: String,
+    message_age: Moments,
+    message_period: Moments,
+    last_received: Option<SystemTime>,
+    window_start: builtin_interfaces::msg::Time,
+}
+
+impl TopicStatisticsCollector {
+    pub(crate) fn new(node_name: String, window_start: builtin_interfaces::msg::Time) -> Self {
+        Self {
+            node_name,
+            message_age: Moments::default(),
+            message_period: Moments::default(),
+            last_received: None,
+            window_start,
+        }
+    }
+
+    /// Adds a received message to the statistics.
+    ///
+    /// If the RMW implementation doesn't provide a received timestamp, the current time is used.
+    pub(crate) fn record(&mut self, message_info: &MessageInfo) {
+        let received = message_info
+            .received_timestamp
+            .unwrap_or_else(SystemTime::now);
+        if let Some(source) = message_info.source_timestamp {
+            // A publisher with a clock ahead of ours produces no meaningful age.
+            if let Ok(age) = received.duration_since(source) {
+                self.message_age.add(as_millis_f64(age));
+            }
+        }
+        if let Some(last_received) = self.last_received {
+            if let Ok(period) = received.duration_since(last_received) {
+                self.message_period.add(as_millis_f64(period));
+            }
+        }
+        self.last_received = Some(received);
+    }
+
+    /// Returns the metrics for the message age and the message period in the current window,
+    /// and starts a new window.
+    pub(crate) fn take_metrics(
+        &mut self,
+        window_stop: builtin_interfaces::msg::Time,
+    ) -> [MetricsMessage; 2] {
+        let window_start = std::mem::replace(&mut self.window_start, window_stop.clone());
+        let metrics = |metrics_source: &str, moments: Moments| MetricsMessage {
+            measurement_source_name: self.node_name.clone(),
+            metrics_source: metrics_source.to_string(),
+            unit: String::from("ms"),
+            window_start: window_start.clone(),
+            window_stop: window_stop.clone(),
+            statistics: moments.data_points(),
+        };
+        let result = [
+            metrics("message_age", self.message_age),
+            metrics("message_period", self.message_period),
+        ];
+        self.message_age = Moments::default();
+        self.message_period = Moments::default();
+        result
+    }
+}
+
+fn as_millis_f64(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// The running minimum, maximum, mean and variance of a metric, using Welford's algorithm.
+#[derive(Clone, Copy, Debug, Default)]
+struct Moments {
+    count: u64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    sum_of_squared_differences: f64,
+}
+
+impl Moments {
+    fn add(&mut self, sample: f64) {
+        if self.count == 0 {
+            self.min = sample;
+            self.max = sample;
+        } else {
+            self.min = self.min.min(sample);
+            self.max = self.max.max(sample);
+        }
+        self.count += 1;
+        let delta = sample - self.mean;
+        self.mean += delta / self.count as f64;
+        self.sum_of_squared_differences += delta * (sample - self.mean);
+    }
+
+    // Without samples, all statistics except the sample count are NaN, as in rclcpp.
+    fn data_points(&self) -> Vec<StatisticDataPoint> {
+        let (average, minimum, maximum, stddev) = if self.count == 0 {
+            (f64::NAN, f64::NAN, f64::NAN, f64::NAN)
+        } else {
+            let variance = self.sum_of_squared_differences / self.count as f64;
+            (self.mean, self.min, self.max, variance.sqrt())
+        };
+        [
+            (StatisticDataType::STATISTICS_DATA_TYPE_AVERAGE, average),
+            (StatisticDataType::STATISTICS_DATA_TYPE_MINIMUM, minimum),
+            (StatisticDataType::STATISTICS_DATA_TYPE_MAXIMUM, maximum),
+            (StatisticDataType::STATISTICS_DATA_TYPE_STDDEV, stddev),
+            (
+                StatisticDataType::STATISTICS_DATA_TYPE_SAMPLE_COUNT,
+                self.count as f64,
+            ),
+        ]
+        .into_iter()
+        .map(|(data_type, data)| StatisticDataPoint { data_type, data })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    fn message_info(source_ms: u64, received_ms: u64) -> MessageInfo {
+        MessageInfo {
+            source_timestamp: Some(SystemTime::UNIX_EPOCH + Duration::from_millis(source_ms)),
+            received_timestamp: Some(SystemTime::UNIX_EPOCH + Duration::from_millis(received_ms)),
+            publication_sequence_number: u64::MAX,
+            reception_sequence_number: u64::MAX,
+            publisher_gid: crate::PublisherGid {
+                data: [0; crate::rcl_bindings::RMW_GID_STORAGE_SIZE],
+                implementation_identifier: std::ptr::null(),
+            },
+            from_intra_process: false,
+        }
+    }
+
+    fn data(metrics: &MetricsMessage, data_type: u8) -> f64 {
+        metrics
+            .statistics
+            .iter()
+            .find(|point| point.data_type == data_type)
+            .unwrap()
+            .data
+    }
+
+    #[test]
+    fn computes_message_age_and_period() {
+        let mut collector = TopicStatisticsCollector::new(
+            String::from("node"),
+            builtin_interfaces::msg::Time { sec: 1, nanosec: 0 },
+        );
+        // Messages received 100, 200 and 150 ms apart, with ages of 5 to 15 ms
+        for (source_ms, received_ms) in [(995, 1000), (1090, 1100), (1285, 1300), (1435, 1450)] {
+            collector.record(&message_info(source_ms, received_ms));
+        }
+        let window_stop = builtin_interfaces::msg::Time { sec: 2, nanosec: 0 };
+        let [age, period] = collector.take_metrics(window_stop.clone());
+
+        assert_eq!(period.metrics_source, "message_period");
+        assert_eq!(period.unit, "ms");
+        assert_eq!(period.measurement_source_name, "node");
+        assert_eq!(period.window_stop, window_stop);
+        use StatisticDataType as Type;
+        assert_eq!(data(&period, Type::STATISTICS_DATA_TYPE_SAMPLE_COUNT), 3.0);
+        assert!((data(&period, Type::STATISTICS_DATA_TYPE_AVERAGE) - 150.0).abs() < 1e-9);
+        assert!((data(&period, Type::STATISTICS_DATA_TYPE_MINIMUM) - 100.0).abs() < 1e-9);
+        assert!((data(&period, Type::STATISTICS_DATA_TYPE_MAXIMUM) - 200.0).abs() < 1e-9);
+        let expected_stddev = (5000.0f64 / 3.0).sqrt();
+        assert!((data(&period, Type::STATISTICS_DATA_TYPE_STDDEV) - expected_stddev).abs() < 1e-9);
+
+        assert_eq!(age.metrics_source, "message_age");
+        assert_eq!(data(&age, Type::STATISTICS_DATA_TYPE_SAMPLE_COUNT), 4.0);
+        assert!((data(&age, Type::STATISTICS_DATA_TYPE_AVERAGE) - 11.25).abs() < 1e-9);
+
+        // The next window starts where the previous one stopped, without samples.
+        let [age, period] =
+            collector.take_metrics(builtin_interfaces::msg::Time { sec: 3, nanosec: 0 });
+        assert_eq!(age.window_start, window_stop);
+        assert_eq!(data(&period, Type::STATISTICS_DATA_TYPE_SAMPLE_COUNT), 0.0);
+        assert!(data(&period, Type::STATISTICS_DATA_TYPE_AVERAGE).is_nan());
+    }
+
+    #[test]
+    fn test_publishes_topic_statistics() -> Result<(), RclrsError> {
+        use crate::{spin_once, SubscriptionOptions};
+        use test_msgs::msg;
+
+        let graph = construct_test_graph("test_publishes_topic_statistics")?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_in_callback = Arc::clone(&received);
+        let _statistics_subscription = graph.node1.create_subscription::<MetricsMessage, _>(
+            "statistics",
+            QOS_PROFILE_DEFAULT,
+            move |metrics: MetricsMessage| {
+                received_in_callback.lock().unwrap().push(metrics);
+            },
+        )?;
+        let _subscription = graph
+            .node2
+            .create_subscription_with_options::<msg::Empty, _>(
+                "observed_topic",
+                SubscriptionOptions::default()
+                    .enable_topic_statistics("statistics", Duration::from_millis(100)),
+                |_msg: msg::Empty| {},
+            )?;
+        let publisher = graph
+            .node1
+            .create_publisher::<msg::Empty>("observed_topic", QOS_PROFILE_DEFAULT)?;
+
+        for _ in 0..50 {
+            publisher.publish(msg::Empty::default())?;
+            for node in [&graph.node1, &graph.node2] {
+                ignore_timeout(spin_once(Arc::clone(node), Some(Duration::from_millis(10))))?;
+            }
+            if received.lock().unwrap().len() >= 2 {
+                break;
+            }
+        }
+
+        let received = received.lock().unwrap();
+        let sources: Vec<_> = received
+            .iter()
+            .map(|metrics| metrics.metrics_source.as_str())
+            .collect();
+        assert!(sources.contains(&"message_age"), "{sources:?}");
+        assert!(sources.contains(&"message_period"), "{sources:?}");
+        assert!(received
+            .iter()
+            .all(|metrics| metrics.measurement_source_name == graph.node2.name()));
+        Ok(())
+    }
+}
@@ -1,12 +1,19 @@
-use crate::{rcl_bindings::*, vendor::builtin_interfaces};
+use crate::{rcl_bindings::*, vendor::builtin_interfaces, Clock, ClockType, RclrsError};
 use std::{
+    cmp::Ordering,
     num::TryFromIntError,
-    ops::{Add, Sub},
-    sync::{Mutex, Weak},
-    time::Duration,
+    ops::{Add, Neg, Sub},
+    sync::{Arc, Mutex, Weak},
+    time::SystemTime,
 };
 
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
 /// Struct that represents time.
+///
+/// A time is tied to the clock that generated it, and can only be compared with times from the
+/// same clock. Arithmetic with durations saturates at the epoch, i.e. at zero, since a time
+/// before the epoch can not be represented in a `builtin_interfaces/Time` message.
 #[derive(Clone, Debug)]
 pub struct Time {
     /// Timestamp in nanoseconds.
@@ -16,6 +23,28 @@ pub struct Time {
 }
 
 impl Time {
+    /// Creates a time from a ROS message, tied to the given clock.
+    pub fn from_ros_msg(msg: &builtin_interfaces::msg::Time, clock: &Clock) -> Self {
+        Time {
+            nsec: i64::from(msg.sec) * NANOS_PER_SEC + i64::from(msg.nanosec),
+            clock: Arc::downgrade(&clock.rcl_clock()),
+        }
+    }
+
+    /// Creates a time from a [`SystemTime`], tied to the given clock.
+    ///
+    /// A system time before the epoch saturates at the epoch.
+    pub fn from_system_time(system_time: SystemTime, clock: &Clock) -> Self {
+        let nsec = system_time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|since_epoch| i64::try_from(since_epoch.as_nanos()).unwrap_or(i64::MAX))
+            .unwrap_or(0);
+        Time {
+            nsec,
+            clock: Arc::downgrade(&clock.rcl_clock()),
+        }
+    }
+
     /// Compares self to rhs, if they can be compared (originated from the same clock) calls f with
     /// the values of the timestamps.
     pub fn compare_with<U, F>(&self, rhs: &Time, f: F) -> Option<U>
@@ -27,26 +56,130 @@ impl Time {
             .then(|| f(self.nsec, rhs.nsec))
     }
 
+    /// Returns the type of the clock that generated this time, or `None` if that clock has been
+    /// dropped.
+    pub fn clock_type(&self) -> Option<ClockType> {
+        let clock = self.clock.upgrade()?;
+        let clock_type = clock.lock().unwrap().type_;
+        match clock_type {
+            rcl_clock_type_t::RCL_ROS_TIME => Some(ClockType::RosTime),
+            rcl_clock_type_t::RCL_SYSTEM_TIME => Some(ClockType::SystemTime),
+            rcl_clock_type_t::RCL_STEADY_TIME => Some(ClockType::SteadyTime),
+            rcl_clock_type_t::RCL_CLOCK_UNINITIALIZED => None,
+        }
+    }
+
+    /// Compares this time to a time from a possibly different clock of the same type.
+    ///
+    /// Unlike the [`PartialOrd`] implementation, this does not require both times to originate
+    /// from the same clock, which is useful e.g. for comparing the stamp of a received message
+    /// against [`Clock::now()`]. Times from clocks of different types, such as ROS time and
+    /// system time, are not comparable and result in an
+    /// [`IncompatibleClockTypes`][1] error, as does a time whose clock has been dropped.
+    ///
+    /// [1]: RclrsError::IncompatibleClockTypes
+    pub fn try_cmp(&self, other: &Time) -> Result<Ordering, RclrsError> {
+        self.check_clock_type(other)?;
+        Ok(self.nsec.cmp(&other.nsec))
+    }
+
+    /// Returns the duration from `earlier` to this time, requiring only that both times
+    /// originate from clocks of the same type.
+    ///
+    /// See [`try_cmp()`][1] for when an error is returned.
+    ///
+    /// [1]: Self::try_cmp
+    pub fn try_duration_since(&self, earlier: &Time) -> Result<Duration, RclrsError> {
+        self.check_clock_type(earlier)?;
+        Ok(Duration {
+            nsec: self.nsec.saturating_sub(earlier.nsec),
+        })
+    }
+
+    fn check_clock_type(&self, other: &Time) -> Result<(), RclrsError> {
+        if self.clock.ptr_eq(&other.clock) {
+            return Ok(());
+        }
+        match (self.clock_type(), other.clock_type()) {
+            (Some(lhs), Some(rhs)) if lhs == rhs => Ok(()),
+            (lhs, rhs) => Err(RclrsError::IncompatibleClockTypes { lhs, rhs }),
+        }
+    }
+
+    /// Returns the duration from `earlier` to this time, which is negative if `earlier` is
+    /// actually later.
+    ///
+    /// Returns `None` if the two times originated from different clocks.
+    pub fn duration_since(&self, earlier: &Time) -> Option<Duration> {
+        self.compare_with(earlier, |nsec, earlier_nsec| Duration {
+            nsec: nsec.saturating_sub(earlier_nsec),
+        })
+    }
+
     /// Convenience function for converting time to ROS message
     pub fn to_ros_msg(&self) -> Result<builtin_interfaces::msg::Time, TryFromIntError> {
-        let nanosec = self.nsec % 1_000_000_000;
-        let sec = self.nsec / 1_000_000_000;
+        let nanosec = self.nsec % NANOS_PER_SEC;
+        let sec = self.nsec / NANOS_PER_SEC;
 
         Ok(builtin_interfaces::msg::Time {
             nanosec: nanosec.try_into()?,
             sec: sec.try_into()?,
         })
     }
+
+    /// Converts the time to a [`SystemTime`], interpreting it as nanoseconds since the epoch.
+    ///
+    /// A negative time saturates at the epoch.
+    pub fn to_system_time(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + std::time::Duration::from_nanos(self.nsec.max(0) as u64)
+    }
+}
+
+impl PartialEq for Time {
+    fn eq(&self, other: &Self) -> bool {
+        self.compare_with(other, |lhs, rhs| lhs == rhs)
+            .unwrap_or(false)
+    }
+}
+
+impl PartialOrd for Time {
+    /// Times from different clocks can not be compared, so this returns `None` for them.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.compare_with(other, |lhs, rhs| lhs.cmp(&rhs))
+    }
+}
+
+impl TryFrom<&Time> for builtin_interfaces::msg::Time {
+    type Error = TryFromIntError;
+
+    fn try_from(time: &Time) -> Result<Self, Self::Error> {
+        time.to_ros_msg()
+    }
+}
+
+impl Add<std::time::Duration> for Time {
+    type Output = Self;
+
+    fn add(self, other: std::time::Duration) -> Self {
+        self + Duration::from(other)
+    }
+}
+
+impl Sub<std::time::Duration> for Time {
+    type Output = Self;
+
+    fn sub(self, other: std::time::Duration) -> Self {
+        self - Duration::from(other)
+    }
 }
 
 impl Add<Duration> for Time {
     type Output = Self;
 
     fn add(self, other: Duration) -> Self {
-        let dur_ns = i64::try_from(other.as_nanos()).unwrap();
         Time {
-            nsec: self.nsec.checked_add(dur_ns).unwrap(),
-            clock: self.clock.clone(),
+            nsec: self.nsec.saturating_add(other.nsec).max(0),
+            clock: self.clock,
         }
     }
 }
@@ -55,10 +188,112 @@ impl Sub<Duration> for Time {
     type Output = Self;
 
     fn sub(self, other: Duration) -> Self {
-        let dur_ns = i64::try_from(other.as_nanos()).unwrap();
         Time {
-            nsec: self.nsec.checked_sub(dur_ns).unwrap(),
-            clock: self.clock.clone(),
+            nsec: self.nsec.saturating_sub(other.nsec).max(0),
+            clock: self.clock,
+        }
+    }
+}
+
+/// A signed span of time, e.g. the difference between two [`Time`]s.
+///
+/// Unlike [`std::time::Duration`], this can be negative, like a `builtin_interfaces/Duration`
+/// message. Arithmetic saturates instead of overflowing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration {
+    /// Duration in nanoseconds.
+    pub nsec: i64,
+}
+
+impl Duration {
+    /// Creates a duration from a number of nanoseconds.
+    pub const fn from_nanos(nsec: i64) -> Self {
+        Self { nsec }
+    }
+
+    /// Creates a duration from a ROS message.
+    pub fn from_ros_msg(msg: &builtin_interfaces::msg::Duration) -> Self {
+        Self {
+            nsec: i64::from(msg.sec) * NANOS_PER_SEC + i64::from(msg.nanosec),
+        }
+    }
+
+    /// Returns the duration in seconds.
+    pub fn as_secs_f64(&self) -> f64 {
+        self.nsec as f64 / NANOS_PER_SEC as f64
+    }
+
+    /// Converts the duration to a [`std::time::Duration`], or returns `None` if it is negative.
+    pub fn to_std(&self) -> Option<std::time::Duration> {
+        u64::try_from(self.nsec)
+            .ok()
+            .map(std::time::Duration::from_nanos)
+    }
+
+    /// Convenience function for converting duration to ROS message
+    ///
+    /// As in the message definition, the nanoseconds are always positive, so e.g. -0.5 s is
+    /// represented as -1 s and 500,000,000 ns.
+    pub fn to_ros_msg(&self) -> Result<builtin_interfaces::msg::Duration, TryFromIntError> {
+        let nanosec = self.nsec.rem_euclid(NANOS_PER_SEC);
+        let sec = self.nsec.div_euclid(NANOS_PER_SEC);
+
+        Ok(builtin_interfaces::msg::Duration {
+            nanosec: nanosec.try_into()?,
+            sec: sec.try_into()?,
+        })
+    }
+}
+
+impl From<std::time::Duration> for Duration {
+    /// Durations that are too long to be represented saturate at [`i64::MAX`] nanoseconds.
+    fn from(duration: std::time::Duration) -> Self {
+        Self {
+            nsec: i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX),
+        }
+    }
+}
+
+impl From<&builtin_interfaces::msg::Duration> for Duration {
+    fn from(msg: &builtin_interfaces::msg::Duration) -> Self {
+        Self::from_ros_msg(msg)
+    }
+}
+
+impl TryFrom<Duration> for builtin_interfaces::msg::Duration {
+    type Error = TryFromIntError;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        duration.to_ros_msg()
+    }
+}
+
+impl Add for Duration {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            nsec: self.nsec.saturating_add(other.nsec),
+        }
+    }
+}
+
+impl Sub for Duration {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            nsec: self.nsec.saturating_sub(other.nsec),
+        }
+    }
+}
+
+impl Neg for Duration {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            nsec: self.nsec.saturating_neg(),
         }
     }
 }
@@ -66,7 +301,7 @@ impl Sub<Duration> for Time {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Clock;
+    use std::time::Duration;
 
     #[test]
     fn compare_times_from_same_clock() {
@@ -111,4 +346,137 @@ mod tests {
         assert_eq!(msg.nanosec, 100);
         assert_eq!(msg.sec, 1);
     }
+
+    #[test]
+    fn time_arithmetic_saturates_at_epoch() {
+        let clock = Clock::system();
+        let time = Time {
+            nsec: 500,
+            clock: clock.now().clock,
+        };
+        assert_eq!((time.clone() - Duration::from_secs(1)).nsec, 0);
+        assert_eq!((time.clone() + super::Duration::from_nanos(-1_000)).nsec, 0);
+        let later = time.clone() + super::Duration::from_nanos(1_500);
+        assert_eq!(later.nsec, 2_000);
+        assert!(later > time);
+        assert_eq!(
+            later.duration_since(&time),
+            Some(super::Duration::from_nanos(1_500))
+        );
+        assert_eq!(
+            time.duration_since(&later),
+            Some(super::Duration::from_nanos(-1_500))
+        );
+        let max = Time {
+            nsec: i64::MAX,
+            clock: time.clock.clone(),
+        };
+        assert_eq!((max + Duration::from_secs(1)).nsec, i64::MAX);
+
+        // Times from different clocks are neither equal nor ordered
+        let other = Time {
+            nsec: 500,
+            clock: Clock::system().now().clock,
+        };
+        assert_ne!(time, other);
+        assert_eq!(time.partial_cmp(&other), None);
+        assert_eq!(time.duration_since(&other), None);
+    }
+
+    #[test]
+    fn duration_arithmetic() {
+        let one_sec = super::Duration::from(Duration::from_secs(1));
+        let half_sec = super::Duration::from_nanos(500_000_000);
+        assert_eq!(one_sec - half_sec, half_sec);
+        assert_eq!(half_sec - one_sec, -half_sec);
+        assert_eq!((half_sec + half_sec).to_std(), Some(Duration::from_secs(1)));
+        assert_eq!((-half_sec).to_std(), None);
+        assert_eq!((-half_sec).as_secs_f64(), -0.5);
+        let max = super::Duration::from_nanos(i64::MAX);
+        assert_eq!(max + one_sec, max);
+        assert_eq!(super::Duration::from(Duration::MAX), max);
+    }
+
+    #[test]
+    fn ros_msg_round_trip() {
+        let clock = Clock::system();
+        let msg = builtin_interfaces::msg::Time {
+            sec: 12,
+            nanosec: 345,
+        };
+        let time = Time::from_ros_msg(&msg, &clock);
+        assert_eq!(time.nsec, 12_000_000_345);
+        assert!(time.clock.ptr_eq(&clock.now().clock));
+        assert_eq!(time.to_ros_msg().unwrap(), msg);
+
+        let msg = builtin_interfaces::msg::Duration {
+            sec: -1,
+            nanosec: 500_000_000,
+        };
+        let duration = super::Duration::from_ros_msg(&msg);
+        assert_eq!(duration.nsec, -500_000_000);
+        assert_eq!(duration.to_ros_msg().unwrap(), msg);
+    }
+
+    #[test]
+    fn system_time_round_trip() {
+        let clock = Clock::system();
+        let system_time = SystemTime::UNIX_EPOCH + Duration::from_nanos(1_234_567_890);
+        let time = Time::from_system_time(system_time, &clock);
+        assert_eq!(time.nsec, 1_234_567_890);
+        assert_eq!(time.to_system_time(), system_time);
+        assert!(matches!(time.clock_type(), Some(ClockType::SystemTime)));
+
+        let before_epoch = SystemTime::UNIX_EPOCH - Duration::from_secs(1);
+        assert_eq!(Time::from_system_time(before_epoch, &clock).nsec, 0);
+        let negative = Time {
+            nsec: -1,
+            clock: time.clock.clone(),
+        };
+        assert_eq!(negative.to_system_time(), SystemTime::UNIX_EPOCH);
+    }
+
+    #[test]
+    fn compare_msg_stamp_with_clock() -> Result<(), RclrsError> {
+        let clock = Clock::system();
+        let stamp = builtin_interfaces::msg::Time::try_from(&clock.now()).unwrap();
+        // A stamp converted with another clock of the same type can be compared.
+        let received = Time::from_ros_msg(&stamp, &Clock::system());
+        std::thread::sleep(Duration::from_millis(1));
+        let now = clock.now();
+        assert_eq!(now.try_cmp(&received)?, Ordering::Greater);
+        let age = now.try_duration_since(&received)?;
+        assert!(age.nsec > 0);
+        assert_eq!(
+            builtin_interfaces::msg::Duration::try_from(age).map(|msg| super::Duration::from(&msg)),
+            Ok(age)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn compare_times_of_incompatible_clock_types() {
+        let (ros_clock, source) = Clock::with_source();
+        source.set_ros_time_override(1_000);
+        let system_clock = Clock::system();
+        let sim_time = ros_clock.now();
+        let system_time = system_clock.now();
+        assert_eq!(
+            sim_time.try_cmp(&system_time),
+            Err(RclrsError::IncompatibleClockTypes {
+                lhs: Some(ClockType::RosTime),
+                rhs: Some(ClockType::SystemTime),
+            })
+        );
+        assert!(system_time.try_duration_since(&sim_time).is_err());
+
+        let orphaned = Clock::steady().now();
+        assert_eq!(
+            system_time.try_cmp(&orphaned),
+            Err(RclrsError::IncompatibleClockTypes {
+                lhs: Some(ClockType::SystemTime),
+                rhs: None,
+            })
+        );
+    }
 }
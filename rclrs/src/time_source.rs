@@ -145,7 +145,13 @@ impl TimeSource {
 
 #[cfg(test)]
 mod tests {
-    use crate::{create_node, Context};
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::{create_node, vendor::builtin_interfaces::msg::Time, Context, JumpThreshold};
 
     #[test]
     fn time_source_default_clock() {
@@ -166,4 +172,140 @@ mod tests {
         // Default sim time value should be 0 (no message received)
         assert_eq!(node.get_clock().now().nsec, 0);
     }
+
+    fn spin_until(node: &Arc<Node>, condition: impl Fn() -> bool) {
+        let start = Instant::now();
+        while !condition() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            let _ = crate::spin_once(Arc::clone(node), Some(Duration::from_millis(10)));
+        }
+    }
+
+    fn sim_time_context() -> Context {
+        Context::new([
+            String::from("--ros-args"),
+            String::from("-p"),
+            String::from("use_sim_time:=true"),
+        ])
+        .unwrap()
+    }
+
+    fn clock_msg(sec: i32) -> ClockMsg {
+        ClockMsg {
+            clock: Time { sec, nanosec: 500 },
+        }
+    }
+
+    #[test]
+    fn time_source_follows_clock_topic() {
+        let ctx = sim_time_context();
+        let node = create_node(&ctx, "test_node").unwrap();
+        let clock_node = create_node(&ctx, "clock_node").unwrap();
+        let clock_publisher = clock_node
+            .create_publisher::<ClockMsg>("/clock", QOS_PROFILE_CLOCK)
+            .unwrap();
+        let clock = node.get_clock();
+        assert!(matches!(clock.clock_type(), ClockType::RosTime));
+
+        spin_until(&node, || {
+            clock_publisher.publish(clock_msg(42)).unwrap();
+            clock.now().nsec != 0
+        });
+        assert_eq!(clock.now().nsec, 42_000_000_500);
+    }
+
+    #[test]
+    fn time_source_reports_backward_jumps() {
+        let ctx = sim_time_context();
+        let node = create_node(&ctx, "test_node").unwrap();
+        let clock_node = create_node(&ctx, "clock_node").unwrap();
+        let clock_publisher = clock_node
+            .create_publisher::<ClockMsg>("/clock", QOS_PROFILE_CLOCK)
+            .unwrap();
+        let clock = node.get_clock();
+        let deltas = Arc::new(Mutex::new(Vec::new()));
+        let deltas_in_callback = Arc::clone(&deltas);
+        let threshold = JumpThreshold {
+            min_backward: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let _jump_handle = clock
+            .add_jump_callback(threshold, move |jump| {
+                deltas_in_callback.lock().unwrap().push(jump.delta);
+            })
+            .unwrap();
+
+        spin_until(&node, || {
+            clock_publisher.publish(clock_msg(100)).unwrap();
+            clock.now().nsec == 100_000_000_500
+        });
+        assert!(deltas.lock().unwrap().is_empty());
+        spin_until(&node, || {
+            clock_publisher.publish(clock_msg(50)).unwrap();
+            clock.now().nsec == 50_000_000_500
+        });
+        assert_eq!(*deltas.lock().unwrap(), [-50_000_000_000]);
+    }
+
+    #[test]
+    fn sleep_follows_sim_time() {
+        let ctx = sim_time_context();
+        let node = create_node(&ctx, "test_node").unwrap();
+        let clock_node = create_node(&ctx, "clock_node").unwrap();
+        let clock_publisher = clock_node
+            .create_publisher::<ClockMsg>("/clock", QOS_PROFILE_CLOCK)
+            .unwrap();
+        let clock = node.get_clock();
+        let woke_up = AtomicBool::new(false);
+
+        std::thread::scope(|s| {
+            let sleeper = s.spawn(|| {
+                let result = clock.sleep_for(Duration::from_secs(10));
+                woke_up.store(true, Ordering::SeqCst);
+                result
+            });
+            spin_until(&node, || {
+                clock_publisher.publish(clock_msg(5)).unwrap();
+                clock.now().nsec == 5_000_000_500
+            });
+            // The simulated time has not reached the deadline yet.
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!woke_up.load(Ordering::SeqCst));
+
+            spin_until(&node, || {
+                clock_publisher.publish(clock_msg(20)).unwrap();
+                woke_up.load(Ordering::SeqCst)
+            });
+            sleeper.join().unwrap().unwrap();
+        });
+    }
+
+    #[test]
+    fn timers_follow_sim_time() {
+        let ctx = sim_time_context();
+        let node = create_node(&ctx, "test_node").unwrap();
+        let clock_node = create_node(&ctx, "clock_node").unwrap();
+        let clock_publisher = clock_node
+            .create_publisher::<ClockMsg>("/clock", QOS_PROFILE_CLOCK)
+            .unwrap();
+        let fired = Arc::new(AtomicBool::new(false));
+        let fired_in_callback = Arc::clone(&fired);
+        let _timer = node
+            .create_timer(Duration::from_secs(10), move || {
+                fired_in_callback.store(true, Ordering::SeqCst);
+            })
+            .unwrap();
+
+        // Wall time passing does not advance the timer.
+        for _ in 0..5 {
+            let _ = crate::spin_once(Arc::clone(&node), Some(Duration::from_millis(10)));
+        }
+        assert!(!fired.load(Ordering::SeqCst));
+
+        // Advancing the simulated time past the period does.
+        spin_until(&node, || {
+            clock_publisher.publish(clock_msg(20)).unwrap();
+            fired.load(Ordering::SeqCst)
+        });
+    }
 }
@@ -0,0 +1,536 @@
+use std::{
+    sync::{atomic::AtomicBool, Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use crate::{
+    error::{RclReturnCode, ToResult},
+    rcl_bindings::*,
+    CallbackGroup, Clock, ContextHandle, RclrsError, ENTITY_LIFECYCLE_MUTEX,
+};
+
+// SAFETY: The functions accessing this type, including drop(), shouldn't care about the thread
+// they are running in. Therefore, this type can be safely sent to another thread.
+unsafe impl Send for rcl_timer_t {}
+
+/// Manage the lifecycle of an `rcl_timer_t`, including managing its dependencies
+/// on `rcl_clock_t` and `rcl_context_t` by ensuring that these dependencies are
+/// [dropped after][1] the `rcl_timer_t`.
+///
+/// [1]: <https://doc.rust-lang.org/reference/destructors.html>
+pub struct TimerHandle {
+    rcl_timer: Mutex<rcl_timer_t>,
+    #[allow(dead_code)]
+    clock: Clock,
+    #[allow(dead_code)]
+    context_handle: Arc<ContextHandle>,
+    pub(crate) in_use_by_wait_set: Arc<AtomicBool>,
+}
+
+impl TimerHandle {
+    pub(crate) fn lock(&self) -> MutexGuard<rcl_timer_t> {
+        self.rcl_timer.lock().unwrap()
+    }
+}
+
+impl Drop for TimerHandle {
+    fn drop(&mut self) {
+        let rcl_timer = self.rcl_timer.get_mut().unwrap();
+        let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+        // SAFETY: The entity lifecycle mutex is locked to protect against the risk of
+        // global variables in the rmw implementation being unsafely modified during cleanup.
+        unsafe {
+            rcl_timer_fini(rcl_timer);
+        }
+    }
+}
+
+/// Information about a timer tick, passed to timer callbacks that take an argument.
+///
+/// Both durations are measured with the clock of the timer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimerInfo {
+    /// The time that elapsed since the timer was last called, i.e. since the previous execution
+    /// of the callback, or since the timer was created or [reset][1] if that happened later.
+    ///
+    /// [1]: Timer::reset
+    pub time_since_last_call: Duration,
+    /// The time that elapsed since the previous execution of the callback, or since the timer was
+    /// created if this is the first execution.
+    ///
+    /// This is the period that was actually achieved, which is at least the configured period and
+    /// grows when the executor is busy with other callbacks. Unlike `time_since_last_call`, it is
+    /// not restarted by [resetting][1] the timer.
+    ///
+    /// [1]: Timer::reset
+    pub actual_period: Duration,
+}
+
+/// A trait for allowed callbacks for timers.
+///
+/// It is implemented for closures without arguments, and for closures taking a [`TimerInfo`]
+/// for callbacks that need to know how much time has actually passed between executions.
+pub trait TimerCallback<Args>: Send + 'static {
+    /// Converts the callback into a boxed closure taking a [`TimerInfo`].
+    ///
+    /// User code never needs to call this function.
+    fn into_timer_callback(self) -> Box<dyn FnMut(TimerInfo) + Send>;
+}
+
+impl<Func> TimerCallback<()> for Func
+where
+    Func: FnMut() + Send + 'static,
+{
+    fn into_timer_callback(mut self) -> Box<dyn FnMut(TimerInfo) + Send> {
+        Box::new(move |_| self())
+    }
+}
+
+impl<Func> TimerCallback<(TimerInfo,)> for Func
+where
+    Func: FnMut(TimerInfo) + Send + 'static,
+{
+    fn into_timer_callback(self) -> Box<dyn FnMut(TimerInfo) + Send> {
+        Box::new(self)
+    }
+}
+
+/// A timer that periodically executes a callback.
+///
+/// The only available ways to instantiate timers are via [`Node::create_timer()`][1] and
+/// [`Node::create_one_shot_timer()`][2]. The period is measured with the clock of the node, see
+/// [`Node::get_clock()`][3].
+///
+/// The callback is executed when spinning the node, so it can be delayed by other callbacks.
+/// If the timer has been dropped or canceled, the callback is not executed anymore.
+/// See [`TimerCallback`] for the possible callback signatures.
+///
+/// [1]: crate::Node::create_timer
+/// [2]: crate::Node::create_one_shot_timer
+/// [3]: crate::Node::get_clock
+pub struct Timer {
+    pub(crate) handle: TimerHandle,
+    callback: Mutex<Box<dyn FnMut(TimerInfo) + Send>>,
+    one_shot: bool,
+    callback_group: Option<Arc<CallbackGroup>>,
+    // The time of the previous execution of the callback, or of the creation of the timer, in
+    // nanoseconds of the timer's clock.
+    last_execution_ns: Mutex<i64>,
+}
+
+impl Timer {
+    /// Creates a new timer.
+    ///
+    /// If `one_shot` is true, the timer is canceled after its callback was executed once.
+    // This uses pub(crate) visibility to avoid instantiating this struct outside
+    // [`Node::create_timer`], see the struct's documentation for the rationale
+    pub(crate) fn new(
+        clock: Clock,
+        context_handle: Arc<ContextHandle>,
+        period: Duration,
+        one_shot: bool,
+        callback: Box<dyn FnMut(TimerInfo) + Send>,
+        callback_group: Option<Arc<CallbackGroup>>,
+    ) -> Result<Self, RclrsError> {
+        let period_ns = i64::try_from(period.as_nanos()).map_err(|_| RclrsError::RclError {
+            code: RclReturnCode::InvalidArgument,
+            operation: None,
+            msg: None,
+        })?;
+        // SAFETY: Getting a zero-initialized value is always safe.
+        let mut rcl_timer = unsafe { rcl_get_zero_initialized_timer() };
+        {
+            let rcl_clock = clock.rcl_clock();
+            let mut rcl_clock = rcl_clock.lock().unwrap();
+            let mut rcl_context = context_handle.rcl_context.lock().unwrap();
+            let _lifecycle_lock = ENTITY_LIFECYCLE_MUTEX.lock().unwrap();
+            // SAFETY: The rcl_timer is zero-initialized as expected by this function.
+            // The clock is kept alive by the handle, and is stored behind an Arc, so the pointer
+            // that the timer keeps to it remains valid.
+            // Passing no callback is explicitly allowed.
+            unsafe {
+                #[cfg(any(ros_distro = "humble", ros_distro = "iron"))]
+                rcl_timer_init(
+                    &mut rcl_timer,
+                    &mut *rcl_clock,
+                    &mut *rcl_context,
+                    period_ns,
+                    None,
+                    rcutils_get_default_allocator(),
+                )
+                .ok_for("rcl_timer_init")?;
+                #[cfg(not(any(ros_distro = "humble", ros_distro = "iron")))]
+                rcl_timer_init2(
+                    &mut rcl_timer,
+                    &mut *rcl_clock,
+                    &mut *rcl_context,
+                    period_ns,
+                    None,
+                    rcutils_get_default_allocator(),
+                    true,
+                )
+                .ok_for("rcl_timer_init2")?;
+            }
+        }
+
+        let created_ns = clock.now().nsec;
+        Ok(Self {
+            handle: TimerHandle {
+                rcl_timer: Mutex::new(rcl_timer),
+                clock,
+                context_handle,
+                in_use_by_wait_set: Arc::new(AtomicBool::new(false)),
+            },
+            callback: Mutex::new(callback),
+            one_shot,
+            callback_group,
+            last_execution_ns: Mutex::new(created_ns),
+        })
+    }
+
+    /// Returns the [`CallbackGroup`] of the timer, if it was created in one.
+    ///
+    /// Otherwise, the timer belongs to the default group of its node.
+    pub fn callback_group(&self) -> Option<&Arc<CallbackGroup>> {
+        self.callback_group.as_ref()
+    }
+
+    /// Returns the period of the timer.
+    pub fn period(&self) -> Result<Duration, RclrsError> {
+        let mut period_ns = 0;
+        // SAFETY: The timer is valid, and the out-parameter is a valid pointer.
+        unsafe { rcl_timer_get_period(&*self.handle.lock(), &mut period_ns) }
+            .ok_for("rcl_timer_get_period")?;
+        Ok(Duration::from_nanos(period_ns as u64))
+    }
+
+    /// Changes the period of the timer.
+    ///
+    /// The tick that is currently pending still happens at the time computed from the old
+    /// period; the new period applies from the next tick on. The timer stays registered with
+    /// the node, so it does not need to be recreated.
+    pub fn set_period(&self, period: Duration) -> Result<(), RclrsError> {
+        let period_ns = i64::try_from(period.as_nanos()).map_err(|_| RclrsError::RclError {
+            code: RclReturnCode::InvalidArgument,
+            operation: None,
+            msg: None,
+        })?;
+        let mut old_period_ns = 0;
+        // SAFETY: The timer is valid, and the out-parameter is a valid pointer.
+        unsafe { rcl_timer_exchange_period(&*self.handle.lock(), period_ns, &mut old_period_ns) }
+            .ok_for("rcl_timer_exchange_period")
+    }
+
+    /// Cancels the timer, so that its callback is not executed anymore until it is
+    /// [reset][1].
+    ///
+    /// This can be used to pause the timer, and [`reset()`][1] to resume it.
+    ///
+    /// [1]: Self::reset
+    pub fn cancel(&self) -> Result<(), RclrsError> {
+        // SAFETY: The timer is valid.
+        unsafe { rcl_timer_cancel(&mut *self.handle.lock()) }.ok_for("rcl_timer_cancel")
+    }
+
+    /// Restarts the timer, so that the next execution happens one period from now.
+    ///
+    /// This also reactivates a canceled timer, which then resumes with a full period from now
+    /// instead of catching up on the ticks it missed. An executor that is waiting on the timer is
+    /// woken up, so this can be called from any thread.
+    pub fn reset(&self) -> Result<(), RclrsError> {
+        // SAFETY: The timer is valid.
+        unsafe { rcl_timer_reset(&mut *self.handle.lock()) }.ok_for("rcl_timer_reset")
+    }
+
+    /// Returns `true` if the timer is canceled after firing once.
+    pub fn is_one_shot(&self) -> bool {
+        self.one_shot
+    }
+
+    /// Returns `true` if the timer has been canceled.
+    ///
+    /// A one-shot timer is canceled after it has fired.
+    pub fn is_canceled(&self) -> Result<bool, RclrsError> {
+        let mut is_canceled = false;
+        // SAFETY: The timer is valid, and the out-parameter is a valid pointer.
+        unsafe { rcl_timer_is_canceled(&*self.handle.lock(), &mut is_canceled) }
+            .ok_for("rcl_timer_is_canceled")?;
+        Ok(is_canceled)
+    }
+
+    /// Executes the callback, if the timer is due.
+    ///
+    /// This is called by the executor when the wait set reports the timer as ready.
+    pub(crate) fn execute(&self) -> Result<(), RclrsError> {
+        let info = {
+            let mut rcl_timer = self.handle.lock();
+            let mut is_ready = false;
+            // SAFETY: The timer is valid, and the out-parameter is a valid pointer.
+            unsafe { rcl_timer_is_ready(&*rcl_timer, &mut is_ready) }
+                .ok_for("rcl_timer_is_ready")?;
+            if !is_ready {
+                // Spurious wakeup, or the timer was canceled in the meantime.
+                return Ok(());
+            }
+            // This has to be queried before rcl_timer_call(), which resets the time of the last call.
+            let mut time_since_last_call_ns = 0;
+            // SAFETY: The timer is valid, and the out-parameter is a valid pointer.
+            unsafe {
+                rcl_timer_get_time_since_last_call(&*rcl_timer, &mut time_since_last_call_ns)
+                    .ok_for("rcl_timer_get_time_since_last_call")?;
+            }
+            // SAFETY: The timer is valid. This updates the time of the last call, and calls
+            // no callback, since none was passed to rcl.
+            match unsafe { rcl_timer_call(&mut *rcl_timer) }.ok_for("rcl_timer_call") {
+                Ok(()) => {}
+                Err(RclrsError::RclError {
+                    code: RclReturnCode::TimerCanceled,
+                    ..
+                }) => return Ok(()),
+                Err(err) => return Err(err),
+            }
+            if self.one_shot {
+                // SAFETY: The timer is valid.
+                unsafe { rcl_timer_cancel(&mut *rcl_timer) }.ok_for("rcl_timer_cancel")?;
+            }
+            let now_ns = self.handle.clock.now().nsec;
+            let last_execution_ns =
+                std::mem::replace(&mut *self.last_execution_ns.lock().unwrap(), now_ns);
+            TimerInfo {
+                // The clock can jump backwards, e.g. with simulated time.
+                time_since_last_call: Duration::from_nanos(time_since_last_call_ns.max(0) as u64),
+                actual_period: Duration::from_nanos((now_ns - last_execution_ns).max(0) as u64),
+            }
+        };
+        (*self.callback.lock().unwrap())(info);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    use super::*;
+    use crate::{test_helpers::*, ClockSource, Context};
+
+    #[test]
+    fn traits() {
+        assert_send::<Timer>();
+        assert_sync::<Timer>();
+    }
+
+    // Creates a timer whose clock is driven by the returned source, so that the tests decide
+    // when the timer is due instead of depending on how the threads are scheduled. The ROS time
+    // of the clock starts at zero.
+    fn create_ros_time_timer(
+        period: Duration,
+        callback: impl FnMut(TimerInfo) + Send + 'static,
+    ) -> Result<(Timer, ClockSource), RclrsError> {
+        let context = Context::new([])?;
+        let (clock, clock_source) = Clock::with_source();
+        let timer = Timer::new(
+            clock,
+            Arc::clone(&context.handle),
+            period,
+            false,
+            Box::new(callback),
+            None,
+        )?;
+        Ok((timer, clock_source))
+    }
+
+    fn millis_to_ns(millis: i64) -> i64 {
+        millis * 1_000_000
+    }
+
+    #[test]
+    fn test_timer_fires_periodically() -> Result<(), RclrsError> {
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_in_callback = Arc::clone(&count);
+        let (timer, clock_source) =
+            create_ros_time_timer(Duration::from_millis(10), move |_: TimerInfo| {
+                count_in_callback.fetch_add(1, Ordering::SeqCst);
+            })?;
+        assert_eq!(timer.period()?, Duration::from_millis(10));
+
+        // The timer is not due before its period elapsed
+        clock_source.set_ros_time_override(millis_to_ns(9));
+        timer.execute()?;
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        for tick in 1..=5 {
+            clock_source.set_ros_time_override(millis_to_ns(10 * tick));
+            timer.execute()?;
+            // The timer fires once per period, however often it is executed
+            timer.execute()?;
+            assert_eq!(count.load(Ordering::SeqCst), tick as usize);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_one_shot_timer_fires_once() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_one_shot_timer_fires_once")?;
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_in_callback = Arc::clone(&count);
+        let timer = graph
+            .node1
+            .create_one_shot_timer(Duration::from_millis(10), move || {
+                count_in_callback.fetch_add(1, Ordering::SeqCst);
+            })?;
+        assert!(timer.is_one_shot());
+
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(200) {
+            ignore_timeout(crate::spin_once(
+                Arc::clone(&graph.node1),
+                Some(Duration::from_millis(10)),
+            ))?;
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(timer.is_canceled()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_timer_cancel_and_reset() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_timer_cancel_and_reset")?;
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_in_callback = Arc::clone(&count);
+        let timer = graph
+            .node1
+            .create_timer(Duration::from_millis(1), move || {
+                count_in_callback.fetch_add(1, Ordering::SeqCst);
+            })?;
+        assert!(!timer.is_canceled()?);
+        timer.cancel()?;
+        assert!(timer.is_canceled()?);
+
+        std::thread::sleep(Duration::from_millis(10));
+        let _ = crate::spin_once(Arc::clone(&graph.node1), Some(Duration::from_millis(10)));
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        timer.reset()?;
+        assert!(!timer.is_canceled()?);
+        let start = Instant::now();
+        while count.load(Ordering::SeqCst) == 0 {
+            assert!(start.elapsed() < Duration::from_secs(1));
+            let _ = crate::spin_once(Arc::clone(&graph.node1), Some(Duration::from_millis(10)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_timer_reset_wakes_up_spin() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_timer_reset_wakes_up_spin")?;
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_in_callback = Arc::clone(&count);
+        let timer = graph
+            .node1
+            .create_timer(Duration::from_millis(10), move || {
+                count_in_callback.fetch_add(1, Ordering::SeqCst);
+            })?;
+        timer.cancel()?;
+
+        // The canceled timer is the only entity of the node, so the spin below only returns
+        // early if resetting the timer wakes it up.
+        let timer_to_reset = Arc::clone(&timer);
+        let reset_thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            timer_to_reset.reset()
+        });
+        let start = Instant::now();
+        while count.load(Ordering::SeqCst) == 0 {
+            ignore_timeout(crate::spin_once(
+                Arc::clone(&graph.node1),
+                Some(Duration::from_secs(10)),
+            ))?;
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+        reset_thread.join().unwrap()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_timer_reports_time_since_last_call() -> Result<(), RclrsError> {
+        let infos = Arc::new(Mutex::new(Vec::new()));
+        let infos_in_callback = Arc::clone(&infos);
+        let (timer, clock_source) =
+            create_ros_time_timer(Duration::from_millis(20), move |info: TimerInfo| {
+                infos_in_callback.lock().unwrap().push(info);
+            })?;
+
+        // The first call is late, and the second one is back on schedule
+        clock_source.set_ros_time_override(millis_to_ns(25));
+        timer.execute()?;
+        clock_source.set_ros_time_override(millis_to_ns(40));
+        timer.execute()?;
+
+        let infos = infos.lock().unwrap();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].time_since_last_call, Duration::from_millis(25));
+        assert_eq!(infos[0].actual_period, Duration::from_millis(25));
+        assert_eq!(infos[1].time_since_last_call, Duration::from_millis(15));
+        assert_eq!(infos[1].actual_period, Duration::from_millis(15));
+        Ok(())
+    }
+
+    #[test]
+    fn test_timer_actual_period_spans_reset() -> Result<(), RclrsError> {
+        let infos = Arc::new(Mutex::new(Vec::new()));
+        let infos_in_callback = Arc::clone(&infos);
+        let (timer, clock_source) =
+            create_ros_time_timer(Duration::from_millis(20), move |info: TimerInfo| {
+                infos_in_callback.lock().unwrap().push(info);
+            })?;
+
+        clock_source.set_ros_time_override(millis_to_ns(20));
+        timer.execute()?;
+        // Resetting delays the next call to a full period from now
+        clock_source.set_ros_time_override(millis_to_ns(30));
+        timer.reset()?;
+        clock_source.set_ros_time_override(millis_to_ns(45));
+        timer.execute()?;
+        assert_eq!(infos.lock().unwrap().len(), 1);
+        clock_source.set_ros_time_override(millis_to_ns(50));
+        timer.execute()?;
+
+        // Resetting restarts the time since the last call, but not the actual period.
+        let info = infos.lock().unwrap()[1];
+        assert_eq!(info.time_since_last_call, Duration::from_millis(20));
+        assert_eq!(info.actual_period, Duration::from_millis(30));
+        Ok(())
+    }
+
+    #[test]
+    fn test_timer_set_period() -> Result<(), RclrsError> {
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_in_callback = Arc::clone(&count);
+        let (timer, clock_source) =
+            create_ros_time_timer(Duration::from_millis(20), move |_: TimerInfo| {
+                count_in_callback.fetch_add(1, Ordering::SeqCst);
+            })?;
+
+        clock_source.set_ros_time_override(millis_to_ns(20));
+        timer.execute()?;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        timer.set_period(Duration::from_millis(10))?;
+        assert_eq!(timer.period()?, Duration::from_millis(10));
+
+        // The pending tick is still due after the old period
+        clock_source.set_ros_time_override(millis_to_ns(30));
+        timer.execute()?;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        clock_source.set_ros_time_override(millis_to_ns(40));
+        timer.execute()?;
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        // The following ticks use the new period
+        clock_source.set_ros_time_override(millis_to_ns(50));
+        timer.execute()?;
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+}
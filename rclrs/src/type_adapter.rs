@@ -0,0 +1,161 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use rosidl_runtime_rs::Message;
+
+use crate::{Publisher, RclrsError};
+
+/// A mapping between a custom Rust type and a ROS message type.
+///
+/// This is the equivalent of type adaptation in `rclcpp`. It lets publishers and subscriptions
+/// work with the custom type, while the ROS message type is used on the wire. See
+/// [`Node::create_adapted_publisher()`][1] and [`Node::create_adapted_subscription()`][2].
+///
+/// The trait is implemented by a separate adapter type rather than by the custom type itself, so
+/// that adapters can be written for types from other crates, e.g. `String`.
+///
+/// # Example
+/// ```
+/// # use rclrs::TypeAdapter;
+/// # use rclrs::msg::Time;
+/// /// Publishes the number of seconds as a `builtin_interfaces/Time` message.
+/// struct SecondsAdapter;
+///
+/// impl TypeAdapter for SecondsAdapter {
+///     type Custom = i32;
+///     type Ros = Time;
+///
+///     fn convert_to_ros_message(custom: &i32) -> Time {
+///         Time {
+///             sec: *custom,
+///             nanosec: 0,
+///         }
+///     }
+///
+///     fn convert_to_custom(ros: Time) -> i32 {
+///         ros.sec
+///     }
+/// }
+///
+/// assert_eq!(SecondsAdapter::convert_to_ros_message(&5).sec, 5);
+/// ```
+///
+/// [1]: crate::Node::create_adapted_publisher
+/// [2]: crate::Node::create_adapted_subscription
+pub trait TypeAdapter: 'static {
+    /// The custom type that publishers and subscriptions work with.
+    type Custom: Send + 'static;
+    /// The ROS message type that is used on the wire.
+    type Ros: Message;
+
+    /// Converts a value of the custom type to a ROS message.
+    fn convert_to_ros_message(custom: &Self::Custom) -> Self::Ros;
+
+    /// Converts a ROS message to a value of the custom type.
+    fn convert_to_custom(ros: Self::Ros) -> Self::Custom;
+}
+
+/// Struct for sending values of a custom type, which are converted to ROS messages by the
+/// type adapter `A`.
+///
+/// Created by [`Node::create_adapted_publisher()`][1].
+///
+/// [1]: crate::Node::create_adapted_publisher
+pub struct AdaptedPublisher<A>
+where
+    A: TypeAdapter,
+{
+    publisher: Arc<Publisher<A::Ros>>,
+    adapter: PhantomData<fn() -> A>,
+}
+
+impl<A> AdaptedPublisher<A>
+where
+    A: TypeAdapter,
+{
+    pub(crate) fn new(publisher: Arc<Publisher<A::Ros>>) -> Self {
+        Self {
+            publisher,
+            adapter: PhantomData,
+        }
+    }
+
+    /// Converts the value to a ROS message and publishes it.
+    pub fn publish(&self, message: &A::Custom) -> Result<(), RclrsError> {
+        self.publisher.publish(A::convert_to_ros_message(message))
+    }
+
+    /// Returns the underlying publisher of ROS messages.
+    pub fn publisher(&self) -> &Arc<Publisher<A::Ros>> {
+        &self.publisher
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{spin_once, test_helpers::*, QOS_PROFILE_DEFAULT};
+    use std::{sync::Mutex, time::Duration};
+
+    struct StringAdapter;
+
+    impl TypeAdapter for StringAdapter {
+        type Custom = String;
+        type Ros = std_msgs::msg::String;
+
+        fn convert_to_ros_message(custom: &String) -> std_msgs::msg::String {
+            std_msgs::msg::String {
+                data: custom.clone(),
+            }
+        }
+
+        fn convert_to_custom(ros: std_msgs::msg::String) -> String {
+            ros.data
+        }
+    }
+
+    #[test]
+    fn traits() {
+        assert_send::<AdaptedPublisher<StringAdapter>>();
+        assert_sync::<AdaptedPublisher<StringAdapter>>();
+    }
+
+    #[test]
+    fn test_string_round_trip() -> Result<(), RclrsError> {
+        let namespace = "/test_type_adapter";
+        let graph = construct_test_graph(namespace)?;
+
+        let received = Arc::new(Mutex::new(None));
+        let received_in_callback = Arc::clone(&received);
+        let _subscription = graph
+            .node2
+            .create_adapted_subscription::<StringAdapter, _>(
+                "adapted_topic",
+                QOS_PROFILE_DEFAULT,
+                move |msg: String| {
+                    *received_in_callback.lock().unwrap() = Some(msg);
+                },
+            )?;
+        let publisher = graph
+            .node1
+            .create_adapted_publisher::<StringAdapter>("adapted_topic", QOS_PROFILE_DEFAULT)?;
+
+        for _ in 0..50 {
+            publisher.publish(&"Hello, adapter".to_string())?;
+            ignore_timeout(spin_once(
+                Arc::clone(&graph.node2),
+                Some(Duration::from_millis(100)),
+            ))?;
+            if received.lock().unwrap().is_some() {
+                break;
+            }
+        }
+
+        let msg = received
+            .lock()
+            .unwrap()
+            .take()
+            .expect("The subscription did not receive a message");
+        assert_eq!(msg, "Hello, adapter");
+        Ok(())
+    }
+}
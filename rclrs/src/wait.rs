@@ -15,18 +15,26 @@
 // DISTRIBUTION A. Approved for public release; distribution unlimited.
 // OPSEC #4584.
 
-use std::{sync::Arc, time::Duration, vec::Vec};
+use std::{
+    mem,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+    vec::Vec,
+};
 
 use crate::{
-    error::{to_rclrs_result, RclReturnCode, RclrsError, ToResult},
+    error::{RclReturnCode, RclrsError, ToResult},
     rcl_bindings::*,
-    ClientBase, Context, ContextHandle, Node, ServiceBase, SubscriptionBase,
+    ActionClientBase, ActionServerBase, ClientBase, Context, ContextHandle, EventBase, Node,
+    ServiceBase, SubscriptionBase, Timer,
 };
 
 mod exclusivity_guard;
 mod guard_condition;
+mod waitable;
 use exclusivity_guard::*;
 pub use guard_condition::*;
+pub use waitable::*;
 
 /// Manage the lifecycle of an `rcl_wait_set_t`, including managing its dependency
 /// on `rcl_context_t` by ensuring that this dependency is [dropped after][1] the
@@ -36,24 +44,259 @@ pub use guard_condition::*;
 struct WaitSetHandle {
     rcl_wait_set: rcl_wait_set_t,
     // Used to ensure the context is alive while the wait set is alive.
-    #[allow(dead_code)]
     context_handle: Arc<ContextHandle>,
 }
 
+/// The number of entities of each kind that a composite waitable, such as an action client,
+/// adds to a wait set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct WaitableNumEntities {
+    pub(crate) subscriptions: usize,
+    pub(crate) guard_conditions: usize,
+    pub(crate) timers: usize,
+    pub(crate) clients: usize,
+    pub(crate) services: usize,
+}
+
+impl std::ops::Add for WaitableNumEntities {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            subscriptions: self.subscriptions + other.subscriptions,
+            guard_conditions: self.guard_conditions + other.guard_conditions,
+            timers: self.timers + other.timers,
+            clients: self.clients + other.clients,
+            services: self.services + other.services,
+        }
+    }
+}
+
+// An entity in the wait set, together with its index in the corresponding array of the
+// rcl_wait_set_t. The index can differ from the position of the entry in its Vec, because
+// composite waitables such as action clients occupy several slots of the arrays.
+struct WaitSetEntry<T> {
+    guard: ExclusivityGuard<T>,
+    index: usize,
+}
+
+// A subscription in the wait set, together with the entry of the guard condition that signals
+// messages delivered through intra-process communication, if the subscription uses it.
+struct SubscriptionEntry {
+    subscription: WaitSetEntry<Arc<dyn SubscriptionBase>>,
+    intra_process: Option<WaitSetEntry<Arc<GuardCondition>>>,
+}
+
+// A custom waitable in the wait set, together with the entries of its guard conditions.
+struct WaitableEntry {
+    waitable: Arc<dyn Waitable>,
+    guard_conditions: Vec<WaitSetEntry<Arc<GuardCondition>>>,
+}
+
+// The number of entities of each kind that a wait set has room for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct WaitSetCapacity {
+    subscriptions: usize,
+    guard_conditions: usize,
+    timers: usize,
+    clients: usize,
+    services: usize,
+    events: usize,
+}
+
+// The waitable entities of some nodes, together with custom waitables, to be added to a wait set.
+//
+// The entities are moved into the wait set by add_to(), which leaves the buffers empty, so that
+// they can be filled again without allocating.
+#[derive(Default)]
+struct NodeEntities {
+    subscriptions: Vec<Arc<dyn SubscriptionBase>>,
+    clients: Vec<Arc<dyn ClientBase>>,
+    guard_conditions: Vec<Arc<GuardCondition>>,
+    services: Vec<Arc<dyn ServiceBase>>,
+    events: Vec<Arc<dyn EventBase>>,
+    timers: Vec<Arc<Timer>>,
+    action_clients: Vec<Arc<dyn ActionClientBase>>,
+    action_servers: Vec<Arc<dyn ActionServerBase>>,
+    waitables: Vec<Arc<dyn Waitable>>,
+}
+
+// Appends the entities that have not been dropped yet to `live`.
+fn extend_live<T: ?Sized>(entities_mtx: &Mutex<Vec<Weak<T>>>, live: &mut Vec<Arc<T>>) {
+    live.extend(
+        { entities_mtx.lock().unwrap() }
+            .iter()
+            .filter_map(Weak::upgrade),
+    );
+}
+
+impl NodeEntities {
+    // Adds the entities of the nodes, the additional guard conditions and the custom waitables.
+    // All nodes must belong to `context_handle`.
+    fn extend<'n>(
+        &mut self,
+        context_handle: &Arc<ContextHandle>,
+        nodes: impl IntoIterator<Item = &'n Node>,
+        guard_conditions: &[Arc<GuardCondition>],
+        waitables: &[Arc<dyn Waitable>],
+    ) {
+        for node in nodes {
+            debug_assert!(Arc::ptr_eq(&node.handle.context_handle, context_handle));
+            extend_live(&node.subscriptions_mtx, &mut self.subscriptions);
+            extend_live(&node.clients_mtx, &mut self.clients);
+            extend_live(&node.guard_conditions_mtx, &mut self.guard_conditions);
+            extend_live(&node.services_mtx, &mut self.services);
+            extend_live(&node.events_mtx, &mut self.events);
+            extend_live(&node.timers_mtx, &mut self.timers);
+            extend_live(&node.action_clients_mtx, &mut self.action_clients);
+            extend_live(&node.action_servers_mtx, &mut self.action_servers);
+        }
+        self.guard_conditions
+            .extend(guard_conditions.iter().map(Arc::clone));
+        self.waitables.extend(waitables.iter().map(Arc::clone));
+    }
+
+    fn capacity(&self) -> WaitSetCapacity {
+        let action_entities = self
+            .action_clients
+            .iter()
+            .map(|action_client| action_client.handle().num_entities)
+            .chain(
+                self.action_servers
+                    .iter()
+                    .map(|action_server| action_server.handle().num_entities),
+            )
+            .fold(WaitableNumEntities::default(), |sum, n| sum + n);
+        let intra_process_guard_conditions = self
+            .subscriptions
+            .iter()
+            .filter(|subscription| subscription.intra_process_guard_condition().is_some())
+            .count();
+        let waitable_guard_conditions: usize = self
+            .waitables
+            .iter()
+            .map(|waitable| waitable.guard_conditions().len())
+            .sum();
+        WaitSetCapacity {
+            subscriptions: self.subscriptions.len() + action_entities.subscriptions,
+            guard_conditions: self.guard_conditions.len()
+                + intra_process_guard_conditions
+                + action_entities.guard_conditions
+                + waitable_guard_conditions,
+            timers: self.timers.len() + action_entities.timers,
+            clients: self.clients.len() + action_entities.clients,
+            services: self.services.len() + action_entities.services,
+            events: self.events.len(),
+        }
+    }
+
+    // Moves the entities into the wait set. The buffers are empty afterwards, even on error.
+    fn add_to(&mut self, wait_set: &mut WaitSet) -> Result<(), RclrsError> {
+        let result = self.try_add_to(wait_set);
+        self.clear();
+        result
+    }
+
+    fn try_add_to(&mut self, wait_set: &mut WaitSet) -> Result<(), RclrsError> {
+        for subscription in self.subscriptions.drain(..) {
+            wait_set.add_subscription(subscription)?;
+        }
+        for client in self.clients.drain(..) {
+            wait_set.add_client(client)?;
+        }
+        for guard_condition in self.guard_conditions.drain(..) {
+            wait_set.add_guard_condition(guard_condition)?;
+        }
+        for service in self.services.drain(..) {
+            wait_set.add_service(service)?;
+        }
+        for event in self.events.drain(..) {
+            wait_set.add_event(event)?;
+        }
+        for timer in self.timers.drain(..) {
+            wait_set.add_timer(timer)?;
+        }
+        for action_client in self.action_clients.drain(..) {
+            wait_set.add_action_client(action_client)?;
+        }
+        for action_server in self.action_servers.drain(..) {
+            wait_set.add_action_server(action_server)?;
+        }
+        for waitable in self.waitables.drain(..) {
+            wait_set.add_waitable(waitable)?;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        self.subscriptions.clear();
+        self.clients.clear();
+        self.guard_conditions.clear();
+        self.services.clear();
+        self.events.clear();
+        self.timers.clear();
+        self.action_clients.clear();
+        self.action_servers.clear();
+        self.waitables.clear();
+    }
+}
+
 /// A struct for waiting on subscriptions and other waitable entities to become ready.
+///
+/// This is the building block of the executors, and can be used directly to implement custom
+/// spinning strategies: entities are added to the wait set, and [`WaitSet::wait`] returns the
+/// ones that became ready. It's then up to the caller to execute them, e.g. with
+/// [`SubscriptionBase::execute()`].
+///
+/// The wait set shares ownership of the entities added to it. Each entity can only be in one wait
+/// set at a time, and becomes available for other wait sets again when this wait set is cleared
+/// or dropped.
+///
+/// Waiting is interrupted when the context of the wait set is shut down, see
+/// [`Context::shutdown()`].
+///
+/// # Example
+/// ```
+/// # use rclrs::{Context, GuardCondition, RclrsError, WaitSet};
+/// # use std::{sync::Arc, time::Duration};
+/// let context = Context::new([])?;
+/// let guard_condition = Arc::new(GuardCondition::new(&context));
+///
+/// let mut wait_set = WaitSet::new(0, 1, 0, 0, 0, 0, &context)?;
+/// wait_set.add_guard_condition(Arc::clone(&guard_condition))?;
+///
+/// for _ in 0..2 {
+///     guard_condition.trigger()?;
+///     let ready_entities = wait_set.wait(Some(Duration::from_millis(10)))?;
+///     assert!(ready_entities.guard_conditions.contains(&guard_condition));
+/// }
+/// # Ok::<(), RclrsError>(())
+/// ```
 pub struct WaitSet {
     // The subscriptions that are currently registered in the wait set.
     // This correspondence is an invariant that must be maintained by all functions,
     // even in the error case.
-    subscriptions: Vec<ExclusivityGuard<Arc<dyn SubscriptionBase>>>,
-    clients: Vec<ExclusivityGuard<Arc<dyn ClientBase>>>,
+    subscriptions: Vec<SubscriptionEntry>,
+    clients: Vec<WaitSetEntry<Arc<dyn ClientBase>>>,
     // The guard conditions that are currently registered in the wait set.
-    guard_conditions: Vec<ExclusivityGuard<Arc<GuardCondition>>>,
-    services: Vec<ExclusivityGuard<Arc<dyn ServiceBase>>>,
+    guard_conditions: Vec<WaitSetEntry<Arc<GuardCondition>>>,
+    services: Vec<WaitSetEntry<Arc<dyn ServiceBase>>>,
+    events: Vec<WaitSetEntry<Arc<dyn EventBase>>>,
+    timers: Vec<WaitSetEntry<Arc<Timer>>>,
+    action_clients: Vec<ExclusivityGuard<Arc<dyn ActionClientBase>>>,
+    action_servers: Vec<ExclusivityGuard<Arc<dyn ActionServerBase>>>,
+    waitables: Vec<WaitableEntry>,
     handle: WaitSetHandle,
+    // The entities that are being added by refill_for_nodes_and_waitables(), kept between calls
+    // so that refilling does not allocate.
+    entities: NodeEntities,
+    // Whether rcl_wait() was called since the entities were added. Waiting removes the entities
+    // that are not ready from the rcl_wait_set_t, so they need to be added again.
+    needs_refill: bool,
 }
 
 /// A list of entities that are ready, returned by [`WaitSet::wait`].
+#[derive(Default)]
 pub struct ReadyEntities {
     /// A list of subscriptions that have potentially received messages.
     pub subscriptions: Vec<Arc<dyn SubscriptionBase>>,
@@ -63,13 +306,25 @@ pub struct ReadyEntities {
     pub guard_conditions: Vec<Arc<GuardCondition>>,
     /// A list of services that have potentially received requests.
     pub services: Vec<Arc<dyn ServiceBase>>,
+    /// A list of events that have potentially occurred.
+    pub events: Vec<Arc<dyn EventBase>>,
+    /// A list of timers that are potentially due.
+    pub timers: Vec<Arc<Timer>>,
+    /// A list of action clients that have potentially received responses, feedback or status
+    /// messages.
+    pub action_clients: Vec<Arc<dyn ActionClientBase>>,
+    /// A list of action servers that have potentially received requests, or whose finished
+    /// goals have potentially expired.
+    pub action_servers: Vec<Arc<dyn ActionServerBase>>,
+    /// A list of custom waitables that are ready to be executed.
+    pub waitables: Vec<Arc<dyn Waitable>>,
 }
 
 impl Drop for rcl_wait_set_t {
     fn drop(&mut self) {
         // SAFETY: No preconditions for this function (besides passing in a valid wait set).
         let rc = unsafe { rcl_wait_set_fini(self) };
-        if let Err(e) = to_rclrs_result(rc) {
+        if let Err(e) = rc.ok_for("rcl_wait_set_fini") {
             panic!("Unable to release WaitSet. {:?}", e)
         }
     }
@@ -89,6 +344,9 @@ impl WaitSet {
     ///
     /// The given number of subscriptions is a capacity, corresponding to how often
     /// [`WaitSet::add_subscription`] may be called.
+    ///
+    /// The wait set additionally reserves room for a guard condition of the context, which wakes
+    /// it up when the context is shut down.
     pub fn new(
         number_of_subscriptions: usize,
         number_of_guard_conditions: usize,
@@ -107,66 +365,150 @@ impl WaitSet {
             rcl_wait_set_init(
                 &mut rcl_wait_set,
                 number_of_subscriptions,
-                number_of_guard_conditions,
+                number_of_guard_conditions + 1,
                 number_of_timers,
                 number_of_clients,
                 number_of_services,
                 number_of_events,
                 &mut *rcl_context,
-                rcutils_get_default_allocator(),
+                context.handle.rcl_allocator(),
             )
-            .ok()?;
+            .ok_for("rcl_wait_set_init")?;
             rcl_wait_set
         };
-        Ok(Self {
+        let mut wait_set = Self {
             subscriptions: Vec::new(),
             guard_conditions: Vec::new(),
             clients: Vec::new(),
             services: Vec::new(),
+            events: Vec::new(),
+            timers: Vec::new(),
+            action_clients: Vec::new(),
+            action_servers: Vec::new(),
+            waitables: Vec::new(),
             handle: WaitSetHandle {
                 rcl_wait_set,
                 context_handle: Arc::clone(&context.handle),
             },
-        })
+            entities: NodeEntities::default(),
+            needs_refill: false,
+        };
+        add_shutdown_guard_condition(
+            &mut wait_set.handle.rcl_wait_set,
+            &wait_set.handle.context_handle,
+        )?;
+        Ok(wait_set)
     }
 
     /// Creates a new wait set and adds all waitable entities in the node to it.
     ///
     /// The wait set is sized to fit the node exactly, so there is no capacity for adding other entities.
     pub fn new_for_node(node: &Node) -> Result<Self, RclrsError> {
-        let live_subscriptions = node.live_subscriptions();
-        let live_clients = node.live_clients();
-        let live_guard_conditions = node.live_guard_conditions();
-        let live_services = node.live_services();
+        Self::new_for_nodes_and_waitables(&[node], &[])
+    }
+
+    /// Creates a new wait set and adds all waitable entities in the nodes to it, as well as the
+    /// given custom waitables.
+    ///
+    /// There must be at least one node, and all nodes must belong to the same context.
+    pub(crate) fn new_for_nodes_and_waitables(
+        nodes: &[&Node],
+        waitables: &[Arc<dyn Waitable>],
+    ) -> Result<Self, RclrsError> {
+        let context_handle = &nodes[0].handle.context_handle;
+        let mut entities = NodeEntities::default();
+        entities.extend(context_handle, nodes.iter().copied(), &[], waitables);
+        let capacity = entities.capacity();
         let ctx = Context {
-            handle: Arc::clone(&node.handle.context_handle),
+            handle: Arc::clone(context_handle),
         };
         let mut wait_set = WaitSet::new(
-            live_subscriptions.len(),
-            live_guard_conditions.len(),
-            0,
-            live_clients.len(),
-            live_services.len(),
-            0,
+            capacity.subscriptions,
+            capacity.guard_conditions,
+            capacity.timers,
+            capacity.clients,
+            capacity.services,
+            capacity.events,
             &ctx,
         )?;
+        entities.add_to(&mut wait_set)?;
+        Ok(wait_set)
+    }
 
-        for live_subscription in &live_subscriptions {
-            wait_set.add_subscription(live_subscription.clone())?;
-        }
-
-        for live_client in &live_clients {
-            wait_set.add_client(live_client.clone())?;
+    /// Removes all entities from the wait set, and adds all waitable entities in the nodes, the
+    /// additional guard conditions and the given custom waitables to it, like
+    /// [`WaitSet::new_for_nodes_and_waitables`].
+    ///
+    /// The wait set only grows if it is too small for the entities. Refilling it with the same
+    /// entities again does not allocate, neither in rcl nor on the Rust side, apart from the
+    /// guard conditions returned by [`Waitable::guard_conditions()`].
+    ///
+    /// All nodes must belong to the context of the wait set.
+    pub(crate) fn refill_for_nodes_and_waitables<'n>(
+        &mut self,
+        nodes: impl IntoIterator<Item = &'n Node>,
+        guard_conditions: &[Arc<GuardCondition>],
+        waitables: &[Arc<dyn Waitable>],
+    ) -> Result<(), RclrsError> {
+        self.clear();
+        let mut entities = mem::take(&mut self.entities);
+        entities.extend(
+            &self.handle.context_handle,
+            nodes,
+            guard_conditions,
+            waitables,
+        );
+        let result = self
+            .reserve(entities.capacity())
+            .and_then(|()| entities.add_to(self));
+        // add_to() leaves the buffers empty, but it isn't called if reserving failed.
+        entities.clear();
+        self.entities = entities;
+        if result.is_err() {
+            // Don't keep some of the entities in use, where no other wait set can add them.
+            self.clear();
         }
+        result
+    }
 
-        for live_guard_condition in &live_guard_conditions {
-            wait_set.add_guard_condition(live_guard_condition.clone())?;
+    // Grows the rcl_wait_set_t to at least the given capacity. The wait set must be empty.
+    fn reserve(&mut self, capacity: WaitSetCapacity) -> Result<(), RclrsError> {
+        let rcl_wait_set = &mut self.handle.rcl_wait_set;
+        let current = WaitSetCapacity {
+            subscriptions: rcl_wait_set.size_of_subscriptions,
+            // The guard condition of the context is not part of the capacity, see WaitSet::new.
+            guard_conditions: rcl_wait_set.size_of_guard_conditions - 1,
+            timers: rcl_wait_set.size_of_timers,
+            clients: rcl_wait_set.size_of_clients,
+            services: rcl_wait_set.size_of_services,
+            events: rcl_wait_set.size_of_events,
+        };
+        let grown = WaitSetCapacity {
+            subscriptions: current.subscriptions.max(capacity.subscriptions),
+            guard_conditions: current.guard_conditions.max(capacity.guard_conditions),
+            timers: current.timers.max(capacity.timers),
+            clients: current.clients.max(capacity.clients),
+            services: current.services.max(capacity.services),
+            events: current.events.max(capacity.events),
+        };
+        if grown == current {
+            return Ok(());
         }
-
-        for live_service in &live_services {
-            wait_set.add_service(live_service.clone())?;
+        // SAFETY: The wait set is valid, and it's empty, so no indices of entries become invalid.
+        unsafe {
+            rcl_wait_set_resize(
+                rcl_wait_set,
+                grown.subscriptions,
+                grown.guard_conditions + 1,
+                grown.timers,
+                grown.clients,
+                grown.services,
+                grown.events,
+            )
         }
-        Ok(wait_set)
+        .ok_for("rcl_wait_set_resize")?;
+        // Resizing removes all entities from the rcl_wait_set_t.
+        add_shutdown_guard_condition(rcl_wait_set, &self.handle.context_handle)
     }
 
     /// Removes all entities from the wait set.
@@ -178,16 +520,77 @@ impl WaitSet {
         self.guard_conditions.clear();
         self.clients.clear();
         self.services.clear();
+        self.events.clear();
+        self.timers.clear();
+        self.action_clients.clear();
+        self.action_servers.clear();
+        self.waitables.clear();
         // This cannot fail – the rcl_wait_set_clear function only checks that the input handle is
         // valid, which it always is in our case. Hence, only debug_assert instead of returning
         // Result.
         // SAFETY: No preconditions for this function (besides passing in a valid wait set).
         let ret = unsafe { rcl_wait_set_clear(&mut self.handle.rcl_wait_set) };
         debug_assert_eq!(ret, 0);
+        // This cannot fail either, since the wait set always has capacity for this guard condition.
+        let ret = add_shutdown_guard_condition(
+            &mut self.handle.rcl_wait_set,
+            &self.handle.context_handle,
+        );
+        debug_assert!(ret.is_ok());
+        self.needs_refill = false;
+    }
+
+    // Adds all entities to the rcl_wait_set_t again, after the previous wait removed those
+    // that were not ready.
+    fn refill(&mut self) -> Result<(), RclrsError> {
+        // SAFETY: No preconditions for this function (besides passing in a valid wait set).
+        unsafe { rcl_wait_set_clear(&mut self.handle.rcl_wait_set) }
+            .ok_for("rcl_wait_set_clear")?;
+        let rcl_wait_set = &mut self.handle.rcl_wait_set;
+        add_shutdown_guard_condition(rcl_wait_set, &self.handle.context_handle)?;
+        for entry in &mut self.subscriptions {
+            entry.subscription.index =
+                add_subscription(rcl_wait_set, &*entry.subscription.guard.waitable)?;
+            if let Some(intra_process) = &mut entry.intra_process {
+                intra_process.index =
+                    add_guard_condition(rcl_wait_set, &intra_process.guard.waitable)?;
+            }
+        }
+        for entry in &mut self.guard_conditions {
+            entry.index = add_guard_condition(rcl_wait_set, &entry.guard.waitable)?;
+        }
+        for entry in &mut self.clients {
+            entry.index = add_client(rcl_wait_set, &*entry.guard.waitable)?;
+        }
+        for entry in &mut self.services {
+            entry.index = add_service(rcl_wait_set, &*entry.guard.waitable)?;
+        }
+        for entry in &mut self.events {
+            entry.index = add_event(rcl_wait_set, &*entry.guard.waitable)?;
+        }
+        for entry in &mut self.timers {
+            entry.index = add_timer(rcl_wait_set, &entry.guard.waitable)?;
+        }
+        for action_client in &self.action_clients {
+            add_action_client(rcl_wait_set, &*action_client.waitable)?;
+        }
+        for action_server in &self.action_servers {
+            add_action_server(rcl_wait_set, &*action_server.waitable)?;
+        }
+        for waitable in &mut self.waitables {
+            for entry in &mut waitable.guard_conditions {
+                entry.index = add_guard_condition(rcl_wait_set, &entry.guard.waitable)?;
+            }
+        }
+        self.needs_refill = false;
+        Ok(())
     }
 
     /// Adds a subscription to the wait set.
     ///
+    /// A subscription that uses [intra-process communication][3] additionally needs capacity for
+    /// a guard condition.
+    ///
     /// # Errors
     /// - If the subscription was already added to this wait set or another one,
     ///   [`AlreadyAddedToWaitSet`][1] will be returned
@@ -196,6 +599,7 @@ impl WaitSet {
     ///
     /// [1]: crate::RclrsError
     /// [2]: crate::RclReturnCode
+    /// [3]: crate::NodeBuilder::use_intra_process_comms
     pub fn add_subscription(
         &mut self,
         subscription: Arc<dyn SubscriptionBase>,
@@ -204,18 +608,35 @@ impl WaitSet {
             Arc::clone(&subscription),
             Arc::clone(&subscription.handle().in_use_by_wait_set),
         )?;
-        unsafe {
-            // SAFETY: I'm not sure if it's required, but the subscription pointer will remain valid
-            // for as long as the wait set exists, because it's stored in self.subscriptions.
-            // Passing in a null pointer for the third argument is explicitly allowed.
-            rcl_wait_set_add_subscription(
-                &mut self.handle.rcl_wait_set,
-                &*subscription.handle().lock(),
-                std::ptr::null_mut(),
-            )
-        }
-        .ok()?;
-        self.subscriptions.push(exclusive_subscription);
+        let exclusive_guard_condition = match subscription.intra_process_guard_condition() {
+            Some(guard_condition) => Some(ExclusivityGuard::new(
+                Arc::clone(guard_condition),
+                Arc::clone(&guard_condition.in_use_by_wait_set),
+            )?),
+            None => None,
+        };
+        let index = add_subscription(&mut self.handle.rcl_wait_set, &*subscription)?;
+        let intra_process = match exclusive_guard_condition {
+            Some(guard) => {
+                match add_guard_condition(&mut self.handle.rcl_wait_set, &guard.waitable) {
+                    Ok(index) => Some(WaitSetEntry { guard, index }),
+                    Err(error) => {
+                        // The subscription was already added. It is removed again by refilling the
+                        // wait set before the next wait.
+                        self.needs_refill = true;
+                        return Err(error);
+                    }
+                }
+            }
+            None => None,
+        };
+        self.subscriptions.push(SubscriptionEntry {
+            subscription: WaitSetEntry {
+                guard: exclusive_subscription,
+                index,
+            },
+            intra_process,
+        });
         Ok(())
     }
 
@@ -237,17 +658,11 @@ impl WaitSet {
             Arc::clone(&guard_condition),
             Arc::clone(&guard_condition.in_use_by_wait_set),
         )?;
-
-        unsafe {
-            // SAFETY: Safe if the wait set and guard condition are initialized
-            rcl_wait_set_add_guard_condition(
-                &mut self.handle.rcl_wait_set,
-                &*guard_condition.handle.rcl_guard_condition.lock().unwrap(),
-                std::ptr::null_mut(),
-            )
-            .ok()?;
-        }
-        self.guard_conditions.push(exclusive_guard_condition);
+        let index = add_guard_condition(&mut self.handle.rcl_wait_set, &guard_condition)?;
+        self.guard_conditions.push(WaitSetEntry {
+            guard: exclusive_guard_condition,
+            index,
+        });
         Ok(())
     }
 
@@ -266,18 +681,11 @@ impl WaitSet {
             Arc::clone(&client),
             Arc::clone(&client.handle().in_use_by_wait_set),
         )?;
-        unsafe {
-            // SAFETY: I'm not sure if it's required, but the client pointer will remain valid
-            // for as long as the wait set exists, because it's stored in self.clients.
-            // Passing in a null pointer for the third argument is explicitly allowed.
-            rcl_wait_set_add_client(
-                &mut self.handle.rcl_wait_set,
-                &*client.handle().lock() as *const _,
-                core::ptr::null_mut(),
-            )
-        }
-        .ok()?;
-        self.clients.push(exclusive_client);
+        let index = add_client(&mut self.handle.rcl_wait_set, &*client)?;
+        self.clients.push(WaitSetEntry {
+            guard: exclusive_client,
+            index,
+        });
         Ok(())
     }
 
@@ -296,18 +704,159 @@ impl WaitSet {
             Arc::clone(&service),
             Arc::clone(&service.handle().in_use_by_wait_set),
         )?;
-        unsafe {
-            // SAFETY: I'm not sure if it's required, but the service pointer will remain valid
-            // for as long as the wait set exists, because it's stored in self.services.
-            // Passing in a null pointer for the third argument is explicitly allowed.
-            rcl_wait_set_add_service(
+        let index = add_service(&mut self.handle.rcl_wait_set, &*service)?;
+        self.services.push(WaitSetEntry {
+            guard: exclusive_service,
+            index,
+        });
+        Ok(())
+    }
+
+    /// Adds an event to the wait set.
+    ///
+    /// # Errors
+    /// - If the event was already added to this wait set or another one,
+    ///   [`AlreadyAddedToWaitSet`][1] will be returned
+    /// - If the number of events in the wait set is larger than the
+    ///   capacity set in [`WaitSet::new`], [`WaitSetFull`][2] will be returned
+    ///
+    /// [1]: crate::RclrsError
+    /// [2]: crate::RclReturnCode
+    pub fn add_event(&mut self, event: Arc<dyn EventBase>) -> Result<(), RclrsError> {
+        let exclusive_event = ExclusivityGuard::new(
+            Arc::clone(&event),
+            Arc::clone(&event.handle().in_use_by_wait_set),
+        )?;
+        let index = add_event(&mut self.handle.rcl_wait_set, &*event)?;
+        self.events.push(WaitSetEntry {
+            guard: exclusive_event,
+            index,
+        });
+        Ok(())
+    }
+
+    /// Adds a timer to the wait set.
+    ///
+    /// # Errors
+    /// - If the timer was already added to this wait set or another one,
+    ///   [`AlreadyAddedToWaitSet`][1] will be returned
+    /// - If the number of timers in the wait set is larger than the
+    ///   capacity set in [`WaitSet::new`], [`WaitSetFull`][2] will be returned
+    ///
+    /// [1]: crate::RclrsError
+    /// [2]: crate::RclReturnCode
+    pub fn add_timer(&mut self, timer: Arc<Timer>) -> Result<(), RclrsError> {
+        let exclusive_timer = ExclusivityGuard::new(
+            Arc::clone(&timer),
+            Arc::clone(&timer.handle.in_use_by_wait_set),
+        )?;
+        let index = add_timer(&mut self.handle.rcl_wait_set, &timer)?;
+        self.timers.push(WaitSetEntry {
+            guard: exclusive_timer,
+            index,
+        });
+        Ok(())
+    }
+
+    /// Adds an action client to the wait set.
+    ///
+    /// An action client consists of several subscriptions and clients, which all need capacity
+    /// in the wait set.
+    ///
+    /// # Errors
+    /// - If the action client was already added to this wait set or another one,
+    ///   [`AlreadyAddedToWaitSet`][1] will be returned
+    /// - If the number of entities in the wait set is larger than the
+    ///   capacity set in [`WaitSet::new`], [`WaitSetFull`][2] will be returned
+    ///
+    /// [1]: crate::RclrsError
+    /// [2]: crate::RclReturnCode
+    pub fn add_action_client(
+        &mut self,
+        action_client: Arc<dyn ActionClientBase>,
+    ) -> Result<(), RclrsError> {
+        let exclusive_action_client = ExclusivityGuard::new(
+            Arc::clone(&action_client),
+            Arc::clone(&action_client.handle().in_use_by_wait_set),
+        )?;
+        add_action_client(&mut self.handle.rcl_wait_set, &*action_client)?;
+        self.action_clients.push(exclusive_action_client);
+        Ok(())
+    }
+
+    /// Adds an action server to the wait set.
+    ///
+    /// An action server consists of several services and a timer, which all need capacity
+    /// in the wait set.
+    ///
+    /// # Errors
+    /// - If the action server was already added to this wait set or another one,
+    ///   [`AlreadyAddedToWaitSet`][1] will be returned
+    /// - If the number of entities in the wait set is larger than the
+    ///   capacity set in [`WaitSet::new`], [`WaitSetFull`][2] will be returned
+    ///
+    /// [1]: crate::RclrsError
+    /// [2]: crate::RclReturnCode
+    pub fn add_action_server(
+        &mut self,
+        action_server: Arc<dyn ActionServerBase>,
+    ) -> Result<(), RclrsError> {
+        let exclusive_action_server = ExclusivityGuard::new(
+            Arc::clone(&action_server),
+            Arc::clone(&action_server.handle().in_use_by_wait_set),
+        )?;
+        add_action_server(&mut self.handle.rcl_wait_set, &*action_server)?;
+        self.action_servers.push(exclusive_action_server);
+        Ok(())
+    }
+
+    /// Adds a custom waitable to the wait set.
+    ///
+    /// Each guard condition of the waitable needs capacity in the wait set. The waitable is
+    /// reported as ready by [`WaitSet::wait`] if one of its guard conditions was triggered and
+    /// [`Waitable::is_ready()`] returns `true`.
+    ///
+    /// # Errors
+    /// - If one of the guard conditions was already added to this wait set or another one,
+    ///   [`AlreadyAddedToWaitSet`][1] will be returned
+    /// - If the number of guard conditions in the wait set is larger than the
+    ///   capacity set in [`WaitSet::new`], [`WaitSetFull`][2] will be returned
+    ///
+    /// [1]: crate::RclrsError
+    /// [2]: crate::RclReturnCode
+    pub fn add_waitable(&mut self, waitable: Arc<dyn Waitable>) -> Result<(), RclrsError> {
+        let guard_conditions = waitable
+            .guard_conditions()
+            .into_iter()
+            .map(|guard_condition| {
+                Ok(WaitSetEntry {
+                    guard: ExclusivityGuard::new(
+                        Arc::clone(&guard_condition),
+                        Arc::clone(&guard_condition.in_use_by_wait_set),
+                    )?,
+                    index: 0,
+                })
+            })
+            .collect::<Result<Vec<_>, RclrsError>>()?;
+        let mut entry = WaitableEntry {
+            waitable,
+            guard_conditions,
+        };
+        for guard_condition in &mut entry.guard_conditions {
+            match add_guard_condition(
                 &mut self.handle.rcl_wait_set,
-                &*service.handle().lock() as *const _,
-                core::ptr::null_mut(),
-            )
+                &guard_condition.guard.waitable,
+            ) {
+                Ok(index) => guard_condition.index = index,
+                Err(error) => {
+                    // Some guard conditions of the waitable may already have been added. They
+                    // are removed again by refilling the wait set before the next wait.
+                    self.needs_refill = true;
+                    return Err(error);
+                }
+            }
         }
-        .ok()?;
-        self.services.push(exclusive_service);
+        self.waitables.push(entry);
         Ok(())
     }
 
@@ -323,7 +872,8 @@ impl WaitSet {
     /// that period of time has elapsed or the wait set becomes ready, which ever
     /// comes first.
     ///
-    /// This function does not change the entities registered in the wait set.
+    /// This function does not change the entities registered in the wait set, so the wait set
+    /// can be waited on repeatedly, e.g. in a custom spin loop.
     ///
     /// # Errors
     ///
@@ -334,93 +884,432 @@ impl WaitSet {
     /// This list is not comprehensive, since further errors may occur in the `rmw` or `rcl` layers.
     ///
     /// [1]: std::time::Duration::ZERO
-    pub fn wait(mut self, timeout: Option<Duration>) -> Result<ReadyEntities, RclrsError> {
+    pub fn wait(&mut self, timeout: Option<Duration>) -> Result<ReadyEntities, RclrsError> {
+        let mut ready_entities = ReadyEntities::default();
+        self.wait_into(timeout, &mut ready_entities)?;
+        Ok(ready_entities)
+    }
+
+    // Like wait(), but stores the ready entities in `ready_entities`, replacing its previous
+    // content. Its buffers are grown to fit all entities of the wait set, so that waiting again
+    // does not allocate.
+    pub(crate) fn wait_into(
+        &mut self,
+        timeout: Option<Duration>,
+        ready_entities: &mut ReadyEntities,
+    ) -> Result<(), RclrsError> {
+        ready_entities.clear();
         let timeout_ns = match timeout.map(|d| d.as_nanos()) {
             None => -1,
             Some(ns) if ns <= i64::MAX as u128 => ns as i64,
             _ => {
                 return Err(RclrsError::RclError {
                     code: RclReturnCode::InvalidArgument,
+                    operation: None,
                     msg: None,
                 })
             }
         };
+        if self.needs_refill {
+            self.refill()?;
+        }
+        self.needs_refill = true;
         // SAFETY: The comments in rcl mention "This function cannot operate on the same wait set
         // in multiple threads, and the wait sets may not share content."
         // We cannot currently guarantee that the wait sets may not share content, but it is
         // mentioned in the doc comment for `add_subscription`.
         // Also, the rcl_wait_set is obviously valid.
-        match unsafe { rcl_wait(&mut self.handle.rcl_wait_set, timeout_ns) }.ok() {
+        match unsafe { rcl_wait(&mut self.handle.rcl_wait_set, timeout_ns) }.ok_for("rcl_wait") {
             Ok(_) => (),
             Err(error) => match error {
-                RclrsError::RclError { code, msg } => match code {
+                RclrsError::RclError {
+                    code,
+                    operation,
+                    msg,
+                } => match code {
                     RclReturnCode::WaitSetEmpty => (),
-                    _ => return Err(RclrsError::RclError { code, msg }),
+                    _ => {
+                        return Err(RclrsError::RclError {
+                            code,
+                            operation,
+                            msg,
+                        })
+                    }
                 },
                 _ => return Err(error),
             },
         }
-        let mut ready_entities = ReadyEntities {
-            subscriptions: Vec::new(),
-            clients: Vec::new(),
-            guard_conditions: Vec::new(),
-            services: Vec::new(),
-        };
-        for (i, subscription) in self.subscriptions.iter().enumerate() {
+        ready_entities.reserve_for(self);
+        for entry in &self.subscriptions {
             // SAFETY: The `subscriptions` entry is an array of pointers, and this dereferencing is
             // equivalent to
             // https://github.com/ros2/rcl/blob/35a31b00a12f259d492bf53c0701003bd7f1745c/rcl/include/rcl/wait.h#L419
-            let wait_set_entry = unsafe { *self.handle.rcl_wait_set.subscriptions.add(i) };
-            if !wait_set_entry.is_null() {
+            let wait_set_entry = unsafe {
+                *self
+                    .handle
+                    .rcl_wait_set
+                    .subscriptions
+                    .add(entry.subscription.index)
+            };
+            let is_intra_process_triggered = entry.intra_process.as_ref().map_or(false, |gc| {
+                // SAFETY: Same as above, for the `guard_conditions` entry.
+                let wait_set_entry =
+                    unsafe { *self.handle.rcl_wait_set.guard_conditions.add(gc.index) };
+                !wait_set_entry.is_null()
+            });
+            if !wait_set_entry.is_null() || is_intra_process_triggered {
                 ready_entities
                     .subscriptions
-                    .push(Arc::clone(&subscription.waitable));
+                    .push(Arc::clone(&entry.subscription.guard.waitable));
             }
         }
 
-        for (i, client) in self.clients.iter().enumerate() {
+        for client in &self.clients {
             // SAFETY: The `clients` entry is an array of pointers, and this dereferencing is
             // equivalent to
             // https://github.com/ros2/rcl/blob/35a31b00a12f259d492bf53c0701003bd7f1745c/rcl/include/rcl/wait.h#L419
-            let wait_set_entry = unsafe { *self.handle.rcl_wait_set.clients.add(i) };
+            let wait_set_entry = unsafe { *self.handle.rcl_wait_set.clients.add(client.index) };
             if !wait_set_entry.is_null() {
-                ready_entities.clients.push(Arc::clone(&client.waitable));
+                ready_entities
+                    .clients
+                    .push(Arc::clone(&client.guard.waitable));
             }
         }
 
-        for (i, guard_condition) in self.guard_conditions.iter().enumerate() {
+        for guard_condition in &self.guard_conditions {
             // SAFETY: The `clients` entry is an array of pointers, and this dereferencing is
             // equivalent to
             // https://github.com/ros2/rcl/blob/35a31b00a12f259d492bf53c0701003bd7f1745c/rcl/include/rcl/wait.h#L419
-            let wait_set_entry = unsafe { *self.handle.rcl_wait_set.guard_conditions.add(i) };
+            let wait_set_entry = unsafe {
+                *self
+                    .handle
+                    .rcl_wait_set
+                    .guard_conditions
+                    .add(guard_condition.index)
+            };
             if !wait_set_entry.is_null() {
                 ready_entities
                     .guard_conditions
-                    .push(Arc::clone(&guard_condition.waitable));
+                    .push(Arc::clone(&guard_condition.guard.waitable));
             }
         }
 
-        for (i, service) in self.services.iter().enumerate() {
+        for service in &self.services {
             // SAFETY: The `services` entry is an array of pointers, and this dereferencing is
             // equivalent to
             // https://github.com/ros2/rcl/blob/35a31b00a12f259d492bf53c0701003bd7f1745c/rcl/include/rcl/wait.h#L419
-            let wait_set_entry = unsafe { *self.handle.rcl_wait_set.services.add(i) };
+            let wait_set_entry = unsafe { *self.handle.rcl_wait_set.services.add(service.index) };
             if !wait_set_entry.is_null() {
-                ready_entities.services.push(Arc::clone(&service.waitable));
+                ready_entities
+                    .services
+                    .push(Arc::clone(&service.guard.waitable));
             }
         }
-        Ok(ready_entities)
+
+        for event in &self.events {
+            // SAFETY: The `events` entry is an array of pointers, and this dereferencing is
+            // equivalent to
+            // https://github.com/ros2/rcl/blob/35a31b00a12f259d492bf53c0701003bd7f1745c/rcl/include/rcl/wait.h#L419
+            let wait_set_entry = unsafe { *self.handle.rcl_wait_set.events.add(event.index) };
+            if !wait_set_entry.is_null() {
+                ready_entities
+                    .events
+                    .push(Arc::clone(&event.guard.waitable));
+            }
+        }
+
+        for timer in &self.timers {
+            // SAFETY: The `timers` entry is an array of pointers, and this dereferencing is
+            // equivalent to
+            // https://github.com/ros2/rcl/blob/35a31b00a12f259d492bf53c0701003bd7f1745c/rcl/include/rcl/wait.h#L419
+            let wait_set_entry = unsafe { *self.handle.rcl_wait_set.timers.add(timer.index) };
+            if !wait_set_entry.is_null() {
+                ready_entities
+                    .timers
+                    .push(Arc::clone(&timer.guard.waitable));
+            }
+        }
+
+        for action_client in &self.action_clients {
+            let mut is_feedback_ready = false;
+            let mut is_status_ready = false;
+            let mut is_goal_response_ready = false;
+            let mut is_cancel_response_ready = false;
+            let mut is_result_response_ready = false;
+            // SAFETY: The action client was added to this wait set, which has been waited on,
+            // and the out-parameters are valid pointers.
+            unsafe {
+                rcl_action_client_wait_set_get_entities_ready(
+                    &self.handle.rcl_wait_set,
+                    &*action_client.waitable.handle().lock(),
+                    &mut is_feedback_ready,
+                    &mut is_status_ready,
+                    &mut is_goal_response_ready,
+                    &mut is_cancel_response_ready,
+                    &mut is_result_response_ready,
+                )
+                .ok_for("rcl_action_client_wait_set_get_entities_ready")?;
+            }
+            if is_feedback_ready
+                || is_status_ready
+                || is_goal_response_ready
+                || is_cancel_response_ready
+                || is_result_response_ready
+            {
+                ready_entities
+                    .action_clients
+                    .push(Arc::clone(&action_client.waitable));
+            }
+        }
+
+        for action_server in &self.action_servers {
+            let mut is_goal_request_ready = false;
+            let mut is_cancel_request_ready = false;
+            let mut is_result_request_ready = false;
+            let mut is_goal_expired = false;
+            // SAFETY: The action server was added to this wait set, which has been waited on,
+            // and the out-parameters are valid pointers.
+            unsafe {
+                rcl_action_server_wait_set_get_entities_ready(
+                    &self.handle.rcl_wait_set,
+                    &*action_server.waitable.handle().lock(),
+                    &mut is_goal_request_ready,
+                    &mut is_cancel_request_ready,
+                    &mut is_result_request_ready,
+                    &mut is_goal_expired,
+                )
+                .ok_for("rcl_action_server_wait_set_get_entities_ready")?;
+            }
+            if is_goal_request_ready
+                || is_cancel_request_ready
+                || is_result_request_ready
+                || is_goal_expired
+            {
+                ready_entities
+                    .action_servers
+                    .push(Arc::clone(&action_server.waitable));
+            }
+        }
+
+        for waitable in &self.waitables {
+            let is_triggered = waitable.guard_conditions.iter().any(|guard_condition| {
+                // SAFETY: The `guard_conditions` entry is an array of pointers, and this
+                // dereferencing is equivalent to
+                // https://github.com/ros2/rcl/blob/35a31b00a12f259d492bf53c0701003bd7f1745c/rcl/include/rcl/wait.h#L419
+                let wait_set_entry = unsafe {
+                    *self
+                        .handle
+                        .rcl_wait_set
+                        .guard_conditions
+                        .add(guard_condition.index)
+                };
+                !wait_set_entry.is_null()
+            });
+            if is_triggered && waitable.waitable.is_ready() {
+                ready_entities
+                    .waitables
+                    .push(Arc::clone(&waitable.waitable));
+            }
+        }
+        Ok(())
     }
 }
 
+impl ReadyEntities {
+    pub(crate) fn clear(&mut self) {
+        self.subscriptions.clear();
+        self.clients.clear();
+        self.guard_conditions.clear();
+        self.services.clear();
+        self.events.clear();
+        self.timers.clear();
+        self.action_clients.clear();
+        self.action_servers.clear();
+        self.waitables.clear();
+    }
+
+    // Makes room for all entities of the wait set, so that none of the buffers grows while the
+    // ready entities are collected. Only allocates if the buffers are too small.
+    fn reserve_for(&mut self, wait_set: &WaitSet) {
+        self.subscriptions.reserve(wait_set.subscriptions.len());
+        self.clients.reserve(wait_set.clients.len());
+        self.guard_conditions
+            .reserve(wait_set.guard_conditions.len());
+        self.services.reserve(wait_set.services.len());
+        self.events.reserve(wait_set.events.len());
+        self.timers.reserve(wait_set.timers.len());
+        self.action_clients.reserve(wait_set.action_clients.len());
+        self.action_servers.reserve(wait_set.action_servers.len());
+        self.waitables.reserve(wait_set.waitables.len());
+    }
+}
+
+// The following functions add an entity to the rcl_wait_set_t and return its index in the
+// corresponding array.
+
+fn add_subscription(
+    rcl_wait_set: &mut rcl_wait_set_t,
+    subscription: &dyn SubscriptionBase,
+) -> Result<usize, RclrsError> {
+    let mut index = 0;
+    unsafe {
+        // SAFETY: I'm not sure if it's required, but the subscription pointer will remain valid
+        // for as long as the wait set exists, because it's stored in WaitSet::subscriptions.
+        // The index of the entity in the wait set is written to the third argument.
+        rcl_wait_set_add_subscription(rcl_wait_set, &*subscription.handle().lock(), &mut index)
+    }
+    .ok_for("rcl_wait_set_add_subscription")?;
+    Ok(index)
+}
+
+// Adds the guard condition that is triggered when the context is shut down. It is not reported
+// as ready, since it only serves to interrupt the wait.
+fn add_shutdown_guard_condition(
+    rcl_wait_set: &mut rcl_wait_set_t,
+    context_handle: &ContextHandle,
+) -> Result<(), RclrsError> {
+    unsafe {
+        // SAFETY: Safe if the wait set and guard condition are initialized
+        rcl_wait_set_add_guard_condition(
+            rcl_wait_set,
+            &*context_handle.shutdown_guard_condition.lock().unwrap(),
+            std::ptr::null_mut(),
+        )
+        .ok_for("rcl_wait_set_add_guard_condition")
+    }
+}
+
+fn add_guard_condition(
+    rcl_wait_set: &mut rcl_wait_set_t,
+    guard_condition: &GuardCondition,
+) -> Result<usize, RclrsError> {
+    let mut index = 0;
+    unsafe {
+        // SAFETY: Safe if the wait set and guard condition are initialized
+        rcl_wait_set_add_guard_condition(
+            rcl_wait_set,
+            &*guard_condition.handle.rcl_guard_condition.lock().unwrap(),
+            &mut index,
+        )
+        .ok_for("rcl_wait_set_add_guard_condition")?;
+    }
+    Ok(index)
+}
+
+fn add_client(
+    rcl_wait_set: &mut rcl_wait_set_t,
+    client: &dyn ClientBase,
+) -> Result<usize, RclrsError> {
+    let mut index = 0;
+    unsafe {
+        // SAFETY: I'm not sure if it's required, but the client pointer will remain valid
+        // for as long as the wait set exists, because it's stored in WaitSet::clients.
+        // The index of the entity in the wait set is written to the third argument.
+        rcl_wait_set_add_client(
+            rcl_wait_set,
+            &*client.handle().lock() as *const _,
+            &mut index,
+        )
+    }
+    .ok_for("rcl_wait_set_add_client")?;
+    Ok(index)
+}
+
+fn add_service(
+    rcl_wait_set: &mut rcl_wait_set_t,
+    service: &dyn ServiceBase,
+) -> Result<usize, RclrsError> {
+    let mut index = 0;
+    unsafe {
+        // SAFETY: I'm not sure if it's required, but the service pointer will remain valid
+        // for as long as the wait set exists, because it's stored in WaitSet::services.
+        // The index of the entity in the wait set is written to the third argument.
+        rcl_wait_set_add_service(
+            rcl_wait_set,
+            &*service.handle().lock() as *const _,
+            &mut index,
+        )
+    }
+    .ok_for("rcl_wait_set_add_service")?;
+    Ok(index)
+}
+
+fn add_event(
+    rcl_wait_set: &mut rcl_wait_set_t,
+    event: &dyn EventBase,
+) -> Result<usize, RclrsError> {
+    let mut index = 0;
+    unsafe {
+        // SAFETY: The event pointer will remain valid for as long as the wait set exists,
+        // because it's stored in WaitSet::events.
+        // The index of the entity in the wait set is written to the third argument.
+        rcl_wait_set_add_event(
+            rcl_wait_set,
+            &*event.handle().lock() as *const _,
+            &mut index,
+        )
+    }
+    .ok_for("rcl_wait_set_add_event")?;
+    Ok(index)
+}
+
+fn add_timer(rcl_wait_set: &mut rcl_wait_set_t, timer: &Timer) -> Result<usize, RclrsError> {
+    let mut index = 0;
+    unsafe {
+        // SAFETY: The timer pointer will remain valid for as long as the wait set exists,
+        // because it's stored in WaitSet::timers.
+        // The index of the entity in the wait set is written to the third argument.
+        rcl_wait_set_add_timer(rcl_wait_set, &*timer.handle.lock() as *const _, &mut index)
+    }
+    .ok_for("rcl_wait_set_add_timer")?;
+    Ok(index)
+}
+
+fn add_action_client(
+    rcl_wait_set: &mut rcl_wait_set_t,
+    action_client: &dyn ActionClientBase,
+) -> Result<(), RclrsError> {
+    unsafe {
+        // SAFETY: The action client pointer will remain valid for as long as the wait set
+        // exists, because it's stored in WaitSet::action_clients.
+        // Passing in null pointers for the indices is explicitly allowed, since the action
+        // client stores them itself.
+        rcl_action_wait_set_add_action_client(
+            rcl_wait_set,
+            &*action_client.handle().lock(),
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+        )
+    }
+    .ok_for("rcl_action_wait_set_add_action_client")
+}
+
+fn add_action_server(
+    rcl_wait_set: &mut rcl_wait_set_t,
+    action_server: &dyn ActionServerBase,
+) -> Result<(), RclrsError> {
+    unsafe {
+        // SAFETY: The action server pointer will remain valid for as long as the wait set
+        // exists, because it's stored in WaitSet::action_servers.
+        // Passing in a null pointer for the index is explicitly allowed, since the action
+        // server stores it itself.
+        rcl_action_wait_set_add_action_server(
+            rcl_wait_set,
+            &*action_server.handle().lock(),
+            core::ptr::null_mut(),
+        )
+    }
+    .ok_for("rcl_action_wait_set_add_action_server")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{test_helpers::*, QOS_PROFILE_DEFAULT};
+    use std::sync::Mutex;
 
     #[test]
     fn traits() {
-        use crate::test_helpers::*;
-
         assert_send::<WaitSet>();
         assert_sync::<WaitSet>();
     }
@@ -440,4 +1329,69 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn subscription_in_wait_set_readies() -> Result<(), RclrsError> {
+        let graph = construct_test_graph("test_subscription_in_wait_set")?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let subscription = {
+            let received = Arc::clone(&received);
+            graph
+                .node2
+                .create_subscription::<test_msgs::msg::BasicTypes, _>(
+                    "wait_set_topic",
+                    QOS_PROFILE_DEFAULT,
+                    move |msg: test_msgs::msg::BasicTypes| {
+                        received.lock().unwrap().push(msg.int32_value)
+                    },
+                )?
+        };
+        let publisher = graph.node1.create_publisher::<test_msgs::msg::BasicTypes>(
+            "wait_set_topic",
+            QOS_PROFILE_DEFAULT,
+        )?;
+
+        let context = Context {
+            handle: Arc::clone(&graph.node2.handle.context_handle),
+        };
+        let mut wait_set = WaitSet::new(1, 0, 0, 0, 0, 0, &context)?;
+        wait_set.add_subscription(Arc::clone(&subscription) as Arc<dyn SubscriptionBase>)?;
+
+        let is_subscription =
+            |ready: &Arc<dyn SubscriptionBase>| std::ptr::eq(ready.handle(), subscription.handle());
+
+        // Messages published before the publisher has discovered the subscription could be lost
+        let start = std::time::Instant::now();
+        while publisher.get_subscription_count()? == 0 {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "No subscription discovered"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        // Nothing has been published yet
+        let readies = wait_set.wait(Some(Duration::ZERO))?;
+        assert!(readies.subscriptions.is_empty());
+
+        // The wait set is reused after each wait, and keeps reporting the subscription while
+        // messages are published
+        for value in 1..=3 {
+            publisher.publish(test_msgs::msg::BasicTypes {
+                int32_value: value,
+                ..Default::default()
+            })?;
+            let readies = wait_set.wait(Some(Duration::from_secs(5)))?;
+            assert_eq!(readies.subscriptions.len(), 1);
+            assert!(is_subscription(&readies.subscriptions[0]));
+            readies.subscriptions[0].execute()?;
+        }
+        assert_eq!(*received.lock().unwrap(), vec![1, 2, 3]);
+
+        // All messages have been taken
+        let readies = wait_set.wait(Some(Duration::ZERO))?;
+        assert!(readies.subscriptions.is_empty());
+
+        Ok(())
+    }
 }
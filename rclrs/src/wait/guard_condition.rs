@@ -11,6 +11,11 @@ use crate::{rcl_bindings::*, Context, ContextHandle, RclrsError, ToResult};
 /// The guard condition may be reused multiple times, but like other waitable entities, can not be used in
 /// multiple wait sets concurrently.
 ///
+/// To wake up an executor that is spinning a node, create the guard condition with
+/// [`Node::create_guard_condition()`][1] instead, which adds it to the wait sets of the node.
+///
+/// [1]: crate::Node::create_guard_condition
+///
 /// # Example
 /// ```
 /// # use rclrs::{Context, GuardCondition, WaitSet, RclrsError};
@@ -59,7 +64,6 @@ pub struct GuardCondition {
 pub(crate) struct GuardConditionHandle {
     pub(crate) rcl_guard_condition: Mutex<rcl_guard_condition_t>,
     /// Keep the context alive for the whole lifecycle of the guard condition
-    #[allow(dead_code)]
     pub(crate) context_handle: Arc<ContextHandle>,
 }
 
@@ -144,7 +148,7 @@ impl GuardCondition {
         unsafe {
             // SAFETY: The rcl_guard_condition_t is valid.
             rcl_trigger_guard_condition(&mut *self.handle.rcl_guard_condition.lock().unwrap())
-                .ok()?;
+                .ok_for("rcl_trigger_guard_condition")?;
         }
         if let Some(callback) = &self.callback {
             callback();
@@ -155,7 +159,10 @@ impl GuardCondition {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::atomic::Ordering;
+    use std::{
+        sync::atomic::Ordering,
+        time::{Duration, Instant},
+    };
 
     use super::*;
     use crate::WaitSet;
@@ -199,6 +206,35 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_guard_condition_wakes_executor() -> Result<(), RclrsError> {
+        let context = Context::new([])?;
+        let node = crate::create_node(&context, "test_guard_condition_wakes_executor")?;
+        let triggered = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let guard_condition = {
+            let triggered = Arc::clone(&triggered);
+            node.create_guard_condition_with_callback(move || {
+                triggered.store(true, Ordering::Release);
+            })
+        };
+
+        let trigger_thread = {
+            let guard_condition = Arc::clone(&guard_condition);
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(100));
+                guard_condition.trigger()
+            })
+        };
+
+        let start = Instant::now();
+        crate::spin_once(Arc::clone(&node), Some(Duration::from_secs(30)))?;
+        assert!(start.elapsed() < Duration::from_secs(10));
+        trigger_thread.join().unwrap()?;
+        assert!(triggered.load(Ordering::Acquire));
+
+        Ok(())
+    }
+
     #[test]
     fn traits() {
         use crate::test_helpers::*;
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use crate::{GuardCondition, RclrsError};
+
+/// A custom entity that can be waited on and executed together with the ROS entities of a node.
+///
+/// This allows integrating other event sources into the event loop of an executor, see e.g.
+/// [`SingleThreadedExecutor::add_waitable()`][1]. A waitable exposes guard conditions that wake
+/// up the wait set, and is executed when one of them was triggered and [`Waitable::is_ready()`]
+/// returns `true`.
+///
+/// Since wait sets can only wait on ROS entities, other event sources such as sockets need to be
+/// bridged to a guard condition, e.g. by a thread that blocks on the socket and triggers the guard
+/// condition when it becomes readable.
+///
+/// # Example
+/// ```
+/// # use rclrs::{Context, GuardCondition, RclrsError, SingleThreadedExecutor, Waitable};
+/// # use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+/// # use std::time::Duration;
+/// struct Counter {
+///     guard_condition: Arc<GuardCondition>,
+///     count: AtomicUsize,
+/// }
+///
+/// impl Waitable for Counter {
+///     fn guard_conditions(&self) -> Vec<Arc<GuardCondition>> {
+///         vec![Arc::clone(&self.guard_condition)]
+///     }
+///
+///     fn is_ready(&self) -> bool {
+///         true
+///     }
+///
+///     fn execute(&self) -> Result<(), RclrsError> {
+///         self.count.fetch_add(1, Ordering::SeqCst);
+///         Ok(())
+///     }
+/// }
+///
+/// let context = Context::new([])?;
+/// let node = rclrs::create_node(&context, "waitable_example")?;
+/// let counter = Arc::new(Counter {
+///     guard_condition: Arc::new(GuardCondition::new(&context)),
+///     count: AtomicUsize::new(0),
+/// });
+/// let executor = SingleThreadedExecutor::new();
+/// executor.add_node(&node)?;
+/// executor.add_waitable(Arc::clone(&counter) as Arc<dyn Waitable>)?;
+///
+/// counter.guard_condition.trigger()?;
+/// executor.spin_once(Some(Duration::from_secs(1)))?;
+/// assert_eq!(counter.count.load(Ordering::SeqCst), 1);
+/// # Ok::<(), RclrsError>(())
+/// ```
+///
+/// [1]: crate::SingleThreadedExecutor::add_waitable
+pub trait Waitable: Send + Sync {
+    /// Returns the guard conditions that wake up the wait set when this waitable has work.
+    ///
+    /// Like other entities, each guard condition can only be in one wait set at a time, so it
+    /// should not be used anywhere else while the waitable is added to an executor.
+    fn guard_conditions(&self) -> Vec<Arc<GuardCondition>>;
+
+    /// Checks whether the waitable has work to do.
+    ///
+    /// This is called after one of the guard conditions of the waitable woke up the wait set.
+    fn is_ready(&self) -> bool;
+
+    /// Does the work of the waitable.
+    ///
+    /// This is called by the executor when [`Waitable::is_ready()`] returned `true`.
+    fn execute(&self) -> Result<(), RclrsError>;
+}
@@ -0,0 +1,51 @@
+// This test replaces the global allocator to count allocations, which is why it is a separate
+// test binary instead of a unit test of the message pool.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use rclrs::MessagePool;
+use test_msgs::msg;
+
+// Counts the allocations of each thread, so that tests running in parallel don't interfere.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn acquiring_after_warm_up_does_not_allocate() {
+    let pool = MessagePool::<msg::BasicTypes>::new(2);
+    drop((pool.acquire(), pool.acquire()));
+    assert_eq!(pool.available(), 2);
+
+    let allocations_after_warm_up = allocations();
+    for i in 0..1000 {
+        let mut first = pool.acquire();
+        let mut second = pool.acquire();
+        first.int32_value = i;
+        second.int64_value = i.into();
+    }
+    assert_eq!(allocations(), allocations_after_warm_up);
+}
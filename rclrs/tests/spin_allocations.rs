@@ -0,0 +1,63 @@
+// This test replaces the global allocator to count allocations, which is why it is a separate
+// test binary instead of a unit test of the executor.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    time::Duration,
+};
+
+use rclrs::{create_node, Context, RclReturnCode, RclrsError, SingleThreadedExecutor};
+
+// Counts the allocations of each thread, so that the threads of the middleware don't interfere.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn spinning_after_warm_up_does_not_allocate() -> Result<(), RclrsError> {
+    let context = Context::new([])?;
+    let node = create_node(&context, "test_spin_allocations")?;
+    let executor = SingleThreadedExecutor::new();
+    executor.add_node(&node)?;
+    // Creating the context and the node allocates.
+    assert!(allocations() > 0);
+
+    let spin = || match executor.spin_once(Some(Duration::ZERO)) {
+        Err(RclrsError::RclError {
+            code: RclReturnCode::Timeout,
+            ..
+        }) => Ok(()),
+        result => result,
+    };
+    for _ in 0..10 {
+        spin()?;
+    }
+
+    let allocations_after_warm_up = allocations();
+    for _ in 0..1000 {
+        spin()?;
+    }
+    assert_eq!(allocations(), allocations_after_warm_up);
+    Ok(())
+}
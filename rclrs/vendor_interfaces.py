@@ -1,11 +1,11 @@
 # This script produces the `vendor` module inside `rclrs` by copying
-# the generated code for the `rosgraph_msgs` and `rcl_interfaces` packages and
-# its dependency `builtin_interfaces` and adjusting the submodule paths in the
-# code.
+# the generated code for the `rosgraph_msgs`, `rcl_interfaces`, `action_msgs`,
+# `lifecycle_msgs` and `statistics_msgs` packages and their dependencies `builtin_interfaces` and
+# `unique_identifier_msgs` and adjusting the submodule paths in the code.
 # If these packages, or the `rosidl_generator_rs`, get changed, you can
 # update the `vendor` module by running this script.
-# The purpose is to avoid an external dependency on `rcl_interfaces`, which
-# is not published on crates.io.
+# The purpose is to avoid an external dependency on `rcl_interfaces`,
+# `action_msgs`, `lifecycle_msgs` and `statistics_msgs`, which are not published on crates.io.
 
 import argparse
 from pathlib import Path
@@ -13,15 +13,19 @@ import shutil
 import subprocess
 
 def get_args():
-  parser = argparse.ArgumentParser(description='Vendor the rcl_interfaces, builtin_interfaces and rosgraph_msgs packages into rclrs')
+  parser = argparse.ArgumentParser(description='Vendor the rcl_interfaces, builtin_interfaces, rosgraph_msgs, action_msgs, lifecycle_msgs, statistics_msgs and unique_identifier_msgs packages into rclrs')
   parser.add_argument('install_base', metavar='install_base', type=Path,
                       help='the install base (must have non-merged layout)')
   return parser.parse_args()
 
 def adjust(pkg, text):
+  text = text.replace('action_msgs::', 'crate::vendor::action_msgs::')
   text = text.replace('builtin_interfaces::', 'crate::vendor::builtin_interfaces::')
+  text = text.replace('lifecycle_msgs::', 'crate::vendor::lifecycle_msgs::')
   text = text.replace('rcl_interfaces::', 'crate::vendor::rcl_interfaces::')
   text = text.replace('rosgraph_msgs::', 'crate::vendor::rosgraph_msgs::')
+  text = text.replace('statistics_msgs::', 'crate::vendor::statistics_msgs::')
+  text = text.replace('unique_identifier_msgs::', 'crate::vendor::unique_identifier_msgs::')
   text = text.replace('crate::msg', f'crate::vendor::{pkg}::msg')
   text = text.replace('crate::srv', f'crate::vendor::{pkg}::srv')
   return text
@@ -32,11 +36,16 @@ def copy_adjusted(pkg, src, dst):
 
 mod_contents = """//! Created by {}
 #![allow(dead_code)]
+#![allow(missing_docs)]
 #![allow(clippy::derive_partial_eq_without_eq)]
 
+pub mod action_msgs;
 pub mod builtin_interfaces;
+pub mod lifecycle_msgs;
 pub mod rcl_interfaces;
 pub mod rosgraph_msgs;
+pub mod statistics_msgs;
+pub mod unique_identifier_msgs;
 """.format(Path(__file__).name)
 
 def main():
@@ -45,11 +54,15 @@ def main():
   assert (args.install_base / 'builtin_interfaces').is_dir(), "Install base does not contain builtin_interfaces"
   assert (args.install_base / 'rcl_interfaces').is_dir(), "Install base does not contain rcl_interfaces"
   assert (args.install_base / 'rosgraph_msgs').is_dir(), "Install base does not contain rosgraph_msgs"
+  assert (args.install_base / 'action_msgs').is_dir(), "Install base does not contain action_msgs"
+  assert (args.install_base / 'lifecycle_msgs').is_dir(), "Install base does not contain lifecycle_msgs"
+  assert (args.install_base / 'statistics_msgs').is_dir(), "Install base does not contain statistics_msgs"
+  assert (args.install_base / 'unique_identifier_msgs').is_dir(), "Install base does not contain unique_identifier_msgs"
   rclrs_root = Path(__file__).parent
   vendor_dir = rclrs_root / 'src' / 'vendor'
   if vendor_dir.exists():
     shutil.rmtree(vendor_dir)
-  for pkg in ['builtin_interfaces', 'rcl_interfaces', 'rosgraph_msgs']:
+  for pkg in ['action_msgs', 'builtin_interfaces', 'lifecycle_msgs', 'rcl_interfaces', 'rosgraph_msgs', 'statistics_msgs', 'unique_identifier_msgs']:
     src = args.install_base / pkg / 'share' / pkg / 'rust' / 'src'
     dst = vendor_dir / pkg
     dst.mkdir(parents=True)
@@ -20,6 +20,7 @@ add_custom_command(
   ${_generated_msg_c_files}
   ${_generated_srv_rs_files}
   ${_generated_srv_c_files}
+  ${_generated_action_rs_files}
   COMMAND ${PYTHON_EXECUTABLE} ${rosidl_generator_rs_BIN}
   --generator-arguments-file "${generator_arguments_file}"
   --typesupport-impls "${_typesupport_impls}"
@@ -40,5 +41,6 @@ else()
     ${_generated_msg_c_files}
     ${_generated_srv_rs_files}
     ${_generated_srv_c_files}
+  ${_generated_action_rs_files}
   )
 endif()
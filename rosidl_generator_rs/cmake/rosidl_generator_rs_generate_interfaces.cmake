@@ -26,9 +26,11 @@ set(_generated_common_rs_files "")
 
 set(_generated_msg_rs_files "")
 set(_generated_srv_rs_files "")
+set(_generated_action_rs_files "")
 
 set(_has_msg FALSE)
 set(_has_srv FALSE)
+set(_has_action FALSE)
 
 foreach(_idl_file ${rosidl_generate_interfaces_ABS_IDL_FILES})
   get_filename_component(_parent_folder "${_idl_file}" DIRECTORY)
@@ -43,7 +45,6 @@ foreach(_idl_file ${rosidl_generate_interfaces_ABS_IDL_FILES})
     set(_idl_file_without_actions ${_idl_file_without_actions} ${_idl_file})
   elseif(_parent_folder STREQUAL "action")
     set(_has_action TRUE)
-    message(WARNING "Rust actions generation is not implemented")
   else()
     message(FATAL_ERROR "Interface file with unknown parent folder: ${_idl_file}")
   endif()
@@ -67,6 +68,12 @@ if(${_has_srv})
   )
 endif()
 
+if(${_has_action})
+  list(APPEND _generated_action_rs_files
+    "${_output_path}/rust/src/action.rs"
+  )
+endif()
+
 set(_dependency_files "")
 set(_dependencies "")
 foreach(_pkg_name ${rosidl_generate_interfaces_DEPENDENCY_PACKAGE_NAMES})
@@ -85,6 +92,7 @@ set(target_dependencies
   "${rosidl_generator_rs_TEMPLATE_DIR}/msg_rmw.rs.em"
   "${rosidl_generator_rs_TEMPLATE_DIR}/msg.rs.em"
   "${rosidl_generator_rs_TEMPLATE_DIR}/srv.rs.em"
+  "${rosidl_generator_rs_TEMPLATE_DIR}/action.rs.em"
   ${rosidl_generate_interfaces_ABS_IDL_FILES}
   ${_idl_file_without_actions}
   ${_dependency_files})
@@ -130,6 +138,7 @@ set_property(
   ${_generated_common_rs_files}
   ${_generated_msg_rs_files}
   ${_generated_srv_rs_files}
+  ${_generated_action_rs_files}
   PROPERTY GENERATED 1)
 
 set(_rsext_suffix "__rsext")
@@ -144,7 +153,8 @@ endif()
 if(BUILD_TESTING AND rosidl_generate_interfaces_ADD_LINTER_TESTS)
   if(
     NOT _generated_msg_rs_files STREQUAL "" OR
-    NOT _generated_srv_rs_files STREQUAL ""
+    NOT _generated_srv_rs_files STREQUAL "" OR
+    NOT _generated_action_rs_files STREQUAL ""
   )
   # TODO(esteve): add linters for Rust files
   endif()
@@ -4,7 +4,7 @@ version = "@(package_version)"
 edition = "2021"
 
 [dependencies]
-rosidl_runtime_rs = "0.4"
+rosidl_runtime_rs = "0.4.2"
 serde = { version = "1", optional = true, features = ["derive"] }
 serde-big-array = { version = "0.5.1", optional = true }
 @[for dep in dependency_packages]@
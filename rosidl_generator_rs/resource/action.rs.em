@@ -0,0 +1,216 @@
+@{
+action_msg_specs = []
+
+for subfolder, action in action_specs:
+    action_msg_specs.append((subfolder, action.goal))
+    action_msg_specs.append((subfolder, action.result))
+    action_msg_specs.append((subfolder, action.feedback))
+    action_msg_specs.append((subfolder, action.feedback_message))
+    action_msg_specs.append((subfolder, action.send_goal_service.request_message))
+    action_msg_specs.append((subfolder, action.send_goal_service.response_message))
+    action_msg_specs.append((subfolder, action.get_result_service.request_message))
+    action_msg_specs.append((subfolder, action.get_result_service.response_message))
+
+action_srv_specs = []
+
+for subfolder, action in action_specs:
+    action_srv_specs.append((subfolder, action.send_goal_service))
+    action_srv_specs.append((subfolder, action.get_result_service))
+}@
+
+@{
+TEMPLATE(
+    'msg_idiomatic.rs.em',
+    package_name=package_name, interface_path=interface_path,
+    msg_specs=action_msg_specs,
+    get_rs_name=get_rs_name, get_rmw_rs_type=get_rmw_rs_type,
+    pre_field_serde=pre_field_serde,
+    get_idiomatic_rs_type=get_idiomatic_rs_type,
+    constant_value_to_rs=constant_value_to_rs)
+}@
+
+@[for subfolder, srv_spec in action_srv_specs]
+
+@{
+type_name = srv_spec.namespaced_type.name
+}@
+
+#[link(name = "@(package_name)__rosidl_typesupport_c")]
+extern "C" {
+    fn rosidl_typesupport_c__get_service_type_support_handle__@(package_name)__@(subfolder)__@(type_name)() -> *const std::os::raw::c_void;
+}
+
+// Corresponds to @(package_name)__@(subfolder)__@(type_name)
+pub struct @(type_name);
+
+impl rosidl_runtime_rs::Service for @(type_name) {
+  type Request = crate::@(subfolder)::@(type_name)_Request;
+  type Response = crate::@(subfolder)::@(type_name)_Response;
+
+  fn get_type_support() -> *const std::os::raw::c_void {
+    // SAFETY: No preconditions for this function.
+    unsafe { rosidl_typesupport_c__get_service_type_support_handle__@(package_name)__@(subfolder)__@(type_name)() }
+  }
+}
+
+@[end for]
+
+@[for subfolder, action_spec in action_specs]
+
+@{
+type_name = action_spec.namespaced_type.name
+}@
+
+#[link(name = "@(package_name)__rosidl_typesupport_c")]
+extern "C" {
+    fn rosidl_typesupport_c__get_action_type_support_handle__@(package_name)__@(subfolder)__@(type_name)() -> *const std::os::raw::c_void;
+}
+
+// Corresponds to @(package_name)__@(subfolder)__@(type_name)
+pub struct @(type_name);
+
+impl rosidl_runtime_rs::Action for @(type_name) {
+  type Goal = crate::@(subfolder)::@(type_name)_Goal;
+  type Result = crate::@(subfolder)::@(type_name)_Result;
+  type Feedback = crate::@(subfolder)::@(type_name)_Feedback;
+
+  fn get_type_support() -> *const std::os::raw::c_void {
+    // SAFETY: No preconditions for this function.
+    unsafe { rosidl_typesupport_c__get_action_type_support_handle__@(package_name)__@(subfolder)__@(type_name)() }
+  }
+}
+
+impl rosidl_runtime_rs::ActionImpl for @(type_name) {
+  type FeedbackMessage = crate::@(subfolder)::@(type_name)_FeedbackMessage;
+  type SendGoalService = crate::@(subfolder)::@(type_name)_SendGoal;
+  type GetResultService = crate::@(subfolder)::@(type_name)_GetResult;
+
+  fn create_goal_request(
+    goal_id: &[u8; 16],
+    goal: crate::@(subfolder)::rmw::@(type_name)_Goal,
+  ) -> crate::@(subfolder)::rmw::@(type_name)_SendGoal_Request {
+    crate::@(subfolder)::rmw::@(type_name)_SendGoal_Request {
+      goal_id: unique_identifier_msgs::msg::rmw::UUID { uuid: *goal_id },
+      goal,
+    }
+  }
+
+  fn split_goal_request(
+    request: crate::@(subfolder)::rmw::@(type_name)_SendGoal_Request,
+  ) -> ([u8; 16], crate::@(subfolder)::rmw::@(type_name)_Goal) {
+    (request.goal_id.uuid, request.goal)
+  }
+
+  fn create_goal_response(
+    accepted: bool,
+    stamp: (i32, u32),
+  ) -> crate::@(subfolder)::rmw::@(type_name)_SendGoal_Response {
+    crate::@(subfolder)::rmw::@(type_name)_SendGoal_Response {
+      accepted,
+      stamp: builtin_interfaces::msg::rmw::Time {
+        sec: stamp.0,
+        nanosec: stamp.1,
+      },
+    }
+  }
+
+  fn get_goal_response_accepted(
+    response: &crate::@(subfolder)::rmw::@(type_name)_SendGoal_Response,
+  ) -> bool {
+    response.accepted
+  }
+
+  fn get_goal_response_stamp(
+    response: &crate::@(subfolder)::rmw::@(type_name)_SendGoal_Response,
+  ) -> (i32, u32) {
+    (response.stamp.sec, response.stamp.nanosec)
+  }
+
+  fn create_feedback_message(
+    goal_id: &[u8; 16],
+    feedback: crate::@(subfolder)::rmw::@(type_name)_Feedback,
+  ) -> crate::@(subfolder)::rmw::@(type_name)_FeedbackMessage {
+    crate::@(subfolder)::rmw::@(type_name)_FeedbackMessage {
+      goal_id: unique_identifier_msgs::msg::rmw::UUID { uuid: *goal_id },
+      feedback,
+    }
+  }
+
+  fn split_feedback_message(
+    feedback: crate::@(subfolder)::rmw::@(type_name)_FeedbackMessage,
+  ) -> ([u8; 16], crate::@(subfolder)::rmw::@(type_name)_Feedback) {
+    (feedback.goal_id.uuid, feedback.feedback)
+  }
+
+  fn create_result_request(
+    goal_id: &[u8; 16],
+  ) -> crate::@(subfolder)::rmw::@(type_name)_GetResult_Request {
+    crate::@(subfolder)::rmw::@(type_name)_GetResult_Request {
+      goal_id: unique_identifier_msgs::msg::rmw::UUID { uuid: *goal_id },
+    }
+  }
+
+  fn get_result_request_uuid(
+    request: &crate::@(subfolder)::rmw::@(type_name)_GetResult_Request,
+  ) -> &[u8; 16] {
+    &request.goal_id.uuid
+  }
+
+  fn create_result_response(
+    status: i8,
+    result: crate::@(subfolder)::rmw::@(type_name)_Result,
+  ) -> crate::@(subfolder)::rmw::@(type_name)_GetResult_Response {
+    crate::@(subfolder)::rmw::@(type_name)_GetResult_Response {
+      status,
+      result,
+    }
+  }
+
+  fn split_result_response(
+    response: crate::@(subfolder)::rmw::@(type_name)_GetResult_Response,
+  ) -> (i8, crate::@(subfolder)::rmw::@(type_name)_Result) {
+    (response.status, response.result)
+  }
+}
+
+@[end for]
+
+pub mod rmw {
+@{
+TEMPLATE(
+    'msg_rmw.rs.em',
+    package_name=package_name, interface_path=interface_path,
+    msg_specs=action_msg_specs,
+    get_rs_name=get_rs_name, get_rmw_rs_type=get_rmw_rs_type,
+    pre_field_serde=pre_field_serde,
+    get_idiomatic_rs_type=get_idiomatic_rs_type,
+    constant_value_to_rs=constant_value_to_rs)
+}@
+
+@[for subfolder, srv_spec in action_srv_specs]
+
+@{
+type_name = srv_spec.namespaced_type.name
+}@
+
+  #[link(name = "@(package_name)__rosidl_typesupport_c")]
+  extern "C" {
+      fn rosidl_typesupport_c__get_service_type_support_handle__@(package_name)__@(subfolder)__@(type_name)() -> *const std::os::raw::c_void;
+  }
+
+  // Corresponds to @(package_name)__@(subfolder)__@(type_name)
+  pub struct @(type_name);
+
+  impl rosidl_runtime_rs::Service for @(type_name) {
+    type Request = crate::@(subfolder)::rmw::@(type_name)_Request;
+    type Response = crate::@(subfolder)::rmw::@(type_name)_Response;
+
+    fn get_type_support() -> *const std::os::raw::c_void {
+      // SAFETY: No preconditions for this function.
+      unsafe { rosidl_typesupport_c__get_service_type_support_handle__@(package_name)__@(subfolder)__@(type_name)() }
+    }
+  }
+
+@[end for]
+
+}  // mod rmw
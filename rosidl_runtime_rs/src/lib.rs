@@ -9,4 +9,8 @@ mod string;
 pub use string::{BoundedString, BoundedWString, String, StringExceedsBoundsError, WString};
 
 mod traits;
-pub use traits::{Message, RmwMessage, SequenceAlloc, Service};
+pub use traits::{
+    Action, ActionImpl, Message, RmwFeedbackData, RmwFeedbackMessage, RmwGoalData, RmwGoalRequest,
+    RmwGoalResponse, RmwMessage, RmwResultData, RmwResultRequest, RmwResultResponse, SequenceAlloc,
+    Service,
+};
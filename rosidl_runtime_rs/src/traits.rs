@@ -159,3 +159,104 @@ pub trait Service: 'static {
     /// Get a pointer to the correct `rosidl_service_type_support_t` structure.
     fn get_type_support() -> *const std::os::raw::c_void;
 }
+
+/// Trait for actions.
+///
+/// User code never needs to call this trait's method, much less implement this trait.
+pub trait Action: 'static {
+    /// The goal message associated with this action.
+    type Goal: Message;
+
+    /// The result message associated with this action.
+    type Result: Message;
+
+    /// The feedback message associated with this action.
+    type Feedback: Message;
+
+    /// Get a pointer to the correct `rosidl_action_type_support_t` structure.
+    fn get_type_support() -> *const std::os::raw::c_void;
+}
+
+/// Trait for the implementation details of actions.
+///
+/// An action is made up of several services and topics, whose messages wrap the goal, result and
+/// feedback messages of the action together with e.g. the ID of the goal. This trait gives
+/// access to these messages.
+///
+/// User code never needs to call this trait's methods, much less implement this trait.
+pub trait ActionImpl: Action {
+    /// The message published on the feedback topic of this action.
+    type FeedbackMessage: Message;
+
+    /// The service for sending a goal.
+    type SendGoalService: Service;
+
+    /// The service for requesting the result of a goal.
+    type GetResultService: Service;
+
+    /// Creates a goal request from a goal ID and the goal.
+    fn create_goal_request(goal_id: &[u8; 16], goal: RmwGoalData<Self>) -> RmwGoalRequest<Self>;
+
+    /// Splits a goal request into the goal ID and the goal.
+    fn split_goal_request(request: RmwGoalRequest<Self>) -> ([u8; 16], RmwGoalData<Self>);
+
+    /// Creates a goal response. The stamp is given as seconds and nanoseconds.
+    fn create_goal_response(accepted: bool, stamp: (i32, u32)) -> RmwGoalResponse<Self>;
+
+    /// Returns whether a goal response accepts the goal.
+    fn get_goal_response_accepted(response: &RmwGoalResponse<Self>) -> bool;
+
+    /// Returns the stamp of a goal response as seconds and nanoseconds.
+    fn get_goal_response_stamp(response: &RmwGoalResponse<Self>) -> (i32, u32);
+
+    /// Creates a feedback message from a goal ID and the feedback.
+    fn create_feedback_message(
+        goal_id: &[u8; 16],
+        feedback: RmwFeedbackData<Self>,
+    ) -> RmwFeedbackMessage<Self>;
+
+    /// Splits a feedback message into the goal ID and the feedback.
+    fn split_feedback_message(
+        feedback: RmwFeedbackMessage<Self>,
+    ) -> ([u8; 16], RmwFeedbackData<Self>);
+
+    /// Creates a result request for the given goal ID.
+    fn create_result_request(goal_id: &[u8; 16]) -> RmwResultRequest<Self>;
+
+    /// Returns the goal ID of a result request.
+    fn get_result_request_uuid(request: &RmwResultRequest<Self>) -> &[u8; 16];
+
+    /// Creates a result response from the status of the goal and the result.
+    fn create_result_response(status: i8, result: RmwResultData<Self>) -> RmwResultResponse<Self>;
+
+    /// Splits a result response into the status of the goal and the result.
+    fn split_result_response(response: RmwResultResponse<Self>) -> (i8, RmwResultData<Self>);
+}
+
+/// The RMW-native goal message of an action.
+pub type RmwGoalData<A> = <<A as Action>::Goal as Message>::RmwMsg;
+
+/// The RMW-native result message of an action.
+pub type RmwResultData<A> = <<A as Action>::Result as Message>::RmwMsg;
+
+/// The RMW-native feedback message of an action.
+pub type RmwFeedbackData<A> = <<A as Action>::Feedback as Message>::RmwMsg;
+
+/// The RMW-native request message of the send goal service of an action.
+pub type RmwGoalRequest<A> =
+    <<<A as ActionImpl>::SendGoalService as Service>::Request as Message>::RmwMsg;
+
+/// The RMW-native response message of the send goal service of an action.
+pub type RmwGoalResponse<A> =
+    <<<A as ActionImpl>::SendGoalService as Service>::Response as Message>::RmwMsg;
+
+/// The RMW-native request message of the get result service of an action.
+pub type RmwResultRequest<A> =
+    <<<A as ActionImpl>::GetResultService as Service>::Request as Message>::RmwMsg;
+
+/// The RMW-native response message of the get result service of an action.
+pub type RmwResultResponse<A> =
+    <<<A as ActionImpl>::GetResultService as Service>::Response as Message>::RmwMsg;
+
+/// The RMW-native feedback message of an action, including the goal ID.
+pub type RmwFeedbackMessage<A> = <<A as ActionImpl>::FeedbackMessage as Message>::RmwMsg;